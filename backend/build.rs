@@ -0,0 +1,361 @@
+//! Build step: generate client bindings (TypeScript + Python) from `openapi.json`.
+//!
+//! The generated sources are packed into zip archives under `OUT_DIR/clients/`
+//! and embedded into the binary, so `GET /api/v1/clients/<lang>.zip` always
+//! serves bindings matching the running server's contract and version.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+/// A single API operation extracted from the OpenAPI document.
+struct Operation {
+    name: String,
+    method: String,
+    path: String,
+    summary: String,
+    path_params: Vec<String>,
+    query_params: Vec<String>,
+    has_body: bool,
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=openapi.json");
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let spec_src = fs::read_to_string("openapi.json").expect("openapi.json should be readable");
+    let spec: Value = serde_json::from_str(&spec_src).expect("openapi.json should be valid JSON");
+    let version = env::var("CARGO_PKG_VERSION").unwrap_or_else(|_| "0.0.0".to_string());
+    let ops = collect_operations(&spec);
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR is set by cargo")).join("clients");
+    fs::create_dir_all(&out_dir).expect("create clients output dir");
+
+    let ts_files = vec![
+        ("kanban-client/package.json".to_string(), ts_package_json(&version)),
+        ("kanban-client/src/index.ts".to_string(), ts_client(&ops, &version)),
+        ("kanban-client/openapi.json".to_string(), spec_src.clone()),
+    ];
+    write_zip(&out_dir.join("typescript.zip"), &ts_files);
+
+    let py_files = vec![
+        ("kanban-client/pyproject.toml".to_string(), py_project(&version)),
+        ("kanban-client/kanban_client/__init__.py".to_string(), py_init(&version)),
+        ("kanban-client/kanban_client/client.py".to_string(), py_client(&ops, &version)),
+        ("kanban-client/openapi.json".to_string(), spec_src),
+    ];
+    write_zip(&out_dir.join("python.zip"), &py_files);
+}
+
+fn collect_operations(spec: &Value) -> Vec<Operation> {
+    let empty = serde_json::Map::new();
+    let paths = spec["paths"].as_object().unwrap_or(&empty);
+    let shared_params = spec["components"]["parameters"].as_object().unwrap_or(&empty);
+
+    let resolve = |p: &Value| -> Value {
+        match p.get("$ref").and_then(|r| r.as_str()) {
+            Some(r) => {
+                let key = r.rsplit('/').next().unwrap_or_default();
+                shared_params.get(key).cloned().unwrap_or(Value::Null)
+            }
+            None => p.clone(),
+        }
+    };
+
+    // BTreeMap keeps generated output stable across builds
+    let mut ops: BTreeMap<String, Operation> = BTreeMap::new();
+    for (path, item) in paths {
+        let Some(methods) = item.as_object() else { continue };
+        for (method, op) in methods {
+            if !["get", "post", "put", "patch", "delete"].contains(&method.as_str()) {
+                continue;
+            }
+            let name = op["operationId"]
+                .as_str()
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| fallback_name(method, path));
+
+            let mut path_params = Vec::new();
+            let mut query_params = Vec::new();
+            let params = op["parameters"].as_array().cloned().unwrap_or_default();
+            for p in params.iter().map(resolve) {
+                let Some(pname) = p["name"].as_str() else { continue };
+                match p["in"].as_str() {
+                    Some("path") => path_params.push(pname.to_string()),
+                    Some("query") => query_params.push(pname.to_string()),
+                    _ => {}
+                }
+            }
+            // Path params may be declared only in the template
+            for seg in path.split('/') {
+                if let Some(inner) = seg.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                    if !path_params.iter().any(|p| p == inner) {
+                        path_params.push(inner.to_string());
+                    }
+                }
+            }
+
+            ops.insert(
+                name.clone(),
+                Operation {
+                    name,
+                    method: method.to_uppercase(),
+                    path: path.clone(),
+                    summary: op["summary"].as_str().unwrap_or_default().to_string(),
+                    path_params,
+                    query_params,
+                    has_body: op.get("requestBody").is_some(),
+                },
+            );
+        }
+    }
+    ops.into_values().collect()
+}
+
+/// Derive a camelCase operation name for operations without an operationId.
+fn fallback_name(method: &str, path: &str) -> String {
+    let mut name = method.to_string();
+    for seg in path.split('/').filter(|s| !s.is_empty() && !s.starts_with('{')) {
+        let mut chars = seg.chars();
+        if let Some(first) = chars.next() {
+            name.push(first.to_ascii_uppercase());
+            name.extend(chars);
+        }
+    }
+    name
+}
+
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else if c == '-' {
+            out.push('_');
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+// ============ TypeScript ============
+
+fn ts_package_json(version: &str) -> String {
+    format!(
+        r#"{{
+  "name": "kanban-client",
+  "version": "{version}",
+  "description": "Generated client for the Kanban API (server version {version})",
+  "main": "src/index.ts",
+  "types": "src/index.ts",
+  "license": "MIT"
+}}
+"#
+    )
+}
+
+fn ts_client(ops: &[Operation], version: &str) -> String {
+    let mut s = r#"// Generated from openapi.json by the kanban server build (v{version}). Do not edit.
+
+export const SERVER_VERSION = "{version}";
+
+export interface ClientOptions {
+  baseUrl: string;
+  manageKey?: string;
+  fetch?: typeof fetch;
+}
+
+export class KanbanError extends Error {
+  constructor(public status: number, public body: unknown) {
+    super(`Kanban API error ${status}`);
+  }
+}
+
+export class KanbanClient {
+  private baseUrl: string;
+  private manageKey?: string;
+  private fetchImpl: typeof fetch;
+
+  constructor(opts: ClientOptions) {
+    this.baseUrl = opts.baseUrl.replace(/\/$/, "");
+    this.manageKey = opts.manageKey;
+    this.fetchImpl = opts.fetch ?? fetch;
+  }
+
+  private async request(method: string, path: string, query?: Record<string, unknown>, body?: unknown): Promise<any> {
+    const qs = new URLSearchParams();
+    for (const [k, v] of Object.entries(query ?? {})) {
+      if (v !== undefined && v !== null) qs.set(k, String(v));
+    }
+    const url = this.baseUrl + path + (qs.toString() ? `?${qs}` : "");
+    const headers: Record<string, string> = {};
+    if (this.manageKey) headers["Authorization"] = `Bearer ${this.manageKey}`;
+    if (body !== undefined) headers["Content-Type"] = "application/json";
+    const resp = await this.fetchImpl(url, { method, headers, body: body === undefined ? undefined : JSON.stringify(body) });
+    const text = await resp.text();
+    const data = text ? JSON.parse(text) : null;
+    if (!resp.ok) throw new KanbanError(resp.status, data);
+    return data;
+  }
+"#
+    .replace("{version}", version);
+
+    for op in ops {
+        let mut args: Vec<String> = op.path_params.iter().map(|p| format!("{}: string", p)).collect();
+        if op.has_body {
+            args.push("body: unknown".to_string());
+        }
+        if !op.query_params.is_empty() {
+            let fields: Vec<String> = op.query_params.iter().map(|q| format!("{}?: unknown", q)).collect();
+            args.push(format!("query: {{ {} }} = {{}}", fields.join("; ")));
+        }
+        let mut path_expr = op.path.clone();
+        for p in &op.path_params {
+            path_expr = path_expr.replace(&format!("{{{}}}", p), &format!("${{encodeURIComponent({})}}", p));
+        }
+        if !op.summary.is_empty() {
+            s.push_str(&format!("\n  /** {} */", op.summary));
+        }
+        s.push_str(&format!(
+            "\n  {}({}): Promise<any> {{\n    return this.request(\"{}\", `{}`, {}, {});\n  }}\n",
+            op.name,
+            args.join(", "),
+            op.method,
+            path_expr,
+            if op.query_params.is_empty() { "undefined" } else { "query" },
+            if op.has_body { "body" } else { "undefined" },
+        ));
+    }
+    s.push_str("}\n");
+    s
+}
+
+// ============ Python ============
+
+fn py_project(version: &str) -> String {
+    format!(
+        "[project]\nname = \"kanban-client\"\nversion = \"{version}\"\n\
+description = \"Generated client for the Kanban API (server version {version})\"\n\
+requires-python = \">=3.8\"\ndependencies = []\n"
+    )
+}
+
+fn py_init(version: &str) -> String {
+    format!(
+        "\"\"\"Generated Kanban API client (server version {version}).\"\"\"\n\n\
+from .client import KanbanClient, KanbanError, SERVER_VERSION\n\n\
+__all__ = [\"KanbanClient\", \"KanbanError\", \"SERVER_VERSION\"]\n"
+    )
+}
+
+fn py_client(ops: &[Operation], version: &str) -> String {
+    let mut s = r#"# Generated from openapi.json by the kanban server build (v{version}). Do not edit.
+
+import json
+import urllib.error
+import urllib.parse
+import urllib.request
+
+SERVER_VERSION = "{version}"
+
+
+class KanbanError(Exception):
+    def __init__(self, status, body):
+        super().__init__(f"Kanban API error {status}")
+        self.status = status
+        self.body = body
+
+
+class KanbanClient:
+    def __init__(self, base_url, manage_key=None, timeout=30):
+        self.base_url = base_url.rstrip("/")
+        self.manage_key = manage_key
+        self.timeout = timeout
+
+    def _request(self, method, path, query=None, body=None):
+        params = {k: v for k, v in (query or {}).items() if v is not None}
+        url = self.base_url + path
+        if params:
+            url += "?" + urllib.parse.urlencode(params)
+        headers = {}
+        if self.manage_key:
+            headers["Authorization"] = f"Bearer {self.manage_key}"
+        data = None
+        if body is not None:
+            headers["Content-Type"] = "application/json"
+            data = json.dumps(body).encode()
+        req = urllib.request.Request(url, data=data, method=method, headers=headers)
+        try:
+            with urllib.request.urlopen(req, timeout=self.timeout) as resp:
+                text = resp.read().decode()
+        except urllib.error.HTTPError as e:
+            text = e.read().decode()
+            raise KanbanError(e.code, json.loads(text) if text else None)
+        return json.loads(text) if text else None
+"#
+    .replace("{version}", version);
+
+    for op in ops {
+        let mut args = vec!["self".to_string()];
+        args.extend(op.path_params.iter().map(|p| snake_case(p)));
+        if op.has_body {
+            args.push("body".to_string());
+        }
+        args.extend(op.query_params.iter().map(|q| format!("{}=None", snake_case(q))));
+
+        let mut path_expr = op.path.clone();
+        for p in &op.path_params {
+            path_expr = path_expr.replace(
+                &format!("{{{}}}", p),
+                &format!("{{urllib.parse.quote(str({}), safe='')}}", snake_case(p)),
+            );
+        }
+        let query = if op.query_params.is_empty() {
+            "None".to_string()
+        } else {
+            let pairs: Vec<String> = op
+                .query_params
+                .iter()
+                .map(|q| format!("\"{}\": {}", q, snake_case(q)))
+                .collect();
+            format!("{{{}}}", pairs.join(", "))
+        };
+
+        s.push_str(&format!("\n    def {}({}):\n", snake_case(&op.name), args.join(", ")));
+        if !op.summary.is_empty() {
+            s.push_str(&format!("        \"\"\"{}\"\"\"\n", op.summary.replace('"', "'")));
+        }
+        s.push_str(&format!(
+            "        return self._request(\"{}\", f\"{}\", {}, {})\n",
+            op.method,
+            path_expr,
+            query,
+            if op.has_body { "body" } else { "None" },
+        ));
+    }
+    s
+}
+
+// ============ Packaging ============
+
+fn write_zip(dest: &PathBuf, files: &[(String, String)]) {
+    let file = fs::File::create(dest).expect("create client archive");
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Stored)
+        // Fixed timestamp keeps archives reproducible between builds
+        .last_modified_time(zip::DateTime::default());
+    for (name, contents) in files {
+        zip.start_file(name.as_str(), options).expect("start zip entry");
+        zip.write_all(contents.as_bytes()).expect("write zip entry");
+    }
+    zip.finish().expect("finish client archive");
+}
@@ -1,7 +1,12 @@
+use crate::auth::{Actor, BoardToken};
+use crate::db::hash_key;
 use crate::models::ApiError;
+use crate::share_links;
+use crate::view_password;
 use rocket::http::Status;
 use rocket::serde::json::Json;
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
 
 /// Check if a board exists. Returns Err(404) if not.
 pub fn require_board_exists(
@@ -57,15 +62,134 @@ pub fn require_not_archived(
     }
 }
 
-/// Verify that the given token hash matches the board's manage_key_hash.
-/// Used by write routes to authorize modifications.
-pub fn require_manage_key(
+/// What a key may do on a board, weakest first. The board's manage key is
+/// always `Owner`; collaborators get keys of their own with any role from
+/// `Viewer` up, and share keys are `Read`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BoardRole {
+    /// Read-only: the board's private data (stats, streams), no writes
+    Read,
+    /// Personal settings (inbox, watches, notifications) and stats
+    Viewer,
+    /// Tasks, comments, columns, dependencies and attachments
+    Editor,
+    /// Board settings, exports, integrations, and editor/viewer collaborators
+    Admin,
+    /// Everything, including deleting the board and managing admins
+    Owner,
+}
+
+impl BoardRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BoardRole::Read => "read",
+            BoardRole::Viewer => "viewer",
+            BoardRole::Editor => "editor",
+            BoardRole::Admin => "admin",
+            BoardRole::Owner => "owner",
+        }
+    }
+
+    pub fn parse(role: &str) -> Option<Self> {
+        match role {
+            "read" => Some(BoardRole::Read),
+            "viewer" => Some(BoardRole::Viewer),
+            "editor" => Some(BoardRole::Editor),
+            "admin" => Some(BoardRole::Admin),
+            "owner" => Some(BoardRole::Owner),
+            _ => None,
+        }
+    }
+}
+
+/// Who can read a board. `public` boards are listed and readable by anyone;
+/// `unlisted` ones are readable by anyone who knows the ID; `private` ones
+/// need a manage, collaborator or share key for reads too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    Public,
+    Unlisted,
+    Private,
+}
+
+impl Visibility {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Visibility::Public => "public",
+            Visibility::Unlisted => "unlisted",
+            Visibility::Private => "private",
+        }
+    }
+
+    pub fn parse(visibility: &str) -> Option<Self> {
+        match visibility {
+            "public" => Some(Visibility::Public),
+            "unlisted" => Some(Visibility::Unlisted),
+            "private" => Some(Visibility::Private),
+            _ => None,
+        }
+    }
+}
+
+/// Gate for reads: 404 if the board doesn't exist; for private boards, 401
+/// without a key and 403 with one that isn't the board's. Boards with a view
+/// password also take a view token, and without any key answer 401. Other
+/// public and unlisted boards are open to everyone. A signed share link
+/// opens any board it was made for until it expires.
+pub fn require_read_access(
     conn: &Connection,
     board_id: &str,
-    token_hash: &str,
+    token: Option<&BoardToken>,
 ) -> Result<(), (Status, Json<ApiError>)> {
     require_board_exists(conn, board_id)?;
 
+    let (visibility, password_hash): (Option<String>, Option<String>) = conn
+        .query_row(
+            "SELECT visibility, view_password_hash FROM boards WHERE id = ?1",
+            rusqlite::params![board_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap_or((None, None));
+    let private = visibility.as_deref().and_then(Visibility::parse) == Some(Visibility::Private);
+    if let Some(link) = token.and_then(|t| share_links::check(conn, board_id, &t.0)) {
+        // Like any other key, a bad link doesn't stop reads of an open board
+        return if private || password_hash.is_some() { link.map(|_| ()) } else { Ok(()) };
+    }
+    let unauthorized = |error: &str, code: &str| {
+        Err((
+            Status::Unauthorized,
+            Json(ApiError {
+                error: error.to_string(),
+                code: code.to_string(),
+                status: 401,
+            }),
+        ))
+    };
+    match (token, password_hash) {
+        (None, _) if private => unauthorized("This board is private; send a manage, collaborator or share key", "KEY_REQUIRED"),
+        (None, Some(_)) => unauthorized(
+            "This board needs a view password; trade it for a token at POST /boards/{id}/view-session",
+            "PASSWORD_REQUIRED",
+        ),
+        (None, None) => Ok(()),
+        // A view token opens password-protected boards, never private ones
+        (Some(token), Some(hash)) if !private && view_password::token_valid(board_id, &hash, &token.0) => Ok(()),
+        (Some(_), None) if !private => Ok(()),
+        (Some(token), _) => board_role(conn, board_id, &hash_key(&token.0)).map(|_| ()),
+    }
+}
+
+/// The role the given token hash has on the board: `Owner` for the manage
+/// key (and the owner's sign-in session), a collaborator's, agent's or share
+/// key's role for theirs, `Read` for scoped tokens (see `require_action`).
+/// 403 for any other key.
+pub fn board_role(
+    conn: &Connection,
+    board_id: &str,
+    token_hash: &str,
+) -> Result<BoardRole, (Status, Json<ApiError>)> {
     let stored_hash: String = conn
         .query_row(
             "SELECT manage_key_hash FROM boards WHERE id = ?1",
@@ -82,21 +206,253 @@ pub fn require_manage_key(
                 }),
             )
         })?;
-
     if stored_hash == token_hash {
-        Ok(())
-    } else {
-        Err((
+        return Ok(BoardRole::Owner);
+    }
+
+    let role: Option<String> = conn
+        .query_row(
+            "SELECT role FROM board_members WHERE board_id = ?1 AND key_hash = ?2 AND (expires_at IS NULL OR expires_at > datetime('now'))
+             UNION ALL SELECT role FROM share_keys WHERE board_id = ?1 AND key_hash = ?2 AND (expires_at IS NULL OR expires_at > datetime('now'))
+             UNION ALL SELECT 'read' FROM scoped_tokens WHERE board_id = ?1 AND key_hash = ?2 AND (expires_at IS NULL OR expires_at > datetime('now'))
+             UNION ALL SELECT role FROM agent_keys WHERE board_id = ?1 AND key_hash = ?2 AND (expires_at IS NULL OR expires_at > datetime('now'))",
+            rusqlite::params![board_id, token_hash],
+            |row| row.get(0),
+        )
+        .optional()
+        .unwrap_or(None);
+    role.as_deref().and_then(BoardRole::parse).ok_or_else(|| {
+        (
             Status::Forbidden,
             Json(ApiError {
                 error: "Invalid management key for this board".to_string(),
                 code: "INVALID_KEY".to_string(),
                 status: 403,
             }),
+        )
+    })
+}
+
+/// Verify the token's role on the board is at least `min`; returns the role.
+pub fn require_role(
+    conn: &Connection,
+    board_id: &str,
+    token_hash: &str,
+    min: BoardRole,
+) -> Result<BoardRole, (Status, Json<ApiError>)> {
+    let role = board_role(conn, board_id, token_hash)?;
+    if role >= min {
+        Ok(role)
+    } else {
+        Err((
+            Status::Forbidden,
+            Json(ApiError {
+                error: format!("This needs the {} role; this key is a board {}", min.as_str(), role.as_str()),
+                code: "INSUFFICIENT_ROLE".to_string(),
+                status: 403,
+            }),
         ))
     }
 }
 
+/// Verify the token may manage the board: its manage key, or a collaborator
+/// key with the admin or owner role. Used by settings and integration routes;
+/// content routes ask `require_role` for less.
+pub fn require_manage_key(
+    conn: &Connection,
+    board_id: &str,
+    token_hash: &str,
+) -> Result<(), (Status, Json<ApiError>)> {
+    require_role(conn, board_id, token_hash, BoardRole::Admin).map(|_| ())
+}
+
+/// Writes a scoped token can be allowed to make. A scoped token with none
+/// of them is read-only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScopeAction {
+    CreateTask,
+    UpdateTask,
+    MoveTask,
+    Comment,
+    Claim,
+}
+
+impl ScopeAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ScopeAction::CreateTask => "create_task",
+            ScopeAction::UpdateTask => "update_task",
+            ScopeAction::MoveTask => "move_task",
+            ScopeAction::Comment => "comment",
+            ScopeAction::Claim => "claim",
+        }
+    }
+}
+
+/// What a scoped token may do: its actions, in its columns (all columns
+/// when `columns` is empty).
+#[derive(Debug, Clone, Default)]
+pub struct TokenScope {
+    pub actions: Vec<ScopeAction>,
+    pub columns: Vec<String>,
+}
+
+impl TokenScope {
+    /// 403 unless the scope covers `column_id`.
+    pub fn require_column(&self, column_id: &str) -> Result<(), (Status, Json<ApiError>)> {
+        if self.columns.is_empty() || self.columns.iter().any(|c| c == column_id) {
+            Ok(())
+        } else {
+            Err(out_of_scope("This token can't act on tasks in that column".to_string()))
+        }
+    }
+
+    /// 403 unless the scope covers the column the task is in.
+    pub fn require_task(&self, conn: &Connection, task_id: &str) -> Result<(), (Status, Json<ApiError>)> {
+        let column_id: Option<String> = conn
+            .query_row("SELECT column_id FROM tasks WHERE id = ?1", rusqlite::params![task_id], |row| row.get(0))
+            .optional()
+            .unwrap_or(None);
+        match column_id {
+            Some(column_id) => self.require_column(&column_id),
+            None => Ok(()),
+        }
+    }
+}
+
+fn out_of_scope(error: String) -> (Status, Json<ApiError>) {
+    (
+        Status::Forbidden,
+        Json(ApiError {
+            error,
+            code: "OUT_OF_SCOPE".to_string(),
+            status: 403,
+        }),
+    )
+}
+
+/// The scope of a scoped token, or None for any other key.
+pub fn token_scope(conn: &Connection, board_id: &str, token_hash: &str) -> Option<TokenScope> {
+    conn.query_row(
+        "SELECT actions, columns FROM scoped_tokens
+         WHERE board_id = ?1 AND key_hash = ?2 AND (expires_at IS NULL OR expires_at > datetime('now'))",
+        rusqlite::params![board_id, token_hash],
+        |row| {
+            let actions: String = row.get(0)?;
+            let columns: String = row.get(1)?;
+            Ok(TokenScope {
+                actions: serde_json::from_str(&actions).unwrap_or_default(),
+                columns: serde_json::from_str(&columns).unwrap_or_default(),
+            })
+        },
+    )
+    .optional()
+    .unwrap_or(None)
+}
+
+/// Verify the token may make this kind of write: a share link or scoped
+/// token whose scope lists `action`, or any key with the editor role. A
+/// scoped token's scope is returned so the route can check the columns
+/// involved. Takes the token as presented, since share links are checked by
+/// signature rather than looked up by hash.
+pub fn require_action(
+    conn: &Connection,
+    board_id: &str,
+    token: &str,
+    action: ScopeAction,
+) -> Result<Option<TokenScope>, (Status, Json<ApiError>)> {
+    if let Some(link) = share_links::check(conn, board_id, token) {
+        return link?.require(action).map(|_| None);
+    }
+    let token_hash = hash_key(token);
+    let Some(scope) = token_scope(conn, board_id, &token_hash) else {
+        return require_role(conn, board_id, &token_hash, BoardRole::Editor).map(|_| None);
+    };
+    if !scope.actions.contains(&action) {
+        return Err(out_of_scope(format!("This token's scope doesn't include {}", action.as_str())));
+    }
+    Ok(Some(scope))
+}
+
+/// Longest lifetime `ttl_seconds` can give a key (a year).
+const MAX_TTL_SECONDS: i64 = 366 * 24 * 3600;
+
+/// When a new key should expire, from a request's `expires_at` (RFC 3339)
+/// or `ttl_seconds`, in the form the database compares. None = never.
+pub fn key_expiry(
+    expires_at: Option<&str>,
+    ttl_seconds: Option<i64>,
+) -> Result<Option<String>, (Status, Json<ApiError>)> {
+    let invalid = |error: &str| {
+        (
+            Status::BadRequest,
+            Json(ApiError {
+                error: error.to_string(),
+                code: "INVALID_INPUT".to_string(),
+                status: 400,
+            }),
+        )
+    };
+    let expires = match (expires_at, ttl_seconds) {
+        (None, None) => return Ok(None),
+        (Some(_), Some(_)) => return Err(invalid("Give expires_at or ttl_seconds, not both")),
+        (None, Some(ttl)) if !(1..=MAX_TTL_SECONDS).contains(&ttl) => {
+            return Err(invalid(&format!("ttl_seconds must be between 1 and {}", MAX_TTL_SECONDS)))
+        }
+        (None, Some(ttl)) => chrono::Utc::now() + chrono::Duration::seconds(ttl),
+        (Some(at), None) => chrono::DateTime::parse_from_rfc3339(at)
+            .map_err(|_| invalid("expires_at must be an RFC 3339 timestamp"))?
+            .with_timezone(&chrono::Utc),
+    };
+    if expires <= chrono::Utc::now() {
+        return Err(invalid("expires_at must be in the future"));
+    }
+    Ok(Some(expires.format("%Y-%m-%d %H:%M:%S").to_string()))
+}
+
+/// What a presented key's hash says about the request before any route
+/// runs: whether it's an expired key, the agent an agent key speaks for, and
+/// whether it has any role on the board in the request path.
+#[derive(Debug, Clone, Default)]
+pub struct KeyStatus {
+    pub expired: bool,
+    pub agent_id: Option<String>,
+    pub on_board: bool,
+}
+
+/// One lookup for `KeyStatus`; `on_board` matches `board_role` succeeding.
+pub fn key_status(conn: &Connection, token_hash: &str, board_id: Option<&str>) -> KeyStatus {
+    conn.query_row(
+        "SELECT COALESCE(k.expires_at <= datetime('now'), 0), k.agent_id,
+                ?2 IS NOT NULL AND (
+                    EXISTS(SELECT 1 FROM boards WHERE id = ?2 AND manage_key_hash = ?1)
+                    OR EXISTS(
+                        SELECT 1 FROM board_members WHERE board_id = ?2 AND key_hash = ?1 AND (expires_at IS NULL OR expires_at > datetime('now'))
+                        UNION ALL SELECT 1 FROM share_keys WHERE board_id = ?2 AND key_hash = ?1 AND (expires_at IS NULL OR expires_at > datetime('now'))
+                        UNION ALL SELECT 1 FROM scoped_tokens WHERE board_id = ?2 AND key_hash = ?1 AND (expires_at IS NULL OR expires_at > datetime('now'))
+                        UNION ALL SELECT 1 FROM agent_keys WHERE board_id = ?2 AND key_hash = ?1 AND (expires_at IS NULL OR expires_at > datetime('now'))
+                    )
+                )
+         FROM (SELECT 1) LEFT JOIN (
+             SELECT expires_at, NULL AS agent_id FROM board_members WHERE key_hash = ?1
+             UNION ALL SELECT expires_at, NULL FROM share_keys WHERE key_hash = ?1
+             UNION ALL SELECT expires_at, NULL FROM scoped_tokens WHERE key_hash = ?1
+             UNION ALL SELECT expires_at, agent_id FROM agent_keys WHERE key_hash = ?1
+             LIMIT 1
+         ) k ON 1",
+        rusqlite::params![token_hash, board_id],
+        |row| {
+            Ok(KeyStatus {
+                expired: row.get(0)?,
+                agent_id: row.get(1)?,
+                on_board: row.get(2)?,
+            })
+        },
+    )
+    .unwrap_or_default()
+}
+
 /// Check if the board requires a display name. Returns true if require_display_name is set.
 pub fn board_requires_display_name(conn: &Connection, board_id: &str) -> bool {
     conn.query_row(
@@ -109,15 +465,13 @@ pub fn board_requires_display_name(conn: &Connection, board_id: &str) -> bool {
 }
 
 /// Validate that a display name is provided when the board requires one.
-/// `actor` should be the display name to validate.
-pub fn require_display_name_if_needed(
+/// Applied uniformly to every attributed write via the `Actor` guard.
+pub fn require_display_name(
     conn: &Connection,
     board_id: &str,
-    actor: &str,
+    actor: &Actor,
 ) -> Result<(), (Status, Json<ApiError>)> {
-    if board_requires_display_name(conn, board_id)
-        && (actor.is_empty() || actor == "anonymous")
-    {
+    if board_requires_display_name(conn, board_id) && actor.is_anonymous() {
         Err((
             Status::BadRequest,
             Json(ApiError {
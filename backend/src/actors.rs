@@ -0,0 +1,375 @@
+//! Actor identities: deterministic avatar colors/initials and optional uploaded avatars.
+//!
+//! Actors are free-text names (no accounts), so every client derives the same
+//! color and initials from the name alone. Anyone holding a board key (or
+//! signed in) may upload a small image for a name; the first upload returns
+//! an `avatar_key` that guards later changes.
+
+use rocket::data::{Data, ToByteUnit};
+use rocket::http::{ContentType, Header, Status};
+use rocket::serde::json::Json;
+use rocket::State;
+use sha2::{Digest, Sha256};
+
+use crate::auth::{AdminToken, BoardToken};
+use crate::db::{hash_key, DbPool};
+use crate::models::*;
+use crate::oidc::SessionUser;
+use crate::routes::{db_error, with_db, with_tx};
+
+/// Upload size cap — avatars are meant to be tiny.
+const MAX_AVATAR_BYTES: u64 = 64 * 1024;
+
+/// Uploaded avatars kept in all; names past this get the generated one.
+const MAX_AVATARS: i64 = 10_000;
+
+/// Image types accepted for upload (SVG excluded: it can carry script).
+const ALLOWED_TYPES: &[&str] = &["image/png", "image/jpeg", "image/gif", "image/webp"];
+
+/// Avatar image response with caching headers. Browsers are told not to
+/// second-guess the content type, so an upload can't be read as HTML.
+#[derive(Responder)]
+pub struct AvatarImage {
+    bytes: Vec<u8>,
+    content_type: ContentType,
+    cache: Header<'static>,
+    nosniff: Header<'static>,
+}
+
+/// Case-insensitive lookup key for an actor name.
+fn name_key(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Deterministic color from the actor name: hue from the hash, fixed saturation/lightness
+/// so white initials stay readable.
+pub fn actor_color(name: &str) -> String {
+    let digest = Sha256::digest(name_key(name).as_bytes());
+    let hue = u16::from_be_bytes([digest[0], digest[1]]) % 360;
+    let (r, g, b) = hsl_to_rgb(hue as f64, 0.55, 0.45);
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn hsl_to_rgb(h: f64, s: f64, l: f64) -> (u8, u8, u8) {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
+    let m = l - c / 2.0;
+    let (r, g, b) = match h as u32 {
+        0..=59 => (c, x, 0.0),
+        60..=119 => (x, c, 0.0),
+        120..=179 => (0.0, c, x),
+        180..=239 => (0.0, x, c),
+        240..=299 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let to_u8 = |v: f64| ((v + m) * 255.0).round() as u8;
+    (to_u8(r), to_u8(g), to_u8(b))
+}
+
+/// Initials: first letter of the first two words ("Jane Doe" → "JD", "agent-7" → "A7"),
+/// or the first letter for single-word names.
+pub fn actor_initials(name: &str) -> String {
+    let words: Vec<&str> = name
+        .split(|c: char| c.is_whitespace() || c == '-' || c == '_' || c == '.')
+        .filter(|w| !w.is_empty())
+        .collect();
+    let initials: String = words
+        .iter()
+        .take(2)
+        .filter_map(|w| w.chars().next())
+        .flat_map(|c| c.to_uppercase())
+        .collect();
+    if initials.is_empty() {
+        "?".to_string()
+    } else {
+        initials
+    }
+}
+
+fn generated_svg(name: &str) -> String {
+    let initials = actor_initials(name)
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="64" height="64" viewBox="0 0 64 64"><rect width="64" height="64" rx="32" fill="{}"/><text x="32" y="32" dy=".35em" text-anchor="middle" font-family="sans-serif" font-size="26" font-weight="600" fill="#ffffff">{}</text></svg>"##,
+        actor_color(name),
+        initials
+    )
+}
+
+/// Check the leading bytes actually match the declared image type.
+fn sniff_matches(content_type: &str, bytes: &[u8]) -> bool {
+    match content_type {
+        "image/png" => bytes.starts_with(b"\x89PNG\r\n\x1a\n"),
+        "image/jpeg" => bytes.starts_with(&[0xFF, 0xD8, 0xFF]),
+        "image/gif" => bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a"),
+        "image/webp" => bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP",
+        _ => false,
+    }
+}
+
+fn avatar_error(status: Status, code: &str, msg: &str) -> (Status, Json<ApiError>) {
+    (
+        status,
+        Json(ApiError {
+            error: msg.to_string(),
+            code: code.to_string(),
+            status: status.code,
+        }),
+    )
+}
+
+/// Actor profile (initials, color, avatar URL) — public.
+#[get("/actors/<name>")]
+pub async fn get_actor(name: &str, db: &State<DbPool>) -> Result<Json<ActorProfile>, (Status, Json<ApiError>)> {
+    let name = name.to_owned();
+    with_db(db, move |conn| {
+        let name = name.as_str();
+        let has_uploaded_avatar: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM actor_avatars WHERE name_key = ?1",
+                rusqlite::params![name_key(name)],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        Ok(Json(ActorProfile {
+            name: name.to_string(),
+            initials: actor_initials(name),
+            color: actor_color(name),
+            avatar_url: format!("/api/v1/actors/{}/avatar", urlencode_segment(name)),
+            has_uploaded_avatar,
+        }))
+    })
+    .await
+}
+
+/// Avatar image — public. Serves the uploaded image, or a generated SVG with
+/// the actor's initials on their deterministic color.
+#[get("/actors/<name>/avatar")]
+pub async fn actor_avatar(name: &str, db: &State<DbPool>) -> Result<AvatarImage, (Status, Json<ApiError>)> {
+    let name = name.to_owned();
+    with_db(db, move |conn| {
+        let name = name.as_str();
+        let uploaded: Option<(String, Vec<u8>)> = conn
+            .query_row(
+                "SELECT content_type, data FROM actor_avatars WHERE name_key = ?1",
+                rusqlite::params![name_key(name)],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .ok();
+
+        Ok(match uploaded {
+            Some((content_type, bytes)) => AvatarImage {
+                bytes,
+                content_type: ContentType::parse_flexible(&content_type).unwrap_or(ContentType::Binary),
+                cache: Header::new("Cache-Control", "public, max-age=300"),
+                nosniff: Header::new("X-Content-Type-Options", "nosniff"),
+            },
+            None => AvatarImage {
+                bytes: generated_svg(name).into_bytes(),
+                content_type: ContentType::SVG,
+                cache: Header::new("Cache-Control", "public, max-age=300"),
+                nosniff: Header::new("X-Content-Type-Options", "nosniff"),
+            },
+        })
+    })
+    .await
+}
+
+/// Whether `key_hash` is a live key of any board: its manage key, or an
+/// unexpired collaborator, share, scoped or agent key.
+fn is_board_key(conn: &rusqlite::Connection, key_hash: &str) -> bool {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM boards WHERE manage_key_hash = ?1)
+             OR EXISTS(
+                 SELECT 1 FROM board_members WHERE key_hash = ?1 AND (expires_at IS NULL OR expires_at > datetime('now'))
+                 UNION ALL SELECT 1 FROM share_keys WHERE key_hash = ?1 AND (expires_at IS NULL OR expires_at > datetime('now'))
+                 UNION ALL SELECT 1 FROM scoped_tokens WHERE key_hash = ?1 AND (expires_at IS NULL OR expires_at > datetime('now'))
+                 UNION ALL SELECT 1 FROM agent_keys WHERE key_hash = ?1 AND (expires_at IS NULL OR expires_at > datetime('now'))
+             )",
+        rusqlite::params![key_hash],
+        |row| row.get(0),
+    )
+    .unwrap_or(false)
+}
+
+/// Upload or replace an actor's avatar (PNG/JPEG/GIF/WebP, max 64 KB, raw image body).
+/// The first upload for a name takes a board key, a signed-in session or the
+/// admin key, and returns an `avatar_key`; replacing an existing avatar
+/// requires that key (or the admin key).
+#[put("/actors/<name>/avatar", data = "<data>")]
+pub async fn upload_actor_avatar(
+    name: &str,
+    content_type: &ContentType,
+    data: Data<'_>,
+    token: Option<BoardToken>,
+    admin: Option<AdminToken>,
+    user: Option<SessionUser>,
+    db: &State<DbPool>,
+) -> Result<Json<AvatarUploadResponse>, (Status, Json<ApiError>)> {
+    if name.trim().is_empty() {
+        return Err(avatar_error(Status::BadRequest, "INVALID_INPUT", "Actor name cannot be empty"));
+    }
+    let mime = format!("{}/{}", content_type.top(), content_type.sub()).to_lowercase();
+    if !ALLOWED_TYPES.contains(&mime.as_str()) {
+        return Err(avatar_error(
+            Status::UnsupportedMediaType,
+            "UNSUPPORTED_IMAGE_TYPE",
+            "Avatar must be image/png, image/jpeg, image/gif or image/webp",
+        ));
+    }
+
+    let body = data
+        .open(MAX_AVATAR_BYTES.bytes())
+        .into_bytes()
+        .await
+        .map_err(|e| avatar_error(Status::BadRequest, "INVALID_INPUT", &e.to_string()))?;
+    if !body.is_complete() {
+        return Err(avatar_error(
+            Status::PayloadTooLarge,
+            "AVATAR_TOO_LARGE",
+            "Avatar images are limited to 64 KB",
+        ));
+    }
+    let bytes = body.into_inner();
+    if !sniff_matches(&mime, &bytes) {
+        return Err(avatar_error(
+            Status::BadRequest,
+            "INVALID_IMAGE",
+            "Image data does not match the declared Content-Type",
+        ));
+    }
+
+    let name = name.to_owned();
+    with_tx(db, move |conn| {
+        let name = name.as_str();
+        let key = name_key(name);
+        let existing_hash: Option<String> = conn
+            .query_row(
+                "SELECT key_hash FROM actor_avatars WHERE name_key = ?1",
+                rusqlite::params![key],
+                |row| row.get(0),
+            )
+            .ok();
+
+        let avatar_key = match existing_hash {
+            Some(ref hash) => {
+                let authorized = admin.is_some() || token.map(|t| &hash_key(&t.0) == hash).unwrap_or(false);
+                if !authorized {
+                    return Err(avatar_error(
+                        Status::Forbidden,
+                        "FORBIDDEN",
+                        "This avatar is already set. Use its avatar_key to replace it.",
+                    ));
+                }
+                conn.execute(
+                    "UPDATE actor_avatars SET name = ?1, content_type = ?2, data = ?3, updated_at = datetime('now')
+                     WHERE name_key = ?4",
+                    rusqlite::params![name.trim(), mime, bytes, key],
+                )
+                .map_err(|e| db_error(&e.to_string()))?;
+                None
+            }
+            None => {
+                let authorized = admin.is_some() || user.is_some() || token.is_some_and(|t| is_board_key(conn, &hash_key(&t.0)));
+                if !authorized {
+                    return Err(avatar_error(
+                        Status::Unauthorized,
+                        "KEY_REQUIRED",
+                        "Uploading an avatar takes a board key or a signed-in session",
+                    ));
+                }
+                let count: i64 = conn
+                    .query_row("SELECT COUNT(*) FROM actor_avatars", [], |row| row.get(0))
+                    .map_err(|e| db_error(&e.to_string()))?;
+                if count >= MAX_AVATARS {
+                    return Err(avatar_error(
+                        Status::InsufficientStorage,
+                        "AVATAR_LIMIT_REACHED",
+                        "This server holds as many uploaded avatars as it allows",
+                    ));
+                }
+                let new_key = format!("av_{}", uuid::Uuid::new_v4().to_string().replace('-', ""));
+                conn.execute(
+                    "INSERT INTO actor_avatars (name_key, name, content_type, data, key_hash) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    rusqlite::params![key, name.trim(), mime, bytes, hash_key(&new_key)],
+                )
+                .map_err(|e| db_error(&e.to_string()))?;
+                Some(new_key)
+            }
+        };
+
+        Ok(Json(AvatarUploadResponse {
+            name: name.trim().to_string(),
+            content_type: mime,
+            bytes: bytes.len(),
+            avatar_key,
+        }))
+    })
+    .await
+}
+
+/// Remove an uploaded avatar (falls back to the generated one). Requires the avatar_key or admin key.
+#[delete("/actors/<name>/avatar")]
+pub async fn delete_actor_avatar(
+    name: &str,
+    token: Option<BoardToken>,
+    admin: Option<AdminToken>,
+    db: &State<DbPool>,
+) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
+    let name = name.to_owned();
+    with_db(db, move |conn| {
+        let name = name.as_str();
+        let key = name_key(name);
+        let hash: String = conn
+            .query_row(
+                "SELECT key_hash FROM actor_avatars WHERE name_key = ?1",
+                rusqlite::params![key],
+                |row| row.get(0),
+            )
+            .map_err(|_| avatar_error(Status::NotFound, "NOT_FOUND", "No uploaded avatar for this actor"))?;
+
+        let authorized = admin.is_some() || token.map(|t| hash_key(&t.0) == hash).unwrap_or(false);
+        if !authorized {
+            return Err(avatar_error(Status::Forbidden, "FORBIDDEN", "Invalid avatar key"));
+        }
+        conn.execute("DELETE FROM actor_avatars WHERE name_key = ?1", rusqlite::params![key])
+            .map_err(|e| db_error(&e.to_string()))?;
+        Ok(Json(serde_json::json!({"deleted": true, "name": name})))
+    })
+    .await
+}
+
+/// Percent-encode a single path segment (names may contain spaces etc.).
+fn urlencode_segment(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_initials() {
+        assert_eq!(actor_initials("Jane Doe"), "JD");
+        assert_eq!(actor_initials("agent-7"), "A7");
+        assert_eq!(actor_initials("nanook"), "N");
+        assert_eq!(actor_initials("  "), "?");
+    }
+
+    #[test]
+    fn test_color_is_deterministic_and_case_insensitive() {
+        let c = actor_color("Nanook");
+        assert_eq!(c, actor_color("nanook"));
+        assert_eq!(c.len(), 7);
+        assert!(c.starts_with('#'));
+        assert_ne!(actor_color("alice"), actor_color("bob"));
+    }
+}
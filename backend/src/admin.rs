@@ -0,0 +1,1202 @@
+//! Instance admin routes (require `ADMIN_KEY`).
+
+use std::collections::{BTreeMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::Utc;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::Connection;
+
+use crate::access::{self, Visibility};
+use crate::auth::{Actor, AdminToken};
+use crate::db::{hash_key, DbPool, DB_STATS};
+use crate::events::EventBus;
+use crate::lifecycle::{self, LifecycleHooks};
+use crate::models::*;
+use crate::rate_limit::{RateLimits, RouteGroup};
+use crate::routes::{self, db_error, not_found, with_db, with_tx};
+use crate::tags;
+use crate::query_guard::QueryGuard;
+use crate::usage::ClientUsage;
+
+// ============ PII Scrub ============
+
+/// JSON keys in event data whose string values are actor names.
+const ACTOR_KEYS: &[&str] = &[
+    "actor",
+    "creator",
+    "created_by",
+    "assigned_to",
+    "claimed_by",
+    "mentions",
+    "changed_by",
+    "deleted_by",
+    "uploaded_by",
+    "locked_by",
+    "agent_id",
+];
+
+/// Replacement used for actor names in "remove" mode.
+const REMOVED_ACTOR: &str = "[redacted]";
+
+#[derive(Clone, Copy, PartialEq)]
+enum ScrubMode {
+    Pseudonymize,
+    Remove,
+}
+
+/// Applies the scrub rules to individual values and tallies replacements.
+struct Scrubber {
+    mode: ScrubMode,
+    salt: String,
+    /// Lowercased actor names to target; None = every actor name.
+    targets: Option<HashSet<String>>,
+    actor_names: i64,
+    emails: i64,
+    ip_addresses: i64,
+}
+
+impl Scrubber {
+    /// Replacement for an actor name, or None if it should be left alone.
+    fn actor(&mut self, name: &str) -> Option<String> {
+        let lower = name.trim().to_lowercase();
+        if lower.is_empty() || lower == "anonymous" || lower.starts_with("anon-") || name == REMOVED_ACTOR {
+            return None;
+        }
+        if let Some(targets) = &self.targets {
+            if !targets.contains(&lower) {
+                return None;
+            }
+        }
+        self.actor_names += 1;
+        Some(match self.mode {
+            ScrubMode::Pseudonymize => format!("anon-{}", &hash_key(&format!("{}:{}", self.salt, lower))[..10]),
+            ScrubMode::Remove => REMOVED_ACTOR.to_string(),
+        })
+    }
+
+    /// Redact emails, IP addresses and @mentions of targeted actors in free text.
+    fn text(&mut self, text: &str) -> Option<String> {
+        let mut out = String::with_capacity(text.len());
+        let mut changed = false;
+        let mut rest = text;
+        while !rest.is_empty() {
+            let ws = rest.len() - rest.trim_start().len();
+            out.push_str(&rest[..ws]);
+            rest = &rest[ws..];
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let word = &rest[..end];
+            rest = &rest[end..];
+
+            let core = word
+                .trim_matches(|c: char| "()[]<>{},;\"'!?.".contains(c))
+                .trim_end_matches(':');
+            let replacement = if core.is_empty() {
+                None
+            } else if is_email(core.strip_prefix("mailto:").unwrap_or(core)) {
+                self.emails += 1;
+                Some("[email]".to_string())
+            } else if is_ip_address(core) {
+                self.ip_addresses += 1;
+                Some("[ip]".to_string())
+            } else if let (Some(name), true) = (core.strip_prefix('@'), self.targets.is_some()) {
+                self.actor(name).map(|r| format!("@{}", r))
+            } else {
+                None
+            };
+
+            match replacement {
+                Some(r) => {
+                    changed = true;
+                    out.push_str(&word.replacen(core, &r, 1));
+                }
+                None => out.push_str(word),
+            }
+        }
+        changed.then_some(out)
+    }
+
+    /// Whether `value` is exactly one of the targeted actor names.
+    fn is_target(&self, value: &str) -> bool {
+        self.targets.as_ref().is_some_and(|t| t.contains(&value.trim().to_lowercase()))
+    }
+
+    /// Replacement for a column value of the given kind, or None if unchanged.
+    fn value(&mut self, kind: Pii, value: &str) -> Option<String> {
+        match kind {
+            Pii::Actor => self.actor(value),
+            Pii::Text => self.text(value),
+            Pii::Email => (self.targets.is_none() || self.is_target(value)).then(|| {
+                self.emails += 1;
+                "[email]".to_string()
+            }),
+            Pii::Transcript => self.transcript(value),
+            Pii::Json | Pii::Names => {
+                let mut doc: serde_json::Value = serde_json::from_str(value).ok()?;
+                self.json(&mut doc, matches!(kind, Pii::Names)).then(|| doc.to_string())
+            }
+        }
+    }
+
+    /// Scrub a comment transcript: actor names in the `[time] actor:` entry
+    /// headers, free text everywhere else.
+    fn transcript(&mut self, transcript: &str) -> Option<String> {
+        let mut out = String::with_capacity(transcript.len());
+        let mut changed = false;
+        for line in transcript.split_inclusive('\n') {
+            let body = line.trim_end_matches('\n');
+            let header = body
+                .strip_prefix('[')
+                .and_then(|rest| rest.split_once("] "))
+                .and_then(|(time, rest)| Some((time, rest.strip_suffix(':')?)));
+            let replaced = match header {
+                Some((time, actor)) => self.actor(actor).map(|a| format!("[{}] {}:{}", time, a, &line[body.len()..])),
+                None => self.text(line),
+            };
+            changed |= replaced.is_some();
+            out.push_str(replaced.as_deref().unwrap_or(line));
+        }
+        changed.then_some(out)
+    }
+
+    /// Scrub a JSON document in place. Returns true if anything changed.
+    fn json(&mut self, value: &mut serde_json::Value, actor_field: bool) -> bool {
+        match value {
+            serde_json::Value::String(s) => {
+                let replaced = if actor_field || self.is_target(s) { self.actor(s) } else { self.text(s) };
+                match replaced {
+                    Some(r) => {
+                        *s = r;
+                        true
+                    }
+                    None => false,
+                }
+            }
+            serde_json::Value::Array(items) => {
+                let mut changed = false;
+                for item in items {
+                    changed |= self.json(item, actor_field);
+                }
+                changed
+            }
+            serde_json::Value::Object(map) => {
+                let mut changed = false;
+                for (key, item) in map.iter_mut() {
+                    changed |= self.json(item, ACTOR_KEYS.contains(&key.as_str()));
+                }
+                changed
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Loose email check: `local@domain.tld` with conservative character sets.
+fn is_email(s: &str) -> bool {
+    let Some((local, domain)) = s.split_once('@') else { return false };
+    !local.is_empty()
+        && local.chars().all(|c| c.is_ascii_alphanumeric() || "._%+-".contains(c))
+        && domain.contains('.')
+        && domain
+            .split('.')
+            .all(|label| !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+}
+
+/// IPv4/IPv6 address, optionally with a port.
+fn is_ip_address(s: &str) -> bool {
+    s.parse::<std::net::IpAddr>().is_ok() || s.parse::<std::net::SocketAddr>().is_ok()
+}
+
+/// Remove or pseudonymize personal data (actor names, emails, IP addresses)
+/// in rows older than a cutoff. Dry-run by default.
+#[post("/admin/scrub", format = "json", data = "<req>")]
+pub async fn scrub_pii(
+    req: Json<ScrubRequest>,
+    _admin: AdminToken,
+    db: &State<DbPool>,
+) -> Result<Json<ScrubReport>, (Status, Json<ApiError>)> {
+    let req = req.into_inner();
+    let mode = match req.mode.as_deref().unwrap_or("pseudonymize") {
+        "pseudonymize" => ScrubMode::Pseudonymize,
+        "remove" => ScrubMode::Remove,
+        other => {
+            return Err((
+                Status::BadRequest,
+                Json(ApiError {
+                    error: format!("Unknown mode '{}'. Use 'pseudonymize' or 'remove'", other),
+                    code: "VALIDATION_ERROR".to_string(),
+                    status: 400,
+                }),
+            ))
+        }
+    };
+    if req.older_than_days < 0 {
+        return Err((
+            Status::BadRequest,
+            Json(ApiError {
+                error: "older_than_days must be >= 0".to_string(),
+                code: "VALIDATION_ERROR".to_string(),
+                status: 400,
+            }),
+        ));
+    }
+    // Unsalted pseudonyms could be reversed by hashing known names
+    let salt = match req.salt {
+        Some(salt) if salt.trim().is_empty() => {
+            return Err((
+                Status::BadRequest,
+                Json(ApiError {
+                    error: "salt must not be empty; omit it to use a random one".to_string(),
+                    code: "VALIDATION_ERROR".to_string(),
+                    status: 400,
+                }),
+            ))
+        }
+        Some(salt) => salt,
+        None => hex::encode(rand::random::<[u8; 32]>()),
+    };
+    let dry_run = req.dry_run.unwrap_or(true);
+    let cutoff = (Utc::now() - chrono::Duration::days(req.older_than_days))
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string();
+
+    let mut scrubber = Scrubber {
+        mode,
+        salt,
+        targets: req
+            .actors
+            .map(|names| names.iter().map(|n| n.trim().to_lowercase()).filter(|n| !n.is_empty()).collect()),
+        actor_names: 0,
+        emails: 0,
+        ip_addresses: 0,
+    };
+    let boards: Option<HashSet<String>> = req.board_ids.map(|ids| ids.into_iter().collect());
+
+    with_db(db, move |conn| {
+        let tx = conn.unchecked_transaction().map_err(|e| db_error(&e.to_string()))?;
+        let mut report = run_scrub(&tx, &mut scrubber, boards.as_ref(), &cutoff, dry_run)
+            .map_err(|e| db_error(&e.to_string()))?;
+        tx.commit().map_err(|e| db_error(&e.to_string()))?;
+
+        report.mode = match mode {
+            ScrubMode::Pseudonymize => "pseudonymize",
+            ScrubMode::Remove => "remove",
+        }
+        .to_string();
+        Ok(Json(report))
+    })
+    .await
+}
+
+/// How a column's value is scrubbed.
+#[derive(Clone, Copy)]
+enum Pii {
+    /// A single actor name.
+    Actor,
+    /// Free text: emails, IP addresses and targeted @mentions.
+    Text,
+    /// An address that belongs to the row's actor: scrubbed by untargeted
+    /// scrubs, or when `actors` lists the address itself.
+    Email,
+    /// A JSON document: strings under `ACTOR_KEYS` are actor names, the rest free text.
+    Json,
+    /// A JSON list of actor names.
+    Names,
+    /// A collapsed comment transcript: `[time] actor:` headers followed by messages.
+    Transcript,
+}
+
+/// A table holding personal data, and how to find and scrub it.
+struct PiiTable {
+    name: &'static str,
+    /// SQL expression for the row's board; `NULL` for instance-wide tables,
+    /// which board-restricted scrubs leave alone.
+    board: &'static str,
+    /// SQL expression compared against the cutoff.
+    timestamp: &'static str,
+    /// Columns to scrub; tables whose rows are deleted may name SQL
+    /// expressions instead, such as a parent row's columns.
+    columns: &'static [(&'static str, Pii)],
+    /// Delete scrubbed rows rather than rewrite them — rows that exist only
+    /// to hold the personal data, or whose key is the data itself.
+    delete: bool,
+}
+
+const TASK_BOARD: &str = "(SELECT board_id FROM tasks WHERE tasks.id = task_id)";
+
+/// A session is scrubbed with its sign-in identity, so it can't keep the
+/// identity's boards open after the identity is gone.
+const SESSION_USER: &[(&str, Pii)] = &[
+    ("(SELECT name FROM users WHERE users.id = user_id)", Pii::Actor),
+    ("(SELECT email FROM users WHERE users.id = user_id)", Pii::Email),
+];
+
+/// Every table the scrub visits. A table added to the schema must be listed
+/// here or in `NO_PII_TABLES`; `test_every_table_is_classified` enforces it.
+const PII_TABLES: &[PiiTable] = &[
+    PiiTable {
+        name: "boards",
+        board: "id",
+        timestamp: "updated_at",
+        columns: &[("created_by", Pii::Actor), ("quick_reassign_to", Pii::Actor), ("name", Pii::Text), ("description", Pii::Text)],
+        delete: false,
+    },
+    PiiTable { name: "columns", board: "board_id", timestamp: "created_at", columns: &[("auto_assign", Pii::Names)], delete: false },
+    PiiTable {
+        name: "tasks",
+        board: "board_id",
+        timestamp: "updated_at",
+        columns: &[
+            ("created_by", Pii::Actor),
+            ("assigned_to", Pii::Actor),
+            ("claimed_by", Pii::Actor),
+            ("title", Pii::Text),
+            ("description", Pii::Text),
+        ],
+        delete: false,
+    },
+    // Comment mentions follow their event through triggers; the entry below
+    // catches anything older than the event it came from.
+    PiiTable { name: "task_events", board: TASK_BOARD, timestamp: "created_at", columns: &[("actor", Pii::Actor), ("data", Pii::Json)], delete: false },
+    PiiTable { name: "task_mentions", board: TASK_BOARD, timestamp: "created_at", columns: &[("name", Pii::Actor)], delete: false },
+    PiiTable {
+        name: "task_dependencies",
+        board: "board_id",
+        timestamp: "created_at",
+        columns: &[("created_by", Pii::Actor), ("note", Pii::Text)],
+        delete: false,
+    },
+    PiiTable {
+        name: "task_trash",
+        board: "board_id",
+        timestamp: "deleted_at",
+        columns: &[("title", Pii::Text), ("snapshot", Pii::Json), ("deleted_by", Pii::Actor)],
+        delete: false,
+    },
+    PiiTable { name: "task_locks", board: TASK_BOARD, timestamp: "created_at", columns: &[("locked_by", Pii::Actor)], delete: false },
+    PiiTable { name: "task_watchers", board: "board_id", timestamp: "created_at", columns: &[("name", Pii::Actor)], delete: false },
+    PiiTable {
+        name: "comment_revisions",
+        board: "board_id",
+        timestamp: "changed_at",
+        columns: &[("data", Pii::Json), ("changed_by", Pii::Actor)],
+        delete: false,
+    },
+    PiiTable {
+        name: "comment_transcripts",
+        board: "board_id",
+        timestamp: "created_at",
+        columns: &[("transcript", Pii::Transcript), ("created_by", Pii::Actor)],
+        delete: false,
+    },
+    PiiTable {
+        name: "attachments",
+        board: "board_id",
+        timestamp: "created_at",
+        columns: &[("filename", Pii::Text), ("uploaded_by", Pii::Actor)],
+        delete: false,
+    },
+    PiiTable { name: "board_events", board: "board_id", timestamp: "created_at", columns: &[("actor", Pii::Actor), ("data", Pii::Json)], delete: false },
+    PiiTable { name: "board_members", board: "board_id", timestamp: "created_at", columns: &[("name", Pii::Actor), ("created_by", Pii::Actor)], delete: false },
+    PiiTable { name: "share_keys", board: "board_id", timestamp: "created_at", columns: &[("name", Pii::Text), ("created_by", Pii::Actor)], delete: false },
+    PiiTable { name: "scoped_tokens", board: "board_id", timestamp: "created_at", columns: &[("name", Pii::Text), ("created_by", Pii::Actor)], delete: false },
+    PiiTable { name: "agent_keys", board: "board_id", timestamp: "created_at", columns: &[("agent_id", Pii::Actor), ("created_by", Pii::Actor)], delete: false },
+    PiiTable { name: "inbox_cursors", board: "board_id", timestamp: "updated_at", columns: &[("name", Pii::Actor)], delete: false },
+    PiiTable { name: "notification_preferences", board: "board_id", timestamp: "updated_at", columns: &[("name", Pii::Actor)], delete: false },
+    PiiTable {
+        name: "email_subscriptions",
+        board: "board_id",
+        timestamp: "updated_at",
+        columns: &[("name", Pii::Actor), ("email", Pii::Text)],
+        delete: true,
+    },
+    PiiTable {
+        name: "email_outbox",
+        board: "board_id",
+        timestamp: "created_at",
+        columns: &[("name", Pii::Actor), ("email", Pii::Text), ("subject", Pii::Text), ("body", Pii::Text)],
+        delete: true,
+    },
+    // URLs are operational config, not personal data
+    PiiTable { name: "webhooks", board: "board_id", timestamp: "created_at", columns: &[("created_by", Pii::Actor)], delete: false },
+    PiiTable { name: "webhook_outbox", board: "board_id", timestamp: "created_at", columns: &[("payload", Pii::Json), ("last_error", Pii::Text)], delete: false },
+    PiiTable {
+        name: "webhook_deliveries",
+        board: "board_id",
+        timestamp: "created_at",
+        columns: &[("payload", Pii::Json), ("response_body", Pii::Text), ("error", Pii::Text)],
+        delete: false,
+    },
+    PiiTable {
+        name: "webhook_dead_letters",
+        board: "board_id",
+        timestamp: "created_at",
+        columns: &[("payload", Pii::Json), ("last_error", Pii::Text)],
+        delete: false,
+    },
+    PiiTable { name: "actor_avatars", board: "NULL", timestamp: "updated_at", columns: &[("name", Pii::Actor)], delete: true },
+    PiiTable { name: "import_validations", board: "NULL", timestamp: "created_at", columns: &[("document", Pii::Json)], delete: true },
+    PiiTable {
+        name: "rate_limit_windows",
+        board: "NULL",
+        timestamp: "datetime(window_start_ms / 1000, 'unixepoch')",
+        columns: &[("key", Pii::Text)],
+        delete: true,
+    },
+    // Sessions first: they find their identity through `users`. A deleted
+    // identity's boards are left without a signed-in owner; signing in again
+    // creates a new identity.
+    PiiTable {
+        name: "user_sessions",
+        board: "NULL",
+        timestamp: "(SELECT last_login_at FROM users WHERE users.id = user_id)",
+        columns: SESSION_USER,
+        delete: true,
+    },
+    PiiTable {
+        name: "users",
+        board: "NULL",
+        timestamp: "last_login_at",
+        columns: &[("name", Pii::Actor), ("email", Pii::Email)],
+        delete: true,
+    },
+];
+
+/// Tables with nothing to scrub: configuration, counters, key material and
+/// indexes derived from scrubbed tables (`tasks_fts` follows `tasks` and
+/// `task_events` through triggers).
+#[cfg(test)]
+const NO_PII_TABLES: &[&str] = &[
+    "board_tags",
+    "board_views",
+    "email_cursor",
+    "email_reminders",
+    "inbound_hooks",
+    "server_keys",
+    "tags",
+    "task_key_aliases",
+    "tasks_fts",
+    "validation_hooks",
+    "webhook_routes",
+    "webhook_signing_keys",
+];
+
+/// A scrubbed table row: rowid, board, then the registry columns.
+type PiiRow = (i64, Option<String>, Vec<Option<String>>);
+
+fn run_scrub(
+    conn: &Connection,
+    s: &mut Scrubber,
+    boards: Option<&HashSet<String>>,
+    cutoff: &str,
+    dry_run: bool,
+) -> Result<ScrubReport, rusqlite::Error> {
+    let in_scope = |board_id: &Option<String>| match (boards, board_id) {
+        (None, _) => true,
+        (Some(set), Some(id)) => set.contains(id),
+        (Some(_), None) => false,
+    };
+    let mut touched_boards: HashSet<String> = HashSet::new();
+    let mut tables = BTreeMap::new();
+
+    for table in PII_TABLES {
+        let names: Vec<&str> = table.columns.iter().map(|(name, _)| *name).collect();
+        let rows: Vec<PiiRow> = conn
+            .prepare(&format!(
+                "SELECT rowid, {}, {} FROM {} WHERE {} <= ?1",
+                table.board,
+                names.join(", "),
+                table.name,
+                table.timestamp
+            ))?
+            .query_map(rusqlite::params![cutoff], |row| {
+                let values = (0..names.len()).map(|i| row.get(i + 2)).collect::<Result<_, _>>()?;
+                Ok((row.get(0)?, row.get(1)?, values))
+            })?
+            .collect::<Result<_, _>>()?;
+
+        let mut affected = 0;
+        for (rowid, board_id, values) in rows {
+            if !in_scope(&board_id) {
+                continue;
+            }
+            let scrubbed: Vec<Option<String>> = table
+                .columns
+                .iter()
+                .zip(&values)
+                .map(|((_, kind), value)| value.as_deref().and_then(|v| s.value(*kind, v)))
+                .collect();
+            if scrubbed.iter().all(Option::is_none) {
+                continue;
+            }
+            affected += 1;
+            touched_boards.extend(board_id);
+            if dry_run {
+                continue;
+            }
+            if table.delete {
+                conn.execute(&format!("DELETE FROM {} WHERE rowid = ?1", table.name), [rowid])?;
+                continue;
+            }
+            // OR REPLACE: names are part of some keys, and two names can
+            // collapse into one in remove mode
+            let assignments: Vec<String> = names.iter().enumerate().map(|(i, name)| format!("{} = ?{}", name, i + 2)).collect();
+            let mut params: Vec<rusqlite::types::Value> = vec![rowid.into()];
+            params.extend(scrubbed.into_iter().zip(values).map(|(new, old)| new.or(old).into()));
+            conn.execute(
+                &format!("UPDATE OR REPLACE {} SET {} WHERE rowid = ?1", table.name, assignments.join(", ")),
+                rusqlite::params_from_iter(params),
+            )?;
+        }
+        if affected > 0 {
+            tables.insert(table.name.to_string(), affected);
+        }
+    }
+
+    let count = |name: &str| tables.get(name).copied().unwrap_or(0);
+    Ok(ScrubReport {
+        dry_run,
+        mode: String::new(),
+        cutoff: cutoff.to_string(),
+        boards_affected: touched_boards.len() as i64,
+        tasks_affected: count("tasks"),
+        events_affected: count("task_events"),
+        dependencies_affected: count("task_dependencies"),
+        webhooks_affected: count("webhooks"),
+        actor_names: s.actor_names,
+        emails: s.emails,
+        ip_addresses: s.ip_addresses,
+        tables,
+    })
+}
+
+// ============ Bulk Board Settings ============
+
+/// Push the same settings to every board in a workspace — the boards carrying
+/// its tag — in one transaction, with a per-board report. Each changed board
+/// gets a `board.updated` event; a dry run writes nothing.
+#[post("/workspaces/<workspace>/apply-settings", format = "json", data = "<req>")]
+pub async fn apply_workspace_settings(
+    workspace: &str,
+    req: Json<ApplySettingsRequest>,
+    _admin: AdminToken,
+    actor: Actor,
+    bus: &State<EventBus>,
+    db: &State<DbPool>,
+) -> Result<Json<ApplySettingsReport>, (Status, Json<ApiError>)> {
+    let req = req.into_inner();
+    let validation = |error: &str| {
+        (
+            Status::BadRequest,
+            Json(ApiError {
+                error: error.to_string(),
+                code: "VALIDATION_ERROR".to_string(),
+                status: 400,
+            }),
+        )
+    };
+    let st = &req.settings;
+    if st.require_display_name.is_none()
+        && st.enforce_dependencies.is_none()
+        && st.quick_done_auto_archive.is_none()
+        && st.archived_task_retention_days.is_none()
+        && st.event_retention_days.is_none()
+        && st.wip_limits.as_ref().is_none_or(|w| w.is_empty())
+    {
+        return Err(validation("settings must include at least one setting"));
+    }
+    if st.wip_limits.iter().flatten().any(|(_, limit)| limit.is_some_and(|l| l < 1)) {
+        return Err(validation("wip_limits must be at least 1 (use null to remove a limit)"));
+    }
+    if [st.archived_task_retention_days, st.event_retention_days].iter().flatten().any(|&days| days < 0) {
+        return Err(validation("retention days must be 0 (keep forever) or a positive number of days"));
+    }
+
+    let (workspace, bus) = (workspace.trim().to_lowercase(), bus.inner().clone());
+    with_tx(db, move |conn| {
+        let in_directory: bool = conn
+            .query_row("SELECT EXISTS(SELECT 1 FROM tags WHERE name = ?1)", rusqlite::params![workspace], |row| row.get(0))
+            .map_err(|e| db_error(&e.to_string()))?;
+        if !in_directory {
+            return Err(not_found("Workspace"));
+        }
+        let board_ids: Vec<String> = conn
+            .prepare("SELECT b.id FROM board_tags t JOIN boards b ON b.id = t.board_id WHERE t.tag = ?1 ORDER BY b.name, b.id")
+            .and_then(|mut stmt| stmt.query_map(rusqlite::params![workspace], |row| row.get(0))?.collect())
+            .map_err(|e| db_error(&e.to_string()))?;
+        let mut results = Vec::new();
+        for board_id in &board_ids {
+            let result = apply_settings(conn, board_id, &req.settings, req.dry_run).map_err(|e| db_error(&e.to_string()))?;
+            if !req.dry_run && result.status == "updated" {
+                emit_settings_changed(conn, &bus, &result, actor.name()).map_err(|e| db_error(&e.to_string()))?;
+            }
+            results.push(result);
+        }
+
+        Ok(Json(ApplySettingsReport {
+            dry_run: req.dry_run,
+            boards_updated: results.iter().filter(|r| r.status == "updated").count(),
+            results,
+        }))
+    })
+    .await
+}
+
+/// `board.updated` for a board the bulk apply changed, shaped like the one
+/// `PATCH /boards/{id}` sends.
+fn emit_settings_changed(conn: &Connection, bus: &EventBus, result: &BoardSettingsResult, actor: &str) -> rusqlite::Result<()> {
+    let mut fields: Vec<&str> = Vec::new();
+    for field in result.changes.iter().filter_map(|change| change.split([':', '[']).next()) {
+        if !fields.contains(&field) {
+            fields.push(field);
+        }
+    }
+    let (name, is_public, visibility): (String, bool, String) = conn.query_row(
+        "SELECT name, is_public, visibility FROM boards WHERE id = ?1",
+        rusqlite::params![result.board_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?;
+    crate::board_events::emit(
+        conn,
+        bus,
+        &result.board_id,
+        crate::event_types::BOARD_UPDATED,
+        actor,
+        serde_json::json!({"fields": fields, "name": name, "is_public": is_public, "visibility": visibility}),
+    )
+}
+
+fn apply_settings(
+    conn: &Connection,
+    board_id: &str,
+    st: &BulkBoardSettings,
+    dry_run: bool,
+) -> Result<BoardSettingsResult, rusqlite::Error> {
+    let mut result = BoardSettingsResult {
+        board_id: board_id.to_string(),
+        status: String::new(),
+        changes: Vec::new(),
+        skipped: Vec::new(),
+    };
+    let (current, current_retention) = conn.query_row(
+        "SELECT require_display_name, enforce_dependencies, quick_done_auto_archive,
+                archived_task_retention_days, event_retention_days
+         FROM boards WHERE id = ?1",
+        rusqlite::params![board_id],
+        |row| {
+            Ok((
+                [row.get::<_, i32>(0)? == 1, row.get::<_, i32>(1)? == 1, row.get::<_, i32>(2)? == 1],
+                [row.get::<_, Option<i64>>(3)?, row.get::<_, Option<i64>>(4)?],
+            ))
+        },
+    )?;
+
+    // Column names are fixed strings, never user input
+    let flags = [
+        ("require_display_name", st.require_display_name),
+        ("enforce_dependencies", st.enforce_dependencies),
+        ("quick_done_auto_archive", st.quick_done_auto_archive),
+    ];
+    for ((column, wanted), was) in flags.into_iter().zip(current) {
+        let Some(wanted) = wanted else { continue };
+        if wanted == was {
+            continue;
+        }
+        result.changes.push(format!("{}: {} → {}", column, was, wanted));
+        if !dry_run {
+            conn.execute(
+                &format!("UPDATE boards SET {} = ?1, updated_at = datetime('now') WHERE id = ?2", column),
+                rusqlite::params![wanted as i32, board_id],
+            )?;
+        }
+    }
+
+    // Retention: 0 clears the policy, stored as NULL like PATCH /boards/{id}
+    let retention = [
+        ("archived_task_retention_days", st.archived_task_retention_days),
+        ("event_retention_days", st.event_retention_days),
+    ];
+    for ((column, wanted), was) in retention.into_iter().zip(current_retention) {
+        let Some(days) = wanted else { continue };
+        let wanted = Some(days).filter(|&d| d > 0);
+        if wanted == was {
+            continue;
+        }
+        let show = |d: Option<i64>| d.map_or("forever".to_string(), |d| d.to_string());
+        result.changes.push(format!("{}: {} → {}", column, show(was), show(wanted)));
+        if !dry_run {
+            conn.execute(
+                &format!("UPDATE boards SET {} = ?1, updated_at = datetime('now') WHERE id = ?2", column),
+                rusqlite::params![wanted, board_id],
+            )?;
+        }
+    }
+
+    if let Some(limits) = &st.wip_limits {
+        let columns: Vec<(String, String, Option<i32>)> = conn
+            .prepare("SELECT id, name, wip_limit FROM columns WHERE board_id = ?1")?
+            .query_map(rusqlite::params![board_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<_, _>>()?;
+        let mut names: Vec<&String> = limits.keys().collect();
+        names.sort();
+        for name in names {
+            let wanted = limits[name];
+            let matching: Vec<&(String, String, Option<i32>)> =
+                columns.iter().filter(|(_, n, _)| n.eq_ignore_ascii_case(name.trim())).collect();
+            if matching.is_empty() {
+                result.skipped.push(format!("wip_limits.{}: no such column", name));
+                continue;
+            }
+            for (col_id, col_name, was) in matching {
+                if *was == wanted {
+                    continue;
+                }
+                let show = |l: Option<i32>| l.map_or("none".to_string(), |l| l.to_string());
+                result.changes.push(format!("wip_limit[{}]: {} → {}", col_name, show(*was), show(wanted)));
+                if !dry_run {
+                    conn.execute(
+                        "UPDATE columns SET wip_limit = ?1 WHERE id = ?2",
+                        rusqlite::params![wanted, col_id],
+                    )?;
+                }
+            }
+        }
+    }
+
+    result.status = if result.changes.is_empty() { "unchanged" } else { "updated" }.to_string();
+    Ok(result)
+}
+
+// ============ Boards ============
+
+/// Largest page `GET /admin/boards` returns.
+const MAX_BOARDS_PAGE: u32 = 500;
+
+/// Every board on the instance — unlisted and private included — newest
+/// first. Filter by `visibility` or `archived`; page with `limit`/`offset`.
+#[get("/admin/boards?<visibility>&<archived>&<limit>&<offset>")]
+pub async fn list_all_boards(
+    visibility: Option<&str>,
+    archived: Option<bool>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    _admin: AdminToken,
+    db: &State<DbPool>,
+) -> Result<Json<Vec<AdminBoardSummary>>, (Status, Json<ApiError>)> {
+    let visibility = visibility
+        .map(|v| {
+            Visibility::parse(v).ok_or_else(|| {
+                (
+                    Status::BadRequest,
+                    Json(ApiError {
+                        error: "visibility must be public, unlisted or private".to_string(),
+                        code: "INVALID_INPUT".to_string(),
+                        status: 400,
+                    }),
+                )
+            })
+        })
+        .transpose()?;
+    let limit = limit.unwrap_or(100).clamp(1, MAX_BOARDS_PAGE);
+    with_db(db, move |conn| {
+        let mut stmt = conn
+            .prepare(
+                "SELECT b.id, b.name, b.visibility, b.archived, b.created_by, b.created_at, b.updated_at,
+                        (SELECT COUNT(*) FROM tasks t WHERE t.board_id = b.id),
+                        (SELECT COUNT(*) FROM board_members k WHERE k.board_id = b.id)
+                        + (SELECT COUNT(*) FROM share_keys k WHERE k.board_id = b.id)
+                        + (SELECT COUNT(*) FROM scoped_tokens k WHERE k.board_id = b.id)
+                        + (SELECT COUNT(*) FROM agent_keys k WHERE k.board_id = b.id)
+                 FROM boards b
+                 WHERE (?1 IS NULL OR b.visibility = ?1) AND (?2 IS NULL OR b.archived = ?2)
+                 ORDER BY b.created_at DESC, b.rowid DESC
+                 LIMIT ?3 OFFSET ?4",
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        let boards = stmt
+            .query_map(
+                rusqlite::params![visibility.map(Visibility::as_str), archived, limit, offset.unwrap_or(0)],
+                |row| {
+                    let visibility: String = row.get(2)?;
+                    Ok(AdminBoardSummary {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        visibility: Visibility::parse(&visibility).unwrap_or(Visibility::Public),
+                        archived: row.get::<_, i32>(3)? == 1,
+                        created_by: row.get(4)?,
+                        created_at: row.get(5)?,
+                        updated_at: row.get(6)?,
+                        task_count: row.get(7)?,
+                        key_count: row.get(8)?,
+                    })
+                },
+            )
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(Json(boards))
+    })
+    .await
+}
+
+/// Unarchive any board without its manage key.
+#[post("/admin/boards/<board_id>/unarchive")]
+pub async fn force_unarchive_board(
+    board_id: &str,
+    _admin: AdminToken,
+    actor: Actor,
+    bus: &State<EventBus>,
+    db: &State<DbPool>,
+) -> Result<Json<BoardResponse>, (Status, Json<ApiError>)> {
+    let (board_id, bus) = (board_id.to_owned(), bus.inner().clone());
+    with_tx(db, move |conn| {
+        let (board_id, bus) = (board_id.as_str(), &bus);
+        access::require_board_exists(conn, board_id)?;
+        routes::unarchive_board_row(conn, bus, board_id, actor.name_or("admin"))
+    })
+    .await
+}
+
+/// Permanently delete any board (spam, abuse) without its manage key. Lifecycle
+/// hooks still get `board.deleted`.
+#[delete("/admin/boards/<board_id>")]
+pub async fn force_delete_board(
+    board_id: &str,
+    _admin: AdminToken,
+    actor: Actor,
+    hooks: &State<LifecycleHooks>,
+    db: &State<DbPool>,
+) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
+    let board = board_id.to_owned();
+    let (response, snapshot) = with_db(db, move |conn| {
+        let board_id = board.as_str();
+        access::require_board_exists(conn, board_id)?;
+        let name: String = conn
+            .query_row("SELECT name FROM boards WHERE id = ?1", rusqlite::params![board_id], |row| row.get(0))
+            .map_err(|e| db_error(&e.to_string()))?;
+        routes::purge_board(conn, board_id, &name)
+    })
+    .await?;
+
+    hooks.send(lifecycle::BOARD_DELETED, board_id, snapshot, Some(actor.name_or("admin")));
+    Ok(Json(response))
+}
+
+// ============ Instance Stats ============
+
+/// How much the instance holds: boards by visibility, tasks by state, webhooks
+/// and handed-out keys, and what was created in the last day.
+#[get("/admin/stats")]
+pub async fn instance_stats(_admin: AdminToken, db: &State<DbPool>) -> Result<Json<InstanceStatsResponse>, (Status, Json<ApiError>)> {
+    with_db(db, move |conn| {
+        conn.query_row(
+            "SELECT
+                (SELECT COUNT(*) FROM boards),
+                (SELECT COUNT(*) FROM boards WHERE visibility = 'public'),
+                (SELECT COUNT(*) FROM boards WHERE visibility = 'unlisted'),
+                (SELECT COUNT(*) FROM boards WHERE visibility = 'private'),
+                (SELECT COUNT(*) FROM boards WHERE archived = 1),
+                (SELECT COUNT(*) FROM tasks),
+                (SELECT COUNT(*) FROM tasks WHERE completed_at IS NULL AND archived_at IS NULL),
+                (SELECT COUNT(*) FROM tasks WHERE completed_at IS NOT NULL),
+                (SELECT COUNT(*) FROM tasks WHERE archived_at IS NOT NULL),
+                (SELECT COUNT(*) FROM task_trash),
+                (SELECT COUNT(*) FROM webhooks),
+                (SELECT COUNT(*) FROM board_members) + (SELECT COUNT(*) FROM share_keys)
+                    + (SELECT COUNT(*) FROM scoped_tokens) + (SELECT COUNT(*) FROM agent_keys),
+                (SELECT COUNT(*) FROM boards WHERE created_at >= datetime('now', '-1 day')),
+                (SELECT COUNT(*) FROM tasks WHERE created_at >= datetime('now', '-1 day'))",
+            [],
+            |row| {
+                Ok(InstanceStatsResponse {
+                    boards: InstanceBoardStats {
+                        total: row.get(0)?,
+                        public: row.get(1)?,
+                        unlisted: row.get(2)?,
+                        private: row.get(3)?,
+                        archived: row.get(4)?,
+                    },
+                    tasks: InstanceTaskStats {
+                        total: row.get(5)?,
+                        open: row.get(6)?,
+                        completed: row.get(7)?,
+                        archived: row.get(8)?,
+                        in_trash: row.get(9)?,
+                    },
+                    webhooks: row.get(10)?,
+                    collaborator_keys: row.get(11)?,
+                    boards_created_24h: row.get(12)?,
+                    tasks_created_24h: row.get(13)?,
+                })
+            },
+        )
+        .map(Json)
+        .map_err(|e| db_error(&e.to_string()))
+    })
+    .await
+}
+
+// ============ Rate Limits ============
+
+/// Each route group's rate limit settings and which clients are using it.
+#[get("/admin/rate-limits")]
+pub fn rate_limits(_admin: AdminToken, limits: &State<RateLimits>) -> Json<RateLimitsResponse> {
+    Json(RateLimitsResponse {
+        board_creation: limits.stats(RouteGroup::BoardCreation),
+        task_writes: limits.stats(RouteGroup::TaskWrites),
+        comments: limits.stats(RouteGroup::Comments),
+        batch: limits.stats(RouteGroup::Batch),
+        view_session: limits.stats(RouteGroup::ViewSession),
+    })
+}
+
+// ============ Slow Queries ============
+
+/// Recent slow statements with the route that ran them, newest first.
+#[get("/admin/slow-queries")]
+pub fn slow_queries(_admin: AdminToken, guard: &State<QueryGuard>) -> Json<SlowQueriesResponse> {
+    Json(guard.slow_queries())
+}
+
+// ============ Event Bus ============
+
+/// Event buffer size and, per board, events emitted and missed by lagging
+/// subscribers — for sizing `EVENT_BUFFER_SIZE`.
+#[get("/admin/event-stats")]
+pub fn event_stats(_admin: AdminToken, bus: &State<EventBus>) -> Json<EventBusStats> {
+    Json(bus.stats())
+}
+
+// ============ Tag Directory ============
+
+/// Add a tag to the instance-wide directory, or update its description.
+#[post("/admin/tags", format = "json", data = "<req>")]
+pub async fn upsert_tag(
+    req: Json<UpsertTagRequest>,
+    _admin: AdminToken,
+    db: &State<DbPool>,
+) -> Result<Json<TagResponse>, (Status, Json<ApiError>)> {
+    let name = tags::normalize_tag(&req.name)?;
+    with_db(db, move |conn| {
+        conn.execute(
+            "INSERT INTO tags (name, description) VALUES (?1, ?2)
+             ON CONFLICT(name) DO UPDATE SET description = excluded.description",
+            rusqlite::params![name, req.description.trim()],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        tags::load_tag(conn, &name).map(Json).map_err(|e| db_error(&e.to_string()))
+    })
+    .await
+}
+
+/// Remove a tag from the directory and from every board carrying it.
+#[delete("/admin/tags/<name>")]
+pub async fn delete_tag(
+    name: &str,
+    _admin: AdminToken,
+    db: &State<DbPool>,
+) -> Result<Json<DeleteTagResponse>, (Status, Json<ApiError>)> {
+    let name = name.trim().to_lowercase();
+    with_db(db, move |conn| {
+        let tx = conn.unchecked_transaction().map_err(|e| db_error(&e.to_string()))?;
+        let boards_untagged = tx
+            .execute("DELETE FROM board_tags WHERE tag = ?1", rusqlite::params![name])
+            .map_err(|e| db_error(&e.to_string()))?;
+        let deleted = tx
+            .execute("DELETE FROM tags WHERE name = ?1", rusqlite::params![name])
+            .map_err(|e| db_error(&e.to_string()))?;
+        if deleted == 0 {
+            return Err(not_found("Tag"));
+        }
+        tx.commit().map_err(|e| db_error(&e.to_string()))?;
+        Ok(Json(DeleteTagResponse {
+            deleted: true,
+            name,
+            boards_untagged,
+        }))
+    })
+    .await
+}
+
+// ============ Database Stats ============
+
+/// SQLite health for diagnosing stalls: file and WAL sizes, pool usage,
+/// lock waits and page-cache hit ratio. Counters run since startup.
+#[get("/admin/db-stats")]
+pub async fn db_stats(_admin: AdminToken, db: &State<DbPool>) -> Result<Json<DbStatsResponse>, (Status, Json<ApiError>)> {
+    let storage = with_db(db, move |conn| storage_stats(conn).map_err(|e| db_error(&e.to_string()))).await?;
+    let stats = &DB_STATS;
+    let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+    let ms = |us: u64| us as f64 / 1000.0;
+    let avg_ms = |total_us: u64, count: u64| if count == 0 { 0.0 } else { ms(total_us) / count as f64 };
+
+    let state = db.state();
+    let checkouts = load(&stats.checkouts);
+    let (hits, misses) = (load(&stats.cache_hits), load(&stats.cache_misses));
+    Ok(Json(DbStatsResponse {
+        busy_timeout_ms: stats.busy_timeout().as_millis() as u64,
+        storage,
+        pool: DbPoolStats {
+            max_size: db.max_size(),
+            connections: state.connections,
+            idle_connections: state.idle_connections,
+            checkouts,
+            avg_checkout_wait_ms: avg_ms(load(&stats.checkout_wait_total_us), checkouts),
+            max_checkout_wait_ms: ms(load(&stats.checkout_wait_max_us)),
+            avg_hold_ms: avg_ms(load(&stats.hold_total_us), checkouts),
+            longest_hold_ms: ms(load(&stats.hold_max_us)),
+        },
+        locks: DbLockStats {
+            busy_waits: load(&stats.busy_waits),
+            busy_timeouts: load(&stats.busy_timeouts),
+            total_busy_wait_ms: ms(load(&stats.busy_wait_total_us)),
+            longest_busy_wait_ms: ms(load(&stats.busy_wait_max_us)),
+        },
+        cache: DbCacheStats {
+            hits,
+            misses,
+            hit_ratio: (hits + misses > 0).then(|| hits as f64 / (hits + misses) as f64),
+        },
+    }))
+}
+
+// ============ Client Stats ============
+
+/// Which clients call the API and how often they fetch `llms.txt` and
+/// `openapi.json`, since startup.
+#[get("/admin/client-stats")]
+pub fn client_stats(_admin: AdminToken, usage: &State<ClientUsage>) -> Json<ClientStatsResponse> {
+    Json(usage.stats())
+}
+
+fn storage_stats(conn: &Connection) -> rusqlite::Result<DbStorageStats> {
+    let pragma = |name: &str| conn.query_row(&format!("PRAGMA {}", name), [], |row| row.get::<_, i64>(0));
+    let journal_mode: String = conn.query_row("PRAGMA journal_mode", [], |row| row.get(0))?;
+    // Empty for in-memory databases
+    let path: String = conn.query_row("SELECT file FROM pragma_database_list WHERE name = 'main'", [], |row| row.get(0))?;
+    let file_size = |p: &str| std::fs::metadata(p).map(|m| m.len()).unwrap_or(0);
+    Ok(DbStorageStats {
+        journal_mode,
+        page_size: pragma("page_size")?,
+        page_count: pragma("page_count")?,
+        freelist_count: pragma("freelist_count")?,
+        database_bytes: if path.is_empty() { 0 } else { file_size(&path) },
+        wal_bytes: if path.is_empty() { 0 } else { file_size(&format!("{}-wal", path)) },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scrubber(targets: Option<&[&str]>) -> Scrubber {
+        Scrubber {
+            mode: ScrubMode::Pseudonymize,
+            salt: "test-salt".to_string(),
+            targets: targets.map(|t| t.iter().map(|n| n.to_string()).collect()),
+            actor_names: 0,
+            emails: 0,
+            ip_addresses: 0,
+        }
+    }
+
+    #[test]
+    fn test_text_redacts_emails_and_ips() {
+        let mut s = scrubber(None);
+        let out = s.text("Ping (alice@example.com) from 10.0.0.1:8080, or [::1].").unwrap();
+        assert_eq!(out, "Ping ([email]) from [ip], or [[ip]].");
+        assert_eq!(s.emails, 1);
+        assert_eq!(s.ip_addresses, 2);
+        assert!(s.text("version 1.2 shipped @ noon").is_none());
+    }
+
+    #[test]
+    fn test_actor_pseudonyms_are_stable_and_targeted() {
+        let mut s = scrubber(Some(&["alice"]));
+        let a = s.actor("Alice").unwrap();
+        assert!(a.starts_with("anon-"));
+        assert_eq!(s.actor("alice"), Some(a.clone()));
+        assert!(s.actor("bob").is_none());
+        assert!(s.actor(&a).is_none());
+        assert_eq!(s.text("thanks @alice!"), Some(format!("thanks @{}!", a)));
+    }
+
+    #[test]
+    fn test_transcript_headers_and_messages() {
+        let mut s = scrubber(Some(&["alice"]));
+        let transcript = "[2026-01-01 10:00:00] alice:\nsee 10.0.0.1\n[2026-01-01 10:05:00] bob:\nok @alice\n";
+        let out = s.transcript(transcript).unwrap();
+        let a = s.actor("alice").unwrap();
+        assert_eq!(out, format!("[2026-01-01 10:00:00] {}:\nsee [ip]\n[2026-01-01 10:05:00] bob:\nok @{}\n", a, a));
+        assert!(s.transcript(&out).is_none());
+    }
+
+    #[test]
+    fn test_scrub_sign_in_identities() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::migrate(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO users (id, issuer, subject, email, name) VALUES
+                 ('u1', 'https://idp', 'sub-1', 'alice@example.com', 'Alice'),
+                 ('u2', 'https://idp', 'sub-2', 'bob@example.com', 'Bob');
+             INSERT INTO user_sessions (token_hash, user_id, expires_at) VALUES
+                 ('s1', 'u1', datetime('now', '+1 day')), ('s2', 'u1', datetime('now', '+1 day')),
+                 ('s3', 'u2', datetime('now', '+1 day'));",
+        )
+        .unwrap();
+        let remaining = |table: &str| -> Vec<String> {
+            let key = if table == "users" { "id" } else { "token_hash" };
+            conn.prepare(&format!("SELECT {} FROM {} ORDER BY 1", key, table))
+                .unwrap()
+                .query_map([], |row| row.get(0))
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap()
+        };
+
+        // Bob's address isn't Alice's personal data
+        let mut s = scrubber(Some(&["alice"]));
+        let report = run_scrub(&conn, &mut s, None, "9999-12-31", false).unwrap();
+        assert_eq!(report.tables.get("users"), Some(&1));
+        assert_eq!(report.tables.get("user_sessions"), Some(&2));
+        assert_eq!(remaining("users"), vec!["u2"]);
+        assert_eq!(remaining("user_sessions"), vec!["s3"]);
+
+        // An address can be named directly; board-restricted scrubs leave identities alone
+        let mut s = scrubber(Some(&["bob@example.com"]));
+        let report = run_scrub(&conn, &mut s, Some(&HashSet::from(["b1".to_string()])), "9999-12-31", false).unwrap();
+        assert!(report.tables.is_empty());
+        let report = run_scrub(&conn, &mut s, None, "9999-12-31", false).unwrap();
+        assert_eq!(report.tables.get("users"), Some(&1));
+        assert!(remaining("users").is_empty() && remaining("user_sessions").is_empty());
+    }
+
+    #[test]
+    fn test_every_table_is_classified() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::migrate(&conn).unwrap();
+        let tables: Vec<String> = conn
+            .prepare(
+                "SELECT name FROM sqlite_master WHERE type = 'table'
+                 AND name NOT LIKE 'sqlite_%' AND name NOT LIKE 'tasks_fts_%'",
+            )
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        for table in &tables {
+            let listed = PII_TABLES.iter().filter(|t| t.name == table).count() + NO_PII_TABLES.iter().filter(|t| *t == table).count();
+            assert_eq!(listed, 1, "table {} must be listed once in PII_TABLES or NO_PII_TABLES", table);
+        }
+        for table in NO_PII_TABLES {
+            assert!(tables.iter().any(|t| t == table), "NO_PII_TABLES lists unknown table {}", table);
+        }
+        // Every registry query runs against the real schema
+        let mut s = scrubber(None);
+        let report = run_scrub(&conn, &mut s, None, "9999-12-31", true).unwrap();
+        assert!(report.tables.is_empty());
+    }
+}
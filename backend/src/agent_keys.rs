@@ -0,0 +1,151 @@
+//! Agent keys: a key per agent, tied to an `agent_id`. Whatever a request
+//! made with one says about who's acting — `actor_name`, `?actor=`,
+//! `X-Actor` — is ignored, and the write (claims included) is attributed to
+//! the key's agent, so the event history can't be spoofed. Otherwise they
+//! work like collaborator keys, with a role (editor by default).
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::access::{self, BoardRole};
+use crate::auth::{Actor, BoardToken};
+use crate::collaborators::require_can_manage;
+use crate::db::{hash_key, DbPool};
+use crate::models::*;
+use crate::routes::{db_error, not_found, with_db};
+
+const MAX_AGENT_ID_CHARS: usize = 100;
+
+fn invalid(error: String) -> (Status, Json<ApiError>) {
+    (
+        Status::BadRequest,
+        Json(ApiError {
+            error,
+            code: "INVALID_INPUT".to_string(),
+            status: 400,
+        }),
+    )
+}
+
+fn key_from_row(board_id: &str, row: &rusqlite::Row) -> rusqlite::Result<AgentKeyResponse> {
+    let role: String = row.get(2)?;
+    Ok(AgentKeyResponse {
+        id: row.get(0)?,
+        board_id: board_id.to_string(),
+        agent_id: row.get(1)?,
+        role: BoardRole::parse(&role).unwrap_or(BoardRole::Editor),
+        key: None,
+        expires_at: row.get(5)?,
+        created_by: row.get(3)?,
+        created_at: row.get(4)?,
+    })
+}
+
+fn load(conn: &Connection, board_id: &str, key_id: &str) -> Result<AgentKeyResponse, (Status, Json<ApiError>)> {
+    conn.query_row(
+        "SELECT id, agent_id, role, created_by, created_at, expires_at FROM agent_keys WHERE id = ?1 AND board_id = ?2",
+        rusqlite::params![key_id, board_id],
+        |row| key_from_row(board_id, row),
+    )
+    .optional()
+    .map_err(|e| db_error(&e.to_string()))?
+    .ok_or_else(|| not_found("Agent key"))
+}
+
+/// The board's agent keys — requires admin. Keys themselves aren't returned.
+#[get("/boards/<board_id>/agent-keys")]
+pub async fn list_agent_keys(
+    board_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<Vec<AgentKeyResponse>>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, agent_id, role, created_by, created_at, expires_at FROM agent_keys
+                 WHERE board_id = ?1 ORDER BY agent_id ASC, created_at ASC, rowid ASC",
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        let keys = stmt
+            .query_map(rusqlite::params![board_id], |row| key_from_row(board_id, row))
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(Json(keys))
+    })
+    .await
+}
+
+/// Mint a key for an agent — requires admin (owner for admin or owner keys).
+/// The key is in the response only.
+#[post("/boards/<board_id>/agent-keys", format = "json", data = "<req>")]
+pub async fn create_agent_key(
+    board_id: &str,
+    req: Json<CreateAgentKeyRequest>,
+    token: BoardToken,
+    actor: Actor,
+    db: &State<DbPool>,
+) -> Result<Json<AgentKeyResponse>, (Status, Json<ApiError>)> {
+    let req = req.into_inner();
+    let agent_id = req.agent_id.trim().to_owned();
+    if agent_id.is_empty() || agent_id.chars().count() > MAX_AGENT_ID_CHARS {
+        return Err(invalid(format!("agent_id must be 1-{} characters", MAX_AGENT_ID_CHARS)));
+    }
+    if Actor::new(Some(agent_id.as_str())).is_anonymous() {
+        return Err(invalid("agent_id can't be \"anonymous\"".to_string()));
+    }
+    if req.role == BoardRole::Read {
+        return Err(invalid(
+            "Agent keys are for agents that write (viewer or above); create read-only keys with POST /boards/{id}/keys"
+                .to_string(),
+        ));
+    }
+    let expires_at = access::key_expiry(req.expires_at.as_deref(), req.ttl_seconds)?;
+
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        let caller = access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Admin)?;
+        require_can_manage(caller, req.role)?;
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let key = format!("kb_{}", uuid::Uuid::new_v4().to_string().replace('-', ""));
+        conn.execute(
+            "INSERT INTO agent_keys (id, board_id, agent_id, role, key_hash, created_by, expires_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![id, board_id, agent_id, req.role.as_str(), hash_key(&key), actor.name(), expires_at],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        let mut agent_key = load(conn, board_id, &id)?;
+        agent_key.key = Some(key);
+        Ok(Json(agent_key))
+    })
+    .await
+}
+
+/// Revoke an agent key; it stops working at once — requires admin (owner
+/// for admin or owner keys).
+#[delete("/boards/<board_id>/agent-keys/<key_id>")]
+pub async fn revoke_agent_key(
+    board_id: &str,
+    key_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
+    let (board_id, key_id) = (board_id.to_owned(), key_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, key_id) = (board_id.as_str(), key_id.as_str());
+        let caller = access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Admin)?;
+        let existing = load(conn, board_id, key_id)?;
+        require_can_manage(caller, existing.role)?;
+        conn.execute("DELETE FROM agent_keys WHERE id = ?1", rusqlite::params![key_id])
+            .map_err(|e| db_error(&e.to_string()))?;
+        Ok(Json(serde_json::json!({"deleted": true, "id": key_id})))
+    })
+    .await
+}
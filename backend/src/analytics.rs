@@ -0,0 +1,573 @@
+//! Board analytics: event-history reports and assignee suggestions (public)
+//! and view counters (owner-only).
+
+use std::collections::HashMap;
+
+use chrono::{NaiveDateTime, Utc};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+
+use crate::access::{self, BoardRole};
+use crate::auth::BoardToken;
+use crate::db::{hash_key, DbPool};
+use crate::models::*;
+use crate::routes::{db_error, not_found, with_db};
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+// ============ Heatmap ============
+
+/// Activity heatmap: event counts by weekday × hour, overall and per actor.
+/// `days` sets the window (default 30, max 365); `tz_offset` (minutes east of UTC)
+/// shifts buckets into the caller's local time.
+#[get("/boards/<board_id>/analytics/heatmap?<days>&<tz_offset>")]
+pub async fn activity_heatmap(
+    board_id: &str,
+    days: Option<i64>,
+    tz_offset: Option<i64>,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+) -> Result<Json<HeatmapResponse>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_read_access(conn, board_id, token.as_ref())?;
+
+        let days = days.unwrap_or(30).clamp(1, 365);
+        // Real-world offsets span UTC-12:00 to UTC+14:00
+        let tz_offset = tz_offset.unwrap_or(0).clamp(-720, 840);
+        let since = (Utc::now() - chrono::Duration::days(days))
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string();
+        let shift = format!("{:+} minutes", tz_offset);
+
+        // strftime('%w') is 0 = Sunday; remapped to Monday-first below
+        let mut stmt = conn
+            .prepare(
+                "SELECT CAST(strftime('%w', te.created_at, ?3) AS INTEGER),
+                        CAST(strftime('%H', te.created_at, ?3) AS INTEGER),
+                        te.actor, COUNT(*)
+                 FROM task_events te
+                 JOIN tasks t ON t.id = te.task_id
+                 WHERE t.board_id = ?1 AND te.created_at >= ?2
+                 GROUP BY 1, 2, 3",
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        let buckets: Vec<(usize, usize, String, i64)> = stmt
+            .query_map(rusqlite::params![board_id, since, shift], |row| {
+                Ok((
+                    row.get::<_, i64>(0)? as usize,
+                    row.get::<_, i64>(1)? as usize,
+                    row.get(2)?,
+                    row.get(3)?,
+                ))
+            })
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let mut cells = vec![vec![0i64; 24]; 7];
+        let mut actors: HashMap<String, ActorActivity> = HashMap::new();
+        let mut total_events = 0;
+        for (sunday_first, hour, actor, count) in buckets {
+            let weekday = (sunday_first + 6) % 7;
+            let hour = hour.min(23);
+            cells[weekday][hour] += count;
+            total_events += count;
+
+            let entry = actors.entry(actor.clone()).or_insert_with(|| ActorActivity {
+                actor,
+                total: 0,
+                by_weekday: vec![0; 7],
+                by_hour: vec![0; 24],
+            });
+            entry.total += count;
+            entry.by_weekday[weekday] += count;
+            entry.by_hour[hour] += count;
+        }
+
+        let mut by_actor: Vec<ActorActivity> = actors.into_values().collect();
+        by_actor.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.actor.cmp(&b.actor)));
+
+        Ok(Json(HeatmapResponse {
+            board_id: board_id.to_string(),
+            since,
+            days,
+            tz_offset_minutes: tz_offset,
+            total_events,
+            weekdays: WEEKDAYS.iter().map(|d| d.to_string()).collect(),
+            cells,
+            by_actor,
+        }))
+    })
+    .await
+}
+
+// ============ View Counters ============
+
+/// Kinds of views counted in `board_views`.
+pub const VIEW_BOARD_LOAD: &str = "board_load";
+pub const VIEW_SSE_CONNECT: &str = "sse_connect";
+
+/// Bump today's counter for a board view. Best-effort: failures never affect the request.
+pub fn record_view(conn: &rusqlite::Connection, board_id: &str, kind: &str) {
+    let _ = conn.execute(
+        "INSERT INTO board_views (board_id, day, kind, count) VALUES (?1, date('now'), ?2, 1)
+         ON CONFLICT(board_id, day, kind) DO UPDATE SET count = count + 1",
+        rusqlite::params![board_id, kind],
+    );
+}
+
+/// Daily view counts (board loads, SSE connects) — requires manage key.
+#[get("/boards/<board_id>/views?<days>")]
+pub async fn board_views(
+    board_id: &str,
+    days: Option<i64>,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<BoardViewsResponse>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_board_exists(conn, board_id)?;
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Read)?;
+
+        let days = days.unwrap_or(30).clamp(1, 365);
+        let mut stmt = conn
+            .prepare(
+                "SELECT day, kind, count FROM board_views
+                 WHERE board_id = ?1 AND day > date('now', ?2)
+                 ORDER BY day ASC",
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        let rows: Vec<(String, String, i64)> = stmt
+            .query_map(rusqlite::params![board_id, format!("-{} days", days)], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let mut totals = ViewCounts::default();
+        let mut daily: Vec<DailyViews> = Vec::new();
+        for (day, kind, count) in rows {
+            if daily.last().map(|d| d.day != day).unwrap_or(true) {
+                daily.push(DailyViews {
+                    day,
+                    board_loads: 0,
+                    sse_connects: 0,
+                });
+            }
+            let entry = daily.last_mut().unwrap();
+            match kind.as_str() {
+                VIEW_BOARD_LOAD => {
+                    entry.board_loads += count;
+                    totals.board_loads += count;
+                }
+                VIEW_SSE_CONNECT => {
+                    entry.sse_connects += count;
+                    totals.sse_connects += count;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Json(BoardViewsResponse {
+            board_id: board_id.to_string(),
+            days,
+            totals,
+            daily,
+        }))
+    })
+    .await
+}
+
+// ============ Aging ============
+
+/// Parse an event/task timestamp (SQLite `datetime('now')` format or RFC 3339).
+fn parse_ts(s: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .or_else(|| chrono::DateTime::parse_from_rfc3339(s).ok().map(|d| d.naive_utc()))
+}
+
+fn hours_between(from: NaiveDateTime, to: NaiveDateTime) -> f64 {
+    let hours = (to - from).num_seconds().max(0) as f64 / 3600.0;
+    (hours * 100.0).round() / 100.0
+}
+
+/// Nearest-rank percentile over an ascending slice.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Column the task landed in for a given event, if the event changed columns.
+fn column_entered(event_type: &str, data: &serde_json::Value) -> Option<String> {
+    let key = match event_type {
+        "created" | "updated" | "reordered" => "column_id",
+        "moved" => "to",
+        _ => return None,
+    };
+    data.get(key).and_then(|v| v.as_str()).map(|s| s.to_string())
+}
+
+/// (id, column_id, title, assigned_to, claimed_by, created_at)
+type OpenTaskRow = (String, String, String, Option<String>, Option<String>, String);
+
+/// Aging WIP report: each open task's time in its current column, plus
+/// percentile bands of how long tasks historically stayed in each column.
+#[get("/boards/<board_id>/analytics/aging")]
+pub async fn aging_report(
+    board_id: &str,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+) -> Result<Json<AgingResponse>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        let now = Utc::now().naive_utc();
+
+        // Column-entry history per task, in event order
+        let mut stmt = conn
+            .prepare(
+                "SELECT te.task_id, te.event_type, te.data, te.created_at
+                 FROM task_events te
+                 JOIN tasks t ON t.id = te.task_id
+                 WHERE t.board_id = ?1 AND te.event_type IN ('created', 'moved', 'updated', 'reordered')
+                 ORDER BY te.task_id, te.seq",
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        let rows: Vec<(String, String, String, String)> = stmt
+            .query_map(rusqlite::params![board_id], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let mut entries: HashMap<String, Vec<(String, NaiveDateTime)>> = HashMap::new();
+        for (task_id, event_type, data, created_at) in rows {
+            let data: serde_json::Value = serde_json::from_str(&data).unwrap_or_default();
+            let (Some(column), Some(at)) = (column_entered(&event_type, &data), parse_ts(&created_at)) else {
+                continue;
+            };
+            let history = entries.entry(task_id).or_default();
+            if history.last().map(|(c, _)| c != &column).unwrap_or(true) {
+                history.push((column, at));
+            }
+        }
+
+        // Completed stints: time between entering a column and entering the next one
+        let mut stints: HashMap<String, Vec<f64>> = HashMap::new();
+        for history in entries.values() {
+            for pair in history.windows(2) {
+                stints
+                    .entry(pair[0].0.clone())
+                    .or_default()
+                    .push(hours_between(pair[0].1, pair[1].1));
+            }
+        }
+
+        let mut columns: Vec<ColumnAging> = conn
+            .prepare("SELECT id, name, position FROM columns WHERE board_id = ?1 ORDER BY position")
+            .map_err(|e| db_error(&e.to_string()))?
+            .query_map(rusqlite::params![board_id], |row| {
+                Ok(ColumnAging {
+                    column_id: row.get(0)?,
+                    name: row.get(1)?,
+                    position: row.get(2)?,
+                    sample_size: 0,
+                    percentiles: None,
+                    tasks: Vec::new(),
+                })
+            })
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        for col in columns.iter_mut() {
+            if let Some(samples) = stints.get_mut(&col.column_id) {
+                samples.sort_by(|a, b| a.total_cmp(b));
+                col.sample_size = samples.len();
+                col.percentiles = Some(AgingPercentiles {
+                    p50: percentile(samples, 50.0),
+                    p70: percentile(samples, 70.0),
+                    p85: percentile(samples, 85.0),
+                    p95: percentile(samples, 95.0),
+                });
+            }
+        }
+
+        // Open tasks = not completed, not archived
+        let open: Vec<OpenTaskRow> = conn
+            .prepare(
+                "SELECT id, column_id, title, assigned_to, claimed_by, created_at FROM tasks
+                 WHERE board_id = ?1 AND completed_at IS NULL AND archived_at IS NULL",
+            )
+            .map_err(|e| db_error(&e.to_string()))?
+            .query_map(rusqlite::params![board_id], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?))
+            })
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        for (id, column_id, title, assigned_to, claimed_by, created_at) in open {
+            let Some(col) = columns.iter_mut().find(|c| c.column_id == column_id) else {
+                continue;
+            };
+            // Tasks predating event history fall back to their creation time
+            let entered = entries
+                .get(&id)
+                .and_then(|h| h.last())
+                .filter(|(c, _)| c == &column_id)
+                .map(|(_, at)| *at)
+                .or_else(|| parse_ts(&created_at))
+                .unwrap_or(now);
+            let age_hours = hours_between(entered, now);
+            let band = col.percentiles.as_ref().map(|p| {
+                if age_hours <= p.p50 {
+                    "p50"
+                } else if age_hours <= p.p70 {
+                    "p70"
+                } else if age_hours <= p.p85 {
+                    "p85"
+                } else if age_hours <= p.p95 {
+                    "p95"
+                } else {
+                    "above_p95"
+                }
+                .to_string()
+            });
+            col.tasks.push(AgingTask {
+                id,
+                title,
+                assigned_to,
+                claimed_by,
+                entered_at: entered.format("%Y-%m-%d %H:%M:%S").to_string(),
+                age_hours,
+                band,
+            });
+        }
+        for col in columns.iter_mut() {
+            col.tasks.sort_by(|a, b| b.age_hours.total_cmp(&a.age_hours));
+        }
+
+        Ok(Json(AgingResponse {
+            board_id: board_id.to_string(),
+            generated_at: now.format("%Y-%m-%d %H:%M:%S").to_string(),
+            columns,
+        }))
+    })
+    .await
+}
+
+// ============ Assignee Suggestions ============
+
+/// Weight of one move out of the task's column, relative to one completed
+/// task carrying all of this task's labels.
+const COLUMN_MOVE_WEIGHT: f64 = 0.5;
+/// Weight of any completion, so actors without label or column history still rank.
+const COMPLETION_WEIGHT: f64 = 0.1;
+/// Names that never receive suggestions.
+const SYSTEM_ACTORS: &[&str] = &["anonymous", "auto-assign"];
+
+#[derive(Default)]
+struct Affinity {
+    actor: String,
+    label_score: f64,
+    label_matches: i64,
+    matched_labels: Vec<String>,
+    column_moves: i64,
+    completed: i64,
+}
+
+/// The entry for an actor (case-insensitive); None for blank and system names.
+fn affinity<'a>(actors: &'a mut HashMap<String, Affinity>, name: &str) -> Option<&'a mut Affinity> {
+    let name = name.trim();
+    if name.is_empty() || SYSTEM_ACTORS.iter().any(|s| s.eq_ignore_ascii_case(name)) {
+        return None;
+    }
+    Some(actors.entry(name.to_lowercase()).or_insert_with(|| Affinity {
+        actor: name.to_string(),
+        ..Default::default()
+    }))
+}
+
+/// The labels of `wanted` that also appear in `labels` (case-insensitive).
+fn shared_labels<'a>(wanted: &'a [String], labels: &[String]) -> Vec<&'a String> {
+    wanted
+        .iter()
+        .filter(|w| labels.iter().any(|l| l.eq_ignore_ascii_case(w)))
+        .collect()
+}
+
+/// Rank actors to take a task by how often they completed tasks with its
+/// labels and moved work out of its column, from the event history — public.
+/// `days` sets the window (default 90, max 365); `limit` caps the list
+/// (default 5, max 20).
+#[get("/boards/<board_id>/tasks/<task_id>/suggest-assignee?<days>&<limit>")]
+pub async fn suggest_assignee(
+    board_id: &str,
+    task_id: &str,
+    days: Option<i64>,
+    limit: Option<usize>,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+) -> Result<Json<AssigneeSuggestionsResponse>, (Status, Json<ApiError>)> {
+    let (board_id, task_id) = (board_id.to_owned(), task_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, task_id) = (board_id.as_str(), task_id.as_str());
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        let (labels, column_id, column_name): (String, String, String) = conn
+            .query_row(
+                "SELECT t.labels, t.column_id, c.name FROM tasks t JOIN columns c ON c.id = t.column_id
+                 WHERE t.id = ?1 AND t.board_id = ?2",
+                rusqlite::params![task_id, board_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .map_err(|_| not_found("Task"))?;
+        let labels: Vec<String> = serde_json::from_str(&labels).unwrap_or_default();
+        let days = days.unwrap_or(90).clamp(1, 365);
+        let limit = limit.unwrap_or(5).clamp(1, 20);
+        let since = (Utc::now() - chrono::Duration::days(days))
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string();
+
+        // Whoever last moved a completed task finished it; tasks created
+        // straight into the done column fall back to their assignee
+        let completed: Vec<(String, Option<String>)> = conn
+            .prepare(
+                "SELECT t.labels,
+                        COALESCE((SELECT te.actor FROM task_events te
+                                  WHERE te.task_id = t.id AND te.event_type = 'moved'
+                                  ORDER BY te.created_at DESC, te.seq DESC LIMIT 1), t.assigned_to)
+                 FROM tasks t
+                 WHERE t.board_id = ?1 AND t.id != ?2 AND t.completed_at IS NOT NULL AND t.completed_at >= ?3",
+            )
+            .map_err(|e| db_error(&e.to_string()))?
+            .query_map(rusqlite::params![board_id, task_id, since], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let mut actors: HashMap<String, Affinity> = HashMap::new();
+        for (task_labels, completer) in &completed {
+            let Some(entry) = completer.as_deref().and_then(|name| affinity(&mut actors, name)) else {
+                continue;
+            };
+            entry.completed += 1;
+            let task_labels: Vec<String> = serde_json::from_str(task_labels).unwrap_or_default();
+            let shared = shared_labels(&labels, &task_labels);
+            if !shared.is_empty() {
+                entry.label_matches += 1;
+                entry.label_score += shared.len() as f64 / labels.len() as f64;
+                for label in shared {
+                    if !entry.matched_labels.contains(label) {
+                        entry.matched_labels.push(label.clone());
+                    }
+                }
+            }
+        }
+
+        let moves: Vec<(String, i64)> = conn
+            .prepare(
+                "SELECT te.actor, COUNT(*) FROM task_events te
+                 JOIN tasks t ON t.id = te.task_id
+                 WHERE t.board_id = ?1 AND te.event_type = 'moved'
+                   AND json_extract(te.data, '$.from') = ?2 AND te.created_at >= ?3
+                 GROUP BY te.actor",
+            )
+            .map_err(|e| db_error(&e.to_string()))?
+            .query_map(rusqlite::params![board_id, column_id, since], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
+        for (actor, count) in moves {
+            if let Some(entry) = affinity(&mut actors, &actor) {
+                entry.column_moves += count;
+            }
+        }
+
+        let mut suggestions: Vec<AssigneeSuggestion> = actors
+            .into_values()
+            .map(|a| AssigneeSuggestion {
+                score: ((a.label_score
+                    + COLUMN_MOVE_WEIGHT * a.column_moves as f64
+                    + COMPLETION_WEIGHT * a.completed as f64)
+                    * 100.0)
+                    .round()
+                    / 100.0,
+                actor: a.actor,
+                label_matches: a.label_matches,
+                matched_labels: a.matched_labels,
+                column_moves: a.column_moves,
+                completed: a.completed,
+                open_tasks: 0,
+            })
+            .collect();
+        suggestions.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| b.completed.cmp(&a.completed))
+                .then_with(|| a.actor.cmp(&b.actor))
+        });
+        suggestions.truncate(limit);
+        for s in suggestions.iter_mut() {
+            s.open_tasks = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM tasks
+                     WHERE board_id = ?1 AND completed_at IS NULL AND archived_at IS NULL
+                       AND (assigned_to = ?2 COLLATE NOCASE OR claimed_by = ?2 COLLATE NOCASE)",
+                    rusqlite::params![board_id, s.actor],
+                    |row| row.get(0),
+                )
+                .map_err(|e| db_error(&e.to_string()))?;
+        }
+
+        Ok(Json(AssigneeSuggestionsResponse {
+            task_id: task_id.to_string(),
+            labels,
+            column_id,
+            column_name,
+            days,
+            sample_size: completed.len(),
+            suggestions,
+        }))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let samples = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0];
+        assert_eq!(percentile(&samples, 50.0), 5.0);
+        assert_eq!(percentile(&samples, 85.0), 9.0);
+        assert_eq!(percentile(&samples, 95.0), 10.0);
+        assert_eq!(percentile(&[4.0], 50.0), 4.0);
+    }
+
+    #[test]
+    fn test_column_entered_from_event_data() {
+        let moved = serde_json::json!({"from": "a", "to": "b"});
+        assert_eq!(column_entered("moved", &moved), Some("b".to_string()));
+        let updated = serde_json::json!({"priority": 2});
+        assert_eq!(column_entered("updated", &updated), None);
+        assert_eq!(column_entered("comment", &moved), None);
+    }
+
+    #[test]
+    fn test_shared_labels_ignore_case() {
+        let wanted = vec!["backend".to_string(), "db".to_string()];
+        let labels = vec!["Backend".to_string(), "ui".to_string()];
+        assert_eq!(shared_labels(&wanted, &labels), vec![&wanted[0]]);
+        assert!(shared_labels(&wanted, &[]).is_empty());
+    }
+}
@@ -0,0 +1,721 @@
+//! File attachments on tasks (`FEATURES=attachments`).
+//!
+//! Metadata lives in the `attachments` table; file contents go to the
+//! configured store — a local directory or an S3-compatible bucket, keyed
+//! `<board>/<task>/<attachment>`. Files of tasks that are permanently deleted
+//! (trash purge, retention, board deletion) are removed by an hourly sweep;
+//! tasks sitting in the trash keep theirs until then.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use rocket::data::Capped;
+use rocket::form::Form;
+use rocket::fs::TempFile;
+use rocket::http::{ContentType, Header, Status};
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::Connection;
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncReadExt;
+
+use crate::access::{self, BoardRole};
+use crate::auth::{Actor, BoardToken};
+use crate::db::{hash_key, DbPool};
+use crate::event_types;
+use crate::events::{BoardEvent, EventBus};
+use crate::features::{self, FeatureFlags};
+use crate::models::*;
+use crate::routes::{db_error, log_event, not_found, with_db, with_tx};
+use crate::signing::hmac;
+use crate::url_policy::OutboundClient;
+
+/// Default upload cap (`ATTACHMENT_MAX_BYTES`): 10 MiB.
+const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+/// Default `ATTACHMENT_ALLOWED_TYPES`. `text/html` and other types browsers
+/// would render are left out on purpose.
+const DEFAULT_ALLOWED_TYPES: &str =
+    "image/png,image/jpeg,image/gif,image/webp,text/plain,text/markdown,text/csv,application/pdf,application/json,application/zip,application/gzip";
+/// Room for multipart boundaries and the other form fields.
+const FORM_OVERHEAD: u64 = 64 * 1024;
+/// How often the sweep looks for files of deleted tasks.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const MAX_FILENAME_LEN: usize = 255;
+
+/// Where attachment contents are kept.
+#[derive(Debug, Clone)]
+pub enum AttachmentStore {
+    Disk { dir: PathBuf },
+    S3(S3Store),
+}
+
+/// An S3-compatible bucket, addressed path-style (`<endpoint>/<bucket>/<key>`)
+/// so MinIO, R2 and friends work alongside AWS.
+#[derive(Debug, Clone)]
+pub struct S3Store {
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct AttachmentConfig {
+    pub store: AttachmentStore,
+    /// Largest accepted file, in bytes
+    pub max_bytes: u64,
+    /// Accepted content types: exact (`application/pdf`), `type/*`, or `*`
+    pub allowed_types: Vec<String>,
+}
+
+impl AttachmentConfig {
+    pub fn disk(dir: impl Into<PathBuf>) -> Self {
+        AttachmentConfig {
+            store: AttachmentStore::Disk { dir: dir.into() },
+            max_bytes: DEFAULT_MAX_BYTES,
+            allowed_types: parse_types(DEFAULT_ALLOWED_TYPES),
+        }
+    }
+
+    /// Read `ATTACHMENT_STORAGE` (`disk`, the default, or `s3`),
+    /// `ATTACHMENTS_DIR` (default `attachments`), `S3_ENDPOINT`, `S3_BUCKET`,
+    /// `S3_REGION` (default `us-east-1`), `S3_ACCESS_KEY_ID`,
+    /// `S3_SECRET_ACCESS_KEY`, `ATTACHMENT_MAX_BYTES` and
+    /// `ATTACHMENT_ALLOWED_TYPES`. Falls back to disk if S3 is incomplete.
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        let mut config = Self::disk(var("ATTACHMENTS_DIR").unwrap_or_else(|| "attachments".to_string()));
+        if var("ATTACHMENT_STORAGE").is_some_and(|s| s.eq_ignore_ascii_case("s3")) {
+            match (var("S3_ENDPOINT"), var("S3_BUCKET"), var("S3_ACCESS_KEY_ID"), var("S3_SECRET_ACCESS_KEY")) {
+                (Some(endpoint), Some(bucket), Some(access_key_id), Some(secret_access_key)) => {
+                    config.store = AttachmentStore::S3(S3Store {
+                        endpoint,
+                        bucket,
+                        region: var("S3_REGION").unwrap_or_else(|| "us-east-1".to_string()),
+                        access_key_id,
+                        secret_access_key,
+                    })
+                }
+                _ => eprintln!(
+                    "⚠️  ATTACHMENT_STORAGE=s3 needs S3_ENDPOINT, S3_BUCKET, S3_ACCESS_KEY_ID and S3_SECRET_ACCESS_KEY; storing attachments on disk"
+                ),
+            }
+        }
+        if let Some(max) = var("ATTACHMENT_MAX_BYTES").and_then(|v| v.parse().ok()).filter(|n| *n > 0) {
+            config.max_bytes = max;
+        }
+        if let Some(types) = var("ATTACHMENT_ALLOWED_TYPES") {
+            config.allowed_types = parse_types(&types);
+        }
+        config
+    }
+
+    /// Raise Rocket's form limits so uploads up to `max_bytes` get through to
+    /// the handler, which reports anything bigger as `ATTACHMENT_TOO_LARGE`.
+    pub fn apply_limits(&self, figment: rocket::figment::Figment) -> rocket::figment::Figment {
+        figment
+            .merge(("limits.file", self.max_bytes + 1))
+            .merge(("limits.data-form", self.max_bytes + FORM_OVERHEAD))
+    }
+
+    fn allows(&self, content_type: &str) -> bool {
+        self.allowed_types.iter().any(|allowed| match allowed.strip_suffix("/*") {
+            _ if allowed == "*" => true,
+            Some(top) => content_type.split('/').next() == Some(top),
+            None => allowed == content_type,
+        })
+    }
+}
+
+fn parse_types(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty())
+        .map(|t| if t == "*/*" { "*".to_string() } else { t })
+        .collect()
+}
+
+// ============ Storage ============
+
+impl AttachmentStore {
+    async fn put(&self, client: &OutboundClient, key: &str, bytes: Vec<u8>, content_type: &str) -> Result<(), String> {
+        match self {
+            AttachmentStore::Disk { dir } => {
+                let path = dir.join(key);
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
+                }
+                tokio::fs::write(&path, bytes).await.map_err(|e| e.to_string())
+            }
+            AttachmentStore::S3(s3) => s3.send(client, reqwest::Method::PUT, key, bytes, Some(content_type)).await.map(|_| ()),
+        }
+    }
+
+    async fn get(&self, client: &OutboundClient, key: &str) -> Result<Vec<u8>, String> {
+        match self {
+            AttachmentStore::Disk { dir } => tokio::fs::read(dir.join(key)).await.map_err(|e| e.to_string()),
+            AttachmentStore::S3(s3) => {
+                let resp = s3.send(client, reqwest::Method::GET, key, Vec::new(), None).await?;
+                resp.bytes().await.map(|b| b.to_vec()).map_err(|e| e.to_string())
+            }
+        }
+    }
+
+    async fn delete(&self, client: &OutboundClient, key: &str) -> Result<(), String> {
+        match self {
+            AttachmentStore::Disk { dir } => match tokio::fs::remove_file(dir.join(key)).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+                _ => Ok(()),
+            },
+            AttachmentStore::S3(s3) => s3.send(client, reqwest::Method::DELETE, key, Vec::new(), None).await.map(|_| ()),
+        }
+    }
+}
+
+/// AWS Signature Version 4 signing key for one day, region and service.
+fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac(format!("AWS4{}", secret).as_bytes(), date.as_bytes());
+    let key = hmac(&key, region.as_bytes());
+    let key = hmac(&key, service.as_bytes());
+    hmac(&key, b"aws4_request")
+}
+
+impl S3Store {
+    /// Send a SigV4-signed request for `key`; non-2xx answers are errors.
+    async fn send(
+        &self,
+        client: &OutboundClient,
+        method: reqwest::Method,
+        key: &str,
+        body: Vec<u8>,
+        content_type: Option<&str>,
+    ) -> Result<reqwest::Response, String> {
+        let url = reqwest::Url::parse(&format!("{}/{}/{}", self.endpoint.trim_end_matches('/'), self.bucket, key))
+            .map_err(|e| format!("invalid S3 URL: {}", e))?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err("S3_ENDPOINT has no host".to_string()),
+        };
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex::encode(Sha256::digest(&body));
+
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method,
+            url.path(),
+            host,
+            payload_hash,
+            amz_date,
+            signed_headers,
+            payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let signature = hex::encode(hmac(
+            &signing_key(&self.secret_access_key, &date, &self.region, "s3"),
+            string_to_sign.as_bytes(),
+        ));
+
+        let mut request = client
+            .request(method, url.as_str())?
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header(
+                "Authorization",
+                format!(
+                    "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                    self.access_key_id, scope, signed_headers, signature
+                ),
+            )
+            .timeout(Duration::from_secs(30));
+        if let Some(content_type) = content_type {
+            request = request.header("Content-Type", content_type);
+        }
+        let resp = request.body(body).send().await.map_err(|e| e.to_string())?;
+        if resp.status().is_success() {
+            Ok(resp)
+        } else {
+            Err(format!("S3 answered HTTP {}", resp.status().as_u16()))
+        }
+    }
+}
+
+// ============ Routes ============
+
+#[derive(FromForm)]
+pub struct AttachmentUpload<'r> {
+    pub file: Capped<TempFile<'r>>,
+    pub actor_name: Option<String>,
+}
+
+/// A stored file, sent as a download so browsers never render it inline.
+#[derive(Responder)]
+pub struct AttachmentDownload {
+    inner: (ContentType, Vec<u8>),
+    disposition: Header<'static>,
+    nosniff: Header<'static>,
+}
+
+fn error(status: Status, error: String, code: &str) -> (Status, Json<ApiError>) {
+    (
+        status,
+        Json(ApiError {
+            error,
+            code: code.to_string(),
+            status: status.code,
+        }),
+    )
+}
+
+fn require_enabled(flags: &FeatureFlags) -> Result<(), (Status, Json<ApiError>)> {
+    if flags.is_enabled(features::ATTACHMENTS) {
+        Ok(())
+    } else {
+        Err(error(
+            Status::NotFound,
+            "Attachments are not enabled on this instance (FEATURES=attachments)".to_string(),
+            "FEATURE_DISABLED",
+        ))
+    }
+}
+
+fn require_task(conn: &Connection, board_id: &str, task_id: &str) -> Result<(), (Status, Json<ApiError>)> {
+    conn.query_row(
+        "SELECT 1 FROM tasks WHERE id = ?1 AND board_id = ?2",
+        rusqlite::params![task_id, board_id],
+        |_| Ok(()),
+    )
+    .map_err(|_| not_found("Task"))
+}
+
+/// The client's filename reduced to its last path segment, without control
+/// characters or quotes, at most 255 characters.
+fn sanitize_filename(raw: &str) -> String {
+    let name: String = raw
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control() && *c != '"')
+        .take(MAX_FILENAME_LEN)
+        .collect();
+    let name = name.trim();
+    if name.is_empty() || name == "." || name == ".." {
+        "attachment".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+const ATTACHMENT_COLUMNS: &str = "id, task_id, filename, content_type, size_bytes, sha256, uploaded_by, created_at";
+
+fn row_to_attachment(board_id: &str, row: &rusqlite::Row) -> rusqlite::Result<AttachmentResponse> {
+    let id: String = row.get(0)?;
+    let task_id: String = row.get(1)?;
+    Ok(AttachmentResponse {
+        url: format!("/api/v1/boards/{}/tasks/{}/attachments/{}", board_id, task_id, id),
+        id,
+        task_id,
+        filename: row.get(2)?,
+        content_type: row.get(3)?,
+        size_bytes: row.get(4)?,
+        sha256: row.get(5)?,
+        uploaded_by: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+/// Most attachments one comment can reference.
+pub const MAX_PER_COMMENT: usize = 20;
+
+/// Resolve the `attachment_ids` of a comment on `task_id`, in the order
+/// given. Each must be an attachment of that task; repeats are dropped.
+pub fn for_comment(
+    conn: &Connection,
+    board_id: &str,
+    task_id: &str,
+    ids: &[String],
+) -> Result<Vec<CommentAttachment>, (Status, Json<ApiError>)> {
+    let mut seen = std::collections::HashSet::new();
+    let ids: Vec<&String> = ids.iter().filter(|id| seen.insert(id.as_str())).collect();
+    if ids.len() > MAX_PER_COMMENT {
+        return Err(error(
+            Status::BadRequest,
+            format!("A comment can reference at most {} attachments", MAX_PER_COMMENT),
+            "INVALID_ATTACHMENT",
+        ));
+    }
+    ids.into_iter()
+        .map(|id| {
+            conn.query_row(
+                &format!("SELECT {} FROM attachments WHERE id = ?1 AND task_id = ?2 AND board_id = ?3", ATTACHMENT_COLUMNS),
+                rusqlite::params![id, task_id, board_id],
+                |row| row_to_attachment(board_id, row),
+            )
+            .map(|a| CommentAttachment {
+                id: a.id,
+                filename: a.filename,
+                content_type: a.content_type,
+                size_bytes: a.size_bytes,
+                url: a.url,
+            })
+            .map_err(|_| {
+                error(
+                    Status::BadRequest,
+                    format!("Attachment '{}' does not belong to this task", id),
+                    "INVALID_ATTACHMENT",
+                )
+            })
+        })
+        .collect()
+}
+
+/// Upload a file (`multipart/form-data`, field `file`) — requires manage key.
+#[allow(clippy::too_many_arguments)]
+#[post("/boards/<board_id>/tasks/<task_id>/attachments", data = "<upload>")]
+pub async fn upload_attachment(
+    board_id: &str,
+    task_id: &str,
+    upload: Form<AttachmentUpload<'_>>,
+    token: BoardToken,
+    actor: Actor,
+    flags: &State<FeatureFlags>,
+    config: &State<AttachmentConfig>,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<AttachmentResponse>, (Status, Json<ApiError>)> {
+    require_enabled(flags)?;
+    let actor = actor.or_body(upload.actor_name.as_deref());
+    let (board, task, uploader) = (board_id.to_owned(), task_id.to_owned(), actor.clone());
+    with_db(db, move |conn| {
+        let (board_id, task_id) = (board.as_str(), task.as_str());
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+        access::require_display_name(conn, board_id, &uploader)?;
+        require_task(conn, board_id, task_id)
+    })
+    .await?;
+
+    let file = &upload.file;
+    if !file.is_complete() || file.len() > config.max_bytes {
+        return Err(error(
+            Status::PayloadTooLarge,
+            format!("Attachments are limited to {} bytes", config.max_bytes),
+            "ATTACHMENT_TOO_LARGE",
+        ));
+    }
+    if file.is_empty() {
+        return Err(error(Status::BadRequest, "Attachment is empty".to_string(), "EMPTY_FILE"));
+    }
+    let content_type = file
+        .content_type()
+        .map(|ct| format!("{}/{}", ct.top(), ct.sub()).to_lowercase())
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+    if !config.allows(&content_type) {
+        return Err(error(
+            Status::UnsupportedMediaType,
+            format!("Content type '{}' is not allowed. Allowed: {}", content_type, config.allowed_types.join(", ")),
+            "ATTACHMENT_TYPE_NOT_ALLOWED",
+        ));
+    }
+    let filename = sanitize_filename(
+        file.raw_name()
+            .map(|name| name.dangerous_unsafe_unsanitized_raw().as_str())
+            .unwrap_or_default(),
+    );
+
+    let mut bytes = Vec::with_capacity(file.len() as usize);
+    file.open()
+        .await
+        .map_err(|e| db_error(&e.to_string()))?
+        .read_to_end(&mut bytes)
+        .await
+        .map_err(|e| db_error(&e.to_string()))?;
+    let sha256 = hex::encode(Sha256::digest(&bytes));
+    let size_bytes = bytes.len() as i64;
+
+    let attachment_id = uuid::Uuid::new_v4().to_string();
+    let storage_key = format!("{}/{}/{}", board_id, task_id, attachment_id);
+    config.store.put(bus.outbound(), &storage_key, bytes, &content_type).await.map_err(|e| {
+        error(
+            Status::ServiceUnavailable,
+            format!("Attachment could not be stored ({}); try again later", e),
+            "STORAGE_UNAVAILABLE",
+        )
+    })?;
+
+    let (board, task, key, events) = (board_id.to_owned(), task_id.to_owned(), storage_key.clone(), bus.inner().clone());
+    let stored = with_tx(db, move |conn| {
+        let (board_id, task_id) = (board.as_str(), task.as_str());
+        conn.execute(
+            "INSERT INTO attachments (id, task_id, board_id, filename, content_type, size_bytes, sha256, storage_key, uploaded_by)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            rusqlite::params![
+                attachment_id,
+                task_id,
+                board_id,
+                filename,
+                content_type,
+                size_bytes,
+                sha256,
+                key,
+                actor.name(),
+            ],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        let event_data = serde_json::json!({
+            "task_id": task_id,
+            "attachment_id": attachment_id,
+            "filename": filename,
+            "content_type": content_type,
+            "size_bytes": size_bytes,
+        });
+        log_event(conn, task_id, "attachment.added", actor.name(), &event_data);
+        let attachment = conn
+            .query_row(
+                &format!("SELECT {} FROM attachments WHERE id = ?1", ATTACHMENT_COLUMNS),
+                rusqlite::params![attachment_id],
+                |row| row_to_attachment(board_id, row),
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        events
+            .emit(
+                conn,
+                BoardEvent {
+                    event: event_types::TASK_ATTACHMENT_ADDED.to_string(),
+                    board_id: board_id.to_string(),
+                    data: event_data,
+                },
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        Ok(attachment)
+    })
+    .await;
+    let attachment = match stored {
+        Ok(attachment) => attachment,
+        Err(e) => {
+            let _ = config.store.delete(bus.outbound(), &storage_key).await;
+            return Err(e);
+        }
+    };
+    Ok(Json(attachment))
+}
+
+/// List a task's attachments, oldest first — public.
+#[get("/boards/<board_id>/tasks/<task_id>/attachments")]
+pub async fn list_attachments(
+    board_id: &str,
+    task_id: &str,
+    flags: &State<FeatureFlags>,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+) -> Result<Json<Vec<AttachmentResponse>>, (Status, Json<ApiError>)> {
+    require_enabled(flags)?;
+    let (board_id, task_id) = (board_id.to_owned(), task_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, task_id) = (board_id.as_str(), task_id.as_str());
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        require_task(conn, board_id, task_id)?;
+        let attachments = conn
+            .prepare(&format!(
+                "SELECT {} FROM attachments WHERE task_id = ?1 ORDER BY created_at ASC, rowid ASC",
+                ATTACHMENT_COLUMNS
+            ))
+            .map_err(|e| db_error(&e.to_string()))?
+            .query_map(rusqlite::params![task_id], |row| row_to_attachment(board_id, row))
+            .map_err(|e| db_error(&e.to_string()))?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| db_error(&e.to_string()))?;
+        Ok(Json(attachments))
+    })
+    .await
+}
+
+/// Download an attachment — public.
+#[allow(clippy::too_many_arguments)]
+#[get("/boards/<board_id>/tasks/<task_id>/attachments/<attachment_id>")]
+pub async fn download_attachment(
+    board_id: &str,
+    task_id: &str,
+    attachment_id: &str,
+    flags: &State<FeatureFlags>,
+    config: &State<AttachmentConfig>,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<AttachmentDownload, (Status, Json<ApiError>)> {
+    require_enabled(flags)?;
+    let (attachment_id, board_id, task_id) = (attachment_id.to_owned(), board_id.to_owned(), task_id.to_owned());
+    let (filename, content_type, storage_key): (String, String, String) = with_db(db, move |conn| {
+        let (attachment_id, board_id, task_id) = (attachment_id.as_str(), board_id.as_str(), task_id.as_str());
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        conn.query_row(
+            "SELECT filename, content_type, storage_key FROM attachments WHERE id = ?1 AND task_id = ?2 AND board_id = ?3",
+            rusqlite::params![attachment_id, task_id, board_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|_| not_found("Attachment"))
+    })
+    .await?;
+    let bytes = config.store.get(bus.outbound(), &storage_key).await.map_err(|e| {
+        error(
+            Status::ServiceUnavailable,
+            format!("Attachment could not be read ({})", e),
+            "STORAGE_UNAVAILABLE",
+        )
+    })?;
+    Ok(AttachmentDownload {
+        inner: (
+            ContentType::parse_flexible(&content_type).unwrap_or(ContentType::Binary),
+            bytes,
+        ),
+        disposition: Header::new("Content-Disposition", format!("attachment; filename=\"{}\"", filename)),
+        nosniff: Header::new("X-Content-Type-Options", "nosniff"),
+    })
+}
+
+/// Delete an attachment and its file — requires manage key.
+#[allow(clippy::too_many_arguments)]
+#[delete("/boards/<board_id>/tasks/<task_id>/attachments/<attachment_id>")]
+pub async fn delete_attachment(
+    board_id: &str,
+    task_id: &str,
+    attachment_id: &str,
+    token: BoardToken,
+    actor: Actor,
+    flags: &State<FeatureFlags>,
+    config: &State<AttachmentConfig>,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
+    require_enabled(flags)?;
+    let task_id = task_id.to_owned();
+    let (attachment, board, events) = (attachment_id.to_owned(), board_id.to_owned(), bus.inner().clone());
+    let storage_key = with_tx(db, move |conn| {
+        let (attachment_id, board_id) = (attachment.as_str(), board.as_str());
+        let task_id = task_id.as_str();
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+        access::require_display_name(conn, board_id, &actor)?;
+        let (filename, storage_key): (String, String) = conn
+            .query_row(
+                "SELECT filename, storage_key FROM attachments WHERE id = ?1 AND task_id = ?2 AND board_id = ?3",
+                rusqlite::params![attachment_id, task_id, board_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|_| not_found("Attachment"))?;
+        conn.execute("DELETE FROM attachments WHERE id = ?1", rusqlite::params![attachment_id])
+            .map_err(|e| db_error(&e.to_string()))?;
+        let event_data = serde_json::json!({"task_id": task_id, "attachment_id": attachment_id, "filename": filename});
+        log_event(conn, task_id, "attachment.removed", actor.name(), &event_data);
+        events
+            .emit(
+                conn,
+                BoardEvent {
+                    event: event_types::TASK_ATTACHMENT_REMOVED.to_string(),
+                    board_id: board_id.to_string(),
+                    data: event_data,
+                },
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        Ok(storage_key)
+    })
+    .await?;
+    if let Err(e) = config.store.delete(bus.outbound(), &storage_key).await {
+        eprintln!("⚠️  Could not delete attachment file {}: {}", storage_key, e);
+    }
+    Ok(Json(serde_json::json!({"deleted": true, "id": attachment_id})))
+}
+
+// ============ Sweep ============
+
+/// Remove attachments (rows and files) whose task is gone for good — neither
+/// on a board nor in the trash. Returns the number removed.
+pub async fn sweep(db: &DbPool, config: &AttachmentConfig, client: &OutboundClient) -> Result<usize, String> {
+    let orphans: Vec<(String, String)> = with_db(db, move |conn| {
+        conn.prepare(
+            "SELECT id, storage_key FROM attachments
+             WHERE task_id NOT IN (SELECT id FROM tasks) AND task_id NOT IN (SELECT task_id FROM task_trash)",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<rusqlite::Result<Vec<_>>>()
+        })
+        .map_err(|e| db_error(&e.to_string()))
+    })
+    .await
+    .map_err(|(_, e)| e.error.clone())?;
+
+    let mut removed = 0;
+    for (id, storage_key) in orphans {
+        if let Err(e) = config.store.delete(client, &storage_key).await {
+            eprintln!("⚠️  Could not delete attachment file {}: {}", storage_key, e);
+            continue;
+        }
+        with_db(db, move |conn| {
+            conn.execute("DELETE FROM attachments WHERE id = ?1", rusqlite::params![id])
+                .map_err(|e| db_error(&e.to_string()))
+        })
+        .await
+        .map_err(|(_, e)| e.error.clone())?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// Run `sweep` hourly.
+pub fn spawn_sweep(db: DbPool, config: AttachmentConfig, client: OutboundClient) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            ticker.tick().await;
+            match sweep(&db, &config, &client).await {
+                Ok(0) => {}
+                Ok(n) => println!("📎 Removed {} attachment(s) of deleted tasks", n),
+                Err(e) => eprintln!("⚠️  Attachment sweep failed: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowed_types() {
+        let mut config = AttachmentConfig::disk("unused");
+        config.allowed_types = parse_types("image/*, application/PDF");
+        assert!(config.allows("image/png"));
+        assert!(config.allows("application/pdf"));
+        assert!(!config.allows("text/html"));
+        assert!(!config.allows("imagex/png"));
+        config.allowed_types = parse_types("*/*");
+        assert!(config.allows("text/html"));
+    }
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(sanitize_filename("../../etc/passwd"), "passwd");
+        assert_eq!(sanitize_filename("C:\\Users\\me\\report \"final\".pdf"), "report final.pdf");
+        assert_eq!(sanitize_filename(".."), "attachment");
+        assert_eq!(sanitize_filename(""), "attachment");
+    }
+
+    #[test]
+    fn test_sigv4_signing_key() {
+        // Example from the AWS Signature Version 4 documentation
+        let key = signing_key("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY", "20120215", "us-east-1", "iam");
+        assert_eq!(hex::encode(key), "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d");
+    }
+}
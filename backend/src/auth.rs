@@ -1,53 +1,422 @@
-use rocket::http::Status;
+use rocket::http::{Method, Status};
 use rocket::request::{FromRequest, Outcome, Request};
+use rocket::serde::json::Json;
+
+use crate::access;
+use crate::db::{hash_key, DbPool};
+use crate::ip_allowlist;
+use crate::models::ApiError;
+use crate::oidc;
+use crate::rate_limit::{ClientIp, RateLimitSlot, RateLimits, RouteGroup};
+use crate::share_links;
+use crate::view_password;
 
 /// Extracts a board management token from the request.
 /// Checks (in order):
 ///   1. `Authorization: Bearer <token>` header
 ///   2. `X-API-Key` header
 ///   3. `?key=<token>` query parameter
+///   4. `kb_session` cookie (an OIDC sign-in session; see `oidc`)
+///   5. `kb_view` cookie (a view token, set by `POST /boards/<id>/view-session`)
+///
+/// A session acts with its user's owner key for the board in the request
+/// path (see `oidc`). A browser can hold both cookies: the session wins, so a
+/// signed-in owner keeps their role, unless it has no key on the board and
+/// the view token is all that opens it.
 ///
-/// The token is NOT validated here — it's just extracted.
-/// Route handlers call `require_manage_key()` to verify against a specific board.
+/// The token is NOT validated here — it's just extracted, and refused only if
+/// it's a key whose `expires_at` has passed, or if the request writes to a
+/// board whose IP allowlist doesn't include the caller (see `ip_allowlist`).
+/// Route handlers call `access::require_role()` (or `require_manage_key()`) to
+/// verify it against a specific board.
 #[derive(Debug, Clone)]
 pub struct BoardToken(pub String);
 
+/// The key the request presents, from the first source that has one.
+fn presented_key(request: &Request<'_>) -> Result<String, &'static str> {
+    // 1. Authorization: Bearer header
+    if let Some(auth) = request.headers().get_one("Authorization") {
+        if let Some(key) = auth.strip_prefix("Bearer ") {
+            if !key.is_empty() {
+                return Ok(key.to_string());
+            }
+        }
+        return Err("Invalid authorization format. Use: Bearer YOUR_MANAGE_KEY");
+    }
+
+    // 2. X-API-Key header
+    if let Some(key) = request.headers().get_one("X-API-Key") {
+        if !key.is_empty() {
+            return Ok(key.to_string());
+        }
+    }
+
+    // 3. ?key= query parameter
+    if let Some(Ok(k)) = request.query_value::<String>("key") {
+        if !k.is_empty() {
+            return Ok(k);
+        }
+    }
+
+    // 4. Sign-in session cookie
+    if let Some(session) = cookie(request, oidc::SESSION_COOKIE) {
+        return Ok(session);
+    }
+
+    // 5. View token cookie
+    if let Some(view) = cookie(request, view_password::VIEW_COOKIE) {
+        return Ok(view);
+    }
+
+    Err("Missing management key. Use Authorization: Bearer YOUR_KEY, X-API-Key header, or ?key= query param")
+}
+
+fn cookie(request: &Request<'_>, name: &str) -> Option<String> {
+    request.cookies().get(name).map(|c| c.value().to_string()).filter(|v| !v.is_empty())
+}
+
+/// The key a request presents and what the database says about it, looked
+/// up once per request and shared by the `BoardToken` and `Actor` guards.
+struct PresentedKey {
+    key: Result<String, &'static str>,
+    status: access::KeyStatus,
+}
+
+async fn presented<'r>(request: &'r Request<'_>) -> &'r PresentedKey {
+    request
+        .local_cache_async(async {
+            let key = presented_key(request);
+            let board_id = match (request.routed_segment(0), request.routed_segment(1)) {
+                (Some("boards"), board_id) => board_id.map(str::to_string),
+                _ => None,
+            };
+            let session = key.as_deref().ok() == cookie(request, oidc::SESSION_COOKIE).as_deref();
+            // The view token to fall back to when the session has no key here
+            let view = cookie(request, view_password::VIEW_COOKIE).filter(|_| session && board_id.is_some());
+            let resolved = match (&key, request.rocket().state::<DbPool>()) {
+                (Ok(presented), Some(db)) => {
+                    let (db, key) = (db.clone(), presented.clone());
+                    crate::db::blocking(move || {
+                        let conn = db.get().ok()?;
+                        let status = |key: &str| {
+                            let mut status = access::key_status(&conn, &hash_key(key), board_id.as_deref());
+                            // Share links aren't stored; a valid one is on its board
+                            if let Some(board_id) = &board_id {
+                                status.on_board |= share_links::check(&conn, board_id, key).is_some_and(|link| link.is_ok());
+                            }
+                            status
+                        };
+                        // A session acts with its user's key for the board
+                        let key = match &board_id {
+                            Some(board_id) if session => oidc::session_keys(&conn, &key, Some(board_id)).pop().unwrap_or(key),
+                            _ => key,
+                        };
+                        let key_status = status(&key);
+                        Some(match view {
+                            Some(view) if !key_status.on_board => {
+                                let view_status = status(&view);
+                                (Ok(view), view_status)
+                            }
+                            _ => (Ok(key), key_status),
+                        })
+                    })
+                    .await
+                    .unwrap_or_else(|| (Ok(presented.clone()), access::KeyStatus::default()))
+                }
+                _ => (key, access::KeyStatus::default()),
+            };
+            let (key, status) = resolved;
+            PresentedKey { key, status }
+        })
+        .await
+}
+
+/// The write limit the request counts against: the limits, its group, and
+/// the presented key's hash. `None` unless it's a limited write by a key with
+/// a role on the board; keys that don't resolve are left to the route to
+/// refuse, so made-up keys can't grow the limiter's store.
+async fn write_limit<'r>(request: &'r Request<'_>) -> Option<(&'r RateLimits, RouteGroup, String)> {
+    let limits = request.rocket().state::<RateLimits>()?;
+    let group = RouteGroup::of_write(request.method(), &request.route()?.uri.to_string())?;
+    let presented = presented(request).await;
+    let key = presented.key.as_ref().ok().filter(|_| presented.status.on_board)?;
+    Some((limits, group, hash_key(key)))
+}
+
+/// Count the request against the key's write limit, if it has one; the
+/// result is kept for the rate limit headers. `false` once over the limit.
+async fn within_write_limit(request: &Request<'_>) -> bool {
+    let Some((limits, group, key_hash)) = write_limit(request).await else {
+        return true;
+    };
+    match limits.charge_async(group, &key_hash, 1).await {
+        Some(result) => {
+            let allowed = result.allowed;
+            RateLimitSlot::of(request).set(result);
+            allowed
+        }
+        None => true,
+    }
+}
+
+/// Request guard for bulk writes. `BoardToken` counts the request once; the
+/// handler charges the rest of what it's about to write with `charge`, so a
+/// CSV import costs a write per row rather than one for the whole file.
+pub struct WriteQuota<'r> {
+    limit: Option<(&'r RateLimits, RouteGroup, String)>,
+    slot: RateLimitSlot,
+}
+
+impl WriteQuota<'_> {
+    /// Count `writes` more writes against the key's limit; 429 (counting
+    /// none of them) if they don't fit in what's left of the window.
+    pub async fn charge(&self, writes: u64) -> Result<(), (Status, Json<ApiError>)> {
+        let Some((limits, group, key_hash)) = &self.limit else {
+            return Ok(());
+        };
+        if writes == 0 {
+            return Ok(());
+        }
+        let Some(result) = limits.charge_async(*group, key_hash, writes).await else {
+            return Ok(());
+        };
+        self.slot.set(result.clone());
+        if result.allowed {
+            return Ok(());
+        }
+        Err((
+            Status::TooManyRequests,
+            Json(ApiError {
+                error: format!(
+                    "This key doesn't have {} writes left in this window. Try again in {} seconds.",
+                    writes, result.reset_secs
+                ),
+                code: "RATE_LIMIT_EXCEEDED".to_string(),
+                status: 429,
+            }),
+        ))
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for WriteQuota<'r> {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(WriteQuota {
+            limit: write_limit(request).await,
+            slot: RateLimitSlot::of(request),
+        })
+    }
+}
+
+/// Whether a write from this request's address is outside the board's IP
+/// allowlist, looked up once per request.
+struct OutsideAllowlist(bool);
+
+/// Whether the request writes to a board from outside its IP allowlist — the
+/// `BoardToken` guard refuses it, and the 403 catcher says why.
+pub async fn outside_ip_allowlist(request: &Request<'_>) -> bool {
+    request
+        .local_cache_async(async {
+            let board_id = match (request.routed_segment(0), request.routed_segment(1)) {
+                (Some("boards"), Some(board_id)) if !matches!(request.method(), Method::Get | Method::Head) => board_id,
+                _ => return OutsideAllowlist(false),
+            };
+            let ranges = match request.rocket().state::<DbPool>() {
+                Some(db) => {
+                    let (db, board_id) = (db.clone(), board_id.to_string());
+                    crate::db::blocking(move || {
+                        db.get().map(|conn| ip_allowlist::board_ranges(&conn, &board_id)).unwrap_or_default()
+                    })
+                    .await
+                }
+                None => Vec::new(),
+            };
+            OutsideAllowlist(!ip_allowlist::allows(&ranges, &ClientIp::of(request).0))
+        })
+        .await
+        .0
+}
+
+/// Request guard for board writes that don't present a key (inbound hook
+/// payloads): refuses them from outside the board's IP allowlist, like
+/// `BoardToken` does for keyed writes.
+pub struct InsideIpAllowlist;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for InsideIpAllowlist {
+    type Error = &'static str;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        if outside_ip_allowlist(request).await {
+            return Outcome::Error((Status::Forbidden, "Writes to this board aren't allowed from this address"));
+        }
+        Outcome::Success(InsideIpAllowlist)
+    }
+}
+
+/// Whether the request presents a key whose expiry has passed — the
+/// `BoardToken` guard refuses it, and the 401 catcher says why.
+pub async fn presents_expired_key(request: &Request<'_>) -> bool {
+    presented(request).await.status.expired
+}
+
 #[rocket::async_trait]
 impl<'r> FromRequest<'r> for BoardToken {
     type Error = &'static str;
 
     async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
-        // 1. Authorization: Bearer header
-        if let Some(auth) = request.headers().get_one("Authorization") {
-            if let Some(key) = auth.strip_prefix("Bearer ") {
-                if !key.is_empty() {
-                    return Outcome::Success(BoardToken(key.to_string()));
-                }
-            }
-            return Outcome::Error((
-                Status::Unauthorized,
-                "Invalid authorization format. Use: Bearer YOUR_MANAGE_KEY",
-            ));
+        let presented = presented(request).await;
+        let key = match &presented.key {
+            Ok(key) => key.clone(),
+            Err(e) => return Outcome::Error((Status::Unauthorized, e)),
+        };
+
+        // Keys minted with an expiry stop here once it has passed
+        if presents_expired_key(request).await {
+            return Outcome::Error((Status::Unauthorized, "This key has expired"));
         }
 
-        // 2. X-API-Key header
-        if let Some(key) = request.headers().get_one("X-API-Key") {
-            if !key.is_empty() {
-                return Outcome::Success(BoardToken(key.to_string()));
-            }
+        // Boards with an IP allowlist only take writes from inside it
+        if outside_ip_allowlist(request).await {
+            return Outcome::Error((Status::Forbidden, "Writes to this board aren't allowed from this address"));
         }
 
-        // 3. ?key= query parameter
-        if let Some(Ok(k)) = request.query_value::<String>("key") {
-            if !k.is_empty() {
-                return Outcome::Success(BoardToken(k));
-            }
+        // Board writes, comments and batch calls are rate limited per key
+        if !within_write_limit(request).await {
+            return Outcome::Error((Status::TooManyRequests, "Too many writes with this key"));
         }
+        Outcome::Success(BoardToken(key))
+    }
+}
 
-        Outcome::Error((
-            Status::Unauthorized,
-            "Missing management key. Use Authorization: Bearer YOUR_KEY, X-API-Key header, or ?key= query param",
-        ))
+/// Instance-level admin configuration, loaded once at startup.
+///
+/// Admin routes are disabled entirely when `ADMIN_KEY` is not set.
+pub struct AdminConfig {
+    key_hash: Option<String>,
+}
+
+impl AdminConfig {
+    /// Read `ADMIN_KEY` from the environment.
+    pub fn from_env() -> Self {
+        Self::new(std::env::var("ADMIN_KEY").ok().filter(|k| !k.is_empty()).as_deref())
+    }
+
+    /// Build a config from an explicit key (None disables admin routes).
+    pub fn new(admin_key: Option<&str>) -> Self {
+        AdminConfig {
+            key_hash: admin_key.map(crate::db::hash_key),
+        }
+    }
+}
+
+/// Request guard for instance admin routes.
+/// Accepts the same token sources as `BoardToken`, verified against `ADMIN_KEY`.
+#[derive(Debug, Clone)]
+pub struct AdminToken;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AdminToken {
+    type Error = &'static str;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let expected = match request.rocket().state::<AdminConfig>().and_then(|c| c.key_hash.as_ref()) {
+            Some(hash) => hash,
+            None => return Outcome::Error((Status::Forbidden, "Admin API is disabled (ADMIN_KEY not set)")),
+        };
+
+        let token = match presented_key(request) {
+            Ok(t) => t,
+            Err(_) => return Outcome::Error((Status::Unauthorized, "Missing admin key")),
+        };
+
+        if &crate::db::hash_key(&token) == expected {
+            Outcome::Success(AdminToken)
+        } else {
+            Outcome::Error((Status::Forbidden, "Invalid admin key"))
+        }
+    }
+}
+
+/// The identity a write is attributed to, resolved once per request.
+/// Checks (in order):
+///   1. `?actor=<name>` query parameter
+///   2. `?agent=<name>` query parameter
+///   3. `X-Actor` header
+///
+/// Handlers with a JSON body call `or_body()` so an explicit `actor_name`
+/// in the body wins. Never fails — with no source the actor is anonymous,
+/// and `access::require_display_name()` decides whether that is allowed.
+///
+/// A request made with an agent key is always its agent: the sources above
+/// and `actor_name` are ignored, so history can't be attributed to anyone
+/// else.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Actor {
+    name: Option<String>,
+    /// Fixed by an agent key; nothing in the request can change it
+    bound: bool,
+}
+
+impl Actor {
+    /// Blank names and the literal "anonymous" resolve to no name.
+    pub fn new(name: Option<&str>) -> Self {
+        Actor {
+            name: name
+                .map(str::trim)
+                .filter(|n| !n.is_empty() && !n.eq_ignore_ascii_case("anonymous"))
+                .map(str::to_string),
+            bound: false,
+        }
+    }
+
+    /// Prefer a name given in the request body over the guard's own sources.
+    pub fn or_body(self, body_name: Option<&str>) -> Self {
+        let body = Actor::new(body_name);
+        if self.bound || body.is_anonymous() {
+            self
+        } else {
+            body
+        }
+    }
+
+    pub fn is_anonymous(&self) -> bool {
+        self.name.is_none()
+    }
+
+    /// Display name for attribution; "anonymous" when unset.
+    pub fn name(&self) -> &str {
+        self.name_or("anonymous")
+    }
+
+    /// Display name for attribution, with a route-specific fallback.
+    pub fn name_or<'a>(&'a self, fallback: &'a str) -> &'a str {
+        self.name.as_deref().unwrap_or(fallback)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Actor {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let status = &presented(request).await.status;
+        if let (Some(agent_id), false) = (&status.agent_id, status.expired) {
+            return Outcome::Success(Actor {
+                name: Some(agent_id.clone()),
+                bound: true,
+            });
+        }
+
+        let query = |name| request.query_value::<String>(name).and_then(Result::ok);
+        let name = query("actor")
+            .filter(|n| !n.trim().is_empty())
+            .or_else(|| query("agent"))
+            .filter(|n| !n.trim().is_empty())
+            .or_else(|| request.headers().get_one("X-Actor").map(str::to_string));
+        Outcome::Success(Actor::new(name.as_deref()))
     }
 }
 
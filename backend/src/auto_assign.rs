@@ -0,0 +1,144 @@
+//! Column auto-assignment. A column can list a pool of agents; tasks entering
+//! it are handed to one of them, either in turn (`round_robin`) or to whoever
+//! holds the fewest open claims on the board (`least_loaded`). Covers simple
+//! fleets without an external dispatcher.
+
+use rusqlite::Connection;
+
+use crate::event_types;
+use crate::events::{BoardEvent, EventBus};
+use crate::routes::log_event;
+
+pub const ROUND_ROBIN: &str = "round_robin";
+pub const LEAST_LOADED: &str = "least_loaded";
+pub const STRATEGIES: &[&str] = &[ROUND_ROBIN, LEAST_LOADED];
+
+/// Actor recorded on `assigned` activity entries.
+const ACTOR: &str = "auto-assign";
+
+/// A task the column rule just assigned.
+#[derive(Debug, Clone)]
+pub struct Assignment {
+    pub task_id: String,
+    pub column_id: String,
+    pub assigned_to: String,
+    pub previous: Option<String>,
+    pub strategy: String,
+}
+
+impl Assignment {
+    fn event_data(&self) -> serde_json::Value {
+        serde_json::json!({
+            "task_id": self.task_id,
+            "column_id": self.column_id,
+            "assigned_to": self.assigned_to,
+            "previous": self.previous,
+            "strategy": self.strategy,
+        })
+    }
+
+    /// Announce the assignment as `task.assigned`.
+    pub fn emit(&self, conn: &Connection, bus: &EventBus, board_id: &str) -> rusqlite::Result<()> {
+        bus.emit(
+            conn,
+            BoardEvent {
+                event: event_types::TASK_ASSIGNED.to_string(),
+                board_id: board_id.to_string(),
+                data: self.event_data(),
+            },
+        )
+    }
+}
+
+/// Parse the stored pool (a JSON array of names); anything else means no pool.
+pub fn parse_pool(raw: Option<String>) -> Vec<String> {
+    raw.and_then(|r| serde_json::from_str(&r).ok()).unwrap_or_default()
+}
+
+/// Trim the requested pool, dropping blanks and case-insensitive duplicates.
+/// Returns the JSON to store, or `None` when the pool is empty (rule off).
+pub fn normalize_pool(agents: &[String]) -> Option<String> {
+    let mut pool: Vec<String> = Vec::new();
+    for agent in agents.iter().map(|a| a.trim()).filter(|a| !a.is_empty()) {
+        if !pool.iter().any(|p| p.eq_ignore_ascii_case(agent)) {
+            pool.push(agent.to_string());
+        }
+    }
+    (!pool.is_empty()).then(|| serde_json::to_string(&pool).unwrap_or_default())
+}
+
+/// Apply the column's rule to a new task, unless it was created with an assignee.
+pub fn on_create(conn: &Connection, task_id: &str, column_id: &str) -> rusqlite::Result<Option<Assignment>> {
+    let assigned: Option<String> =
+        conn.query_row("SELECT assigned_to FROM tasks WHERE id = ?1", rusqlite::params![task_id], |row| row.get(0))?;
+    if assigned.is_some_and(|a| !a.is_empty()) {
+        return Ok(None);
+    }
+    on_enter(conn, task_id, column_id)
+}
+
+/// Apply the column's rule to a task that just entered it. Tasks already
+/// assigned to someone in the pool keep their assignee. Logs an `assigned`
+/// activity entry; the caller emits the returned assignment once committed.
+pub fn on_enter(conn: &Connection, task_id: &str, column_id: &str) -> rusqlite::Result<Option<Assignment>> {
+    let (raw_pool, strategy, cursor): (Option<String>, String, i64) = conn.query_row(
+        "SELECT auto_assign, COALESCE(auto_assign_strategy, ?2), auto_assign_cursor FROM columns WHERE id = ?1",
+        rusqlite::params![column_id, ROUND_ROBIN],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?;
+    let pool = parse_pool(raw_pool);
+    if pool.is_empty() {
+        return Ok(None);
+    }
+
+    let (board_id, previous): (String, Option<String>) = conn.query_row(
+        "SELECT board_id, assigned_to FROM tasks WHERE id = ?1",
+        rusqlite::params![task_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let previous = previous.filter(|p| !p.is_empty());
+    if previous.as_deref().is_some_and(|p| pool.iter().any(|a| a.eq_ignore_ascii_case(p))) {
+        return Ok(None);
+    }
+
+    // Walk the pool from the cursor; round robin takes the first agent,
+    // least-loaded the first with the fewest open claims (ties rotate too)
+    let start = cursor.rem_euclid(pool.len() as i64) as usize;
+    let mut pick = start;
+    if strategy == LEAST_LOADED {
+        let mut best = i64::MAX;
+        for offset in 0..pool.len() {
+            let i = (start + offset) % pool.len();
+            let load: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM tasks WHERE board_id = ?1 AND claimed_by = ?2 COLLATE NOCASE
+                   AND completed_at IS NULL AND archived_at IS NULL",
+                rusqlite::params![board_id, pool[i]],
+                |row| row.get(0),
+            )?;
+            if load < best {
+                best = load;
+                pick = i;
+            }
+        }
+    }
+    let agent = pool[pick].clone();
+
+    conn.execute(
+        "UPDATE tasks SET assigned_to = ?1, updated_at = datetime('now') WHERE id = ?2",
+        rusqlite::params![agent, task_id],
+    )?;
+    conn.execute(
+        "UPDATE columns SET auto_assign_cursor = ?1 WHERE id = ?2",
+        rusqlite::params![(pick + 1) as i64, column_id],
+    )?;
+
+    let assignment = Assignment {
+        task_id: task_id.to_string(),
+        column_id: column_id.to_string(),
+        assigned_to: agent,
+        previous,
+        strategy,
+    };
+    log_event(conn, task_id, "assigned", ACTOR, &assignment.event_data());
+    Ok(Some(assignment))
+}
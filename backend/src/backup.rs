@@ -0,0 +1,355 @@
+//! SQLite online backups: admin snapshot/restore routes and the optional
+//! scheduled backup task.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::Utc;
+use rocket::data::{Data, Limits, ToByteUnit};
+use rocket::http::{ContentType, Header, Status};
+use rocket::serde::json::Json;
+use rocket::tokio::fs::File;
+use rocket::State;
+use rusqlite::backup::{Backup, StepResult};
+use rusqlite::Connection;
+
+use crate::auth::AdminToken;
+use crate::db::{self, DbPool};
+use crate::models::*;
+use crate::routes::{not_found, with_db_blocking};
+
+/// Scheduled backups kept by default (`BACKUP_KEEP`).
+const DEFAULT_KEEP: usize = 7;
+
+/// File name prefixes; only scheduled backups are ever pruned.
+const MANUAL_PREFIX: &str = "kanban-";
+const SCHEDULED_PREFIX: &str = "kanban-auto-";
+const PRE_RESTORE_PREFIX: &str = "kanban-pre-restore-";
+
+/// Where backups are written and how often the scheduler takes one.
+#[derive(Debug, Clone)]
+pub struct BackupConfig {
+    pub dir: PathBuf,
+    /// None disables scheduled backups
+    pub interval: Option<Duration>,
+    /// Newest scheduled backups to keep; older ones are deleted
+    pub keep: usize,
+}
+
+impl BackupConfig {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        BackupConfig {
+            dir: dir.into(),
+            interval: None,
+            keep: DEFAULT_KEEP,
+        }
+    }
+
+    /// Read `BACKUP_DIR`, `BACKUP_INTERVAL_MINUTES` (0 or unset = no schedule) and `BACKUP_KEEP`.
+    pub fn from_env() -> Self {
+        let mut config = Self::new(std::env::var("BACKUP_DIR").unwrap_or_else(|_| "backups".to_string()));
+        config.interval = std::env::var("BACKUP_INTERVAL_MINUTES")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|m| *m > 0)
+            .map(|m| Duration::from_secs(m * 60));
+        config.keep = std::env::var("BACKUP_KEEP")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|n| *n >= 1)
+            .unwrap_or(DEFAULT_KEEP);
+        config
+    }
+}
+
+/// One per request, so the size of the file handle doesn't matter.
+#[allow(clippy::large_enum_variant)]
+#[derive(Responder)]
+pub enum BackupOutput {
+    Saved(Json<BackupResponse>),
+    Download(File, ContentType, Header<'static>),
+}
+
+// ============ Routes ============
+
+/// Snapshot the live database with SQLite's online backup API — requires admin key.
+/// Writes `kanban-<timestamp>.db` to `BACKUP_DIR`, or with `?download=true`
+/// streams the snapshot back instead of keeping it.
+#[post("/admin/backup?<download>")]
+pub async fn create_backup(
+    download: Option<bool>,
+    _admin: AdminToken,
+    config: &State<BackupConfig>,
+    db: &State<DbPool>,
+) -> Result<BackupOutput, (Status, Json<ApiError>)> {
+    let (pool, dir) = (db.inner().clone(), config.dir.clone());
+    let (file, path, bytes) = db::blocking(move || take_backup(&pool, &dir, MANUAL_PREFIX)).await?;
+
+    if !download.unwrap_or(false) {
+        return Ok(BackupOutput::Saved(Json(BackupResponse {
+            file,
+            path: path.display().to_string(),
+            bytes,
+            created_at: Utc::now().to_rfc3339(),
+        })));
+    }
+
+    // The open handle keeps the snapshot readable after it is unlinked
+    let handle = File::open(&path).await.map_err(|e| backup_failed(e.to_string()))?;
+    let _ = std::fs::remove_file(&path);
+    Ok(BackupOutput::Download(
+        handle,
+        ContentType::new("application", "vnd.sqlite3"),
+        Header::new("Content-Disposition", format!("attachment; filename=\"{}\"", file)),
+    ))
+}
+
+/// Replace the live database with a backup — requires admin key.
+/// Restores `?file=<name>` from `BACKUP_DIR`, or the request body (a raw SQLite
+/// file, capped by the `backup` data limit, default 1 GiB). The backup is checked
+/// before anything is touched, the current database is snapshotted first, and
+/// migrations run afterwards so older backups come up to date.
+#[post("/admin/restore?<file>", data = "<upload>")]
+pub async fn restore_backup(
+    file: Option<&str>,
+    upload: Data<'_>,
+    limits: &Limits,
+    _admin: AdminToken,
+    config: &State<BackupConfig>,
+    db: &State<DbPool>,
+) -> Result<Json<RestoreResponse>, (Status, Json<ApiError>)> {
+    let (source, restored_from, temporary) = match file {
+        Some(name) => {
+            if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+                return Err(validation(format!("Invalid backup file name '{}'", name)));
+            }
+            let path = config.dir.join(name);
+            if !path.is_file() {
+                return Err(not_found("Backup file"));
+            }
+            (path, name.to_string(), false)
+        }
+        None => {
+            std::fs::create_dir_all(&config.dir).map_err(|e| backup_failed(e.to_string()))?;
+            let path = config.dir.join(format!(".upload-{}.db", uuid::Uuid::new_v4()));
+            let limit = limits.get("backup").unwrap_or(1.gibibytes());
+            let written = upload
+                .open(limit)
+                .into_file(&path)
+                .await
+                .map_err(|e| backup_failed(format!("Could not read body: {}", e)))?;
+            if !written.is_complete() {
+                let _ = std::fs::remove_file(&path);
+                return Err((
+                    Status::PayloadTooLarge,
+                    Json(ApiError {
+                        error: format!("Backup is larger than the {} limit", limit),
+                        code: "PAYLOAD_TOO_LARGE".to_string(),
+                        status: 413,
+                    }),
+                ));
+            }
+            (path, "upload".to_string(), true)
+        }
+    };
+
+    let (pool, dir, restoring) = (db.inner().clone(), config.dir.clone(), source.clone());
+    let result = db::blocking(move || restore_from(&pool, &dir, &restoring, restored_from)).await;
+    if temporary {
+        let _ = std::fs::remove_file(&source);
+    }
+    result.map(Json)
+}
+
+// ============ Snapshot / Restore ============
+
+/// Snapshot the live database into `dir`. Returns (file name, path, size).
+fn take_backup(db: &DbPool, dir: &Path, prefix: &str) -> Result<(String, PathBuf, u64), (Status, Json<ApiError>)> {
+    std::fs::create_dir_all(dir).map_err(|e| backup_failed(e.to_string()))?;
+    let file = format!(
+        "{}{}-{}.db",
+        prefix,
+        Utc::now().format("%Y%m%dT%H%M%SZ"),
+        &uuid::Uuid::new_v4().simple().to_string()[..8]
+    );
+    let path = dir.join(&file);
+    with_db_blocking(db, |conn| snapshot(conn, &path).map_err(|e| backup_failed(e.to_string())))?;
+    let bytes = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    Ok((file, path, bytes))
+}
+
+/// Copy every page of `conn`'s main database to `dest` in one step, so the
+/// copy is consistent even while other connections write. With `DATABASE_KEY`
+/// set the copy is encrypted with the same key.
+pub fn snapshot(conn: &Connection, dest: &Path) -> rusqlite::Result<()> {
+    let mut out = db::open_connection(dest, db::database_key().as_deref())?;
+    copy_database(conn, &mut out)?;
+    // A standalone file shouldn't need -wal/-shm companions to be opened
+    out.query_row("PRAGMA journal_mode=DELETE", [], |_| Ok(()))?;
+    Ok(())
+}
+
+/// Copy all pages in a single backup step, retrying while the source is locked.
+fn copy_database(src: &Connection, dest: &mut Connection) -> rusqlite::Result<()> {
+    let backup = Backup::new(src, dest)?;
+    loop {
+        match backup.step(-1)? {
+            StepResult::Done => return Ok(()),
+            _ => std::thread::sleep(Duration::from_millis(10)),
+        }
+    }
+}
+
+fn restore_from(
+    db: &DbPool,
+    dir: &Path,
+    source: &Path,
+    restored_from: String,
+) -> Result<RestoreResponse, (Status, Json<ApiError>)> {
+    let (boards, tasks) = inspect(source).map_err(|e| {
+        (
+            Status::BadRequest,
+            Json(ApiError {
+                error: format!("Not a usable kanban backup: {}", e),
+                code: "INVALID_BACKUP".to_string(),
+                status: 400,
+            }),
+        )
+    })?;
+    let bytes = std::fs::metadata(source).map(|m| m.len()).unwrap_or(0);
+
+    let live_path: String = with_db_blocking(db, |conn| {
+        conn.query_row("SELECT file FROM pragma_database_list WHERE name = 'main'", [], |row| row.get(0))
+            .map_err(|e| backup_failed(e.to_string()))
+    })?;
+    if live_path.is_empty() {
+        return Err(backup_failed("in-memory databases cannot be restored".to_string()));
+    }
+    let (pre_restore_backup, _, _) = take_backup(db, dir, PRE_RESTORE_PREFIX)?;
+
+    let restore = || -> Result<(), String> {
+        let key = db::database_key();
+        let src = db::open_connection(source, key.as_deref()).map_err(|e| e.to_string())?;
+        let mut live = db::open_connection(&live_path, key.as_deref()).map_err(|e| e.to_string())?;
+        copy_database(&src, &mut live).map_err(|e| e.to_string())?;
+        db::migrate(&live)
+    };
+    restore().map_err(|e| backup_failed(format!("{} (previous database saved as {})", e, pre_restore_backup)))?;
+
+    Ok(RestoreResponse {
+        restored_from,
+        bytes,
+        boards,
+        tasks,
+        pre_restore_backup,
+    })
+}
+
+/// Check a candidate backup is an intact kanban database; returns (boards, tasks).
+fn inspect(path: &Path) -> Result<(i64, i64), String> {
+    let conn = db::open_connection(path, db::database_key().as_deref()).map_err(|e| e.to_string())?;
+    let count = |sql: &str| conn.query_row(sql, [], |row| row.get(0)).map_err(|e| e.to_string());
+    let check: String = conn
+        .query_row("PRAGMA quick_check", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    if check != "ok" {
+        return Err(format!("integrity check failed: {}", check));
+    }
+    Ok((count("SELECT COUNT(*) FROM boards")?, count("SELECT COUNT(*) FROM tasks")?))
+}
+
+// ============ Scheduled Backups ============
+
+/// Take a backup every `config.interval` (if set), keeping the newest `config.keep`.
+pub fn spawn_scheduled(db: DbPool, config: BackupConfig) {
+    let Some(interval) = config.interval else {
+        return;
+    };
+    println!("💾 Scheduled backups every {}m to {}", interval.as_secs() / 60, config.dir.display());
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await; // the first tick fires immediately
+        loop {
+            ticker.tick().await;
+            let (db, config) = (db.clone(), config.clone());
+            let result = tokio::task::spawn_blocking(move || {
+                take_backup(&db, &config.dir, SCHEDULED_PREFIX).map_err(|(_, e)| e.error.clone())?;
+                prune(&config.dir, config.keep).map_err(|e| e.to_string())
+            })
+            .await;
+            match result {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => eprintln!("⚠️  Scheduled backup failed: {}", e),
+                Err(e) => eprintln!("⚠️  Scheduled backup task panicked: {}", e),
+            }
+        }
+    });
+}
+
+/// Delete all but the newest `keep` scheduled backups in `dir`.
+fn prune(dir: &Path, keep: usize) -> std::io::Result<()> {
+    let mut scheduled: Vec<String> = std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.file_name().into_string().ok())
+        .filter(|name| name.starts_with(SCHEDULED_PREFIX) && name.ends_with(".db"))
+        .collect();
+    // Names embed a sortable UTC timestamp
+    scheduled.sort();
+    let excess = scheduled.len().saturating_sub(keep);
+    for name in &scheduled[..excess] {
+        std::fs::remove_file(dir.join(name))?;
+    }
+    Ok(())
+}
+
+fn backup_failed(error: String) -> (Status, Json<ApiError>) {
+    (
+        Status::InternalServerError,
+        Json(ApiError {
+            error: format!("Backup failed: {}", error),
+            code: "BACKUP_FAILED".to_string(),
+            status: 500,
+        }),
+    )
+}
+
+fn validation(error: String) -> (Status, Json<ApiError>) {
+    (
+        Status::BadRequest,
+        Json(ApiError {
+            error,
+            code: "VALIDATION_ERROR".to_string(),
+            status: 400,
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prune_keeps_newest_scheduled_backups() {
+        let dir = std::env::temp_dir().join(format!("kanban_prune_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let names = [
+            "kanban-auto-20260101T000000Z-aaaaaaaa.db",
+            "kanban-auto-20260102T000000Z-bbbbbbbb.db",
+            "kanban-auto-20260103T000000Z-cccccccc.db",
+            "kanban-20260101T000000Z-dddddddd.db",
+        ];
+        for name in names {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+
+        prune(&dir, 2).unwrap();
+
+        let mut left: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        left.sort();
+        assert_eq!(left, vec![names[3], names[1], names[2]]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,88 @@
+//! Board- and column-level events (`board.*`, `column.*`). They have no task
+//! to hang off, so instead of `task_events` they're kept in `board_events`,
+//! and go out on the event bus (SSE and webhooks) like task events.
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::Connection;
+
+use crate::access;
+use crate::auth::BoardToken;
+use crate::db::DbPool;
+use crate::events::{BoardEvent, EventBus};
+use crate::models::*;
+use crate::routes::{db_error, with_db};
+
+/// Record a structural event on the board and emit it. `actor` is stored with
+/// the event and added to its data.
+pub fn emit(
+    conn: &Connection,
+    bus: &EventBus,
+    board_id: &str,
+    event: &str,
+    actor: &str,
+    mut data: serde_json::Value,
+) -> rusqlite::Result<()> {
+    data["actor"] = serde_json::json!(actor);
+    let data_str = serde_json::to_string(&data).unwrap_or_else(|_| "{}".to_string());
+    conn.execute(
+        "INSERT INTO board_events (board_id, event_type, actor, data) VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![board_id, event, actor, data_str],
+    )?;
+    bus.emit(
+        conn,
+        BoardEvent {
+            event: event.to_string(),
+            board_id: board_id.to_string(),
+            data,
+        },
+    )
+}
+
+/// The board's structural events — public, like the activity feed. Newest
+/// first, or oldest first from a `?after=<seq>` cursor. `limit` defaults to 50
+/// (max 200).
+#[get("/boards/<board_id>/board-events?<after>&<limit>")]
+pub async fn list_board_events(
+    board_id: &str,
+    after: Option<i64>,
+    limit: Option<u32>,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+) -> Result<Json<Vec<BoardEventItem>>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        let limit = limit.unwrap_or(50).min(200);
+        let sql = match after {
+            Some(_) => {
+                "SELECT seq, event_type, actor, data, created_at FROM board_events
+                 WHERE board_id = ?1 AND seq > ?2 ORDER BY seq ASC LIMIT ?3"
+            }
+            None => {
+                "SELECT seq, event_type, actor, data, created_at FROM board_events
+                 WHERE board_id = ?1 AND ?2 IS NULL ORDER BY seq DESC LIMIT ?3"
+            }
+        };
+        let events = conn
+            .prepare(sql)
+            .map_err(|e| db_error(&e.to_string()))?
+            .query_map(rusqlite::params![board_id, after, limit], |row| {
+                let data: String = row.get(3)?;
+                Ok(BoardEventItem {
+                    seq: row.get(0)?,
+                    event: row.get(1)?,
+                    actor: row.get(2)?,
+                    data: serde_json::from_str(&data).unwrap_or(serde_json::json!({})),
+                    created_at: row.get(4)?,
+                })
+            })
+            .map_err(|e| db_error(&e.to_string()))?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| db_error(&e.to_string()))?;
+        Ok(Json(events))
+    })
+    .await
+}
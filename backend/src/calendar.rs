@@ -0,0 +1,215 @@
+//! A board's due dates as an iCalendar (RFC 5545) feed, so Google Calendar,
+//! Outlook and friends can subscribe to it. Each task with a `due_at` is one
+//! event: a date-only due date is an all-day event, a timestamp is a
+//! zero-length event at that instant (UTC).
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use rocket::http::{ContentType, Status};
+use rocket::serde::json::Json;
+use rocket::State;
+
+use crate::access;
+use crate::auth::BoardToken;
+use crate::db::DbPool;
+use crate::feed::RequestOrigin;
+use crate::models::*;
+use crate::routes::{db_error, normalize_labels, task_key, with_db};
+
+/// When a task is due.
+#[derive(Debug, PartialEq)]
+enum Due {
+    Day(NaiveDate),
+    At(DateTime<Utc>),
+}
+
+/// Parse a stored `due_at`: RFC 3339, SQLite's `YYYY-MM-DD HH:MM:SS` (UTC),
+/// or a bare date. Anything else isn't put on the calendar.
+fn parse_due(due_at: &str) -> Option<Due> {
+    let due_at = due_at.trim();
+    if let Ok(at) = DateTime::parse_from_rfc3339(due_at) {
+        return Some(Due::At(at.with_timezone(&Utc)));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"] {
+        if let Ok(at) = NaiveDateTime::parse_from_str(due_at, format) {
+            return Some(Due::At(at.and_utc()));
+        }
+    }
+    NaiveDate::parse_from_str(due_at, "%Y-%m-%d").ok().map(Due::Day)
+}
+
+/// Escape a TEXT value: backslashes, commas, semicolons and newlines.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Append a content line, folded at 75 octets as RFC 5545 requires.
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn utc_stamp(at: &DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Due dates on the board as `text/calendar` — public, no auth required.
+/// `?label=` and `?assignee=` narrow it to one label or one assignee.
+/// Archived tasks are left out.
+#[get("/boards/<board_id>/calendar.ics?<label>&<assignee>")]
+pub async fn board_calendar(
+    board_id: &str,
+    label: Option<&str>,
+    assignee: Option<&str>,
+    origin: RequestOrigin,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+) -> Result<(ContentType, String), (Status, Json<ApiError>)> {
+    let label = label
+        .map(|l| normalize_labels(&[l.to_string()]))
+        .and_then(|l| l.into_iter().next());
+    let assignee = assignee.map(str::trim).filter(|a| !a.is_empty());
+    let board_url = format!("{}/board/{}", origin.0, board_id);
+
+    let (assignee, board_id) = (assignee.map(str::to_owned), board_id.to_owned());
+    with_db(db, move |conn| {
+        let (assignee, board_id) = (assignee.as_deref(), board_id.as_str());
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        let (name, prefix): (String, Option<String>) = conn
+            .query_row(
+                "SELECT name, task_prefix FROM boards WHERE id = ?1",
+                rusqlite::params![board_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+
+        let mut out = String::new();
+        for line in [
+            "BEGIN:VCALENDAR",
+            "VERSION:2.0",
+            "PRODID:-//Humans Not Required//Kanban//EN",
+            "CALSCALE:GREGORIAN",
+            "METHOD:PUBLISH",
+        ] {
+            push_line(&mut out, line);
+        }
+        push_line(&mut out, &format!("X-WR-CALNAME:{}", escape(&format!("{} — due dates", name))));
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT t.id, t.title, t.description, t.due_at, t.assigned_to, t.labels, t.number,
+                        t.completed_at, t.updated_at, c.name
+                 FROM tasks t
+                 JOIN columns c ON c.id = t.column_id
+                 WHERE t.board_id = ?1 AND t.due_at IS NOT NULL AND t.due_at != '' AND t.archived_at IS NULL
+                   AND (?2 IS NULL OR EXISTS (SELECT 1 FROM json_each(t.labels) WHERE json_each.value = ?2))
+                   AND (?3 IS NULL OR t.assigned_to = ?3 COLLATE NOCASE)
+                 ORDER BY t.due_at ASC, t.rowid ASC",
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        let rows = stmt
+            .query_map(rusqlite::params![board_id, label, assignee], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, String>(5)?,
+                    row.get::<_, Option<i64>>(6)?,
+                    row.get::<_, Option<String>>(7)?,
+                    row.get::<_, String>(8)?,
+                    row.get::<_, String>(9)?,
+                ))
+            })
+            .map_err(|e| db_error(&e.to_string()))?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| db_error(&e.to_string()))?;
+
+        for (task_id, title, description, due_at, assigned_to, labels, number, completed_at, updated_at, column) in rows {
+            let Some(due) = parse_due(&due_at) else {
+                continue;
+            };
+            let key = number.map(|n| task_key(prefix.as_deref(), n));
+            let summary = match &key {
+                Some(key) => format!("{} {}", key, title),
+                None => title,
+            };
+            let summary = if completed_at.is_some() { format!("✓ {}", summary) } else { summary };
+            let url = format!("{}?task={}", board_url, task_id);
+            let mut details = vec![format!("Column: {}", column)];
+            if let Some(ref assignee) = assigned_to {
+                details.push(format!("Assigned to: {}", assignee));
+            }
+            if !description.is_empty() {
+                details.push(String::new());
+                details.push(description);
+            }
+            details.push(String::new());
+            details.push(url.clone());
+            let stamp = NaiveDateTime::parse_from_str(&updated_at, "%Y-%m-%d %H:%M:%S")
+                .map(|t| t.and_utc())
+                .unwrap_or_else(|_| Utc::now());
+            let labels: Vec<String> = serde_json::from_str(&labels).unwrap_or_default();
+
+            push_line(&mut out, "BEGIN:VEVENT");
+            push_line(&mut out, &format!("UID:{}@kanban", task_id));
+            push_line(&mut out, &format!("DTSTAMP:{}", utc_stamp(&stamp)));
+            match due {
+                Due::Day(day) => {
+                    push_line(&mut out, &format!("DTSTART;VALUE=DATE:{}", day.format("%Y%m%d")));
+                    push_line(&mut out, &format!("DTEND;VALUE=DATE:{}", (day + Duration::days(1)).format("%Y%m%d")));
+                }
+                Due::At(at) => {
+                    push_line(&mut out, &format!("DTSTART:{}", utc_stamp(&at)));
+                    push_line(&mut out, &format!("DTEND:{}", utc_stamp(&at)));
+                }
+            }
+            push_line(&mut out, &format!("SUMMARY:{}", escape(&summary)));
+            push_line(&mut out, &format!("DESCRIPTION:{}", escape(&details.join("\n"))));
+            push_line(&mut out, &format!("URL:{}", url));
+            if !labels.is_empty() {
+                let categories: Vec<String> = labels.iter().map(|l| escape(l)).collect();
+                push_line(&mut out, &format!("CATEGORIES:{}", categories.join(",")));
+            }
+            push_line(&mut out, "END:VEVENT");
+        }
+        push_line(&mut out, "END:VCALENDAR");
+
+        Ok((ContentType::Calendar, out))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_due_and_fold() {
+        assert_eq!(parse_due("2026-03-01"), NaiveDate::from_ymd_opt(2026, 3, 1).map(Due::Day));
+        let noon = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap().and_hms_opt(12, 0, 0).unwrap().and_utc();
+        assert_eq!(parse_due("2026-03-01T14:00:00+02:00"), Some(Due::At(noon)));
+        assert_eq!(parse_due("2026-03-01 12:00:00"), Some(Due::At(noon)));
+        assert_eq!(parse_due("next tuesday"), None);
+
+        assert_eq!(escape("a, b; c\\d\ne"), "a\\, b\\; c\\\\d\\ne");
+        let mut out = String::new();
+        push_line(&mut out, &format!("SUMMARY:{}", "é".repeat(40)));
+        let lines: Vec<&str> = out.trim_end().split("\r\n").collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|l| l.len() <= 75));
+        assert!(lines[1].starts_with(' '));
+    }
+}
@@ -2,14 +2,36 @@ use rocket::serde::json::Json;
 use rocket::Request;
 use serde_json::json;
 
+use crate::auth;
+
 #[catch(401)]
-pub fn unauthorized(_req: &Request) -> Json<serde_json::Value> {
+pub async fn unauthorized(req: &Request<'_>) -> Json<serde_json::Value> {
+    if auth::presents_expired_key(req).await {
+        return Json(json!({
+            "error": "TOKEN_EXPIRED",
+            "message": "This key has expired. Ask the board's admins for a new one."
+        }));
+    }
     Json(json!({
         "error": "UNAUTHORIZED",
         "message": "Missing or invalid management key. Use Authorization: Bearer YOUR_KEY, X-API-Key header, or ?key= query param."
     }))
 }
 
+#[catch(403)]
+pub async fn forbidden(req: &Request<'_>) -> Json<serde_json::Value> {
+    if auth::outside_ip_allowlist(req).await {
+        return Json(json!({
+            "error": "IP_NOT_ALLOWED",
+            "message": "This board only accepts writes from its IP allowlist, which doesn't include this address."
+        }));
+    }
+    Json(json!({
+        "error": "FORBIDDEN",
+        "message": "The provided key does not grant access to this resource."
+    }))
+}
+
 #[catch(404)]
 pub fn not_found(_req: &Request) -> Json<serde_json::Value> {
     Json(json!({
@@ -18,6 +40,14 @@ pub fn not_found(_req: &Request) -> Json<serde_json::Value> {
     }))
 }
 
+#[catch(413)]
+pub fn payload_too_large(_req: &Request) -> Json<serde_json::Value> {
+    Json(json!({
+        "error": "PAYLOAD_TOO_LARGE",
+        "message": "The request body is larger than this server accepts."
+    }))
+}
+
 #[catch(422)]
 pub fn unprocessable(_req: &Request) -> Json<serde_json::Value> {
     Json(json!({
@@ -0,0 +1,249 @@
+//! Collaborators: keys of their own for the people and agents working on a
+//! board, each with a role, so the manage key needn't be handed around.
+//! Admins add and remove editors and viewers; only owners (the manage key,
+//! or collaborators made owners) can grant or take away admin and owner.
+//! Keys are shown once and stored hashed, like the manage key.
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::access::{self, BoardRole};
+use crate::auth::{Actor, BoardToken};
+use crate::db::{hash_key, DbPool};
+use crate::models::*;
+use crate::routes::{db_error, not_found, with_db};
+
+const MAX_NAME_CHARS: usize = 100;
+
+fn check_name(name: &str) -> Result<String, (Status, Json<ApiError>)> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_CHARS {
+        return Err((
+            Status::BadRequest,
+            Json(ApiError {
+                error: format!("name must be 1-{} characters", MAX_NAME_CHARS),
+                code: "INVALID_INPUT".to_string(),
+                status: 400,
+            }),
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// Collaborators can do at least `viewer`; read-only keys are share keys.
+fn check_role(role: BoardRole) -> Result<(), (Status, Json<ApiError>)> {
+    if role == BoardRole::Read {
+        return Err((
+            Status::BadRequest,
+            Json(ApiError {
+                error: "Collaborators are viewers or above; create read-only keys with POST /boards/{id}/keys".to_string(),
+                code: "INVALID_INPUT".to_string(),
+                status: 400,
+            }),
+        ));
+    }
+    Ok(())
+}
+
+/// Admins may only hand out and manage roles below their own.
+pub(crate) fn require_can_manage(caller: BoardRole, role: BoardRole) -> Result<(), (Status, Json<ApiError>)> {
+    if caller == BoardRole::Owner || role < BoardRole::Admin {
+        return Ok(());
+    }
+    Err((
+        Status::Forbidden,
+        Json(ApiError {
+            error: format!("Only owners can manage {} collaborators", role.as_str()),
+            code: "INSUFFICIENT_ROLE".to_string(),
+            status: 403,
+        }),
+    ))
+}
+
+fn collaborator_from_row(board_id: &str, row: &rusqlite::Row) -> rusqlite::Result<CollaboratorResponse> {
+    let role: String = row.get(2)?;
+    Ok(CollaboratorResponse {
+        id: row.get(0)?,
+        board_id: board_id.to_string(),
+        name: row.get(1)?,
+        role: BoardRole::parse(&role).unwrap_or(BoardRole::Viewer),
+        key: None,
+        created_by: row.get(3)?,
+        created_at: row.get(4)?,
+        expires_at: row.get(5)?,
+    })
+}
+
+fn load(conn: &Connection, board_id: &str, collaborator_id: &str) -> Result<CollaboratorResponse, (Status, Json<ApiError>)> {
+    conn.query_row(
+        "SELECT id, name, role, created_by, created_at, expires_at FROM board_members WHERE id = ?1 AND board_id = ?2",
+        rusqlite::params![collaborator_id, board_id],
+        |row| collaborator_from_row(board_id, row),
+    )
+    .optional()
+    .map_err(|e| db_error(&e.to_string()))?
+    .ok_or_else(|| not_found("Collaborator"))
+}
+
+/// The board's collaborators — requires admin. Keys aren't returned.
+#[get("/boards/<board_id>/collaborators")]
+pub async fn list_collaborators(
+    board_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<Vec<CollaboratorResponse>>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Admin)?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, name, role, created_by, created_at, expires_at FROM board_members
+                 WHERE board_id = ?1 ORDER BY created_at ASC, rowid ASC",
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        let collaborators = stmt
+            .query_map(rusqlite::params![board_id], |row| collaborator_from_row(board_id, row))
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(Json(collaborators))
+    })
+    .await
+}
+
+/// What the calling key may do on the board — any valid key.
+#[get("/boards/<board_id>/collaborators/me")]
+pub async fn my_board_role(
+    board_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<BoardRoleResponse>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        let token_hash = hash_key(&token.0);
+        let role = access::board_role(conn, board_id, &token_hash)?;
+        let member: Option<(String, String)> = conn
+            .query_row(
+                "SELECT id, name FROM board_members WHERE board_id = ?1 AND key_hash = ?2",
+                rusqlite::params![board_id, token_hash],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| db_error(&e.to_string()))?;
+        let (collaborator_id, name) = member.unzip();
+        Ok(Json(BoardRoleResponse {
+            board_id: board_id.to_string(),
+            role,
+            collaborator_id,
+            name,
+        }))
+    })
+    .await
+}
+
+/// Add a collaborator with their own key — requires admin (owner to add
+/// admins or owners). The key is in the response only.
+#[post("/boards/<board_id>/collaborators", format = "json", data = "<req>")]
+pub async fn add_collaborator(
+    board_id: &str,
+    req: Json<CreateCollaboratorRequest>,
+    token: BoardToken,
+    actor: Actor,
+    db: &State<DbPool>,
+) -> Result<Json<CollaboratorResponse>, (Status, Json<ApiError>)> {
+    let req = req.into_inner();
+    let name = check_name(&req.name)?;
+    check_role(req.role)?;
+    let expires_at = access::key_expiry(req.expires_at.as_deref(), req.ttl_seconds)?;
+
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        let caller = access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Admin)?;
+        require_can_manage(caller, req.role)?;
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let key = format!("kb_{}", uuid::Uuid::new_v4().to_string().replace('-', ""));
+        conn.execute(
+            "INSERT INTO board_members (id, board_id, name, role, key_hash, created_by, expires_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![id, board_id, name, req.role.as_str(), hash_key(&key), actor.name(), expires_at],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        let mut collaborator = load(conn, board_id, &id)?;
+        collaborator.key = Some(key);
+        Ok(Json(collaborator))
+    })
+    .await
+}
+
+/// Rename a collaborator or change their role — requires admin (owner when
+/// the collaborator is, or would become, an admin or owner).
+#[patch("/boards/<board_id>/collaborators/<collaborator_id>", format = "json", data = "<req>")]
+pub async fn update_collaborator(
+    board_id: &str,
+    collaborator_id: &str,
+    req: Json<UpdateCollaboratorRequest>,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<CollaboratorResponse>, (Status, Json<ApiError>)> {
+    let req = req.into_inner();
+    let name = req.name.as_deref().map(check_name).transpose()?;
+    req.role.map(check_role).transpose()?;
+
+    let (board_id, collaborator_id) = (board_id.to_owned(), collaborator_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, collaborator_id) = (board_id.as_str(), collaborator_id.as_str());
+        let caller = access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Admin)?;
+        let existing = load(conn, board_id, collaborator_id)?;
+        require_can_manage(caller, existing.role)?;
+        if let Some(role) = req.role {
+            require_can_manage(caller, role)?;
+        }
+        conn.execute(
+            "UPDATE board_members SET name = COALESCE(?1, name), role = COALESCE(?2, role) WHERE id = ?3",
+            rusqlite::params![name, req.role.map(BoardRole::as_str), collaborator_id],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        load(conn, board_id, collaborator_id).map(Json)
+    })
+    .await
+}
+
+/// Remove a collaborator; their key stops working at once — requires admin
+/// (owner for admins and owners). Any collaborator may remove themselves.
+#[delete("/boards/<board_id>/collaborators/<collaborator_id>")]
+pub async fn remove_collaborator(
+    board_id: &str,
+    collaborator_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
+    let (board_id, collaborator_id) = (board_id.to_owned(), collaborator_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, collaborator_id) = (board_id.as_str(), collaborator_id.as_str());
+        let token_hash = hash_key(&token.0);
+        let caller = access::board_role(conn, board_id, &token_hash)?;
+        let existing = load(conn, board_id, collaborator_id)?;
+        let own_key = conn
+            .query_row(
+                "SELECT 1 FROM board_members WHERE id = ?1 AND key_hash = ?2",
+                rusqlite::params![collaborator_id, token_hash],
+                |_| Ok(()),
+            )
+            .optional()
+            .map_err(|e| db_error(&e.to_string()))?
+            .is_some();
+        if !own_key {
+            access::require_role(conn, board_id, &token_hash, BoardRole::Admin)?;
+            require_can_manage(caller, existing.role)?;
+        }
+        conn.execute("DELETE FROM board_members WHERE id = ?1", rusqlite::params![collaborator_id])
+            .map_err(|e| db_error(&e.to_string()))?;
+        Ok(Json(serde_json::json!({"deleted": true, "id": collaborator_id})))
+    })
+    .await
+}
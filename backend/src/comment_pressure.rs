@@ -0,0 +1,295 @@
+//! Soft quota on comment volume. Past a board's `comment_pressure_threshold`
+//! a task carries a `comment_pressure` hint and `task.comment_pressure` fires
+//! once, suggesting consolidation. Collapsing moves all but the newest
+//! comments into a plain-text transcript kept with the task, so long agent
+//! threads stay readable without losing history.
+
+use rocket::http::{ContentType, Status};
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::{Connection, TransactionBehavior};
+
+use crate::access::{self, BoardRole};
+use crate::auth::{Actor, BoardToken};
+use crate::db::{hash_key, DbPool};
+use crate::event_types;
+use crate::events::{BoardEvent, EventBus};
+use crate::models::*;
+use crate::routes::{db_error, log_event, not_found, with_db};
+
+/// Highest threshold a board can configure.
+pub const MAX_THRESHOLD: i64 = 10_000;
+/// Comments left on the task by a collapse unless the request says otherwise.
+const DEFAULT_KEEP: usize = 5;
+
+/// The hint for a task with `comment_count` comments, if over `threshold`.
+pub fn pressure(board_id: &str, task_id: &str, comment_count: i64, threshold: Option<i64>) -> Option<CommentPressure> {
+    let threshold = threshold.filter(|t| *t > 0 && comment_count > *t)?;
+    Some(CommentPressure {
+        comment_count,
+        threshold,
+        hint: format!(
+            "This task has {} comments (board threshold {}). Summarize the thread in a comment, then \
+             POST /api/v1/boards/{}/tasks/{}/comments/collapse to move older comments into a transcript.",
+            comment_count, threshold, board_id, task_id
+        ),
+    })
+}
+
+/// After a comment is posted: the hint for the task, and `task.comment_pressure`
+/// when this comment is the one that crossed the threshold.
+pub fn after_comment(
+    conn: &Connection,
+    bus: &EventBus,
+    board_id: &str,
+    task_id: &str,
+) -> rusqlite::Result<Option<CommentPressure>> {
+    let Ok((count, threshold)) = conn.query_row(
+        "SELECT (SELECT COUNT(*) FROM task_events WHERE task_id = ?2 AND event_type = 'comment'),
+                comment_pressure_threshold
+         FROM boards WHERE id = ?1",
+        rusqlite::params![board_id, task_id],
+        |row| Ok((row.get::<_, i64>(0)?, row.get::<_, Option<i64>>(1)?)),
+    ) else {
+        return Ok(None);
+    };
+    let Some(pressure) = pressure(board_id, task_id, count, threshold) else {
+        return Ok(None);
+    };
+    if count == pressure.threshold + 1 {
+        bus.emit(
+            conn,
+            BoardEvent {
+                event: event_types::TASK_COMMENT_PRESSURE.to_string(),
+                board_id: board_id.to_string(),
+                data: serde_json::json!({
+                    "task_id": task_id,
+                    "comment_count": count,
+                    "threshold": pressure.threshold,
+                }),
+            },
+        )?;
+    }
+    Ok(Some(pressure))
+}
+
+fn transcript_url(board_id: &str, task_id: &str, transcript_id: &str) -> String {
+    format!("/api/v1/boards/{}/tasks/{}/transcripts/{}", board_id, task_id, transcript_id)
+}
+
+/// One comment as a transcript line block: `[time] actor:` then the message.
+fn transcript_entry(actor: &str, created_at: &str, data: &str) -> String {
+    let data: serde_json::Value = serde_json::from_str(data).unwrap_or_default();
+    let message = data["message"].as_str().unwrap_or_default();
+    format!("[{}] {}:\n{}\n", created_at, actor, message)
+}
+
+const TRANSCRIPT_COLUMNS: &str = "id, task_id, comment_count, first_comment_at, last_comment_at, created_by, created_at";
+
+fn row_to_transcript(board_id: &str, row: &rusqlite::Row) -> rusqlite::Result<CommentTranscriptResponse> {
+    let id: String = row.get(0)?;
+    let task_id: String = row.get(1)?;
+    Ok(CommentTranscriptResponse {
+        url: transcript_url(board_id, &task_id, &id),
+        id,
+        task_id,
+        comment_count: row.get(2)?,
+        first_comment_at: row.get(3)?,
+        last_comment_at: row.get(4)?,
+        created_by: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+fn require_task(conn: &Connection, board_id: &str, task_id: &str) -> Result<(), (Status, Json<ApiError>)> {
+    conn.query_row(
+        "SELECT 1 FROM tasks WHERE id = ?1 AND board_id = ?2",
+        rusqlite::params![task_id, board_id],
+        |_| Ok(()),
+    )
+    .map_err(|_| not_found("Task"))
+}
+
+/// Collapse all but the newest `keep` comments into a transcript — requires
+/// manage key.
+#[post("/boards/<board_id>/tasks/<task_id>/comments/collapse", format = "json", data = "<req>")]
+pub async fn collapse_comments(
+    board_id: &str,
+    task_id: &str,
+    req: Option<Json<CollapseCommentsRequest>>,
+    token: BoardToken,
+    actor: Actor,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<CollapseCommentsResponse>, (Status, Json<ApiError>)> {
+    let req = req.map(|r| r.into_inner()).unwrap_or_default();
+    let keep = req.keep.unwrap_or(DEFAULT_KEEP);
+    let actor = actor.or_body(req.actor_name.as_deref());
+
+    let (board, task, collapser, bus) = (board_id.to_owned(), task_id.to_owned(), actor.clone(), bus.inner().clone());
+    let response = with_db(db, move |conn| {
+        let (board_id, task_id, actor) = (board.as_str(), task.as_str(), &collapser);
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+        access::require_display_name(conn, board_id, actor)?;
+        require_task(conn, board_id, task_id)?;
+
+        let tx = rusqlite::Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+            .map_err(|e| db_error(&e.to_string()))?;
+        let comments: Vec<(String, String, String, String)> = tx
+            .prepare(
+                "SELECT id, actor, data, created_at FROM task_events
+                 WHERE task_id = ?1 AND event_type = 'comment'
+                 ORDER BY created_at ASC, seq ASC",
+            )
+            .map_err(|e| db_error(&e.to_string()))?
+            .query_map(rusqlite::params![task_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+            .map_err(|e| db_error(&e.to_string()))?
+            .collect::<rusqlite::Result<_>>()
+            .map_err(|e| db_error(&e.to_string()))?;
+        if comments.len() <= keep {
+            return Err((
+                Status::BadRequest,
+                Json(ApiError {
+                    error: format!("Task has {} comment(s); nothing older than the newest {} to collapse", comments.len(), keep),
+                    code: "NOTHING_TO_COLLAPSE".to_string(),
+                    status: 400,
+                }),
+            ));
+        }
+
+        let older = &comments[..comments.len() - keep];
+        let transcript: String = older
+            .iter()
+            .map(|(_, actor, data, created_at)| transcript_entry(actor, created_at, data))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let transcript_id = uuid::Uuid::new_v4().to_string();
+        tx.execute(
+            "INSERT INTO comment_transcripts
+                (id, task_id, board_id, comment_count, first_comment_at, last_comment_at, transcript, created_by)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            rusqlite::params![
+                transcript_id,
+                task_id,
+                board_id,
+                older.len() as i64,
+                older[0].3,
+                older[older.len() - 1].3,
+                transcript,
+                actor.name(),
+            ],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        for (id, ..) in older {
+            tx.execute("DELETE FROM task_events WHERE id = ?1", rusqlite::params![id])
+                .map_err(|e| db_error(&e.to_string()))?;
+        }
+        log_event(
+            &tx,
+            task_id,
+            "comments_collapsed",
+            actor.name(),
+            &serde_json::json!({"transcript_id": transcript_id, "collapsed": older.len()}),
+        );
+        let transcript = tx
+            .query_row(
+                &format!("SELECT {} FROM comment_transcripts WHERE id = ?1", TRANSCRIPT_COLUMNS),
+                rusqlite::params![transcript_id],
+                |row| row_to_transcript(board_id, row),
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        bus.emit(
+            &tx,
+            BoardEvent {
+                event: event_types::TASK_COMMENTS_COLLAPSED.to_string(),
+                board_id: board_id.to_string(),
+                data: serde_json::json!({
+                    "task_id": task_id,
+                    "transcript_id": transcript.id,
+                    "collapsed": older.len(),
+                    "remaining": keep,
+                    "actor": actor.name(),
+                }),
+            },
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        crate::events::commit(tx).map_err(|e| db_error(&e.to_string()))?;
+
+        Ok(CollapseCommentsResponse {
+            task_id: task_id.to_string(),
+            collapsed: older.len() as i64,
+            remaining: keep as i64,
+            transcript,
+        })
+    })
+    .await?;
+    Ok(Json(response))
+}
+
+/// A task's comment transcripts, newest first — public.
+#[get("/boards/<board_id>/tasks/<task_id>/transcripts")]
+pub async fn list_transcripts(
+    board_id: &str,
+    task_id: &str,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+) -> Result<Json<Vec<CommentTranscriptResponse>>, (Status, Json<ApiError>)> {
+    let (board_id, task_id) = (board_id.to_owned(), task_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, task_id) = (board_id.as_str(), task_id.as_str());
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        require_task(conn, board_id, task_id)?;
+        let transcripts = conn
+            .prepare(&format!(
+                "SELECT {} FROM comment_transcripts WHERE task_id = ?1 ORDER BY created_at DESC, rowid DESC",
+                TRANSCRIPT_COLUMNS
+            ))
+            .map_err(|e| db_error(&e.to_string()))?
+            .query_map(rusqlite::params![task_id], |row| row_to_transcript(board_id, row))
+            .map_err(|e| db_error(&e.to_string()))?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| db_error(&e.to_string()))?;
+        Ok(Json(transcripts))
+    })
+    .await
+}
+
+/// Download a transcript as plain text — public.
+#[get("/boards/<board_id>/tasks/<task_id>/transcripts/<transcript_id>")]
+pub async fn get_transcript(
+    board_id: &str,
+    task_id: &str,
+    transcript_id: &str,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+) -> Result<(ContentType, String), (Status, Json<ApiError>)> {
+    let (board_id, task_id, transcript_id) = (board_id.to_owned(), task_id.to_owned(), transcript_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, task_id, transcript_id) = (board_id.as_str(), task_id.as_str(), transcript_id.as_str());
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        let transcript: String = conn
+            .query_row(
+                "SELECT transcript FROM comment_transcripts WHERE id = ?1 AND task_id = ?2 AND board_id = ?3",
+                rusqlite::params![transcript_id, task_id, board_id],
+                |row| row.get(0),
+            )
+            .map_err(|_| not_found("Transcript"))?;
+        Ok((ContentType::Plain, transcript))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pressure_only_past_threshold() {
+        assert!(pressure("b", "t", 10, Some(10)).is_none());
+        assert!(pressure("b", "t", 50, None).is_none());
+        let hint = pressure("b", "t", 11, Some(10)).unwrap();
+        assert_eq!((hint.comment_count, hint.threshold), (11, 10));
+        assert!(hint.hint.contains("/boards/b/tasks/t/comments/collapse"));
+    }
+}
@@ -0,0 +1,247 @@
+//! Editing and deleting comments. A comment is a `comment` task event; an
+//! edit rewrites its message in place and marks it with `edited_at` /
+//! `edited_by`, and a delete removes it. Either way the text it had before
+//! is kept in `comment_revisions`, so the thread's history survives.
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::{Connection, TransactionBehavior};
+
+use crate::access::{self, BoardRole};
+use crate::auth::{Actor, BoardToken};
+use crate::db::{hash_key, DbPool};
+use crate::event_types;
+use crate::events::{BoardEvent, EventBus};
+use crate::models::*;
+use crate::routes::{db_error, extract_mentions, log_event, not_found, with_db};
+
+/// Change names stored with each revision.
+const EDITED: &str = "edited";
+const DELETED: &str = "deleted";
+
+/// The comment's stored data and creation time.
+fn load_comment(
+    conn: &Connection,
+    board_id: &str,
+    task_id: &str,
+    comment_id: &str,
+) -> Result<(serde_json::Value, String, String), (Status, Json<ApiError>)> {
+    let (data, actor, created_at): (String, String, String) = conn
+        .query_row(
+            "SELECT te.data, te.actor, te.created_at FROM task_events te
+             JOIN tasks t ON t.id = te.task_id
+             WHERE te.id = ?1 AND te.task_id = ?2 AND t.board_id = ?3 AND te.event_type = 'comment'",
+            rusqlite::params![comment_id, task_id, board_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|_| not_found("Comment"))?;
+    Ok((serde_json::from_str(&data).unwrap_or_default(), actor, created_at))
+}
+
+fn save_revision(
+    conn: &Connection,
+    board_id: &str,
+    task_id: &str,
+    comment_id: &str,
+    data: &serde_json::Value,
+    change: &str,
+    changed_by: &str,
+) -> Result<(), (Status, Json<ApiError>)> {
+    conn.execute(
+        "INSERT INTO comment_revisions (id, comment_id, task_id, board_id, data, change, changed_by)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        rusqlite::params![
+            uuid::Uuid::new_v4().to_string(),
+            comment_id,
+            task_id,
+            board_id,
+            data.to_string(),
+            change,
+            changed_by
+        ],
+    )
+    .map(|_| ())
+    .map_err(|e| db_error(&e.to_string()))
+}
+
+/// Edit a comment's message — requires manage key. Mentions are re-read from
+/// the new text; attachments stay. The previous text goes into the history.
+#[allow(clippy::too_many_arguments)]
+#[patch("/boards/<board_id>/tasks/<task_id>/comments/<comment_id>", format = "json", data = "<req>")]
+pub async fn edit_comment(
+    board_id: &str,
+    task_id: &str,
+    comment_id: &str,
+    req: Json<EditCommentRequest>,
+    token: BoardToken,
+    actor: Actor,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<TaskEventResponse>, (Status, Json<ApiError>)> {
+    let req = req.into_inner();
+    let actor = actor.or_body(req.actor_name.as_deref());
+    let message = req.message.as_str();
+
+    let (board, comment, task, bus) = (board_id.to_owned(), comment_id.to_owned(), task_id.to_owned(), bus.inner().clone());
+    let (editor, text) = (actor.clone(), message.to_owned());
+    let response = with_db(db, move |conn| {
+        let (board_id, comment_id, task_id) = (board.as_str(), comment.as_str(), task.as_str());
+        let (actor, message) = (&editor, text.as_str());
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+        access::require_display_name(conn, board_id, actor)?;
+        let tx = rusqlite::Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+            .map_err(|e| db_error(&e.to_string()))?;
+        let (previous, author, created_at) = load_comment(&tx, board_id, task_id, comment_id)?;
+        let has_attachments = previous["attachments"].as_array().is_some_and(|a| !a.is_empty());
+        if message.is_empty() && !has_attachments {
+            return Err((
+                Status::BadRequest,
+                Json(ApiError {
+                    error: "Comment message cannot be empty".to_string(),
+                    code: "EMPTY_MESSAGE".to_string(),
+                    status: 400,
+                }),
+            ));
+        }
+
+        let mut data = previous.clone();
+        data["message"] = serde_json::json!(message);
+        let mentions = extract_mentions(message);
+        match data.as_object_mut() {
+            Some(fields) if mentions.is_empty() => {
+                fields.remove("mentions");
+            }
+            _ => data["mentions"] = serde_json::json!(mentions),
+        }
+        data["edited_at"] = serde_json::json!(chrono::Utc::now().to_rfc3339());
+        data["edited_by"] = serde_json::json!(actor.name());
+
+        save_revision(&tx, board_id, task_id, comment_id, &previous, EDITED, actor.name())?;
+        tx.execute(
+            "UPDATE task_events SET data = ?1 WHERE id = ?2",
+            rusqlite::params![data.to_string(), comment_id],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        log_event(&tx, task_id, "comment_edited", actor.name(), &serde_json::json!({"comment_id": comment_id}));
+        bus.emit(
+            &tx,
+            BoardEvent {
+                event: event_types::TASK_COMMENT_EDITED.to_string(),
+                board_id: board_id.to_string(),
+                data: serde_json::json!({
+                    "task_id": task_id,
+                    "comment_id": comment_id,
+                    "actor": actor.name(),
+                    "message": message,
+                    "mentions": data.get("mentions").cloned().unwrap_or_else(|| serde_json::json!([])),
+                }),
+            },
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        crate::events::commit(tx).map_err(|e| db_error(&e.to_string()))?;
+
+        Ok(TaskEventResponse {
+            id: comment_id.to_string(),
+            event_type: "comment".to_string(),
+            actor: author,
+            data,
+            created_at,
+            comment_pressure: None,
+        })
+    })
+    .await?;
+    Ok(Json(response))
+}
+
+/// Delete a comment — requires manage key. Its last text stays in the history.
+#[delete("/boards/<board_id>/tasks/<task_id>/comments/<comment_id>")]
+pub async fn delete_comment(
+    board_id: &str,
+    task_id: &str,
+    comment_id: &str,
+    token: BoardToken,
+    actor: Actor,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
+    let (board, comment, task, bus) = (board_id.to_owned(), comment_id.to_owned(), task_id.to_owned(), bus.inner().clone());
+    let deleter = actor.clone();
+    with_db(db, move |conn| {
+        let (board_id, comment_id, task_id) = (board.as_str(), comment.as_str(), task.as_str());
+        let actor = &deleter;
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+        access::require_display_name(conn, board_id, actor)?;
+        let tx = rusqlite::Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+            .map_err(|e| db_error(&e.to_string()))?;
+        let (previous, ..) = load_comment(&tx, board_id, task_id, comment_id)?;
+        save_revision(&tx, board_id, task_id, comment_id, &previous, DELETED, actor.name())?;
+        tx.execute("DELETE FROM task_events WHERE id = ?1", rusqlite::params![comment_id])
+            .map_err(|e| db_error(&e.to_string()))?;
+        log_event(&tx, task_id, "comment_deleted", actor.name(), &serde_json::json!({"comment_id": comment_id}));
+        bus.emit(
+            &tx,
+            BoardEvent {
+                event: event_types::TASK_COMMENT_DELETED.to_string(),
+                board_id: board_id.to_string(),
+                data: serde_json::json!({"task_id": task_id, "comment_id": comment_id, "actor": actor.name()}),
+            },
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        crate::events::commit(tx).map_err(|e| db_error(&e.to_string()))
+    })
+    .await?;
+    Ok(Json(serde_json::json!({"deleted": true, "id": comment_id})))
+}
+
+/// A comment's edit history, oldest revision first — public. Works for
+/// deleted comments too.
+#[get("/boards/<board_id>/tasks/<task_id>/comments/<comment_id>/history")]
+pub async fn comment_history(
+    board_id: &str,
+    task_id: &str,
+    comment_id: &str,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+) -> Result<Json<CommentHistoryResponse>, (Status, Json<ApiError>)> {
+    let (board_id, comment_id, task_id) = (board_id.to_owned(), comment_id.to_owned(), task_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, comment_id, task_id) = (board_id.as_str(), comment_id.as_str(), task_id.as_str());
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        let current = load_comment(conn, board_id, task_id, comment_id).ok();
+        let revisions: Vec<CommentRevision> = conn
+            .prepare(
+                "SELECT data, change, changed_by, changed_at FROM comment_revisions
+                 WHERE comment_id = ?1 AND task_id = ?2 AND board_id = ?3
+                 ORDER BY changed_at ASC, rowid ASC",
+            )
+            .map_err(|e| db_error(&e.to_string()))?
+            .query_map(rusqlite::params![comment_id, task_id, board_id], |row| {
+                let data: serde_json::Value = serde_json::from_str(&row.get::<_, String>(0)?).unwrap_or_default();
+                Ok(CommentRevision {
+                    message: data["message"].as_str().unwrap_or_default().to_string(),
+                    change: row.get(1)?,
+                    changed_by: row.get(2)?,
+                    changed_at: row.get(3)?,
+                })
+            })
+            .map_err(|e| db_error(&e.to_string()))?
+            .collect::<rusqlite::Result<_>>()
+            .map_err(|e| db_error(&e.to_string()))?;
+        if current.is_none() && revisions.is_empty() {
+            return Err(not_found("Comment"));
+        }
+        Ok(Json(CommentHistoryResponse {
+            comment_id: comment_id.to_string(),
+            task_id: task_id.to_string(),
+            message: current
+                .as_ref()
+                .map(|(data, ..)| data["message"].as_str().unwrap_or_default().to_string()),
+            deleted: current.is_none(),
+            revisions,
+        }))
+    })
+    .await
+}
@@ -0,0 +1,155 @@
+//! Response compression fairing (brotli / gzip).
+//!
+//! Negotiates on `Accept-Encoding` and compresses the body as a stream, so
+//! chunked responses (e.g. large task listings) are never buffered in full.
+
+use async_compression::tokio::bufread::{BrotliEncoder, GzipEncoder};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{ContentType, Header};
+use rocket::tokio::io::BufReader;
+use rocket::{Request, Response};
+
+/// Default minimum body size worth compressing (bytes).
+const DEFAULT_MIN_BYTES: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    Brotli,
+    Gzip,
+}
+
+impl Encoding {
+    fn as_str(self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gzip",
+        }
+    }
+}
+
+pub struct Compression {
+    enabled: bool,
+    /// Sized bodies smaller than this are sent as-is. Streamed bodies are always compressed.
+    min_bytes: usize,
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Compression {
+            enabled: true,
+            min_bytes: DEFAULT_MIN_BYTES,
+        }
+    }
+}
+
+impl Compression {
+    /// Read `COMPRESSION` (set to `off` to disable) and `COMPRESSION_MIN_BYTES`.
+    pub fn from_env() -> Self {
+        let enabled = !matches!(
+            std::env::var("COMPRESSION").ok().as_deref(),
+            Some("off") | Some("false") | Some("0")
+        );
+        let min_bytes = std::env::var("COMPRESSION_MIN_BYTES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_MIN_BYTES);
+        Compression { enabled, min_bytes }
+    }
+}
+
+/// Pick the preferred encoding the client accepts (brotli over gzip).
+/// Encodings listed with `q=0` are treated as refused.
+fn negotiate(accept_encoding: &str) -> Option<Encoding> {
+    let mut br = false;
+    let mut gzip = false;
+    for part in accept_encoding.split(',') {
+        let mut pieces = part.split(';');
+        let name = pieces.next().unwrap_or("").trim().to_ascii_lowercase();
+        let refused = pieces.any(|p| {
+            p.trim()
+                .strip_prefix("q=")
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .is_some_and(|q| q <= 0.0)
+        });
+        if refused {
+            continue;
+        }
+        match name.as_str() {
+            "br" => br = true,
+            "gzip" | "*" => gzip = true,
+            _ => {}
+        }
+    }
+    if br {
+        Some(Encoding::Brotli)
+    } else if gzip {
+        Some(Encoding::Gzip)
+    } else {
+        None
+    }
+}
+
+/// Only text-like payloads benefit; SSE must stay uncompressed so events flush immediately.
+fn is_compressible(content_type: Option<&ContentType>) -> bool {
+    match content_type {
+        Some(ct) if ct.top() == "text" => ct.sub() != "event-stream",
+        Some(ct) => ct.is_json() || ct.is_javascript() || ct.is_xml() || ct.sub().as_str().ends_with("+json"),
+        None => false,
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for Compression {
+    fn info(&self) -> Info {
+        Info {
+            name: "Response Compression",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        if !self.enabled || res.headers().contains("Content-Encoding") || res.body().is_none() {
+            return;
+        }
+        if !is_compressible(res.content_type().as_ref()) {
+            return;
+        }
+        let Some(encoding) = req.headers().get_one("Accept-Encoding").and_then(negotiate) else {
+            return;
+        };
+        if let Some(size) = res.body().preset_size() {
+            if size < self.min_bytes {
+                return;
+            }
+        }
+
+        let body = BufReader::new(res.body_mut().take());
+        match encoding {
+            Encoding::Brotli => res.set_streamed_body(BrotliEncoder::new(body)),
+            Encoding::Gzip => res.set_streamed_body(GzipEncoder::new(body)),
+        }
+        res.set_header(Header::new("Content-Encoding", encoding.as_str()));
+        res.adjoin_header(Header::new("Vary", "Accept-Encoding"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_prefers_brotli() {
+        assert_eq!(negotiate("gzip, deflate, br"), Some(Encoding::Brotli));
+        assert_eq!(negotiate("gzip"), Some(Encoding::Gzip));
+        assert_eq!(negotiate("br;q=0, gzip;q=0.5"), Some(Encoding::Gzip));
+        assert_eq!(negotiate("identity"), None);
+    }
+
+    #[test]
+    fn test_event_streams_are_not_compressed() {
+        assert!(is_compressible(Some(&ContentType::JSON)));
+        assert!(is_compressible(Some(&ContentType::Plain)));
+        assert!(!is_compressible(Some(&ContentType::new("text", "event-stream"))));
+        assert!(!is_compressible(Some(&ContentType::ZIP)));
+    }
+}
@@ -0,0 +1,149 @@
+//! Cross-origin policy, from the environment:
+//!
+//! - `CORS_ALLOWED_ORIGINS`: comma-separated origins (`https://app.example.com`),
+//!   or `*` / unset for any origin — the open default agents and dashboards
+//!   on other hosts rely on
+//! - `CORS_ALLOWED_METHODS`: comma-separated methods (default: all the API uses)
+//! - `CORS_ALLOW_CREDENTIALS`: `true` lets browsers send cookies cross-origin;
+//!   only with a list of origins, since every origin could then act as a
+//!   signed-in user
+//! - `CORS_MAX_AGE`: seconds browsers may cache a preflight (default 3600)
+
+use std::str::FromStr;
+
+use rocket_cors::{AllowedOrigins, Cors, CorsOptions, Method};
+
+const DEFAULT_METHODS: &str = "GET,POST,PUT,PATCH,DELETE,OPTIONS,HEAD";
+const DEFAULT_MAX_AGE: usize = 3600;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorsConfig {
+    /// `None` allows any origin
+    origins: Option<Vec<String>>,
+    methods: Vec<String>,
+    allow_credentials: bool,
+    max_age: usize,
+}
+
+impl CorsConfig {
+    pub fn from_env() -> Result<Self, String> {
+        let var = |name: &str| std::env::var(name).ok();
+        Self::parse(
+            var("CORS_ALLOWED_ORIGINS").as_deref(),
+            var("CORS_ALLOWED_METHODS").as_deref(),
+            var("CORS_ALLOW_CREDENTIALS").as_deref(),
+            var("CORS_MAX_AGE").as_deref(),
+        )
+    }
+
+    /// A policy from the settings' raw values (`None` = unset).
+    pub fn parse(
+        origins: Option<&str>,
+        methods: Option<&str>,
+        allow_credentials: Option<&str>,
+        max_age: Option<&str>,
+    ) -> Result<Self, String> {
+        let list = |value: &str| -> Vec<String> {
+            value.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
+        };
+
+        let origins = match origins.map(str::trim) {
+            None | Some("") | Some("*") => None,
+            Some(value) => {
+                let origins: Vec<String> = list(value).into_iter().map(|o| o.trim_end_matches('/').to_string()).collect();
+                if let Some(bad) = origins.iter().find(|o| !(o.starts_with("http://") || o.starts_with("https://"))) {
+                    return Err(format!("CORS_ALLOWED_ORIGINS: '{}' isn't an origin like https://app.example.com", bad));
+                }
+                Some(origins)
+            }
+        };
+
+        let methods: Vec<String> = list(methods.unwrap_or(DEFAULT_METHODS)).into_iter().map(|m| m.to_uppercase()).collect();
+        if methods.is_empty() {
+            return Err("CORS_ALLOWED_METHODS is empty".to_string());
+        }
+        if let Some(bad) = methods.iter().find(|m| Method::from_str(m).is_err()) {
+            return Err(format!("CORS_ALLOWED_METHODS: '{}' isn't an HTTP method", bad));
+        }
+
+        let allow_credentials = match allow_credentials.map(|v| v.trim().to_lowercase()).as_deref() {
+            None | Some("") | Some("false") | Some("0") => false,
+            Some("true") | Some("1") => true,
+            Some(other) => return Err(format!("CORS_ALLOW_CREDENTIALS: expected true or false, got '{}'", other)),
+        };
+        if allow_credentials && origins.is_none() {
+            return Err("CORS_ALLOW_CREDENTIALS=true needs CORS_ALLOWED_ORIGINS to list the origins to trust".to_string());
+        }
+
+        let max_age = match max_age.map(str::trim).filter(|v| !v.is_empty()) {
+            None => DEFAULT_MAX_AGE,
+            Some(v) => v.parse().map_err(|_| format!("CORS_MAX_AGE: '{}' isn't a number of seconds", v))?,
+        };
+
+        Ok(CorsConfig {
+            origins,
+            methods,
+            allow_credentials,
+            max_age,
+        })
+    }
+
+    /// The fairing that applies the policy.
+    pub fn to_cors(&self) -> Result<Cors, String> {
+        let allowed_origins = match &self.origins {
+            None => AllowedOrigins::all(),
+            Some(origins) => AllowedOrigins::some_exact(origins),
+        };
+        CorsOptions {
+            allowed_origins,
+            allowed_methods: self.methods.iter().filter_map(|m| Method::from_str(m).ok()).collect(),
+            allow_credentials: self.allow_credentials,
+            max_age: Some(self.max_age),
+            ..Default::default()
+        }
+        .to_cors()
+        .map_err(|e| format!("CORS configuration failed: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let open = CorsConfig::parse(None, None, None, None).unwrap();
+        assert_eq!(open.origins, None);
+        assert_eq!(open.methods.len(), 7);
+        assert!(!open.allow_credentials);
+        assert_eq!(open.max_age, 3600);
+        assert_eq!(CorsConfig::parse(Some(" * "), None, None, None).unwrap(), open);
+
+        let strict = CorsConfig::parse(
+            Some("https://app.example.com/, http://localhost:5173"),
+            Some("get, post"),
+            Some("true"),
+            Some("600"),
+        )
+        .unwrap();
+        assert_eq!(
+            strict.origins,
+            Some(vec!["https://app.example.com".to_string(), "http://localhost:5173".to_string()])
+        );
+        assert_eq!(strict.methods, vec!["GET", "POST"]);
+        assert!(strict.allow_credentials);
+        assert_eq!(strict.max_age, 600);
+        assert!(strict.to_cors().is_ok());
+
+        for (origins, methods, credentials, max_age) in [
+            (Some("app.example.com"), None, None, None),
+            (None, Some("GET,FETCH"), None, None),
+            (None, Some(" , "), None, None),
+            (None, None, Some("yes"), None),
+            (None, None, Some("true"), None),
+            (None, None, None, Some("an hour")),
+        ] {
+            assert!(CorsConfig::parse(origins, methods, credentials, max_age).is_err(), "{:?}", (origins, methods, credentials));
+        }
+    }
+}
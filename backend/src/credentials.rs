@@ -0,0 +1,69 @@
+//! One view over every extra key a board has handed out — collaborator,
+//! share, scoped and agent keys — that still works, soonest to expire first, so
+//! admins can see what credentials are live and when short-lived ones lapse.
+//! The manage key never expires and isn't listed.
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+
+use crate::access::{self, BoardRole};
+use crate::auth::BoardToken;
+use crate::db::{hash_key, DbPool};
+use crate::models::*;
+use crate::routes::{db_error, with_db};
+
+/// The board's active (unexpired) keys of every kind — requires admin.
+#[get("/boards/<board_id>/credentials")]
+pub async fn list_active_keys(
+    board_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<Vec<ActiveKeyResponse>>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT kind, id, name, role, expires_at,
+                        CAST(strftime('%s', expires_at) - strftime('%s', 'now') AS INTEGER),
+                        created_by, created_at
+                 FROM (
+                     SELECT 'collaborator' AS kind, id, name, role, expires_at, created_by, created_at
+                     FROM board_members WHERE board_id = ?1
+                     UNION ALL
+                     SELECT 'share', id, name, role, expires_at, created_by, created_at
+                     FROM share_keys WHERE board_id = ?1
+                     UNION ALL
+                     SELECT 'scoped', id, name, 'read', expires_at, created_by, created_at
+                     FROM scoped_tokens WHERE board_id = ?1
+                     UNION ALL
+                     SELECT 'agent', id, agent_id, role, expires_at, created_by, created_at
+                     FROM agent_keys WHERE board_id = ?1
+                 )
+                 WHERE expires_at IS NULL OR expires_at > datetime('now')
+                 ORDER BY expires_at IS NULL, expires_at ASC, created_at ASC",
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        let keys = stmt
+            .query_map(rusqlite::params![board_id], |row| {
+                let role: String = row.get(3)?;
+                Ok(ActiveKeyResponse {
+                    kind: row.get(0)?,
+                    id: row.get(1)?,
+                    name: row.get(2)?,
+                    role: BoardRole::parse(&role).unwrap_or(BoardRole::Read),
+                    expires_at: row.get(4)?,
+                    expires_in_seconds: row.get(5)?,
+                    created_by: row.get(6)?,
+                    created_at: row.get(7)?,
+                })
+            })
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(Json(keys))
+    })
+    .await
+}
@@ -1,27 +1,289 @@
+use r2d2_sqlite::SqliteConnectionManager;
 use rusqlite::Connection;
 use sha2::{Digest, Sha256};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-pub type DbPool = Mutex<Connection>;
+/// SQLite connection pool. Routes check out a connection per request (via
+/// `routes::with_db`), so reads proceed in parallel under WAL mode.
+pub type DbPool = r2d2::Pool<SqliteConnectionManager>;
 pub type WebhookDb = Arc<Mutex<Connection>>;
 
+/// Run blocking SQLite work on Tokio's blocking pool, so the async workers
+/// keep serving SSE heartbeats and webhook deliveries meanwhile. The calling
+/// request's timeout and slow-query log follow the work there. A panic in `f`
+/// resumes in the caller.
+pub async fn blocking<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+    let caller = crate::query_guard::caller();
+    match tokio::task::spawn_blocking(move || crate::query_guard::on_behalf(caller, f)).await {
+        Ok(value) => value,
+        Err(e) => match e.try_into_panic() {
+            Ok(panic) => std::panic::resume_unwind(panic),
+            Err(e) => panic!("Database work was cancelled: {}", e),
+        },
+    }
+}
+
+/// Default number of pooled connections (override with `DB_POOL_SIZE`).
+const DEFAULT_POOL_SIZE: u32 = 8;
+
+/// How long a connection waits on a locked database before failing (ms; override
+/// with `DB_BUSY_TIMEOUT_MS`). Writers still serialize in SQLite; this keeps them
+/// queueing instead of erroring.
+const DEFAULT_BUSY_TIMEOUT_MS: u64 = 5000;
+
+/// Longest single sleep between retries while the database is locked.
+const MAX_BUSY_SLEEP: Duration = Duration::from_millis(25);
+
+/// Process-wide lock and connection counters, reported by `GET /admin/db-stats`.
+///
+/// A static rather than managed state: SQLite's busy handler is a plain `fn`
+/// and can't capture anything. Durations are in microseconds.
+pub struct DbStats {
+    busy_timeout_ms: AtomicU64,
+    pub busy_waits: AtomicU64,
+    pub busy_timeouts: AtomicU64,
+    pub busy_wait_total_us: AtomicU64,
+    pub busy_wait_max_us: AtomicU64,
+    pub checkouts: AtomicU64,
+    pub checkout_wait_total_us: AtomicU64,
+    pub checkout_wait_max_us: AtomicU64,
+    pub hold_total_us: AtomicU64,
+    pub hold_max_us: AtomicU64,
+    pub cache_hits: AtomicU64,
+    pub cache_misses: AtomicU64,
+}
+
+pub static DB_STATS: DbStats = DbStats {
+    busy_timeout_ms: AtomicU64::new(DEFAULT_BUSY_TIMEOUT_MS),
+    busy_waits: AtomicU64::new(0),
+    busy_timeouts: AtomicU64::new(0),
+    busy_wait_total_us: AtomicU64::new(0),
+    busy_wait_max_us: AtomicU64::new(0),
+    checkouts: AtomicU64::new(0),
+    checkout_wait_total_us: AtomicU64::new(0),
+    checkout_wait_max_us: AtomicU64::new(0),
+    hold_total_us: AtomicU64::new(0),
+    hold_max_us: AtomicU64::new(0),
+    cache_hits: AtomicU64::new(0),
+    cache_misses: AtomicU64::new(0),
+};
+
+impl DbStats {
+    pub fn busy_timeout(&self) -> Duration {
+        Duration::from_millis(self.busy_timeout_ms.load(Ordering::Relaxed))
+    }
+
+    /// A request checked out a connection after `waited`, then held it for `held`.
+    pub fn record_checkout(&self, waited: Duration, held: Duration) {
+        self.checkouts.fetch_add(1, Ordering::Relaxed);
+        add_max(&self.checkout_wait_total_us, &self.checkout_wait_max_us, waited);
+        add_max(&self.hold_total_us, &self.hold_max_us, held);
+    }
+
+    /// Fold a connection's page-cache hits and misses into the totals (and reset them).
+    pub fn collect_cache_stats(&self, conn: &Connection) {
+        let hits = take_db_status(conn, rusqlite::ffi::SQLITE_DBSTATUS_CACHE_HIT);
+        let misses = take_db_status(conn, rusqlite::ffi::SQLITE_DBSTATUS_CACHE_MISS);
+        self.cache_hits.fetch_add(hits, Ordering::Relaxed);
+        self.cache_misses.fetch_add(misses, Ordering::Relaxed);
+    }
+}
+
+fn add_max(total: &AtomicU64, max: &AtomicU64, d: Duration) {
+    let us = d.as_micros() as u64;
+    total.fetch_add(us, Ordering::Relaxed);
+    max.fetch_max(us, Ordering::Relaxed);
+}
+
+/// Read and reset one `sqlite3_db_status` counter; rusqlite has no safe wrapper.
+fn take_db_status(conn: &Connection, op: i32) -> u64 {
+    let (mut current, mut highwater) = (0, 0);
+    // SAFETY: the handle is valid for the lifetime of `conn`, and the out
+    // pointers are live locals.
+    let rc = unsafe { rusqlite::ffi::sqlite3_db_status(conn.handle(), op, &mut current, &mut highwater, 1) };
+    if rc == rusqlite::ffi::SQLITE_OK {
+        current.max(0) as u64
+    } else {
+        0
+    }
+}
+
+thread_local! {
+    /// When the current thread started waiting on a locked database.
+    static BUSY_SINCE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// Busy handler used instead of `PRAGMA busy_timeout`: same retry-until-timeout
+/// behaviour, but every wait is counted in `DB_STATS`.
+fn busy_handler(attempt: i32) -> bool {
+    let now = Instant::now();
+    if attempt == 0 {
+        BUSY_SINCE.with(|s| s.set(Some(now)));
+        DB_STATS.busy_waits.fetch_add(1, Ordering::Relaxed);
+    }
+    let since = BUSY_SINCE.with(|s| s.get()).unwrap_or(now);
+    let waited = now.duration_since(since);
+    DB_STATS.busy_wait_max_us.fetch_max(waited.as_micros() as u64, Ordering::Relaxed);
+    if waited >= DB_STATS.busy_timeout() {
+        DB_STATS.busy_timeouts.fetch_add(1, Ordering::Relaxed);
+        return false;
+    }
+    let sleep = Duration::from_millis(1u64 << attempt.clamp(0, 5)).min(MAX_BUSY_SLEEP);
+    thread::sleep(sleep);
+    DB_STATS.busy_wait_total_us.fetch_add(sleep.as_micros() as u64, Ordering::Relaxed);
+    true
+}
+
+/// Install the instrumented busy handler on a connection.
+pub fn configure_busy_handler(conn: &Connection) -> rusqlite::Result<()> {
+    conn.busy_handler(Some(busy_handler))
+}
+
+fn busy_timeout_from_env() -> u64 {
+    std::env::var("DB_BUSY_TIMEOUT_MS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_BUSY_TIMEOUT_MS)
+}
+
 pub fn hash_key(key: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(key.as_bytes());
     format!("{:x}", hasher.finalize())
 }
 
+/// `DATABASE_KEY`: the SQLCipher key for at-rest encryption, if configured.
+pub fn database_key() -> Option<String> {
+    std::env::var("DATABASE_KEY").ok().filter(|k| !k.is_empty())
+}
+
+/// Whether this build links SQLCipher (`cargo build --features sqlcipher`).
+/// Plain SQLite silently ignores `PRAGMA key`, so this must be checked first.
+pub fn cipher_available(conn: &Connection) -> bool {
+    conn.query_row("PRAGMA cipher_version", [], |row| row.get::<_, String>(0)).is_ok()
+}
+
+/// Unlock a freshly opened connection; must run before any other statement.
+pub fn apply_key(conn: &Connection, key: Option<&str>) -> rusqlite::Result<()> {
+    match key {
+        Some(key) => conn.pragma_update(None, "key", key),
+        None => Ok(()),
+    }
+}
+
+/// Key and busy handler for every new connection, pooled or not.
+pub fn configure_connection(conn: &Connection, key: Option<&str>) -> rusqlite::Result<()> {
+    apply_key(conn, key)?;
+    configure_busy_handler(conn)
+}
+
+/// Open `path` as a standalone connection, configured like the pool's.
+pub fn open_connection(path: impl AsRef<std::path::Path>, key: Option<&str>) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(path)?;
+    configure_connection(&conn, key)?;
+    Ok(conn)
+}
+
+/// Fail fast with a clear message when a key can't be used: no SQLCipher in
+/// this build, or the key doesn't open the file.
+fn check_key(conn: &Connection, key: Option<&str>) -> Result<(), String> {
+    if key.is_some() && !cipher_available(conn) {
+        return Err("DATABASE_KEY is set but this build has no SQLCipher support \
+                    (rebuild with `--features sqlcipher`)"
+            .to_string());
+    }
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))
+        .map(|_| ())
+        .map_err(|e| match key {
+            Some(_) => format!("Cannot open database with DATABASE_KEY (wrong key, or the file is not encrypted): {}", e),
+            None => format!("Cannot open database (encrypted? set DATABASE_KEY): {}", e),
+        })
+}
+
+/// Re-encrypt the database at `path` from `old_key` to `new_key`; either may be
+/// None for a plaintext file, so this also encrypts or decrypts. The server
+/// must be stopped. The previous file is deleted once the new one opens with
+/// `new_key`, since it may be plaintext; if it doesn't, the previous file is
+/// put back.
+pub fn rekey(path: &str, old_key: Option<&str>, new_key: Option<&str>) -> Result<(), String> {
+    let conn = open_connection(path, old_key).map_err(|e| e.to_string())?;
+    if !cipher_available(&conn) {
+        return Err("Key rotation needs SQLCipher (rebuild with `--features sqlcipher`)".to_string());
+    }
+    check_key(&conn, old_key)?;
+
+    let rekeyed = format!("{}.rekey", path);
+    let _ = std::fs::remove_file(&rekeyed);
+    conn.execute(
+        "ATTACH DATABASE ?1 AS rekeyed KEY ?2",
+        rusqlite::params![rekeyed, new_key.unwrap_or("")],
+    )
+    .and_then(|_| conn.query_row("SELECT sqlcipher_export('rekeyed')", [], |_| Ok(())))
+    .and_then(|_| conn.execute_batch("DETACH DATABASE rekeyed;"))
+    .map_err(|e| format!("Export failed: {}", e))?;
+    conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);").map_err(|e| e.to_string())?;
+    drop(conn);
+
+    let previous = format!("{}.pre-rekey", path);
+    std::fs::rename(path, &previous).map_err(|e| e.to_string())?;
+    std::fs::rename(&rekeyed, path).map_err(|e| e.to_string())?;
+    for suffix in ["-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path, suffix));
+    }
+
+    // Prove the new key opens the new file before letting go of the old one
+    let verified = open_connection(path, new_key)
+        .map_err(|e| e.to_string())
+        .and_then(|conn| check_key(&conn, new_key));
+    if let Err(e) = verified {
+        std::fs::rename(&previous, path)
+            .map_err(|restore| format!("{}; restoring the previous file from {} failed: {}", e, previous, restore))?;
+        return Err(format!("The re-keyed file did not open ({}); the previous file was put back", e));
+    }
+    std::fs::remove_file(&previous)
+        .map_err(|e| format!("Re-keyed, but the previous file {} could not be deleted: {}", previous, e))
+}
+
+/// The SQLite file from `DATABASE_PATH` (default `kanban.db`).
+pub fn database_path() -> String {
+    std::env::var("DATABASE_PATH").unwrap_or_else(|_| "kanban.db".to_string())
+}
+
 pub fn init_db() -> Result<DbPool, String> {
-    let db_path = std::env::var("DATABASE_PATH").unwrap_or_else(|_| "kanban.db".to_string());
-    init_db_with_path(&db_path)
+    init_db_with_key(&database_path(), database_key().as_deref())
 }
 
 /// Initialize the database at the given path. Prefer this over `init_db()` in tests
 /// to avoid process-global env var races with `std::env::set_var`.
+#[allow(dead_code)]
 pub fn init_db_with_path(db_path: &str) -> Result<DbPool, String> {
-    let conn = Connection::open(db_path).map_err(|e| format!("Failed to open database: {}", e))?;
+    init_db_with_key(db_path, None)
+}
+
+/// Initialize a database encrypted with `key` (None = plaintext).
+pub fn init_db_with_key(db_path: &str, key: Option<&str>) -> Result<DbPool, String> {
+    let pool_size = std::env::var("DB_POOL_SIZE")
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(DEFAULT_POOL_SIZE);
+    DB_STATS.busy_timeout_ms.store(busy_timeout_from_env(), Ordering::Relaxed);
+    let owned_key = key.map(str::to_string);
+    let manager = SqliteConnectionManager::file(db_path).with_init(move |c| {
+        configure_connection(c, owned_key.as_deref())?;
+        crate::query_guard::install_profiler(c);
+        Ok(())
+    });
+    let pool = r2d2::Pool::builder()
+        .max_size(pool_size)
+        .build(manager)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    let conn = pool.get().map_err(|e| format!("Failed to open database: {}", e))?;
+    check_key(&conn, key)?;
 
     // Enable WAL mode for better concurrent read performance
     // Retry a few times to handle transient locks during test initialization
@@ -29,7 +291,7 @@ pub fn init_db_with_path(db_path: &str) -> Result<DbPool, String> {
     loop {
         match conn.execute_batch("PRAGMA journal_mode=WAL;") {
             Ok(_) => break,
-            Err(e) if attempts < 3 => {
+            Err(_) if attempts < 3 => {
                 attempts += 1;
                 thread::sleep(Duration::from_millis(10));
             }
@@ -37,6 +299,15 @@ pub fn init_db_with_path(db_path: &str) -> Result<DbPool, String> {
         }
     }
 
+    migrate(&conn)?;
+
+    drop(conn);
+    Ok(pool)
+}
+
+/// Create the schema and apply every migration. Idempotent — also run after
+/// a restore, so an older backup is brought up to date.
+pub fn migrate(conn: &Connection) -> Result<(), String> {
     conn.execute_batch(
         "
         -- Boards group related tasks
@@ -129,7 +400,94 @@ pub fn init_db_with_path(db_path: &str) -> Result<DbPool, String> {
             UNIQUE(blocker_task_id, blocked_task_id)
         );
 
+        -- Webhook payloads that failed every delivery attempt, kept for re-drive
+        CREATE TABLE IF NOT EXISTS webhook_dead_letters (
+            id TEXT PRIMARY KEY,
+            webhook_id TEXT NOT NULL,
+            board_id TEXT NOT NULL,
+            event TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            last_error TEXT NOT NULL DEFAULT '',
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (webhook_id) REFERENCES webhooks(id) ON DELETE CASCADE
+        );
+
+        -- Webhook payloads waiting to be sent (or retried); rows leave once
+        -- delivered or dead-lettered. next_attempt_at is Unix milliseconds
+        CREATE TABLE IF NOT EXISTS webhook_outbox (
+            id TEXT PRIMARY KEY,
+            webhook_id TEXT NOT NULL,
+            board_id TEXT NOT NULL,
+            event TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            next_attempt_at INTEGER NOT NULL,
+            last_error TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (webhook_id) REFERENCES webhooks(id) ON DELETE CASCADE
+        );
+
+        -- Every webhook delivery attempt, for debugging a receiver
+        CREATE TABLE IF NOT EXISTS webhook_deliveries (
+            id TEXT PRIMARY KEY,
+            webhook_id TEXT NOT NULL,
+            board_id TEXT NOT NULL,
+            event TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            attempt INTEGER NOT NULL DEFAULT 1,
+            status_code INTEGER,
+            duration_ms INTEGER NOT NULL DEFAULT 0,
+            response_body TEXT NOT NULL DEFAULT '',
+            error TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (webhook_id) REFERENCES webhooks(id) ON DELETE CASCADE
+        );
+
+        -- Short-lived edit locks (distinct from claims: a lock guards the card's
+        -- fields while someone edits them, a claim marks who is doing the work)
+        CREATE TABLE IF NOT EXISTS task_locks (
+            task_id TEXT PRIMARY KEY,
+            locked_by TEXT NOT NULL,
+            expires_at TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+        );
+
+        -- Uploaded actor avatars (actors without one get a generated SVG)
+        CREATE TABLE IF NOT EXISTS actor_avatars (
+            name_key TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            content_type TEXT NOT NULL,
+            data BLOB NOT NULL,
+            key_hash TEXT NOT NULL,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        -- Daily view counters per board (kind: board_load, sse_connect)
+        CREATE TABLE IF NOT EXISTS board_views (
+            board_id TEXT NOT NULL,
+            day TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            count INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (board_id, day, kind),
+            FOREIGN KEY (board_id) REFERENCES boards(id) ON DELETE CASCADE
+        );
+
+        -- Deleted tasks, restorable until purged (snapshot = the task row as JSON)
+        CREATE TABLE IF NOT EXISTS task_trash (
+            task_id TEXT PRIMARY KEY,
+            board_id TEXT NOT NULL,
+            title TEXT NOT NULL,
+            column_id TEXT NOT NULL,
+            snapshot TEXT NOT NULL,
+            deleted_by TEXT NOT NULL,
+            deleted_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (board_id) REFERENCES boards(id) ON DELETE CASCADE
+        );
+
         -- Indexes
+        CREATE INDEX IF NOT EXISTS idx_trash_board ON task_trash(board_id, deleted_at);
         CREATE INDEX IF NOT EXISTS idx_tasks_board ON tasks(board_id);
         CREATE INDEX IF NOT EXISTS idx_tasks_column ON tasks(column_id);
         CREATE INDEX IF NOT EXISTS idx_tasks_assigned ON tasks(assigned_to);
@@ -137,6 +495,10 @@ pub fn init_db_with_path(db_path: &str) -> Result<DbPool, String> {
         CREATE INDEX IF NOT EXISTS idx_events_task ON task_events(task_id);
         CREATE INDEX IF NOT EXISTS idx_columns_board ON columns(board_id);
         CREATE INDEX IF NOT EXISTS idx_webhooks_board ON webhooks(board_id);
+        CREATE INDEX IF NOT EXISTS idx_dead_letters_webhook ON webhook_dead_letters(webhook_id);
+        CREATE INDEX IF NOT EXISTS idx_dead_letters_board ON webhook_dead_letters(board_id);
+        CREATE INDEX IF NOT EXISTS idx_deliveries_webhook ON webhook_deliveries(webhook_id);
+        CREATE INDEX IF NOT EXISTS idx_outbox_due ON webhook_outbox(next_attempt_at);
         CREATE INDEX IF NOT EXISTS idx_deps_blocker ON task_dependencies(blocker_task_id);
         CREATE INDEX IF NOT EXISTS idx_deps_blocked ON task_dependencies(blocked_task_id);
         CREATE INDEX IF NOT EXISTS idx_deps_board ON task_dependencies(board_id);
@@ -175,6 +537,27 @@ pub fn init_db_with_path(db_path: &str) -> Result<DbPool, String> {
     );
     // (silently ignored if column already exists)
 
+    // Migration: add enforce_dependencies setting to boards
+    let _ = conn.execute_batch(
+        "ALTER TABLE boards ADD COLUMN enforce_dependencies INTEGER NOT NULL DEFAULT 0;"
+    );
+    // (silently ignored if column already exists)
+
+    // Migration: record who created a board (reported to lifecycle hooks)
+    let _ = conn.execute_batch(
+        "ALTER TABLE boards ADD COLUMN created_by TEXT;"
+    );
+    // (silently ignored if column already exists)
+
+    // Migration: per-board retention policy (days; NULL = keep forever)
+    let _ = conn.execute_batch(
+        "ALTER TABLE boards ADD COLUMN archived_task_retention_days INTEGER;"
+    );
+    let _ = conn.execute_batch(
+        "ALTER TABLE boards ADD COLUMN event_retention_days INTEGER;"
+    );
+    // (silently ignored if column already exists)
+
     // Migration: add monotonic seq column to task_events for cursor pagination
     let _ = conn.execute_batch(
         "ALTER TABLE task_events ADD COLUMN seq INTEGER;"
@@ -198,20 +581,701 @@ pub fn init_db_with_path(db_path: &str) -> Result<DbPool, String> {
     let _ = conn.execute_batch(
         "CREATE INDEX IF NOT EXISTS idx_events_seq ON task_events(seq);"
     );
+    // Actor filters on the activity feed and task event log
+    let _ = conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_events_actor ON task_events(actor COLLATE NOCASE, seq);"
+    );
+
+    // Migration: short per-board task numbers (OPS-12) with a configurable prefix
+    let _ = conn.execute_batch("ALTER TABLE tasks ADD COLUMN number INTEGER;");
+    let _ = conn.execute_batch("ALTER TABLE boards ADD COLUMN task_prefix TEXT;");
+    let _ = conn.execute_batch("ALTER TABLE boards ADD COLUMN next_task_number INTEGER NOT NULL DEFAULT 1;");
+    // (silently ignored if columns already exist)
+    // Number pre-existing tasks in creation order, once
+    let needs_numbers: bool = conn
+        .query_row("SELECT EXISTS(SELECT 1 FROM tasks WHERE number IS NULL)", [], |row| row.get(0))
+        .unwrap_or(false);
+    if needs_numbers {
+        let _ = conn.execute_batch(
+            "UPDATE tasks SET number = (
+                SELECT COUNT(*) FROM tasks t2 WHERE t2.board_id = tasks.board_id
+                  AND (t2.created_at < tasks.created_at OR (t2.created_at = tasks.created_at AND t2.rowid <= tasks.rowid))
+             ) WHERE number IS NULL;
+             UPDATE boards SET next_task_number = COALESCE((SELECT MAX(number) FROM tasks WHERE board_id = boards.id), 0) + 1;"
+        );
+    }
+    conn.execute_batch(
+        "
+        CREATE INDEX IF NOT EXISTS idx_tasks_number ON tasks(board_id, number);
+
+        -- New tasks take the board's next number, whichever code path inserts them
+        CREATE TRIGGER IF NOT EXISTS tasks_assign_number AFTER INSERT ON tasks
+        WHEN new.number IS NULL BEGIN
+            UPDATE tasks SET number = (SELECT next_task_number FROM boards WHERE id = new.board_id)
+            WHERE rowid = new.rowid;
+            UPDATE boards SET next_task_number = next_task_number + 1 WHERE id = new.board_id;
+        END;
+
+        -- Keys a task used to answer to before renumbering or a prefix change
+        CREATE TABLE IF NOT EXISTS task_key_aliases (
+            board_id TEXT NOT NULL,
+            key TEXT NOT NULL COLLATE NOCASE,
+            task_id TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (board_id, key),
+            FOREIGN KEY (board_id) REFERENCES boards(id) ON DELETE CASCADE
+        );
+        ",
+    )
+    .map_err(|e| format!("Failed to set up task numbers: {}", e))?;
+
+    // Migration: duplicate create suppression (window in seconds; NULL = off)
+    let _ = conn.execute_batch("ALTER TABLE boards ADD COLUMN duplicate_window_seconds INTEGER;");
+    let _ = conn.execute_batch("ALTER TABLE tasks ADD COLUMN content_hash TEXT;");
+    // (silently ignored if columns already exist)
+    let _ = conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_tasks_content_hash ON tasks(board_id, content_hash, created_at);"
+    );
+
+    // Migration: per-column auto-assignment pool (JSON array of agent names)
+    let _ = conn.execute_batch("ALTER TABLE columns ADD COLUMN auto_assign TEXT;");
+    let _ = conn.execute_batch("ALTER TABLE columns ADD COLUMN auto_assign_strategy TEXT;");
+    let _ = conn.execute_batch("ALTER TABLE columns ADD COLUMN auto_assign_cursor INTEGER NOT NULL DEFAULT 0;");
+    // (silently ignored if columns already exist)
+
+    // Migration: parent/child tasks (deleting a parent detaches its children)
+    let _ = conn.execute_batch(
+        "ALTER TABLE tasks ADD COLUMN parent_task_id TEXT REFERENCES tasks(id) ON DELETE SET NULL;"
+    );
+    // (silently ignored if column already exists)
+    let _ = conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_tasks_parent ON tasks(parent_task_id);"
+    );
+
+    // Instance-wide tag directory and the tags applied to each board
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS tags (
+            name TEXT PRIMARY KEY,
+            description TEXT NOT NULL DEFAULT '',
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS board_tags (
+            board_id TEXT NOT NULL,
+            tag TEXT NOT NULL,
+            PRIMARY KEY (board_id, tag),
+            FOREIGN KEY (board_id) REFERENCES boards(id) ON DELETE CASCADE,
+            FOREIGN KEY (tag) REFERENCES tags(name) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_board_tags_tag ON board_tags(tag);
+        ",
+    )
+    .map_err(|e| format!("Failed to set up tags: {}", e))?;
 
-    Ok(Mutex::new(conn))
+    // Write-ahead validation hook: at most one per board
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS validation_hooks (
+            board_id TEXT PRIMARY KEY,
+            url TEXT NOT NULL,
+            secret TEXT NOT NULL,
+            timeout_ms INTEGER NOT NULL DEFAULT 2000,
+            fail_open INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (board_id) REFERENCES boards(id) ON DELETE CASCADE
+        );
+        ",
+    )
+    .map_err(|e| format!("Failed to set up validation hooks: {}", e))?;
+
+    // Migration: comment pressure threshold (NULL = off) and collapsed comment transcripts
+    let _ = conn.execute_batch("ALTER TABLE boards ADD COLUMN comment_pressure_threshold INTEGER;");
+    // (silently ignored if column already exists)
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS comment_transcripts (
+            id TEXT PRIMARY KEY,
+            task_id TEXT NOT NULL,
+            board_id TEXT NOT NULL,
+            comment_count INTEGER NOT NULL,
+            first_comment_at TEXT NOT NULL,
+            last_comment_at TEXT NOT NULL,
+            transcript TEXT NOT NULL,
+            created_by TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_comment_transcripts_task ON comment_transcripts(task_id, created_at);
+        ",
+    )
+    .map_err(|e| format!("Failed to set up comment transcripts: {}", e))?;
+
+    // Column routing: a webhook with routes only receives tasks entering those columns
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS webhook_routes (
+            board_id TEXT NOT NULL,
+            column_id TEXT NOT NULL,
+            webhook_id TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (column_id, webhook_id),
+            FOREIGN KEY (webhook_id) REFERENCES webhooks(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_webhook_routes_webhook ON webhook_routes(webhook_id);
+        ",
+    )
+    .map_err(|e| format!("Failed to set up webhook routes: {}", e))?;
+
+    // File attachments: metadata here, contents in the configured store. Rows
+    // outlive their task (trash, board deletion) until `attachments::sweep`
+    // has removed the file, so task_id doesn't cascade
+    const ATTACHMENTS_TABLE: &str = "
+        CREATE TABLE IF NOT EXISTS attachments (
+            id TEXT PRIMARY KEY,
+            task_id TEXT NOT NULL,
+            board_id TEXT NOT NULL,
+            filename TEXT NOT NULL,
+            content_type TEXT NOT NULL,
+            size_bytes INTEGER NOT NULL,
+            sha256 TEXT NOT NULL,
+            storage_key TEXT NOT NULL,
+            uploaded_by TEXT NOT NULL DEFAULT '',
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE INDEX IF NOT EXISTS idx_attachments_task ON attachments(task_id, created_at);
+    ";
+    conn.execute_batch(ATTACHMENTS_TABLE)
+        .map_err(|e| format!("Failed to set up attachments: {}", e))?;
+
+    // Migration: earlier attachments tables cascaded deletes from tasks,
+    // dropping the rows (and leaking the files) of trashed tasks
+    let cascades: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_foreign_key_list('attachments') WHERE \"table\" = 'tasks'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap_or(false);
+    if cascades {
+        conn.execute_batch(&format!(
+            "BEGIN;
+             ALTER TABLE attachments RENAME TO attachments_cascading;
+             DROP INDEX IF EXISTS idx_attachments_task;
+             {}
+             INSERT INTO attachments (id, task_id, board_id, filename, content_type, size_bytes, sha256, storage_key, uploaded_by, created_at)
+                 SELECT id, task_id, board_id, filename, content_type, size_bytes, sha256, storage_key, uploaded_by, created_at
+                 FROM attachments_cascading;
+             DROP TABLE attachments_cascading;
+             COMMIT;",
+            ATTACHMENTS_TABLE
+        ))
+        .map_err(|e| format!("Failed to migrate attachments: {}", e))?;
+    }
+
+    // Validated board imports waiting for their commit call, keyed by payload hash
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS import_validations (
+            payload_hash TEXT PRIMARY KEY,
+            source TEXT NOT NULL,
+            document TEXT NOT NULL,
+            warnings TEXT NOT NULL DEFAULT '[]',
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            expires_at TEXT NOT NULL
+        );
+        ",
+    )
+    .map_err(|e| format!("Failed to set up import validations: {}", e))?;
+
+    // Migration: when a webhook's own secret stops signing (NULL = never)
+    let _ = conn.execute_batch(
+        "ALTER TABLE webhooks ADD COLUMN secret_expires_at TEXT;"
+    );
+    // (silently ignored if column already exists)
+
+    // Migration: what a webhook is sent — `json` events or `slack` messages
+    let _ = conn.execute_batch(
+        "ALTER TABLE webhooks ADD COLUMN kind TEXT NOT NULL DEFAULT 'json';"
+    );
+    // (silently ignored if column already exists)
+
+    // Migration: the chat a `telegram` webhook sends to
+    let _ = conn.execute_batch(
+        "ALTER TABLE webhooks ADD COLUMN chat_id TEXT;"
+    );
+    // (silently ignored if column already exists)
+
+    // Migration: a webhook's payload template (JSON; NULL = send the event)
+    let _ = conn.execute_batch(
+        "ALTER TABLE webhooks ADD COLUMN template TEXT;"
+    );
+    // (silently ignored if column already exists)
+
+    // Migration: a webhook's custom headers (JSON object of encrypted values)
+    let _ = conn.execute_batch(
+        "ALTER TABLE webhooks ADD COLUMN headers TEXT;"
+    );
+    // (silently ignored if column already exists)
+
+    // Migration: a webhook's task filters (labels / column / min priority; NULL = none)
+    let _ = conn.execute_batch(
+        "ALTER TABLE webhooks ADD COLUMN filters TEXT;"
+    );
+    // (silently ignored if column already exists)
+
+    // Extra webhook signing keys with activation windows, for rotation
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS webhook_signing_keys (
+            id TEXT PRIMARY KEY,
+            webhook_id TEXT NOT NULL,
+            board_id TEXT NOT NULL,
+            secret TEXT NOT NULL,
+            active_from TEXT NOT NULL,
+            active_until TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (webhook_id) REFERENCES webhooks(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_webhook_signing_keys ON webhook_signing_keys(webhook_id, active_from);
+        ",
+    )
+    .map_err(|e| format!("Failed to set up webhook signing keys: {}", e))?;
+
+    // Server-wide keys, e.g. the generated key for encrypted secrets when
+    // SECRETS_KEY isn't set
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS server_keys (
+            name TEXT PRIMARY KEY,
+            value TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        ",
+    )
+    .map_err(|e| format!("Failed to set up server keys: {}", e))?;
+
+    // Earlier versions of edited and deleted comments
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS comment_revisions (
+            id TEXT PRIMARY KEY,
+            comment_id TEXT NOT NULL,
+            task_id TEXT NOT NULL,
+            board_id TEXT NOT NULL,
+            data TEXT NOT NULL,
+            change TEXT NOT NULL,
+            changed_by TEXT NOT NULL DEFAULT '',
+            changed_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE INDEX IF NOT EXISTS idx_comment_revisions_comment ON comment_revisions(comment_id, changed_at);
+        ",
+    )
+    .map_err(|e| format!("Failed to set up comment revisions: {}", e))?;
+
+    // Names watching a task (notification fan-out targets)
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS task_watchers (
+            task_id TEXT NOT NULL,
+            board_id TEXT NOT NULL,
+            name TEXT NOT NULL COLLATE NOCASE,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (task_id, name)
+        );
+        CREATE INDEX IF NOT EXISTS idx_task_watchers_board_name ON task_watchers(board_id, name);
+        ",
+    )
+    .map_err(|e| format!("Failed to set up task watchers: {}", e))?;
+
+    // One row per @mention in a comment, for the per-name inbox. Triggers
+    // keep it in step with the comment events; inbox_cursors records how far
+    // each name has read on each board.
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS task_mentions (
+            event_id TEXT NOT NULL,
+            task_id TEXT NOT NULL,
+            name TEXT NOT NULL COLLATE NOCASE,
+            seq INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            PRIMARY KEY (event_id, name)
+        );
+        CREATE INDEX IF NOT EXISTS idx_task_mentions_name_seq ON task_mentions(name, seq);
+
+        CREATE TABLE IF NOT EXISTS inbox_cursors (
+            board_id TEXT NOT NULL,
+            name TEXT NOT NULL COLLATE NOCASE,
+            seq INTEGER NOT NULL DEFAULT 0,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (board_id, name)
+        );
+
+        CREATE TRIGGER IF NOT EXISTS task_mentions_insert AFTER INSERT ON task_events
+        WHEN new.event_type = 'comment' BEGIN
+            INSERT OR IGNORE INTO task_mentions (event_id, task_id, name, seq, created_at)
+            SELECT new.id, new.task_id, m.value, COALESCE(new.seq, 0), new.created_at
+            FROM json_each(new.data, '$.mentions') m WHERE m.type = 'text';
+        END;
+        CREATE TRIGGER IF NOT EXISTS task_mentions_update AFTER UPDATE OF data ON task_events
+        WHEN new.event_type = 'comment' BEGIN
+            DELETE FROM task_mentions WHERE event_id = old.id;
+            INSERT OR IGNORE INTO task_mentions (event_id, task_id, name, seq, created_at)
+            SELECT new.id, new.task_id, m.value, COALESCE(new.seq, 0), new.created_at
+            FROM json_each(new.data, '$.mentions') m WHERE m.type = 'text';
+        END;
+        CREATE TRIGGER IF NOT EXISTS task_mentions_delete AFTER DELETE ON task_events
+        WHEN old.event_type = 'comment' BEGIN
+            DELETE FROM task_mentions WHERE event_id = old.id;
+        END;
+
+        -- Backfill comments posted before the table existed
+        INSERT OR IGNORE INTO task_mentions (event_id, task_id, name, seq, created_at)
+        SELECT te.id, te.task_id, m.value, COALESCE(te.seq, 0), te.created_at
+        FROM task_events te, json_each(CASE WHEN json_valid(te.data) THEN te.data ELSE '{}' END, '$.mentions') m
+        WHERE te.event_type = 'comment' AND m.type = 'text'
+          AND NOT EXISTS (SELECT 1 FROM task_mentions);
+        ",
+    )
+    .map_err(|e| format!("Failed to set up mention inbox: {}", e))?;
+
+    // What each name wants to hear about on a board
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS notification_preferences (
+            board_id TEXT NOT NULL,
+            name TEXT NOT NULL COLLATE NOCASE,
+            level TEXT NOT NULL DEFAULT 'mentions',
+            updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (board_id, name)
+        );
+        ",
+    )
+    .map_err(|e| format!("Failed to set up notification preferences: {}", e))?;
+
+    // Email notifications: addresses per name, the outbox the SMTP worker
+    // drains, reminders already queued, and how far the activity log was read
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS email_subscriptions (
+            board_id TEXT NOT NULL,
+            name TEXT NOT NULL COLLATE NOCASE,
+            email TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (board_id, name)
+        );
+        CREATE TABLE IF NOT EXISTS email_outbox (
+            id TEXT PRIMARY KEY,
+            board_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            email TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            subject TEXT NOT NULL,
+            body TEXT NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            last_error TEXT,
+            sent_at TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE INDEX IF NOT EXISTS idx_email_outbox_pending ON email_outbox(sent_at, attempts);
+        CREATE TABLE IF NOT EXISTS email_reminders (
+            task_id TEXT NOT NULL,
+            due_at TEXT NOT NULL,
+            PRIMARY KEY (task_id, due_at)
+        );
+        CREATE TABLE IF NOT EXISTS email_cursor (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            seq INTEGER NOT NULL
+        );
+        ",
+    )
+    .map_err(|e| format!("Failed to set up email notifications: {}", e))?;
+
+    // Board- and column-level events (`board.*`, `column.*`); task events
+    // stay in task_events
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS board_events (
+            seq INTEGER PRIMARY KEY AUTOINCREMENT,
+            board_id TEXT NOT NULL,
+            event_type TEXT NOT NULL,
+            actor TEXT NOT NULL,
+            data TEXT NOT NULL DEFAULT '{}',
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE INDEX IF NOT EXISTS idx_board_events_board ON board_events(board_id, seq);
+        ",
+    )
+    .map_err(|e| format!("Failed to set up board events: {}", e))?;
+
+    // Collaborators: extra keys on a board, each with a role (see `access::BoardRole`)
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS board_members (
+            id TEXT PRIMARY KEY,
+            board_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            role TEXT NOT NULL,
+            key_hash TEXT NOT NULL UNIQUE,
+            created_by TEXT NOT NULL DEFAULT '',
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (board_id) REFERENCES boards(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_board_members_board ON board_members(board_id);
+        ",
+    )
+    .map_err(|e| format!("Failed to set up board members: {}", e))?;
+
+    // Share keys: extra keys that can only read a board (role `read`)
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS share_keys (
+            id TEXT PRIMARY KEY,
+            board_id TEXT NOT NULL,
+            name TEXT NOT NULL DEFAULT '',
+            role TEXT NOT NULL DEFAULT 'read',
+            key_hash TEXT NOT NULL UNIQUE,
+            created_by TEXT NOT NULL DEFAULT '',
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (board_id) REFERENCES boards(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_share_keys_board ON share_keys(board_id);
+        ",
+    )
+    .map_err(|e| format!("Failed to set up share keys: {}", e))?;
+
+    // Least-privilege tokens: a JSON list of allowed writes, optionally
+    // limited to some columns (JSON list of column ids; empty = all)
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS scoped_tokens (
+            id TEXT PRIMARY KEY,
+            board_id TEXT NOT NULL,
+            name TEXT NOT NULL DEFAULT '',
+            actions TEXT NOT NULL DEFAULT '[]',
+            columns TEXT NOT NULL DEFAULT '[]',
+            key_hash TEXT NOT NULL UNIQUE,
+            created_by TEXT NOT NULL DEFAULT '',
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (board_id) REFERENCES boards(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_scoped_tokens_board ON scoped_tokens(board_id);
+        ",
+    )
+    .map_err(|e| format!("Failed to set up scoped tokens: {}", e))?;
+
+    // Migration: optional expiry on collaborator, share and scoped keys
+    for table in ["board_members", "share_keys", "scoped_tokens"] {
+        let _ = conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN expires_at TEXT;", table));
+        // (silently ignored if column already exists)
+    }
+
+    // Keys that act as one agent: writes made with them are always
+    // attributed to agent_id
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS agent_keys (
+            id TEXT PRIMARY KEY,
+            board_id TEXT NOT NULL,
+            agent_id TEXT NOT NULL,
+            role TEXT NOT NULL DEFAULT 'editor',
+            key_hash TEXT NOT NULL UNIQUE,
+            created_by TEXT NOT NULL DEFAULT '',
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            expires_at TEXT,
+            FOREIGN KEY (board_id) REFERENCES boards(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_agent_keys_board ON agent_keys(board_id);
+        ",
+    )
+    .map_err(|e| format!("Failed to set up agent keys: {}", e))?;
+
+    // Migration: board visibility (public / unlisted / private); is_public
+    // stays as the listing flag, derived from it
+    let _ = conn.execute_batch("ALTER TABLE boards ADD COLUMN visibility TEXT;");
+    // (silently ignored if column already exists)
+    conn.execute_batch(
+        "UPDATE boards SET visibility = CASE WHEN is_public = 1 THEN 'public' ELSE 'unlisted' END
+         WHERE visibility IS NULL;",
+    )
+    .map_err(|e| format!("Failed to set up board visibility: {}", e))?;
+
+    // Migration: view password (salted hash) readers trade for a view token
+    let _ = conn.execute_batch("ALTER TABLE boards ADD COLUMN view_password_hash TEXT;");
+    // (silently ignored if column already exists)
+
+    // Migration: secret signed share links are checked against
+    let _ = conn.execute_batch("ALTER TABLE boards ADD COLUMN share_link_secret TEXT;");
+    // (silently ignored if column already exists)
+
+    // People signed in through OIDC, and their sessions
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS users (
+            id TEXT PRIMARY KEY,
+            issuer TEXT NOT NULL,
+            subject TEXT NOT NULL,
+            email TEXT,
+            name TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            last_login_at TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE (issuer, subject)
+        );
+        CREATE TABLE IF NOT EXISTS user_sessions (
+            token_hash TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            expires_at TEXT NOT NULL,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_user_sessions_user ON user_sessions(user_id);
+        ",
+    )
+    .map_err(|e| format!("Failed to set up users: {}", e))?;
+
+    // Migration: boards created by a signed-in user are owned by them
+    let _ = conn.execute_batch("ALTER TABLE boards ADD COLUMN owner_user_id TEXT;");
+    // (silently ignored if column already exists)
+
+    // Migration: CIDR ranges (JSON array) a board's writes must come from
+    let _ = conn.execute_batch("ALTER TABLE boards ADD COLUMN write_allowlist TEXT;");
+    // (silently ignored if column already exists)
+
+    // Rate limit windows, for RATE_LIMIT_STORE=sqlite (limits that survive restarts)
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS rate_limit_windows (
+            scope TEXT NOT NULL,
+            key TEXT NOT NULL,
+            window_start_ms INTEGER NOT NULL,
+            count INTEGER NOT NULL,
+            last_hit_allowed INTEGER NOT NULL DEFAULT 1,
+            PRIMARY KEY (scope, key)
+        );
+        ",
+    )
+    .map_err(|e| format!("Failed to set up rate_limit_windows: {}", e))?;
+
+    // Per-board URLs external services post JSON to, each turned into a task
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS inbound_hooks (
+            id TEXT PRIMARY KEY,
+            board_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            token_hash TEXT NOT NULL UNIQUE,
+            mapping TEXT NOT NULL DEFAULT '{}',
+            last_used_at TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (board_id) REFERENCES boards(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_inbound_hooks_board ON inbound_hooks(board_id);
+        ",
+    )
+    .map_err(|e| format!("Failed to set up inbound hooks: {}", e))?;
+
+    // Full-text index over task titles, descriptions, labels and comments.
+    // Rows share the task's rowid; triggers keep the index in sync.
+    conn.execute_batch(
+        "
+        CREATE VIRTUAL TABLE IF NOT EXISTS tasks_fts USING fts5(
+            task_id UNINDEXED,
+            title,
+            description,
+            labels,
+            comments,
+            tokenize = 'unicode61 remove_diacritics 2',
+            prefix = '2 3'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS tasks_fts_insert AFTER INSERT ON tasks BEGIN
+            INSERT INTO tasks_fts (rowid, task_id, title, description, labels, comments)
+            VALUES (new.rowid, new.id, new.title, new.description, new.labels, '');
+        END;
+        CREATE TRIGGER IF NOT EXISTS tasks_fts_update AFTER UPDATE OF title, description, labels ON tasks BEGIN
+            UPDATE tasks_fts SET title = new.title, description = new.description, labels = new.labels
+            WHERE rowid = new.rowid;
+        END;
+        CREATE TRIGGER IF NOT EXISTS tasks_fts_delete AFTER DELETE ON tasks BEGIN
+            DELETE FROM tasks_fts WHERE rowid = old.rowid;
+        END;
+
+        -- Comments are re-collected from the event log on every change,
+        -- so edits and scrubs never leave stale text in the index
+        CREATE TRIGGER IF NOT EXISTS tasks_fts_comment_insert AFTER INSERT ON task_events
+        WHEN new.event_type = 'comment' BEGIN
+            UPDATE tasks_fts SET comments = (
+                SELECT COALESCE(group_concat(json_extract(data, '$.message'), ' '), '')
+                FROM task_events WHERE task_id = new.task_id AND event_type = 'comment'
+            ) WHERE rowid = (SELECT rowid FROM tasks WHERE id = new.task_id);
+        END;
+        CREATE TRIGGER IF NOT EXISTS tasks_fts_comment_update AFTER UPDATE OF data ON task_events
+        WHEN new.event_type = 'comment' BEGIN
+            UPDATE tasks_fts SET comments = (
+                SELECT COALESCE(group_concat(json_extract(data, '$.message'), ' '), '')
+                FROM task_events WHERE task_id = new.task_id AND event_type = 'comment'
+            ) WHERE rowid = (SELECT rowid FROM tasks WHERE id = new.task_id);
+        END;
+        CREATE TRIGGER IF NOT EXISTS tasks_fts_comment_delete AFTER DELETE ON task_events
+        WHEN old.event_type = 'comment' BEGIN
+            UPDATE tasks_fts SET comments = (
+                SELECT COALESCE(group_concat(json_extract(data, '$.message'), ' '), '')
+                FROM task_events WHERE task_id = old.task_id AND event_type = 'comment'
+            ) WHERE rowid = (SELECT rowid FROM tasks WHERE id = old.task_id);
+        END;
+        ",
+    )
+    .map_err(|e| format!("Failed to create search index: {}", e))?;
+    rebuild_search_index_if_stale(conn)
+}
+
+/// Repopulate `tasks_fts` when it doesn't match `tasks` — on first run after
+/// upgrading, or if a VACUUM renumbered task rowids.
+fn rebuild_search_index_if_stale(conn: &Connection) -> Result<(), String> {
+    let stale: bool = conn
+        .query_row(
+            "SELECT (SELECT COUNT(*) FROM tasks) != (SELECT COUNT(*) FROM tasks_fts)
+                 OR EXISTS (
+                     SELECT 1 FROM tasks t LEFT JOIN tasks_fts f ON f.rowid = t.rowid
+                     WHERE f.task_id IS NOT t.id
+                 )",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to check search index: {}", e))?;
+    if !stale {
+        return Ok(());
+    }
+    conn.execute_batch(
+        "
+        DELETE FROM tasks_fts;
+        INSERT INTO tasks_fts (rowid, task_id, title, description, labels, comments)
+        SELECT t.rowid, t.id, t.title, t.description, t.labels,
+               COALESCE((SELECT group_concat(json_extract(e.data, '$.message'), ' ')
+                         FROM task_events e WHERE e.task_id = t.id AND e.event_type = 'comment'), '')
+        FROM tasks t;
+        ",
+    )
+    .map_err(|e| format!("Failed to rebuild search index: {}", e))
 }
 
 /// Open a separate database connection for async webhook delivery.
 /// Uses WAL mode for concurrent reads alongside the main connection.
 pub fn init_webhook_db() -> Result<WebhookDb, String> {
-    let db_path = std::env::var("DATABASE_PATH").unwrap_or_else(|_| "kanban.db".to_string());
-    init_webhook_db_with_path(&db_path)
+    init_webhook_db_with_key(&database_path(), database_key().as_deref())
 }
 
 /// Initialize webhook database at the given path. Prefer this over `init_webhook_db()` in tests.
+#[allow(dead_code)]
 pub fn init_webhook_db_with_path(db_path: &str) -> Result<WebhookDb, String> {
-    let conn = Connection::open(db_path)
+    init_webhook_db_with_key(db_path, None)
+}
+
+/// Webhook connection for a database encrypted with `key` (None = plaintext).
+pub fn init_webhook_db_with_key(db_path: &str, key: Option<&str>) -> Result<WebhookDb, String> {
+    let conn = open_connection(db_path, key)
         .map_err(|e| format!("Failed to open webhook database: {}", e))?;
 
     // Retry a few times to handle transient locks during test initialization
@@ -219,7 +1283,7 @@ pub fn init_webhook_db_with_path(db_path: &str) -> Result<WebhookDb, String> {
     loop {
         match conn.execute_batch("PRAGMA journal_mode=WAL;") {
             Ok(_) => break,
-            Err(e) if attempts < 3 => {
+            Err(_) if attempts < 3 => {
                 attempts += 1;
                 thread::sleep(Duration::from_millis(10));
             }
@@ -0,0 +1,127 @@
+//! Discord webhook targets. A webhook created with `"kind": "discord"`
+//! points at a Discord channel webhook URL and receives `task.*` events as
+//! embeds, colour-coded by what happened and by the task's priority. The
+//! summary line is the same one Slack targets get, in Discord markdown.
+
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::event_types;
+use crate::events::BoardEvent;
+use crate::slack::{self, Markup};
+
+const GREEN: u32 = 0x2ecc71;
+const BLUE: u32 = 0x3498db;
+const PURPLE: u32 = 0x9b59b6;
+const ORANGE: u32 = 0xe67e22;
+const RED: u32 = 0xe74c3c;
+const GREY: u32 = 0x95a5a6;
+
+const DISCORD: Markup = Markup { escape, bold, quote: slack::quote_lines };
+
+/// Backslash-escape Discord markdown.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '~' | '`' | '|' | '>' | '[' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn bold(text: &str) -> String {
+    format!("**{}**", text)
+}
+
+fn priority_name(priority: i32) -> &'static str {
+    match priority {
+        p if p >= 3 => "critical",
+        2 => "high",
+        1 => "medium",
+        _ => "low",
+    }
+}
+
+/// Embed colour: red for critical tasks and for tasks leaving the board,
+/// orange for high priority, otherwise by event — green when created, blue
+/// when moved, purple for comments, orange for claims and assignments.
+pub(crate) fn color(event: &str, priority: i32) -> u32 {
+    match event {
+        event_types::TASK_DELETED | event_types::TASK_ARCHIVED => RED,
+        _ if priority >= 3 => RED,
+        _ if priority == 2 => ORANGE,
+        event_types::TASK_CREATED | event_types::TASK_RESTORED | event_types::TASK_UNARCHIVED => GREEN,
+        event_types::TASK_MOVED | event_types::TASK_BATCH_MOVED | event_types::TASK_REORDERED => BLUE,
+        event_types::TASK_COMMENT | event_types::TASK_COMMENT_EDITED => PURPLE,
+        event_types::TASK_CLAIMED | event_types::TASK_ASSIGNED => ORANGE,
+        _ => GREY,
+    }
+}
+
+/// The webhook body for a task event: one embed titled with the task, linked
+/// to the board when `PUBLIC_URL` is set.
+pub fn message(conn: &Connection, event: &BoardEvent) -> serde_json::Value {
+    let priority: Option<i32> = event.data["task_id"].as_str().and_then(|id| {
+        conn.query_row("SELECT priority FROM tasks WHERE id = ?1", rusqlite::params![id], |row| row.get(0))
+            .optional()
+            .ok()
+            .flatten()
+    });
+    let board = slack::board_name(conn, &event.board_id);
+    let mut footer = format!("{} · {}", board, event.event);
+    if let Some(actor) = slack::actor(&event.data) {
+        footer = format!("{} · by {}", footer, actor);
+    }
+
+    let mut embed = serde_json::json!({
+        "title": slack::task_title(conn, event).chars().take(256).collect::<String>(),
+        "description": slack::summary(conn, event, &DISCORD).chars().take(4096).collect::<String>(),
+        "color": color(&event.event, priority.unwrap_or(0)),
+        "footer": { "text": footer },
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+    });
+    if let Some(priority) = priority {
+        embed["fields"] = serde_json::json!([{ "name": "Priority", "value": priority_name(priority), "inline": true }]);
+    }
+    if let Some(base) = slack::public_url() {
+        embed["url"] = serde_json::json!(format!("{}/board/{}", base, event.board_id));
+    }
+    serde_json::json!({ "embeds": [embed] })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embed_colour_and_summary() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE boards (id TEXT, name TEXT);
+             CREATE TABLE columns (id TEXT, name TEXT);
+             CREATE TABLE tasks (id TEXT, title TEXT, priority INTEGER);
+             INSERT INTO boards VALUES ('b1', 'Launch');
+             INSERT INTO tasks VALUES ('t1', 'Rotate keys', 3), ('t2', 'Tidy docs', 0);",
+        )
+        .unwrap();
+        let event = |name: &str, task_id: &str| BoardEvent {
+            event: name.to_string(),
+            board_id: "b1".to_string(),
+            data: serde_json::json!({"task_id": task_id, "actor": "Sam", "from_column": "Todo", "to_column": "Done_now"}),
+        };
+
+        let moved = message(&conn, &event(event_types::TASK_MOVED, "t2"));
+        let embed = &moved["embeds"][0];
+        assert_eq!(embed["title"], "Tidy docs");
+        assert_eq!(embed["description"], r"**Todo** → **Done\_now**");
+        assert_eq!(embed["color"], BLUE);
+        assert_eq!(embed["fields"][0]["value"], "low");
+        assert_eq!(embed["footer"]["text"], "Launch · task.moved · by Sam");
+
+        let critical = message(&conn, &event(event_types::TASK_MOVED, "t1"));
+        assert_eq!(critical["embeds"][0]["color"], RED);
+        assert_eq!(color(event_types::TASK_COMMENT, 1), PURPLE);
+        assert_eq!(color(event_types::TASK_CREATED, 2), ORANGE);
+    }
+}
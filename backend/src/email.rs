@@ -0,0 +1,523 @@
+//! Email notifications over SMTP. Board owners register an address per actor
+//! name; a background worker reads new activity from the event log, queues a
+//! message for each mention, assignment and upcoming due date in
+//! `email_outbox`, and sends the queue through the configured SMTP relay.
+//! Each name's notification preference is honoured, and nobody is mailed
+//! about their own actions. Without `SMTP_HOST` the worker doesn't run.
+
+use std::time::Duration;
+
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Address, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::access;
+use crate::auth::BoardToken;
+use crate::db::{hash_key, DbPool};
+use crate::models::*;
+use crate::notifications;
+use crate::routes::{db_error, not_found, with_db, with_db_blocking};
+
+/// How often the worker collects new activity and drains the outbox.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// Send attempts per message before it is left in the outbox as failed.
+const MAX_ATTEMPTS: i64 = 5;
+/// Messages sent per worker pass.
+const BATCH_SIZE: i64 = 100;
+/// How far ahead of a due date the reminder goes out.
+const REMINDER_WINDOW_HOURS: i64 = 24;
+
+pub const KIND_MENTION: &str = "mention";
+pub const KIND_ASSIGNMENT: &str = "assignment";
+pub const KIND_DUE_REMINDER: &str = "due_reminder";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SmtpTls {
+    /// Plain connection upgraded with STARTTLS (port 587)
+    Starttls,
+    /// TLS from the first byte (port 465)
+    Tls,
+    /// No encryption — local relays only
+    None,
+}
+
+#[derive(Debug, Clone)]
+pub struct EmailConfig {
+    pub host: String,
+    pub port: u16,
+    pub tls: SmtpTls,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    /// Base URL of the dashboard, used for links in messages
+    pub public_url: Option<String>,
+}
+
+impl EmailConfig {
+    /// Read `SMTP_HOST`, `SMTP_PORT`, `SMTP_TLS` (`starttls`, `tls` or
+    /// `none`), `SMTP_USERNAME`, `SMTP_PASSWORD`, `SMTP_FROM` and
+    /// `PUBLIC_URL`. None — email off — unless both host and from are set.
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        let host = var("SMTP_HOST")?;
+        let Some(from) = var("SMTP_FROM") else {
+            eprintln!("⚠️  SMTP_HOST is set but SMTP_FROM is not; email notifications are off");
+            return None;
+        };
+        let tls = match var("SMTP_TLS").map(|t| t.to_lowercase()).as_deref() {
+            Some("tls") => SmtpTls::Tls,
+            Some("none") => SmtpTls::None,
+            _ => SmtpTls::Starttls,
+        };
+        let default_port = match tls {
+            SmtpTls::Starttls => 587,
+            SmtpTls::Tls => 465,
+            SmtpTls::None => 25,
+        };
+        Some(EmailConfig {
+            host,
+            port: var("SMTP_PORT").and_then(|p| p.parse().ok()).unwrap_or(default_port),
+            tls,
+            username: var("SMTP_USERNAME"),
+            password: var("SMTP_PASSWORD"),
+            from,
+            public_url: var("PUBLIC_URL").map(|u| u.trim_end_matches('/').to_string()),
+        })
+    }
+
+    fn transport(&self) -> Result<AsyncSmtpTransport<Tokio1Executor>, String> {
+        let builder = match self.tls {
+            SmtpTls::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&self.host),
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&self.host),
+            SmtpTls::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&self.host)),
+        }
+        .map_err(|e| e.to_string())?;
+        let builder = match (&self.username, &self.password) {
+            (Some(user), Some(pass)) => builder.credentials(Credentials::new(user.clone(), pass.clone())),
+            _ => builder,
+        };
+        Ok(builder.port(self.port).timeout(Some(Duration::from_secs(30))).build())
+    }
+}
+
+// ============ Collecting notices ============
+
+/// A logged event that may call for a message.
+struct Activity {
+    event_type: String,
+    actor: String,
+    data: serde_json::Value,
+    board_id: String,
+    title: String,
+    assigned_to: Option<String>,
+    board_name: String,
+}
+
+/// One message to queue.
+struct Notice<'a> {
+    board_id: &'a str,
+    name: &'a str,
+    kind: &'static str,
+    mentioned: bool,
+    assigned: bool,
+    subject: String,
+    body: String,
+}
+
+fn board_link(public_url: Option<&str>, board_id: &str) -> String {
+    match public_url {
+        Some(base) => format!("\n\n{}/board/{}", base, board_id),
+        None => String::new(),
+    }
+}
+
+/// Queue `notice` if its recipient has an address and its level lets it
+/// through. Returns whether it was queued.
+fn enqueue(conn: &Connection, notice: Notice) -> rusqlite::Result<bool> {
+    let email: Option<String> = conn
+        .query_row(
+            "SELECT email FROM email_subscriptions WHERE board_id = ?1 AND name = ?2",
+            rusqlite::params![notice.board_id, notice.name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(email) = email else {
+        return Ok(false);
+    };
+    let level = notifications::level(conn, notice.board_id, notice.name)?;
+    if !notifications::wants(&level, notice.mentioned, notice.assigned) {
+        return Ok(false);
+    }
+    conn.execute(
+        "INSERT INTO email_outbox (id, board_id, name, email, kind, subject, body)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        rusqlite::params![
+            uuid::Uuid::new_v4().to_string(),
+            notice.board_id,
+            notice.name,
+            email,
+            notice.kind,
+            notice.subject,
+            notice.body
+        ],
+    )?;
+    Ok(true)
+}
+
+/// Queue messages for activity logged since the last pass and for tasks
+/// coming due. Returns how many were queued.
+pub fn collect(conn: &Connection, public_url: Option<&str>) -> rusqlite::Result<usize> {
+    let cursor: i64 = conn
+        .query_row("SELECT seq FROM email_cursor WHERE id = 1", [], |row| row.get(0))
+        .optional()?
+        .unwrap_or(0);
+    let latest: i64 = conn.query_row("SELECT COALESCE(MAX(seq), 0) FROM task_events", [], |row| row.get(0))?;
+
+    let events = conn
+        .prepare(
+            "SELECT te.event_type, te.actor, te.data, t.board_id, t.title, t.assigned_to, b.name
+             FROM task_events te
+             JOIN tasks t ON t.id = te.task_id
+             JOIN boards b ON b.id = t.board_id
+             WHERE te.seq > ?1 AND te.seq <= ?2
+               AND te.event_type IN ('comment', 'created', 'updated', 'assigned')
+             ORDER BY te.seq ASC",
+        )?
+        .query_map(rusqlite::params![cursor, latest], |row| {
+            Ok(Activity {
+                event_type: row.get(0)?,
+                actor: row.get(1)?,
+                data: serde_json::from_str(&row.get::<_, String>(2)?).unwrap_or_default(),
+                board_id: row.get(3)?,
+                title: row.get(4)?,
+                assigned_to: row.get(5)?,
+                board_name: row.get(6)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut queued = 0;
+    for Activity { event_type, actor, data, board_id, title, assigned_to, board_name } in &events {
+        let link = board_link(public_url, board_id);
+        let is_assignee = |name: &str| assigned_to.as_deref().is_some_and(|a| a.eq_ignore_ascii_case(name));
+
+        if event_type == "comment" {
+            let message = data["message"].as_str().unwrap_or_default();
+            let mentions = data["mentions"].as_array().cloned().unwrap_or_default();
+            for name in mentions.iter().filter_map(|m| m.as_str()) {
+                if name.eq_ignore_ascii_case(actor) {
+                    continue;
+                }
+                queued += enqueue(
+                    conn,
+                    Notice {
+                        board_id,
+                        name,
+                        kind: KIND_MENTION,
+                        mentioned: true,
+                        assigned: is_assignee(name),
+                        subject: format!("[{}] {} mentioned you on \"{}\"", board_name, actor, title),
+                        body: format!("{} wrote on \"{}\":\n\n{}{}", actor, title, message, link),
+                    },
+                )? as usize;
+            }
+            continue;
+        }
+
+        // Assignments: set on create, changed by an update, or made by a column rule
+        let Some(name) = data["assigned_to"].as_str().filter(|n| !n.is_empty() && !n.eq_ignore_ascii_case(actor)) else {
+            continue;
+        };
+        queued += enqueue(
+            conn,
+            Notice {
+                board_id,
+                name,
+                kind: KIND_ASSIGNMENT,
+                mentioned: false,
+                assigned: true,
+                subject: format!("[{}] You were assigned \"{}\"", board_name, title),
+                body: format!("{} assigned \"{}\" to you.{}", actor, title, link),
+            },
+        )? as usize;
+    }
+    conn.execute(
+        "INSERT INTO email_cursor (id, seq) VALUES (1, ?1) ON CONFLICT(id) DO UPDATE SET seq = excluded.seq",
+        rusqlite::params![latest],
+    )?;
+
+    Ok(queued + collect_due_reminders(conn, public_url)?)
+}
+
+/// One reminder per assigned task and due date, once it is less than
+/// `REMINDER_WINDOW_HOURS` away.
+fn collect_due_reminders(conn: &Connection, public_url: Option<&str>) -> rusqlite::Result<usize> {
+    // Only due dates still ahead get reminders, so rows for ones that have
+    // passed (tasks since deleted or rescheduled included) are done with
+    conn.execute("DELETE FROM email_reminders WHERE datetime(due_at) <= datetime('now')", [])?;
+    let due: Vec<(String, String, String, String, String, String)> = conn
+        .prepare(
+            "SELECT t.id, t.board_id, t.title, t.assigned_to, t.due_at, b.name
+             FROM tasks t
+             JOIN boards b ON b.id = t.board_id
+             JOIN email_subscriptions s ON s.board_id = t.board_id AND s.name = t.assigned_to
+             WHERE t.due_at IS NOT NULL AND t.completed_at IS NULL AND t.archived_at IS NULL
+               AND datetime(t.due_at) > datetime('now')
+               AND datetime(t.due_at) <= datetime('now', '+' || ?1 || ' hours')
+               AND NOT EXISTS (SELECT 1 FROM email_reminders r WHERE r.task_id = t.id AND r.due_at = t.due_at)",
+        )?
+        .query_map(rusqlite::params![REMINDER_WINDOW_HOURS], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let mut queued = 0;
+    for (task_id, board_id, title, assigned_to, due_at, board_name) in &due {
+        conn.execute(
+            "INSERT OR IGNORE INTO email_reminders (task_id, due_at) VALUES (?1, ?2)",
+            rusqlite::params![task_id, due_at],
+        )?;
+        queued += enqueue(
+            conn,
+            Notice {
+                board_id,
+                name: assigned_to,
+                kind: KIND_DUE_REMINDER,
+                mentioned: false,
+                assigned: true,
+                subject: format!("[{}] \"{}\" is due {}", board_name, title, due_at),
+                body: format!("\"{}\", assigned to you, is due {}.{}", title, due_at, board_link(public_url, board_id)),
+            },
+        )? as usize;
+    }
+    Ok(queued)
+}
+
+/// The first time the worker runs, start the activity cursor at the newest
+/// event, so turning email on doesn't mail out history.
+fn skip_backlog(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO email_cursor (id, seq) SELECT 1, COALESCE(MAX(seq), 0) FROM task_events WHERE 1
+         ON CONFLICT(id) DO NOTHING",
+        [],
+    )
+    .map(|_| ())
+}
+
+// ============ Delivery ============
+
+struct Outgoing {
+    id: String,
+    email: String,
+    subject: String,
+    body: String,
+}
+
+fn pending(conn: &Connection) -> rusqlite::Result<Vec<Outgoing>> {
+    conn.prepare(
+        "SELECT id, email, subject, body FROM email_outbox
+         WHERE sent_at IS NULL AND attempts < ?1
+         ORDER BY created_at ASC, rowid ASC LIMIT ?2",
+    )?
+    .query_map(rusqlite::params![MAX_ATTEMPTS, BATCH_SIZE], |row| {
+        Ok(Outgoing {
+            id: row.get(0)?,
+            email: row.get(1)?,
+            subject: row.get(2)?,
+            body: row.get(3)?,
+        })
+    })?
+    .collect()
+}
+
+fn record(conn: &Connection, id: &str, result: &Result<(), String>) -> rusqlite::Result<()> {
+    match result {
+        Ok(()) => conn.execute(
+            "UPDATE email_outbox SET attempts = attempts + 1, sent_at = datetime('now'), last_error = NULL WHERE id = ?1",
+            rusqlite::params![id],
+        ),
+        Err(e) => conn.execute(
+            "UPDATE email_outbox SET attempts = attempts + 1, last_error = ?2 WHERE id = ?1",
+            rusqlite::params![id, e],
+        ),
+    }
+    .map(|_| ())
+}
+
+async fn send(transport: &AsyncSmtpTransport<Tokio1Executor>, from: &str, mail: &Outgoing) -> Result<(), String> {
+    let message = Message::builder()
+        .from(from.parse().map_err(|e| format!("invalid SMTP_FROM: {}", e))?)
+        .to(mail.email.parse().map_err(|e| format!("invalid address: {}", e))?)
+        .subject(mail.subject.as_str())
+        .header(ContentType::TEXT_PLAIN)
+        .body(mail.body.clone())
+        .map_err(|e| e.to_string())?;
+    transport.send(message).await.map(|_| ()).map_err(|e| e.to_string())
+}
+
+/// One worker pass: queue new notices, then send what's pending.
+async fn run_once(db: &DbPool, config: &EmailConfig, transport: &AsyncSmtpTransport<Tokio1Executor>) -> Result<(), String> {
+    let (pool, public_url) = (db.clone(), config.public_url.clone());
+    let outgoing = tokio::task::spawn_blocking(move || {
+        with_db_blocking(&pool, |conn| {
+            collect(conn, public_url.as_deref())
+                .and_then(|_| pending(conn))
+                .map_err(|e| db_error(&e.to_string()))
+        })
+        .map_err(|(_, e)| e.error.clone())
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    for mail in outgoing {
+        let result = send(transport, &config.from, &mail).await;
+        if let Err(e) = &result {
+            eprintln!("⚠️  Email to {} failed: {}", mail.email, e);
+        }
+        let pool = db.clone();
+        tokio::task::spawn_blocking(move || {
+            with_db_blocking(&pool, |conn| record(conn, &mail.id, &result).map_err(|e| db_error(&e.to_string())))
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|(_, e)| e.error.clone())?;
+    }
+    Ok(())
+}
+
+/// Run the email worker in the background. Does nothing when email is off.
+pub fn spawn(db: DbPool, config: Option<EmailConfig>) {
+    let Some(config) = config else {
+        return;
+    };
+    let transport = match config.transport() {
+        Ok(transport) => transport,
+        Err(e) => {
+            eprintln!("⚠️  Email notifications are off: SMTP setup failed ({})", e);
+            return;
+        }
+    };
+    tokio::spawn(async move {
+        if let Err((_, e)) = with_db(&db, move |conn| skip_backlog(conn).map_err(|e| db_error(&e.to_string()))).await {
+            eprintln!("⚠️  Email worker could not start: {}", e.error);
+            return;
+        }
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = run_once(&db, &config, &transport).await {
+                eprintln!("⚠️  Email worker pass failed: {}", e);
+            }
+        }
+    });
+}
+
+// ============ Routes ============
+
+fn row_to_subscription(board_id: &str, row: &rusqlite::Row) -> rusqlite::Result<EmailSubscriptionResponse> {
+    Ok(EmailSubscriptionResponse {
+        board_id: board_id.to_string(),
+        name: row.get(0)?,
+        email: row.get(1)?,
+        created_at: row.get(2)?,
+        updated_at: row.get(3)?,
+    })
+}
+
+/// List the board's email addresses — requires manage key.
+#[get("/boards/<board_id>/email-subscriptions")]
+pub async fn list_email_subscriptions(
+    board_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<Vec<EmailSubscriptionResponse>>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        conn.prepare(
+            "SELECT name, email, created_at, updated_at FROM email_subscriptions
+             WHERE board_id = ?1 ORDER BY name COLLATE NOCASE",
+        )
+        .map_err(|e| db_error(&e.to_string()))?
+        .query_map(rusqlite::params![board_id], |row| row_to_subscription(board_id, row))
+        .map_err(|e| db_error(&e.to_string()))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map(Json)
+        .map_err(|e| db_error(&e.to_string()))
+    })
+    .await
+}
+
+/// Set the address a name is mailed at — requires manage key.
+#[put("/boards/<board_id>/email-subscriptions/<name>", format = "json", data = "<req>")]
+pub async fn set_email_subscription(
+    board_id: &str,
+    name: &str,
+    req: Json<SetEmailSubscriptionRequest>,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<EmailSubscriptionResponse>, (Status, Json<ApiError>)> {
+    let email = req.email.trim().to_owned();
+    if email.parse::<Address>().is_err() {
+        return Err((
+            Status::BadRequest,
+            Json(ApiError {
+                error: format!("'{}' is not a valid email address", email),
+                code: "INVALID_INPUT".to_string(),
+                status: 400,
+            }),
+        ));
+    }
+    let name = name.trim();
+
+    let (board_id, name) = (board_id.to_owned(), name.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, name) = (board_id.as_str(), name.as_str());
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        conn.execute(
+            "INSERT INTO email_subscriptions (board_id, name, email) VALUES (?1, ?2, ?3)
+             ON CONFLICT(board_id, name) DO UPDATE SET email = excluded.email, updated_at = datetime('now')",
+            rusqlite::params![board_id, name, email],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        conn.query_row(
+            "SELECT name, email, created_at, updated_at FROM email_subscriptions WHERE board_id = ?1 AND name = ?2",
+            rusqlite::params![board_id, name],
+            |row| row_to_subscription(board_id, row),
+        )
+        .map(Json)
+        .map_err(|e| db_error(&e.to_string()))
+    })
+    .await
+}
+
+/// Stop mailing a name — requires manage key.
+#[delete("/boards/<board_id>/email-subscriptions/<name>")]
+pub async fn delete_email_subscription(
+    board_id: &str,
+    name: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
+    let (board_id, name) = (board_id.to_owned(), name.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, name) = (board_id.as_str(), name.as_str());
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        let affected = conn
+            .execute(
+                "DELETE FROM email_subscriptions WHERE board_id = ?1 AND name = ?2",
+                rusqlite::params![board_id, name.trim()],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        if affected == 0 {
+            return Err(not_found("Email subscription"));
+        }
+        Ok(Json(serde_json::json!({"deleted": true, "name": name.trim()})))
+    })
+    .await
+}
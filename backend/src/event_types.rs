@@ -0,0 +1,154 @@
+//! Registry of every event type the server emits.
+//!
+//! The single source of truth for webhook subscription validation, SSE
+//! `?events=` filters, the OpenAPI `EventType` enum, and `GET /event-types`.
+//! Emit sites use these constants, so a new event only needs adding here.
+
+use serde::Serialize;
+
+pub const TASK_CREATED: &str = "task.created";
+pub const TASK_UPDATED: &str = "task.updated";
+pub const TASK_DELETED: &str = "task.deleted";
+pub const TASK_CLAIMED: &str = "task.claimed";
+pub const TASK_RELEASED: &str = "task.released";
+pub const TASK_MOVED: &str = "task.moved";
+pub const TASK_REORDERED: &str = "task.reordered";
+pub const TASK_COMMENT: &str = "task.comment";
+pub const TASK_COMMENT_EDITED: &str = "task.comment.edited";
+pub const TASK_COMMENT_DELETED: &str = "task.comment.deleted";
+pub const TASK_ARCHIVED: &str = "task.archived";
+pub const TASK_UNARCHIVED: &str = "task.unarchived";
+pub const TASK_RESTORED: &str = "task.restored";
+pub const TASK_DEPENDENCY_ADDED: &str = "task.dependency.added";
+pub const TASK_DEPENDENCY_REMOVED: &str = "task.dependency.removed";
+pub const TASK_CHILD_ADDED: &str = "task.child.added";
+pub const TASK_CHILD_REMOVED: &str = "task.child.removed";
+pub const TASK_ATTACHMENT_ADDED: &str = "task.attachment.added";
+pub const TASK_ATTACHMENT_REMOVED: &str = "task.attachment.removed";
+pub const TASK_LOCKED: &str = "task.locked";
+pub const TASK_UNLOCKED: &str = "task.unlocked";
+pub const TASK_BATCH_MOVED: &str = "task.batch_moved";
+pub const TASK_BATCH_UPDATED: &str = "task.batch_updated";
+pub const TASK_ASSIGNED: &str = "task.assigned";
+pub const TASK_COMMENT_PRESSURE: &str = "task.comment_pressure";
+pub const TASK_COMMENTS_COLLAPSED: &str = "task.comments_collapsed";
+pub const BOARD_RETENTION_PURGED: &str = "board.retention_purged";
+pub const BOARD_MERGED: &str = "board.merged";
+pub const BOARD_UPDATED: &str = "board.updated";
+pub const BOARD_ARCHIVED: &str = "board.archived";
+pub const BOARD_UNARCHIVED: &str = "board.unarchived";
+pub const BOARD_KEY_ROTATED: &str = "board.key_rotated";
+pub const COLUMN_CREATED: &str = "column.created";
+pub const COLUMN_UPDATED: &str = "column.updated";
+pub const COLUMN_DELETED: &str = "column.deleted";
+pub const COLUMN_REORDERED: &str = "column.reordered";
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct EventType {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+pub const EVENT_TYPES: &[EventType] = &[
+    EventType { name: TASK_CREATED, description: "A task was created (including clones)" },
+    EventType { name: TASK_UPDATED, description: "Task fields changed" },
+    EventType { name: TASK_DELETED, description: "A task was deleted" },
+    EventType { name: TASK_CLAIMED, description: "An agent claimed a task" },
+    EventType { name: TASK_RELEASED, description: "A claim was released" },
+    EventType { name: TASK_MOVED, description: "A task moved to another column" },
+    EventType { name: TASK_REORDERED, description: "A task's position (and possibly column) changed" },
+    EventType { name: TASK_COMMENT, description: "A comment was posted" },
+    EventType { name: TASK_COMMENT_EDITED, description: "A comment's text was edited" },
+    EventType { name: TASK_COMMENT_DELETED, description: "A comment was deleted" },
+    EventType { name: TASK_ARCHIVED, description: "A task was archived" },
+    EventType { name: TASK_UNARCHIVED, description: "A task was restored from the archive" },
+    EventType { name: TASK_RESTORED, description: "A deleted task was restored from the trash" },
+    EventType { name: TASK_DEPENDENCY_ADDED, description: "A dependency between two tasks was added" },
+    EventType { name: TASK_DEPENDENCY_REMOVED, description: "A dependency between two tasks was removed" },
+    EventType { name: TASK_CHILD_ADDED, description: "A task was attached as a child of another task" },
+    EventType { name: TASK_CHILD_REMOVED, description: "A child task was detached from its parent" },
+    EventType { name: TASK_ATTACHMENT_ADDED, description: "A file was attached to a task" },
+    EventType { name: TASK_ATTACHMENT_REMOVED, description: "An attachment was deleted from a task" },
+    EventType { name: TASK_LOCKED, description: "Someone started (or renewed) an edit lock on a task" },
+    EventType { name: TASK_UNLOCKED, description: "An edit lock was released" },
+    EventType { name: TASK_BATCH_MOVED, description: "A batch operation moved several tasks to one column" },
+    EventType { name: TASK_BATCH_UPDATED, description: "A batch operation applied the same fields to several tasks" },
+    EventType { name: TASK_ASSIGNED, description: "A column's auto-assignment rule assigned a task that entered it" },
+    EventType { name: TASK_COMMENT_PRESSURE, description: "A task's comments passed the board's comment pressure threshold" },
+    EventType { name: TASK_COMMENTS_COLLAPSED, description: "A task's older comments were collapsed into a transcript" },
+    EventType { name: BOARD_RETENTION_PURGED, description: "The board's retention policy purged archived tasks or old activity" },
+    EventType { name: BOARD_MERGED, description: "Another board's tasks were merged into this board" },
+    EventType { name: BOARD_UPDATED, description: "Board settings (name, description, visibility, policies) changed" },
+    EventType { name: BOARD_ARCHIVED, description: "The board was archived" },
+    EventType { name: BOARD_UNARCHIVED, description: "The board was restored from the archive" },
+    EventType { name: BOARD_KEY_ROTATED, description: "The board's manage key was replaced; the old key no longer works" },
+    EventType { name: COLUMN_CREATED, description: "A column was added" },
+    EventType { name: COLUMN_UPDATED, description: "A column was renamed or its WIP limit or auto-assignment changed" },
+    EventType { name: COLUMN_DELETED, description: "A column was removed" },
+    EventType { name: COLUMN_REORDERED, description: "The board's columns were put in a new order" },
+];
+
+/// All registered event type names, in registry order.
+pub fn names() -> impl Iterator<Item = &'static str> {
+    EVENT_TYPES.iter().map(|t| t.name)
+}
+
+pub fn is_valid(name: &str) -> bool {
+    names().any(|n| n == name)
+}
+
+/// Error message for an unknown event type, listing the valid ones.
+pub fn unknown_message(name: &str) -> String {
+    format!(
+        "Invalid event type '{}'. Valid types: {}",
+        name,
+        names().collect::<Vec<_>>().join(", ")
+    )
+}
+
+/// Check a subscription list; returns the first unknown name's error message.
+pub fn validate(events: &[String]) -> Result<(), String> {
+    match events.iter().find(|e| !is_valid(e)) {
+        Some(bad) => Err(unknown_message(bad)),
+        None => Ok(()),
+    }
+}
+
+/// Parse a comma-separated filter (e.g. `?events=task.moved,task.comment`).
+/// Blank entries are ignored; an empty result means "all events".
+pub fn parse_filter(filter: &str) -> Result<Vec<String>, String> {
+    let events: Vec<String> = filter
+        .split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(str::to_string)
+        .collect();
+    validate(&events)?;
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_has_no_duplicates() {
+        let mut all: Vec<&str> = names().collect();
+        let count = all.len();
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), count);
+        assert!(is_valid(TASK_ARCHIVED) && is_valid(TASK_UNARCHIVED));
+    }
+
+    #[test]
+    fn test_parse_filter() {
+        assert_eq!(
+            parse_filter("task.moved, task.comment,,").unwrap(),
+            vec!["task.moved", "task.comment"]
+        );
+        assert!(parse_filter("").unwrap().is_empty());
+        let err = parse_filter("task.moved,task.exploded").unwrap_err();
+        assert!(err.contains("'task.exploded'"));
+    }
+}
@@ -1,23 +1,93 @@
+use rusqlite::{Connection, OptionalExtension, Transaction};
 use serde::Serialize;
-use std::collections::HashMap;
-use std::sync::Mutex;
-use tokio::sync::broadcast;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use tokio::sync::{broadcast, Notify};
 
 use crate::db::WebhookDb;
+use crate::models::{BoardChannelStats, EventBusStats, SubscriberLag};
+use crate::url_policy::{OutboundClient, UrlPolicy};
 use crate::webhooks;
 
-/// Maximum events buffered per board channel before old events are dropped.
-const CHANNEL_CAPACITY: usize = 256;
+/// Default for the events buffered per board channel before slow subscribers
+/// miss some (`EVENT_BUFFER_SIZE`). Also how many recent events a board keeps
+/// for long-polling.
+pub const DEFAULT_CAPACITY: usize = 256;
+
+/// Current event envelope version. Fields may be added within a version;
+/// removing, renaming or retyping one means a new version.
+pub const ENVELOPE_VERSION: u32 = 1;
 
 /// A board-level event broadcast system.
 ///
 /// Each board gets its own broadcast channel, created lazily on first
-/// subscription. Events are sent to all subscribers of a board.
-/// Also delivers events to registered webhooks.
+/// subscription or event. Events are numbered per board, wrapped in an
+/// `Envelope` and sent to all subscribers of the board.
+/// Also delivers events to registered webhooks. Clones share the same channels,
+/// so background jobs can hold their own handle.
+#[derive(Clone)]
 pub struct EventBus {
-    channels: Mutex<HashMap<String, broadcast::Sender<BoardEvent>>>,
+    channels: Arc<Mutex<HashMap<String, Channel>>>,
+    capacity: usize,
     webhook_db: Option<WebhookDb>,
-    http_client: reqwest::Client,
+    outbound: OutboundClient,
+    retry_policy: webhooks::RetryPolicy,
+    /// Wakes the webhook outbox dispatcher when rows are queued
+    outbox_wake: Arc<Notify>,
+    dispatcher_started: Arc<AtomicBool>,
+}
+
+/// A board's broadcast channel plus its most recent events, numbered so a
+/// long-poller can ask for what came after a cursor.
+struct Channel {
+    sender: broadcast::Sender<Envelope>,
+    /// Number of the last event emitted; counts from 1 per server process
+    seq: u64,
+    recent: VecDeque<Envelope>,
+    /// Open subscriptions, for stats; dropped ones are pruned as found
+    subscribers: Vec<Subscriber>,
+    /// Events missed by all subscribers since the server started
+    events_lost: Arc<AtomicU64>,
+}
+
+impl Channel {
+    fn new(capacity: usize) -> Self {
+        Self {
+            sender: broadcast::channel(capacity).0,
+            seq: 0,
+            recent: VecDeque::new(),
+            subscribers: Vec::new(),
+            events_lost: Arc::new(AtomicU64::new(0)),
+        }
+    }
+}
+
+/// How far one subscription has fallen behind, shared between it and the stats.
+#[derive(Debug, Default)]
+struct Lag {
+    /// Times the subscriber fell behind
+    count: AtomicU64,
+    events_lost: AtomicU64,
+}
+
+struct Subscriber {
+    kind: &'static str,
+    connected_at: String,
+    lag: Weak<Lag>,
+}
+
+/// The recent events on a board after a cursor.
+#[derive(Debug)]
+pub struct Backlog {
+    pub events: Vec<Envelope>,
+    /// Number of the board's latest event — the next cursor
+    pub seq: u64,
+    /// Events after the cursor are missing: they fell out of the buffer, or
+    /// the cursor is from before a server restart
+    pub lost: bool,
 }
 
 /// A typed event emitted when something happens on a board.
@@ -31,6 +101,149 @@ pub struct BoardEvent {
     pub data: serde_json::Value,
 }
 
+/// An event as subscribers and webhooks receive it: the event plus its
+/// envelope version, its number on the board and when it happened.
+/// Derefs to the `BoardEvent`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Envelope {
+    pub version: u32,
+    #[serde(flatten)]
+    pub inner: BoardEvent,
+    /// Per-board event number; counts from 1 per server process
+    pub seq: u64,
+    /// RFC 3339, UTC
+    pub occurred_at: String,
+}
+
+impl Deref for Envelope {
+    type Target = BoardEvent;
+
+    fn deref(&self) -> &BoardEvent {
+        &self.inner
+    }
+}
+
+/// Which of a board's events a subscriber wants.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventFilter {
+    /// Event types to pass; empty = all
+    pub events: Vec<String>,
+    /// Only events about tasks assigned to this agent (case-insensitive)
+    pub assigned: Option<String>,
+    /// A column-scoped token's columns: only events about them or the tasks
+    /// in them; empty = all
+    pub columns: Vec<String>,
+}
+
+impl EventFilter {
+    /// Whether `event` passes the type and assignee filters. Task events
+    /// name their task's assignee (`assigned_to`, or `assignees` for several
+    /// tasks) as the write left it; events about no task don't pass an
+    /// `assigned` filter.
+    pub fn matches(&self, event: &BoardEvent) -> bool {
+        if !self.events.is_empty() && !self.events.contains(&event.event) {
+            return false;
+        }
+        let Some(ref wanted) = self.assigned else {
+            return true;
+        };
+        let single = event.data["assigned_to"].as_str();
+        let batch = event.data["assignees"].as_array().into_iter().flatten().filter_map(|a| a.as_str());
+        single.into_iter().chain(batch).any(|a| a.eq_ignore_ascii_case(wanted))
+    }
+
+    /// Whether `event` is inside `columns`: it names one of them as where its
+    /// task is (`column_id`, or `column_ids` for several tasks) or where a
+    /// move took it from or to (`from` / `to`, a batch move's `moves`).
+    /// Events about no column or task don't pass a scope.
+    pub fn in_columns(&self, event: &BoardEvent) -> bool {
+        if self.columns.is_empty() {
+            return true;
+        }
+        let data = &event.data;
+        let named = ["column_id", "from", "to"].into_iter().filter_map(|key| data[key].as_str());
+        let batch = data["column_ids"].as_array().into_iter().flatten().filter_map(|c| c.as_str());
+        let moved_from = data["moves"].as_array().into_iter().flatten().filter_map(|m| m["from"].as_str());
+        named.chain(batch).chain(moved_from).any(|c| self.columns.iter().any(|s| s == c))
+    }
+
+    /// `matches` and `in_columns`.
+    pub fn admits(&self, event: &BoardEvent) -> bool {
+        self.matches(event) && self.in_columns(event)
+    }
+}
+
+/// Fill in where the tasks an event is about are and who has them, as the
+/// write leaves them on `conn`, so subscribers filter on the event alone:
+/// `column_id` and `assigned_to` for one task (`task_id`), the distinct
+/// `column_ids` and `assignees` for several (`task_ids`). Keys the event
+/// already carries are kept, and tasks that are gone add nothing.
+fn describe_tasks(conn: &Connection, data: &mut serde_json::Value) -> rusqlite::Result<()> {
+    let Some(fields) = data.as_object_mut() else {
+        return Ok(());
+    };
+    let locate = |task_id: &str| {
+        conn.query_row(
+            "SELECT column_id, assigned_to FROM tasks WHERE id = ?1",
+            rusqlite::params![task_id],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)),
+        )
+        .optional()
+    };
+    if let Some(task_id) = fields.get("task_id").and_then(|id| id.as_str()) {
+        if let Some((column_id, assigned_to)) = locate(task_id)? {
+            fields.entry("column_id").or_insert(column_id.into());
+            fields.entry("assigned_to").or_insert(assigned_to.into());
+        }
+    } else if let Some(task_ids) = fields.get("task_ids").and_then(|ids| ids.as_array()) {
+        let (mut column_ids, mut assignees) = (Vec::new(), Vec::new());
+        for task_id in task_ids.iter().filter_map(|id| id.as_str()) {
+            let Some((column_id, assigned_to)) = locate(task_id)? else {
+                continue;
+            };
+            if !column_ids.contains(&column_id) {
+                column_ids.push(column_id);
+            }
+            if let Some(assignee) = assigned_to.filter(|a| !assignees.contains(a)) {
+                assignees.push(assignee);
+            }
+        }
+        fields.entry("column_ids").or_insert(column_ids.into());
+        fields.entry("assignees").or_insert(assignees.into());
+    }
+    Ok(())
+}
+
+/// A board subscription that only yields events passing its filter, and
+/// counts the events it misses for `EventBus::stats`.
+pub struct Subscription {
+    rx: broadcast::Receiver<Envelope>,
+    pub filter: EventFilter,
+    lag: Arc<Lag>,
+    board_events_lost: Arc<AtomicU64>,
+}
+
+impl Subscription {
+    /// The next event passing the filter. Lag is reported as soon as it's
+    /// seen, like a plain receiver's.
+    pub async fn recv(&mut self) -> Result<Envelope, broadcast::error::RecvError> {
+        loop {
+            let event = match self.rx.recv().await {
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    self.lag.count.fetch_add(1, Ordering::Relaxed);
+                    self.lag.events_lost.fetch_add(missed, Ordering::Relaxed);
+                    self.board_events_lost.fetch_add(missed, Ordering::Relaxed);
+                    return Err(broadcast::error::RecvError::Lagged(missed));
+                }
+                other => other?,
+            };
+            if self.filter.admits(&event) {
+                return Ok(event);
+            }
+        }
+    }
+}
+
 impl Default for EventBus {
     fn default() -> Self {
         Self::new()
@@ -41,45 +254,470 @@ impl EventBus {
     #[allow(dead_code)]
     pub fn new() -> Self {
         Self {
-            channels: Mutex::new(HashMap::new()),
+            channels: Arc::new(Mutex::new(HashMap::new())),
+            capacity: DEFAULT_CAPACITY,
             webhook_db: None,
-            http_client: reqwest::Client::new(),
+            outbound: OutboundClient::default(),
+            retry_policy: webhooks::RetryPolicy::default(),
+            outbox_wake: Arc::new(Notify::new()),
+            dispatcher_started: Arc::new(AtomicBool::new(false)),
         }
     }
 
     /// Create an EventBus with webhook delivery support.
     pub fn with_webhooks(webhook_db: WebhookDb) -> Self {
         Self {
-            channels: Mutex::new(HashMap::new()),
+            channels: Arc::new(Mutex::new(HashMap::new())),
+            capacity: DEFAULT_CAPACITY,
             webhook_db: Some(webhook_db),
-            http_client: reqwest::Client::new(),
+            outbound: OutboundClient::default(),
+            retry_policy: webhooks::RetryPolicy::default(),
+            outbox_wake: Arc::new(Notify::new()),
+            dispatcher_started: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Override how many events each board buffers (at least 1). Applies to
+    /// boards whose channel hasn't been created yet.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Buffer size from `EVENT_BUFFER_SIZE`, else `DEFAULT_CAPACITY`.
+    pub fn capacity_from_env() -> usize {
+        std::env::var("EVENT_BUFFER_SIZE")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .filter(|n| *n >= 1)
+            .unwrap_or(DEFAULT_CAPACITY)
+    }
+
+    /// Override how webhook deliveries are retried before dead-lettering.
+    pub fn with_retry_policy(mut self, policy: webhooks::RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Override which URLs outbound requests may be sent to.
+    pub fn with_url_policy(mut self, policy: UrlPolicy) -> Self {
+        self.outbound = OutboundClient::new(policy);
+        self
+    }
+
+    /// Start draining the webhook outbox, once. Called at liftoff so rows left
+    /// by a previous process go out, and on the first queued event otherwise.
+    pub fn start_webhook_dispatcher(&self) {
+        let (Some(db), Ok(runtime)) = (&self.webhook_db, tokio::runtime::Handle::try_current()) else {
+            return;
+        };
+        if self.dispatcher_started.swap(true, Ordering::SeqCst) {
+            return;
         }
+        runtime.spawn(webhooks::run_dispatcher(
+            db.clone(),
+            self.outbound.clone(),
+            self.retry_policy,
+            self.outbox_wake.clone(),
+        ));
     }
 
-    /// Subscribe to events for a specific board.
-    /// Returns a broadcast receiver that yields BoardEvents.
-    pub fn subscribe(&self, board_id: &str) -> broadcast::Receiver<BoardEvent> {
+    /// Have the dispatcher look at the outbox now, e.g. after dead letters
+    /// were requeued.
+    pub fn wake_webhook_dispatcher(&self) {
+        self.start_webhook_dispatcher();
+        self.outbox_wake.notify_one();
+    }
+
+    /// Client for the requests the server makes on a board's behalf —
+    /// webhook deliveries, validation hooks, attachment storage, GitHub
+    /// imports — held to the URL policy.
+    pub fn outbound(&self) -> &OutboundClient {
+        &self.outbound
+    }
+
+    /// The buffered events on a board numbered after `after`, and a receiver
+    /// for the ones after those.
+    pub fn poll(&self, board_id: &str, after: u64) -> (Backlog, broadcast::Receiver<Envelope>) {
+        let mut channels = self.channels.lock().unwrap();
+        let capacity = self.capacity;
+        let channel = channels.entry(board_id.to_string()).or_insert_with(|| Channel::new(capacity));
+        let restarted = after > channel.seq;
+        let after = if restarted { 0 } else { after };
+        let events: Vec<Envelope> = channel.recent.iter().filter(|e| e.seq > after).cloned().collect();
+        let first = events.first().map(|e| e.seq).unwrap_or(channel.seq + 1);
+        let backlog = Backlog {
+            lost: restarted || first > after + 1,
+            events,
+            seq: channel.seq,
+        };
+        (backlog, channel.sender.subscribe())
+    }
+
+    /// Subscribe to the events on a board that pass `filter`, checked as
+    /// they arrive so filtered-out events are never sent. `kind` names the
+    /// transport in the stats.
+    pub fn subscribe_filtered(&self, board_id: &str, kind: &'static str, filter: EventFilter) -> Subscription {
         let mut channels = self.channels.lock().unwrap();
-        let sender = channels
-            .entry(board_id.to_string())
-            .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0);
-        sender.subscribe()
+        let capacity = self.capacity;
+        let channel = channels.entry(board_id.to_string()).or_insert_with(|| Channel::new(capacity));
+        let lag = Arc::new(Lag::default());
+        channel.subscribers.retain(|s| s.lag.strong_count() > 0);
+        channel.subscribers.push(Subscriber {
+            kind,
+            connected_at: chrono::Utc::now().to_rfc3339(),
+            lag: Arc::downgrade(&lag),
+        });
+        Subscription {
+            rx: channel.sender.subscribe(),
+            filter,
+            lag,
+            board_events_lost: channel.events_lost.clone(),
+        }
     }
 
-    /// Emit an event to all subscribers of a board.
-    /// Also delivers to registered webhooks asynchronously.
-    pub fn emit(&self, event: BoardEvent) {
-        // Deliver to SSE subscribers
+    /// Buffer size, and per-board event counts and subscriber lag, busiest
+    /// boards first.
+    pub fn stats(&self) -> EventBusStats {
+        let mut channels = self.channels.lock().unwrap();
+        let mut boards: Vec<BoardChannelStats> = channels
+            .iter_mut()
+            .map(|(board_id, channel)| {
+                channel.subscribers.retain(|s| s.lag.strong_count() > 0);
+                let subscribers: Vec<SubscriberLag> = channel
+                    .subscribers
+                    .iter()
+                    .filter_map(|s| {
+                        let lag = s.lag.upgrade()?;
+                        Some(SubscriberLag {
+                            kind: s.kind.to_string(),
+                            connected_at: s.connected_at.clone(),
+                            lag_count: lag.count.load(Ordering::Relaxed),
+                            events_lost: lag.events_lost.load(Ordering::Relaxed),
+                        })
+                    })
+                    .collect();
+                BoardChannelStats {
+                    board_id: board_id.clone(),
+                    events_emitted: channel.seq,
+                    buffered: channel.recent.len(),
+                    events_lost: channel.events_lost.load(Ordering::Relaxed),
+                    subscriber_count: subscribers.len(),
+                    subscribers,
+                }
+            })
+            .collect();
+        boards.sort_by(|a, b| {
+            (b.events_lost, b.events_emitted, &a.board_id).cmp(&(a.events_lost, a.events_emitted, &b.board_id))
+        });
+        EventBusStats {
+            capacity: self.capacity,
+            boards,
+        }
+    }
+
+    /// Number and emit an event to all subscribers of a board, and queue its
+    /// webhook outbox rows on `conn`. Inside a transaction the event is held
+    /// back until `commit` and dropped if the transaction rolls back, so
+    /// subscribers and webhooks only hear about changes that were stored. A
+    /// failure to queue is returned for the caller to fail the write with.
+    pub fn emit(&self, conn: &Connection, mut event: BoardEvent) -> rusqlite::Result<()> {
+        describe_tasks(conn, &mut event.data)?;
+        if !conn.is_autocommit() {
+            conn.rollback_hook(Some(|| PENDING.with(|pending| pending.borrow_mut().clear())));
+            PENDING.with(|pending| pending.borrow_mut().push((self.clone(), event)));
+            return Ok(());
+        }
+        self.stage(conn, vec![event])?.publish();
+        Ok(())
+    }
+
+    /// Number `events` and queue their webhooks on `conn`, holding the
+    /// channel lock until `Staged::publish` so seqs go out in order and a
+    /// write that fails in between uses none up.
+    fn stage(&self, conn: &Connection, events: Vec<BoardEvent>) -> rusqlite::Result<Staged<'_>> {
         let channels = self.channels.lock().unwrap();
-        if let Some(sender) = channels.get(&event.board_id) {
+        let mut seqs: HashMap<String, u64> = HashMap::new();
+        let occurred_at = chrono::Utc::now().to_rfc3339();
+        let envelopes: Vec<Envelope> = events
+            .into_iter()
+            .map(|event| {
+                let seq = seqs
+                    .entry(event.board_id.clone())
+                    .or_insert_with(|| channels.get(&event.board_id).map_or(0, |c| c.seq));
+                *seq += 1;
+                Envelope {
+                    version: ENVELOPE_VERSION,
+                    inner: event,
+                    seq: *seq,
+                    occurred_at: occurred_at.clone(),
+                }
+            })
+            .collect();
+        let mut queued = 0;
+        if self.webhook_db.is_some() {
+            for envelope in &envelopes {
+                queued += webhooks::enqueue(conn, envelope)?;
+            }
+        }
+        Ok(Staged {
+            bus: self,
+            channels,
+            envelopes,
+            queued,
+        })
+    }
+}
+
+/// Events numbered and queued for webhooks, waiting for their write to be
+/// stored before subscribers see them.
+struct Staged<'a> {
+    bus: &'a EventBus,
+    channels: MutexGuard<'a, HashMap<String, Channel>>,
+    envelopes: Vec<Envelope>,
+    queued: usize,
+}
+
+impl Staged<'_> {
+    /// Deliver to SSE subscribers and long-pollers, and wake the webhook
+    /// dispatcher for the queued rows.
+    fn publish(mut self) {
+        let capacity = self.bus.capacity;
+        for envelope in self.envelopes {
+            let channel = self
+                .channels
+                .entry(envelope.board_id.clone())
+                .or_insert_with(|| Channel::new(capacity));
+            channel.seq = envelope.seq;
+            channel.recent.push_back(envelope.clone());
+            if channel.recent.len() > capacity {
+                channel.recent.pop_front();
+            }
             // Ignore send errors (no subscribers)
-            let _ = sender.send(event.clone());
+            let _ = channel.sender.send(envelope);
+        }
+        drop(self.channels);
+        if self.queued > 0 {
+            self.bus.wake_webhook_dispatcher();
+        }
+    }
+}
+
+thread_local! {
+    /// Events emitted in a transaction still open on this thread
+    static PENDING: RefCell<Vec<(EventBus, BoardEvent)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Commit `tx` along with the events emitted in it: their webhook outbox rows
+/// are stored in it, and subscribers get them once it has committed. Every
+/// transaction that emits must end here rather than in `Transaction::commit`;
+/// `discard_uncommitted` asserts it in debug builds.
+pub fn commit(tx: Transaction<'_>) -> rusqlite::Result<()> {
+    let mut groups: Vec<(EventBus, Vec<BoardEvent>)> = Vec::new();
+    for (bus, event) in PENDING.with(|pending| pending.take()) {
+        match groups.iter_mut().find(|(b, _)| Arc::ptr_eq(&b.channels, &bus.channels)) {
+            Some((_, events)) => events.push(event),
+            None => groups.push((bus, vec![event])),
         }
-        drop(channels);
+    }
+    // A consistent lock order across threads
+    groups.sort_by_key(|(bus, _)| Arc::as_ptr(&bus.channels) as usize);
+    let staged = groups
+        .iter_mut()
+        .map(|(bus, events)| bus.stage(&tx, std::mem::take(events)))
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    tx.commit()?;
+    for staged in staged {
+        staged.publish();
+    }
+    Ok(())
+}
+
+/// Drop the events of transactions that never reached `commit`. `with_db`
+/// calls it when a handler's work is done. A rolled-back transaction has
+/// already cleared its events, so any left over were emitted in one committed
+/// some other way, and their outbox rows were never written.
+pub(crate) fn discard_uncommitted() {
+    let leaked = PENDING.with(|pending| pending.take());
+    debug_assert!(
+        leaked.is_empty(),
+        "{} event(s) emitted in a transaction committed outside events::commit",
+        leaked.len()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_filter_by_type_and_assignee() {
+        let event = |name: &str, data: serde_json::Value| BoardEvent {
+            event: name.to_string(),
+            board_id: "b1".to_string(),
+            data,
+        };
+        let filter = EventFilter {
+            events: vec!["task.moved".to_string(), "task.batch_moved".to_string()],
+            assigned: Some("nanookbot".to_string()),
+            ..Default::default()
+        };
+
+        assert!(filter.matches(&event("task.moved", serde_json::json!({"task_id": "t1", "assigned_to": "NanookBot"}))));
+        assert!(!filter.matches(&event("task.moved", serde_json::json!({"task_id": "t2", "assigned_to": "Sam"}))));
+        assert!(!filter.matches(&event("task.moved", serde_json::json!({"task_id": "t3", "assigned_to": null}))));
+        assert!(!filter.matches(&event("task.comment", serde_json::json!({"task_id": "t1", "assigned_to": "NanookBot"}))));
+        assert!(filter.matches(&event(
+            "task.batch_moved",
+            serde_json::json!({"task_ids": ["t2", "t1"], "assignees": ["Sam", "NanookBot"]})
+        )));
+        assert!(EventFilter::default().matches(&event("board.merged", serde_json::json!({}))));
+        let assigned_only = EventFilter { assigned: Some("NanookBot".to_string()), ..Default::default() };
+        assert!(!assigned_only.matches(&event("board.merged", serde_json::json!({}))));
+    }
+
+    #[test]
+    fn test_event_filter_by_column_scope() {
+        let event = |data: serde_json::Value| BoardEvent {
+            event: "task.updated".to_string(),
+            board_id: "b1".to_string(),
+            data,
+        };
+        let scoped = EventFilter { columns: vec!["inbox".to_string()], ..Default::default() };
+
+        assert!(scoped.in_columns(&event(serde_json::json!({"task_id": "t1", "column_id": "inbox"}))));
+        assert!(!scoped.in_columns(&event(serde_json::json!({"task_id": "t2", "column_id": "done"}))));
+        assert!(scoped.in_columns(&event(serde_json::json!({"task_ids": ["t2", "t1"], "column_ids": ["done", "inbox"]}))));
+        // A move out of the scope is still seen, single or batched
+        assert!(scoped.in_columns(&event(serde_json::json!({"task_id": "t2", "from": "inbox", "to": "done"}))));
+        assert!(scoped.in_columns(&event(serde_json::json!({
+            "task_ids": ["t2"],
+            "column_ids": ["done"],
+            "to": "done",
+            "moves": [{"task_id": "t2", "from": "inbox"}],
+        }))));
+        assert!(!scoped.in_columns(&event(serde_json::json!({"task_id": "t2"}))));
+        assert!(!scoped.in_columns(&event(serde_json::json!({}))));
+        assert!(EventFilter::default().in_columns(&event(serde_json::json!({}))));
+    }
+
+    #[test]
+    fn test_emit_describes_tasks() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE tasks (id TEXT PRIMARY KEY, column_id TEXT NOT NULL, assigned_to TEXT);
+             INSERT INTO tasks VALUES ('t1', 'inbox', 'NanookBot'), ('t2', 'done', NULL), ('t3', 'inbox', 'NanookBot');",
+        )
+        .unwrap();
+        let bus = EventBus::new();
+        let emit = |data: serde_json::Value| {
+            let event = BoardEvent { event: "task.updated".to_string(), board_id: "b1".to_string(), data };
+            bus.emit(&conn, event).unwrap();
+            bus.poll("b1", 0).0.events.last().unwrap().data.clone()
+        };
 
-        // Deliver to webhooks (async, non-blocking)
-        if let Some(ref db) = self.webhook_db {
-            webhooks::deliver_webhooks(db.clone(), event, self.http_client.clone());
+        let data = emit(serde_json::json!({"task_id": "t1"}));
+        assert_eq!((data["column_id"].as_str(), data["assigned_to"].as_str()), (Some("inbox"), Some("NanookBot")));
+        // What the event says is kept
+        let data = emit(serde_json::json!({"task_id": "t1", "assigned_to": "Sam"}));
+        assert_eq!(data["assigned_to"], "Sam");
+        let data = emit(serde_json::json!({"task_id": "t2"}));
+        assert!(data["assigned_to"].is_null() && data.get("assigned_to").is_some());
+        let data = emit(serde_json::json!({"task_ids": ["t1", "t2", "t3", "gone"]}));
+        assert_eq!(data["column_ids"], serde_json::json!(["inbox", "done"]));
+        assert_eq!(data["assignees"], serde_json::json!(["NanookBot"]));
+        let data = emit(serde_json::json!({"task_id": "gone", "title": "Deleted"}));
+        assert!(data.get("column_id").is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "committed outside events::commit")]
+    fn test_commit_outside_events_commit_is_caught() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE tasks (id TEXT PRIMARY KEY, column_id TEXT, assigned_to TEXT);").unwrap();
+        let bus = EventBus::new();
+        let tx = conn.unchecked_transaction().unwrap();
+        let event = BoardEvent { event: "task.deleted".to_string(), board_id: "b1".to_string(), data: serde_json::json!({}) };
+        bus.emit(&tx, event).unwrap();
+        tx.commit().unwrap();
+        discard_uncommitted();
+    }
+
+    #[test]
+    fn test_poll_backlog() {
+        let bus = EventBus::new();
+        let conn = Connection::open_in_memory().unwrap();
+        let event = |n: u64| BoardEvent {
+            event: "task.created".to_string(),
+            board_id: "b1".to_string(),
+            data: serde_json::json!({"n": n}),
+        };
+        let (backlog, _rx) = bus.poll("b1", 0);
+        assert!(backlog.events.is_empty());
+        assert_eq!(backlog.seq, 0);
+        assert!(!backlog.lost);
+
+        for n in 1..=3 {
+            bus.emit(&conn, event(n)).unwrap();
         }
+        let (backlog, _) = bus.poll("b1", 1);
+        assert_eq!(backlog.events.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(backlog.events[0].data["n"], 2);
+        assert_eq!(backlog.seq, 3);
+        assert!(!backlog.lost);
+
+        let envelope = serde_json::to_value(&backlog.events[0]).unwrap();
+        assert_eq!(envelope["version"], ENVELOPE_VERSION);
+        assert_eq!(envelope["event"], "task.created");
+        assert_eq!(envelope["board_id"], "b1");
+        assert_eq!(envelope["seq"], 2);
+        assert!(envelope["occurred_at"].as_str().unwrap().ends_with("+00:00"));
+        assert_eq!(envelope["data"]["n"], 2);
+
+        for n in 4..=(DEFAULT_CAPACITY as u64 + 4) {
+            bus.emit(&conn, event(n)).unwrap();
+        }
+        let (backlog, _) = bus.poll("b1", 3);
+        assert!(backlog.lost);
+        assert_eq!(backlog.events.len(), DEFAULT_CAPACITY);
+        // A cursor from a previous server process starts over
+        let (backlog, _) = bus.poll("b1", 10_000);
+        assert!(backlog.lost);
+        assert_eq!(backlog.events.len(), DEFAULT_CAPACITY);
+    }
+
+    #[rocket::async_test]
+    async fn test_capacity_and_lag_stats() {
+        let bus = EventBus::new().with_capacity(2);
+        let mut slow = bus.subscribe_filtered("b1", "sse", EventFilter::default());
+        let idle = bus.subscribe_filtered("b2", "websocket", EventFilter::default());
+        let conn = Connection::open_in_memory().unwrap();
+        for n in 0..5 {
+            bus.emit(
+                &conn,
+                BoardEvent {
+                    event: "task.created".to_string(),
+                    board_id: "b1".to_string(),
+                    data: serde_json::json!({"n": n}),
+                },
+            )
+            .unwrap();
+        }
+        assert!(matches!(slow.recv().await, Err(broadcast::error::RecvError::Lagged(3))));
+        assert_eq!(slow.recv().await.unwrap().data["n"], 3);
+
+        let stats = bus.stats();
+        assert_eq!(stats.capacity, 2);
+        assert_eq!(stats.boards.iter().map(|b| b.board_id.as_str()).collect::<Vec<_>>(), vec!["b1", "b2"]);
+        let b1 = &stats.boards[0];
+        assert_eq!((b1.events_emitted, b1.buffered, b1.events_lost), (5, 2, 3));
+        assert_eq!(b1.subscribers[0].kind, "sse");
+        assert_eq!((b1.subscribers[0].lag_count, b1.subscribers[0].events_lost), (1, 3));
+
+        // Closed subscriptions drop out; the board keeps its total
+        drop((slow, idle));
+        let stats = bus.stats();
+        assert_eq!(stats.boards[0].events_lost, 3);
+        assert!(stats.boards.iter().all(|b| b.subscribers.is_empty()));
     }
 }
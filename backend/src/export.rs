@@ -0,0 +1,215 @@
+//! Whole-board export: a single versioned JSON document for backup or migration.
+
+use chrono::Utc;
+use rocket::http::{Header, Status};
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::Connection;
+
+use crate::access::{self, BoardRole, Visibility};
+use crate::auth::BoardToken;
+use crate::db::{hash_key, DbPool};
+use crate::models::*;
+use crate::routes::{db_error, task_from_row, with_db, TASK_COLUMNS};
+use crate::webhooks;
+
+pub const EXPORT_FORMAT: &str = "kanban-board-export";
+pub const EXPORT_VERSION: u32 = 1;
+
+#[derive(Responder)]
+pub struct ExportDownload {
+    body: Json<BoardExport>,
+    disposition: Header<'static>,
+}
+
+/// Export a board — requires manage key (or an admin collaborator key).
+/// Columns, tasks, activity, comments, dependencies and webhooks (without
+/// secrets, URLs cut back to their origin),
+/// read inside one transaction so the snapshot is consistent.
+#[get("/boards/<board_id>/export")]
+pub async fn export_board(
+    board_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<ExportDownload, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Admin)?;
+
+        let tx = conn.unchecked_transaction().map_err(|e| db_error(&e.to_string()))?;
+        let export = build_export(&tx, board_id).map_err(|e| db_error(&e.to_string()))?;
+        tx.commit().map_err(|e| db_error(&e.to_string()))?;
+
+        Ok(ExportDownload {
+            body: Json(export),
+            disposition: Header::new(
+                "Content-Disposition",
+                format!("attachment; filename=\"board-{}.json\"", board_id),
+            ),
+        })
+    })
+    .await
+}
+
+pub fn build_export(conn: &Connection, board_id: &str) -> rusqlite::Result<BoardExport> {
+    let board = conn.query_row(
+        "SELECT id, name, description, is_public, archived, require_display_name, enforce_dependencies,
+                quick_done_column_id, quick_done_auto_archive, quick_reassign_column_id, quick_reassign_to,
+                created_at, updated_at, visibility
+         FROM boards WHERE id = ?1",
+        rusqlite::params![board_id],
+        |row| {
+            Ok(ExportBoard {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                description: row.get(2)?,
+                is_public: row.get::<_, i32>(3)? == 1,
+                visibility: row.get::<_, Option<String>>(13)?.as_deref().and_then(Visibility::parse),
+                archived: row.get::<_, i32>(4)? == 1,
+                require_display_name: row.get::<_, i32>(5)? == 1,
+                enforce_dependencies: row.get::<_, i32>(6)? == 1,
+                quick_done_column_id: row.get(7)?,
+                quick_done_auto_archive: row.get::<_, i32>(8)? == 1,
+                quick_reassign_column_id: row.get(9)?,
+                quick_reassign_to: row.get(10)?,
+                created_at: row.get(11)?,
+                updated_at: row.get(12)?,
+            })
+        },
+    )?;
+
+    let columns = collect(
+        conn,
+        "SELECT id, name, position, wip_limit, created_at
+         FROM columns WHERE board_id = ?1 ORDER BY position ASC",
+        board_id,
+        |row| {
+            Ok(ExportColumn {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                position: row.get(2)?,
+                wip_limit: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        },
+    )?;
+
+    let tasks = collect(
+        conn,
+        &format!(
+            "SELECT {TASK_COLUMNS}
+             FROM tasks t JOIN columns c ON c.id = t.column_id
+             WHERE t.board_id = ?1
+             ORDER BY c.position ASC, t.position ASC, t.created_at ASC, t.rowid ASC"
+        ),
+        board_id,
+        |row| task_from_row(row).map(export_task),
+    )?;
+
+    let (comments, events): (Vec<ExportEvent>, Vec<ExportEvent>) = collect(
+        conn,
+        "SELECT te.id, te.task_id, te.event_type, te.actor, te.data, te.seq, te.created_at
+         FROM task_events te JOIN tasks t ON t.id = te.task_id
+         WHERE t.board_id = ?1
+         ORDER BY COALESCE(te.seq, 0) ASC, te.created_at ASC",
+        board_id,
+        |row| {
+            let data: String = row.get(4)?;
+            Ok(ExportEvent {
+                id: row.get(0)?,
+                task_id: row.get(1)?,
+                event_type: row.get(2)?,
+                actor: row.get(3)?,
+                data: serde_json::from_str(&data).unwrap_or(serde_json::json!({})),
+                seq: row.get(5)?,
+                created_at: row.get(6)?,
+            })
+        },
+    )?
+    .into_iter()
+    .partition(|e| e.event_type == "comment");
+
+    let dependencies = collect(
+        conn,
+        "SELECT id, blocker_task_id, blocked_task_id, note, created_by, created_at
+         FROM task_dependencies WHERE board_id = ?1 ORDER BY created_at ASC, id ASC",
+        board_id,
+        |row| {
+            Ok(ExportDependency {
+                id: row.get(0)?,
+                blocker_task_id: row.get(1)?,
+                blocked_task_id: row.get(2)?,
+                note: row.get(3)?,
+                created_by: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        },
+    )?;
+
+    let webhooks = collect(
+        conn,
+        "SELECT id, url, events, active, created_by, created_at
+         FROM webhooks WHERE board_id = ?1 ORDER BY created_at ASC, id ASC",
+        board_id,
+        |row| {
+            let events: String = row.get(2)?;
+            Ok(ExportWebhook {
+                id: row.get(0)?,
+                url: webhooks::redact_url(&row.get::<_, String>(1)?),
+                events: serde_json::from_str(&events).unwrap_or_default(),
+                active: row.get::<_, i32>(3)? == 1,
+                created_by: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        },
+    )?;
+
+    Ok(BoardExport {
+        format: EXPORT_FORMAT.to_string(),
+        version: EXPORT_VERSION,
+        exported_at: Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        server_version: env!("CARGO_PKG_VERSION").to_string(),
+        board,
+        columns,
+        tasks,
+        events,
+        comments,
+        dependencies,
+        webhooks,
+    })
+}
+
+/// The stored fields of a task, as the export carries them.
+fn export_task(task: TaskResponse) -> ExportTask {
+    ExportTask {
+        id: task.id,
+        column_id: task.column_id,
+        title: task.title,
+        description: task.description,
+        priority: task.priority,
+        position: task.position,
+        created_by: task.created_by,
+        assigned_to: task.assigned_to,
+        claimed_by: task.claimed_by,
+        claimed_at: task.claimed_at,
+        labels: task.labels,
+        metadata: task.metadata,
+        due_at: task.due_at,
+        completed_at: task.completed_at,
+        archived_at: task.archived_at,
+        created_at: task.created_at,
+        updated_at: task.updated_at,
+    }
+}
+
+fn collect<T>(
+    conn: &Connection,
+    sql: &str,
+    board_id: &str,
+    map: impl FnMut(&rusqlite::Row) -> rusqlite::Result<T>,
+) -> rusqlite::Result<Vec<T>> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map(rusqlite::params![board_id], map)?;
+    rows.collect()
+}
@@ -0,0 +1,102 @@
+//! Feature flags for experimental subsystems.
+//!
+//! Operators opt in with `FEATURES=attachments,mcp` or per-flag
+//! `FEATURE_<NAME>=true|false` (which wins over the list). Enabled flags are
+//! reported by `GET /health` so clients can detect capabilities at runtime.
+
+use std::collections::BTreeSet;
+
+pub const MCP: &str = "mcp";
+pub const GRAPHQL: &str = "graphql";
+pub const ATTACHMENTS: &str = "attachments";
+pub const POSTGRES: &str = "postgres";
+
+pub struct Feature {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Whether this build contains the subsystem. Flags for subsystems that
+    /// aren't built yet are accepted but never reported as enabled.
+    pub available: bool,
+}
+
+pub const FEATURES: &[Feature] = &[
+    Feature { name: MCP, description: "Model Context Protocol endpoint for agents", available: false },
+    Feature { name: GRAPHQL, description: "GraphQL API alongside REST", available: false },
+    Feature { name: ATTACHMENTS, description: "File attachments on tasks", available: true },
+    Feature { name: POSTGRES, description: "PostgreSQL storage backend", available: false },
+];
+
+/// The set of experimental subsystems switched on for this instance.
+#[derive(Debug, Clone, Default)]
+pub struct FeatureFlags {
+    enabled: BTreeSet<&'static str>,
+}
+
+impl FeatureFlags {
+    /// Read `FEATURES` and `FEATURE_<NAME>` overrides. Unknown or unavailable
+    /// flags are logged and ignored.
+    pub fn from_env() -> Self {
+        let listed = std::env::var("FEATURES").unwrap_or_default();
+        let mut requested: BTreeSet<String> = listed
+            .split(',')
+            .map(|f| f.trim().to_lowercase())
+            .filter(|f| !f.is_empty())
+            .collect();
+        for feature in FEATURES {
+            let var = format!("FEATURE_{}", feature.name.to_uppercase());
+            match std::env::var(&var).map(|v| v.trim().to_lowercase()).as_deref() {
+                Ok("1" | "true" | "on" | "yes") => {
+                    requested.insert(feature.name.to_string());
+                }
+                Ok("0" | "false" | "off" | "no") => {
+                    requested.remove(feature.name);
+                }
+                _ => {}
+            }
+        }
+
+        let mut flags = Self::default();
+        for name in requested {
+            match FEATURES.iter().find(|f| f.name == name) {
+                Some(f) if f.available => flags = flags.with(f.name),
+                Some(f) => eprintln!(
+                    "⚠️  Feature '{}' ({}) is not available in this build; ignoring",
+                    f.name, f.description
+                ),
+                None => eprintln!("⚠️  Unknown feature flag '{}'; ignoring", name),
+            }
+        }
+        flags
+    }
+
+    /// Enable a flag directly (tests, embedding). Unavailable flags stay off.
+    pub fn with(mut self, name: &str) -> Self {
+        if let Some(f) = FEATURES.iter().find(|f| f.name == name && f.available) {
+            self.enabled.insert(f.name);
+        }
+        self
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.enabled.contains(name)
+    }
+
+    /// Enabled flag names, sorted.
+    pub fn enabled(&self) -> Vec<&'static str> {
+        self.enabled.iter().copied().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unavailable_flags_stay_off() {
+        let flags = FeatureFlags::default().with(MCP).with("teleport");
+        assert!(!flags.is_enabled(MCP));
+        assert!(flags.enabled().is_empty());
+        assert!(FeatureFlags::default().with(ATTACHMENTS).is_enabled(ATTACHMENTS));
+        assert!(FEATURES.iter().any(|f| f.name == POSTGRES));
+    }
+}
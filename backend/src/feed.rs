@@ -0,0 +1,277 @@
+//! A board's recently created and completed tasks as a JSON Feed
+//! (https://jsonfeed.org), so static site generators, widgets and feed
+//! readers can show board status without a custom client.
+//!
+//! The feed is pinned: standard JSON Feed 1.1 fields plus a `_kanban`
+//! extension per item whose `schema_version` only changes on breaking changes.
+//!
+//! The board's activity — tasks created, moved and commented on — is also
+//! available as an Atom feed for ordinary feed readers.
+
+use chrono::{DateTime, NaiveDateTime};
+use rocket::http::{ContentType, Status};
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::serde::json::Json;
+use rocket::State;
+
+use crate::access;
+use crate::auth::BoardToken;
+use crate::db::DbPool;
+use crate::models::*;
+use crate::routes::{db_error, task_key, with_db};
+
+/// `_kanban.schema_version` of the items this build emits.
+pub const FEED_SCHEMA_VERSION: u32 = 1;
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 200;
+
+/// Scheme and host the request came in on, for the feed's absolute URLs.
+/// Empty when the request has no `Host`, leaving the URLs relative.
+pub struct RequestOrigin(pub(crate) String);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RequestOrigin {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let host = request.host().map(|h| h.to_string()).or_else(|| request.headers().get_one("Host").map(str::to_string));
+        let origin = match host {
+            Some(host) => {
+                let scheme = request.headers().get_one("X-Forwarded-Proto").unwrap_or("http");
+                format!("{}://{}", scheme, host)
+            }
+            None => String::new(),
+        };
+        Outcome::Success(RequestOrigin(origin))
+    }
+}
+
+/// SQLite's `YYYY-MM-DD HH:MM:SS` (UTC) as RFC 3339; other values pass through.
+fn rfc3339(timestamp: &str) -> String {
+    if DateTime::parse_from_rfc3339(timestamp).is_ok() {
+        return timestamp.to_string();
+    }
+    NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S")
+        .map(|t| t.and_utc().to_rfc3339())
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+/// Board feed — public, no auth required. Newest first; `?limit=` (default
+/// 50, max 200) caps the number of items.
+#[get("/boards/<board_id>/feed.json?<limit>")]
+pub async fn board_feed(
+    board_id: &str,
+    limit: Option<u32>,
+    origin: RequestOrigin,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+) -> Result<(ContentType, Json<JsonFeed>), (Status, Json<ApiError>)> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let origin = origin.0;
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        let (name, description, prefix): (String, String, Option<String>) = conn
+            .query_row(
+                "SELECT name, description, task_prefix FROM boards WHERE id = ?1",
+                rusqlite::params![board_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        let home_page_url = format!("{}/board/{}", origin, board_id);
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT t.id, t.title, t.description, t.priority, t.created_by, t.assigned_to, t.labels,
+                        t.completed_at, t.number, c.name, e.event, e.at
+                 FROM (SELECT id, 'created' AS event, created_at AS at FROM tasks WHERE board_id = ?1
+                       UNION ALL
+                       SELECT id, 'completed', completed_at FROM tasks WHERE board_id = ?1 AND completed_at IS NOT NULL) e
+                 JOIN tasks t ON t.id = e.id
+                 JOIN columns c ON c.id = t.column_id
+                 ORDER BY e.at DESC, e.event ASC
+                 LIMIT ?2",
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        let items = stmt
+            .query_map(rusqlite::params![board_id, limit], |row| {
+                let task_id: String = row.get(0)?;
+                let title: String = row.get(1)?;
+                let description: String = row.get(2)?;
+                let created_by: String = row.get(4)?;
+                let assigned_to: Option<String> = row.get(5)?;
+                let labels: String = row.get(6)?;
+                let key = row.get::<_, Option<i64>>(8)?.map(|n| task_key(prefix.as_deref(), n));
+                let event: String = row.get(10)?;
+                let at: String = row.get(11)?;
+
+                let (verb, author) = if event == "completed" {
+                    ("Completed", assigned_to.clone())
+                } else {
+                    ("Created", Some(created_by).filter(|n| !n.is_empty()))
+                };
+                Ok(JsonFeedItem {
+                    id: format!("{}:{}", task_id, event),
+                    url: format!("{}?task={}", home_page_url, task_id),
+                    title: match &key {
+                        Some(key) => format!("{}: {} {}", verb, key, title),
+                        None => format!("{}: {}", verb, title),
+                    },
+                    content_text: if description.is_empty() { title } else { description },
+                    date_published: rfc3339(&at),
+                    tags: serde_json::from_str(&labels).unwrap_or_default(),
+                    authors: author.map(|name| JsonFeedAuthor { name }).into_iter().collect(),
+                    kanban: JsonFeedKanban {
+                        schema_version: FEED_SCHEMA_VERSION,
+                        event,
+                        task_id,
+                        task_key: key,
+                        column: row.get(9)?,
+                        priority: row.get(3)?,
+                        assigned_to,
+                        completed_at: row.get::<_, Option<String>>(7)?.map(|t| rfc3339(&t)),
+                    },
+                })
+            })
+            .map_err(|e| db_error(&e.to_string()))?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| db_error(&e.to_string()))?;
+
+        Ok((
+            ContentType::new("application", "feed+json"),
+            Json(JsonFeed {
+                version: "https://jsonfeed.org/version/1.1".to_string(),
+                title: name,
+                feed_url: format!("{}/api/v1/boards/{}/feed.json", origin, board_id),
+                home_page_url,
+                description,
+                items,
+            }),
+        ))
+    })
+    .await
+}
+
+/// Escape text for XML content and attribute values.
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Board activity as an Atom 1.0 feed — public, no auth required. Task
+/// creations, moves and comments, newest first; `?limit=` (default 50, max
+/// 200) caps the number of entries.
+#[get("/boards/<board_id>/activity.atom?<limit>")]
+pub async fn board_activity_atom(
+    board_id: &str,
+    limit: Option<u32>,
+    origin: RequestOrigin,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+) -> Result<(ContentType, String), (Status, Json<ApiError>)> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let origin = origin.0;
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        let (name, description, prefix): (String, String, Option<String>) = conn
+            .query_row(
+                "SELECT name, description, task_prefix FROM boards WHERE id = ?1",
+                rusqlite::params![board_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        let home_page_url = format!("{}/board/{}", origin, board_id);
+        let feed_url = format!("{}/api/v1/boards/{}/activity.atom", origin, board_id);
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT te.id, te.task_id, t.title, t.number, te.event_type, te.actor, te.data, te.created_at
+                 FROM task_events te
+                 JOIN tasks t ON t.id = te.task_id
+                 WHERE t.board_id = ?1 AND te.event_type IN ('created', 'moved', 'comment')
+                 ORDER BY te.created_at DESC, te.seq DESC
+                 LIMIT ?2",
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        let entries = stmt
+            .query_map(rusqlite::params![board_id, limit], |row| {
+                let event_id: String = row.get(0)?;
+                let task_id: String = row.get(1)?;
+                let title: String = row.get(2)?;
+                let task = match row.get::<_, Option<i64>>(3)? {
+                    Some(n) => format!("{} {}", task_key(prefix.as_deref(), n), title),
+                    None => title,
+                };
+                let event_type: String = row.get(4)?;
+                let actor: String = row.get(5)?;
+                let data: serde_json::Value = serde_json::from_str(&row.get::<_, String>(6)?).unwrap_or_default();
+                let (title, content) = match event_type.as_str() {
+                    "moved" => {
+                        let from = data["from_column"].as_str().unwrap_or("?");
+                        let to = data["to_column"].as_str().unwrap_or("?");
+                        (format!("Moved: {} ({} → {})", task, from, to), format!("{} moved {} from {} to {}.", actor, task, from, to))
+                    }
+                    "comment" => (
+                        format!("Comment on {}", task),
+                        data["message"].as_str().unwrap_or_default().to_string(),
+                    ),
+                    _ => (format!("Created: {}", task), format!("{} created {}.", actor, task)),
+                };
+                Ok((event_id, task_id, title, content, actor, rfc3339(&row.get::<_, String>(7)?)))
+            })
+            .map_err(|e| db_error(&e.to_string()))?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| db_error(&e.to_string()))?;
+
+        let updated = entries
+            .first()
+            .map(|entry| entry.5.clone())
+            .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+        xml.push_str(&format!("  <id>{}</id>\n", xml_escape(&feed_url)));
+        xml.push_str(&format!("  <title>{} — activity</title>\n", xml_escape(&name)));
+        if !description.is_empty() {
+            xml.push_str(&format!("  <subtitle>{}</subtitle>\n", xml_escape(&description)));
+        }
+        xml.push_str(&format!("  <updated>{}</updated>\n", updated));
+        xml.push_str(&format!("  <link rel=\"self\" href=\"{}\"/>\n", xml_escape(&feed_url)));
+        xml.push_str(&format!("  <link rel=\"alternate\" href=\"{}\"/>\n", xml_escape(&home_page_url)));
+        for (event_id, task_id, title, content, actor, at) in entries {
+            xml.push_str("  <entry>\n");
+            xml.push_str(&format!("    <id>urn:uuid:{}</id>\n", xml_escape(&event_id)));
+            xml.push_str(&format!("    <title>{}</title>\n", xml_escape(&title)));
+            xml.push_str(&format!("    <updated>{}</updated>\n", at));
+            xml.push_str(&format!("    <author><name>{}</name></author>\n", xml_escape(&actor)));
+            xml.push_str(&format!(
+                "    <link rel=\"alternate\" href=\"{}\"/>\n",
+                xml_escape(&format!("{}?task={}", home_page_url, task_id))
+            ));
+            xml.push_str(&format!("    <content type=\"text\">{}</content>\n", xml_escape(&content)));
+            xml.push_str("  </entry>\n");
+        }
+        xml.push_str("</feed>\n");
+
+        Ok((ContentType::new("application", "atom+xml"), xml))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc3339() {
+        assert_eq!(rfc3339("2026-02-12 09:41:50"), "2026-02-12T09:41:50+00:00");
+        assert_eq!(rfc3339("2026-02-12T09:41:50+02:00"), "2026-02-12T09:41:50+02:00");
+        assert_eq!(rfc3339("garbage"), "garbage");
+        assert_eq!(xml_escape(r#"<a href="x">Tom & 'Jerry'</a>"#), "&lt;a href=&quot;x&quot;&gt;Tom &amp; &apos;Jerry&apos;&lt;/a&gt;");
+    }
+}
@@ -0,0 +1,421 @@
+//! GitHub Issues import: turn a repository's issues into tasks on an existing board.
+
+use std::collections::{HashMap, HashSet};
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::Connection;
+use serde::Deserialize;
+
+use crate::access::{self, BoardRole};
+use crate::auth::{Actor, BoardToken, WriteQuota};
+use crate::db::{hash_key, DbPool};
+use crate::event_types;
+use crate::events::EventBus;
+use crate::import::db_time;
+use crate::models::*;
+use crate::routes::{db_error, log_event, normalize_labels, with_db};
+use crate::url_policy::OutboundClient;
+use crate::validation_hook::{self, Gate};
+
+const DEFAULT_API_URL: &str = "https://api.github.com";
+const PER_PAGE: usize = 100;
+/// Fetches stop after this many pages (1000 issues).
+const MAX_PAGES: usize = 10;
+
+#[derive(Debug, Clone, Deserialize)]
+struct GithubIssue {
+    number: i64,
+    title: String,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    html_url: String,
+    #[serde(default)]
+    labels: Vec<GithubLabel>,
+    #[serde(default)]
+    assignees: Vec<GithubUser>,
+    #[serde(default)]
+    assignee: Option<GithubUser>,
+    #[serde(default)]
+    user: Option<GithubUser>,
+    #[serde(default)]
+    milestone: Option<GithubMilestone>,
+    #[serde(default)]
+    created_at: Option<String>,
+    #[serde(default)]
+    closed_at: Option<String>,
+    #[serde(default)]
+    pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct GithubLabel {
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct GithubUser {
+    login: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct GithubMilestone {
+    title: String,
+}
+
+/// Where each issue lands, resolved against the board before anything is fetched.
+#[derive(Clone)]
+struct ColumnPlan {
+    milestones: HashMap<String, String>,
+    default: String,
+    closed: String,
+}
+
+impl ColumnPlan {
+    fn column_for(&self, issue: &GithubIssue) -> &str {
+        let mapped = issue.milestone.as_ref().and_then(|m| self.milestones.get(&m.title));
+        match mapped {
+            Some(column_id) => column_id,
+            None if issue.state == "closed" => &self.closed,
+            None => &self.default,
+        }
+    }
+}
+
+/// Import GitHub issues as tasks — requires manage key.
+/// Pass `issues` (GitHub API JSON) or `repo` (+ optional `token`) to fetch them.
+/// Pull requests are skipped, as are issues a previous import already brought in
+/// and issues the board's validation hook refuses.
+/// Each issue counts as a task write.
+#[allow(clippy::too_many_arguments)]
+#[post("/boards/<board_id>/import/github", format = "json", data = "<req>")]
+pub async fn import_github_issues(
+    board_id: &str,
+    req: Json<GithubImportRequest>,
+    token: BoardToken,
+    quota: WriteQuota<'_>,
+    actor: Actor,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<GithubImportReport>, (Status, Json<ApiError>)> {
+    let req = req.into_inner();
+    let (board, importer) = (board_id.to_owned(), actor.clone());
+    let (plan, req) = with_db(db, move |conn| {
+        let board_id = board.as_str();
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+        access::require_display_name(conn, board_id, &importer)?;
+        Ok((plan_columns(conn, board_id, &req)?, req))
+    })
+    .await?;
+
+    let mut report = GithubImportReport::default();
+    let raw = match (req.issues, req.repo.as_deref()) {
+        (Some(issues), None) => issues,
+        (None, Some(repo)) => {
+            let state = req.state.as_deref().unwrap_or("open");
+            if !["open", "closed", "all"].contains(&state) {
+                return Err(validation_error("state must be 'open', 'closed' or 'all'"));
+            }
+            fetch_issues(bus.outbound(), repo, req.token.as_deref(), state, &mut report).await?
+        }
+        _ => return Err(validation_error("Provide either 'issues' or 'repo'")),
+    };
+
+    let mut issues = Vec::new();
+    for (i, value) in raw.into_iter().enumerate() {
+        match serde_json::from_value::<GithubIssue>(value) {
+            Ok(issue) if issue.pull_request.is_some() => report.pull_requests_skipped += 1,
+            Ok(issue) => issues.push(issue),
+            Err(e) => report.warnings.push(format!("Item {} is not a GitHub issue ({}); skipped", i, e)),
+        }
+    }
+    issues.sort_by_key(|issue| issue.number);
+    // The request itself was counted as one write
+    quota.charge(issues.len().saturating_sub(1) as u64).await?;
+
+    let (board, bus) = (board_id.to_owned(), bus.inner().clone());
+    let client = bus.outbound().clone();
+    let report = validation_hook::run(db, &client, move |conn, gate| {
+        let mut report = report;
+        let created = write_issues(conn, gate, &board, &plan, &issues, actor.name(), &mut report)?;
+        for data in created {
+            bus.emit(
+                conn,
+                crate::events::BoardEvent {
+                    event: event_types::TASK_CREATED.to_string(),
+                    board_id: board.clone(),
+                    data,
+                },
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        }
+        Ok::<_, (Status, Json<ApiError>)>(report)
+    })
+    .await?;
+    Ok(Json(report))
+}
+
+fn plan_columns(
+    conn: &Connection,
+    board_id: &str,
+    req: &GithubImportRequest,
+) -> Result<ColumnPlan, (Status, Json<ApiError>)> {
+    let mut stmt = conn
+        .prepare("SELECT id, name FROM columns WHERE board_id = ?1 ORDER BY position ASC")
+        .map_err(|e| db_error(&e.to_string()))?;
+    let columns: Vec<(String, String)> = stmt
+        .query_map(rusqlite::params![board_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| db_error(&e.to_string()))?
+        .filter_map(|r| r.ok())
+        .collect();
+    let (Some(first), Some(last)) = (columns.first(), columns.last()) else {
+        return Err((
+            Status::BadRequest,
+            Json(ApiError {
+                error: "Board has no columns".to_string(),
+                code: "NO_COLUMNS".to_string(),
+                status: 400,
+            }),
+        ));
+    };
+
+    // Column references may be ids or (case-insensitive) names
+    let resolve = |reference: &str| -> Result<String, (Status, Json<ApiError>)> {
+        columns
+            .iter()
+            .find(|(id, name)| id == reference || name.eq_ignore_ascii_case(reference.trim()))
+            .map(|(id, _)| id.clone())
+            .ok_or_else(|| {
+                (
+                    Status::BadRequest,
+                    Json(ApiError {
+                        error: format!("Column '{}' not found in this board", reference),
+                        code: "INVALID_COLUMN".to_string(),
+                        status: 400,
+                    }),
+                )
+            })
+    };
+
+    let mut milestones = HashMap::new();
+    for (milestone, column) in &req.milestone_columns {
+        milestones.insert(milestone.clone(), resolve(column)?);
+    }
+    Ok(ColumnPlan {
+        milestones,
+        default: match &req.default_column {
+            Some(c) => resolve(c)?,
+            None => first.0.clone(),
+        },
+        closed: match &req.closed_column {
+            Some(c) => resolve(c)?,
+            None => last.0.clone(),
+        },
+    })
+}
+
+/// Page through `GET /repos/<repo>/issues`. The API base can be overridden with
+/// `GITHUB_API_URL` (GitHub Enterprise).
+async fn fetch_issues(
+    client: &OutboundClient,
+    repo: &str,
+    token: Option<&str>,
+    state: &str,
+    report: &mut GithubImportReport,
+) -> Result<Vec<serde_json::Value>, (Status, Json<ApiError>)> {
+    let valid_repo = repo
+        .split_once('/')
+        .is_some_and(|(owner, name)| !owner.is_empty() && !name.is_empty() && !name.contains('/'));
+    if !valid_repo {
+        return Err(validation_error("repo must look like 'owner/name'"));
+    }
+    let base = std::env::var("GITHUB_API_URL").unwrap_or_else(|_| DEFAULT_API_URL.to_string());
+
+    let mut issues = Vec::new();
+    for page in 1..=MAX_PAGES {
+        let mut request = client
+            .get(&format!("{}/repos/{}/issues", base.trim_end_matches('/'), repo))
+            .map_err(|e| fetch_failed(format!("GitHub request refused: {}", e)))?
+            .query(&[("state", state), ("per_page", &PER_PAGE.to_string()), ("page", &page.to_string())])
+            .header("User-Agent", concat!("kanban/", env!("CARGO_PKG_VERSION")))
+            .header("Accept", "application/vnd.github+json")
+            .timeout(std::time::Duration::from_secs(30));
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        let resp = request.send().await.map_err(|e| fetch_failed(format!("GitHub request failed: {}", e)))?;
+        if !resp.status().is_success() {
+            return Err(fetch_failed(format!("GitHub returned HTTP {} for {}", resp.status().as_u16(), repo)));
+        }
+        let batch: Vec<serde_json::Value> = resp
+            .json()
+            .await
+            .map_err(|e| fetch_failed(format!("Unreadable GitHub response: {}", e)))?;
+        let done = batch.len() < PER_PAGE;
+        issues.extend(batch);
+        if done {
+            return Ok(issues);
+        }
+    }
+    report.warnings.push(format!(
+        "Stopped after {} pages ({} items); import the rest with a narrower 'state' or as an 'issues' payload",
+        MAX_PAGES,
+        issues.len()
+    ));
+    Ok(issues)
+}
+
+/// Insert one task per issue; returns the `task.created` payloads to emit after commit.
+#[allow(clippy::too_many_arguments)]
+fn write_issues(
+    conn: &Connection,
+    gate: &Gate,
+    board_id: &str,
+    plan: &ColumnPlan,
+    issues: &[GithubIssue],
+    actor: &str,
+    report: &mut GithubImportReport,
+) -> Result<Vec<serde_json::Value>, (Status, Json<ApiError>)> {
+    let db_err = |e: rusqlite::Error| db_error(&e.to_string());
+    let mut stmt = conn
+        .prepare(
+            "SELECT json_extract(metadata, '$.github.url') FROM tasks
+             WHERE board_id = ?1 AND json_extract(metadata, '$.github.url') IS NOT NULL",
+        )
+        .map_err(db_err)?;
+    let existing: HashSet<String> = stmt
+        .query_map(rusqlite::params![board_id], |row| row.get(0))
+        .map_err(db_err)?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut created = Vec::new();
+    for issue in issues {
+        if !issue.html_url.is_empty() && existing.contains(&issue.html_url) {
+            report.already_imported += 1;
+            continue;
+        }
+        let column_id = plan.column_for(issue);
+
+        let mut assignees: Vec<&str> = issue.assignees.iter().map(|u| u.login.as_str()).collect();
+        if assignees.is_empty() {
+            assignees.extend(issue.assignee.as_ref().map(|u| u.login.as_str()));
+        }
+        if assignees.len() > 1 {
+            report.warnings.push(format!(
+                "Issue #{} had {} assignees; assigned to {} only",
+                issue.number,
+                assignees.len(),
+                assignees[0]
+            ));
+        }
+        let labels: Vec<String> = issue.labels.iter().map(|l| l.name.clone()).collect();
+        let metadata = serde_json::json!({"github": {
+            "number": issue.number,
+            "url": issue.html_url,
+            "state": issue.state,
+            "milestone": issue.milestone.as_ref().map(|m| &m.title),
+        }});
+        let created_by = issue.user.as_ref().map(|u| u.login.as_str()).unwrap_or(actor);
+        let created_at = issue.created_at.as_deref().and_then(db_time);
+        let completed_at = if issue.state == "closed" {
+            issue.closed_at.as_deref().and_then(db_time)
+        } else {
+            None
+        };
+
+        let verdict = validation_hook::check(
+            conn,
+            gate,
+            board_id,
+            validation_hook::TASK_CREATE,
+            actor,
+            serde_json::json!({"task": {
+                "title": issue.title.trim(),
+                "description": issue.body.as_deref().unwrap_or(""),
+                "column_id": column_id,
+                "assigned_to": assignees.first(),
+                "labels": normalize_labels(&labels),
+                "metadata": metadata,
+            }}),
+        );
+        match verdict {
+            Err((status, err)) if status == Status::InternalServerError => return Err((status, err)),
+            Err((_, Json(err))) => {
+                report.warnings.push(format!("Issue #{} skipped: {}", issue.number, err.error));
+                continue;
+            }
+            Ok(()) => {}
+        }
+        let position: i32 = conn
+            .query_row(
+                "SELECT COALESCE(MAX(position), -1) + 1 FROM tasks WHERE column_id = ?1",
+                rusqlite::params![column_id],
+                |row| row.get(0),
+            )
+            .map_err(db_err)?;
+
+        let task_id = uuid::Uuid::new_v4().to_string();
+        conn.execute(
+            "INSERT INTO tasks (id, board_id, column_id, title, description, position, created_by,
+                                assigned_to, labels, metadata, completed_at, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, COALESCE(?12, datetime('now')))",
+            rusqlite::params![
+                task_id,
+                board_id,
+                column_id,
+                issue.title.trim(),
+                issue.body.as_deref().unwrap_or(""),
+                position,
+                created_by,
+                assignees.first(),
+                serde_json::to_string(&normalize_labels(&labels)).unwrap_or_else(|_| "[]".to_string()),
+                serde_json::to_string(&metadata).unwrap_or_else(|_| "{}".to_string()),
+                completed_at,
+                created_at,
+            ],
+        )
+        .map_err(db_err)?;
+
+        let event_data = serde_json::json!({
+            "title": issue.title.trim(),
+            "task_id": task_id,
+            "column_id": column_id,
+            "creator": actor,
+            "source": "github",
+            "issue": issue.number,
+        });
+        log_event(conn, &task_id, "created", actor, &event_data);
+        created.push(event_data);
+        report.imported += 1;
+        report.task_ids.push(task_id);
+    }
+    Ok(created)
+}
+
+fn validation_error(msg: &str) -> (Status, Json<ApiError>) {
+    (
+        Status::BadRequest,
+        Json(ApiError {
+            error: msg.to_string(),
+            code: "INVALID_INPUT".to_string(),
+            status: 400,
+        }),
+    )
+}
+
+fn fetch_failed(error: String) -> (Status, Json<ApiError>) {
+    (
+        Status::BadGateway,
+        Json(ApiError {
+            error,
+            code: "GITHUB_FETCH_FAILED".to_string(),
+            status: 502,
+        }),
+    )
+}
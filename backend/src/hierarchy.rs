@@ -0,0 +1,220 @@
+//! Parent/child tasks (epics). A task may have one parent on the same board;
+//! parents report roll-up progress over their children. Deleting a parent
+//! detaches its children.
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::Connection;
+
+use crate::access::{self, BoardRole};
+use crate::auth::{Actor, BoardToken};
+use crate::db::{hash_key, DbPool};
+use crate::event_types;
+use crate::events::{BoardEvent, EventBus};
+use crate::models::*;
+use crate::routes::{db_error, load_task_response, log_event, not_found, with_db, with_tx};
+
+fn require_task(conn: &Connection, board_id: &str, task_id: &str, what: &str) -> Result<(), (Status, Json<ApiError>)> {
+    let exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM tasks WHERE id = ?1 AND board_id = ?2",
+            rusqlite::params![task_id, board_id],
+            |row| row.get(0),
+        )
+        .unwrap_or(false);
+    if exists {
+        Ok(())
+    } else {
+        Err(not_found(what))
+    }
+}
+
+/// Whether `ancestor` is `task` or sits above it in the parent chain.
+fn is_ancestor(conn: &Connection, ancestor: &str, task: &str) -> bool {
+    let mut visited = std::collections::HashSet::new();
+    let mut current = Some(task.to_string());
+    while let Some(id) = current {
+        if id == ancestor {
+            return true;
+        }
+        if !visited.insert(id.clone()) {
+            return false;
+        }
+        current = conn
+            .query_row("SELECT parent_task_id FROM tasks WHERE id = ?1", rusqlite::params![id], |row| row.get(0))
+            .unwrap_or(None);
+    }
+    false
+}
+
+/// Check that `parent_id` can take `child_id` (None for a task about to be
+/// created): same board, and no cycle.
+pub(crate) fn check_parent(
+    conn: &Connection,
+    board_id: &str,
+    parent_id: &str,
+    child_id: Option<&str>,
+) -> Result<(), (Status, Json<ApiError>)> {
+    require_task(conn, board_id, parent_id, "Parent task")?;
+    if child_id.is_some_and(|child| is_ancestor(conn, child, parent_id)) {
+        return Err((
+            Status::Conflict,
+            Json(ApiError {
+                error: "Circular hierarchy: a task cannot be the parent of itself or of its own ancestor".to_string(),
+                code: "CIRCULAR_HIERARCHY".to_string(),
+                status: 409,
+            }),
+        ));
+    }
+    Ok(())
+}
+
+/// Children done / total for a task, or None when it has no children.
+pub(crate) fn progress(total: i64, done: i64) -> Option<TaskProgress> {
+    (total > 0).then(|| TaskProgress {
+        done,
+        total,
+        percent: (done * 100 / total) as u8,
+    })
+}
+
+/// List a task's children with its roll-up progress — public.
+#[get("/boards/<board_id>/tasks/<task_id>/children")]
+pub async fn list_children(
+    board_id: &str,
+    task_id: &str,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+) -> Result<Json<TaskChildrenResponse>, (Status, Json<ApiError>)> {
+    let (board_id, task_id) = (board_id.to_owned(), task_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, task_id) = (board_id.as_str(), task_id.as_str());
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        require_task(conn, board_id, task_id, "Task")?;
+        let child_ids: Vec<String> = conn
+            .prepare(
+                "SELECT t.id FROM tasks t JOIN columns c ON c.id = t.column_id
+                 WHERE t.parent_task_id = ?1 ORDER BY c.position ASC, t.position ASC",
+            )
+            .map_err(|e| db_error(&e.to_string()))?
+            .query_map(rusqlite::params![task_id], |row| row.get(0))
+            .map_err(|e| db_error(&e.to_string()))?
+            .collect::<rusqlite::Result<_>>()
+            .map_err(|e| db_error(&e.to_string()))?;
+        let children = child_ids
+            .iter()
+            .map(|id| load_task_response(conn, id).map(|t| t.into_inner()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let done = children.iter().filter(|t| t.completed_at.is_some()).count() as i64;
+        Ok(Json(TaskChildrenResponse {
+            task_id: task_id.to_string(),
+            progress: progress(children.len() as i64, done).unwrap_or(TaskProgress {
+                done: 0,
+                total: 0,
+                percent: 0,
+            }),
+            children,
+        }))
+    })
+    .await
+}
+
+/// Attach a task as a child — requires manage key. A child that already has
+/// a parent moves to this one. Returns the parent.
+#[post("/boards/<board_id>/tasks/<task_id>/children", format = "json", data = "<req>")]
+pub async fn attach_child(
+    board_id: &str,
+    task_id: &str,
+    req: Json<AttachChildRequest>,
+    token: BoardToken,
+    actor: Actor,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<TaskResponse>, (Status, Json<ApiError>)> {
+    let (board_id, bus, task_id) = (board_id.to_owned(), bus.inner().clone(), task_id.to_owned());
+    with_tx(db, move |conn| {
+        let (board_id, bus, task_id) = (board_id.as_str(), &bus, task_id.as_str());
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+        access::require_display_name(conn, board_id, &actor)?;
+        require_task(conn, board_id, &req.child_task_id, "Child task")?;
+        check_parent(conn, board_id, task_id, Some(&req.child_task_id))?;
+
+        let previous: Option<String> = conn
+            .query_row(
+                "SELECT parent_task_id FROM tasks WHERE id = ?1",
+                rusqlite::params![req.child_task_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        if previous.as_deref() != Some(task_id) {
+            conn.execute(
+                "UPDATE tasks SET parent_task_id = ?1, updated_at = datetime('now') WHERE id = ?2",
+                rusqlite::params![task_id, req.child_task_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+            let event_data = serde_json::json!({
+                "parent_task_id": task_id,
+                "child_task_id": req.child_task_id,
+                "previous_parent_task_id": previous,
+            });
+            log_event(conn, task_id, "child.added", actor.name(), &event_data);
+            bus.emit(
+                conn,
+                BoardEvent {
+                    event: event_types::TASK_CHILD_ADDED.to_string(),
+                    board_id: board_id.to_string(),
+                    data: event_data,
+                },
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        }
+        load_task_response(conn, task_id)
+    })
+    .await
+}
+
+/// Detach a child — requires manage key. Returns the parent.
+#[delete("/boards/<board_id>/tasks/<task_id>/children/<child_id>")]
+pub async fn detach_child(
+    board_id: &str,
+    task_id: &str,
+    child_id: &str,
+    token: BoardToken,
+    actor: Actor,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<TaskResponse>, (Status, Json<ApiError>)> {
+    let (board_id, bus, child_id, task_id) = (board_id.to_owned(), bus.inner().clone(), child_id.to_owned(), task_id.to_owned());
+    with_tx(db, move |conn| {
+        let (board_id, bus, child_id, task_id) = (board_id.as_str(), &bus, child_id.as_str(), task_id.as_str());
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+        access::require_display_name(conn, board_id, &actor)?;
+
+        let affected = conn
+            .execute(
+                "UPDATE tasks SET parent_task_id = NULL, updated_at = datetime('now')
+                 WHERE id = ?1 AND parent_task_id = ?2 AND board_id = ?3",
+                rusqlite::params![child_id, task_id, board_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        if affected == 0 {
+            return Err(not_found("Child task"));
+        }
+        let event_data = serde_json::json!({"parent_task_id": task_id, "child_task_id": child_id});
+        log_event(conn, task_id, "child.removed", actor.name(), &event_data);
+        bus.emit(
+            conn,
+            BoardEvent {
+                event: event_types::TASK_CHILD_REMOVED.to_string(),
+                board_id: board_id.to_string(),
+                data: event_data,
+            },
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        load_task_response(conn, task_id)
+    })
+    .await
+}
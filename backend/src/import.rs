@@ -0,0 +1,866 @@
+//! Imports: recreate a board from an export document under fresh ids (in one
+//! call, or validated first and committed by payload hash), and bulk-create
+//! tasks from CSV.
+
+use std::collections::{HashMap, HashSet};
+
+use rocket::data::{Data, Limits, ToByteUnit};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::{Connection, OptionalExtension, TransactionBehavior};
+use sha2::{Digest, Sha256};
+
+use crate::access::{self, BoardRole, Visibility};
+use crate::auth::{Actor, BoardToken, WriteQuota};
+use crate::auto_assign;
+use crate::db::{hash_key, DbPool};
+use crate::event_types;
+use crate::events::EventBus;
+use crate::export::{EXPORT_FORMAT, EXPORT_VERSION};
+use crate::lifecycle::{self, LifecycleHooks};
+use crate::models::*;
+use crate::rate_limit::IpRateLimit;
+use crate::routes::{
+    check_board_rate_limit, check_wip_limit, db_error, log_event, normalize_labels, not_found, with_db,
+    NEXT_EVENT_SEQ,
+};
+use crate::trello::{self, TrelloBoard};
+use crate::validation_hook::{self, Gate};
+
+/// Import a board from `GET /boards/<id>/export` output — no auth required.
+/// Every row gets a fresh UUID and the board a new manage key; column order,
+/// task positions, dependencies and event history are preserved. Counts against
+/// the board-creation rate limit.
+#[post("/boards/import", format = "json", data = "<doc>")]
+pub async fn import_board(
+    doc: Json<BoardExport>,
+    actor: Actor,
+    rate_limit: IpRateLimit<'_>,
+    hooks: &State<LifecycleHooks>,
+    db: &State<DbPool>,
+) -> Result<Json<ImportBoardResponse>, (Status, Json<ApiError>)> {
+    let doc = doc.into_inner();
+    validate_document(&doc)?;
+    check_board_rate_limit(&rate_limit).await?;
+    store_document(db, hooks, doc, &actor).await.map(Json)
+}
+
+// ============ Two-phase import ============
+
+/// `?source=` values for `POST /boards/import/validate`.
+const SOURCE_KANBAN: &str = "kanban";
+const SOURCE_TRELLO: &str = "trello";
+/// How long a validated import can be committed.
+const VALIDATION_TTL_MINUTES: i64 = 60;
+
+/// Check an import without writing it — no auth required. `?source=` is
+/// `kanban` (an export document, the default) or `trello`. Every problem is
+/// reported, not just the first, and a dry run in a rolled-back transaction
+/// fills in the counts and warnings the import would produce. A valid
+/// document is held for an hour under `payload_hash` for
+/// `POST /boards/import/commit`. Body size is capped by the `json` data limit.
+#[post("/boards/import/validate?<source>", format = "json", data = "<body>")]
+pub async fn validate_import(
+    source: Option<&str>,
+    body: Data<'_>,
+    limits: &Limits,
+    db: &State<DbPool>,
+) -> Result<Json<ImportValidationResponse>, (Status, Json<ApiError>)> {
+    let source = source.unwrap_or(SOURCE_KANBAN);
+    if source != SOURCE_KANBAN && source != SOURCE_TRELLO {
+        return Err((
+            Status::BadRequest,
+            Json(ApiError {
+                error: format!("Unknown import source '{}' (expected 'kanban' or 'trello')", source),
+                code: "INVALID_INPUT".to_string(),
+                status: 400,
+            }),
+        ));
+    }
+    let limit = limits.get("json").unwrap_or(1.mebibytes());
+    let body = body
+        .open(limit)
+        .into_string()
+        .await
+        .map_err(|e| invalid_import(format!("Could not read body: {}", e)))?;
+    if !body.is_complete() {
+        return Err((
+            Status::PayloadTooLarge,
+            Json(ApiError {
+                error: format!("Import is larger than the {} limit", limit),
+                code: "PAYLOAD_TOO_LARGE".to_string(),
+                status: 413,
+            }),
+        ));
+    }
+    let payload_hash = hex::encode(Sha256::digest(body.as_bytes()));
+
+    let mut response = ImportValidationResponse {
+        valid: false,
+        source: source.to_string(),
+        payload_hash,
+        expires_at: None,
+        issues: Vec::new(),
+        report: ImportReport::default(),
+    };
+    let (doc, warnings) = match parse_import(source, &body) {
+        Ok(parsed) => parsed,
+        Err(issue) => {
+            response.issues.push(issue);
+            return Ok(Json(response));
+        }
+    };
+    response.issues = document_issues(&doc);
+    if !response.issues.is_empty() {
+        response.report.warnings = warnings;
+        return Ok(Json(response));
+    }
+
+    let source = source.to_owned();
+    let response = with_db(db, move |conn| {
+        conn.execute("DELETE FROM import_validations WHERE expires_at <= datetime('now')", [])
+            .map_err(|e| db_error(&e.to_string()))?;
+
+        let tx = rusqlite::Transaction::new_unchecked(conn, TransactionBehavior::Deferred)
+            .map_err(|e| db_error(&e.to_string()))?;
+        let dry_run = write_board(&tx, &doc);
+        tx.rollback().map_err(|e| db_error(&e.to_string()))?;
+        match dry_run {
+            Ok(dry_run) => {
+                response.report = dry_run.report;
+                response.report.warnings.splice(0..0, warnings.iter().cloned());
+            }
+            Err(e) => {
+                response.issues.push(ImportIssue {
+                    code: "WRITE_FAILED".to_string(),
+                    path: None,
+                    message: format!("The document could not be written: {}", e),
+                });
+                response.report.warnings = warnings;
+                return Ok(response);
+            }
+        }
+
+        let expires_at: String = conn
+            .query_row(
+                "INSERT OR REPLACE INTO import_validations (payload_hash, source, document, warnings, expires_at)
+                 VALUES (?1, ?2, ?3, ?4, datetime('now', ?5))
+                 RETURNING expires_at",
+                rusqlite::params![
+                    response.payload_hash,
+                    source,
+                    serde_json::to_string(&doc).unwrap_or_default(),
+                    serde_json::to_string(&warnings).unwrap_or_else(|_| "[]".to_string()),
+                    format!("+{} minutes", VALIDATION_TTL_MINUTES),
+                ],
+                |row| row.get(0),
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        response.valid = true;
+        response.expires_at = Some(expires_at);
+        Ok(response)
+    })
+    .await?;
+    Ok(Json(response))
+}
+
+/// Read the body as `source`: the export document plus any conversion warnings.
+fn parse_import(source: &str, body: &str) -> Result<(BoardExport, Vec<String>), ImportIssue> {
+    let unreadable = |e: serde_json::Error| ImportIssue {
+        code: "INVALID_IMPORT".to_string(),
+        path: None,
+        message: format!("Could not parse the document: {}", e),
+    };
+    if source == SOURCE_TRELLO {
+        let board: TrelloBoard = serde_json::from_str(body).map_err(unreadable)?;
+        trello::convert(&board).map_err(|(_, Json(err))| ImportIssue {
+            code: err.code,
+            path: None,
+            message: err.error,
+        })
+    } else {
+        serde_json::from_str(body).map(|doc| (doc, Vec::new())).map_err(unreadable)
+    }
+}
+
+/// Write an import checked by `POST /boards/import/validate` — no auth
+/// required. Each validation commits once; unknown, expired or already
+/// committed hashes are a 404. Counts against the board-creation rate limit.
+#[post("/boards/import/commit", format = "json", data = "<req>")]
+pub async fn commit_import(
+    req: Json<CommitImportRequest>,
+    actor: Actor,
+    rate_limit: IpRateLimit<'_>,
+    hooks: &State<LifecycleHooks>,
+    db: &State<DbPool>,
+) -> Result<Json<ImportBoardResponse>, (Status, Json<ApiError>)> {
+    let payload_hash = req.payload_hash.trim().to_ascii_lowercase();
+    let hash = payload_hash.clone();
+    let load = move |conn: &Connection| {
+        conn.query_row(
+            "SELECT document, warnings FROM import_validations
+             WHERE payload_hash = ?1 AND expires_at > datetime('now')",
+            rusqlite::params![hash],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+        )
+        .optional()
+        .map_err(|e| db_error(&e.to_string()))?
+        .ok_or_else(|| not_found("Validated import"))
+    };
+    with_db(db, load.clone()).await?;
+    check_board_rate_limit(&rate_limit).await?;
+
+    // Claim the validation so a repeated commit can't create a second board
+    let (document, warnings) = with_db(db, move |conn| {
+        let claimed = load(conn)?;
+        conn.execute("DELETE FROM import_validations WHERE payload_hash = ?1", rusqlite::params![payload_hash])
+            .map_err(|e| db_error(&e.to_string()))?;
+        Ok(claimed)
+    })
+    .await?;
+    let doc: BoardExport = serde_json::from_str(&document).map_err(|e| db_error(&e.to_string()))?;
+    let warnings: Vec<String> = serde_json::from_str(&warnings).unwrap_or_default();
+
+    let mut response = store_document(db, hooks, doc, &actor).await?;
+    response.report.warnings.splice(0..0, warnings);
+    Ok(Json(response))
+}
+
+/// Write a validated document as a new board, in one transaction, owned by
+/// `actor`. Fires the `board.created` lifecycle hook once committed.
+pub(crate) async fn store_document(
+    db: &DbPool,
+    hooks: &LifecycleHooks,
+    doc: BoardExport,
+    actor: &Actor,
+) -> Result<ImportBoardResponse, (Status, Json<ApiError>)> {
+    let owner = (!actor.is_anonymous()).then(|| actor.name().to_owned());
+    let created_by = owner.clone();
+    let response = with_db(db, move |conn| {
+        let tx = rusqlite::Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+            .map_err(|e| db_error(&e.to_string()))?;
+        let response = write_board(&tx, &doc).map_err(|e| db_error(&e.to_string()))?;
+        tx.execute(
+            "UPDATE boards SET created_by = ?1 WHERE id = ?2",
+            rusqlite::params![created_by, response.board.id],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        tx.commit().map_err(|e| db_error(&e.to_string()))?;
+        Ok(response)
+    })
+    .await?;
+
+    hooks.notify(db, lifecycle::BOARD_CREATED, &response.board.id, owner.as_deref()).await;
+    Ok(response)
+}
+
+/// Structural checks that must pass before anything is written; the first
+/// problem `document_issues` finds is the error.
+pub(crate) fn validate_document(doc: &BoardExport) -> Result<(), (Status, Json<ApiError>)> {
+    match document_issues(doc).into_iter().next() {
+        Some(issue) => Err((
+            Status::BadRequest,
+            Json(ApiError {
+                error: issue.message,
+                code: issue.code,
+                status: 400,
+            }),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Every structural problem in a document, in document order.
+fn document_issues(doc: &BoardExport) -> Vec<ImportIssue> {
+    let mut issues = Vec::new();
+    let mut issue = |code: &str, path: String, message: String| {
+        issues.push(ImportIssue {
+            code: code.to_string(),
+            path: Some(path),
+            message,
+        })
+    };
+    if doc.format != EXPORT_FORMAT {
+        issue(
+            "INVALID_IMPORT",
+            "format".to_string(),
+            format!("Not a board export (format '{}', expected '{}')", doc.format, EXPORT_FORMAT),
+        );
+    }
+    if doc.version == 0 || doc.version > EXPORT_VERSION {
+        issue(
+            "UNSUPPORTED_EXPORT_VERSION",
+            "version".to_string(),
+            format!(
+                "Export version {} is not supported (this server reads up to version {})",
+                doc.version, EXPORT_VERSION
+            ),
+        );
+    }
+    if doc.board.name.trim().is_empty() {
+        issue("EMPTY_NAME", "board.name".to_string(), "Board name cannot be empty".to_string());
+    }
+    if doc.columns.is_empty() {
+        issue("INVALID_IMPORT", "columns".to_string(), "Export has no columns".to_string());
+    }
+
+    let mut column_ids = HashSet::new();
+    for (i, col) in doc.columns.iter().enumerate() {
+        if !column_ids.insert(col.id.as_str()) {
+            issue("INVALID_IMPORT", format!("columns[{}].id", i), format!("Duplicate column id '{}'", col.id));
+        }
+    }
+    let mut task_ids = HashSet::new();
+    for (i, task) in doc.tasks.iter().enumerate() {
+        if !task_ids.insert(task.id.as_str()) {
+            issue("INVALID_IMPORT", format!("tasks[{}].id", i), format!("Duplicate task id '{}'", task.id));
+        }
+        if !column_ids.contains(task.column_id.as_str()) {
+            issue(
+                "INVALID_IMPORT",
+                format!("tasks[{}].column_id", i),
+                format!("Task '{}' references unknown column '{}'", task.id, task.column_id),
+            );
+        }
+    }
+    issues
+}
+
+/// No validation hook is asked: the board is new, so it can't have one yet.
+fn write_board(conn: &Connection, doc: &BoardExport) -> rusqlite::Result<ImportBoardResponse> {
+    let mut report = ImportReport {
+        webhooks_skipped: doc.webhooks.len(),
+        ..Default::default()
+    };
+    let board_id = uuid::Uuid::new_v4().to_string();
+    let manage_key = format!("kb_{}", uuid::Uuid::new_v4().to_string().replace('-', ""));
+
+    // Old id → new id for everything other rows (and event data) can reference
+    let mut ids: HashMap<String, String> = HashMap::new();
+    ids.insert(doc.board.id.clone(), board_id.clone());
+    for col in &doc.columns {
+        ids.insert(col.id.clone(), uuid::Uuid::new_v4().to_string());
+    }
+    for task in &doc.tasks {
+        ids.insert(task.id.clone(), uuid::Uuid::new_v4().to_string());
+    }
+    let column_ref = |old: &Option<String>, setting: &str, report: &mut ImportReport| match old {
+        Some(id) => match ids.get(id) {
+            Some(new_id) => Some(new_id.clone()),
+            None => {
+                report.warnings.push(format!("{} pointed at an unknown column; cleared", setting));
+                None
+            }
+        },
+        None => None,
+    };
+    let quick_done = column_ref(&doc.board.quick_done_column_id, "quick_done_column_id", &mut report);
+    let quick_reassign = column_ref(&doc.board.quick_reassign_column_id, "quick_reassign_column_id", &mut report);
+
+    let b = &doc.board;
+    let visibility = b.visibility.unwrap_or(if b.is_public { Visibility::Public } else { Visibility::Unlisted });
+    conn.execute(
+        "INSERT INTO boards (id, name, description, manage_key_hash, is_public, archived,
+                             require_display_name, enforce_dependencies,
+                             quick_done_column_id, quick_done_auto_archive,
+                             quick_reassign_column_id, quick_reassign_to, created_at, updated_at, visibility)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+        rusqlite::params![
+            board_id,
+            b.name.trim(),
+            b.description,
+            hash_key(&manage_key),
+            (visibility == Visibility::Public) as i32,
+            b.archived as i32,
+            b.require_display_name as i32,
+            b.enforce_dependencies as i32,
+            quick_done,
+            b.quick_done_auto_archive as i32,
+            quick_reassign,
+            b.quick_reassign_to,
+            b.created_at,
+            b.updated_at,
+            visibility.as_str(),
+        ],
+    )?;
+
+    // Column order is what matters; positions are renumbered densely
+    let mut columns: Vec<&ExportColumn> = doc.columns.iter().collect();
+    columns.sort_by_key(|c| c.position);
+    let mut col_responses = Vec::new();
+    for (i, col) in columns.iter().enumerate() {
+        let new_id = &ids[&col.id];
+        conn.execute(
+            "INSERT INTO columns (id, board_id, name, position, wip_limit, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![new_id, board_id, col.name, i as i32, col.wip_limit, col.created_at],
+        )?;
+        col_responses.push(ColumnResponse {
+            id: new_id.clone(),
+            name: col.name.clone(),
+            position: i as i32,
+            wip_limit: col.wip_limit,
+            task_count: doc.tasks.iter().filter(|t| t.column_id == col.id).count() as i64,
+            auto_assign: Vec::new(),
+            auto_assign_strategy: auto_assign::ROUND_ROBIN.to_string(),
+        });
+        report.columns += 1;
+    }
+
+    for task in &doc.tasks {
+        conn.execute(
+            "INSERT INTO tasks (id, board_id, column_id, title, description, priority, position,
+                                created_by, assigned_to, claimed_by, claimed_at, labels, metadata,
+                                due_at, completed_at, archived_at, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
+            rusqlite::params![
+                ids[&task.id],
+                board_id,
+                ids[&task.column_id],
+                task.title,
+                task.description,
+                task.priority,
+                task.position,
+                task.created_by,
+                task.assigned_to,
+                task.claimed_by,
+                task.claimed_at,
+                serde_json::to_string(&task.labels).unwrap_or_else(|_| "[]".to_string()),
+                serde_json::to_string(&task.metadata).unwrap_or_else(|_| "{}".to_string()),
+                task.due_at,
+                task.completed_at,
+                task.archived_at,
+                task.created_at,
+                task.updated_at,
+            ],
+        )?;
+        report.tasks += 1;
+    }
+
+    for dep in &doc.dependencies {
+        let (Some(blocker), Some(blocked)) = (ids.get(&dep.blocker_task_id), ids.get(&dep.blocked_task_id)) else {
+            report.warnings.push(format!("Dependency '{}' references a task not in the export; skipped", dep.id));
+            continue;
+        };
+        conn.execute(
+            "INSERT OR IGNORE INTO task_dependencies (id, board_id, blocker_task_id, blocked_task_id, created_by, note, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![
+                uuid::Uuid::new_v4().to_string(),
+                board_id,
+                blocker,
+                blocked,
+                dep.created_by,
+                dep.note,
+                dep.created_at
+            ],
+        )?;
+        report.dependencies += 1;
+    }
+
+    // Replay history in its original order so new seq values keep it
+    let mut history: Vec<&ExportEvent> = doc.events.iter().chain(doc.comments.iter()).collect();
+    history.sort_by(|a, b| (a.seq.unwrap_or(0), &a.created_at).cmp(&(b.seq.unwrap_or(0), &b.created_at)));
+    let mut orphaned = 0;
+    for event in history {
+        let Some(task_id) = ids.get(&event.task_id) else {
+            orphaned += 1;
+            continue;
+        };
+        let data = remap_ids(&event.data, &ids);
+        conn.execute(
+            &format!(
+                "INSERT INTO task_events (id, task_id, event_type, actor, data, created_at, seq)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, {})",
+                NEXT_EVENT_SEQ
+            ),
+            rusqlite::params![
+                uuid::Uuid::new_v4().to_string(),
+                task_id,
+                event.event_type,
+                event.actor,
+                serde_json::to_string(&data).unwrap_or_else(|_| "{}".to_string()),
+                event.created_at,
+            ],
+        )?;
+        if event.event_type == "comment" {
+            report.comments += 1;
+        } else {
+            report.events += 1;
+        }
+    }
+    if orphaned > 0 {
+        report.warnings.push(format!("{} event(s) referenced tasks not in the export; skipped", orphaned));
+    }
+    if report.webhooks_skipped > 0 {
+        report.warnings.push(format!(
+            "{} webhook(s) not recreated — register them again on the new board",
+            report.webhooks_skipped
+        ));
+    }
+
+    Ok(ImportBoardResponse {
+        board: CreateBoardResponse {
+            id: board_id.clone(),
+            name: b.name.trim().to_string(),
+            description: b.description.clone(),
+            columns: col_responses,
+            manage_key: manage_key.clone(),
+            view_url: format!("/board/{}", board_id),
+            manage_url: format!("/board/{}?key={}", board_id, manage_key),
+            api_base: format!("/api/v1/boards/{}", board_id),
+            created_at: chrono::Utc::now().to_rfc3339(),
+        },
+        report,
+    })
+}
+
+/// Rewrite any string in event data that is an exported id (task, column, board).
+fn remap_ids(value: &serde_json::Value, ids: &HashMap<String, String>) -> serde_json::Value {
+    match value {
+        serde_json::Value::String(s) => match ids.get(s) {
+            Some(new_id) => serde_json::Value::String(new_id.clone()),
+            None => value.clone(),
+        },
+        serde_json::Value::Array(items) => items.iter().map(|v| remap_ids(v, ids)).collect(),
+        serde_json::Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| (k.clone(), remap_ids(v, ids)))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+        _ => value.clone(),
+    }
+}
+
+/// RFC 3339 timestamps from other tools, in the form SQLite's `datetime('now')` writes.
+pub(crate) fn db_time(ts: &str) -> Option<String> {
+    chrono::DateTime::parse_from_rfc3339(ts)
+        .ok()
+        .map(|dt| dt.with_timezone(&chrono::Utc).format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+pub(crate) fn invalid_import(error: String) -> (Status, Json<ApiError>) {
+    (
+        Status::BadRequest,
+        Json(ApiError {
+            error,
+            code: "INVALID_IMPORT".to_string(),
+            status: 400,
+        }),
+    )
+}
+
+// ============ CSV Tasks ============
+
+/// Columns a task CSV may have, in any order. Only `title` is required.
+const CSV_COLUMNS: &[&str] = &["title", "description", "column", "priority", "labels", "assigned_to", "due_at"];
+const MAX_CSV_ROWS: usize = 1000;
+
+/// One data row, keyed by header.
+#[derive(Debug, Clone)]
+struct CsvRow {
+    line: u64,
+    fields: HashMap<String, String>,
+}
+
+impl CsvRow {
+    fn get(&self, column: &str) -> &str {
+        self.fields.get(column).map(|v| v.as_str()).unwrap_or("")
+    }
+}
+
+/// Bulk-create tasks from CSV — requires manage key. Rows are validated one by one:
+/// good rows are created, bad rows are reported with their line number, and
+/// everything is written in one transaction. Rows the board's validation hook
+/// refuses are reported like any other bad row. Body size is capped by the `csv`
+/// data limit (default 2 MiB). Each row counts as a task write.
+#[allow(clippy::too_many_arguments)]
+#[post("/boards/<board_id>/tasks/import", format = "text/csv", data = "<csv>")]
+pub async fn import_tasks_csv(
+    board_id: &str,
+    csv: Data<'_>,
+    limits: &Limits,
+    token: BoardToken,
+    quota: WriteQuota<'_>,
+    actor: Actor,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<CsvImportResponse>, (Status, Json<ApiError>)> {
+    let limit = limits.get("csv").unwrap_or(2.mebibytes());
+    let body = csv
+        .open(limit)
+        .into_string()
+        .await
+        .map_err(|e| invalid_csv(format!("Could not read body: {}", e)))?;
+    if !body.is_complete() {
+        return Err((
+            Status::PayloadTooLarge,
+            Json(ApiError {
+                error: format!("CSV is larger than the {} limit", limit),
+                code: "PAYLOAD_TOO_LARGE".to_string(),
+                status: 413,
+            }),
+        ));
+    }
+    let rows = parse_csv(&body)?;
+
+    let (board, writer) = (board_id.to_owned(), actor.clone());
+    with_db(db, move |conn| {
+        let board_id = board.as_str();
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+        access::require_display_name(conn, board_id, &writer)
+    })
+    .await?;
+    // The request itself was counted as one write
+    quota.charge(rows.len().saturating_sub(1) as u64).await?;
+
+    let (board, bus) = (board_id.to_owned(), bus.inner().clone());
+    let client = bus.outbound().clone();
+    let response = validation_hook::run(db, &client, move |tx, gate| {
+        let board_id = board.as_str();
+        let actor = actor.name();
+
+        let mut stmt = tx
+            .prepare("SELECT id, name FROM columns WHERE board_id = ?1 ORDER BY position ASC")
+            .map_err(|e| db_error(&e.to_string()))?;
+        let columns: Vec<(String, String)> = stmt
+            .query_map(rusqlite::params![board_id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
+        drop(stmt);
+
+        let mut results = Vec::new();
+        let mut created = Vec::new();
+        for row in &rows {
+            match create_csv_task(tx, gate, board_id, &columns, row, actor) {
+                Ok((task_id, event_data)) => {
+                    created.push(event_data);
+                    results.push(CsvRowResult {
+                        line: row.line,
+                        success: true,
+                        task_id: Some(task_id),
+                        error: None,
+                        code: None,
+                    });
+                }
+                // Storage failures abort the whole import; anything else is the row's fault
+                Err((status, err)) if status == Status::InternalServerError => return Err((status, err)),
+                Err((_, Json(err))) => results.push(CsvRowResult {
+                    line: row.line,
+                    success: false,
+                    task_id: None,
+                    error: Some(err.error),
+                    code: Some(err.code),
+                }),
+            }
+        }
+        let succeeded = created.len();
+        for data in created {
+            bus.emit(
+                tx,
+                crate::events::BoardEvent {
+                    event: event_types::TASK_CREATED.to_string(),
+                    board_id: board_id.to_string(),
+                    data,
+                },
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        }
+
+        Ok(CsvImportResponse {
+            total: rows.len(),
+            succeeded,
+            failed: rows.len() - succeeded,
+            results,
+        })
+    })
+    .await?;
+    Ok(Json(response))
+}
+
+/// Read the header and rows; header problems reject the whole file.
+fn parse_csv(body: &str) -> Result<Vec<CsvRow>, (Status, Json<ApiError>)> {
+    // Spreadsheet exports often start with a byte-order mark
+    let body = body.strip_prefix('\u{feff}').unwrap_or(body);
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(body.as_bytes());
+
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| invalid_csv(format!("Unreadable header row: {}", e)))?
+        .iter()
+        .map(|h| h.to_lowercase().replace(' ', "_"))
+        .collect();
+    if let Some(unknown) = headers.iter().find(|h| !CSV_COLUMNS.contains(&h.as_str())) {
+        return Err(invalid_csv(format!(
+            "Unknown column '{}'. Valid columns: {}",
+            unknown,
+            CSV_COLUMNS.join(", ")
+        )));
+    }
+    if !headers.iter().any(|h| h == "title") {
+        return Err(invalid_csv("Header row must include a 'title' column".to_string()));
+    }
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| invalid_csv(e.to_string()))?;
+        if record.iter().all(|f| f.is_empty()) {
+            continue;
+        }
+        if rows.len() == MAX_CSV_ROWS {
+            return Err(invalid_csv(format!("Maximum {} rows per import", MAX_CSV_ROWS)));
+        }
+        rows.push(CsvRow {
+            line: record.position().map(|p| p.line()).unwrap_or(0),
+            fields: headers.iter().cloned().zip(record.iter().map(str::to_string)).collect(),
+        });
+    }
+    if rows.is_empty() {
+        return Err(invalid_csv("CSV has no data rows".to_string()));
+    }
+    Ok(rows)
+}
+
+/// Validate and insert one row. Returns the new task id and its `task.created` payload.
+fn create_csv_task(
+    conn: &Connection,
+    gate: &Gate,
+    board_id: &str,
+    columns: &[(String, String)],
+    row: &CsvRow,
+    actor: &str,
+) -> Result<(String, serde_json::Value), (Status, Json<ApiError>)> {
+    let row_error = |code: &str, error: String| {
+        (
+            Status::BadRequest,
+            Json(ApiError {
+                error,
+                code: code.to_string(),
+                status: 400,
+            }),
+        )
+    };
+
+    let (title, description) = (row.get("title"), row.get("description"));
+    if title.is_empty() && description.is_empty() {
+        return Err(row_error("EMPTY_TASK", "Either title or description must be provided".to_string()));
+    }
+    // Columns by name (case-insensitive) or id; blank means the first column
+    let column_id = match row.get("column") {
+        "" => columns.first().map(|(id, _)| id.clone()),
+        wanted => columns
+            .iter()
+            .find(|(id, name)| id == wanted || name.eq_ignore_ascii_case(wanted))
+            .map(|(id, _)| id.clone()),
+    }
+    .ok_or_else(|| row_error("INVALID_COLUMN", format!("Column '{}' not found in this board", row.get("column"))))?;
+    let priority = match row.get("priority") {
+        "" => 0,
+        p => parse_priority(p).map_err(|e| row_error("INVALID_PRIORITY", e))?,
+    };
+    let labels: Vec<String> = row.get("labels").split([',', ';']).map(str::to_string).collect();
+    let assigned_to = Some(row.get("assigned_to")).filter(|a| !a.is_empty());
+    let due_at = Some(row.get("due_at")).filter(|d| !d.is_empty());
+
+    check_wip_limit(conn, &column_id, None)?;
+    validation_hook::check(
+        conn,
+        gate,
+        board_id,
+        validation_hook::TASK_CREATE,
+        actor,
+        serde_json::json!({"task": {
+            "title": title,
+            "description": description,
+            "column_id": column_id,
+            "priority": priority,
+            "assigned_to": assigned_to,
+            "labels": normalize_labels(&labels),
+            "due_at": due_at,
+        }}),
+    )?;
+
+    let task_id = uuid::Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO tasks (id, board_id, column_id, title, description, priority, position, created_by, assigned_to, labels, due_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6,
+                 (SELECT COALESCE(MAX(position), -1) + 1 FROM tasks WHERE column_id = ?3),
+                 ?7, ?8, ?9, ?10)",
+        rusqlite::params![
+            task_id,
+            board_id,
+            column_id,
+            title,
+            description,
+            priority,
+            actor,
+            assigned_to,
+            serde_json::to_string(&normalize_labels(&labels)).unwrap_or_else(|_| "[]".to_string()),
+            due_at,
+        ],
+    )
+    .map_err(|e| db_error(&e.to_string()))?;
+
+    let event_data = serde_json::json!({"title": title, "task_id": task_id, "column_id": column_id, "creator": actor});
+    log_event(conn, &task_id, "created", actor, &event_data);
+    Ok((task_id, event_data))
+}
+
+fn invalid_csv(error: String) -> (Status, Json<ApiError>) {
+    (
+        Status::BadRequest,
+        Json(ApiError {
+            error,
+            code: "INVALID_CSV".to_string(),
+            status: 400,
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remap_ids_rewrites_nested_references() {
+        let ids: HashMap<String, String> = [("old-task", "new-task"), ("old-col", "new-col")]
+            .into_iter()
+            .map(|(a, b)| (a.to_string(), b.to_string()))
+            .collect();
+        let data = serde_json::json!({
+            "task_id": "old-task",
+            "to": {"column_id": "old-col"},
+            "message": "old-task is mentioned in prose",
+            "ids": ["old-col", 3],
+        });
+        let out = remap_ids(&data, &ids);
+        assert_eq!(out["task_id"], "new-task");
+        assert_eq!(out["to"]["column_id"], "new-col");
+        assert_eq!(out["message"], "old-task is mentioned in prose");
+        assert_eq!(out["ids"], serde_json::json!(["new-col", 3]));
+    }
+
+    #[test]
+    fn test_parse_csv_headers_and_quoting() {
+        let rows = parse_csv("\u{feff}Title,Assigned To,labels\n\"Fix, then ship\",ada,\"bug;ui\"\n,,\nShort\n").unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].get("title"), "Fix, then ship");
+        assert_eq!(rows[0].get("assigned_to"), "ada");
+        assert_eq!(rows[0].line, 2);
+        // Short rows are allowed; blank rows are skipped but still counted for line numbers
+        assert_eq!(rows[1].get("title"), "Short");
+        assert_eq!(rows[1].get("labels"), "");
+        assert_eq!(rows[1].line, 4);
+
+        let err = parse_csv("title,owner\nx,y\n").unwrap_err();
+        assert!(err.1.error.contains("'owner'"));
+        assert!(parse_csv("description\nx\n").is_err());
+        assert!(parse_csv("title\n").is_err());
+    }
+}
@@ -0,0 +1,481 @@
+//! Inbound webhooks: per-board URLs that CI systems, alerting, form builders
+//! and the like can post JSON to, each post becoming a task. The secret is a
+//! token in the URL (shown once, stored hashed). A hook's mapping picks the
+//! task's fields out of the payload with the same `{{path}}` templates as
+//! outgoing webhook payloads, and the raw payload is kept in the task's
+//! `metadata.inbound` so nothing the sender included is lost.
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::access;
+use crate::auth::{BoardToken, InsideIpAllowlist};
+use crate::auto_assign;
+use crate::db::{hash_key, DbPool};
+use crate::event_types;
+use crate::events::{BoardEvent, EventBus};
+use crate::models::*;
+use crate::routes::{
+    check_wip_limit, db_error, find_recent_duplicate, load_task_response, log_event, normalize_labels, not_found,
+    task_content_hash, with_db,
+};
+use crate::validation_hook;
+use crate::webhook_template;
+
+/// Largest payload accepted; it's stored with the task.
+const MAX_PAYLOAD_BYTES: usize = 64 * 1024;
+const MAX_NAME_CHARS: usize = 100;
+/// Top-level names mapping placeholders may use.
+const ROOTS: &[&str] = &["payload"];
+const DEFAULT_TITLE: &str = "{{payload.title}}";
+
+fn invalid(code: &str, error: String) -> (Status, Json<ApiError>) {
+    (
+        Status::BadRequest,
+        Json(ApiError {
+            error,
+            code: code.to_string(),
+            status: 400,
+        }),
+    )
+}
+
+fn check_name(name: &str) -> Result<String, (Status, Json<ApiError>)> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_CHARS {
+        return Err(invalid("INVALID_INPUT", format!("name must be 1-{} characters", MAX_NAME_CHARS)));
+    }
+    Ok(name.to_string())
+}
+
+fn check_mapping(mapping: &InboundMapping) -> Result<(), (Status, Json<ApiError>)> {
+    let fields = [
+        ("title", &mapping.title),
+        ("description", &mapping.description),
+        ("priority", &mapping.priority),
+        ("labels", &mapping.labels),
+        ("assigned_to", &mapping.assigned_to),
+        ("column", &mapping.column),
+        ("due_at", &mapping.due_at),
+    ];
+    for (field, template) in fields {
+        if let Some(template) = template {
+            webhook_template::validate_paths(template, ROOTS)
+                .map_err(|e| invalid("INVALID_TEMPLATE", format!("mapping.{}: {}", field, e)))?;
+        }
+    }
+    Ok(())
+}
+
+fn load(conn: &Connection, board_id: &str, hook_id: &str) -> Result<InboundHookResponse, (Status, Json<ApiError>)> {
+    conn.query_row(
+        "SELECT id, name, mapping, last_used_at, created_at FROM inbound_hooks WHERE id = ?1 AND board_id = ?2",
+        rusqlite::params![hook_id, board_id],
+        |row| hook_from_row(board_id, row),
+    )
+    .optional()
+    .map_err(|e| db_error(&e.to_string()))?
+    .ok_or_else(|| not_found("Inbound hook"))
+}
+
+fn hook_from_row(board_id: &str, row: &rusqlite::Row) -> rusqlite::Result<InboundHookResponse> {
+    let mapping: String = row.get(2)?;
+    Ok(InboundHookResponse {
+        id: row.get(0)?,
+        board_id: board_id.to_string(),
+        name: row.get(1)?,
+        mapping: serde_json::from_str(&mapping).unwrap_or_default(),
+        token: None,
+        last_used_at: row.get(3)?,
+        created_at: row.get(4)?,
+    })
+}
+
+/// The board's inbound hooks — requires manage key. Tokens aren't returned.
+#[get("/boards/<board_id>/inbound")]
+pub async fn list_inbound_hooks(
+    board_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<Vec<InboundHookResponse>>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, name, mapping, last_used_at, created_at FROM inbound_hooks
+                 WHERE board_id = ?1 ORDER BY created_at ASC, rowid ASC",
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        let hooks = stmt
+            .query_map(rusqlite::params![board_id], |row| hook_from_row(board_id, row))
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(Json(hooks))
+    })
+    .await
+}
+
+/// Create an inbound hook — requires manage key. The response carries the
+/// token for the hook's URL; it isn't shown again.
+#[post("/boards/<board_id>/inbound", format = "json", data = "<req>")]
+pub async fn create_inbound_hook(
+    board_id: &str,
+    req: Json<CreateInboundHookRequest>,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<InboundHookResponse>, (Status, Json<ApiError>)> {
+    let req = req.into_inner();
+    let name = check_name(&req.name)?;
+    check_mapping(&req.mapping)?;
+
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        let id = uuid::Uuid::new_v4().to_string();
+        let secret = format!("inb_{}", uuid::Uuid::new_v4().to_string().replace('-', ""));
+        conn.execute(
+            "INSERT INTO inbound_hooks (id, board_id, name, token_hash, mapping) VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                id,
+                board_id,
+                name,
+                hash_key(&secret),
+                serde_json::to_string(&req.mapping).unwrap_or_else(|_| "{}".to_string())
+            ],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        let mut hook = load(conn, board_id, &id)?;
+        hook.token = Some(secret);
+        Ok(Json(hook))
+    })
+    .await
+}
+
+/// Rename an inbound hook or replace its mapping — requires manage key.
+#[patch("/boards/<board_id>/inbound/<hook_id>", format = "json", data = "<req>")]
+pub async fn update_inbound_hook(
+    board_id: &str,
+    hook_id: &str,
+    req: Json<UpdateInboundHookRequest>,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<InboundHookResponse>, (Status, Json<ApiError>)> {
+    let req = req.into_inner();
+    let name = req.name.as_deref().map(check_name).transpose()?;
+    if let Some(ref mapping) = req.mapping {
+        check_mapping(mapping)?;
+    }
+
+    let (board_id, hook_id) = (board_id.to_owned(), hook_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, hook_id) = (board_id.as_str(), hook_id.as_str());
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        load(conn, board_id, hook_id)?;
+        conn.execute(
+            "UPDATE inbound_hooks SET name = COALESCE(?1, name), mapping = COALESCE(?2, mapping) WHERE id = ?3",
+            rusqlite::params![
+                name,
+                req.mapping.as_ref().and_then(|m| serde_json::to_string(m).ok()),
+                hook_id
+            ],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        load(conn, board_id, hook_id).map(Json)
+    })
+    .await
+}
+
+/// Delete an inbound hook; its URL stops working — requires manage key.
+#[delete("/boards/<board_id>/inbound/<hook_id>")]
+pub async fn delete_inbound_hook(
+    board_id: &str,
+    hook_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
+    let (board_id, hook_id) = (board_id.to_owned(), hook_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, hook_id) = (board_id.as_str(), hook_id.as_str());
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        load(conn, board_id, hook_id)?;
+        conn.execute("DELETE FROM inbound_hooks WHERE id = ?1", rusqlite::params![hook_id])
+            .map_err(|e| db_error(&e.to_string()))?;
+        Ok(Json(serde_json::json!({"deleted": true, "id": hook_id})))
+    })
+    .await
+}
+
+/// A rendered value as text: strings trimmed, numbers and booleans written
+/// out, null and empty strings as None.
+fn text(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(s) => Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// The task fields a mapping picks out of a payload.
+#[derive(Debug, PartialEq)]
+struct MappedTask {
+    title: Option<String>,
+    description: String,
+    priority: i32,
+    labels: Vec<String>,
+    assigned_to: Option<String>,
+    column: Option<String>,
+    due_at: Option<String>,
+}
+
+fn apply(mapping: &InboundMapping, payload: &serde_json::Value) -> MappedTask {
+    let context = serde_json::json!({ "payload": payload });
+    let render = |template: &Option<serde_json::Value>| {
+        template
+            .as_ref()
+            .map(|t| webhook_template::render(t, &context))
+            .unwrap_or(serde_json::Value::Null)
+    };
+    let title = match &mapping.title {
+        Some(template) => webhook_template::render(template, &context),
+        None => webhook_template::render(&serde_json::json!(DEFAULT_TITLE), &context),
+    };
+    let priority = match render(&mapping.priority) {
+        serde_json::Value::Number(n) => n.as_i64().map(|p| p as i32),
+        serde_json::Value::String(s) => parse_priority(s.trim()).ok(),
+        _ => None,
+    };
+    let labels = match render(&mapping.labels) {
+        serde_json::Value::Array(items) => items.iter().filter_map(text).collect(),
+        serde_json::Value::String(s) => s.split(',').map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect(),
+        other => text(&other).into_iter().collect(),
+    };
+    MappedTask {
+        title: text(&title),
+        description: text(&render(&mapping.description)).unwrap_or_default(),
+        priority: priority.unwrap_or(0),
+        labels,
+        assigned_to: text(&render(&mapping.assigned_to)),
+        column: text(&render(&mapping.column)),
+        due_at: text(&render(&mapping.due_at)),
+    }
+}
+
+/// Receive a payload and create a task from it — authorized by the token in
+/// the URL, no board key needed, but subject to the board's IP allowlist.
+/// The task's creator is the hook's name. Repeats within the board's duplicate
+/// window get the earlier task back.
+#[post("/boards/<board_id>/inbound/<hook_token>", data = "<payload>")]
+pub async fn receive_inbound(
+    board_id: &str,
+    hook_token: &str,
+    payload: Json<serde_json::Value>,
+    _address: InsideIpAllowlist,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<TaskResponse>, (Status, Json<ApiError>)> {
+    let payload = payload.into_inner();
+    if serde_json::to_vec(&payload).map_or(0, |p| p.len()) > MAX_PAYLOAD_BYTES {
+        return Err((
+            Status::PayloadTooLarge,
+            Json(ApiError {
+                error: format!("Inbound payloads are limited to {} bytes", MAX_PAYLOAD_BYTES),
+                code: "PAYLOAD_TOO_LARGE".to_string(),
+                status: 413,
+            }),
+        ));
+    }
+
+    let (board_id, bus, hook_token) = (board_id.to_owned(), bus.inner().clone(), hook_token.to_owned());
+    let client = bus.outbound().clone();
+    // Fixed up front so every pass asks the validation hook the same question
+    let received_at = chrono::Utc::now().to_rfc3339();
+    validation_hook::run(db, &client, move |conn, gate| {
+        let (board_id, bus, hook_token) = (board_id.as_str(), &bus, hook_token.as_str());
+        let (hook_id, name, mapping): (String, String, String) = conn
+            .query_row(
+                "SELECT id, name, mapping FROM inbound_hooks WHERE board_id = ?1 AND token_hash = ?2",
+                rusqlite::params![board_id, hash_key(hook_token)],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()
+            .map_err(|e| db_error(&e.to_string()))?
+            .ok_or_else(|| not_found("Inbound hook"))?;
+        access::require_not_archived(conn, board_id)?;
+        conn.execute(
+            "UPDATE inbound_hooks SET last_used_at = datetime('now') WHERE id = ?1",
+            rusqlite::params![hook_id],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+
+        let mapped = apply(&serde_json::from_str(&mapping).unwrap_or_default(), &payload);
+        let title = mapped.title.unwrap_or_else(|| name.clone());
+        let content_hash = task_content_hash(&title, &mapped.description, &name);
+        if let Some(existing) = find_recent_duplicate(conn, board_id, &content_hash) {
+            let mut task = load_task_response(conn, &existing)?;
+            task.duplicate = Some(true);
+            return Ok(task);
+        }
+
+        let mut stmt = conn
+            .prepare("SELECT id, name FROM columns WHERE board_id = ?1 ORDER BY position ASC")
+            .map_err(|e| db_error(&e.to_string()))?;
+        let columns: Vec<(String, String)> = stmt
+            .query_map(rusqlite::params![board_id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
+        let column_id = mapped
+            .column
+            .as_deref()
+            .and_then(|c| columns.iter().find(|(id, name)| id == c || name.eq_ignore_ascii_case(c)))
+            .or(columns.first())
+            .map(|(id, _)| id.clone())
+            .ok_or_else(|| {
+                (
+                    Status::BadRequest,
+                    Json(ApiError {
+                        error: "Board has no columns".to_string(),
+                        code: "NO_COLUMNS".to_string(),
+                        status: 400,
+                    }),
+                )
+            })?;
+        check_wip_limit(conn, &column_id, None)?;
+        let metadata = serde_json::json!({"inbound": {
+            "hook_id": hook_id,
+            "hook": name,
+            "received_at": received_at,
+            "payload": payload,
+        }});
+        validation_hook::check(
+            conn,
+            gate,
+            board_id,
+            validation_hook::TASK_CREATE,
+            &name,
+            serde_json::json!({"task": {
+                "title": title,
+                "description": mapped.description,
+                "column_id": column_id,
+                "priority": mapped.priority,
+                "assigned_to": mapped.assigned_to,
+                "labels": normalize_labels(&mapped.labels),
+                "metadata": metadata,
+                "due_at": mapped.due_at,
+            }}),
+        )?;
+
+        let position: i32 = conn
+            .query_row(
+                "SELECT COALESCE(MAX(position), -1) + 1 FROM tasks WHERE column_id = ?1",
+                rusqlite::params![column_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(0);
+        let task_id = uuid::Uuid::new_v4().to_string();
+        conn.execute(
+            "INSERT INTO tasks (id, board_id, column_id, title, description, priority, position, created_by, assigned_to, labels, metadata, due_at, content_hash)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            rusqlite::params![
+                task_id,
+                board_id,
+                column_id,
+                title,
+                mapped.description,
+                mapped.priority,
+                position,
+                name,
+                mapped.assigned_to,
+                serde_json::to_string(&normalize_labels(&mapped.labels)).unwrap_or_else(|_| "[]".to_string()),
+                serde_json::to_string(&metadata).unwrap_or_else(|_| "{}".to_string()),
+                mapped.due_at,
+                content_hash,
+            ],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+
+        let mut event_data = serde_json::json!({
+            "title": title,
+            "task_id": task_id,
+            "column_id": column_id,
+            "creator": name,
+            "source": "inbound",
+            "inbound_hook_id": hook_id,
+        });
+        if let Some(ref assignee) = mapped.assigned_to {
+            event_data["assigned_to"] = serde_json::json!(assignee);
+        }
+        log_event(conn, &task_id, "created", &name, &event_data);
+        bus.emit(
+            conn,
+            BoardEvent {
+                event: event_types::TASK_CREATED.to_string(),
+                board_id: board_id.to_string(),
+                data: event_data,
+            },
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        if let Some(assignment) =
+            auto_assign::on_create(conn, &task_id, &column_id).map_err(|e| db_error(&e.to_string()))?
+        {
+            assignment.emit(conn, bus, board_id).map_err(|e| db_error(&e.to_string()))?;
+        }
+
+        load_task_response(conn, &task_id)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_mapping() {
+        let payload = serde_json::json!({
+            "alert": {"name": "Disk full", "severity": "critical", "host": "db-1"},
+            "tags": ["ops", "  ", 3],
+            "owner": null,
+        });
+        let mapping: InboundMapping = serde_json::from_value(serde_json::json!({
+            "title": "{{payload.alert.name}} on {{payload.alert.host}}",
+            "description": "Severity: {{payload.alert.severity}}",
+            "priority": "{{payload.alert.severity}}",
+            "labels": "{{payload.tags}}",
+            "assigned_to": "{{payload.owner}}",
+            "column": "Triage",
+        }))
+        .unwrap();
+        assert_eq!(
+            apply(&mapping, &payload),
+            MappedTask {
+                title: Some("Disk full on db-1".to_string()),
+                description: "Severity: critical".to_string(),
+                priority: 3,
+                labels: vec!["ops".to_string(), "3".to_string()],
+                assigned_to: None,
+                column: Some("Triage".to_string()),
+                due_at: None,
+            }
+        );
+
+        // Defaults: title from `payload.title`, comma-separated labels split
+        let mapped = apply(
+            &InboundMapping { labels: Some(serde_json::json!("ci, {{payload.branch}}")), ..Default::default() },
+            &serde_json::json!({"title": "Build failed", "branch": "main"}),
+        );
+        assert_eq!(mapped.title.as_deref(), Some("Build failed"));
+        assert_eq!(mapped.labels, vec!["ci", "main"]);
+        assert_eq!(apply(&InboundMapping::default(), &serde_json::json!([1, 2])).title, None);
+
+        assert!(check_mapping(&mapping).is_ok());
+        let bad = InboundMapping { title: Some(serde_json::json!("{{event}}")), ..Default::default() };
+        assert_eq!(check_mapping(&bad).unwrap_err().1.code, "INVALID_TEMPLATE");
+    }
+}
@@ -0,0 +1,166 @@
+//! Per-name mention inbox. Every `@name` in a comment lands in
+//! `task_mentions` (kept in sync by triggers on `task_events`), so "was I
+//! pinged?" is one indexed lookup instead of a scan of the activity feed.
+//! Each name has a read cursor per board, moved forward with `ack`, and the
+//! name's notification preference decides which mentions show up at all.
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::access::{self, BoardRole};
+use crate::auth::BoardToken;
+use crate::db::{hash_key, DbPool};
+use crate::models::*;
+use crate::notifications;
+use crate::routes::{db_error, with_db};
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 200;
+
+fn cursor(conn: &Connection, board_id: &str, name: &str) -> Result<i64, (Status, Json<ApiError>)> {
+    conn.query_row(
+        "SELECT seq FROM inbox_cursors WHERE board_id = ?1 AND name = ?2",
+        rusqlite::params![board_id, name],
+        |row| row.get(0),
+    )
+    .optional()
+    .map(|seq| seq.unwrap_or(0))
+    .map_err(|e| db_error(&e.to_string()))
+}
+
+/// Extra condition on `task_mentions m JOIN tasks t` (name bound as ?2)
+/// for a notification level: `assigned` keeps mentions on the name's own
+/// tasks, `mute` keeps none.
+fn level_filter(level: &str) -> &'static str {
+    match level {
+        notifications::ASSIGNED => " AND (t.assigned_to = ?2 COLLATE NOCASE OR t.claimed_by = ?2 COLLATE NOCASE)",
+        notifications::MUTE => " AND 0",
+        _ => "",
+    }
+}
+
+/// Mentions of `name` on the board after `after` that its level lets through.
+fn unread_count(
+    conn: &Connection,
+    board_id: &str,
+    name: &str,
+    level: &str,
+    after: i64,
+) -> Result<i64, (Status, Json<ApiError>)> {
+    conn.query_row(
+        &format!(
+            "SELECT COUNT(*) FROM task_mentions m JOIN tasks t ON t.id = m.task_id
+             WHERE m.name = ?2 AND m.seq > ?3 AND t.board_id = ?1{}",
+            level_filter(level)
+        ),
+        rusqlite::params![board_id, name, after],
+        |row| row.get(0),
+    )
+    .map_err(|e| db_error(&e.to_string()))
+}
+
+fn notification_level(conn: &Connection, board_id: &str, name: &str) -> Result<String, (Status, Json<ApiError>)> {
+    notifications::level(conn, board_id, name).map_err(|e| db_error(&e.to_string()))
+}
+
+/// Unread mentions of a name, oldest first — public. `?limit=` caps the
+/// items (default 50, max 200); `unread` always counts all of them. A muted
+/// name's inbox is always empty.
+#[get("/boards/<board_id>/inbox/<name>?<limit>")]
+pub async fn get_inbox(
+    board_id: &str,
+    name: &str,
+    limit: Option<u32>,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+) -> Result<Json<InboxResponse>, (Status, Json<ApiError>)> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let (board_id, name) = (board_id.to_owned(), name.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, name) = (board_id.as_str(), name.as_str());
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        let cursor = cursor(conn, board_id, name)?;
+        let level = notification_level(conn, board_id, name)?;
+        let items = conn
+            .prepare(&format!(
+                "SELECT m.seq, m.event_id, m.task_id, t.title, te.actor, te.data, m.created_at
+                 FROM task_mentions m
+                 JOIN tasks t ON t.id = m.task_id
+                 JOIN task_events te ON te.id = m.event_id
+                 WHERE m.name = ?2 AND m.seq > ?3 AND t.board_id = ?1{}
+                 ORDER BY m.seq ASC
+                 LIMIT ?4",
+                level_filter(&level)
+            ))
+            .map_err(|e| db_error(&e.to_string()))?
+            .query_map(rusqlite::params![board_id, name, cursor, limit], |row| {
+                let data: serde_json::Value = serde_json::from_str(&row.get::<_, String>(5)?).unwrap_or_default();
+                Ok(InboxItem {
+                    seq: row.get(0)?,
+                    event_id: row.get(1)?,
+                    task_id: row.get(2)?,
+                    task_title: row.get(3)?,
+                    actor: row.get(4)?,
+                    message: data["message"].as_str().unwrap_or_default().to_string(),
+                    created_at: row.get(6)?,
+                })
+            })
+            .map_err(|e| db_error(&e.to_string()))?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| db_error(&e.to_string()))?;
+        Ok(Json(InboxResponse {
+            name: name.to_string(),
+            cursor,
+            unread: unread_count(conn, board_id, name, &level, cursor)?,
+            level,
+            items,
+        }))
+    })
+    .await
+}
+
+/// Mark a name's mentions read up to `?seq=` (default: all of them) —
+/// requires manage key. The cursor never moves backwards.
+#[post("/boards/<board_id>/inbox/<name>/ack?<seq>")]
+pub async fn ack_inbox(
+    board_id: &str,
+    name: &str,
+    seq: Option<i64>,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<InboxCursorResponse>, (Status, Json<ApiError>)> {
+    let (board_id, name) = (board_id.to_owned(), name.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, name) = (board_id.as_str(), name.as_str());
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Viewer)?;
+        let seq = match seq {
+            Some(seq) => seq,
+            None => conn
+                .query_row(
+                    "SELECT COALESCE(MAX(m.seq), 0) FROM task_mentions m JOIN tasks t ON t.id = m.task_id
+                     WHERE m.name = ?2 AND t.board_id = ?1",
+                    rusqlite::params![board_id, name],
+                    |row| row.get(0),
+                )
+                .map_err(|e| db_error(&e.to_string()))?,
+        };
+        conn.execute(
+            "INSERT INTO inbox_cursors (board_id, name, seq) VALUES (?1, ?2, ?3)
+             ON CONFLICT(board_id, name) DO UPDATE SET
+                 seq = MAX(inbox_cursors.seq, excluded.seq),
+                 updated_at = datetime('now')",
+            rusqlite::params![board_id, name, seq],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        let cursor = cursor(conn, board_id, name)?;
+        let level = notification_level(conn, board_id, name)?;
+        Ok(Json(InboxCursorResponse {
+            name: name.to_string(),
+            cursor,
+            unread: unread_count(conn, board_id, name, &level, cursor)?,
+        }))
+    })
+    .await
+}
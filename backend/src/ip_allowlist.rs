@@ -0,0 +1,236 @@
+//! Per-board IP allowlists: CIDR ranges a board's writes must come from, so a
+//! leaked key is no use outside the owner's infrastructure. Reads are
+//! unaffected. The `BoardToken` guard checks every non-GET request to
+//! `/boards/<id>/...` against `ClientIp`, and `InsideIpAllowlist` does the same
+//! for inbound hook payloads. `ClientIp` is the socket peer unless that peer is
+//! one of the `TRUSTED_PROXIES`, so forwarding headers can't be spoofed past it.
+
+use std::net::IpAddr;
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::access::{self, BoardRole};
+use crate::auth::BoardToken;
+use crate::db::{hash_key, DbPool};
+use crate::models::*;
+use crate::rate_limit::ClientIp;
+use crate::routes::{db_error, with_db};
+
+const MAX_RANGES: usize = 100;
+
+/// An address range like `10.0.0.0/8` or `2001:db8::/32`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Cidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+/// IPv4-mapped IPv6 addresses (`::ffff:10.0.0.1`) as the IPv4 they carry.
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        v4 => v4,
+    }
+}
+
+fn bits(ip: IpAddr) -> (u128, u8) {
+    match ip {
+        IpAddr::V4(v4) => (u32::from(v4) as u128, 32),
+        IpAddr::V6(v6) => (u128::from(v6), 128),
+    }
+}
+
+fn mask(prefix: u8, width: u8) -> u128 {
+    if prefix == 0 {
+        0
+    } else {
+        (u128::MAX << (128 - prefix as u32)) >> (128 - width as u32)
+    }
+}
+
+impl Cidr {
+    /// A range, or a bare address (a range of one).
+    pub(crate) fn parse(s: &str) -> Option<Cidr> {
+        let (addr, prefix) = match s.trim().split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix.parse::<u8>().ok()?)),
+            None => (s.trim(), None),
+        };
+        let ip = canonical(addr.parse::<IpAddr>().ok()?);
+        let (value, width) = bits(ip);
+        let prefix = prefix.unwrap_or(width);
+        if prefix > width {
+            return None;
+        }
+        let network = value & mask(prefix, width);
+        let network = match ip {
+            IpAddr::V4(_) => IpAddr::V4((network as u32).into()),
+            IpAddr::V6(_) => IpAddr::V6(network.into()),
+        };
+        Some(Cidr { network, prefix })
+    }
+
+    pub(crate) fn contains(&self, ip: IpAddr) -> bool {
+        let ip = canonical(ip);
+        let ((network, width), (value, ip_width)) = (bits(self.network), bits(ip));
+        width == ip_width && value & mask(self.prefix, width) == network
+    }
+}
+
+impl std::fmt::Display for Cidr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+/// Whether `ip` may write under `ranges`. No ranges means no restriction; an
+/// address that can't be parsed (`ClientIp` of "unknown") is outside them all.
+pub fn allows(ranges: &[String], ip: &str) -> bool {
+    if ranges.is_empty() {
+        return true;
+    }
+    let Ok(ip) = ip.parse::<IpAddr>() else {
+        return false;
+    };
+    ranges.iter().filter_map(|r| Cidr::parse(r)).any(|cidr| cidr.contains(ip))
+}
+
+/// The board's allowlist; empty when it has none (or doesn't exist).
+pub fn board_ranges(conn: &Connection, board_id: &str) -> Vec<String> {
+    conn.query_row(
+        "SELECT write_allowlist FROM boards WHERE id = ?1",
+        rusqlite::params![board_id],
+        |row| row.get::<_, Option<String>>(0),
+    )
+    .optional()
+    .ok()
+    .flatten()
+    .flatten()
+    .and_then(|json| serde_json::from_str(&json).ok())
+    .unwrap_or_default()
+}
+
+fn invalid(error: String, code: &str) -> (Status, Json<ApiError>) {
+    (
+        Status::BadRequest,
+        Json(ApiError {
+            error,
+            code: code.to_string(),
+            status: 400,
+        }),
+    )
+}
+
+/// Ranges as normalized CIDRs (`10.1.2.3/8` → `10.0.0.0/8`), duplicates dropped.
+fn normalize(ranges: &[String]) -> Result<Vec<String>, (Status, Json<ApiError>)> {
+    if ranges.len() > MAX_RANGES {
+        return Err(invalid(format!("At most {} ranges", MAX_RANGES), "INVALID_INPUT"));
+    }
+    let mut normalized: Vec<String> = Vec::new();
+    for range in ranges {
+        let cidr = Cidr::parse(range)
+            .ok_or_else(|| invalid(format!("'{}' isn't an IP address or CIDR range", range.trim()), "INVALID_INPUT"))?
+            .to_string();
+        if !normalized.contains(&cidr) {
+            normalized.push(cidr);
+        }
+    }
+    Ok(normalized)
+}
+
+fn response(board_id: &str, ranges: Vec<String>, client_ip: ClientIp) -> Json<IpAllowlistResponse> {
+    Json(IpAllowlistResponse {
+        board_id: board_id.to_string(),
+        ranges,
+        client_ip: client_ip.0,
+    })
+}
+
+/// The ranges writes must come from, and the caller's address as seen by the
+/// server — requires admin.
+#[get("/boards/<board_id>/ip-allowlist")]
+pub async fn get_ip_allowlist(
+    board_id: &str,
+    token: BoardToken,
+    client_ip: ClientIp,
+    db: &State<DbPool>,
+) -> Result<Json<IpAllowlistResponse>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Admin)?;
+        Ok(response(board_id, board_ranges(conn, board_id), client_ip))
+    })
+    .await
+}
+
+/// Replace the allowlist (`[]` lifts it) — requires admin. A list that leaves
+/// out the caller's own address is refused, so nobody locks themselves out.
+#[put("/boards/<board_id>/ip-allowlist", format = "json", data = "<req>")]
+pub async fn set_ip_allowlist(
+    board_id: &str,
+    req: Json<SetIpAllowlistRequest>,
+    token: BoardToken,
+    client_ip: ClientIp,
+    db: &State<DbPool>,
+) -> Result<Json<IpAllowlistResponse>, (Status, Json<ApiError>)> {
+    let ranges = normalize(&req.ranges)?;
+    if !allows(&ranges, &client_ip.0) {
+        return Err(invalid(
+            format!("The list doesn't include your address ({}); add it so you can still change the board", client_ip.0),
+            "WOULD_LOCK_OUT",
+        ));
+    }
+
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Admin)?;
+        let stored = (!ranges.is_empty()).then(|| serde_json::to_string(&ranges).unwrap_or_else(|_| "[]".to_string()));
+        conn.execute(
+            "UPDATE boards SET write_allowlist = ?1, updated_at = datetime('now') WHERE id = ?2",
+            rusqlite::params![stored, board_id],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        Ok(response(board_id, ranges, client_ip))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(ranges: &[&str]) -> Vec<String> {
+        ranges.iter().map(|r| r.to_string()).collect()
+    }
+
+    #[test]
+    fn test_allows() {
+        let ranges = list(&["10.0.0.0/8", "192.168.1.7", "2001:db8::/32"]);
+        assert!(allows(&ranges, "10.200.3.4"));
+        assert!(allows(&ranges, "192.168.1.7"));
+        assert!(allows(&ranges, "::ffff:10.0.0.1"));
+        assert!(allows(&ranges, "2001:db8:1::5"));
+        assert!(!allows(&ranges, "192.168.1.8"));
+        assert!(!allows(&ranges, "11.0.0.1"));
+        assert!(!allows(&ranges, "2001:db9::1"));
+        assert!(!allows(&ranges, "unknown"));
+        assert!(allows(&[], "unknown"));
+        assert!(allows(&list(&["0.0.0.0/0"]), "8.8.8.8"));
+        assert!(!allows(&list(&["0.0.0.0/0"]), "2001:db8::1"));
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize(&list(&["10.1.2.3/8", " 10.0.0.0/8 ", "::1", "2001:db8::1/32"])).unwrap(),
+            list(&["10.0.0.0/8", "::1/128", "2001:db8::/32"])
+        );
+        for bad in ["10.0.0.0/33", "::/129", "example.com", "10.0.0/8", ""] {
+            assert!(normalize(&list(&[bad])).is_err(), "{:?}", bad);
+        }
+    }
+}
@@ -2,11 +2,62 @@
 extern crate rocket;
 
 pub mod access;
+pub mod actors;
+pub mod admin;
+pub mod agent_keys;
+pub mod analytics;
+pub mod attachments;
 pub mod auth;
+pub mod auto_assign;
+pub mod backup;
+pub mod board_events;
+pub mod calendar;
 pub mod catchers;
+pub mod collaborators;
+pub mod comment_pressure;
+pub mod comments;
+pub mod compression;
+pub mod cors;
+pub mod credentials;
 pub mod db;
+pub mod discord;
+pub mod email;
 pub mod events;
+pub mod event_types;
+pub mod export;
+pub mod features;
+pub mod feed;
+pub mod github;
+pub mod hierarchy;
+pub mod import;
+pub mod inbound;
+pub mod inbox;
+pub mod ip_allowlist;
+pub mod lifecycle;
+pub mod merge;
 pub mod models;
+pub mod notifications;
+pub mod oidc;
+pub mod query_guard;
 pub mod rate_limit;
+pub mod retention;
 pub mod routes;
+pub mod scoped_tokens;
+pub mod secrets;
+pub mod share_keys;
+pub mod share_links;
+pub mod signing;
+pub mod slack;
+pub mod tags;
+pub mod telegram;
+pub mod trash;
+pub mod trello;
+pub mod url_policy;
+pub mod usage;
+pub mod validation_hook;
+pub mod view_password;
+pub mod watchers;
+pub mod webhook_keys;
+pub mod webhook_template;
 pub mod webhooks;
+pub mod websocket;
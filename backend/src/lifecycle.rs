@@ -0,0 +1,166 @@
+//! Instance-level board lifecycle hooks.
+//!
+//! Unlike per-board webhooks, these are configured by the operator (env vars)
+//! and fire for every board on the instance, so related resources such as chat
+//! channels or repos can be provisioned when agents spin up new boards.
+
+use rusqlite::Connection;
+
+use crate::db::DbPool;
+use crate::routes::{db_error, with_db};
+use crate::url_policy::{OutboundClient, UrlPolicy};
+use crate::webhooks::{self, RetryPolicy};
+
+pub const BOARD_CREATED: &str = "board.created";
+pub const BOARD_ARCHIVED: &str = "board.archived";
+pub const BOARD_DELETED: &str = "board.deleted";
+
+/// Outbound hook targets shared by every board.
+#[derive(Debug, Clone, Default)]
+pub struct LifecycleHooks {
+    urls: Vec<String>,
+    secret: String,
+    client: OutboundClient,
+    policy: RetryPolicy,
+}
+
+impl LifecycleHooks {
+    /// Hooks posting to `urls`, signed with `secret` like board webhooks.
+    pub fn new(urls: Vec<String>, secret: &str) -> Self {
+        LifecycleHooks {
+            urls,
+            secret: secret.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Read `BOARD_HOOK_URLS` (comma-separated) and `BOARD_HOOK_SECRET`.
+    /// Retries follow the webhook retry settings.
+    pub fn from_env() -> Self {
+        let urls = std::env::var("BOARD_HOOK_URLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|u| !u.is_empty())
+            .map(str::to_string)
+            .collect();
+        let secret = std::env::var("BOARD_HOOK_SECRET").unwrap_or_default();
+        Self::new(urls, &secret).with_retry_policy(RetryPolicy::from_env())
+    }
+
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Override which URLs the hooks may be sent to.
+    pub fn with_url_policy(mut self, policy: UrlPolicy) -> Self {
+        self.client = OutboundClient::new(policy);
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.urls.is_empty()
+    }
+
+    /// Snapshot the board and deliver `event` to every hook in the background.
+    /// Does nothing when no hooks are configured; failures are logged, never surfaced.
+    pub async fn notify(&self, db: &DbPool, event: &str, board_id: &str, actor: Option<&str>) {
+        if !self.is_enabled() {
+            return;
+        }
+        let board = board_id.to_owned();
+        match with_db(db, move |conn| board_payload(conn, &board).map_err(|e| db_error(&e.to_string()))).await {
+            Ok(board) => self.send(event, board_id, board, actor),
+            Err(_) => eprintln!("⚠️  Board hook {} skipped: board {} not readable", event, board_id),
+        }
+    }
+
+    /// Deliver a pre-built board snapshot — for boards that no longer exist.
+    pub fn send(&self, event: &str, board_id: &str, mut data: serde_json::Value, actor: Option<&str>) {
+        if !self.is_enabled() {
+            return;
+        }
+        data["actor"] = serde_json::json!(actor);
+        let payload = serde_json::json!({
+            "event": event,
+            "board_id": board_id,
+            "data": data,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        });
+        let payload_bytes = serde_json::to_vec(&payload).unwrap_or_default();
+        let hooks = self.clone();
+        let event = event.to_string();
+        let board_id = board_id.to_string();
+
+        tokio::spawn(async move {
+            let keys = [webhooks::SigningKey::default_key(&hooks.secret)];
+            for url in &hooks.urls {
+                let mut attempts = 0;
+                let mut delay = hooks.policy.base_delay;
+                loop {
+                    attempts += 1;
+                    let result =
+                        webhooks::send_signed(&hooks.client, url, &keys, &event, &board_id, &payload_bytes)
+                            .await;
+                    match result {
+                        Ok(()) => break,
+                        Err(e) if attempts >= hooks.policy.max_attempts => {
+                            eprintln!("⚠️  Board hook {} to {} failed after {} attempts: {}", event, url, attempts, e);
+                            break;
+                        }
+                        Err(_) => {
+                            tokio::time::sleep(delay).await;
+                            delay *= 2;
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Board metadata and owner sent with every lifecycle event.
+pub fn board_payload(conn: &Connection, board_id: &str) -> rusqlite::Result<serde_json::Value> {
+    let (name, description, is_public, archived, created_by, created_at, updated_at) = conn.query_row(
+        "SELECT name, description, is_public, archived, created_by, created_at, updated_at
+         FROM boards WHERE id = ?1",
+        rusqlite::params![board_id],
+        |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i32>(2)? == 1,
+                row.get::<_, i32>(3)? == 1,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, String>(6)?,
+            ))
+        },
+    )?;
+    let columns: Vec<String> = conn
+        .prepare("SELECT name FROM columns WHERE board_id = ?1 ORDER BY position ASC")?
+        .query_map(rusqlite::params![board_id], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    let task_count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM tasks WHERE board_id = ?1",
+        rusqlite::params![board_id],
+        |row| row.get(0),
+    )?;
+
+    Ok(serde_json::json!({
+        "board": {
+            "id": board_id,
+            "name": name,
+            "description": description,
+            "is_public": is_public,
+            "archived": archived,
+            "columns": columns,
+            "task_count": task_count,
+            "view_url": format!("/board/{}", board_id),
+            "created_at": created_at,
+            "updated_at": updated_at,
+        },
+        "owner": { "name": created_by },
+    }))
+}
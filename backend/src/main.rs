@@ -2,33 +2,104 @@
 extern crate rocket;
 
 mod access;
+mod actors;
+mod admin;
+mod agent_keys;
+mod analytics;
+mod attachments;
 mod auth;
+mod auto_assign;
+mod backup;
+mod board_events;
+mod calendar;
+mod collaborators;
+mod comment_pressure;
+mod comments;
+mod compression;
+mod cors;
+mod credentials;
 mod db;
+mod discord;
+mod email;
 mod events;
+mod event_types;
+mod export;
+mod features;
+mod feed;
+mod github;
+mod hierarchy;
+mod import;
+mod inbound;
+mod inbox;
+mod ip_allowlist;
+mod lifecycle;
+mod merge;
 mod models;
+mod notifications;
+mod oidc;
+mod query_guard;
 mod rate_limit;
+mod retention;
 mod routes;
+mod scoped_tokens;
+mod secrets;
+mod share_keys;
+mod share_links;
+mod signing;
+mod slack;
+mod tags;
+mod telegram;
+mod trash;
+mod trello;
+mod url_policy;
+mod usage;
+mod validation_hook;
+mod view_password;
+mod watchers;
+mod webhook_keys;
+mod webhook_template;
 mod webhooks;
+mod websocket;
 
 use std::path::PathBuf;
 
-use std::time::Duration;
 
 use events::EventBus;
-use rate_limit::RateLimiter;
+use rocket::fairing::AdHoc;
 use rocket::fs::{FileServer, Options};
-use rocket_cors::{AllowedOrigins, CorsOptions};
 
 use kanban::catchers::*;
 
+/// Re-encrypt the database from `DATABASE_KEY` to `NEW_DATABASE_KEY` (either
+/// may be unset: plaintext). Returns the process exit code.
+fn rekey_from_env() -> i32 {
+    let path = db::database_path();
+    let new_key = std::env::var("NEW_DATABASE_KEY").ok().filter(|k| !k.is_empty());
+    match db::rekey(&path, db::database_key().as_deref(), new_key.as_deref()) {
+        Ok(_) => {
+            println!("🔑 Re-keyed {} (previous file deleted).", path);
+            println!("   Set DATABASE_KEY to the new key before starting the server.");
+            0
+        }
+        Err(e) => {
+            eprintln!("❌ Rekey failed: {}", e);
+            1
+        }
+    }
+}
+
 #[launch]
 fn rocket() -> _ {
     let _ = dotenvy::dotenv();
 
-    let cors = CorsOptions::default()
-        .allowed_origins(AllowedOrigins::all())
-        .to_cors()
-        .expect("CORS configuration failed");
+    // `kanban rekey`: offline key rotation, run with the server stopped
+    if std::env::args().nth(1).as_deref() == Some("rekey") {
+        std::process::exit(rekey_from_env());
+    }
+
+    let cors = cors::CorsConfig::from_env()
+        .and_then(|config| config.to_cors())
+        .expect("Invalid CORS settings");
 
     // Frontend static files directory (default: ../frontend/dist relative to CWD)
     let static_dir: PathBuf = std::env::var("STATIC_DIR")
@@ -41,32 +112,121 @@ fn rocket() -> _ {
     // Initialize a separate DB connection for async webhook delivery
     let webhook_db = db::init_webhook_db().expect("Failed to initialize webhook database");
 
-    // Board creation rate limiter: 10 boards per hour per IP
-    let board_rate_limit = std::env::var("BOARD_RATE_LIMIT")
-        .ok()
-        .and_then(|v| v.parse::<u64>().ok())
-        .unwrap_or(10);
-    let board_rate_limiter = RateLimiter::new(Duration::from_secs(3600), board_rate_limit);
+    // Rate limits per route group, tunable from Rocket.toml or the environment
+    let rate_limit_config =
+        rate_limit::RateLimitConfig::from_figment(&rocket::Config::figment()).expect("Invalid rate limit settings");
+    let rate_limits = rate_limit::RateLimits::with_stores(&rate_limit_config, |group| {
+        rate_limit::store_from_env(&db, group.as_str()).expect("Invalid rate limit settings")
+    });
 
-    let mut build = rocket::build()
+    let client_usage = usage::ClientUsage::default();
+    let query_guard = query_guard::QueryGuard::from_env();
+    let attachment_config = attachments::AttachmentConfig::from_env();
+    let email_config = email::EmailConfig::from_env();
+    let trusted_proxies = rate_limit::TrustedProxies::from_env().expect("Invalid TRUSTED_PROXIES");
+    let url_policy = url_policy::UrlPolicy::from_env();
+    let mut build = rocket::custom(trusted_proxies.apply(attachment_config.apply_limits(rocket::Config::figment())))
         .attach(cors)
-        .register("/", catchers![unauthorized, not_found, unprocessable, too_many_requests, internal_error])
+        .attach(compression::Compression::from_env())
+        .attach(client_usage.clone())
+        .attach(query_guard.clone())
+        .attach(rate_limit::RateLimitHeaders)
+        .register("/", catchers![unauthorized, forbidden, not_found, payload_too_large, unprocessable, too_many_requests, internal_error])
         .manage(db)
-        .manage(board_rate_limiter)
-        .manage(EventBus::with_webhooks(webhook_db))
+        .manage(rate_limits)
+        .manage(
+            EventBus::with_webhooks(webhook_db)
+                .with_capacity(EventBus::capacity_from_env())
+                .with_retry_policy(webhooks::RetryPolicy::from_env())
+                .with_url_policy(url_policy),
+        )
+        .manage(auth::AdminConfig::from_env())
+        .manage(oidc::OidcConfig::from_env().with_url_policy(url_policy))
+        .manage(lifecycle::LifecycleHooks::from_env().with_url_policy(url_policy))
+        .manage(backup::BackupConfig::from_env())
+        .manage(trash::TrashConfig::from_env())
+        .manage(features::FeatureFlags::from_env())
+        .manage(attachment_config)
+        .manage(client_usage)
+        .manage(query_guard)
+        .attach(AdHoc::on_liftoff("Scheduled jobs", |rocket| {
+            Box::pin(async move {
+                if let (Some(db), Some(config)) = (rocket.state::<db::DbPool>(), rocket.state::<backup::BackupConfig>()) {
+                    backup::spawn_scheduled(db.clone(), config.clone());
+                }
+                if let (Some(db), Some(config)) = (rocket.state::<db::DbPool>(), rocket.state::<trash::TrashConfig>()) {
+                    trash::spawn_purge(db.clone(), config.clone());
+                }
+                if let (Some(db), Some(bus)) = (rocket.state::<db::DbPool>(), rocket.state::<EventBus>()) {
+                    retention::spawn(db.clone(), bus.clone());
+                }
+                if let Some(limits) = rocket.state::<rate_limit::RateLimits>() {
+                    rate_limit::spawn_prune(limits.clone());
+                }
+                if let Some(bus) = rocket.state::<EventBus>() {
+                    // Send webhooks queued before the last shutdown
+                    bus.start_webhook_dispatcher();
+                }
+                if let (Some(db), Some(config), Some(bus)) = (
+                    rocket.state::<db::DbPool>(),
+                    rocket.state::<attachments::AttachmentConfig>(),
+                    rocket.state::<EventBus>(),
+                ) {
+                    attachments::spawn_sweep(db.clone(), config.clone(), bus.outbound().clone());
+                }
+                if let Some(db) = rocket.state::<db::DbPool>() {
+                    email::spawn(db.clone(), email_config);
+                }
+            })
+        }))
         .mount(
             "/api/v1",
             routes![
                 routes::health,
                 routes::openapi,
+                routes::list_event_types,
                 routes::llms_txt,
-                // Boards (create = no auth, list = public only)
+                routes::client_sdk,
+                // Boards (create/import = no auth, list = public only)
                 routes::create_board,
+                import::import_board,
+                import::validate_import,
+                import::commit_import,
+                trello::import_trello_board,
                 routes::list_boards,
+                tags::list_tags,
                 routes::get_board,
                 routes::update_board,
                 routes::archive_board,
                 routes::unarchive_board,
+                routes::rotate_manage_key,
+                routes::delete_board,
+                collaborators::list_collaborators,
+                collaborators::my_board_role,
+                collaborators::add_collaborator,
+                collaborators::update_collaborator,
+                collaborators::remove_collaborator,
+                share_keys::list_share_keys,
+                share_keys::create_share_key,
+                share_keys::revoke_share_key,
+                share_links::create_share_link,
+                share_links::revoke_share_links,
+                oidc::auth_config,
+                oidc::login,
+                oidc::callback,
+                oidc::me,
+                oidc::logout,
+                ip_allowlist::get_ip_allowlist,
+                ip_allowlist::set_ip_allowlist,
+                scoped_tokens::list_scoped_tokens,
+                scoped_tokens::create_scoped_token,
+                scoped_tokens::revoke_scoped_token,
+                view_password::create_view_session,
+                agent_keys::list_agent_keys,
+                agent_keys::create_agent_key,
+                agent_keys::revoke_agent_key,
+                credentials::list_active_keys,
+                merge::merge_boards,
                 // Columns (manage key required)
                 routes::create_column,
                 routes::update_column,
@@ -74,36 +234,136 @@ fn rocket() -> _ {
                 routes::reorder_columns,
                 // Tasks (read = public, write = manage key)
                 routes::create_task,
+                routes::clone_task,
+                github::import_github_issues,
                 routes::search_tasks,
+                routes::sample_tasks,
+                routes::resolve_task_key,
+                routes::renumber_tasks,
                 routes::list_tasks,
                 routes::get_task,
                 routes::update_task,
                 routes::delete_task,
                 routes::archive_task,
+                trash::list_trash,
+                trash::restore_task,
                 routes::unarchive_task,
                 // Batch operations (manage key required)
                 routes::batch_tasks,
+                import::import_tasks_csv,
                 // Agent-first: claim/release/move/reorder (manage key required)
                 routes::claim_task,
                 routes::release_task,
+                routes::list_claims,
                 routes::move_task,
                 routes::reorder_task,
+                // Edit locks (read = public, lock/unlock = manage key)
+                routes::lock_task,
+                routes::get_task_lock,
+                routes::unlock_task,
+                // Whole-board ordering snapshots (read = public, apply = manage key)
+                routes::get_board_layout,
+                routes::apply_board_layout,
                 // Board activity feed (public)
                 routes::get_board_activity,
+                board_events::list_board_events,
+                feed::board_feed,
+                feed::board_activity_atom,
+                calendar::board_calendar,
+                // Full board export (manage key required)
+                export::export_board,
+                // Board analytics (public; view counters = manage key)
+                analytics::activity_heatmap,
+                analytics::aging_report,
+                analytics::suggest_assignee,
+                analytics::board_views,
                 // Task events (read = public) & comments (manage key required)
                 routes::get_task_events,
                 routes::comment_on_task,
+                comment_pressure::collapse_comments,
+                comments::edit_comment,
+                comments::delete_comment,
+                comments::comment_history,
+                watchers::watch_task,
+                watchers::unwatch_task,
+                inbox::get_inbox,
+                inbox::ack_inbox,
+                notifications::list_preferences,
+                notifications::get_preference,
+                notifications::set_preference,
+                email::list_email_subscriptions,
+                email::set_email_subscription,
+                email::delete_email_subscription,
+                telegram::get_telegram,
+                telegram::set_telegram,
+                telegram::delete_telegram,
+                inbound::list_inbound_hooks,
+                inbound::create_inbound_hook,
+                inbound::update_inbound_hook,
+                inbound::delete_inbound_hook,
+                inbound::receive_inbound,
+                comment_pressure::list_transcripts,
+                comment_pressure::get_transcript,
+                attachments::upload_attachment,
+                attachments::list_attachments,
+                attachments::download_attachment,
+                attachments::delete_attachment,
                 // SSE event stream (public)
                 routes::board_event_stream,
+                routes::board_event_poll,
+                websocket::board_event_socket,
+                routes::multiplexed_event_stream,
                 // Task dependencies (read = public, write = manage key)
                 routes::create_dependency,
                 routes::list_dependencies,
                 routes::delete_dependency,
+                hierarchy::list_children,
+                hierarchy::attach_child,
+                hierarchy::detach_child,
+                routes::schedule_dependencies,
                 // Webhooks (manage key required)
                 routes::create_webhook,
                 routes::list_webhooks,
                 routes::update_webhook,
                 routes::delete_webhook,
+                routes::get_webhook_routes,
+                webhook_keys::list_signing_keys,
+                webhook_keys::create_signing_key,
+                webhook_keys::update_signing_key,
+                webhook_keys::delete_signing_key,
+                routes::set_webhook_routes,
+                routes::test_webhook,
+                routes::list_webhook_deliveries,
+                routes::redeliver_webhook_delivery,
+                routes::list_dead_letters,
+                routes::list_board_dead_letters,
+                routes::requeue_dead_letters,
+                routes::redrive_dead_letters,
+                // Write-ahead validation hook (manage key required)
+                validation_hook::get_validation_hook,
+                validation_hook::set_validation_hook,
+                validation_hook::delete_validation_hook,
+                // Actor avatars (read = public, upload = first-come avatar key)
+                actors::get_actor,
+                actors::actor_avatar,
+                actors::upload_actor_avatar,
+                actors::delete_actor_avatar,
+                // Instance admin (ADMIN_KEY required)
+                admin::scrub_pii,
+                admin::apply_workspace_settings,
+                admin::db_stats,
+                admin::client_stats,
+                admin::slow_queries,
+                admin::event_stats,
+                admin::upsert_tag,
+                admin::delete_tag,
+                admin::list_all_boards,
+                admin::force_unarchive_board,
+                admin::force_delete_board,
+                admin::instance_stats,
+                admin::rate_limits,
+                backup::create_backup,
+                backup::restore_backup,
             ],
         );
 
@@ -0,0 +1,292 @@
+//! Merging one board into another: every task moves (with its history,
+//! dependencies and locks) into the target board, then the emptied source is
+//! archived or deleted.
+
+use std::collections::HashMap;
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::Connection;
+
+use crate::access;
+use crate::auth::{Actor, BoardToken};
+use crate::board_events;
+use crate::db::{hash_key, DbPool};
+use crate::event_types;
+use crate::events::EventBus;
+use crate::lifecycle::{self, LifecycleHooks};
+use crate::models::*;
+use crate::routes::{db_error, delete_board_rows, load_task_response, log_event};
+use crate::validation_hook::{self, Gate};
+
+const ARCHIVE: &str = "archive";
+const DELETE: &str = "delete";
+
+fn invalid(error: String, code: &str) -> (Status, Json<ApiError>) {
+    (Status::BadRequest, Json(ApiError {
+        error,
+        code: code.to_string(),
+        status: 400,
+    }))
+}
+
+/// Merge the source board into this one — requires the manage keys of both
+/// (this board's as the bearer token, the source's in the body). This board's
+/// validation hook is asked about every incoming task as a create; one veto
+/// stops the merge.
+#[post("/boards/<board_id>/merge", format = "json", data = "<req>")]
+pub async fn merge_boards(
+    board_id: &str,
+    req: Json<MergeBoardsRequest>,
+    token: BoardToken,
+    actor: Actor,
+    hooks: &State<LifecycleHooks>,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<MergeBoardsResponse>, (Status, Json<ApiError>)> {
+    let req = req.into_inner();
+    let source_id = req.source_board_id.clone();
+    let source_action = req.source_action.clone().unwrap_or_else(|| ARCHIVE.to_string());
+    if source_action != ARCHIVE && source_action != DELETE {
+        return Err(invalid(
+            format!("Unknown source_action '{}'. Use 'archive' or 'delete'", source_action),
+            "INVALID_INPUT",
+        ));
+    }
+    if source_id == board_id {
+        return Err(invalid("A board cannot be merged into itself".to_string(), "INVALID_INPUT"));
+    }
+    let actor = actor.or_body(req.actor_name.as_deref());
+
+    let (board, source, merger, bus) = (board_id.to_owned(), source_id.clone(), actor.clone(), bus.inner().clone());
+    let client = bus.outbound().clone();
+    let (response, snapshot) = validation_hook::run(db, &client, move |tx, gate| {
+        let (board_id, source_id, actor) = (board.as_str(), source.as_str(), &merger);
+        access::require_manage_key(tx, board_id, &hash_key(&token.0))?;
+        access::require_not_archived(tx, board_id)?;
+        access::require_display_name(tx, board_id, actor)?;
+        access::require_manage_key(tx, source_id, &hash_key(&req.source_key))?;
+
+        let mut response = move_tasks(tx, gate, board_id, source_id, &req.column_mapping, actor.name())?;
+        let snapshot = if source_action == DELETE {
+            let snapshot = lifecycle::board_payload(tx, source_id).map_err(|e| db_error(&e.to_string()))?;
+            delete_board_rows(tx, source_id).map_err(|e| db_error(&e.to_string()))?;
+            response.source_action = "deleted".to_string();
+            Some(snapshot)
+        } else {
+            tx.execute(
+                "UPDATE boards SET archived = 1, updated_at = datetime('now') WHERE id = ?1",
+                rusqlite::params![source_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+            response.source_action = "archived".to_string();
+            None
+        };
+        let merged = serde_json::json!({
+            "source_board_id": response.source_board_id,
+            "source_name": response.source_name,
+            "tasks_moved": response.tasks_moved,
+            "columns_created": response.columns_created,
+            "source_action": response.source_action,
+        });
+        board_events::emit(tx, &bus, board_id, event_types::BOARD_MERGED, actor.name(), merged)
+            .map_err(|e| db_error(&e.to_string()))?;
+        if snapshot.is_none() {
+            let archived = serde_json::json!({"name": response.source_name, "merged_into": board_id});
+            board_events::emit(tx, &bus, source_id, event_types::BOARD_ARCHIVED, actor.name(), archived)
+                .map_err(|e| db_error(&e.to_string()))?;
+        }
+        Ok((response, snapshot))
+    })
+    .await?;
+
+    match snapshot {
+        Some(snapshot) => hooks.send(lifecycle::BOARD_DELETED, &source_id, snapshot, Some(actor.name())),
+        None => hooks.notify(db, lifecycle::BOARD_ARCHIVED, &source_id, Some(actor.name())).await,
+    }
+    Ok(Json(response))
+}
+
+/// Resolve a column reference (id, or case-insensitive name) among `columns`.
+fn find_column<'a>(columns: &'a [(String, String)], reference: &str) -> Option<&'a (String, String)> {
+    columns
+        .iter()
+        .find(|(id, _)| id == reference)
+        .or_else(|| columns.iter().find(|(_, name)| name.eq_ignore_ascii_case(reference)))
+}
+
+fn board_columns(conn: &Connection, board_id: &str) -> rusqlite::Result<Vec<(String, String)>> {
+    conn.prepare("SELECT id, name FROM columns WHERE board_id = ?1 ORDER BY position ASC")?
+        .query_map(rusqlite::params![board_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect()
+}
+
+/// Map columns and move every source task into the target board.
+fn move_tasks(
+    tx: &Connection,
+    gate: &Gate,
+    board_id: &str,
+    source_id: &str,
+    mapping: &HashMap<String, String>,
+    actor: &str,
+) -> Result<MergeBoardsResponse, (Status, Json<ApiError>)> {
+    let dbe = |e: rusqlite::Error| db_error(&e.to_string());
+    let source_name: String = tx
+        .query_row("SELECT name FROM boards WHERE id = ?1", rusqlite::params![source_id], |row| row.get(0))
+        .map_err(dbe)?;
+    let source_columns = board_columns(tx, source_id).map_err(dbe)?;
+    let mut target_columns = board_columns(tx, board_id).map_err(dbe)?;
+
+    // Explicit mappings first; every reference must resolve
+    let mut mapped: HashMap<String, String> = HashMap::new();
+    for (from, to) in mapping {
+        let Some((from_id, _)) = find_column(&source_columns, from) else {
+            return Err(invalid(format!("Column '{}' not found on the source board", from), "INVALID_COLUMN"));
+        };
+        let Some((to_id, _)) = find_column(&target_columns, to) else {
+            return Err(invalid(format!("Column '{}' not found on this board", to), "INVALID_COLUMN"));
+        };
+        mapped.insert(from_id.clone(), to_id.clone());
+    }
+
+    // Then by name; columns this board lacks are created once the hook has agreed
+    let mut missing = Vec::new();
+    for (from_id, from_name) in &source_columns {
+        if mapped.contains_key(from_id) {
+            continue;
+        }
+        match find_column(&target_columns, from_name) {
+            Some((id, _)) => {
+                mapped.insert(from_id.clone(), id.clone());
+            }
+            None => missing.push((from_id, from_name)),
+        }
+    }
+
+    // Tasks land after the target column's existing tasks, keeping their order,
+    // and take fresh numbers from this board
+    let tasks: Vec<(String, String)> = tx
+        .prepare(
+            "SELECT t.id, t.column_id FROM tasks t JOIN columns c ON c.id = t.column_id
+             WHERE t.board_id = ?1 ORDER BY c.position ASC, t.position ASC, t.created_at ASC",
+        )
+        .map_err(dbe)?
+        .query_map(rusqlite::params![source_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(dbe)?
+        .collect::<rusqlite::Result<_>>()
+        .map_err(dbe)?;
+    let column_name = |id: &str, columns: &[(String, String)]| {
+        columns.iter().find(|(c, _)| c == id).map(|(_, n)| n.clone()).unwrap_or_default()
+    };
+
+    // New columns have no id yet, so the hook is told where tasks land by name
+    let mut checks = Vec::new();
+    for (task_id, from_column) in &tasks {
+        let to_column = match mapped.get(from_column) {
+            Some(id) => column_name(id, &target_columns),
+            None => column_name(from_column, &source_columns),
+        };
+        checks.push((
+            validation_hook::TASK_CREATE,
+            serde_json::json!({
+                "task": load_task_response(tx, task_id)?.into_inner(),
+                "merged_from_board_id": source_id,
+                "to_column": to_column,
+            }),
+        ));
+    }
+    validation_hook::check_all(tx, gate, board_id, actor, checks)?;
+
+    let mut columns_created = Vec::new();
+    for (from_id, from_name) in missing {
+        let to_id = match find_column(&target_columns, from_name) {
+            Some((id, _)) => id.clone(),
+            None => {
+                let id = uuid::Uuid::new_v4().to_string();
+                tx.execute(
+                    "INSERT INTO columns (id, board_id, name, position)
+                     VALUES (?1, ?2, ?3, (SELECT COALESCE(MAX(position), -1) + 1 FROM columns WHERE board_id = ?2))",
+                    rusqlite::params![id, board_id, from_name],
+                )
+                .map_err(dbe)?;
+                target_columns.push((id.clone(), from_name.clone()));
+                columns_created.push(from_name.clone());
+                id
+            }
+        };
+        mapped.insert(from_id.clone(), to_id);
+    }
+
+    let mut next_position: HashMap<String, i64> = HashMap::new();
+    for (task_id, from_column) in &tasks {
+        let to_column = &mapped[from_column];
+        let position: i64 = match next_position.get(to_column) {
+            Some(p) => *p,
+            None => tx
+                .query_row(
+                    "SELECT COALESCE(MAX(position), -1) + 1 FROM tasks WHERE column_id = ?1",
+                    rusqlite::params![to_column],
+                    |row| row.get(0),
+                )
+                .map_err(dbe)?,
+        };
+        next_position.insert(to_column.clone(), position + 1);
+        tx.execute(
+            "UPDATE tasks SET board_id = ?1, column_id = ?2, position = ?3,
+                    number = (SELECT next_task_number FROM boards WHERE id = ?1), updated_at = datetime('now')
+             WHERE id = ?4",
+            rusqlite::params![board_id, to_column, position, task_id],
+        )
+        .map_err(dbe)?;
+        tx.execute(
+            "UPDATE boards SET next_task_number = next_task_number + 1 WHERE id = ?1",
+            rusqlite::params![board_id],
+        )
+        .map_err(dbe)?;
+        log_event(
+            tx,
+            task_id,
+            "merged",
+            actor,
+            &serde_json::json!({
+                "from_board_id": source_id,
+                "from_board": source_name,
+                "from_column": column_name(from_column, &source_columns),
+                "to_column": column_name(to_column, &target_columns),
+            }),
+        );
+    }
+    let dependencies_moved = tx
+        .execute(
+            "UPDATE task_dependencies SET board_id = ?1 WHERE board_id = ?2",
+            rusqlite::params![board_id, source_id],
+        )
+        .map_err(dbe)?;
+    tx.execute("UPDATE boards SET updated_at = datetime('now') WHERE id = ?1", rusqlite::params![board_id])
+        .map_err(dbe)?;
+
+    let column_mapping = source_columns
+        .iter()
+        .map(|(from_id, from_name)| {
+            let to_id = &mapped[from_id];
+            MergedColumn {
+                from_column_id: from_id.clone(),
+                from_column: from_name.clone(),
+                to_column_id: to_id.clone(),
+                to_column: column_name(to_id, &target_columns),
+            }
+        })
+        .collect();
+
+    Ok(MergeBoardsResponse {
+        board_id: board_id.to_string(),
+        source_board_id: source_id.to_string(),
+        source_name,
+        tasks_moved: tasks.len(),
+        dependencies_moved,
+        columns_created,
+        column_mapping,
+        source_action: String::new(),
+    })
+}
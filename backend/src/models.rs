@@ -1,5 +1,8 @@
 use serde::{Deserialize, Deserializer, Serialize};
 
+use crate::access::{BoardRole, ScopeAction, Visibility};
+use crate::share_links::ShareLinkScope;
+
 /// Deserialize priority from either an integer or a string like "low", "medium", "high", "critical".
 fn deserialize_priority<'de, D>(deserializer: D) -> Result<i32, D::Error>
 where
@@ -8,18 +11,34 @@ where
     let value = serde_json::Value::deserialize(deserializer)?;
     match value {
         serde_json::Value::Number(n) => n.as_i64().map(|v| v as i32).ok_or_else(|| serde::de::Error::custom("invalid number")),
-        serde_json::Value::String(s) => match s.to_lowercase().as_str() {
-            "critical" | "urgent" => Ok(3),
-            "high" => Ok(2),
-            "medium" | "normal" => Ok(1),
-            "low" | "none" => Ok(0),
-            other => other.parse::<i32>().map_err(|_| serde::de::Error::custom(format!("unknown priority: {}", other))),
-        },
+        serde_json::Value::String(s) => parse_priority(&s).map_err(serde::de::Error::custom),
         serde_json::Value::Null => Ok(0),
         _ => Err(serde::de::Error::custom("priority must be a number or string")),
     }
 }
 
+/// Like `deserialize_priority`, but null stays None.
+fn deserialize_optional_priority<'de, D>(deserializer: D) -> Result<Option<i32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Null => Ok(None),
+        value => deserialize_priority(value).map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
+/// Parse a priority name ("low" … "critical") or number.
+pub fn parse_priority(s: &str) -> Result<i32, String> {
+    match s.to_lowercase().as_str() {
+        "critical" | "urgent" => Ok(3),
+        "high" => Ok(2),
+        "medium" | "normal" => Ok(1),
+        "low" | "none" => Ok(0),
+        other => other.parse::<i32>().map_err(|_| format!("unknown priority: {}", other)),
+    }
+}
+
 /// Deserialize a String that accepts null as empty string.
 fn deserialize_string_or_null<'de, D>(deserializer: D) -> Result<String, D::Error>
 where
@@ -29,6 +48,15 @@ where
     Ok(value.unwrap_or_default())
 }
 
+/// With `#[serde(default)]`: None when the field is missing, Some(Null) when
+/// it is explicitly `null`.
+fn deserialize_present<'de, D>(deserializer: D) -> Result<Option<serde_json::Value>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    serde_json::Value::deserialize(deserializer).map(Some)
+}
+
 // ============ Boards ============
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -42,9 +70,18 @@ pub struct CreateBoardRequest {
     /// Optional: make the board publicly listed (default: false = unlisted)
     #[serde(default)]
     pub is_public: bool,
+    /// public, unlisted or private (needs a key to read); overrides is_public
+    #[serde(default)]
+    pub visibility: Option<Visibility>,
+    /// Readers without a key must trade this for a view token first
+    #[serde(default, skip_serializing)]
+    pub view_password: Option<String>,
     /// Require display name on tasks and comments (default: false = allow anonymous)
     #[serde(default)]
     pub require_display_name: bool,
+    /// Refuse to move tasks into the done column while blockers are unfinished (default: false)
+    #[serde(default)]
+    pub enforce_dependencies: bool,
 }
 
 /// Update board settings (all fields optional).
@@ -53,11 +90,30 @@ pub struct UpdateBoardRequest {
     pub name: Option<String>,
     pub description: Option<String>,
     pub is_public: Option<bool>,
+    /// public, unlisted or private; overrides is_public
+    pub visibility: Option<Visibility>,
+    /// Set the view password (empty string removes it)
+    pub view_password: Option<String>,
     pub require_display_name: Option<bool>,
+    pub enforce_dependencies: Option<bool>,
     pub quick_done_column_id: Option<String>,
     pub quick_done_auto_archive: Option<bool>,
     pub quick_reassign_column_id: Option<String>,
     pub quick_reassign_to: Option<String>,
+    /// Purge archived tasks this many days after archiving (0 = never)
+    pub archived_task_retention_days: Option<i64>,
+    /// Prune activity events (not comments) older than this many days (0 = never)
+    pub event_retention_days: Option<i64>,
+    /// Prefix for task keys, e.g. "OPS-" (empty string restores the default "#")
+    pub task_prefix: Option<String>,
+    /// Return the existing task when the same creator posts the same title and
+    /// description again within this many seconds (0 = off)
+    pub duplicate_window_seconds: Option<i64>,
+    /// Past this many comments a task carries a `comment_pressure` hint
+    /// suggesting consolidation (0 = off)
+    pub comment_pressure_threshold: Option<i64>,
+    /// Replace the board's tags (each must be in the tag directory; [] clears)
+    pub tags: Option<Vec<String>>,
 }
 
 /// Returned when creating a board. Includes the manage_key (shown only once).
@@ -83,15 +139,114 @@ pub struct BoardResponse {
     pub task_count: usize,
     pub archived: bool,
     pub is_public: bool,
+    pub visibility: Visibility,
+    /// Reads without a key need a view token (`POST /boards/{id}/view-session`)
+    pub password_protected: bool,
     pub require_display_name: bool,
+    pub enforce_dependencies: bool,
     pub quick_done_column_id: Option<String>,
     pub quick_done_auto_archive: bool,
     pub quick_reassign_column_id: Option<String>,
     pub quick_reassign_to: Option<String>,
+    pub archived_task_retention_days: Option<i64>,
+    pub event_retention_days: Option<i64>,
+    pub task_prefix: Option<String>,
+    pub next_task_number: i64,
+    pub duplicate_window_seconds: Option<i64>,
+    pub comment_pressure_threshold: Option<i64>,
+    pub tags: Vec<String>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+/// Whether the web UI can offer sign-in (`GET /auth/config`).
+#[derive(Debug, Serialize)]
+pub struct AuthConfigResponse {
+    pub enabled: bool,
+    pub login_url: Option<String>,
+}
+
+/// The signed-in user (`GET /auth/me`).
+#[derive(Debug, Serialize)]
+pub struct SessionUserResponse {
+    pub id: String,
+    pub name: String,
+    pub email: Option<String>,
+    /// Boards created while signed in, newest first
+    pub boards: Vec<OwnedBoard>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OwnedBoard {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SetIpAllowlistRequest {
+    /// IP addresses or CIDR ranges; empty lifts the restriction
+    pub ranges: Vec<String>,
+}
+
+/// The ranges a board's writes must come from (`/boards/{id}/ip-allowlist`).
+#[derive(Debug, Serialize)]
+pub struct IpAllowlistResponse {
+    pub board_id: String,
+    pub ranges: Vec<String>,
+    /// The caller's address as the server sees it
+    pub client_ip: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateShareLinkRequest {
+    #[serde(default)]
+    pub scope: ShareLinkScope,
+    /// When the link stops working (RFC 3339); or use ttl_seconds
+    #[serde(default)]
+    pub expires_at: Option<String>,
+    /// Seconds until the link stops working (default 7 days)
+    #[serde(default)]
+    pub ttl_seconds: Option<i64>,
+}
+
+/// A signed share link; the token can't be looked up again.
+#[derive(Debug, Serialize)]
+pub struct ShareLinkResponse {
+    pub board_id: String,
+    pub scope: ShareLinkScope,
+    /// Board page URL with the token as `?key=`
+    pub url: String,
+    pub token: String,
+    pub expires_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ViewSessionRequest {
+    pub password: String,
+}
+
+/// A view token for a password-protected board.
+#[derive(Debug, Serialize)]
+pub struct ViewSessionResponse {
+    pub board_id: String,
+    /// Send as a key (`Authorization: Bearer`, `X-API-Key` or `?key=`), or
+    /// rely on the `kb_view` cookie
+    pub token: String,
+    pub expires_at: String,
+    pub expires_in_seconds: i64,
+}
+
+/// A freshly rotated manage key — shown once, like the original.
+#[derive(Debug, Serialize)]
+pub struct RotateKeyResponse {
+    pub board_id: String,
+    pub manage_key: String,
+    pub manage_url: String,
+    pub rotated_at: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct BoardSummary {
     pub id: String,
@@ -100,6 +255,7 @@ pub struct BoardSummary {
     pub task_count: i64,
     pub archived: bool,
     pub is_public: bool,
+    pub tags: Vec<String>,
     pub created_at: String,
 }
 
@@ -112,6 +268,10 @@ pub struct ColumnResponse {
     pub position: i32,
     pub wip_limit: Option<i32>,
     pub task_count: i64,
+    /// Agents that tasks entering this column are assigned to (empty = off)
+    pub auto_assign: Vec<String>,
+    /// "round_robin" or "least_loaded" (fewest open claims)
+    pub auto_assign_strategy: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -119,12 +279,18 @@ pub struct CreateColumnRequest {
     pub name: String,
     pub position: Option<i32>,
     pub wip_limit: Option<i32>,
+    #[serde(default)]
+    pub auto_assign: Vec<String>,
+    pub auto_assign_strategy: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct UpdateColumnRequest {
     pub name: Option<String>,
     pub wip_limit: Option<Option<i32>>,
+    /// Replace the auto-assignment pool; an empty list turns it off
+    pub auto_assign: Option<Vec<String>>,
+    pub auto_assign_strategy: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -135,7 +301,7 @@ pub struct ReorderColumnsRequest {
 
 // ============ Tasks ============
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct CreateTaskRequest {
     #[serde(default, deserialize_with = "deserialize_string_or_null")]
     pub title: String,
@@ -154,12 +320,37 @@ pub struct CreateTaskRequest {
     #[serde(default = "default_metadata")]
     pub metadata: serde_json::Value,
     pub due_at: Option<String>,
+    /// Make the new task a child of this task (same board)
+    pub parent_task_id: Option<String>,
     /// Optional: identify who created this task (free text, e.g. "nanook", "jordan")
     #[serde(default, deserialize_with = "deserialize_string_or_null")]
     pub actor_name: String,
 }
 
-#[derive(Debug, Deserialize)]
+/// Clone a task. Every field is an optional override of the source task's value.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CloneTaskRequest {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Defaults to the source task's column
+    pub column_id: Option<String>,
+    pub priority: Option<i32>,
+    pub assigned_to: Option<String>,
+    pub labels: Option<Vec<String>>,
+    pub metadata: Option<serde_json::Value>,
+    pub due_at: Option<String>,
+    /// Copy the source labels when `labels` isn't given (default: true)
+    #[serde(default = "default_true")]
+    pub include_labels: bool,
+    /// Copy the source metadata (checklists etc.) when `metadata` isn't given (default: true)
+    #[serde(default = "default_true")]
+    pub include_metadata: bool,
+    /// Who is cloning the task
+    #[serde(default, deserialize_with = "deserialize_string_or_null")]
+    pub actor_name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct UpdateTaskRequest {
     pub title: Option<String>,
     pub description: Option<String>,
@@ -174,7 +365,7 @@ pub struct UpdateTaskRequest {
     pub actor_name: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct ReorderTaskRequest {
     /// New position (0-indexed). Tasks at and after this position shift down.
     pub position: i32,
@@ -182,6 +373,35 @@ pub struct ReorderTaskRequest {
     pub column_id: Option<String>,
 }
 
+/// Whole-board ordering snapshot, applied atomically.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApplyLayoutRequest {
+    /// `version` from the layout the client last saw; stale versions get 409
+    pub version: String,
+    /// column_id → ordered task ids. Tasks not listed keep their column and
+    /// follow the listed ones; columns not listed keep their order.
+    pub columns: std::collections::HashMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LayoutColumn {
+    pub column_id: String,
+    pub name: String,
+    pub task_ids: Vec<String>,
+}
+
+/// Current task ordering for every column (archived tasks excluded).
+#[derive(Debug, Serialize)]
+pub struct BoardLayoutResponse {
+    pub board_id: String,
+    /// Opaque token that changes whenever any task's column or position changes
+    pub version: String,
+    pub columns: Vec<LayoutColumn>,
+    /// Tasks whose column or position changed (only set when applying)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changed: Option<usize>,
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct TaskResponse {
     pub id: String,
@@ -204,198 +424,1877 @@ pub struct TaskResponse {
     pub created_at: String,
     pub updated_at: String,
     pub comment_count: i64,
+    pub attachment_count: i64,
+    /// Names watching the task, in the order they started
+    pub watchers: Vec<String>,
+    /// Short per-board number
+    pub number: Option<i64>,
+    /// Number with the board's prefix, e.g. "OPS-12" (default prefix "#")
+    pub key: Option<String>,
+    /// Set on create when the request matched a task created moments earlier
+    /// (see the board's `duplicate_window_seconds`); that task is returned
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate: Option<bool>,
+    pub parent_task_id: Option<String>,
+    /// Roll-up over child tasks; only present on tasks that have children
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<TaskProgress>,
+    /// Present once the task has more comments than the board's
+    /// `comment_pressure_threshold`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment_pressure: Option<CommentPressure>,
 }
 
 #[derive(Debug, Serialize)]
-pub struct TaskEventResponse {
-    pub id: String,
-    pub event_type: String,
-    pub actor: String,
-    pub data: serde_json::Value,
-    pub created_at: String,
+pub struct WatchersResponse {
+    pub task_id: String,
+    pub watchers: Vec<String>,
 }
 
+/// A comment that @mentions an inbox's name.
 #[derive(Debug, Serialize)]
-pub struct BoardActivityItem {
-    pub id: String,
+pub struct InboxItem {
+    pub seq: i64,
+    pub event_id: String,
     pub task_id: String,
     pub task_title: String,
-    pub event_type: String,
-    pub actor: String,
-    pub data: serde_json::Value,
-    pub created_at: String,
-    /// Monotonic sequence number for cursor-based pagination (use `?after=<seq>`)
-    pub seq: i64,
-    /// Full task snapshot — included on `created` and `comment` events only.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub task: Option<TaskResponse>,
-    /// Recent comments (newest first, up to 10) — included on `comment` events only.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub recent_comments: Option<Vec<CommentSnapshot>>,
-    /// @mentions extracted from comment text. Present on `comment` events.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub mentions: Option<Vec<String>>,
-}
-
-/// Lightweight comment representation for activity feed enrichment.
-#[derive(Debug, Serialize, Clone)]
-pub struct CommentSnapshot {
-    pub id: String,
     pub actor: String,
     pub message: String,
     pub created_at: String,
 }
 
-// ============ Search ============
+#[derive(Debug, Serialize)]
+pub struct InboxResponse {
+    pub name: String,
+    /// Highest `seq` acknowledged so far (0 = nothing read yet)
+    pub cursor: i64,
+    pub unread: i64,
+    /// The name's notification level (see notification preferences)
+    pub level: String,
+    pub items: Vec<InboxItem>,
+}
 
 #[derive(Debug, Serialize)]
-pub struct SearchResponse {
-    pub query: String,
-    pub tasks: Vec<TaskResponse>,
-    pub total: i64,
-    pub limit: i64,
-    pub offset: i64,
+pub struct InboxCursorResponse {
+    pub name: String,
+    pub cursor: i64,
+    pub unread: i64,
 }
 
-// ============ Batch Operations ============
+#[derive(Debug, Serialize)]
+pub struct NotificationPreferenceResponse {
+    pub board_id: String,
+    pub name: String,
+    /// all | mentions | assigned | mute
+    pub level: String,
+    /// Null while the name is on the default
+    pub updated_at: Option<String>,
+}
 
 #[derive(Debug, Deserialize)]
-pub struct BatchRequest {
-    /// List of operations to perform. Max 50 per request.
-    pub operations: Vec<BatchOperation>,
-    /// Optional actor name for attribution (defaults to "batch" if not provided).
-    #[serde(default)]
-    pub actor_name: Option<String>,
+#[serde(deny_unknown_fields)]
+pub struct SetNotificationPreferenceRequest {
+    pub level: String,
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(tag = "action")]
-pub enum BatchOperation {
-    /// Move tasks to a different column
-    #[serde(rename = "move")]
-    Move {
-        task_ids: Vec<String>,
-        column_id: String,
-    },
-    /// Update fields on multiple tasks
-    #[serde(rename = "update")]
-    Update {
-        task_ids: Vec<String>,
-        #[serde(flatten)]
-        fields: BatchUpdateFields,
-    },
-    /// Delete multiple tasks
-    #[serde(rename = "delete")]
-    Delete { task_ids: Vec<String> },
+#[derive(Debug, Serialize)]
+pub struct EmailSubscriptionResponse {
+    pub board_id: String,
+    pub name: String,
+    pub email: String,
+    pub created_at: String,
+    pub updated_at: String,
 }
 
 #[derive(Debug, Deserialize)]
-pub struct BatchUpdateFields {
-    pub priority: Option<i32>,
-    pub assigned_to: Option<String>,
-    pub labels: Option<Vec<String>>,
-    pub due_at: Option<String>,
+#[serde(deny_unknown_fields)]
+pub struct SetEmailSubscriptionRequest {
+    pub email: String,
 }
 
-#[derive(Debug, Serialize)]
-pub struct BatchResponse {
-    /// Total operations submitted
-    pub total: usize,
-    /// Successfully completed
-    pub succeeded: usize,
-    /// Failed operations
-    pub failed: usize,
-    /// Per-operation results
-    pub results: Vec<BatchOperationResult>,
+/// Children done (completed) out of total.
+#[derive(Debug, Serialize, Clone)]
+pub struct TaskProgress {
+    pub done: i64,
+    pub total: i64,
+    pub percent: u8,
 }
 
 #[derive(Debug, Serialize)]
-pub struct BatchOperationResult {
-    pub action: String,
-    pub task_ids: Vec<String>,
-    pub success: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub error: Option<String>,
-    /// Number of tasks affected in this operation
-    pub affected: usize,
+pub struct TaskChildrenResponse {
+    pub task_id: String,
+    pub progress: TaskProgress,
+    pub children: Vec<TaskResponse>,
 }
 
-// ============ Webhooks ============
+/// A board as a JSON Feed 1.1 document (https://jsonfeed.org/version/1.1).
+#[derive(Debug, Serialize)]
+pub struct JsonFeed {
+    pub version: String,
+    pub title: String,
+    pub home_page_url: String,
+    pub feed_url: String,
+    pub description: String,
+    pub items: Vec<JsonFeedItem>,
+}
 
-#[derive(Debug, Deserialize)]
-pub struct CreateWebhookRequest {
-    /// URL to POST events to (must be HTTPS in production)
+#[derive(Debug, Serialize)]
+pub struct JsonFeedItem {
+    /// "<task id>:created" or "<task id>:completed"
+    pub id: String,
     pub url: String,
-    /// Optional filter: list of event types to subscribe to.
-    /// If empty, all events are delivered.
-    #[serde(default)]
-    pub events: Vec<String>,
+    pub title: String,
+    pub content_text: String,
+    pub date_published: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<JsonFeedAuthor>,
+    /// Board-specific fields; `schema_version` changes only on breaking changes
+    #[serde(rename = "_kanban")]
+    pub kanban: JsonFeedKanban,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct UpdateWebhookRequest {
-    pub url: Option<String>,
-    pub events: Option<Vec<String>>,
-    pub active: Option<bool>,
+#[derive(Debug, Serialize)]
+pub struct JsonFeedAuthor {
+    pub name: String,
 }
 
 #[derive(Debug, Serialize)]
-pub struct WebhookResponse {
-    pub id: String,
-    pub board_id: String,
-    pub url: String,
-    /// Only returned on creation
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub secret: Option<String>,
-    pub events: Vec<String>,
-    pub active: bool,
-    pub failure_count: i32,
-    pub last_triggered_at: Option<String>,
-    pub created_at: String,
+pub struct JsonFeedKanban {
+    pub schema_version: u32,
+    /// "created" or "completed"
+    pub event: String,
+    pub task_id: String,
+    pub task_key: Option<String>,
+    pub column: String,
+    pub priority: i32,
+    pub assigned_to: Option<String>,
+    pub completed_at: Option<String>,
 }
 
-// ============ Task Dependencies ============
-
 #[derive(Debug, Deserialize)]
-pub struct CreateDependencyRequest {
-    /// The task that blocks (must be completed first)
-    pub blocker_task_id: String,
-    /// The task that is blocked (cannot proceed until blocker is done)
-    pub blocked_task_id: String,
-    /// Optional note explaining the dependency
-    #[serde(default)]
-    pub note: String,
+pub struct AttachChildRequest {
+    pub child_task_id: String,
 }
 
 #[derive(Debug, Serialize)]
-pub struct DependencyResponse {
+pub struct TaskEventResponse {
     pub id: String,
-    pub board_id: String,
-    pub blocker_task_id: String,
-    pub blocker_title: String,
-    pub blocker_column: String,
-    pub blocker_completed: bool,
-    pub blocked_task_id: String,
-    pub blocked_title: String,
+    pub event_type: String,
+    pub actor: String,
+    pub data: serde_json::Value,
+    pub created_at: String,
+    /// Set on a posted comment that leaves the task over the board's threshold
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment_pressure: Option<CommentPressure>,
+}
+
+/// Hint that a task's comment thread should be consolidated.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommentPressure {
+    pub comment_count: i64,
+    pub threshold: i64,
+    pub hint: String,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct CollapseCommentsRequest {
+    /// Newest comments left on the task (default 5)
+    #[serde(default)]
+    pub keep: Option<usize>,
+    #[serde(default)]
+    pub actor_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EditCommentRequest {
+    pub message: String,
+    #[serde(default)]
+    pub actor_name: Option<String>,
+}
+
+/// A comment's text before one edit (or before it was deleted).
+#[derive(Debug, Serialize)]
+pub struct CommentRevision {
+    pub message: String,
+    /// `edited` or `deleted`
+    pub change: String,
+    pub changed_by: String,
+    pub changed_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommentHistoryResponse {
+    pub comment_id: String,
+    pub task_id: String,
+    /// Current text; null once the comment is deleted
+    pub message: Option<String>,
+    pub deleted: bool,
+    /// Oldest first
+    pub revisions: Vec<CommentRevision>,
+}
+
+/// Older comments collapsed into a plain-text transcript.
+#[derive(Debug, Serialize)]
+pub struct CommentTranscriptResponse {
+    pub id: String,
+    pub task_id: String,
+    pub comment_count: i64,
+    pub first_comment_at: String,
+    pub last_comment_at: String,
+    pub created_by: String,
+    pub created_at: String,
+    /// Where the transcript text can be downloaded
+    pub url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CollapseCommentsResponse {
+    pub task_id: String,
+    pub collapsed: i64,
+    /// Comments still on the task
+    pub remaining: i64,
+    pub transcript: CommentTranscriptResponse,
+}
+
+// ============ Attachments ============
+
+#[derive(Debug, Serialize)]
+pub struct AttachmentResponse {
+    pub id: String,
+    pub task_id: String,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    /// Hex SHA-256 of the file contents
+    pub sha256: String,
+    pub uploaded_by: String,
+    pub created_at: String,
+    /// Where the file can be downloaded
+    pub url: String,
+}
+
+/// Periodic SSE `heartbeat` payload: enough state for a dashboard to notice
+/// it has drifted from the server and refetch.
+#[derive(Debug, Serialize)]
+pub struct StreamHeartbeat {
+    /// Highest activity `seq` on the board (0 if none)
+    pub seq: i64,
+    pub task_count: i64,
+    pub columns: Vec<ColumnTaskCount>,
+    pub server_time: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ColumnTaskCount {
+    pub id: String,
+    pub task_count: i64,
+}
+
+/// Event bus buffer size and subscriber lag (`GET /admin/event-stats`).
+#[derive(Debug, Serialize)]
+pub struct EventBusStats {
+    /// Events buffered per board (`EVENT_BUFFER_SIZE`)
+    pub capacity: usize,
+    /// Boards with a channel since the server started, most events lost first
+    pub boards: Vec<BoardChannelStats>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BoardChannelStats {
+    pub board_id: String,
+    pub events_emitted: u64,
+    /// Recent events held for long-polling
+    pub buffered: usize,
+    /// Events missed by any subscriber, including ones since disconnected
+    pub events_lost: u64,
+    pub subscriber_count: usize,
+    pub subscribers: Vec<SubscriberLag>,
+}
+
+/// A board in the instance-wide listing (`GET /admin/boards`).
+#[derive(Debug, Serialize)]
+pub struct AdminBoardSummary {
+    pub id: String,
+    pub name: String,
+    pub visibility: Visibility,
+    pub archived: bool,
+    pub task_count: i64,
+    /// Collaborator, share, scoped and agent keys handed out
+    pub key_count: i64,
+    pub created_by: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Instance-wide counts (`GET /admin/stats`).
+#[derive(Debug, Serialize)]
+pub struct InstanceStatsResponse {
+    pub boards: InstanceBoardStats,
+    pub tasks: InstanceTaskStats,
+    pub webhooks: i64,
+    pub collaborator_keys: i64,
+    /// Boards and tasks created in the last 24 hours
+    pub boards_created_24h: i64,
+    pub tasks_created_24h: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InstanceBoardStats {
+    pub total: i64,
+    pub public: i64,
+    pub unlisted: i64,
+    pub private: i64,
+    pub archived: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InstanceTaskStats {
+    pub total: i64,
+    /// Not completed and not archived
+    pub open: i64,
+    pub completed: i64,
+    pub archived: i64,
+    /// Soft-deleted, waiting in the trash
+    pub in_trash: i64,
+}
+
+/// Rate limiter state (`GET /admin/rate-limits`).
+#[derive(Debug, Serialize)]
+pub struct RateLimitsResponse {
+    /// Boards created per client IP
+    pub board_creation: RateLimiterStats,
+    /// Task writes per key (clients are key hashes)
+    pub task_writes: RateLimiterStats,
+    /// Comments per key
+    pub comments: RateLimiterStats,
+    /// Batch calls per key
+    pub batch: RateLimiterStats,
+    /// View password attempts per client IP
+    pub view_session: RateLimiterStats,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RateLimiterStats {
+    pub window_seconds: u64,
+    pub limit: u64,
+    /// Checks refused since startup because the counter store was unavailable
+    pub store_errors: u64,
+    /// Clients with requests in their current window, busiest first
+    pub clients: Vec<RateLimitBucket>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RateLimitBucket {
+    /// What the limit is keyed by (the client IP, or the hash of a key for writes)
+    pub key: String,
+    pub count: u64,
+    pub remaining: u64,
+    pub reset_secs: u64,
+    /// At the limit until the window resets
+    pub limited: bool,
+}
+
+/// One open stream's lag.
+#[derive(Debug, Serialize)]
+pub struct SubscriberLag {
+    /// `sse`, `sse-multi` or `websocket`
+    pub kind: String,
+    pub connected_at: String,
+    /// Times it fell behind (each got a `warning`)
+    pub lag_count: u64,
+    pub events_lost: u64,
+}
+
+/// Response from `GET /boards/{id}/events/poll`.
+#[derive(Debug, Serialize)]
+pub struct EventPollResponse {
+    pub events: Vec<crate::events::Envelope>,
+    /// Pass as `after` on the next poll
+    pub cursor: u64,
+    /// Events after `after` were missed: the buffer overflowed or the server restarted
+    pub events_lost: bool,
+}
+
+/// A board- or column-level event from `GET /boards/{id}/board-events`.
+#[derive(Debug, Serialize)]
+pub struct BoardEventItem {
+    /// Cursor for `?after=`
+    pub seq: i64,
+    pub event: String,
+    pub actor: String,
+    pub data: serde_json::Value,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BoardActivityItem {
+    pub id: String,
+    pub task_id: String,
+    pub task_title: String,
+    pub event_type: String,
+    pub actor: String,
+    pub data: serde_json::Value,
+    pub created_at: String,
+    /// Monotonic sequence number for cursor-based pagination (use `?after=<seq>`)
+    pub seq: i64,
+    /// Full task snapshot — included on `created` and `comment` events only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<TaskResponse>,
+    /// Recent comments (newest first, up to 10) — included on `comment` events only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recent_comments: Option<Vec<CommentSnapshot>>,
+    /// @mentions extracted from comment text. Present on `comment` events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mentions: Option<Vec<String>>,
+}
+
+/// Lightweight comment representation for activity feed enrichment.
+#[derive(Debug, Serialize, Clone)]
+pub struct CommentSnapshot {
+    pub id: String,
+    pub actor: String,
+    pub message: String,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<CommentAttachment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edited_at: Option<String>,
+}
+
+/// An attachment referenced by a comment, as it was when the comment was
+/// posted (stored in the comment's event data).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CommentAttachment {
+    pub id: String,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub url: String,
+}
+
+/// An active edit lock on a task.
+#[derive(Debug, Serialize)]
+pub struct TaskLockResponse {
+    pub task_id: String,
+    pub locked_by: String,
+    pub expires_at: String,
+    pub ttl_seconds: i64,
+}
+
+/// Claimed, in-flight tasks on a board, grouped by the agent holding them.
+#[derive(Debug, Serialize)]
+pub struct ClaimsResponse {
+    pub board_id: String,
+    pub group_by: String,
+    pub total_claimed: usize,
+    pub agents: usize,
+    /// Largest group first
+    pub groups: Vec<AgentClaims>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AgentClaims {
+    pub agent: String,
+    pub count: usize,
+    pub oldest_claim_age_seconds: i64,
+    /// Oldest claim first
+    pub tasks: Vec<ClaimedTask>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClaimedTask {
+    pub task_id: String,
+    pub title: String,
+    pub column_id: String,
+    pub column_name: String,
+    pub priority: i32,
+    pub claimed_at: String,
+    pub claim_age_seconds: i64,
+}
+
+// ============ Search ============
+
+#[derive(Debug, Serialize)]
+pub struct SearchResponse {
+    pub query: String,
+    pub tasks: Vec<TaskResponse>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// Re-sequence a board's task numbers (all fields optional).
+#[derive(Debug, Deserialize)]
+pub struct RenumberTasksRequest {
+    /// First number to hand out (default 1)
+    pub start: Option<i64>,
+    /// "created" (default) or "position" (column order, then position)
+    pub order: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RenumberTasksResponse {
+    pub board_id: String,
+    pub renumbered: usize,
+    /// Number the next new task will get
+    pub next_number: i64,
+    /// Previous keys recorded so old references still resolve
+    pub aliases_recorded: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SampleResponse {
+    /// Sample size requested (after clamping to 1..=50)
+    pub requested: i64,
+    /// Total tasks matching the filters
+    pub matched: i64,
+    /// Randomly chosen tasks (at most `requested`)
+    pub tasks: Vec<TaskResponse>,
+}
+
+// ============ Batch Operations ============
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchRequest {
+    /// List of operations to perform. Max 50 per request.
+    pub operations: Vec<BatchOperation>,
+    /// Optional actor name for attribution (defaults to "batch" if not provided).
+    #[serde(default)]
+    pub actor_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "action")]
+pub enum BatchOperation {
+    /// Move tasks to a different column
+    #[serde(rename = "move")]
+    Move {
+        task_ids: Vec<String>,
+        column_id: String,
+    },
+    /// Update fields on multiple tasks
+    #[serde(rename = "update")]
+    Update {
+        task_ids: Vec<String>,
+        #[serde(flatten)]
+        fields: BatchUpdateFields,
+    },
+    /// Delete multiple tasks
+    #[serde(rename = "delete")]
+    Delete { task_ids: Vec<String> },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchUpdateFields {
+    pub priority: Option<i32>,
+    pub assigned_to: Option<String>,
+    pub labels: Option<Vec<String>>,
+    pub due_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchResponse {
+    /// Total operations submitted
+    pub total: usize,
+    /// Successfully completed
+    pub succeeded: usize,
+    /// Failed operations
+    pub failed: usize,
+    /// Per-operation results
+    pub results: Vec<BatchOperationResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchOperationResult {
+    pub action: String,
+    pub task_ids: Vec<String>,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Number of tasks affected in this operation
+    pub affected: usize,
+}
+
+/// Outcome of `POST /boards/<id>/tasks/import` (CSV).
+#[derive(Debug, Serialize)]
+pub struct CsvImportResponse {
+    /// Data rows read (header excluded)
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<CsvRowResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CsvRowResult {
+    /// Line in the file, counting the header as line 1
+    pub line: u64,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+// ============ Trash ============
+
+#[derive(Debug, Serialize)]
+pub struct TrashedTask {
+    pub id: String,
+    pub title: String,
+    pub column_id: String,
+    pub deleted_by: String,
+    pub deleted_at: String,
+    /// When the purge job removes it for good; null if trash is kept forever
+    pub purge_at: Option<String>,
+    /// The task row as it was when deleted
+    pub task: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrashResponse {
+    pub board_id: String,
+    /// None = deleted tasks are never purged
+    pub retention_days: Option<u64>,
+    pub tasks: Vec<TrashedTask>,
+}
+
+// ============ Webhooks ============
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookRequest {
+    /// URL to POST events to (must be HTTPS in production)
+    pub url: String,
+    /// Optional filter: list of event types to subscribe to.
+    /// If empty, all events are delivered.
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// `json` (default): the signed event as JSON. `slack`: a Slack
+    /// incoming-webhook URL, sent a Block Kit message per task event.
+    #[serde(default)]
+    pub kind: Option<String>,
+    /// JSON sent instead of the event, with `{{path}}` placeholders
+    #[serde(default)]
+    pub template: Option<serde_json::Value>,
+    /// Extra headers sent with every delivery; stored encrypted
+    #[serde(default)]
+    pub headers: Option<std::collections::BTreeMap<String, String>>,
+    /// Only deliver events about tasks that match
+    #[serde(default)]
+    pub filters: Option<WebhookFilters>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateWebhookRequest {
+    pub url: Option<String>,
+    pub events: Option<Vec<String>>,
+    pub active: Option<bool>,
+    pub kind: Option<String>,
+    /// A new template; `null` removes it
+    #[serde(default, deserialize_with = "deserialize_present")]
+    pub template: Option<serde_json::Value>,
+    /// Replaces all custom headers; `{}` removes them
+    pub headers: Option<std::collections::BTreeMap<String, String>>,
+    /// Replaces the task filters; `{}` removes them
+    pub filters: Option<WebhookFilters>,
+}
+
+/// Narrow a webhook to events about certain tasks. Every filter that is set
+/// must match; events about no task don't pass once any is set.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookFilters {
+    /// Tasks with at least one of these labels
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    /// Tasks in this column
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column_id: Option<String>,
+    /// Tasks at or above this priority (number or name)
+    #[serde(default, deserialize_with = "deserialize_optional_priority", skip_serializing_if = "Option::is_none")]
+    pub min_priority: Option<i32>,
+}
+
+impl WebhookFilters {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookResponse {
+    pub id: String,
+    pub board_id: String,
+    pub url: String,
+    pub kind: String,
+    /// Only returned on creation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub events: Vec<String>,
+    /// Payload template, if the webhook has one
+    pub template: Option<serde_json::Value>,
+    /// Names of the custom headers (values are never returned)
+    pub header_names: Vec<String>,
+    pub filters: WebhookFilters,
+    pub active: bool,
+    pub failure_count: i32,
+    pub last_triggered_at: Option<String>,
+    pub created_at: String,
+}
+
+/// A payload that failed every delivery attempt.
+#[derive(Debug, Serialize)]
+pub struct DeadLetterResponse {
+    pub id: String,
+    pub webhook_id: String,
+    pub event: String,
+    /// The exact body that was (and will be re-)sent
+    pub payload: serde_json::Value,
+    pub attempts: i64,
+    pub last_error: String,
+    pub created_at: String,
+}
+
+/// One attempt to deliver a payload to a webhook.
+#[derive(Debug, Serialize)]
+pub struct WebhookDeliveryResponse {
+    pub id: String,
+    pub webhook_id: String,
+    pub event: String,
+    /// 1 for the first try, counting up through retries and re-drives
+    pub attempt: i64,
+    pub success: bool,
+    /// None when no response arrived
+    pub status_code: Option<i64>,
+    pub duration_ms: i64,
+    /// The first 1 KB of the response
+    pub response_body: String,
+    pub error: Option<String>,
+    pub payload: serde_json::Value,
+    pub created_at: String,
+}
+
+/// What the target answered to a test delivery.
+#[derive(Debug, Serialize)]
+pub struct WebhookTestResponse {
+    pub success: bool,
+    /// None when no response arrived
+    pub status_code: Option<u16>,
+    pub duration_ms: i64,
+    /// The first 1 KB of the response
+    pub response_body: String,
+    pub error: Option<String>,
+    /// The body that was sent
+    pub payload: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct RedriveRequest {
+    /// Dead letters to re-send; omit to re-drive all of them, oldest first
+    #[serde(default)]
+    pub ids: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RedriveReport {
+    pub attempted: usize,
+    pub delivered: usize,
+    pub failed: usize,
+    /// Dead letters still queued for this webhook afterwards
+    pub remaining: i64,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct RequeueRequest {
+    /// Dead letters to requeue; omit for all of the board's (or the webhook's)
+    #[serde(default)]
+    pub ids: Option<Vec<String>>,
+    /// Only this webhook's dead letters
+    #[serde(default)]
+    pub webhook_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RequeueReport {
+    pub requeued: usize,
+    /// Webhooks the requeued letters belong to; their failure counts are reset
+    pub webhook_ids: Vec<String>,
+    /// Dead letters still on the board afterwards
+    pub remaining: i64,
+}
+
+/// Replaces the board's whole routing table.
+/// Add a signing key to a webhook. Times are RFC 3339; `active_from`
+/// defaults to now and `active_until` to never.
+#[derive(Debug, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct CreateSigningKeyRequest {
+    #[serde(default)]
+    pub active_from: Option<String>,
+    #[serde(default)]
+    pub active_until: Option<String>,
+}
+
+/// Schedule (or, with null, cancel) a signing key's retirement.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateSigningKeyRequest {
+    #[serde(default)]
+    pub active_until: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SigningKeyResponse {
+    /// `default` for the webhook's own secret
+    pub id: String,
+    pub webhook_id: String,
+    /// Only returned when the key is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub active_from: String,
+    pub active_until: Option<String>,
+    /// `pending`, `active` or `expired`
+    pub status: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetWebhookRoutesRequest {
+    pub routes: Vec<WebhookRouteInput>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookRouteInput {
+    pub column_id: String,
+    /// Webhooks notified when a task enters the column; [] removes the route
+    pub webhook_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookRoute {
+    pub column_id: String,
+    pub column_name: String,
+    pub webhook_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookRoutesResponse {
+    pub board_id: String,
+    /// In column order; columns without a route are omitted
+    pub routes: Vec<WebhookRoute>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SetTelegramRequest {
+    /// From @BotFather, e.g. `123456789:AAE…`
+    pub bot_token: String,
+    /// Numeric chat ID, or `@channelusername`
+    pub chat_id: String,
+    /// Event types to send; omit to keep the current filter ([] = all)
+    #[serde(default)]
+    pub events: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TelegramResponse {
+    pub board_id: String,
+    /// The webhook carrying the deliveries (dead letters, redrive)
+    pub webhook_id: String,
+    /// `sendMessage` URL with the bot token's secret redacted
+    pub url: String,
+    pub chat_id: String,
+    pub events: Vec<String>,
+    pub active: bool,
+    pub failure_count: i32,
+    pub last_triggered_at: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateCollaboratorRequest {
+    /// Who the key is for, e.g. "Sam" or "ci-bot"
+    pub name: String,
+    pub role: BoardRole,
+    /// When the key stops working (RFC 3339); or use ttl_seconds
+    #[serde(default)]
+    pub expires_at: Option<String>,
+    /// Seconds until the key stops working
+    #[serde(default)]
+    pub ttl_seconds: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateCollaboratorRequest {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub role: Option<BoardRole>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CollaboratorResponse {
+    pub id: String,
+    pub board_id: String,
+    pub name: String,
+    pub role: BoardRole,
+    /// Only returned when the collaborator is added; use it like a manage key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// When the key stops working; null = never
+    pub expires_at: Option<String>,
+    pub created_by: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateShareKeyRequest {
+    /// Who or what the key is for
+    #[serde(default, deserialize_with = "deserialize_string_or_null")]
+    pub name: String,
+    /// Only `read` for now (the default)
+    #[serde(default = "default_share_role")]
+    pub role: BoardRole,
+    /// When the key stops working (RFC 3339); or use ttl_seconds
+    #[serde(default)]
+    pub expires_at: Option<String>,
+    /// Seconds until the key stops working
+    #[serde(default)]
+    pub ttl_seconds: Option<i64>,
+}
+
+fn default_share_role() -> BoardRole {
+    BoardRole::Read
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShareKeyResponse {
+    pub id: String,
+    pub board_id: String,
+    pub name: String,
+    pub role: BoardRole,
+    /// Only returned when the key is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// When the key stops working; null = never
+    pub expires_at: Option<String>,
+    pub created_by: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateAgentKeyRequest {
+    /// The agent every write made with the key is attributed to
+    pub agent_id: String,
+    #[serde(default = "default_agent_role")]
+    pub role: BoardRole,
+    /// When the key stops working (RFC 3339); or use ttl_seconds
+    #[serde(default)]
+    pub expires_at: Option<String>,
+    /// Seconds until the key stops working
+    #[serde(default)]
+    pub ttl_seconds: Option<i64>,
+}
+
+fn default_agent_role() -> BoardRole {
+    BoardRole::Editor
+}
+
+#[derive(Debug, Serialize)]
+pub struct AgentKeyResponse {
+    pub id: String,
+    pub board_id: String,
+    pub agent_id: String,
+    pub role: BoardRole,
+    /// Only returned when the key is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// When the key stops working; null = never
+    pub expires_at: Option<String>,
+    pub created_by: String,
+    pub created_at: String,
+}
+
+/// A working key in the board's credential listing.
+#[derive(Debug, Serialize)]
+pub struct ActiveKeyResponse {
+    /// collaborator, share, scoped or agent
+    pub kind: String,
+    pub id: String,
+    pub name: String,
+    pub role: BoardRole,
+    pub expires_at: Option<String>,
+    pub expires_in_seconds: Option<i64>,
+    pub created_by: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateScopedTokenRequest {
+    /// Who or what the token is for
+    #[serde(default, deserialize_with = "deserialize_string_or_null")]
+    pub name: String,
+    /// Writes the token may make; none = read-only
+    #[serde(default)]
+    pub actions: Vec<ScopeAction>,
+    /// Column ids or names the writes are limited to; empty = every column
+    #[serde(default)]
+    pub columns: Vec<String>,
+    /// When the key stops working (RFC 3339); or use ttl_seconds
+    #[serde(default)]
+    pub expires_at: Option<String>,
+    /// Seconds until the key stops working
+    #[serde(default)]
+    pub ttl_seconds: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScopedTokenResponse {
+    pub id: String,
+    pub board_id: String,
+    pub name: String,
+    pub actions: Vec<ScopeAction>,
+    /// Column ids; empty = every column
+    pub columns: Vec<String>,
+    /// Only returned when the token is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// When the key stops working; null = never
+    pub expires_at: Option<String>,
+    pub created_by: String,
+    pub created_at: String,
+}
+
+/// What the calling key may do on a board.
+#[derive(Debug, Serialize)]
+pub struct BoardRoleResponse {
+    pub board_id: String,
+    pub role: BoardRole,
+    /// None for the board's manage key and share keys
+    pub collaborator_id: Option<String>,
+    pub name: Option<String>,
+}
+
+/// How an inbound payload becomes a task. Each field is a template whose
+/// `{{payload.…}}` placeholders are filled in from the posted JSON.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InboundMapping {
+    /// Defaults to `{{payload.title}}`; an empty result falls back to the hook's name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<serde_json::Value>,
+    /// A number or name ("high"); unknown values leave the default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<serde_json::Value>,
+    /// A list, or a comma-separated string
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub labels: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assigned_to: Option<serde_json::Value>,
+    /// Column id or name; unknown columns fall back to the first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_at: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateInboundHookRequest {
+    /// What posts here, e.g. "CI failures"; also the tasks' creator
+    pub name: String,
+    #[serde(default)]
+    pub mapping: InboundMapping,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateInboundHookRequest {
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Replaces the whole mapping
+    #[serde(default)]
+    pub mapping: Option<InboundMapping>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InboundHookResponse {
+    pub id: String,
+    pub board_id: String,
+    pub name: String,
+    pub mapping: InboundMapping,
+    /// Only returned when the hook is created; post to `/boards/{id}/inbound/{token}`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    pub last_used_at: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetValidationHookRequest {
+    /// Policy service asked before task creates and moves are written
+    pub url: String,
+    /// How long to wait for a verdict (default 2000, max 10000)
+    #[serde(default)]
+    pub timeout_ms: Option<i64>,
+    /// Allow the write when the service can't be reached or answers badly
+    /// (default false: reject it)
+    #[serde(default)]
+    pub fail_open: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidationHookResponse {
+    pub board_id: String,
+    pub url: String,
+    /// Only returned when the hook is first set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub timeout_ms: i64,
+    pub fail_open: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+// ============ Task Dependencies ============
+
+#[derive(Debug, Deserialize)]
+pub struct CreateDependencyRequest {
+    /// The task that blocks (must be completed first)
+    pub blocker_task_id: String,
+    /// The task that is blocked (cannot proceed until blocker is done)
+    pub blocked_task_id: String,
+    /// Optional note explaining the dependency
+    #[serde(default)]
+    pub note: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DependencyResponse {
+    pub id: String,
+    pub board_id: String,
+    pub blocker_task_id: String,
+    pub blocker_title: String,
+    pub blocker_column: String,
+    pub blocker_completed: bool,
+    pub blocked_task_id: String,
+    pub blocked_title: String,
     pub blocked_column: String,
     pub note: String,
     pub created_by: String,
     pub created_at: String,
 }
 
-// ============ Common ============
+/// Back-propagate due dates from a deadline onto a task's upstream blockers.
+#[derive(Debug, Deserialize)]
+pub struct ScheduleRequest {
+    /// The final task that must be done by `deadline`
+    pub task_id: String,
+    /// RFC 3339 timestamp or `YYYY-MM-DD` (end of that day, UTC)
+    pub deadline: String,
+    /// Per-task estimates in hours (task_id → hours)
+    #[serde(default)]
+    pub estimates: std::collections::HashMap<String, f64>,
+    /// Used when a task has no estimate here or in `metadata.estimate_hours` (default 8)
+    pub default_estimate_hours: Option<f64>,
+    /// Replace due dates that are already set (default: only fill empty ones)
+    #[serde(default)]
+    pub overwrite: bool,
+    /// Compute and return the schedule without writing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScheduledTask {
+    pub task_id: String,
+    pub title: String,
+    pub estimate_hours: f64,
+    /// Latest start that still meets the deadline
+    pub start_by: String,
+    /// Suggested due date
+    pub due_at: String,
+    pub current_due_at: Option<String>,
+    /// Whether `due_at` was (or, in a dry run, would be) written
+    pub applied: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScheduleResponse {
+    pub task_id: String,
+    pub deadline: String,
+    pub dry_run: bool,
+    /// Target task first, then blockers, earliest due date first
+    pub schedule: Vec<ScheduledTask>,
+    pub updated: usize,
+}
+
+// ============ Actors ============
+
+#[derive(Debug, Serialize)]
+pub struct ActorProfile {
+    pub name: String,
+    /// One or two letters for avatar placeholders
+    pub initials: String,
+    /// Deterministic `#rrggbb` color derived from the name
+    pub color: String,
+    /// Image URL (uploaded avatar, or generated SVG)
+    pub avatar_url: String,
+    pub has_uploaded_avatar: bool,
+}
+
+/// Returned when an avatar is first uploaded. The key is shown only once.
+#[derive(Debug, Serialize)]
+pub struct AvatarUploadResponse {
+    pub name: String,
+    pub content_type: String,
+    pub bytes: usize,
+    /// Required to replace or delete this avatar later (only on first upload)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar_key: Option<String>,
+}
+
+// ============ Analytics ============
+
+#[derive(Debug, Serialize)]
+pub struct HeatmapResponse {
+    pub board_id: String,
+    /// Window start (UTC), `days` before now
+    pub since: String,
+    pub days: i64,
+    /// Offset applied before bucketing, so hours are local to the caller
+    pub tz_offset_minutes: i64,
+    pub total_events: i64,
+    /// Row labels for `cells` (Monday first)
+    pub weekdays: Vec<String>,
+    /// 7 × 24 event counts: `cells[weekday][hour]`
+    pub cells: Vec<Vec<i64>>,
+    /// Per-actor breakdown, most active first
+    pub by_actor: Vec<ActorActivity>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ActorActivity {
+    pub actor: String,
+    pub total: i64,
+    /// 7 counts, Monday first
+    pub by_weekday: Vec<i64>,
+    /// 24 counts
+    pub by_hour: Vec<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AgingResponse {
+    pub board_id: String,
+    pub generated_at: String,
+    /// Columns in board order
+    pub columns: Vec<ColumnAging>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ColumnAging {
+    pub column_id: String,
+    pub name: String,
+    pub position: i32,
+    /// Completed stints in this column used for the percentile bands
+    pub sample_size: usize,
+    /// Historical time-in-column bands (hours); null until a task has left the column
+    pub percentiles: Option<AgingPercentiles>,
+    /// Open tasks currently in the column, oldest first
+    pub tasks: Vec<AgingTask>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AgingPercentiles {
+    pub p50: f64,
+    pub p70: f64,
+    pub p85: f64,
+    pub p95: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AgingTask {
+    pub id: String,
+    pub title: String,
+    pub assigned_to: Option<String>,
+    pub claimed_by: Option<String>,
+    /// When the task entered its current column
+    pub entered_at: String,
+    pub age_hours: f64,
+    /// Which band the age falls in: "p50", "p70", "p85", "p95" or "above_p95" (null without history)
+    pub band: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BoardViewsResponse {
+    pub board_id: String,
+    pub days: i64,
+    pub totals: ViewCounts,
+    /// One entry per day with any views, oldest first
+    pub daily: Vec<DailyViews>,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct ViewCounts {
+    pub board_loads: i64,
+    pub sse_connects: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DailyViews {
+    pub day: String,
+    pub board_loads: i64,
+    pub sse_connects: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AssigneeSuggestionsResponse {
+    pub task_id: String,
+    pub labels: Vec<String>,
+    pub column_id: String,
+    pub column_name: String,
+    /// History window: completions and moves from the last `days` days
+    pub days: i64,
+    /// Completed tasks the scores were computed from
+    pub sample_size: usize,
+    /// Best match first
+    pub suggestions: Vec<AssigneeSuggestion>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AssigneeSuggestion {
+    pub actor: String,
+    pub score: f64,
+    /// Completed tasks sharing at least one label with this task
+    pub label_matches: i64,
+    /// This task's labels the actor has completed work under
+    pub matched_labels: Vec<String>,
+    /// Tasks the actor moved out of this task's column
+    pub column_moves: i64,
+    /// All tasks the actor completed in the window
+    pub completed: i64,
+    /// Open tasks currently assigned to or claimed by the actor
+    pub open_tasks: i64,
+}
+
+// ============ Admin ============
+
+#[derive(Debug, Deserialize)]
+pub struct ScrubRequest {
+    /// Only touch rows last updated more than this many days ago (default: 0 = all rows)
+    #[serde(default)]
+    pub older_than_days: i64,
+    /// "pseudonymize" (default) replaces names with salted `anon-…` handles; "remove" replaces them with `[redacted]`
+    #[serde(default)]
+    pub mode: Option<String>,
+    /// Restrict to these actor names (case-insensitive), or sign-in email addresses. Omit to scrub every actor name.
+    #[serde(default)]
+    pub actors: Option<Vec<String>>,
+    /// Restrict to these boards. Omit to scrub across all boards.
+    #[serde(default)]
+    pub board_ids: Option<Vec<String>>,
+    /// Salt mixed into pseudonyms so they can't be reversed by hashing known names.
+    /// Omitted = a random salt for this scrub only; empty is refused.
+    #[serde(default)]
+    pub salt: Option<String>,
+    /// Report what would change without writing anything (default: true)
+    #[serde(default)]
+    pub dry_run: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScrubReport {
+    pub dry_run: bool,
+    pub mode: String,
+    /// Rows older than this timestamp were considered
+    pub cutoff: String,
+    pub boards_affected: i64,
+    pub tasks_affected: i64,
+    pub events_affected: i64,
+    pub dependencies_affected: i64,
+    pub webhooks_affected: i64,
+    /// Individual replacements, by kind
+    pub actor_names: i64,
+    pub emails: i64,
+    pub ip_addresses: i64,
+    /// Rows affected in each table that had any
+    pub tables: std::collections::BTreeMap<String, i64>,
+}
+
+/// Settings pushed to many boards at once. Omitted fields are left alone.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BulkBoardSettings {
+    #[serde(default)]
+    pub require_display_name: Option<bool>,
+    #[serde(default)]
+    pub enforce_dependencies: Option<bool>,
+    #[serde(default)]
+    pub quick_done_auto_archive: Option<bool>,
+    /// Retention policies in days; 0 clears the policy (keep forever)
+    #[serde(default)]
+    pub archived_task_retention_days: Option<i64>,
+    #[serde(default)]
+    pub event_retention_days: Option<i64>,
+    /// WIP limits by column name (case-insensitive); `null` removes the limit
+    #[serde(default)]
+    pub wip_limits: Option<std::collections::HashMap<String, Option<i32>>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApplySettingsRequest {
+    pub settings: BulkBoardSettings,
+    /// Report what would change without writing anything (default: false)
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BoardSettingsResult {
+    pub board_id: String,
+    /// "updated" or "unchanged"
+    pub status: String,
+    pub changes: Vec<String>,
+    /// Requested settings that don't apply to this board (e.g. no such column)
+    pub skipped: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApplySettingsReport {
+    pub dry_run: bool,
+    pub boards_updated: usize,
+    pub results: Vec<BoardSettingsResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DbStatsResponse {
+    pub busy_timeout_ms: u64,
+    pub storage: DbStorageStats,
+    pub pool: DbPoolStats,
+    pub locks: DbLockStats,
+    pub cache: DbCacheStats,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DbStorageStats {
+    pub journal_mode: String,
+    pub page_size: i64,
+    pub page_count: i64,
+    pub freelist_count: i64,
+    pub database_bytes: u64,
+    /// Size of the `-wal` file; grows until a checkpoint runs
+    pub wal_bytes: u64,
+}
+
+/// Connection checkouts through the request path, since startup.
+#[derive(Debug, Serialize)]
+pub struct DbPoolStats {
+    pub max_size: u32,
+    pub connections: u32,
+    pub idle_connections: u32,
+    pub checkouts: u64,
+    pub avg_checkout_wait_ms: f64,
+    pub max_checkout_wait_ms: f64,
+    pub avg_hold_ms: f64,
+    /// Longest any request held a connection (and any lock taken on it)
+    pub longest_hold_ms: f64,
+}
+
+/// Waits on SQLite's database lock, since startup.
+#[derive(Debug, Serialize)]
+pub struct DbLockStats {
+    pub busy_waits: u64,
+    /// Waits that hit the busy timeout and failed with "database is locked"
+    pub busy_timeouts: u64,
+    pub total_busy_wait_ms: f64,
+    pub longest_busy_wait_ms: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DbCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// hits / (hits + misses); null before any page was read
+    pub hit_ratio: Option<f64>,
+}
+
+/// API usage per client since startup (`GET /admin/client-stats`).
+#[derive(Debug, Serialize)]
+pub struct ClientStatsResponse {
+    pub since: String,
+    pub total_requests: u64,
+    pub llms_txt_fetches: u64,
+    pub openapi_fetches: u64,
+    /// Busiest first
+    pub clients: Vec<ClientUsageStats>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClientUsageStats {
+    /// `X-Client` name, or the family detected from `User-Agent` (e.g. "langchain", "curl")
+    pub client: String,
+    /// All API requests, including the llms.txt and openapi.json fetches
+    pub requests: u64,
+    pub llms_txt_fetches: u64,
+    pub openapi_fetches: u64,
+    pub first_seen: String,
+    pub last_seen: String,
+    /// Most frequent raw user agents for this client (top 10)
+    pub user_agents: Vec<UserAgentCount>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserAgentCount {
+    pub user_agent: String,
+    pub requests: u64,
+}
+
+/// Recent statements slower than `SLOW_QUERY_MS` (`GET /admin/slow-queries`).
+#[derive(Debug, Serialize)]
+pub struct SlowQueriesResponse {
+    /// None when the slow-query log is off
+    pub threshold_ms: Option<u64>,
+    /// Default request timeout; None when off
+    pub request_timeout_ms: Option<u64>,
+    /// Newest first, up to 100
+    pub queries: Vec<SlowQuery>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SlowQuery {
+    pub method: String,
+    /// Route pattern, e.g. "/api/v1/boards/<board_id>/tasks"
+    pub route: String,
+    pub sql: String,
+    pub duration_ms: f64,
+    pub at: String,
+}
+
+/// An entry in the instance-wide tag directory.
+#[derive(Debug, Serialize)]
+pub struct TagResponse {
+    pub name: String,
+    pub description: String,
+    /// Public, unarchived boards carrying the tag
+    pub board_count: i64,
+    pub created_at: String,
+}
+
+/// Add a tag to the directory, or update its description.
+#[derive(Debug, Deserialize)]
+pub struct UpsertTagRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteTagResponse {
+    pub deleted: bool,
+    pub name: String,
+    pub boards_untagged: usize,
+}
+
+/// Merge another board into this one.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MergeBoardsRequest {
+    pub source_board_id: String,
+    /// Manage key of the source board
+    pub source_key: String,
+    /// Source column (id or name) → this board's column (id or name). Other
+    /// columns go to the column with the same name, created if missing.
+    #[serde(default)]
+    pub column_mapping: std::collections::HashMap<String, String>,
+    /// "archive" (default) or "delete" the emptied source board
+    pub source_action: Option<String>,
+    pub actor_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MergedColumn {
+    pub from_column_id: String,
+    pub from_column: String,
+    pub to_column_id: String,
+    pub to_column: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MergeBoardsResponse {
+    pub board_id: String,
+    pub source_board_id: String,
+    pub source_name: String,
+    pub tasks_moved: usize,
+    pub dependencies_moved: usize,
+    /// Names of columns added to this board for unmapped source columns
+    pub columns_created: Vec<String>,
+    pub column_mapping: Vec<MergedColumn>,
+    /// "archived" or "deleted"
+    pub source_action: String,
+}
+
+/// A snapshot written to `BACKUP_DIR`.
+#[derive(Debug, Serialize)]
+pub struct BackupResponse {
+    pub file: String,
+    pub path: String,
+    pub bytes: u64,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestoreResponse {
+    /// Backup file name, or "upload"
+    pub restored_from: String,
+    pub bytes: u64,
+    pub boards: i64,
+    pub tasks: i64,
+    /// Snapshot of the database as it was just before the restore
+    pub pre_restore_backup: String,
+}
+
+// ============ Export / Import ============
+
+/// Complete, versioned dump of one board. Rows are exported as stored (ids,
+/// timestamps, raw event data) rather than as API responses, so the document
+/// can be re-imported without loss.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BoardExport {
+    /// Always "kanban-board-export"
+    pub format: String,
+    /// Document schema version; bumped on incompatible changes
+    pub version: u32,
+    pub exported_at: String,
+    /// Version of the server that produced the export
+    pub server_version: String,
+    pub board: ExportBoard,
+    pub columns: Vec<ExportColumn>,
+    pub tasks: Vec<ExportTask>,
+    /// Activity log entries other than comments
+    pub events: Vec<ExportEvent>,
+    pub comments: Vec<ExportEvent>,
+    pub dependencies: Vec<ExportDependency>,
+    /// Webhook configuration only — secrets are never exported
+    pub webhooks: Vec<ExportWebhook>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportBoard {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub is_public: bool,
+    /// Absent in exports from before board visibility; derived from is_public
+    #[serde(default)]
+    pub visibility: Option<Visibility>,
+    pub archived: bool,
+    pub require_display_name: bool,
+    /// Absent in exports from before dependency enforcement; defaults to off
+    #[serde(default)]
+    pub enforce_dependencies: bool,
+    pub quick_done_column_id: Option<String>,
+    pub quick_done_auto_archive: bool,
+    pub quick_reassign_column_id: Option<String>,
+    pub quick_reassign_to: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportColumn {
+    pub id: String,
+    pub name: String,
+    pub position: i32,
+    pub wip_limit: Option<i32>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportTask {
+    pub id: String,
+    pub column_id: String,
+    pub title: String,
+    pub description: String,
+    pub priority: i32,
+    pub position: i32,
+    pub created_by: String,
+    pub assigned_to: Option<String>,
+    pub claimed_by: Option<String>,
+    pub claimed_at: Option<String>,
+    pub labels: Vec<String>,
+    pub metadata: serde_json::Value,
+    pub due_at: Option<String>,
+    pub completed_at: Option<String>,
+    pub archived_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportEvent {
+    pub id: String,
+    pub task_id: String,
+    pub event_type: String,
+    pub actor: String,
+    pub data: serde_json::Value,
+    pub seq: Option<i64>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportDependency {
+    pub id: String,
+    pub blocker_task_id: String,
+    pub blocked_task_id: String,
+    pub note: String,
+    pub created_by: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportWebhook {
+    pub id: String,
+    pub url: String,
+    pub events: Vec<String>,
+    pub active: bool,
+    pub created_by: String,
+    pub created_at: String,
+}
+
+/// What an import created, and anything it had to leave behind.
+#[derive(Debug, Serialize, Default)]
+pub struct ImportReport {
+    pub columns: usize,
+    pub tasks: usize,
+    pub events: usize,
+    pub comments: usize,
+    pub dependencies: usize,
+    /// Webhooks are not recreated: their secrets aren't exported, and the
+    /// original board may still be delivering to the same URLs
+    pub webhooks_skipped: usize,
+    pub warnings: Vec<String>,
+}
+
+/// The new board (with its fresh manage key) plus the import report.
+#[derive(Debug, Serialize)]
+pub struct ImportBoardResponse {
+    #[serde(flatten)]
+    pub board: CreateBoardResponse,
+    pub report: ImportReport,
+}
+
+/// One problem that would stop an import. `path` points into the submitted
+/// document (`tasks[899].column_id`) when the problem is in a single row.
+#[derive(Debug, Serialize)]
+pub struct ImportIssue {
+    pub code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub message: String,
+}
 
+/// Result of `POST /boards/import/validate`. When `valid`, the document is
+/// held until `expires_at` and can be written by committing `payload_hash`.
 #[derive(Debug, Serialize)]
+pub struct ImportValidationResponse {
+    pub valid: bool,
+    pub source: String,
+    /// SHA-256 (hex) of the request body
+    pub payload_hash: String,
+    pub expires_at: Option<String>,
+    pub issues: Vec<ImportIssue>,
+    /// What committing would create; counts stay zero while there are issues
+    pub report: ImportReport,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CommitImportRequest {
+    pub payload_hash: String,
+}
+
+/// Import GitHub issues into an existing board. Give either `issues` (the JSON
+/// from GitHub's issues API) or `repo` to fetch them.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GithubImportRequest {
+    #[serde(default)]
+    pub issues: Option<Vec<serde_json::Value>>,
+    /// `owner/name`
+    #[serde(default)]
+    pub repo: Option<String>,
+    /// Used for the fetch only; never stored
+    #[serde(default)]
+    pub token: Option<String>,
+    /// `open` (default), `closed` or `all` — fetch only
+    #[serde(default)]
+    pub state: Option<String>,
+    /// Milestone title → column name or id
+    #[serde(default)]
+    pub milestone_columns: std::collections::HashMap<String, String>,
+    /// Column for open issues without a mapped milestone (default: first column)
+    #[serde(default)]
+    pub default_column: Option<String>,
+    /// Column for closed issues without a mapped milestone (default: last column)
+    #[serde(default)]
+    pub closed_column: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GithubImportReport {
+    pub imported: usize,
+    /// Issues already on this board from an earlier import
+    pub already_imported: usize,
+    pub pull_requests_skipped: usize,
+    pub task_ids: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+// ============ Common ============
+
+#[derive(Debug, Clone, Serialize)]
 pub struct ApiError {
     pub error: String,
     pub code: String,
     pub status: u16,
 }
 
+/// One failed policy from the composite move/reorder validation.
+#[derive(Debug, Serialize)]
+pub struct PolicyViolation {
+    pub code: String,
+    pub error: String,
+}
+
+/// 409 body listing every policy a move would violate. `code` is the first
+/// violation's code, so clients matching on a single code keep working.
+#[derive(Debug, Serialize)]
+pub struct PolicyViolationsError {
+    pub error: String,
+    pub code: String,
+    pub status: u16,
+    pub codes: Vec<String>,
+    pub violations: Vec<PolicyViolation>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct HealthResponse {
     pub status: String,
     pub version: String,
+    /// Enabled experimental feature flags
+    pub features: Vec<&'static str>,
 }
 
 #[allow(dead_code)]
@@ -410,3 +2309,7 @@ pub struct PaginatedResponse<T: Serialize> {
 fn default_metadata() -> serde_json::Value {
     serde_json::json!({})
 }
+
+fn default_true() -> bool {
+    true
+}
@@ -0,0 +1,170 @@
+//! Notification preferences: per board, each name chooses what reaches it —
+//! every notice (`all`), only @mentions (`mentions`, the default), only
+//! notices about tasks it is assigned to or has claimed (`assigned`), or
+//! nothing (`mute`). The mention inbox honours the setting, and every
+//! notification channel should ask `wants` before delivering.
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::access::{self, BoardRole};
+use crate::auth::BoardToken;
+use crate::db::{hash_key, DbPool};
+use crate::models::*;
+use crate::routes::{db_error, with_db};
+
+pub const ALL: &str = "all";
+pub const MENTIONS: &str = "mentions";
+pub const ASSIGNED: &str = "assigned";
+pub const MUTE: &str = "mute";
+pub const LEVELS: &[&str] = &[ALL, MENTIONS, ASSIGNED, MUTE];
+
+/// Level for names that never set one.
+pub const DEFAULT_LEVEL: &str = MENTIONS;
+
+/// The level `name` chose on the board, or `DEFAULT_LEVEL`.
+pub fn level(conn: &Connection, board_id: &str, name: &str) -> rusqlite::Result<String> {
+    conn.query_row(
+        "SELECT level FROM notification_preferences WHERE board_id = ?1 AND name = ?2",
+        rusqlite::params![board_id, name],
+        |row| row.get(0),
+    )
+    .optional()
+    .map(|level| level.unwrap_or_else(|| DEFAULT_LEVEL.to_string()))
+}
+
+/// Whether a name at `level` should hear about a notice that mentions it
+/// (`mentioned`) or concerns a task it is assigned to (`assigned`).
+pub fn wants(level: &str, mentioned: bool, assigned: bool) -> bool {
+    match level {
+        ALL => true,
+        ASSIGNED => assigned,
+        MUTE => false,
+        _ => mentioned,
+    }
+}
+
+fn load(conn: &Connection, board_id: &str, name: &str) -> rusqlite::Result<NotificationPreferenceResponse> {
+    let stored = conn
+        .query_row(
+            "SELECT name, level, updated_at FROM notification_preferences WHERE board_id = ?1 AND name = ?2",
+            rusqlite::params![board_id, name],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+    let (name, level, updated_at) = match stored {
+        Some((name, level, updated_at)) => (name, level, Some(updated_at)),
+        None => (name.to_string(), DEFAULT_LEVEL.to_string(), None),
+    };
+    Ok(NotificationPreferenceResponse {
+        board_id: board_id.to_string(),
+        name,
+        level,
+        updated_at,
+    })
+}
+
+/// Every preference set on the board — requires manage key. Names without
+/// one use the default (`mentions`).
+#[get("/boards/<board_id>/notification-preferences")]
+pub async fn list_preferences(
+    board_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<Vec<NotificationPreferenceResponse>>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Viewer)?;
+        let prefs = conn
+            .prepare(
+                "SELECT name, level, updated_at FROM notification_preferences
+                 WHERE board_id = ?1 ORDER BY name COLLATE NOCASE",
+            )
+            .map_err(|e| db_error(&e.to_string()))?
+            .query_map(rusqlite::params![board_id], |row| {
+                Ok(NotificationPreferenceResponse {
+                    board_id: board_id.to_string(),
+                    name: row.get(0)?,
+                    level: row.get(1)?,
+                    updated_at: row.get(2)?,
+                })
+            })
+            .map_err(|e| db_error(&e.to_string()))?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| db_error(&e.to_string()))?;
+        Ok(Json(prefs))
+    })
+    .await
+}
+
+/// A name's preference — public, so an agent can check its own.
+#[get("/boards/<board_id>/notification-preferences/<name>")]
+pub async fn get_preference(
+    board_id: &str,
+    name: &str,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+) -> Result<Json<NotificationPreferenceResponse>, (Status, Json<ApiError>)> {
+    let (board_id, name) = (board_id.to_owned(), name.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, name) = (board_id.as_str(), name.as_str());
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        load(conn, board_id, name).map(Json).map_err(|e| db_error(&e.to_string()))
+    })
+    .await
+}
+
+/// Set a name's preference — requires manage key.
+#[put("/boards/<board_id>/notification-preferences/<name>", format = "json", data = "<req>")]
+pub async fn set_preference(
+    board_id: &str,
+    name: &str,
+    req: Json<SetNotificationPreferenceRequest>,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<NotificationPreferenceResponse>, (Status, Json<ApiError>)> {
+    let level = req.level.trim().to_lowercase();
+    if !LEVELS.contains(&level.as_str()) {
+        return Err((
+            Status::BadRequest,
+            Json(ApiError {
+                error: format!("level must be one of: {}", LEVELS.join(", ")),
+                code: "INVALID_INPUT".to_string(),
+                status: 400,
+            }),
+        ));
+    }
+    let name = name.trim();
+
+    let (board_id, name) = (board_id.to_owned(), name.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, name) = (board_id.as_str(), name.as_str());
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Viewer)?;
+        conn.execute(
+            "INSERT INTO notification_preferences (board_id, name, level) VALUES (?1, ?2, ?3)
+             ON CONFLICT(board_id, name) DO UPDATE SET level = excluded.level, updated_at = datetime('now')",
+            rusqlite::params![board_id, name, level],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        load(conn, board_id, name).map(Json).map_err(|e| db_error(&e.to_string()))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wants_follows_level() {
+        assert!(wants(ALL, false, false));
+        assert!(wants(MENTIONS, true, false));
+        assert!(!wants(MENTIONS, false, true));
+        assert!(wants(ASSIGNED, false, true));
+        assert!(!wants(ASSIGNED, true, false));
+        assert!(!wants(MUTE, true, true));
+    }
+}
@@ -0,0 +1,443 @@
+//! Optional OIDC sign-in for people using the web UI (Google, or any
+//! provider with OpenID discovery). It's off unless `OIDC_ISSUER`,
+//! `OIDC_CLIENT_ID`, `OIDC_CLIENT_SECRET` and `OIDC_REDIRECT_URL` are set.
+//!
+//! Sign-in is the authorization code flow: `/auth/login` sends the browser
+//! to the provider, `/auth/callback` trades the code for tokens, reads the
+//! user from the provider's userinfo endpoint and starts a server-side
+//! session held in the `kb_session` cookie. Boards created while signed in
+//! are owned by that user and get an owner key for them (a `board_members`
+//! record, so its role and expiry work like any member key's). The key is
+//! derived from the user, the board and a server secret rather than handed
+//! out, and the session acts with it on that board. Key-based access is
+//! unchanged — agents never need this.
+
+use std::sync::Mutex;
+
+use rand::RngCore;
+use rocket::http::{Cookie, CookieJar, SameSite, Status};
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::response::Redirect;
+use rocket::serde::json::Json;
+use rocket::State;
+use serde::Deserialize;
+
+use crate::db::{hash_key, DbPool};
+use crate::models::*;
+use crate::routes::{db_error, with_db};
+use crate::{secrets, signing};
+use crate::url_policy::{OutboundClient, UrlPolicy};
+
+/// Cookie holding the session token.
+pub const SESSION_COOKIE: &str = "kb_session";
+/// Cookies carrying the login attempt to the callback.
+const STATE_COOKIE: &str = "kb_oidc_state";
+const RETURN_COOKIE: &str = "kb_oidc_return";
+/// How long the provider round trip may take.
+const LOGIN_TIMEOUT_SECONDS: i64 = 600;
+
+/// The provider endpoints from its discovery document.
+#[derive(Debug, Clone, Deserialize)]
+struct Discovery {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    userinfo_endpoint: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserInfo {
+    sub: String,
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    preferred_username: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Provider {
+    issuer: String,
+    client_id: String,
+    client_secret: String,
+    redirect_url: String,
+}
+
+/// OIDC settings, loaded once at startup. Disabled without a provider.
+#[derive(Debug, Default)]
+pub struct OidcConfig {
+    provider: Option<Provider>,
+    /// `OIDC_SESSION_HOURS` (default a week)
+    session_hours: i64,
+    discovery: Mutex<Option<Discovery>>,
+    http: OutboundClient,
+}
+
+impl OidcConfig {
+    /// Read the `OIDC_*` variables; any of the four required ones missing
+    /// leaves sign-in off.
+    pub fn from_env() -> Self {
+        let var = |name| std::env::var(name).ok().filter(|v: &String| !v.trim().is_empty());
+        let session_hours = var("OIDC_SESSION_HOURS").and_then(|v| v.parse().ok()).unwrap_or(168);
+        match (var("OIDC_ISSUER"), var("OIDC_CLIENT_ID"), var("OIDC_CLIENT_SECRET"), var("OIDC_REDIRECT_URL")) {
+            (Some(issuer), Some(client_id), Some(client_secret), Some(redirect_url)) => {
+                Self::new(&issuer, &client_id, &client_secret, &redirect_url).with_session_hours(session_hours)
+            }
+            _ => Self::disabled(),
+        }
+    }
+
+    pub fn new(issuer: &str, client_id: &str, client_secret: &str, redirect_url: &str) -> Self {
+        OidcConfig {
+            provider: Some(Provider {
+                issuer: issuer.trim_end_matches('/').to_string(),
+                client_id: client_id.to_string(),
+                client_secret: client_secret.to_string(),
+                redirect_url: redirect_url.to_string(),
+            }),
+            ..Self::disabled()
+        }
+    }
+
+    pub fn disabled() -> Self {
+        OidcConfig {
+            session_hours: 168,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_session_hours(mut self, hours: i64) -> Self {
+        self.session_hours = hours.max(1);
+        self
+    }
+
+    /// Override which URLs the provider may be reached at.
+    pub fn with_url_policy(mut self, policy: UrlPolicy) -> Self {
+        self.http = OutboundClient::new(policy);
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.provider.is_some()
+    }
+
+    fn provider(&self) -> Result<&Provider, (Status, Json<ApiError>)> {
+        self.provider.as_ref().ok_or_else(|| {
+            (
+                Status::NotFound,
+                Json(ApiError {
+                    error: "Sign-in is not enabled on this server (OIDC_ISSUER not set)".to_string(),
+                    code: "OIDC_DISABLED".to_string(),
+                    status: 404,
+                }),
+            )
+        })
+    }
+
+    /// The provider's endpoints, fetched on first use.
+    async fn discovery(&self, provider: &Provider) -> Result<Discovery, (Status, Json<ApiError>)> {
+        if let Some(discovery) = self.discovery.lock().unwrap().clone() {
+            return Ok(discovery);
+        }
+        let url = format!("{}/.well-known/openid-configuration", provider.issuer);
+        let discovery: Discovery = self.fetch_json(self.http.get(&url)).await?;
+        *self.discovery.lock().unwrap() = Some(discovery.clone());
+        Ok(discovery)
+    }
+
+    async fn fetch_json<T: serde::de::DeserializeOwned>(
+        &self,
+        request: Result<reqwest::RequestBuilder, String>,
+    ) -> Result<T, (Status, Json<ApiError>)> {
+        let provider_error = |e: String| {
+            (
+                Status::BadGateway,
+                Json(ApiError {
+                    error: format!("Sign-in provider request failed: {}", e),
+                    code: "OIDC_PROVIDER_ERROR".to_string(),
+                    status: 502,
+                }),
+            )
+        };
+        let response = request.map_err(provider_error)?.send().await.map_err(|e| provider_error(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(provider_error(format!("HTTP {}", response.status())));
+        }
+        response.json().await.map_err(|e| provider_error(e.to_string()))
+    }
+}
+
+fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn login_error(error: &str) -> (Status, Json<ApiError>) {
+    (
+        Status::BadRequest,
+        Json(ApiError {
+            error: error.to_string(),
+            code: "OIDC_LOGIN_FAILED".to_string(),
+            status: 400,
+        }),
+    )
+}
+
+/// Only same-site paths, so the login can't be used as an open redirect.
+fn safe_return_path(path: Option<&str>) -> String {
+    match path {
+        Some(p) if p.starts_with('/') && !p.starts_with("//") && !p.contains('\\') => p.to_string(),
+        _ => "/".to_string(),
+    }
+}
+
+fn short_cookie(name: &'static str, value: String) -> Cookie<'static> {
+    Cookie::build((name, value))
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .max_age(rocket::time::Duration::seconds(LOGIN_TIMEOUT_SECONDS))
+        .build()
+}
+
+/// A signed-in user, from the `kb_session` cookie. 401 without a live session.
+#[derive(Debug, Clone)]
+pub struct SessionUser {
+    pub id: String,
+    pub name: String,
+    pub email: Option<String>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for SessionUser {
+    type Error = &'static str;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let Some(token) = request.cookies().get(SESSION_COOKIE).map(|c| c.value().to_string()) else {
+            return Outcome::Error((Status::Unauthorized, "Not signed in"));
+        };
+        let Some(db) = request.rocket().state::<DbPool>().cloned() else {
+            return Outcome::Error((Status::Unauthorized, "Session expired; sign in again"));
+        };
+        let token_hash = hash_key(&token);
+        let user = crate::db::blocking(move || {
+            db.get().ok()?.query_row(
+                "SELECT u.id, u.name, u.email FROM user_sessions s JOIN users u ON u.id = s.user_id
+                 WHERE s.token_hash = ?1 AND s.expires_at > datetime('now')",
+                rusqlite::params![token_hash],
+                |row| {
+                    Ok(SessionUser {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        email: row.get(2)?,
+                    })
+                },
+            )
+            .ok()
+        })
+        .await;
+        match user {
+            Some(user) => Outcome::Success(user),
+            None => Outcome::Error((Status::Unauthorized, "Session expired; sign in again")),
+        }
+    }
+}
+
+/// The owner key a user acts with on a board they created. Never shown to
+/// anyone: only its hash is stored, and a session re-derives it.
+pub fn board_key(conn: &rusqlite::Connection, user_id: &str, board_id: &str) -> rusqlite::Result<String> {
+    let secret = secrets::server_key(conn, "user_keys")?;
+    let mac = signing::hmac(secret.as_bytes(), format!("{}:{}", user_id, board_id).as_bytes());
+    Ok(format!("kb_u_{}", hex::encode(&mac[..16])))
+}
+
+/// The keys a live sign-in session acts with: one per board its user owns,
+/// or just the one for `board_id` when given. Empty when `token` isn't a
+/// live session.
+pub fn session_keys(conn: &rusqlite::Connection, token: &str, board_id: Option<&str>) -> Vec<String> {
+    let boards = conn
+        .prepare_cached(
+            "SELECT s.user_id, b.id FROM user_sessions s JOIN boards b ON b.owner_user_id = s.user_id
+             WHERE s.token_hash = ?1 AND s.expires_at > datetime('now') AND (?2 IS NULL OR b.id = ?2)",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map(rusqlite::params![hash_key(token), board_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<rusqlite::Result<Vec<(String, String)>>>()
+        })
+        .unwrap_or_default();
+    boards.iter().filter_map(|(user_id, board_id)| board_key(conn, user_id, board_id).ok()).collect()
+}
+
+/// Whether sign-in is available, for the web UI — public.
+#[get("/auth/config")]
+pub fn auth_config(oidc: &State<OidcConfig>) -> Json<AuthConfigResponse> {
+    Json(AuthConfigResponse {
+        enabled: oidc.is_enabled(),
+        login_url: oidc.is_enabled().then(|| "/api/v1/auth/login".to_string()),
+    })
+}
+
+/// Start signing in: redirect to the provider. `return_to` is the path to
+/// come back to afterwards.
+#[get("/auth/login?<return_to>")]
+pub async fn login(
+    return_to: Option<&str>,
+    cookies: &CookieJar<'_>,
+    oidc: &State<OidcConfig>,
+) -> Result<Redirect, (Status, Json<ApiError>)> {
+    let provider = oidc.provider()?;
+    let discovery = oidc.discovery(provider).await?;
+
+    let state = random_token();
+    let url = reqwest::Url::parse_with_params(
+        &discovery.authorization_endpoint,
+        &[
+            ("response_type", "code"),
+            ("client_id", provider.client_id.as_str()),
+            ("redirect_uri", provider.redirect_url.as_str()),
+            ("scope", "openid email profile"),
+            ("state", state.as_str()),
+        ],
+    )
+    .map_err(|e| login_error(&format!("Bad authorization endpoint: {}", e)))?;
+    cookies.add(short_cookie(STATE_COOKIE, state));
+    cookies.add(short_cookie(RETURN_COOKIE, safe_return_path(return_to)));
+    Ok(Redirect::to(url.to_string()))
+}
+
+/// Where the provider sends the browser back: finish signing in, start a
+/// session and return to where the login started.
+#[get("/auth/callback?<code>&<state>&<error>")]
+pub async fn callback(
+    code: Option<&str>,
+    state: Option<&str>,
+    error: Option<&str>,
+    cookies: &CookieJar<'_>,
+    oidc: &State<OidcConfig>,
+    db: &State<DbPool>,
+) -> Result<Redirect, (Status, Json<ApiError>)> {
+    let provider = oidc.provider()?;
+    if let Some(error) = error {
+        return Err(login_error(&format!("The provider refused the sign-in: {}", error)));
+    }
+    let expected_state = cookies.get(STATE_COOKIE).map(|c| c.value().to_string());
+    let return_to = safe_return_path(cookies.get(RETURN_COOKIE).map(|c| c.value()));
+    cookies.remove(Cookie::from(STATE_COOKIE));
+    cookies.remove(Cookie::from(RETURN_COOKIE));
+    let (Some(code), Some(state)) = (code, state) else {
+        return Err(login_error("Missing code or state"));
+    };
+    if expected_state.as_deref() != Some(state) {
+        return Err(login_error("Sign-in state doesn't match; start again from /auth/login"));
+    }
+
+    let discovery = oidc.discovery(provider).await?;
+    let tokens: TokenResponse = oidc
+        .fetch_json(oidc.http.post(&discovery.token_endpoint).map(|request| request.form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", provider.redirect_url.as_str()),
+            ("client_id", provider.client_id.as_str()),
+            ("client_secret", provider.client_secret.as_str()),
+        ])))
+        .await?;
+    let info: UserInfo = oidc
+        .fetch_json(oidc.http.get(&discovery.userinfo_endpoint).map(|request| request.bearer_auth(&tokens.access_token)))
+        .await?;
+    let name = info
+        .name
+        .clone()
+        .or(info.preferred_username.clone())
+        .or(info.email.clone())
+        .unwrap_or_else(|| info.sub.clone());
+
+    let session = random_token();
+    let (token_hash, issuer, lifetime) =
+        (hash_key(&session), provider.issuer.clone(), format!("+{} hours", oidc.session_hours));
+    with_db(db, move |conn| {
+        conn.execute(
+            "INSERT INTO users (id, issuer, subject, email, name) VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(issuer, subject) DO UPDATE SET email = excluded.email, name = excluded.name, last_login_at = datetime('now')",
+            rusqlite::params![uuid::Uuid::new_v4().to_string(), issuer, info.sub, info.email, name],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        conn.execute(
+            "INSERT INTO user_sessions (token_hash, user_id, expires_at)
+             SELECT ?1, id, datetime('now', ?4) FROM users WHERE issuer = ?2 AND subject = ?3",
+            rusqlite::params![token_hash, issuer, info.sub, lifetime],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        Ok(())
+    })
+    .await?;
+
+    cookies.add(
+        Cookie::build((SESSION_COOKIE, session))
+            .path("/")
+            .http_only(true)
+            .same_site(SameSite::Lax)
+            .secure(provider.redirect_url.starts_with("https://"))
+            .max_age(rocket::time::Duration::hours(oidc.session_hours)),
+    );
+    Ok(Redirect::to(return_to))
+}
+
+/// The signed-in user and the boards they own — requires a session.
+#[get("/auth/me")]
+pub async fn me(user: SessionUser, db: &State<DbPool>) -> Result<Json<SessionUserResponse>, (Status, Json<ApiError>)> {
+    with_db(db, move |conn| {
+        let mut stmt = conn
+            .prepare("SELECT id, name FROM boards WHERE owner_user_id = ?1 ORDER BY created_at DESC")
+            .map_err(|e| db_error(&e.to_string()))?;
+        let boards = stmt
+            .query_map(rusqlite::params![user.id], |row| {
+                Ok(OwnedBoard {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                })
+            })
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(Json(SessionUserResponse {
+            id: user.id,
+            name: user.name,
+            email: user.email,
+            boards,
+        }))
+    })
+    .await
+}
+
+/// End the session.
+#[post("/auth/logout")]
+pub async fn logout(cookies: &CookieJar<'_>, db: &State<DbPool>) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
+    if let Some(token) = cookies.get(SESSION_COOKIE).map(|c| c.value().to_string()) {
+        with_db(db, move |conn| {
+            conn.execute("DELETE FROM user_sessions WHERE token_hash = ?1", rusqlite::params![hash_key(&token)])
+                .map_err(|e| db_error(&e.to_string()))
+        })
+        .await?;
+    }
+    cookies.remove(Cookie::from(SESSION_COOKIE));
+    Ok(Json(serde_json::json!({"signed_out": true})))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_return_path() {
+        assert_eq!(safe_return_path(Some("/board/abc?x=1")), "/board/abc?x=1");
+        for unsafe_path in [None, Some("https://evil.example"), Some("//evil.example"), Some("/\\evil.example"), Some("board")] {
+            assert_eq!(safe_return_path(unsafe_path), "/", "{:?}", unsafe_path);
+        }
+    }
+}
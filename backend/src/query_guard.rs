@@ -0,0 +1,347 @@
+//! Request timeouts and the slow-query log, so one pathological board or
+//! filter can't monopolize the database unnoticed.
+//!
+//! The fairing registers each request as it arrives. While the request runs
+//! database work (`routes::with_db`), an SQLite progress handler interrupts
+//! statements once the request's deadline has passed, and the connection's
+//! profiler notes statements slower than the threshold. When the response
+//! goes out, those are logged with the route and kept for
+//! `GET /admin/slow-queries`. Background jobs are not covered.
+//!
+//! Requests are told apart by Tokio task (or by thread outside a task), since
+//! SQLite's profile hook is a plain `fn` and can't carry request state.
+//! `with_db` runs the work on Tokio's blocking pool, so the thread doing it
+//! is marked as acting for the request's task (`on_behalf`).
+
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{Data, Request, Response};
+use rusqlite::Connection;
+
+use crate::models::{ApiError, SlowQueriesResponse, SlowQuery};
+
+/// Default request timeout (`REQUEST_TIMEOUT_MS`).
+const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+/// Default slow-query threshold (`SLOW_QUERY_MS`).
+const DEFAULT_SLOW_QUERY_MS: u64 = 500;
+/// Slow queries kept for the admin endpoint.
+const MAX_LOGGED: usize = 100;
+/// Slow statements remembered per request.
+const MAX_PER_REQUEST: usize = 20;
+/// Longest SQL text kept per entry.
+const MAX_SQL_LEN: usize = 1000;
+/// VM instructions between deadline checks.
+const PROGRESS_OPS: i32 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Key {
+    Task(tokio::task::Id),
+    Thread(ThreadId),
+}
+
+thread_local! {
+    /// The request a blocking-pool thread is doing database work for
+    static ON_BEHALF: Cell<Option<Key>> = const { Cell::new(None) };
+}
+
+fn current_key() -> Key {
+    if let Some(key) = ON_BEHALF.get() {
+        return key;
+    }
+    match tokio::task::try_id() {
+        Some(id) => Key::Task(id),
+        None => Key::Thread(thread::current().id()),
+    }
+}
+
+/// The request (or thread) asking for database work, to hand to `on_behalf`.
+#[derive(Debug, Clone, Copy)]
+pub struct Caller(Key);
+
+pub fn caller() -> Caller {
+    Caller(current_key())
+}
+
+/// Run `f` on this thread as `caller`'s database work: its deadline and
+/// slow-query log apply.
+pub fn on_behalf<T>(caller: Caller, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<Key>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            ON_BEHALF.set(self.0);
+        }
+    }
+    let _restore = Restore(ON_BEHALF.replace(Some(caller.0)));
+    f()
+}
+
+struct InFlight {
+    deadline: Option<Instant>,
+    slow_threshold: Option<Duration>,
+    slow: Vec<(String, Duration)>,
+}
+
+static IN_FLIGHT: LazyLock<Mutex<HashMap<Key, InFlight>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Lowest threshold any guard uses (µs): statements faster than this skip
+/// the `IN_FLIGHT` lookup entirely.
+static SLOW_FLOOR_US: AtomicU64 = AtomicU64::new(u64::MAX);
+
+/// Profile hook installed on every connection (`db::configure_connection`).
+fn profile(sql: &str, took: Duration) {
+    if (took.as_micros() as u64) < SLOW_FLOOR_US.load(Ordering::Relaxed) {
+        return;
+    }
+    let mut in_flight = IN_FLIGHT.lock().unwrap();
+    if let Some(request) = in_flight.get_mut(&current_key()) {
+        if request.slow_threshold.is_some_and(|t| took >= t) && request.slow.len() < MAX_PER_REQUEST {
+            request.slow.push((sql.chars().take(MAX_SQL_LEN).collect(), took));
+        }
+    }
+}
+
+/// Install the slow-query profiler on a connection.
+pub fn install_profiler(conn: &mut Connection) {
+    conn.profile(Some(profile));
+}
+
+/// Set while a connection is armed; tells `with_db` the deadline interrupted it.
+pub struct Armed {
+    tripped: Arc<AtomicBool>,
+}
+
+impl Armed {
+    pub fn tripped(&self) -> bool {
+        self.tripped.load(Ordering::Relaxed)
+    }
+}
+
+/// Interrupt statements on `conn` once the current request's deadline passes,
+/// and from then on turn commits into rollbacks, so work reported as timed
+/// out is never stored. None outside a request or without a timeout.
+pub fn arm(conn: &Connection) -> Option<Armed> {
+    let deadline = IN_FLIGHT.lock().unwrap().get(&current_key())?.deadline?;
+    let tripped = Arc::new(AtomicBool::new(false));
+    let flag = tripped.clone();
+    conn.progress_handler(
+        PROGRESS_OPS,
+        Some(move || {
+            let expired = Instant::now() >= deadline;
+            if expired {
+                flag.store(true, Ordering::Relaxed);
+            }
+            expired
+        }),
+    );
+    let vetoed = tripped.clone();
+    conn.commit_hook(Some(move || vetoed.load(Ordering::Relaxed)));
+    Some(Armed { tripped })
+}
+
+/// Remove the deadline check before the connection goes back to the pool.
+pub fn disarm(conn: &Connection) {
+    conn.progress_handler(0, None::<fn() -> bool>);
+    conn.commit_hook(None::<fn() -> bool>);
+}
+
+pub fn timeout_error() -> (Status, Json<ApiError>) {
+    (
+        Status::ServiceUnavailable,
+        Json(ApiError {
+            error: "The request took too long and its database work was stopped. Narrow the query and retry."
+                .to_string(),
+            code: "REQUEST_TIMEOUT".to_string(),
+            status: 503,
+        }),
+    )
+}
+
+/// Per-request timeouts and slow-query logging. Attach as a fairing and
+/// manage a clone for `GET /admin/slow-queries`.
+#[derive(Debug, Clone)]
+pub struct QueryGuard {
+    /// None disables the timeout
+    pub timeout: Option<Duration>,
+    /// (path fragment, timeout) overrides; the first fragment the path contains wins
+    pub route_timeouts: Vec<(String, Duration)>,
+    /// None disables the slow-query log
+    pub slow_threshold: Option<Duration>,
+    log: Arc<Mutex<VecDeque<SlowQuery>>>,
+}
+
+impl Default for QueryGuard {
+    fn default() -> Self {
+        QueryGuard::new(
+            Some(Duration::from_millis(DEFAULT_TIMEOUT_MS)),
+            Vec::new(),
+            Some(Duration::from_millis(DEFAULT_SLOW_QUERY_MS)),
+        )
+    }
+}
+
+impl QueryGuard {
+    pub fn new(timeout: Option<Duration>, route_timeouts: Vec<(String, Duration)>, slow_threshold: Option<Duration>) -> Self {
+        if let Some(threshold) = slow_threshold {
+            SLOW_FLOOR_US.fetch_min(threshold.as_micros() as u64, Ordering::Relaxed);
+        }
+        QueryGuard {
+            timeout,
+            route_timeouts,
+            slow_threshold,
+            log: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Read `REQUEST_TIMEOUT_MS` (default 30000), `REQUEST_TIMEOUT_ROUTES`
+    /// (`/tasks/search=5000,/export=120000`) and `SLOW_QUERY_MS` (default 500).
+    /// 0 turns the timeout or the slow-query log off.
+    pub fn from_env() -> Self {
+        let millis = |name: &str, default: u64| {
+            let ms = std::env::var(name).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(default);
+            (ms > 0).then(|| Duration::from_millis(ms))
+        };
+        let route_timeouts = std::env::var("REQUEST_TIMEOUT_ROUTES")
+            .map(|v| parse_route_timeouts(&v))
+            .unwrap_or_default();
+        QueryGuard::new(
+            millis("REQUEST_TIMEOUT_MS", DEFAULT_TIMEOUT_MS),
+            route_timeouts,
+            millis("SLOW_QUERY_MS", DEFAULT_SLOW_QUERY_MS),
+        )
+    }
+
+    fn timeout_for(&self, path: &str) -> Option<Duration> {
+        self.route_timeouts
+            .iter()
+            .find(|(fragment, _)| path.contains(fragment.as_str()))
+            .map(|(_, timeout)| *timeout)
+            .or(self.timeout)
+    }
+
+    /// Slow queries seen since startup, newest first.
+    pub fn slow_queries(&self) -> SlowQueriesResponse {
+        SlowQueriesResponse {
+            threshold_ms: self.slow_threshold.map(|t| t.as_millis() as u64),
+            request_timeout_ms: self.timeout.map(|t| t.as_millis() as u64),
+            queries: self.log.lock().unwrap().iter().rev().cloned().collect(),
+        }
+    }
+}
+
+/// Parse `fragment=ms` pairs; malformed entries are skipped.
+fn parse_route_timeouts(raw: &str) -> Vec<(String, Duration)> {
+    raw.split(',')
+        .filter_map(|pair| {
+            let (fragment, ms) = pair.split_once('=')?;
+            let ms: u64 = ms.trim().parse().ok()?;
+            let fragment = fragment.trim();
+            (!fragment.is_empty() && ms > 0).then(|| (fragment.to_string(), Duration::from_millis(ms)))
+        })
+        .collect()
+}
+
+#[rocket::async_trait]
+impl Fairing for QueryGuard {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request Timeout / Slow Query Log",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &mut Data<'_>) {
+        let deadline = self.timeout_for(req.uri().path().as_str()).map(|t| Instant::now() + t);
+        IN_FLIGHT.lock().unwrap().insert(
+            current_key(),
+            InFlight {
+                deadline,
+                slow_threshold: self.slow_threshold,
+                slow: Vec::new(),
+            },
+        );
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, _: &mut Response<'r>) {
+        let Some(request) = IN_FLIGHT.lock().unwrap().remove(&current_key()) else {
+            return;
+        };
+        if request.slow.is_empty() {
+            return;
+        }
+        let route = req.route().map(|r| r.uri.to_string()).unwrap_or_else(|| req.uri().path().to_string());
+        let mut log = self.log.lock().unwrap();
+        for (sql, took) in request.slow {
+            let duration_ms = took.as_secs_f64() * 1000.0;
+            eprintln!("🐢 Slow query ({:.1} ms) on {} {}: {}", duration_ms, req.method(), route, sql);
+            if log.len() >= MAX_LOGGED {
+                log.pop_front();
+            }
+            log.push_back(SlowQuery {
+                method: req.method().to_string(),
+                route: route.clone(),
+                sql,
+                duration_ms,
+                at: Utc::now().to_rfc3339(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_route_timeouts() {
+        let parsed = parse_route_timeouts("/tasks/search=5000, /export = 120000,bad,/x=0,=5");
+        assert_eq!(
+            parsed,
+            vec![
+                ("/tasks/search".to_string(), Duration::from_millis(5000)),
+                ("/export".to_string(), Duration::from_millis(120_000)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deadline_interrupts_query() {
+        let conn = Connection::open_in_memory().unwrap();
+        IN_FLIGHT.lock().unwrap().insert(
+            current_key(),
+            InFlight {
+                deadline: Some(Instant::now()),
+                slow_threshold: None,
+                slow: Vec::new(),
+            },
+        );
+        let armed = arm(&conn).expect("request has a deadline");
+        let result = conn.query_row(
+            "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 100000000) SELECT COUNT(*) FROM n",
+            [],
+            |row| row.get::<_, i64>(0),
+        );
+        assert!(result.is_err());
+        assert!(armed.tripped());
+
+        // Work that carries on past the deadline is rolled back, not committed
+        let err = conn.execute_batch("CREATE TABLE t (x INTEGER)").unwrap_err();
+        assert_eq!(err.sqlite_error_code(), Some(rusqlite::ErrorCode::ConstraintViolation), "{}", err);
+        assert!(conn.prepare("SELECT x FROM t").is_err());
+
+        disarm(&conn);
+        IN_FLIGHT.lock().unwrap().remove(&current_key());
+        let count: i64 = conn
+            .query_row("WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 1000) SELECT COUNT(*) FROM n", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1000);
+    }
+}
@@ -1,84 +1,335 @@
 use std::collections::HashMap;
-use std::sync::Mutex;
-use std::time::{Duration, Instant};
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use rocket::fairing::{Fairing, Info, Kind};
-use rocket::http::Header;
+use rocket::figment::Figment;
+use rocket::http::{Header, Method, Status};
 use rocket::request::{FromRequest, Outcome, Request};
 use rocket::Response;
+use serde::Deserialize;
+
+use crate::db::DbPool;
+use crate::ip_allowlist::Cidr;
+use crate::models::{RateLimitBucket, RateLimiterStats};
 
 /// Fixed-window rate limiter keyed by arbitrary string (e.g. client IP).
 ///
-/// Each key gets a counter that resets every `window` duration.
+/// Each key gets a counter that resets every `window` duration. The counters
+/// live in a `RateLimitStore`: in memory by default, or in the database.
 pub struct RateLimiter {
     window: Duration,
     default_limit: u64,
+    store: Box<dyn RateLimitStore>,
+    /// Checks refused because the store couldn't be reached
+    store_errors: AtomicU64,
+}
+
+/// One hit against a key's window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowHit {
+    /// Whether the hit was under the limit (and so counted).
+    pub allowed: bool,
+    /// Hits counted in the window, including this one if allowed.
+    pub count: u64,
+    /// How long ago the window opened.
+    pub age: Duration,
+}
+
+/// Where a `RateLimiter` keeps its counters. A key's window opens at its
+/// first hit and starts over once `window` has passed since.
+pub trait RateLimitStore: Send + Sync {
+    /// Count a hit of `cost` requests for `key`, unless that would take it
+    /// past `limit` this window — then nothing is counted. An error means
+    /// the store couldn't be reached; the limiter then refuses the request.
+    fn hit(&self, key: &str, cost: u64, limit: u64, window: Duration) -> Result<WindowHit, String>;
+
+    /// Every key with a window still open, as (key, count, age).
+    fn windows(&self, window: Duration) -> Vec<(String, u64, Duration)>;
+
+    /// Forget windows that have closed.
+    fn prune(&self, window: Duration);
+}
+
+/// How often `spawn_prune` sweeps closed windows out of the stores.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Counters in process memory: fast, but reset on restart and not shared
+/// between replicas.
+#[derive(Default)]
+pub struct MemoryStore {
     /// key → (window_start, count)
     buckets: Mutex<HashMap<String, (Instant, u64)>>,
 }
 
-/// Client IP address extracted from the request.
+impl RateLimitStore for MemoryStore {
+    fn hit(&self, key: &str, cost: u64, limit: u64, window: Duration) -> Result<WindowHit, String> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+
+        let entry = buckets.entry(key.to_string()).or_insert_with(|| (now, 0));
+
+        // If the window has elapsed, reset.
+        if now.duration_since(entry.0) >= window {
+            *entry = (now, 0);
+        }
+
+        let allowed = entry.1 + cost <= limit;
+        if allowed {
+            entry.1 += cost;
+        }
+        Ok(WindowHit {
+            allowed,
+            count: entry.1,
+            age: now.duration_since(entry.0),
+        })
+    }
+
+    fn windows(&self, window: Duration) -> Vec<(String, u64, Duration)> {
+        let now = Instant::now();
+        let buckets = self.buckets.lock().unwrap();
+        buckets
+            .iter()
+            .map(|(key, (start, count))| (key.clone(), *count, now.duration_since(*start)))
+            .filter(|(_, _, age)| *age < window)
+            .collect()
+    }
+
+    fn prune(&self, window: Duration) {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        buckets.retain(|_, (start, _)| now.duration_since(*start) < window);
+    }
+}
+
+/// Counters in the `rate_limit_windows` table, so limits survive restarts and
+/// are shared by every server process using the database. `scope` keeps each
+/// limiter's keys apart.
+pub struct SqliteStore {
+    db: DbPool,
+    scope: String,
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as i64).unwrap_or(0)
+}
+
+impl SqliteStore {
+    pub fn new(db: DbPool, scope: &str) -> Self {
+        SqliteStore {
+            db,
+            scope: scope.to_string(),
+        }
+    }
+}
+
+impl RateLimitStore for SqliteStore {
+    fn hit(&self, key: &str, cost: u64, limit: u64, window: Duration) -> Result<WindowHit, String> {
+        let conn = self.db.get().map_err(|e| e.to_string())?;
+        let now = now_ms();
+        let (start, count, allowed): (i64, i64, bool) = conn
+            .query_row(
+                "INSERT INTO rate_limit_windows (scope, key, window_start_ms, count, last_hit_allowed)
+                 VALUES (?1, ?2, ?3, CASE WHEN ?6 <= ?5 THEN ?6 ELSE 0 END, ?6 <= ?5)
+                 ON CONFLICT(scope, key) DO UPDATE SET
+                     count = CASE WHEN ?3 - window_start_ms >= ?4 THEN CASE WHEN ?6 <= ?5 THEN ?6 ELSE 0 END
+                                  WHEN count + ?6 <= ?5 THEN count + ?6 ELSE count END,
+                     last_hit_allowed = CASE WHEN ?3 - window_start_ms >= ?4 THEN ?6 <= ?5 ELSE count + ?6 <= ?5 END,
+                     window_start_ms = CASE WHEN ?3 - window_start_ms >= ?4 THEN ?3 ELSE window_start_ms END
+                 RETURNING window_start_ms, count, last_hit_allowed",
+                rusqlite::params![self.scope, key, now, window.as_millis() as i64, limit as i64, cost as i64],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(WindowHit {
+            allowed,
+            count: count as u64,
+            age: Duration::from_millis(now.saturating_sub(start).max(0) as u64),
+        })
+    }
+
+    fn windows(&self, window: Duration) -> Vec<(String, u64, Duration)> {
+        let now = now_ms();
+        let Ok(conn) = self.db.get() else {
+            return Vec::new();
+        };
+        conn.prepare("SELECT key, count, window_start_ms FROM rate_limit_windows WHERE scope = ?1 AND ?2 - window_start_ms < ?3")
+            .and_then(|mut stmt| {
+                stmt.query_map(rusqlite::params![self.scope, now, window.as_millis() as i64], |row| {
+                    let start: i64 = row.get(2)?;
+                    Ok((row.get(0)?, row.get::<_, i64>(1)? as u64, Duration::from_millis(now.saturating_sub(start).max(0) as u64)))
+                })?
+                .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn prune(&self, window: Duration) {
+        if let Ok(conn) = self.db.get() {
+            let _ = conn.execute(
+                "DELETE FROM rate_limit_windows WHERE scope = ?1 AND ?2 - window_start_ms >= ?3",
+                rusqlite::params![self.scope, now_ms(), window.as_millis() as i64],
+            );
+        }
+    }
+}
+
+/// The store `RATE_LIMIT_STORE` asks for: `memory` (default) or `sqlite`.
+/// Redis and other shared stores plug in by implementing `RateLimitStore`.
+pub fn store_from_env(db: &DbPool, scope: &str) -> Result<Box<dyn RateLimitStore>, String> {
+    match std::env::var("RATE_LIMIT_STORE").unwrap_or_default().trim().to_lowercase().as_str() {
+        "" | "memory" => Ok(Box::new(MemoryStore::default())),
+        "sqlite" => Ok(Box::new(SqliteStore::new(db.clone(), scope))),
+        other => Err(format!("RATE_LIMIT_STORE: unknown store '{}' (use memory or sqlite)", other)),
+    }
+}
+
+/// Proxies whose forwarding headers are believed, from `TRUSTED_PROXIES`
+/// (comma-separated addresses or CIDR ranges, e.g. `10.0.0.0/8,127.0.0.1`).
+/// Empty by default: forwarding headers are then ignored, since any client
+/// can set them.
 ///
-/// Checks (in order):
-/// 1. `X-Forwarded-For` header (first IP — set by reverse proxies / Cloudflare Tunnel)
-/// 2. `X-Real-Ip` header
-/// 3. Socket peer address
+/// The list travels in Rocket's figment under `trusted_proxies` (see
+/// `apply`), so every part of the server reads the same setting.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies(Vec<Cidr>);
+
+impl TrustedProxies {
+    pub fn from_env() -> Result<Self, String> {
+        Self::parse(&std::env::var("TRUSTED_PROXIES").unwrap_or_default())
+    }
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| Cidr::parse(s).ok_or_else(|| format!("TRUSTED_PROXIES: '{}' isn't an IP address or CIDR range", s)))
+            .collect::<Result<_, _>>()
+            .map(TrustedProxies)
+    }
+
+    /// Record the list in the figment the server is built from.
+    pub fn apply(&self, figment: Figment) -> Figment {
+        figment.merge(("trusted_proxies", self.0.iter().map(|c| c.to_string()).collect::<Vec<_>>()))
+    }
+
+    /// The list the server was built with.
+    fn of(request: &Request<'_>) -> Self {
+        let ranges: Vec<String> = request.rocket().figment().extract_inner("trusted_proxies").unwrap_or_default();
+        TrustedProxies(ranges.iter().filter_map(|r| Cidr::parse(r)).collect())
+    }
+
+    fn contains(&self, ip: IpAddr) -> bool {
+        self.0.iter().any(|cidr| cidr.contains(ip))
+    }
+
+    /// The client behind `peer`: the peer itself unless it's a trusted proxy;
+    /// otherwise the rightmost `X-Forwarded-For` hop that isn't one (or,
+    /// without that header, `X-Real-Ip`).
+    fn resolve(&self, peer: IpAddr, forwarded_for: &[&str], real_ip: Option<&str>) -> IpAddr {
+        if !self.contains(peer) {
+            return peer;
+        }
+        if forwarded_for.is_empty() {
+            return real_ip.and_then(|ip| ip.trim().parse().ok()).unwrap_or(peer);
+        }
+        let mut client = peer;
+        for hop in forwarded_for.iter().flat_map(|v| v.split(',')).rev() {
+            // A hop we can't read ends the chain we can vouch for
+            let Ok(ip) = hop.trim().parse::<IpAddr>() else {
+                break;
+            };
+            client = ip;
+            if !self.contains(ip) {
+                break;
+            }
+        }
+        client
+    }
+}
+
+/// Client IP address of the request: the socket peer, or — when the peer is
+/// one of the `TrustedProxies` — the client it forwarded for, from
+/// `X-Forwarded-For` (rightmost untrusted hop) or `X-Real-Ip`.
 ///
-/// Falls back to "unknown" if none are available.
+/// Falls back to "unknown" when there's no peer address.
 #[derive(Debug, Clone)]
 pub struct ClientIp(pub String);
 
+impl ClientIp {
+    pub fn of(request: &Request<'_>) -> ClientIp {
+        request
+            .local_cache(|| {
+                let Some(peer) = request.remote().map(|addr| addr.ip()) else {
+                    return ClientIp("unknown".to_string());
+                };
+                let forwarded_for: Vec<&str> = request.headers().get("X-Forwarded-For").collect();
+                let real_ip = request.headers().get_one("X-Real-Ip");
+                ClientIp(TrustedProxies::of(request).resolve(peer, &forwarded_for, real_ip).to_string())
+            })
+            .clone()
+    }
+}
+
 #[rocket::async_trait]
 impl<'r> FromRequest<'r> for ClientIp {
     type Error = std::convert::Infallible;
 
     async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
-        // 1. X-Forwarded-For (first entry is the real client)
-        if let Some(xff) = request.headers().get_one("X-Forwarded-For") {
-            if let Some(first_ip) = xff.split(',').next() {
-                let ip = first_ip.trim();
-                if !ip.is_empty() {
-                    return Outcome::Success(ClientIp(ip.to_string()));
-                }
-            }
-        }
-
-        // 2. X-Real-Ip
-        if let Some(real_ip) = request.headers().get_one("X-Real-Ip") {
-            let ip = real_ip.trim();
-            if !ip.is_empty() {
-                return Outcome::Success(ClientIp(ip.to_string()));
-            }
-        }
-
-        // 3. Socket peer address
-        if let Some(addr) = request.client_ip() {
-            return Outcome::Success(ClientIp(addr.to_string()));
-        }
-
-        Outcome::Success(ClientIp("unknown".to_string()))
+        Outcome::Success(ClientIp::of(request))
     }
 }
 
 /// Result of a rate limit check.
 /// Stored in request-local state so the response fairing can attach headers.
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct RateLimitResult {
     /// Whether the request is allowed.
     pub allowed: bool,
     /// Configured limit for this key.
     pub limit: u64,
-    /// Requests remaining in the current window (used by headers fairing + tests).
-    #[allow(dead_code)]
+    /// Requests remaining in the current window.
     pub remaining: u64,
     /// Seconds until the current window resets.
     pub reset_secs: u64,
 }
 
-/// Rocket fairing that attaches rate limit headers to every response.
-/// Reads `RateLimitResult` from request-local state (set by the auth guard).
-/// Currently unused — will be wired up when more endpoints need rate limit headers.
-#[allow(dead_code)]
+/// The request's rate limit check, in request-local state: filled in by the
+/// `BoardToken` guard for limited writes, and by handlers (which take it as a
+/// guard) for board creation, then read by `RateLimitHeaders`.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitSlot(Arc<Mutex<Option<RateLimitResult>>>);
+
+impl RateLimitSlot {
+    /// The request's slot.
+    pub fn of(request: &Request<'_>) -> RateLimitSlot {
+        request.local_cache(RateLimitSlot::default).clone()
+    }
+
+    pub fn set(&self, result: RateLimitResult) {
+        *self.0.lock().unwrap() = Some(result);
+    }
+
+    pub fn get(&self) -> Option<RateLimitResult> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RateLimitSlot {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(RateLimitSlot::of(request))
+    }
+}
+
+/// Rocket fairing that attaches rate limit headers to every rate-limited
+/// response, from the request's `RateLimitSlot`, and adds `Retry-After` when
+/// the request was refused.
 pub struct RateLimitHeaders;
 
 #[rocket::async_trait]
@@ -91,79 +342,391 @@ impl Fairing for RateLimitHeaders {
     }
 
     async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
-        if let Some(rl) = request.local_cache(|| Option::<RateLimitResult>::None) {
+        if let Some(rl) = RateLimitSlot::of(request).get() {
             response.set_header(Header::new("X-RateLimit-Limit", rl.limit.to_string()));
             response.set_header(Header::new(
                 "X-RateLimit-Remaining",
                 rl.remaining.to_string(),
             ));
             response.set_header(Header::new("X-RateLimit-Reset", rl.reset_secs.to_string()));
+            if !rl.allowed {
+                response.set_header(Header::new("Retry-After", rl.reset_secs.max(1).to_string()));
+            }
         }
     }
 }
 
-impl RateLimiter {
-    /// Create a new rate limiter with the given window duration and default limit.
-    pub fn new(window: Duration, default_limit: u64) -> Self {
-        RateLimiter {
-            window,
-            default_limit,
-            buckets: Mutex::new(HashMap::new()),
+/// The groups of routes with a rate limit of their own. Board creation and
+/// view password attempts are counted per client IP; the write groups per key (by its hash — for share
+/// links, the link itself), so one runaway agent can't flood a board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RouteGroup {
+    /// Creating and importing boards
+    BoardCreation,
+    /// Every other write to a board: tasks, columns, dependencies,
+    /// attachments, webhooks, keys and settings. Bulk imports count each row.
+    TaskWrites,
+    /// Posting, editing, deleting and collapsing comments
+    Comments,
+    /// `/tasks/batch` calls, however many operations they carry
+    Batch,
+    /// View password attempts (`/boards/<id>/view-session`)
+    ViewSession,
+}
+
+impl RouteGroup {
+    pub const ALL: [RouteGroup; 5] = [
+        RouteGroup::BoardCreation,
+        RouteGroup::TaskWrites,
+        RouteGroup::Comments,
+        RouteGroup::Batch,
+        RouteGroup::ViewSession,
+    ];
+
+    /// The group's name in configuration (`rate_limits.<name>`,
+    /// `RATE_LIMIT_<NAME>`), and its store scope.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RouteGroup::BoardCreation => "board_creation",
+            RouteGroup::TaskWrites => "task_writes",
+            RouteGroup::Comments => "comments",
+            RouteGroup::Batch => "batch",
+            RouteGroup::ViewSession => "view_session",
         }
     }
 
-    /// Check (and consume) one request for `key_id` using the default limit.
-    pub fn check_default(&self, key_id: &str) -> RateLimitResult {
-        self.check(key_id, self.default_limit)
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|g| g.as_str() == name)
     }
 
-    /// Check (and consume) one request for `key_id` with the given `limit`.
-    ///
-    /// Returns a `RateLimitResult` indicating whether the request is allowed
-    /// and the current rate limit state for response headers.
-    pub fn check(&self, key_id: &str, limit: u64) -> RateLimitResult {
-        let now = Instant::now();
-        let mut buckets = self.buckets.lock().unwrap();
+    /// The limit that applies unless configured otherwise.
+    fn default_rule(self) -> RateLimitRule {
+        let (limit, window_secs) = match self {
+            RouteGroup::BoardCreation => (10, 3600),
+            RouteGroup::TaskWrites => (600, 60),
+            RouteGroup::Comments => (300, 60),
+            RouteGroup::Batch => (60, 60),
+            RouteGroup::ViewSession => (20, 900),
+        };
+        RateLimitRule { limit, window_secs }
+    }
 
-        let entry = buckets
-            .entry(key_id.to_string())
-            .or_insert_with(|| (now, 0));
+    /// The older single-number setting for the group's limit, still honoured.
+    fn legacy_env(self) -> Option<&'static str> {
+        match self {
+            RouteGroup::BoardCreation => Some("BOARD_RATE_LIMIT"),
+            RouteGroup::TaskWrites => Some("TASK_WRITE_RATE_LIMIT"),
+            RouteGroup::Comments => Some("COMMENT_RATE_LIMIT"),
+            RouteGroup::Batch => Some("BATCH_RATE_LIMIT"),
+            RouteGroup::ViewSession => None,
+        }
+    }
 
-        // If the window has elapsed, reset.
-        if now.duration_since(entry.0) >= self.window {
-            *entry = (now, 0);
+    /// The write group of a request from its method and route pattern;
+    /// `None` for reads and for writes outside a board.
+    pub fn of_write(method: Method, route: &str) -> Option<Self> {
+        if matches!(method, Method::Get | Method::Head | Method::Options) {
+            return None;
+        }
+        let path = route.split('?').next().unwrap_or(route);
+        if !path.contains("/boards/<board_id>") {
+            return None;
         }
+        if path.ends_with("/tasks/batch") {
+            Some(RouteGroup::Batch)
+        } else if path.contains("/comment") {
+            Some(RouteGroup::Comments)
+        } else {
+            Some(RouteGroup::TaskWrites)
+        }
+    }
+}
 
-        let reset_secs = self
-            .window
-            .checked_sub(now.duration_since(entry.0))
-            .unwrap_or(Duration::ZERO)
-            .as_secs();
+/// `limit` requests per `window_secs`; a limit of `0` turns the group's
+/// limit off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct RateLimitRule {
+    pub limit: u64,
+    pub window_secs: u64,
+}
+
+/// A group's entry under `rate_limits` in Rocket's configuration; either
+/// field may be left out to keep the default.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleOverride {
+    limit: Option<u64>,
+    window_secs: Option<u64>,
+}
+
+/// The limit and window of every route group. Later sources win:
+///
+/// 1. built-in defaults (10 boards an hour per IP; 600 task writes, 300
+///    comments and 60 batch calls a minute per key; 20 view password
+///    attempts every 15 minutes per IP)
+/// 2. `BOARD_RATE_LIMIT`, `TASK_WRITE_RATE_LIMIT`, `COMMENT_RATE_LIMIT` and
+///    `BATCH_RATE_LIMIT` (limits only)
+/// 3. Rocket's configuration: `[default.rate_limits.<group>]` tables in
+///    `Rocket.toml`, or `ROCKET_RATE_LIMITS={task_writes={limit=100}}`
+/// 4. `RATE_LIMIT_<GROUP>=<limit>` or `<limit>/<window_secs>`, e.g.
+///    `RATE_LIMIT_BOARD_CREATION=50/86400`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    rules: HashMap<RouteGroup, RateLimitRule>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            rules: RouteGroup::ALL.into_iter().map(|g| (g, g.default_rule())).collect(),
+        }
+    }
+}
 
-        if entry.1 >= limit {
-            RateLimitResult {
-                allowed: false,
-                limit,
-                remaining: 0,
-                reset_secs,
+impl RateLimitConfig {
+    pub fn from_figment(figment: &Figment) -> Result<Self, String> {
+        Self::from_sources(figment, |name| std::env::var(name).ok())
+    }
+
+    fn from_sources(figment: &Figment, env: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        let mut config = RateLimitConfig::default();
+
+        for (group, name) in RouteGroup::ALL.into_iter().filter_map(|g| Some((g, g.legacy_env()?))) {
+            let Some(value) = env(name).filter(|v| !v.trim().is_empty()) else {
+                continue;
+            };
+            let limit = value
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format!("{}: expected a number, got '{}'", name, value.trim()))?;
+            config.rules.get_mut(&group).unwrap().limit = limit;
+        }
+
+        if figment.contains("rate_limits") {
+            let overrides: HashMap<String, RuleOverride> =
+                figment.extract_inner("rate_limits").map_err(|e| format!("rate_limits: {}", e))?;
+            for (name, rule) in overrides {
+                let group = RouteGroup::parse(&name).ok_or_else(|| format!("rate_limits: unknown route group '{}'", name))?;
+                let current = config.rule(group);
+                config = config.with_rule(
+                    group,
+                    rule.limit.unwrap_or(current.limit),
+                    rule.window_secs.unwrap_or(current.window_secs),
+                );
             }
-        } else {
-            entry.1 += 1;
-            RateLimitResult {
-                allowed: true,
-                limit,
-                remaining: limit.saturating_sub(entry.1),
-                reset_secs,
+        }
+
+        for group in RouteGroup::ALL {
+            let name = format!("RATE_LIMIT_{}", group.as_str().to_uppercase());
+            let Some(value) = env(&name).filter(|v| !v.trim().is_empty()) else {
+                continue;
+            };
+            let bad = || format!("{}: expected <limit> or <limit>/<window_secs>, got '{}'", name, value.trim());
+            let (limit, window_secs) = match value.trim().split_once('/') {
+                Some((limit, window)) => (
+                    limit.trim().parse().map_err(|_| bad())?,
+                    window.trim().parse().map_err(|_| bad())?,
+                ),
+                None => (value.trim().parse().map_err(|_| bad())?, config.rule(group).window_secs),
+            };
+            config = config.with_rule(group, limit, window_secs);
+        }
+
+        if let Some(group) = RouteGroup::ALL.into_iter().find(|g| config.rule(*g).window_secs == 0) {
+            return Err(format!("rate_limits.{}: window_secs must be at least 1", group.as_str()));
+        }
+        Ok(config)
+    }
+
+    pub fn with_rule(mut self, group: RouteGroup, limit: u64, window_secs: u64) -> Self {
+        self.rules.insert(group, RateLimitRule { limit, window_secs });
+        self
+    }
+
+    pub fn rule(&self, group: RouteGroup) -> RateLimitRule {
+        self.rules.get(&group).copied().unwrap_or_else(|| group.default_rule())
+    }
+}
+
+/// One `RateLimiter` per route group, built from a `RateLimitConfig`; kept in
+/// managed state and consulted through the `BoardToken` guard (writes) and
+/// `IpRateLimit` (board creation, view passwords).
+#[derive(Clone)]
+pub struct RateLimits {
+    limiters: Arc<HashMap<RouteGroup, RateLimiter>>,
+}
+
+impl RateLimits {
+    /// Limits counting in the store `store` gives each group.
+    pub fn with_stores(config: &RateLimitConfig, store: impl Fn(RouteGroup) -> Box<dyn RateLimitStore>) -> Self {
+        let limiters = RouteGroup::ALL
+            .into_iter()
+            .map(|group| {
+                let rule = config.rule(group);
+                (group, RateLimiter::with_store(Duration::from_secs(rule.window_secs), rule.limit, store(group)))
+            })
+            .collect();
+        RateLimits { limiters: Arc::new(limiters) }
+    }
+
+    fn limiter(&self, group: RouteGroup) -> &RateLimiter {
+        &self.limiters[&group]
+    }
+
+    pub fn rule(&self, group: RouteGroup) -> RateLimitRule {
+        let limiter = self.limiter(group);
+        RateLimitRule {
+            limit: limiter.default_limit,
+            window_secs: limiter.window.as_secs(),
+        }
+    }
+
+    /// Check (and consume) `cost` requests of `group` for `key` at once, for
+    /// routes that write many things per call; `None` when the limit is off.
+    pub fn charge(&self, group: RouteGroup, key: &str, cost: u64) -> Option<RateLimitResult> {
+        let limiter = self.limiter(group);
+        (limiter.default_limit > 0).then(|| limiter.check_cost(key, cost, limiter.default_limit))
+    }
+
+    /// `charge` on the blocking pool, for request guards and handlers: the
+    /// store may be the database.
+    pub async fn charge_async(&self, group: RouteGroup, key: &str, cost: u64) -> Option<RateLimitResult> {
+        let (limits, key) = (self.clone(), key.to_string());
+        crate::db::blocking(move || limits.charge(group, &key, cost)).await
+    }
+
+    pub fn stats(&self, group: RouteGroup) -> RateLimiterStats {
+        self.limiter(group).stats()
+    }
+
+    /// Forget closed windows in every group's store.
+    pub fn prune_stale(&self) {
+        for limiter in self.limiters.values() {
+            limiter.prune_stale();
+        }
+    }
+}
+
+/// Sweep closed windows out of every store in the background, so clients
+/// that stop sending don't stay in memory (or in the table) for good.
+pub fn spawn_prune(limits: RateLimits) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(PRUNE_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let limits = limits.clone();
+            if let Err(e) = tokio::task::spawn_blocking(move || limits.prune_stale()).await {
+                eprintln!("⚠️  Rate limit prune task panicked: {}", e);
+            }
+        }
+    });
+}
+
+/// Request guard for handlers limited per client IP. The handler calls
+/// `check` once it has validated its input, so a rejected request doesn't use
+/// up the caller's allowance; the result is kept for the rate limit headers.
+pub struct IpRateLimit<'r> {
+    limits: &'r RateLimits,
+    client_ip: ClientIp,
+    slot: RateLimitSlot,
+}
+
+impl IpRateLimit<'_> {
+    /// Count the request against `group`; `None` when the group's limit is off.
+    pub async fn check(&self, group: RouteGroup) -> Option<RateLimitResult> {
+        let result = self.limits.charge_async(group, &self.client_ip.0, 1).await?;
+        self.slot.set(result.clone());
+        Some(result)
+    }
+
+    pub fn rule(&self, group: RouteGroup) -> RateLimitRule {
+        self.limits.rule(group)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for IpRateLimit<'r> {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.rocket().state::<RateLimits>() {
+            Some(limits) => Outcome::Success(IpRateLimit {
+                limits,
+                client_ip: ClientIp::of(request),
+                slot: RateLimitSlot::of(request),
+            }),
+            None => Outcome::Error((Status::InternalServerError, ())),
+        }
+    }
+}
+
+impl RateLimiter {
+    /// Create a rate limiter with the given window duration and default
+    /// limit, counting in `store`.
+    pub fn with_store(window: Duration, default_limit: u64, store: Box<dyn RateLimitStore>) -> Self {
+        RateLimiter {
+            window,
+            default_limit,
+            store,
+            store_errors: AtomicU64::new(0),
+        }
+    }
+
+    /// Check (and consume) `cost` requests for `key_id` against `limit`: all
+    /// of them are counted, or (over the limit) none. Returns whether they're
+    /// allowed and the current rate limit state for response headers.
+    ///
+    /// If the store can't be reached the request is refused, to be retried
+    /// in a second, rather than let through unlimited; the error is logged
+    /// and counted in the limiter's stats.
+    pub fn check_cost(&self, key_id: &str, cost: u64, limit: u64) -> RateLimitResult {
+        let hit = match self.store.hit(key_id, cost, limit, self.window) {
+            Ok(hit) => hit,
+            Err(e) => {
+                self.store_errors.fetch_add(1, Ordering::Relaxed);
+                eprintln!("⚠️  Rate limit store unavailable, refusing the request: {}", e);
+                return RateLimitResult {
+                    allowed: false,
+                    limit,
+                    remaining: 0,
+                    reset_secs: 1,
+                };
             }
+        };
+        RateLimitResult {
+            allowed: hit.allowed,
+            limit,
+            remaining: if hit.allowed { limit.saturating_sub(hit.count) } else { 0 },
+            reset_secs: self.window.checked_sub(hit.age).unwrap_or(Duration::ZERO).as_secs(),
+        }
+    }
+
+    /// The limiter's settings and every key with a live window, busiest first.
+    pub fn stats(&self) -> RateLimiterStats {
+        let mut clients: Vec<RateLimitBucket> = self
+            .store
+            .windows(self.window)
+            .into_iter()
+            .map(|(key, count, age)| RateLimitBucket {
+                key,
+                count,
+                remaining: self.default_limit.saturating_sub(count),
+                reset_secs: self.window.saturating_sub(age).as_secs(),
+                limited: count >= self.default_limit,
+            })
+            .collect();
+        clients.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+        RateLimiterStats {
+            window_seconds: self.window.as_secs(),
+            limit: self.default_limit,
+            store_errors: self.store_errors.load(Ordering::Relaxed),
+            clients,
         }
     }
 
-    /// Periodically prune stale entries to prevent unbounded memory growth.
-    #[allow(dead_code)]
+    /// Forget closed windows; run by `spawn_prune`.
     pub fn prune_stale(&self) {
-        let now = Instant::now();
-        let mut buckets = self.buckets.lock().unwrap();
-        buckets.retain(|_, (start, _)| now.duration_since(*start) < self.window);
+        self.store.prune(self.window);
     }
 }
 
@@ -171,10 +734,23 @@ impl RateLimiter {
 mod tests {
     use super::*;
 
+    fn in_memory(window_secs: u64, limit: u64) -> RateLimiter {
+        RateLimiter::with_store(Duration::from_secs(window_secs), limit, Box::new(MemoryStore::default()))
+    }
+
+    fn limits_in_memory(config: &RateLimitConfig) -> RateLimits {
+        RateLimits::with_stores(config, |_| Box::new(MemoryStore::default()))
+    }
+
+    /// One request for `key` against the limiter's own limit.
+    fn hit(rl: &RateLimiter, key: &str) -> RateLimitResult {
+        rl.check_cost(key, 1, rl.default_limit)
+    }
+
     #[test]
     fn allows_under_limit() {
-        let rl = RateLimiter::new(Duration::from_secs(60), 10);
-        let r = rl.check("key1", 10);
+        let rl = in_memory(60, 10);
+        let r = hit(&rl, "key1");
         assert!(r.allowed);
         assert_eq!(r.remaining, 9);
         assert_eq!(r.limit, 10);
@@ -182,33 +758,228 @@ mod tests {
 
     #[test]
     fn blocks_at_limit() {
-        let rl = RateLimiter::new(Duration::from_secs(60), 5);
+        let rl = in_memory(60, 5);
         for _ in 0..5 {
-            rl.check("key1", 5);
+            hit(&rl, "key1");
         }
-        let r = rl.check("key1", 5);
+        let r = hit(&rl, "key1");
         assert!(!r.allowed);
         assert_eq!(r.remaining, 0);
     }
 
     #[test]
     fn separate_keys_independent() {
-        let rl = RateLimiter::new(Duration::from_secs(60), 5);
+        let rl = in_memory(60, 5);
         for _ in 0..5 {
-            rl.check("key1", 5);
+            hit(&rl, "key1");
+        }
+        assert!(!hit(&rl, "key1").allowed);
+        assert!(hit(&rl, "key2").allowed);
+    }
+
+    /// A store that can't be reached.
+    struct DownStore;
+
+    impl RateLimitStore for DownStore {
+        fn hit(&self, _key: &str, _cost: u64, _limit: u64, _window: Duration) -> Result<WindowHit, String> {
+            Err("database is locked".to_string())
+        }
+
+        fn windows(&self, _window: Duration) -> Vec<(String, u64, Duration)> {
+            Vec::new()
         }
-        assert!(!rl.check("key1", 5).allowed);
-        assert!(rl.check("key2", 5).allowed);
+
+        fn prune(&self, _window: Duration) {}
+    }
+
+    #[test]
+    fn store_errors_fail_closed() {
+        let rl = RateLimiter::with_store(Duration::from_secs(60), 5, Box::new(DownStore));
+        let r = hit(&rl, "key1");
+        assert!(!r.allowed);
+        assert_eq!((r.remaining, r.reset_secs), (0, 1));
+        hit(&rl, "key2");
+        assert_eq!(rl.stats().store_errors, 2);
+        assert_eq!(in_memory(60, 5).stats().store_errors, 0);
     }
 
     #[test]
-    fn check_default_uses_default_limit() {
-        let rl = RateLimiter::new(Duration::from_secs(60), 3);
-        assert!(rl.check_default("ip1").allowed); // 1 of 3
-        assert!(rl.check_default("ip1").allowed); // 2 of 3
-        assert!(rl.check_default("ip1").allowed); // 3 of 3
-        assert!(!rl.check_default("ip1").allowed); // 4 - blocked
-        // Different IP is independent
-        assert!(rl.check_default("ip2").allowed);
+    fn prune_stale_forgets_closed_windows() {
+        let limits = limits_in_memory(&RateLimitConfig::default().with_rule(RouteGroup::TaskWrites, 5, 1));
+        limits.charge(RouteGroup::TaskWrites, "k1", 1);
+        limits.charge(RouteGroup::BoardCreation, "ip1", 1);
+        std::thread::sleep(Duration::from_millis(1100));
+        limits.prune_stale();
+        let store_len = |group| {
+            let limiter = limits.limiter(group);
+            limiter.store.windows(Duration::MAX).len()
+        };
+        assert_eq!(store_len(RouteGroup::TaskWrites), 0);
+        assert_eq!(store_len(RouteGroup::BoardCreation), 1, "still in its hour-long window");
+    }
+
+    #[test]
+    fn write_groups_limited_separately() {
+        let route = |r: &str| format!("/api/v1/boards/<board_id>/tasks{}", r);
+        assert_eq!(RouteGroup::of_write(Method::Post, &route("")), Some(RouteGroup::TaskWrites));
+        assert_eq!(RouteGroup::of_write(Method::Post, &route("/<task_id>/lock?<ttl>")), Some(RouteGroup::TaskWrites));
+        assert_eq!(RouteGroup::of_write(Method::Post, &route("/<task_id>/comment")), Some(RouteGroup::Comments));
+        assert_eq!(RouteGroup::of_write(Method::Patch, &route("/<task_id>/comments/<comment_id>")), Some(RouteGroup::Comments));
+        assert_eq!(RouteGroup::of_write(Method::Post, &route("/batch")), Some(RouteGroup::Batch));
+        assert_eq!(RouteGroup::of_write(Method::Get, &route("")), None);
+        assert_eq!(RouteGroup::of_write(Method::Patch, "/api/v1/boards/<board_id>"), Some(RouteGroup::TaskWrites));
+        assert_eq!(RouteGroup::of_write(Method::Post, "/api/v1/boards/<board_id>/dependencies"), Some(RouteGroup::TaskWrites));
+        assert_eq!(RouteGroup::of_write(Method::Post, "/api/v1/boards"), None);
+
+        let config = RateLimitConfig::default()
+            .with_rule(RouteGroup::TaskWrites, 2, 60)
+            .with_rule(RouteGroup::Comments, 1, 60)
+            .with_rule(RouteGroup::Batch, 0, 60);
+        let rl = limits_in_memory(&config);
+        assert!(rl.charge(RouteGroup::TaskWrites, "k1", 1).unwrap().allowed);
+        assert!(rl.charge(RouteGroup::TaskWrites, "k1", 1).unwrap().allowed);
+        assert!(!rl.charge(RouteGroup::TaskWrites, "k1", 1).unwrap().allowed);
+        assert!(rl.charge(RouteGroup::TaskWrites, "k2", 1).unwrap().allowed);
+        assert!(rl.charge(RouteGroup::Comments, "k1", 1).unwrap().allowed);
+        assert!(!rl.charge(RouteGroup::Comments, "k1", 1).unwrap().allowed);
+        assert!(rl.charge(RouteGroup::Batch, "k1", 1).is_none());
+    }
+
+    #[test]
+    fn charges_count_all_or_nothing() {
+        let rl = limits_in_memory(&RateLimitConfig::default().with_rule(RouteGroup::TaskWrites, 5, 60));
+        let db = test_db();
+        let sqlite = RateLimiter::with_store(Duration::from_secs(60), 5, Box::new(SqliteStore::new(db, "writes")));
+        let charges: [Box<dyn Fn(u64) -> RateLimitResult>; 2] = [
+            Box::new(|cost| rl.charge(RouteGroup::TaskWrites, "k1", cost).unwrap()),
+            Box::new(|cost| sqlite.check_cost("k1", cost, 5)),
+        ];
+        for charge in charges {
+            assert_eq!(charge(3).remaining, 2);
+            // Three more don't fit, so none are counted
+            assert!(!charge(3).allowed);
+            let r = charge(2);
+            assert!(r.allowed);
+            assert_eq!(r.remaining, 0);
+            assert!(!charge(1).allowed);
+        }
+    }
+
+    #[test]
+    fn config_layers_env_over_figment_over_defaults() {
+        use rocket::figment::providers::{Format, Toml};
+
+        let none = |_: &str| None;
+        let defaults = RateLimitConfig::from_sources(&Figment::new(), none).unwrap();
+        assert_eq!(defaults, RateLimitConfig::default());
+        assert_eq!(defaults.rule(RouteGroup::BoardCreation), RateLimitRule { limit: 10, window_secs: 3600 });
+        assert_eq!(defaults.rule(RouteGroup::Batch), RateLimitRule { limit: 60, window_secs: 60 });
+
+        let figment = Figment::from(Toml::string(
+            "[rate_limits.board_creation]\nlimit = 50\nwindow_secs = 86400\n[rate_limits.comments]\nwindow_secs = 10\n",
+        ));
+        let env = |name: &str| match name {
+            "BOARD_RATE_LIMIT" => Some("20".to_string()),
+            "TASK_WRITE_RATE_LIMIT" => Some("100".to_string()),
+            "RATE_LIMIT_BATCH" => Some("5/30".to_string()),
+            "RATE_LIMIT_COMMENTS" => Some(" 7 ".to_string()),
+            _ => None,
+        };
+        let config = RateLimitConfig::from_sources(&figment, env).unwrap();
+        assert_eq!(config.rule(RouteGroup::BoardCreation), RateLimitRule { limit: 50, window_secs: 86400 });
+        assert_eq!(config.rule(RouteGroup::TaskWrites), RateLimitRule { limit: 100, window_secs: 60 });
+        assert_eq!(config.rule(RouteGroup::Comments), RateLimitRule { limit: 7, window_secs: 10 });
+        assert_eq!(config.rule(RouteGroup::Batch), RateLimitRule { limit: 5, window_secs: 30 });
+
+        for toml in ["[rate_limits.boards]\nlimit = 1", "[rate_limits.batch]\nlimt = 1", "[rate_limits.batch]\nwindow_secs = 0"] {
+            assert!(RateLimitConfig::from_sources(&Figment::from(Toml::string(toml)), none).is_err(), "{}", toml);
+        }
+        for value in ["ten", "5/", "5/hour", "5/0"] {
+            let env = |name: &str| (name == "RATE_LIMIT_TASK_WRITES").then(|| value.to_string());
+            assert!(RateLimitConfig::from_sources(&Figment::new(), env).is_err(), "{}", value);
+        }
+        // The legacy variables are held to the same standard
+        for value in ["ten", "-5", "5/60"] {
+            let env = |name: &str| (name == "TASK_WRITE_RATE_LIMIT").then(|| value.to_string());
+            assert!(RateLimitConfig::from_sources(&Figment::new(), env).is_err(), "{}", value);
+        }
+    }
+
+
+    fn test_db() -> DbPool {
+        crate::db::init_db_with_path(&format!("/tmp/kanban_rate_limit_{}.db", uuid::Uuid::new_v4())).unwrap()
+    }
+
+    #[test]
+    fn sqlite_store_matches_memory() {
+        let db = test_db();
+        let rl = RateLimiter::with_store(Duration::from_secs(60), 3, Box::new(SqliteStore::new(db.clone(), "boards")));
+        for remaining in [2, 1, 0] {
+            let r = hit(&rl, "ip1");
+            assert!(r.allowed);
+            assert_eq!(r.remaining, remaining);
+        }
+        let r = hit(&rl, "ip1");
+        assert!(!r.allowed);
+        assert_eq!(r.remaining, 0);
+        assert!(r.reset_secs <= 60);
+        assert!(hit(&rl, "ip2").allowed);
+
+        // Another limiter on the same database shares the windows; other scopes don't
+        let replica = RateLimiter::with_store(Duration::from_secs(60), 3, Box::new(SqliteStore::new(db.clone(), "boards")));
+        assert!(!hit(&replica, "ip1").allowed);
+        let writes = RateLimiter::with_store(Duration::from_secs(60), 3, Box::new(SqliteStore::new(db, "writes")));
+        assert!(hit(&writes, "ip1").allowed);
+
+        let keys: Vec<(String, u64, bool)> = rl.stats().clients.into_iter().map(|c| (c.key, c.count, c.limited)).collect();
+        assert_eq!(keys, vec![("ip1".to_string(), 3, true), ("ip2".to_string(), 1, false)]);
+    }
+
+    #[test]
+    fn sqlite_store_windows_expire() {
+        let store = SqliteStore::new(test_db(), "writes");
+        let window = Duration::from_millis(50);
+        assert!(store.hit("key", 1, 1, window).unwrap().allowed);
+        assert!(!store.hit("key", 1, 1, window).unwrap().allowed);
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(store.windows(window).is_empty());
+        let hit = store.hit("key", 1, 1, window).unwrap();
+        assert!(hit.allowed);
+        assert_eq!(hit.count, 1);
+        store.prune(Duration::ZERO);
+        assert!(store.windows(Duration::from_secs(60)).is_empty());
+    }
+
+    #[test]
+    fn stats_lists_busiest_first() {
+        let rl = in_memory(60, 2);
+        hit(&rl, "ip1");
+        hit(&rl, "ip2");
+        hit(&rl, "ip2");
+        let stats = rl.stats();
+        assert_eq!((stats.window_seconds, stats.limit), (60, 2));
+        let keys: Vec<(&str, u64, bool)> = stats.clients.iter().map(|c| (c.key.as_str(), c.count, c.limited)).collect();
+        assert_eq!(keys, vec![("ip2", 2, true), ("ip1", 1, false)]);
+    }
+
+    #[test]
+    fn client_ip_only_trusts_forwarding_from_trusted_proxies() {
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+        let none = TrustedProxies::default();
+        assert_eq!(none.resolve(ip("203.0.113.9"), &["10.0.0.1"], Some("10.0.0.2")), ip("203.0.113.9"));
+
+        let proxies = TrustedProxies::parse("10.0.0.0/8, 127.0.0.1").unwrap();
+        // Spoofed entries left of the real client are ignored
+        assert_eq!(
+            proxies.resolve(ip("10.1.1.1"), &["10.9.9.9, 198.51.100.7", "10.2.2.2"], None),
+            ip("198.51.100.7")
+        );
+        assert_eq!(proxies.resolve(ip("127.0.0.1"), &[], Some(" 198.51.100.8 ")), ip("198.51.100.8"));
+        assert_eq!(proxies.resolve(ip("10.1.1.1"), &["10.3.3.3"], None), ip("10.3.3.3"));
+        assert_eq!(proxies.resolve(ip("10.1.1.1"), &["198.51.100.7, junk"], None), ip("10.1.1.1"));
+        assert_eq!(proxies.resolve(ip("198.51.100.1"), &["10.0.0.1"], None), ip("198.51.100.1"));
+
+        assert!(TrustedProxies::parse("").unwrap().0.is_empty());
+        assert!(TrustedProxies::parse("10.0.0.0/8,proxy.local").is_err());
     }
 }
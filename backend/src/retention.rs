@@ -0,0 +1,122 @@
+//! Per-board data retention. Boards can opt into purging archived tasks and
+//! pruning old activity (`PATCH /boards/<id>`); a background job applies the
+//! policies and emits `board.retention_purged` whenever it removes something.
+
+use std::time::Duration;
+
+use rusqlite::Connection;
+
+use crate::db::DbPool;
+use crate::event_types;
+use crate::board_events;
+use crate::events::EventBus;
+use crate::routes::{db_error, with_db_blocking};
+
+/// Who `board.retention_purged` events are attributed to.
+const ACTOR: &str = "retention";
+
+/// How often the retention job runs.
+const RETENTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// What one board lost in a retention run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetentionPurge {
+    pub board_id: String,
+    pub archived_task_ids: Vec<String>,
+    pub events_pruned: usize,
+}
+
+/// Apply every board's retention policy once. Archived tasks past their
+/// window are deleted for good (with their events and dependencies); activity
+/// events past the event window are pruned, but comments are kept. Each
+/// board's `board.retention_purged` is emitted in the transaction of its purge.
+pub fn enforce(conn: &Connection, bus: &EventBus) -> rusqlite::Result<Vec<RetentionPurge>> {
+    let policies: Vec<(String, Option<i64>, Option<i64>)> = conn
+        .prepare(
+            "SELECT id, archived_task_retention_days, event_retention_days FROM boards
+             WHERE archived_task_retention_days IS NOT NULL OR event_retention_days IS NOT NULL",
+        )?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let mut purges = Vec::new();
+    for (board_id, task_days, event_days) in policies {
+        // Immediate: the job reads before writing and must not lose the write
+        // lock race to request handlers or webhook bookkeeping
+        let tx = rusqlite::Transaction::new_unchecked(conn, rusqlite::TransactionBehavior::Immediate)?;
+        let mut archived_task_ids = Vec::new();
+        if let Some(days) = task_days {
+            archived_task_ids = tx
+                .prepare(
+                    "SELECT id FROM tasks WHERE board_id = ?1 AND archived_at IS NOT NULL
+                       AND archived_at <= datetime('now', '-' || ?2 || ' days')",
+                )?
+                .query_map(rusqlite::params![board_id, days], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?;
+            for task_id in &archived_task_ids {
+                tx.execute("DELETE FROM tasks WHERE id = ?1", rusqlite::params![task_id])?;
+                tx.execute("DELETE FROM email_reminders WHERE task_id = ?1", rusqlite::params![task_id])?;
+            }
+        }
+        let events_pruned = match event_days {
+            Some(days) => {
+                tx.execute(
+                    "DELETE FROM task_events WHERE event_type != 'comment'
+                       AND created_at <= datetime('now', '-' || ?2 || ' days')
+                       AND task_id IN (SELECT id FROM tasks WHERE board_id = ?1)",
+                    rusqlite::params![board_id, days],
+                )? + tx.execute(
+                    "DELETE FROM board_events WHERE board_id = ?1
+                       AND created_at <= datetime('now', '-' || ?2 || ' days')",
+                    rusqlite::params![board_id, days],
+                )?
+            }
+            None => 0,
+        };
+        if !archived_task_ids.is_empty() || events_pruned > 0 {
+            board_events::emit(
+                &tx,
+                bus,
+                &board_id,
+                event_types::BOARD_RETENTION_PURGED,
+                ACTOR,
+                serde_json::json!({
+                    "archived_tasks_purged": archived_task_ids.len(),
+                    "task_ids": archived_task_ids,
+                    "events_pruned": events_pruned,
+                }),
+            )?;
+        }
+        crate::events::commit(tx)?;
+
+        if !archived_task_ids.is_empty() || events_pruned > 0 {
+            purges.push(RetentionPurge {
+                board_id,
+                archived_task_ids,
+                events_pruned,
+            });
+        }
+    }
+    Ok(purges)
+}
+
+/// Run `enforce` on a pooled connection.
+pub fn run(db: &DbPool, bus: &EventBus) -> Result<Vec<RetentionPurge>, String> {
+    with_db_blocking(db, |conn| enforce(conn, bus).map_err(|e| db_error(&e.to_string()))).map_err(|(_, e)| e.error.clone())
+}
+
+/// Run the retention job hourly in the background.
+pub fn spawn(db: DbPool, bus: EventBus) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(RETENTION_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let (db, bus) = (db.clone(), bus.clone());
+            match tokio::task::spawn_blocking(move || run(&db, &bus)).await {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => eprintln!("⚠️  Retention job failed: {}", e),
+                Err(e) => eprintln!("⚠️  Retention job panicked: {}", e),
+            }
+        }
+    });
+}
@@ -1,19 +1,37 @@
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::PathBuf;
 
-use chrono::Utc;
-use rocket::http::{ContentType, Status};
-use rocket::response::stream::{Event, EventStream};
+use chrono::{NaiveDateTime, Utc};
+use rocket::http::{ContentType, Header, Status};
+use rocket::response::stream::{ByteStream, Event, EventStream};
 use rocket::serde::json::Json;
 use rocket::tokio::select;
 use rocket::tokio::time::Duration;
 use rocket::{Shutdown, State};
 
-use crate::access;
-use crate::auth::BoardToken;
+use crate::access::{self, BoardRole, ScopeAction, Visibility};
+use crate::attachments;
+use crate::auth::{Actor, BoardToken};
+use crate::auto_assign;
+use crate::board_events;
+use crate::comment_pressure;
 use crate::db::{hash_key, DbPool};
+use crate::event_types;
 use crate::events::EventBus;
+use crate::features::FeatureFlags;
+use crate::hierarchy;
+use crate::lifecycle::{self, LifecycleHooks};
 use crate::models::*;
-use crate::rate_limit::{ClientIp, RateLimiter};
+use crate::oidc::SessionUser;
+use crate::rate_limit::{IpRateLimit, RouteGroup};
+use crate::tags;
+use crate::telegram;
+use crate::trash;
+use crate::validation_hook;
+use crate::view_password;
+use crate::webhook_keys;
+use crate::webhook_template;
+use crate::webhooks;
 
 // ============ Label Normalization ============
 
@@ -26,7 +44,7 @@ fn normalize_label(label: &str) -> String {
     s
 }
 
-fn normalize_labels(labels: &[String]) -> Vec<String> {
+pub(crate) fn normalize_labels(labels: &[String]) -> Vec<String> {
     labels.iter()
         .map(|l| normalize_label(l))
         .filter(|l| !l.is_empty())
@@ -37,7 +55,7 @@ fn normalize_labels(labels: &[String]) -> Vec<String> {
 
 /// Extract @mentions from text. Supports `@Name` and `@"Name With Spaces"`.
 /// Returns deduplicated list of mentioned names (case-preserved).
-fn extract_mentions(text: &str) -> Vec<String> {
+pub(crate) fn extract_mentions(text: &str) -> Vec<String> {
     let mut mentions = Vec::new();
     let mut seen = std::collections::HashSet::new();
     let chars: Vec<char> = text.chars().collect();
@@ -80,17 +98,39 @@ fn extract_mentions(text: &str) -> Vec<String> {
 
 // ============ Health & OpenAPI ============
 
+/// Liveness plus the server version and enabled feature flags.
 #[get("/health")]
-pub fn health() -> Json<HealthResponse> {
+pub fn health(features: &State<FeatureFlags>) -> Json<HealthResponse> {
     Json(HealthResponse {
         status: "ok".to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
+        features: features.enabled(),
     })
 }
 
+/// The bundled spec, with the `EventType` enum filled in from the event registry.
+/// Every mounted route must be in it (`test_http_openapi_lists_every_route`).
 #[get("/openapi.json")]
 pub fn openapi() -> (ContentType, &'static str) {
-    (ContentType::JSON, include_str!("../openapi.json"))
+    static SPEC: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+    let spec = SPEC.get_or_init(|| {
+        let raw = include_str!("../openapi.json");
+        let Ok(mut spec) = serde_json::from_str::<serde_json::Value>(raw) else {
+            return raw.to_string();
+        };
+        spec["components"]["schemas"]["EventType"] = serde_json::json!({
+            "type": "string",
+            "enum": event_types::names().collect::<Vec<_>>(),
+        });
+        serde_json::to_string_pretty(&spec).unwrap_or_else(|_| raw.to_string())
+    });
+    (ContentType::JSON, spec.as_str())
+}
+
+/// Every event type emitted over SSE and webhooks — public.
+#[get("/event-types")]
+pub fn list_event_types() -> Json<&'static [event_types::EventType]> {
+    Json(event_types::EVENT_TYPES)
 }
 
 #[get("/llms.txt")]
@@ -98,6 +138,48 @@ pub fn llms_txt() -> (ContentType, &'static str) {
     (ContentType::Text, include_str!("../llms.txt"))
 }
 
+/// Generated client bindings, built from openapi.json at compile time (see build.rs).
+static TYPESCRIPT_CLIENT: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/clients/typescript.zip"));
+static PYTHON_CLIENT: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/clients/python.zip"));
+
+#[derive(Responder)]
+#[response(content_type = "application/zip")]
+pub struct ClientArchive {
+    bytes: &'static [u8],
+    disposition: Header<'static>,
+    version: Header<'static>,
+}
+
+/// Download generated client bindings — `typescript.zip` (or `ts.zip`) and `python.zip` (or `py.zip`).
+/// Archives are versioned with the server so agents code against the running contract.
+#[get("/clients/<archive>")]
+pub fn client_sdk(archive: &str) -> Result<ClientArchive, (Status, Json<ApiError>)> {
+    let lang = archive.strip_suffix(".zip").unwrap_or(archive);
+    let (name, bytes) = match lang {
+        "typescript" | "ts" => ("typescript", TYPESCRIPT_CLIENT),
+        "python" | "py" => ("python", PYTHON_CLIENT),
+        _ => {
+            return Err((
+                Status::NotFound,
+                Json(ApiError {
+                    error: format!("Unknown client '{}'. Available: typescript.zip, python.zip", lang),
+                    code: "UNKNOWN_CLIENT".to_string(),
+                    status: 404,
+                }),
+            ))
+        }
+    };
+    let version = env!("CARGO_PKG_VERSION");
+    Ok(ClientArchive {
+        bytes,
+        disposition: Header::new(
+            "Content-Disposition",
+            format!("attachment; filename=\"kanban-client-{}-{}.zip\"", name, version),
+        ),
+        version: Header::new("X-Kanban-Version", version),
+    })
+}
+
 /// Root-level /llms.txt for standard discovery (outside /api/v1)
 #[get("/llms.txt", rank = 2)]
 pub fn root_llms_txt() -> (ContentType, &'static str) {
@@ -106,27 +188,57 @@ pub fn root_llms_txt() -> (ContentType, &'static str) {
 
 // ============ SSE Event Stream ============
 
+/// How often the stream sends a `heartbeat` frame.
+pub(crate) const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
 /// Public: anyone with the board UUID can subscribe to events.
-#[get("/boards/<board_id>/events/stream")]
-pub fn board_event_stream(
+/// `?events=` and `?assigned=` narrow the stream to some event types and to
+/// tasks assigned to one agent; filtered-out events are dropped server-side.
+/// `?version=1` sends each event as its envelope (also numbering frames by
+/// the event's seq) instead of the bare payload.
+/// A `heartbeat` event with per-column task counts and the board's latest
+/// activity seq is sent on connect and every 15 seconds, regardless of filters.
+#[allow(clippy::too_many_arguments)]
+#[get("/boards/<board_id>/events/stream?<events>&<assigned>&<version>")]
+pub async fn board_event_stream(
     board_id: &str,
+    events: Option<&str>,
+    assigned: Option<&str>,
+    version: Option<u32>,
+    token: Option<BoardToken>,
     db: &State<DbPool>,
     bus: &State<EventBus>,
     mut shutdown: Shutdown,
 ) -> Result<EventStream![], (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
-    access::require_board_exists(&conn, board_id)?;
-    drop(conn);
+    let board = board_id.to_owned();
+    let columns = with_db(db, move |conn| {
+        let board_id = board.as_str();
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        crate::analytics::record_view(conn, board_id, crate::analytics::VIEW_SSE_CONNECT);
+        Ok::<_, (Status, Json<ApiError>)>(token_columns(conn, board_id, token.as_ref()))
+    })
+    .await?;
 
-    let mut rx = bus.subscribe(board_id);
+    let filter = crate::events::EventFilter { columns, ..event_filter(events, assigned)? };
+    let version = stream_version(version)?;
+    let pool = db.inner().clone();
+    let mut subscription = bus.subscribe_filtered(board_id, "sse", filter);
+    let board_id = board_id.to_string();
+    let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
 
     Ok(EventStream! {
         loop {
             select! {
-                msg = rx.recv() => match msg {
-                    Ok(event) => {
-                        yield Event::json(&event.data).event(event.event);
+                _ = heartbeat.tick() => {
+                    let board = board_id.clone();
+                    match with_db(&pool, move |conn| board_heartbeat(conn, &board).map_err(|e| db_error(&e.to_string()))).await {
+                        Ok(frame) => yield Event::json(&frame).event("heartbeat"),
+                        Err(_) => yield Event::comment("keepalive"),
                     }
+                }
+                msg = subscription.recv() => match msg {
+                    Ok(event) if version == 0 => yield Event::json(&event.data).event(event.event.clone()),
+                    Ok(event) => yield Event::json(&event).event(event.event.clone()).id(event.seq.to_string()),
                     Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
                     Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {
                         yield Event::data("events_lost").event("warning".to_string());
@@ -135,38 +247,383 @@ pub fn board_event_stream(
                 _ = &mut shutdown => break,
             }
         }
+    })
+}
+
+/// Longest a long-poll may wait, in seconds.
+const MAX_POLL_TIMEOUT: u64 = 60;
+
+/// Long-polling fallback for clients that can't hold an SSE connection —
+/// public. Returns the buffered events numbered after `after` at once if
+/// there are any; otherwise waits up to `timeout` seconds (default 30, max
+/// 60) for new ones. Without `after`, only events from now on are returned.
+/// `events` and `assigned` filter as for the SSE stream.
+#[allow(clippy::too_many_arguments)]
+#[get("/boards/<board_id>/events/poll?<after>&<timeout>&<events>&<assigned>")]
+pub async fn board_event_poll(
+    board_id: &str,
+    after: Option<u64>,
+    timeout: Option<u64>,
+    events: Option<&str>,
+    assigned: Option<&str>,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+    mut shutdown: Shutdown,
+) -> Result<Json<EventPollResponse>, (Status, Json<ApiError>)> {
+    let board = board_id.to_owned();
+    let columns = with_db(db, move |conn| {
+        let board_id = board.as_str();
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        Ok::<_, (Status, Json<ApiError>)>(token_columns(conn, board_id, token.as_ref()))
+    })
+    .await?;
+    let filter = crate::events::EventFilter { columns, ..event_filter(events, assigned)? };
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(timeout.unwrap_or(30).min(MAX_POLL_TIMEOUT));
+    let mut cursor = match after {
+        Some(after) => after,
+        None => bus.poll(board_id, 0).0.seq,
+    };
+    let mut events_lost = false;
+
+    loop {
+        let (backlog, mut rx) = bus.poll(board_id, cursor);
+        events_lost |= backlog.lost;
+        cursor = backlog.seq;
+        let events: Vec<_> = backlog
+            .events
+            .into_iter()
+            .filter(|event| filter.admits(event))
+            .collect();
+        if !events.is_empty() || events_lost {
+            return Ok(Json(EventPollResponse { events, cursor, events_lost }));
+        }
+        select! {
+            // Anything new (or a lag) means there's a backlog to read
+            msg = rx.recv() => if matches!(msg, Err(tokio::sync::broadcast::error::RecvError::Closed)) { break },
+            _ = tokio::time::sleep_until(deadline) => break,
+            _ = &mut shutdown => break,
+        }
+    }
+    Ok(Json(EventPollResponse {
+        events: vec![],
+        cursor,
+        events_lost,
+    }))
+}
+
+/// The payload version a stream was asked for with `?version=`: 0 (the
+/// default) is each event's bare `data`, `ENVELOPE_VERSION` the envelope.
+fn stream_version(version: Option<u32>) -> Result<u32, (Status, Json<ApiError>)> {
+    match version.unwrap_or(0) {
+        version @ (0 | crate::events::ENVELOPE_VERSION) => Ok(version),
+        other => Err((
+            Status::BadRequest,
+            Json(ApiError {
+                error: format!(
+                    "Unsupported event version {}. Use 0 (bare payloads) or {} (envelope)",
+                    other,
+                    crate::events::ENVELOPE_VERSION
+                ),
+                code: "UNSUPPORTED_VERSION".to_string(),
+                status: 400,
+            }),
+        )),
+    }
+}
+
+/// A stream's `?events=` and `?assigned=` filter.
+pub(crate) fn event_filter(
+    events: Option<&str>,
+    assigned: Option<&str>,
+) -> Result<crate::events::EventFilter, (Status, Json<ApiError>)> {
+    Ok(crate::events::EventFilter {
+        events: event_types::parse_filter(events.unwrap_or("")).map_err(invalid_event_type)?,
+        assigned: assigned.map(str::trim).filter(|a| !a.is_empty()).map(str::to_string),
+        columns: Vec::new(),
+    })
+}
+
+/// The columns a column-scoped token limits a stream to; empty (all
+/// columns) for any other key, or none.
+pub(crate) fn token_columns(conn: &Connection, board_id: &str, token: Option<&BoardToken>) -> Vec<String> {
+    token
+        .and_then(|token| access::token_scope(conn, board_id, &hash_key(&token.0)))
+        .map(|scope| scope.columns)
+        .unwrap_or_default()
+}
+
+/// Snapshot for the SSE heartbeat; counts match `GET /boards/{id}` column `task_count`.
+pub(crate) fn board_heartbeat(conn: &Connection, board_id: &str) -> rusqlite::Result<StreamHeartbeat> {
+    let columns: Vec<ColumnTaskCount> = conn
+        .prepare(
+            "SELECT c.id, (SELECT COUNT(*) FROM tasks t WHERE t.column_id = c.id)
+             FROM columns c WHERE c.board_id = ?1
+             ORDER BY c.position ASC",
+        )?
+        .query_map(rusqlite::params![board_id], |row| {
+            Ok(ColumnTaskCount {
+                id: row.get(0)?,
+                task_count: row.get(1)?,
+            })
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    let seq: i64 = conn.query_row(
+        "SELECT COALESCE(MAX(te.seq), 0) FROM task_events te
+         JOIN tasks t ON t.id = te.task_id
+         WHERE t.board_id = ?1",
+        rusqlite::params![board_id],
+        |row| row.get(0),
+    )?;
+    Ok(StreamHeartbeat {
+        seq,
+        task_count: columns.iter().map(|c| c.task_count).sum(),
+        columns,
+        server_time: Utc::now().to_rfc3339(),
+    })
+}
+
+/// Most boards one multiplexed stream can watch.
+const MAX_STREAM_BOARDS: usize = 50;
+
+/// Boards a token may be a key for; `access::board_role` decides which it
+/// opens.
+const TOKEN_BOARDS: &str = "SELECT id FROM boards WHERE manage_key_hash = ?1
+     UNION SELECT board_id FROM board_members WHERE key_hash = ?1
+     UNION SELECT board_id FROM share_keys WHERE key_hash = ?1
+     UNION SELECT board_id FROM scoped_tokens WHERE key_hash = ?1
+     UNION SELECT board_id FROM agent_keys WHERE key_hash = ?1";
+
+/// The `Last-Event-ID` an EventSource sends when it reconnects.
+pub struct LastEventId(Option<String>);
+
+#[rocket::async_trait]
+impl<'r> rocket::request::FromRequest<'r> for LastEventId {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(request: &'r rocket::Request<'_>) -> rocket::request::Outcome<Self, Self::Error> {
+        rocket::request::Outcome::Success(LastEventId(request.headers().get_one("Last-Event-ID").map(str::to_string)))
+    }
+}
+
+/// A `board_id:seq` stream cursor.
+fn stream_cursor(cursor: &str) -> Option<(String, u64)> {
+    let (board_id, seq) = cursor.trim().rsplit_once(':')?;
+    Some((board_id.to_string(), seq.parse().ok()?))
+}
+
+/// One SSE connection for several boards. Each `token` (repeatable, or
+/// comma-separated) is a manage, collaborator, share or scoped key, or a
+/// sign-in session, and adds the boards it has a role on, private or not;
+/// `boards` adds public and unlisted boards by id. Frames keep their event
+/// names and carry `{board_id, seq, data}`, where `seq` is the event's
+/// number on its board and the SSE id is `board_id:seq`. A reconnect's
+/// `Last-Event-ID`, and `after` (comma-separated `board_id:seq`), replay
+/// what those boards buffered since.
+/// With `?version=1`, an event's `data` is its envelope.
+/// Each board's `heartbeat` is sent every 15 seconds.
+#[allow(clippy::too_many_arguments)]
+#[get("/events/stream?<token>&<boards>&<events>&<version>&<after>")]
+pub async fn multiplexed_event_stream(
+    token: Vec<String>,
+    boards: Option<&str>,
+    events: Option<&str>,
+    version: Option<u32>,
+    after: Option<&str>,
+    last_event_id: LastEventId,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+    mut shutdown: Shutdown,
+) -> Result<EventStream![], (Status, Json<ApiError>)> {
+    use rocket::futures::stream::{self, StreamExt};
+
+    let filter = event_types::parse_filter(events.unwrap_or("")).map_err(invalid_event_type)?;
+    let version = stream_version(version)?;
+    let split = |raw: &str| raw.split(',').map(str::trim).filter(|v| !v.is_empty()).map(String::from).collect::<Vec<_>>();
+    let tokens: Vec<String> = token.iter().flat_map(|t| split(t)).collect();
+    let requested = boards.map(split).unwrap_or_default();
+    let mut cursors: HashMap<String, u64> = HashMap::new();
+    for cursor in after.map(split).unwrap_or_default() {
+        let (board_id, seq) = stream_cursor(&cursor).ok_or_else(|| {
+            (
+                Status::BadRequest,
+                Json(ApiError {
+                    error: format!("Cursor '{}' isn't board_id:seq", cursor),
+                    code: "INVALID_INPUT".to_string(),
+                    status: 400,
+                }),
+            )
+        })?;
+        cursors.insert(board_id, seq);
     }
-    .heartbeat(Duration::from_secs(15)))
+    // The reconnect's own cursor is the newest; ids from elsewhere are ignored
+    if let Some((board_id, seq)) = last_event_id.0.as_deref().and_then(stream_cursor) {
+        let newest = cursors.entry(board_id).or_insert(seq);
+        *newest = (*newest).max(seq);
+    }
+
+    let (board_ids, scopes) = with_db(db, move |conn| {
+        let mut board_ids: Vec<String> = Vec::new();
+        // Columns per board when every token that opened it is column-scoped
+        let mut scopes: HashMap<String, Vec<String>> = HashMap::new();
+        let mut unscoped: HashSet<String> = HashSet::new();
+        for token in &tokens {
+            // A sign-in session streams with its user's keys
+            let keys = crate::oidc::session_keys(conn, token, None);
+            let keys = if keys.is_empty() { vec![token.clone()] } else { keys };
+            let mut opened: Vec<(String, String)> = Vec::new();
+            for token_hash in keys.iter().map(|key| hash_key(key)) {
+                let boards: Vec<String> = conn
+                    .prepare_cached(TOKEN_BOARDS)
+                    .and_then(|mut stmt| stmt.query_map(rusqlite::params![token_hash], |row| row.get(0))?.collect())
+                    .map_err(|e| db_error(&e.to_string()))?;
+                opened.extend(
+                    boards
+                        .into_iter()
+                        .filter(|board_id| access::board_role(conn, board_id, &token_hash).is_ok())
+                        .map(|board_id| (board_id, token_hash.clone())),
+                );
+            }
+            if opened.is_empty() {
+                return Err((
+                    Status::Forbidden,
+                    Json(ApiError {
+                        error: "A token does not match any board".to_string(),
+                        code: "INVALID_KEY".to_string(),
+                        status: 403,
+                    }),
+                ));
+            }
+            for (board_id, token_hash) in opened {
+                match access::token_scope(conn, &board_id, &token_hash).filter(|s| !s.columns.is_empty()) {
+                    Some(scope) => scopes.entry(board_id.clone()).or_default().extend(scope.columns),
+                    None => {
+                        unscoped.insert(board_id.clone());
+                    }
+                }
+                board_ids.push(board_id);
+            }
+        }
+        scopes.retain(|board_id, _| !unscoped.contains(board_id));
+        for board_id in &requested {
+            // Private boards come in only through one of their keys
+            if !board_ids.contains(board_id) {
+                access::require_read_access(conn, board_id, None)?;
+            }
+            board_ids.push(board_id.clone());
+        }
+        let mut seen = HashSet::new();
+        board_ids.retain(|id| seen.insert(id.clone()));
+        if board_ids.is_empty() || board_ids.len() > MAX_STREAM_BOARDS {
+            return Err((
+                Status::BadRequest,
+                Json(ApiError {
+                    error: format!("Give between 1 and {} boards via token or boards", MAX_STREAM_BOARDS),
+                    code: "INVALID_INPUT".to_string(),
+                    status: 400,
+                }),
+            ));
+        }
+        for board_id in &board_ids {
+            crate::analytics::record_view(conn, board_id, crate::analytics::VIEW_SSE_CONNECT);
+        }
+        Ok((board_ids, scopes))
+    })
+    .await?;
+
+    // Subscribe before reading the backlogs, so nothing falls between them;
+    // live events the replay already sent are skipped by seq
+    let mut replay: Vec<crate::events::Envelope> = Vec::new();
+    let mut lost: Vec<String> = Vec::new();
+    let mut sent: HashMap<String, u64> = HashMap::new();
+    let receivers: Vec<_> = board_ids
+        .iter()
+        .map(|board_id| {
+            let filter = crate::events::EventFilter {
+                events: filter.clone(),
+                assigned: None,
+                columns: scopes.get(board_id).cloned().unwrap_or_default(),
+            };
+            let subscription = bus.subscribe_filtered(board_id, "sse-multi", filter.clone());
+            if let Some(&cursor) = cursors.get(board_id) {
+                let (backlog, _) = bus.poll(board_id, cursor);
+                if backlog.lost {
+                    lost.push(board_id.clone());
+                }
+                sent.insert(board_id.clone(), backlog.seq);
+                replay.extend(backlog.events.into_iter().filter(|event| filter.admits(event)));
+            }
+            stream::unfold(subscription, |mut subscription| async move {
+                let msg = subscription.recv().await;
+                (!matches!(msg, Err(tokio::sync::broadcast::error::RecvError::Closed))).then_some((msg, subscription))
+            })
+            .map({
+                let board_id = board_id.clone();
+                move |msg| (board_id.clone(), msg)
+            })
+            .boxed()
+        })
+        .collect();
+    replay.sort_by(|a, b| a.occurred_at.cmp(&b.occurred_at));
+    let mut merged = stream::select_all(receivers);
+    let pool = db.inner().clone();
+    let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+    let frame = move |event: &crate::events::Envelope| {
+        let data = if version == 0 { event.data.clone() } else { serde_json::json!(event) };
+        Event::json(&serde_json::json!({"board_id": event.board_id, "seq": event.seq, "data": data}))
+            .event(event.event.clone())
+            .id(format!("{}:{}", event.board_id, event.seq))
+    };
+    let notice = |board_id: &str, data: serde_json::Value, name: &str| {
+        Event::json(&serde_json::json!({"board_id": board_id, "data": data})).event(name.to_string())
+    };
+
+    Ok(EventStream! {
+        for board_id in &lost {
+            yield notice(board_id, serde_json::json!("events_lost"), "warning");
+        }
+        for event in &replay {
+            yield frame(event);
+        }
+        loop {
+            select! {
+                _ = heartbeat.tick() => {
+                    for board_id in &board_ids {
+                        let board = board_id.clone();
+                        match with_db(&pool, move |conn| board_heartbeat(conn, &board).map_err(|e| db_error(&e.to_string()))).await {
+                            Ok(beat) => yield notice(board_id, serde_json::json!(beat), "heartbeat"),
+                            Err(_) => yield Event::comment("keepalive"),
+                        }
+                    }
+                }
+                next = merged.next() => match next {
+                    Some((_, Ok(event))) if sent.get(&event.board_id).is_some_and(|&seq| event.seq <= seq) => {}
+                    Some((_, Ok(event))) => yield frame(&event),
+                    Some((board_id, Err(_))) => yield notice(&board_id, serde_json::json!("events_lost"), "warning"),
+                    None => break,
+                },
+                _ = &mut shutdown => break,
+            }
+        }
+    })
 }
 
 // ============ Boards ============
 
 /// Create a board — no auth required. Returns a manage_key (shown only once).
-/// Rate limited per IP address to prevent spam.
+/// Rate limited per IP address to prevent spam. The actor (if any) is recorded as the owner;
+/// when signed in (OIDC), the user owns the board and their session can manage it.
 #[post("/boards", format = "json", data = "<req>")]
-pub fn create_board(
+pub async fn create_board(
     req: Json<CreateBoardRequest>,
-    client_ip: ClientIp,
-    rate_limiter: &State<RateLimiter>,
+    actor: Actor,
+    user: Option<SessionUser>,
+    rate_limit: IpRateLimit<'_>,
+    hooks: &State<LifecycleHooks>,
     db: &State<DbPool>,
 ) -> Result<Json<CreateBoardResponse>, (Status, Json<ApiError>)> {
     let req = req.into_inner();
-
-    // Check IP-based rate limit for board creation
-    let rl_result = rate_limiter.check_default(&client_ip.0);
-    if !rl_result.allowed {
-        return Err((
-            Status::TooManyRequests,
-            Json(ApiError {
-                error: format!(
-                    "Rate limit exceeded. You can create {} boards per hour. Try again in {} seconds.",
-                    rl_result.limit, rl_result.reset_secs
-                ),
-                code: "RATE_LIMIT_EXCEEDED".to_string(),
-                status: 429,
-            }),
-        ));
-    }
+    check_board_rate_limit(&rate_limit).await?;
 
     if req.name.trim().is_empty() {
         return Err((
@@ -183,272 +640,492 @@ pub fn create_board(
     let manage_key = format!("kb_{}", uuid::Uuid::new_v4().to_string().replace('-', ""));
     let manage_key_hash = hash_key(&manage_key);
 
-    let conn = db.lock().unwrap();
-
-    conn.execute(
-        "INSERT INTO boards (id, name, description, manage_key_hash, is_public, require_display_name) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-        rusqlite::params![board_id, req.name.trim(), req.description, manage_key_hash, req.is_public as i32, req.require_display_name as i32],
-    )
-    .map_err(|e| db_error(&e.to_string()))?;
-
-    // Create default columns if none specified
-    let columns = if req.columns.is_empty() {
-        vec![
-            "Backlog".to_string(),
-            "Up Next".to_string(),
-            "In Progress".to_string(),
-            "Review".to_string(),
-            "Done".to_string(),
-        ]
-    } else {
-        req.columns
+    let visibility = req.visibility.unwrap_or(if req.is_public { Visibility::Public } else { Visibility::Unlisted });
+    let is_public = visibility == Visibility::Public;
+    let view_password_hash = match req.view_password.clone().filter(|p| !p.is_empty()) {
+        Some(password) => Some(crate::db::blocking(move || view_password::hash_password(&password)).await?),
+        None => None,
     };
 
-    let mut col_responses = Vec::new();
-    for (i, col_name) in columns.iter().enumerate() {
-        let col_id = uuid::Uuid::new_v4().to_string();
+    let owner = match (&user, actor.is_anonymous()) {
+        (Some(user), true) => Some(user.name.clone()),
+        (_, false) => Some(actor.name().to_string()),
+        (None, true) => None,
+    };
+    let (board, created_by) = (board_id.clone(), owner.clone());
+    let response = with_db(db, move |conn| {
+        let board_id = board;
         conn.execute(
-            "INSERT INTO columns (id, board_id, name, position) VALUES (?1, ?2, ?3, ?4)",
-            rusqlite::params![col_id, board_id, col_name, i as i32],
+            "INSERT INTO boards (id, name, description, manage_key_hash, is_public, visibility, require_display_name, enforce_dependencies, created_by, view_password_hash, owner_user_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            rusqlite::params![board_id, req.name.trim(), req.description, manage_key_hash, is_public as i32, visibility.as_str(), req.require_display_name as i32, req.enforce_dependencies as i32, created_by, view_password_hash, user.as_ref().map(|u| &u.id)],
         )
         .map_err(|e| db_error(&e.to_string()))?;
 
-        col_responses.push(ColumnResponse {
-            id: col_id,
-            name: col_name.clone(),
-            position: i as i32,
-            wip_limit: None,
-            task_count: 0,
-        });
-    }
+        // The signed-in creator's owner key, which their sessions act with
+        if let Some(user) = &user {
+            let key = crate::oidc::board_key(conn, &user.id, &board_id).map_err(|e| db_error(&e.to_string()))?;
+            conn.execute(
+                "INSERT INTO board_members (id, board_id, name, role, key_hash, created_by) VALUES (?1, ?2, ?3, 'owner', ?4, ?3)",
+                rusqlite::params![uuid::Uuid::new_v4().to_string(), board_id, user.name, hash_key(&key)],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        }
 
-    Ok(Json(CreateBoardResponse {
-        id: board_id.clone(),
-        name: req.name,
-        description: req.description,
-        columns: col_responses,
-        manage_key: manage_key.clone(),
-        view_url: format!("/board/{}", board_id),
-        manage_url: format!("/board/{}?key={}", board_id, manage_key),
-        api_base: format!("/api/v1/boards/{}", board_id),
-        created_at: chrono::Utc::now().to_rfc3339(),
-    }))
+        // Create default columns if none specified
+        let columns = if req.columns.is_empty() {
+            vec![
+                "Backlog".to_string(),
+                "Up Next".to_string(),
+                "In Progress".to_string(),
+                "Review".to_string(),
+                "Done".to_string(),
+            ]
+        } else {
+            req.columns
+        };
+
+        let mut col_responses = Vec::new();
+        for (i, col_name) in columns.iter().enumerate() {
+            let col_id = uuid::Uuid::new_v4().to_string();
+            conn.execute(
+                "INSERT INTO columns (id, board_id, name, position) VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![col_id, board_id, col_name, i as i32],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+
+            col_responses.push(ColumnResponse {
+                id: col_id,
+                name: col_name.clone(),
+                position: i as i32,
+                wip_limit: None,
+                task_count: 0,
+                auto_assign: Vec::new(),
+                auto_assign_strategy: auto_assign::ROUND_ROBIN.to_string(),
+            });
+        }
+
+        Ok(Json(CreateBoardResponse {
+            id: board_id.clone(),
+            name: req.name,
+            description: req.description,
+            columns: col_responses,
+            manage_key: manage_key.clone(),
+            view_url: format!("/board/{}", board_id),
+            manage_url: format!("/board/{}?key={}", board_id, manage_key),
+            api_base: format!("/api/v1/boards/{}", board_id),
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }))
+    })
+    .await?;
+
+    hooks.notify(db, lifecycle::BOARD_CREATED, &board_id, owner.as_deref()).await;
+    Ok(response)
+}
+
+/// Per-IP `board_creation` limit shared by every route that creates a board.
+pub(crate) async fn check_board_rate_limit(rate_limit: &IpRateLimit<'_>) -> Result<(), (Status, Json<ApiError>)> {
+    let Some(rl_result) = rate_limit.check(RouteGroup::BoardCreation).await else {
+        return Ok(());
+    };
+    if !rl_result.allowed {
+        let per = match rate_limit.rule(RouteGroup::BoardCreation).window_secs {
+            3600 => "per hour".to_string(),
+            86400 => "per day".to_string(),
+            60 => "per minute".to_string(),
+            secs => format!("every {} seconds", secs),
+        };
+        return Err((
+            Status::TooManyRequests,
+            Json(ApiError {
+                error: format!(
+                    "Rate limit exceeded. You can create {} boards {}. Try again in {} seconds.",
+                    rl_result.limit, per, rl_result.reset_secs
+                ),
+                code: "RATE_LIMIT_EXCEEDED".to_string(),
+                status: 429,
+            }),
+        ));
+    }
+    Ok(())
 }
 
 /// List boards — public boards only (unless authenticated, future feature).
-#[get("/boards?<include_archived>")]
-pub fn list_boards(
+#[get("/boards?<include_archived>&<tag>")]
+pub async fn list_boards(
     include_archived: Option<bool>,
+    tag: Option<&str>,
     db: &State<DbPool>,
 ) -> Result<Json<Vec<BoardSummary>>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
-    let show_archived = include_archived.unwrap_or(false);
+    let tag = tag.map(str::to_owned);
+    with_db(db, move |conn| {
+        let tag = tag.as_deref();
+        let show_archived = include_archived.unwrap_or(false);
 
-    let archive_filter = if show_archived {
-        ""
-    } else {
-        " AND b.archived = 0"
-    };
+        let archive_filter = if show_archived {
+            ""
+        } else {
+            " AND b.archived = 0"
+        };
+        let tag = tag.map(|t| t.trim().to_lowercase()).filter(|t| !t.is_empty());
+        let tag_filter = if tag.is_some() {
+            " AND EXISTS (SELECT 1 FROM board_tags bt WHERE bt.board_id = b.id AND bt.tag = ?1)"
+        } else {
+            ""
+        };
 
-    // Only show public boards in the listing
-    let sql = format!(
-        "SELECT b.id, b.name, b.description, b.archived, b.is_public, b.created_at,
-                (SELECT COUNT(*) FROM tasks t WHERE t.board_id = b.id)
-         FROM boards b
-         WHERE b.is_public = 1{}
-         ORDER BY b.created_at DESC",
-        archive_filter
-    );
+        // Only show public boards in the listing
+        let sql = format!(
+            "SELECT b.id, b.name, b.description, b.archived, b.is_public, b.created_at,
+                    (SELECT COUNT(*) FROM tasks t WHERE t.board_id = b.id),
+                    (SELECT json_group_array(tag) FROM (SELECT tag FROM board_tags WHERE board_id = b.id ORDER BY tag))
+             FROM boards b
+             WHERE b.is_public = 1{}{}
+             ORDER BY b.created_at DESC",
+            archive_filter, tag_filter
+        );
 
-    let mut stmt = conn.prepare(&sql).map_err(|e| db_error(&e.to_string()))?;
+        let mut stmt = conn.prepare(&sql).map_err(|e| db_error(&e.to_string()))?;
+        let params: Vec<&dyn rusqlite::types::ToSql> = tag.iter().map(|t| t as &dyn rusqlite::types::ToSql).collect();
 
-    let boards: Vec<BoardSummary> = stmt
-        .query_map([], |row| {
-            Ok(BoardSummary {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                archived: row.get::<_, i32>(3)? == 1,
-                is_public: row.get::<_, i32>(4)? == 1,
-                created_at: row.get(5)?,
-                task_count: row.get(6)?,
+        let boards: Vec<BoardSummary> = stmt
+            .query_map(params.as_slice(), |row| {
+                let tags: String = row.get(7)?;
+                Ok(BoardSummary {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    description: row.get(2)?,
+                    archived: row.get::<_, i32>(3)? == 1,
+                    is_public: row.get::<_, i32>(4)? == 1,
+                    tags: serde_json::from_str(&tags).unwrap_or_default(),
+                    created_at: row.get(5)?,
+                    task_count: row.get(6)?,
+                })
             })
-        })
-        .map_err(|e| db_error(&e.to_string()))?
-        .filter_map(|r| r.ok())
-        .collect();
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
 
-    Ok(Json(boards))
+        Ok(Json(boards))
+    })
+    .await
 }
 
 // ============ Update Board Settings ============
 
 /// Update board name, description, or public flag — requires manage key.
 #[patch("/boards/<board_id>", format = "json", data = "<req>")]
-pub fn update_board(
+pub async fn update_board(
     board_id: &str,
     req: Json<UpdateBoardRequest>,
     token: BoardToken,
+    actor: Actor,
+    bus: &State<EventBus>,
     db: &State<DbPool>,
 ) -> Result<Json<BoardResponse>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
-    let token_hash = hash_key(&token.0);
-    access::require_board_exists(&conn, board_id)?;
-    access::require_manage_key(&conn, board_id, &token_hash)?;
-
-    // Build dynamic update
-    let mut updates = Vec::new();
-    let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
-
-    if let Some(ref name) = req.name {
-        let trimmed = name.trim();
-        if trimmed.is_empty() {
-            return Err((Status::BadRequest, Json(ApiError {
-                error: "Board name cannot be empty".to_string(),
-                code: "INVALID_INPUT".to_string(),
-                status: 400,
-            })));
-        }
-        updates.push("name = ?");
-        params.push(Box::new(trimmed.to_string()));
-    }
-    if let Some(ref desc) = req.description {
-        updates.push("description = ?");
-        params.push(Box::new(desc.trim().to_string()));
-    }
-    if let Some(is_public) = req.is_public {
-        updates.push("is_public = ?");
-        params.push(Box::new(is_public as i32));
-    }
-    if let Some(ref col_id) = req.quick_done_column_id {
-        if col_id.is_empty() {
-            // Empty string clears the setting (use default last column)
-            updates.push("quick_done_column_id = NULL");
-        } else {
-            // Validate that the column exists on this board
-            let col_exists: bool = conn
-                .query_row(
-                    "SELECT COUNT(*) > 0 FROM columns WHERE id = ?1 AND board_id = ?2",
-                    rusqlite::params![col_id, board_id],
-                    |row| row.get(0),
-                )
-                .unwrap_or(false);
-            if !col_exists {
+    let (board_id, bus) = (board_id.to_owned(), bus.inner().clone());
+    with_tx(db, move |conn| {
+        let (board_id, bus) = (board_id.as_str(), &bus);
+        let token_hash = hash_key(&token.0);
+        access::require_board_exists(conn, board_id)?;
+        access::require_manage_key(conn, board_id, &token_hash)?;
+
+        // Build dynamic update
+        let mut updates = Vec::new();
+        let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+
+        if let Some(ref name) = req.name {
+            let trimmed = name.trim();
+            if trimmed.is_empty() {
                 return Err((Status::BadRequest, Json(ApiError {
-                    error: "quick_done_column_id must reference a column on this board".to_string(),
-                    code: "INVALID_COLUMN".to_string(),
+                    error: "Board name cannot be empty".to_string(),
+                    code: "INVALID_INPUT".to_string(),
                     status: 400,
                 })));
             }
-            updates.push("quick_done_column_id = ?");
-            params.push(Box::new(col_id.clone()));
+            updates.push("name = ?");
+            params.push(Box::new(trimmed.to_string()));
         }
-    }
-    if let Some(auto_archive) = req.quick_done_auto_archive {
-        updates.push("quick_done_auto_archive = ?");
-        params.push(Box::new(auto_archive as i32));
-    }
-    if let Some(ref col_id) = req.quick_reassign_column_id {
-        if col_id.is_empty() {
-            updates.push("quick_reassign_column_id = NULL");
-        } else {
-            let col_exists: bool = conn
-                .query_row(
-                    "SELECT COUNT(*) > 0 FROM columns WHERE id = ?1 AND board_id = ?2",
-                    rusqlite::params![col_id, board_id],
-                    |row| row.get(0),
-                )
-                .unwrap_or(false);
-            if !col_exists {
-                return Err((Status::BadRequest, Json(ApiError {
-                    error: "quick_reassign_column_id must reference a column on this board".to_string(),
-                    code: "INVALID_COLUMN".to_string(),
-                    status: 400,
-                })));
-            }
-            updates.push("quick_reassign_column_id = ?");
-            params.push(Box::new(col_id.clone()));
+        if let Some(ref desc) = req.description {
+            updates.push("description = ?");
+            params.push(Box::new(desc.trim().to_string()));
         }
-    }
-    if let Some(ref reassign_to) = req.quick_reassign_to {
-        if reassign_to.is_empty() {
-            updates.push("quick_reassign_to = NULL");
-        } else {
-            updates.push("quick_reassign_to = ?");
-            params.push(Box::new(reassign_to.trim().to_string()));
+        if let Some(visibility) = req.visibility {
+            updates.push("visibility = ?");
+            params.push(Box::new(visibility.as_str()));
+            updates.push("is_public = ?");
+            params.push(Box::new((visibility == Visibility::Public) as i32));
+        } else if let Some(is_public) = req.is_public {
+            // Unlisting a private board leaves it private
+            updates.push(if is_public {
+                "visibility = 'public'"
+            } else {
+                "visibility = CASE WHEN visibility = 'private' THEN 'private' ELSE 'unlisted' END"
+            });
+            updates.push("is_public = ?");
+            params.push(Box::new(is_public as i32));
+        }
+        if let Some(ref password) = req.view_password {
+            // Changing or clearing the password ends every view session
+            let hash = (!password.is_empty()).then(|| view_password::hash_password(password)).transpose()?;
+            updates.push("view_password_hash = ?");
+            params.push(Box::new(hash));
+        }
+        if let Some(ref col_id) = req.quick_done_column_id {
+            if col_id.is_empty() {
+                // Empty string clears the setting (use default last column)
+                updates.push("quick_done_column_id = NULL");
+            } else {
+                // Validate that the column exists on this board
+                let col_exists: bool = conn
+                    .query_row(
+                        "SELECT COUNT(*) > 0 FROM columns WHERE id = ?1 AND board_id = ?2",
+                        rusqlite::params![col_id, board_id],
+                        |row| row.get(0),
+                    )
+                    .unwrap_or(false);
+                if !col_exists {
+                    return Err((Status::BadRequest, Json(ApiError {
+                        error: "quick_done_column_id must reference a column on this board".to_string(),
+                        code: "INVALID_COLUMN".to_string(),
+                        status: 400,
+                    })));
+                }
+                updates.push("quick_done_column_id = ?");
+                params.push(Box::new(col_id.clone()));
+            }
+        }
+        if let Some(auto_archive) = req.quick_done_auto_archive {
+            updates.push("quick_done_auto_archive = ?");
+            params.push(Box::new(auto_archive as i32));
+        }
+        if let Some(ref col_id) = req.quick_reassign_column_id {
+            if col_id.is_empty() {
+                updates.push("quick_reassign_column_id = NULL");
+            } else {
+                let col_exists: bool = conn
+                    .query_row(
+                        "SELECT COUNT(*) > 0 FROM columns WHERE id = ?1 AND board_id = ?2",
+                        rusqlite::params![col_id, board_id],
+                        |row| row.get(0),
+                    )
+                    .unwrap_or(false);
+                if !col_exists {
+                    return Err((Status::BadRequest, Json(ApiError {
+                        error: "quick_reassign_column_id must reference a column on this board".to_string(),
+                        code: "INVALID_COLUMN".to_string(),
+                        status: 400,
+                    })));
+                }
+                updates.push("quick_reassign_column_id = ?");
+                params.push(Box::new(col_id.clone()));
+            }
+        }
+        if let Some(ref reassign_to) = req.quick_reassign_to {
+            if reassign_to.is_empty() {
+                updates.push("quick_reassign_to = NULL");
+            } else {
+                updates.push("quick_reassign_to = ?");
+                params.push(Box::new(reassign_to.trim().to_string()));
+            }
+        }
+        if let Some(require_display_name) = req.require_display_name {
+            updates.push("require_display_name = ?");
+            params.push(Box::new(require_display_name as i32));
+        }
+        if let Some(enforce_dependencies) = req.enforce_dependencies {
+            updates.push("enforce_dependencies = ?");
+            params.push(Box::new(enforce_dependencies as i32));
+        }
+        // Retention policies: 0 clears the policy (keep forever)
+        for (field, update, days) in [
+            ("archived_task_retention_days", "archived_task_retention_days = NULLIF(?, 0)", req.archived_task_retention_days),
+            ("event_retention_days", "event_retention_days = NULLIF(?, 0)", req.event_retention_days),
+        ] {
+            let Some(days) = days else { continue };
+            if days < 0 {
+                return Err((Status::BadRequest, Json(ApiError {
+                    error: format!("{} must be 0 (keep forever) or a positive number of days", field),
+                    code: "INVALID_INPUT".to_string(),
+                    status: 400,
+                })));
+            }
+            updates.push(update);
+            params.push(Box::new(days));
         }
-    }
-    if let Some(require_display_name) = req.require_display_name {
-        updates.push("require_display_name = ?");
-        params.push(Box::new(require_display_name as i32));
-    }
 
-    if updates.is_empty() {
-        return load_board_response(&conn, board_id);
-    }
+        if let Some(seconds) = req.duplicate_window_seconds {
+            if !(0..=MAX_DUPLICATE_WINDOW_SECONDS).contains(&seconds) {
+                return Err((Status::BadRequest, Json(ApiError {
+                    error: format!(
+                        "duplicate_window_seconds must be between 0 (off) and {}",
+                        MAX_DUPLICATE_WINDOW_SECONDS
+                    ),
+                    code: "INVALID_INPUT".to_string(),
+                    status: 400,
+                })));
+            }
+            updates.push("duplicate_window_seconds = NULLIF(?, 0)");
+            params.push(Box::new(seconds));
+        }
+
+        if let Some(threshold) = req.comment_pressure_threshold {
+            if !(0..=comment_pressure::MAX_THRESHOLD).contains(&threshold) {
+                return Err((Status::BadRequest, Json(ApiError {
+                    error: format!(
+                        "comment_pressure_threshold must be between 0 (off) and {}",
+                        comment_pressure::MAX_THRESHOLD
+                    ),
+                    code: "INVALID_INPUT".to_string(),
+                    status: 400,
+                })));
+            }
+            updates.push("comment_pressure_threshold = NULLIF(?, 0)");
+            params.push(Box::new(threshold));
+        }
+
+        if let Some(ref prefix) = req.task_prefix {
+            let prefix = prefix.trim();
+            if prefix.len() > 10 || !prefix.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+                return Err((Status::BadRequest, Json(ApiError {
+                    error: "task_prefix must be at most 10 letters, digits, '-' or '_'".to_string(),
+                    code: "INVALID_INPUT".to_string(),
+                    status: 400,
+                })));
+            }
+            // Keep the old keys resolvable before they change
+            record_task_key_aliases(conn, board_id).map_err(|e| db_error(&e.to_string()))?;
+            if prefix.is_empty() {
+                updates.push("task_prefix = NULL");
+            } else {
+                updates.push("task_prefix = ?");
+                params.push(Box::new(prefix.to_string()));
+            }
+        }
+
+        if let Some(ref tags) = req.tags {
+            tags::set_board_tags(conn, board_id, tags)?;
+        }
 
-    updates.push("updated_at = datetime('now')");
-    let sql = format!("UPDATE boards SET {} WHERE id = ?", updates.join(", "));
-    params.push(Box::new(board_id.to_string()));
+        if updates.is_empty() && req.tags.is_none() {
+            return load_board_response(conn, board_id);
+        }
+
+        let mut fields: Vec<&str> = updates.iter().filter_map(|u| u.split(' ').next()).collect();
+        if req.tags.is_some() {
+            fields.push("tags");
+        }
+        updates.push("updated_at = datetime('now')");
+        let sql = format!("UPDATE boards SET {} WHERE id = ?", updates.join(", "));
+        params.push(Box::new(board_id.to_string()));
 
-    let param_refs: Vec<&dyn rusqlite::types::ToSql> = params.iter().map(|p| p.as_ref()).collect();
-    conn.execute(&sql, param_refs.as_slice()).map_err(|e| db_error(&e.to_string()))?;
+        let param_refs: Vec<&dyn rusqlite::types::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        conn.execute(&sql, param_refs.as_slice()).map_err(|e| db_error(&e.to_string()))?;
 
-    load_board_response(&conn, board_id)
+        let response = load_board_response(conn, board_id)?;
+        board_events::emit(
+            conn,
+            bus,
+            board_id,
+            event_types::BOARD_UPDATED,
+            actor.name(),
+            serde_json::json!({"fields": fields, "name": response.name, "is_public": response.is_public, "visibility": response.visibility}),
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        Ok(response)
+    })
+    .await
 }
 
 // ============ Board Archive / Unarchive ============
 
 /// Archive a board — requires manage key.
 #[post("/boards/<board_id>/archive")]
-pub fn archive_board(
+pub async fn archive_board(
     board_id: &str,
     token: BoardToken,
+    actor: Actor,
+    hooks: &State<LifecycleHooks>,
+    bus: &State<EventBus>,
     db: &State<DbPool>,
 ) -> Result<Json<BoardResponse>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
+    let bus = bus.inner().clone();
+    let (board, archiver) = (board_id.to_owned(), actor.clone());
+    let response = with_tx(db, move |conn| {
+        let (board_id, actor) = (board.as_str(), &archiver);
+        let bus = &bus;
+        let token_hash = hash_key(&token.0);
+        access::require_manage_key(conn, board_id, &token_hash)?;
+
+        let already_archived: bool = conn
+            .query_row(
+                "SELECT archived = 1 FROM boards WHERE id = ?1",
+                rusqlite::params![board_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
 
-    let already_archived: bool = conn
-        .query_row(
-            "SELECT archived = 1 FROM boards WHERE id = ?1",
+        if already_archived {
+            return Err((
+                Status::Conflict,
+                Json(ApiError {
+                    error: "Board is already archived".to_string(),
+                    code: "ALREADY_ARCHIVED".to_string(),
+                    status: 409,
+                }),
+            ));
+        }
+
+        conn.execute(
+            "UPDATE boards SET archived = 1, updated_at = datetime('now') WHERE id = ?1",
             rusqlite::params![board_id],
-            |row| row.get(0),
         )
-        .unwrap_or(false);
-
-    if already_archived {
-        return Err((
-            Status::Conflict,
-            Json(ApiError {
-                error: "Board is already archived".to_string(),
-                code: "ALREADY_ARCHIVED".to_string(),
-                status: 409,
-            }),
-        ));
-    }
+        .map_err(|e| db_error(&e.to_string()))?;
 
-    conn.execute(
-        "UPDATE boards SET archived = 1, updated_at = datetime('now') WHERE id = ?1",
-        rusqlite::params![board_id],
-    )
-    .map_err(|e| db_error(&e.to_string()))?;
+        let response = load_board_response(conn, board_id)?;
+        board_events::emit(
+            conn,
+            bus,
+            board_id,
+            event_types::BOARD_ARCHIVED,
+            actor.name(),
+            serde_json::json!({"name": response.name}),
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        Ok(response)
+    })
+    .await?;
 
-    load_board_response(&conn, board_id)
+    hooks.notify(db, lifecycle::BOARD_ARCHIVED, board_id, Some(actor.name())).await;
+    Ok(response)
 }
 
 /// Unarchive a board — requires manage key.
 #[post("/boards/<board_id>/unarchive")]
-pub fn unarchive_board(
+pub async fn unarchive_board(
     board_id: &str,
     token: BoardToken,
+    actor: Actor,
+    bus: &State<EventBus>,
     db: &State<DbPool>,
 ) -> Result<Json<BoardResponse>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
+    let (board_id, bus) = (board_id.to_owned(), bus.inner().clone());
+    with_tx(db, move |conn| {
+        let (board_id, bus) = (board_id.as_str(), &bus);
+        let token_hash = hash_key(&token.0);
+        access::require_manage_key(conn, board_id, &token_hash)?;
+        unarchive_board_row(conn, bus, board_id, actor.name())
+    })
+    .await
+}
 
+/// Take a board out of the archive and log it; 409 when it isn't archived.
+/// Shared with the admin override.
+pub(crate) fn unarchive_board_row(
+    conn: &Connection,
+    bus: &EventBus,
+    board_id: &str,
+    actor: &str,
+) -> Result<Json<BoardResponse>, (Status, Json<ApiError>)> {
     let is_archived: bool = conn
         .query_row(
             "SELECT archived = 1 FROM boards WHERE id = ?1",
@@ -474,332 +1151,787 @@ pub fn unarchive_board(
     )
     .map_err(|e| db_error(&e.to_string()))?;
 
-    load_board_response(&conn, board_id)
+    let response = load_board_response(conn, board_id)?;
+    board_events::emit(
+        conn,
+        bus,
+        board_id,
+        event_types::BOARD_UNARCHIVED,
+        actor,
+        serde_json::json!({"name": response.name}),
+    )
+    .map_err(|e| db_error(&e.to_string()))?;
+    Ok(response)
+}
+
+// ============ Manage Key Rotation ============
+
+/// Replace the board's manage key — requires the current one. The old key
+/// stops working at once; collaborator and share keys are untouched.
+#[post("/boards/<board_id>/rotate-key")]
+pub async fn rotate_manage_key(
+    board_id: &str,
+    token: BoardToken,
+    actor: Actor,
+    bus: &State<EventBus>,
+    db: &State<DbPool>,
+) -> Result<Json<RotateKeyResponse>, (Status, Json<ApiError>)> {
+    let (board_id, bus) = (board_id.to_owned(), bus.inner().clone());
+    with_tx(db, move |conn| {
+        let (board_id, bus) = (board_id.as_str(), &bus);
+        access::require_board_exists(conn, board_id)?;
+
+        let manage_key = format!("kb_{}", uuid::Uuid::new_v4().to_string().replace('-', ""));
+        let rotated = conn
+            .execute(
+                "UPDATE boards SET manage_key_hash = ?1, updated_at = datetime('now') WHERE id = ?2 AND manage_key_hash = ?3",
+                rusqlite::params![hash_key(&manage_key), board_id, hash_key(&token.0)],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        if rotated == 0 {
+            return Err((
+                Status::Forbidden,
+                Json(ApiError {
+                    error: "Only the board's current manage key can rotate it".to_string(),
+                    code: "INVALID_KEY".to_string(),
+                    status: 403,
+                }),
+            ));
+        }
+
+        let name: String = conn
+            .query_row("SELECT name FROM boards WHERE id = ?1", rusqlite::params![board_id], |row| row.get(0))
+            .map_err(|e| db_error(&e.to_string()))?;
+        board_events::emit(
+            conn,
+            bus,
+            board_id,
+            event_types::BOARD_KEY_ROTATED,
+            actor.name(),
+            serde_json::json!({"name": name}),
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        Ok(Json(RotateKeyResponse {
+            board_id: board_id.to_string(),
+            manage_url: format!("/board/{}?key={}", board_id, manage_key),
+            manage_key,
+            rotated_at: chrono::Utc::now().to_rfc3339(),
+        }))
+    })
+    .await
+}
+
+// ============ Board Deletion ============
+
+/// Permanently delete a board and everything on it — requires the owner role and
+/// `?confirm=<board name>`. Unlike archiving this cannot be undone.
+#[delete("/boards/<board_id>?<confirm>")]
+pub async fn delete_board(
+    board_id: &str,
+    confirm: Option<&str>,
+    token: BoardToken,
+    actor: Actor,
+    hooks: &State<LifecycleHooks>,
+    db: &State<DbPool>,
+) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
+    let confirm = confirm.map(str::to_owned);
+    let board = board_id.to_owned();
+    let (response, snapshot) = with_db(db, move |conn| {
+        let board_id = board.as_str();
+        let confirm = confirm.as_deref();
+        let token_hash = hash_key(&token.0);
+        access::require_board_exists(conn, board_id)?;
+        access::require_role(conn, board_id, &token_hash, BoardRole::Owner)?;
+
+        let name: String = conn
+            .query_row("SELECT name FROM boards WHERE id = ?1", rusqlite::params![board_id], |row| row.get(0))
+            .map_err(|e| db_error(&e.to_string()))?;
+        if confirm != Some(name.as_str()) {
+            return Err((Status::BadRequest, Json(ApiError {
+                error: "Pass ?confirm=<board name> to permanently delete this board".to_string(),
+                code: "CONFIRMATION_REQUIRED".to_string(),
+                status: 400,
+            })));
+        }
+
+        purge_board(conn, board_id, &name)
+    })
+    .await?;
+
+    hooks.send(lifecycle::BOARD_DELETED, board_id, snapshot, Some(actor.name()));
+    Ok(Json(response))
+}
+
+/// Delete a board for good, returning the deletion summary and the board as
+/// it was (for lifecycle hooks). Shared with the admin override.
+pub(crate) fn purge_board(
+    conn: &Connection,
+    board_id: &str,
+    name: &str,
+) -> Result<(serde_json::Value, serde_json::Value), (Status, Json<ApiError>)> {
+    // Snapshot for lifecycle hooks while the board still exists
+    let snapshot = lifecycle::board_payload(conn, board_id).map_err(|e| db_error(&e.to_string()))?;
+
+    let tx = conn.unchecked_transaction().map_err(|e| db_error(&e.to_string()))?;
+    let deleted = delete_board_rows(&tx, board_id).map_err(|e| db_error(&e.to_string()))?;
+    tx.commit().map_err(|e| db_error(&e.to_string()))?;
+
+    Ok((
+        serde_json::json!({
+            "deleted": true,
+            "id": board_id,
+            "name": name,
+            "tasks_deleted": deleted.tasks,
+            "columns_deleted": deleted.columns,
+            "webhooks_deleted": deleted.webhooks,
+            "events_deleted": deleted.events,
+        }),
+        snapshot,
+    ))
+}
+
+/// Row counts removed by `delete_board_rows`.
+pub(crate) struct DeletedBoard {
+    pub tasks: usize,
+    pub columns: usize,
+    pub webhooks: usize,
+    pub events: usize,
+}
+
+/// Delete a board and everything hanging off it. Run inside a transaction.
+pub(crate) fn delete_board_rows(conn: &Connection, board_id: &str) -> rusqlite::Result<DeletedBoard> {
+    let delete = |sql: &str| conn.execute(sql, rusqlite::params![board_id]);
+    let tasks_on_board = "SELECT id FROM tasks WHERE board_id = ?1";
+    delete("DELETE FROM task_dependencies WHERE board_id = ?1")?;
+    let events = delete(&format!("DELETE FROM task_events WHERE task_id IN ({})", tasks_on_board))?;
+    delete(&format!("DELETE FROM task_locks WHERE task_id IN ({})", tasks_on_board))?;
+    delete(&format!(
+        "DELETE FROM email_reminders WHERE task_id IN ({}) OR task_id IN (SELECT task_id FROM task_trash WHERE board_id = ?1)",
+        tasks_on_board
+    ))?;
+    delete("DELETE FROM task_trash WHERE board_id = ?1")?;
+    delete("DELETE FROM task_key_aliases WHERE board_id = ?1")?;
+    delete("DELETE FROM task_watchers WHERE board_id = ?1")?;
+    delete("DELETE FROM inbox_cursors WHERE board_id = ?1")?;
+    delete("DELETE FROM notification_preferences WHERE board_id = ?1")?;
+    delete("DELETE FROM email_subscriptions WHERE board_id = ?1")?;
+    delete("DELETE FROM email_outbox WHERE board_id = ?1")?;
+    let tasks = delete("DELETE FROM tasks WHERE board_id = ?1")?;
+    let columns = delete("DELETE FROM columns WHERE board_id = ?1")?;
+    delete("DELETE FROM webhook_dead_letters WHERE board_id = ?1")?;
+    delete("DELETE FROM webhook_deliveries WHERE board_id = ?1")?;
+    delete("DELETE FROM webhook_outbox WHERE board_id = ?1")?;
+    delete("DELETE FROM webhook_routes WHERE board_id = ?1")?;
+    delete("DELETE FROM webhook_signing_keys WHERE board_id = ?1")?;
+    let webhooks = delete("DELETE FROM webhooks WHERE board_id = ?1")?;
+    delete("DELETE FROM board_views WHERE board_id = ?1")?;
+    delete("DELETE FROM board_tags WHERE board_id = ?1")?;
+    delete("DELETE FROM validation_hooks WHERE board_id = ?1")?;
+    delete("DELETE FROM inbound_hooks WHERE board_id = ?1")?;
+    delete("DELETE FROM board_members WHERE board_id = ?1")?;
+    delete("DELETE FROM share_keys WHERE board_id = ?1")?;
+    delete("DELETE FROM scoped_tokens WHERE board_id = ?1")?;
+    delete("DELETE FROM agent_keys WHERE board_id = ?1")?;
+    delete("DELETE FROM comment_transcripts WHERE board_id = ?1")?;
+    delete("DELETE FROM comment_revisions WHERE board_id = ?1")?;
+    delete("DELETE FROM board_events WHERE board_id = ?1")?;
+    // Attachment rows stay for `attachments::sweep`, which also removes their files
+    delete("DELETE FROM boards WHERE id = ?1")?;
+    Ok(DeletedBoard {
+        tasks,
+        columns,
+        webhooks,
+        events,
+    })
 }
 
 /// Get board details — public, no auth required. Anyone with the UUID can view.
 #[get("/boards/<board_id>")]
-pub fn get_board(
+pub async fn get_board(
     board_id: &str,
+    token: Option<BoardToken>,
     db: &State<DbPool>,
 ) -> Result<Json<BoardResponse>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
-    load_board_response(&conn, board_id)
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        let board = load_board_response(conn, board_id)?;
+        crate::analytics::record_view(conn, board_id, crate::analytics::VIEW_BOARD_LOAD);
+        Ok(board)
+    })
+    .await
 }
 
 // ============ Columns ============
 
 /// Create a column — requires manage key.
 #[post("/boards/<board_id>/columns", format = "json", data = "<req>")]
-pub fn create_column(
+pub async fn create_column(
     board_id: &str,
     req: Json<CreateColumnRequest>,
     token: BoardToken,
+    actor: Actor,
+    bus: &State<EventBus>,
     db: &State<DbPool>,
 ) -> Result<Json<ColumnResponse>, (Status, Json<ApiError>)> {
     let req = req.into_inner();
-    let conn = db.lock().unwrap();
+    let (board_id, bus) = (board_id.to_owned(), bus.inner().clone());
+    with_tx(db, move |conn| {
+        let (board_id, bus) = (board_id.as_str(), &bus);
+        let token_hash = hash_key(&token.0);
+        access::require_role(conn, board_id, &token_hash, BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+
+        let position = req.position.unwrap_or_else(|| {
+            conn.query_row(
+                "SELECT COALESCE(MAX(position), -1) + 1 FROM columns WHERE board_id = ?1",
+                rusqlite::params![board_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(0)
+        });
 
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
-    access::require_not_archived(&conn, board_id)?;
+        let strategy = check_auto_assign_strategy(req.auto_assign_strategy.as_deref())?;
+        let pool = auto_assign::normalize_pool(&req.auto_assign);
 
-    let position = req.position.unwrap_or_else(|| {
-        conn.query_row(
-            "SELECT COALESCE(MAX(position), -1) + 1 FROM columns WHERE board_id = ?1",
-            rusqlite::params![board_id],
-            |row| row.get(0),
+        let col_id = uuid::Uuid::new_v4().to_string();
+        conn.execute(
+            "INSERT INTO columns (id, board_id, name, position, wip_limit, auto_assign, auto_assign_strategy)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![col_id, board_id, req.name, position, req.wip_limit, pool, strategy],
         )
-        .unwrap_or(0)
-    });
+        .map_err(|e| db_error(&e.to_string()))?;
+        board_events::emit(
+            conn,
+            bus,
+            board_id,
+            event_types::COLUMN_CREATED,
+            actor.name(),
+            serde_json::json!({"column_id": col_id, "name": req.name, "position": position, "wip_limit": req.wip_limit}),
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
 
-    let col_id = uuid::Uuid::new_v4().to_string();
-    conn.execute(
-        "INSERT INTO columns (id, board_id, name, position, wip_limit) VALUES (?1, ?2, ?3, ?4, ?5)",
-        rusqlite::params![col_id, board_id, req.name, position, req.wip_limit],
-    )
-    .map_err(|e| db_error(&e.to_string()))?;
+        Ok(Json(ColumnResponse {
+            id: col_id,
+            name: req.name,
+            position,
+            wip_limit: req.wip_limit,
+            task_count: 0,
+            auto_assign: auto_assign::parse_pool(pool),
+            auto_assign_strategy: strategy.unwrap_or(auto_assign::ROUND_ROBIN).to_string(),
+        }))
+    })
+    .await
+}
 
-    Ok(Json(ColumnResponse {
-        id: col_id,
-        name: req.name,
-        position,
-        wip_limit: req.wip_limit,
-        task_count: 0,
-    }))
+/// Validate a requested auto-assignment strategy.
+fn check_auto_assign_strategy(strategy: Option<&str>) -> Result<Option<&str>, (Status, Json<ApiError>)> {
+    match strategy {
+        Some(s) if !auto_assign::STRATEGIES.contains(&s) => Err((Status::BadRequest, Json(ApiError {
+            error: format!("Unknown auto_assign_strategy '{}'. Use one of: {}", s, auto_assign::STRATEGIES.join(", ")),
+            code: "INVALID_INPUT".to_string(),
+            status: 400,
+        }))),
+        _ => Ok(strategy),
+    }
 }
 
-/// Update a column (rename, change WIP limit) — requires manage key.
+/// Update a column (rename, change WIP limit, auto-assignment) — requires manage key.
 #[patch("/boards/<board_id>/columns/<column_id>", format = "json", data = "<req>")]
-pub fn update_column(
+pub async fn update_column(
     board_id: &str,
     column_id: &str,
     req: Json<UpdateColumnRequest>,
     token: BoardToken,
+    actor: Actor,
+    bus: &State<EventBus>,
     db: &State<DbPool>,
 ) -> Result<Json<ColumnResponse>, (Status, Json<ApiError>)> {
     let req = req.into_inner();
-    let conn = db.lock().unwrap();
-
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
-    access::require_not_archived(&conn, board_id)?;
-
-    // Verify column exists and belongs to this board
-    let col: (String, i32, Option<i32>) = conn
-        .query_row(
-            "SELECT name, position, wip_limit FROM columns WHERE id = ?1 AND board_id = ?2",
-            rusqlite::params![column_id, board_id],
-            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
-        )
-        .map_err(|_| {
-            (
-                Status::NotFound,
-                Json(ApiError {
-                    error: "Column not found".to_string(),
-                    code: "COLUMN_NOT_FOUND".to_string(),
-                    status: 404,
-                }),
+    let (board_id, bus, column_id) = (board_id.to_owned(), bus.inner().clone(), column_id.to_owned());
+    with_tx(db, move |conn| {
+        let (board_id, bus, column_id) = (board_id.as_str(), &bus, column_id.as_str());
+        let token_hash = hash_key(&token.0);
+        access::require_role(conn, board_id, &token_hash, BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+
+        // Verify column exists and belongs to this board
+        let col: (String, i32, Option<i32>, Option<String>, Option<String>) = conn
+            .query_row(
+                "SELECT name, position, wip_limit, auto_assign, auto_assign_strategy FROM columns WHERE id = ?1 AND board_id = ?2",
+                rusqlite::params![column_id, board_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
             )
-        })?;
-
-    let new_name = req.name.unwrap_or(col.0);
-    let new_wip = match req.wip_limit {
-        Some(wip) => wip, // explicitly set (Some(n) or None to clear)
-        None => col.2,    // not provided, keep existing
-    };
+            .map_err(|_| {
+                (
+                    Status::NotFound,
+                    Json(ApiError {
+                        error: "Column not found".to_string(),
+                        code: "COLUMN_NOT_FOUND".to_string(),
+                        status: 404,
+                    }),
+                )
+            })?;
 
-    conn.execute(
-        "UPDATE columns SET name = ?1, wip_limit = ?2 WHERE id = ?3 AND board_id = ?4",
-        rusqlite::params![new_name, new_wip, column_id, board_id],
-    )
-    .map_err(|e| db_error(&e.to_string()))?;
+        let fields: Vec<&str> = [
+            ("name", req.name.is_some()),
+            ("wip_limit", req.wip_limit.is_some()),
+            ("auto_assign", req.auto_assign.is_some()),
+            ("auto_assign_strategy", req.auto_assign_strategy.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(field, given)| given.then_some(field))
+        .collect();
+        let new_name = req.name.unwrap_or(col.0);
+        let new_wip = match req.wip_limit {
+            Some(wip) => wip, // explicitly set (Some(n) or None to clear)
+            None => col.2,    // not provided, keep existing
+        };
+        let new_pool = match req.auto_assign {
+            Some(ref agents) => auto_assign::normalize_pool(agents),
+            None => col.3,
+        };
+        let new_strategy = check_auto_assign_strategy(req.auto_assign_strategy.as_deref())?
+            .map(str::to_string)
+            .or(col.4);
 
-    let task_count: i64 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM tasks WHERE column_id = ?1",
-            rusqlite::params![column_id],
-            |row| row.get(0),
+        conn.execute(
+            "UPDATE columns SET name = ?1, wip_limit = ?2, auto_assign = ?3, auto_assign_strategy = ?4
+             WHERE id = ?5 AND board_id = ?6",
+            rusqlite::params![new_name, new_wip, new_pool, new_strategy, column_id, board_id],
         )
-        .unwrap_or(0);
+        .map_err(|e| db_error(&e.to_string()))?;
+        board_events::emit(
+            conn,
+            bus,
+            board_id,
+            event_types::COLUMN_UPDATED,
+            actor.name(),
+            serde_json::json!({"column_id": column_id, "name": new_name, "fields": fields, "wip_limit": new_wip}),
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
 
-    Ok(Json(ColumnResponse {
-        id: column_id.to_string(),
-        name: new_name,
-        position: col.1,
-        wip_limit: new_wip,
-        task_count,
-    }))
+        let task_count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM tasks WHERE column_id = ?1",
+                rusqlite::params![column_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(0);
+
+        Ok(Json(ColumnResponse {
+            id: column_id.to_string(),
+            name: new_name,
+            position: col.1,
+            wip_limit: new_wip,
+            task_count,
+            auto_assign: auto_assign::parse_pool(new_pool),
+            auto_assign_strategy: new_strategy.unwrap_or_else(|| auto_assign::ROUND_ROBIN.to_string()),
+        }))
+    })
+    .await
 }
 
 /// Delete a column — requires manage key.
 /// Fails if the column still contains tasks (must move/delete them first).
 #[delete("/boards/<board_id>/columns/<column_id>")]
-pub fn delete_column(
+pub async fn delete_column(
     board_id: &str,
     column_id: &str,
     token: BoardToken,
+    actor: Actor,
+    bus: &State<EventBus>,
     db: &State<DbPool>,
 ) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
+    let (board_id, bus, column_id) = (board_id.to_owned(), bus.inner().clone(), column_id.to_owned());
+    with_tx(db, move |conn| {
+        let (board_id, bus, column_id) = (board_id.as_str(), &bus, column_id.as_str());
+        let token_hash = hash_key(&token.0);
+        access::require_role(conn, board_id, &token_hash, BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+
+        // Verify column exists and belongs to this board
+        let (col_name, col_position): (String, i32) = conn
+            .query_row(
+                "SELECT name, position FROM columns WHERE id = ?1 AND board_id = ?2",
+                rusqlite::params![column_id, board_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|_| {
+                (
+                    Status::NotFound,
+                    Json(ApiError {
+                        error: "Column not found".to_string(),
+                        code: "COLUMN_NOT_FOUND".to_string(),
+                        status: 404,
+                    }),
+                )
+            })?;
 
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
-    access::require_not_archived(&conn, board_id)?;
+        // Check if column has tasks
+        let task_count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM tasks WHERE column_id = ?1",
+                rusqlite::params![column_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(0);
 
-    // Verify column exists and belongs to this board
-    let col_position: i32 = conn
-        .query_row(
-            "SELECT position FROM columns WHERE id = ?1 AND board_id = ?2",
-            rusqlite::params![column_id, board_id],
-            |row| row.get(0),
-        )
-        .map_err(|_| {
-            (
-                Status::NotFound,
+        if task_count > 0 {
+            return Err((
+                Status::Conflict,
                 Json(ApiError {
-                    error: "Column not found".to_string(),
-                    code: "COLUMN_NOT_FOUND".to_string(),
-                    status: 404,
+                    error: format!(
+                        "Column has {} task(s). Move or delete them before removing the column.",
+                        task_count
+                    ),
+                    code: "COLUMN_NOT_EMPTY".to_string(),
+                    status: 409,
                 }),
-            )
-        })?;
+            ));
+        }
 
-    // Check if column has tasks
-    let task_count: i64 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM tasks WHERE column_id = ?1",
-            rusqlite::params![column_id],
-            |row| row.get(0),
-        )
-        .unwrap_or(0);
+        // Count total columns — prevent deleting the last one
+        let total_columns: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM columns WHERE board_id = ?1",
+                rusqlite::params![board_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(0);
 
-    if task_count > 0 {
-        return Err((
-            Status::Conflict,
-            Json(ApiError {
-                error: format!(
-                    "Column has {} task(s). Move or delete them before removing the column.",
-                    task_count
-                ),
-                code: "COLUMN_NOT_EMPTY".to_string(),
-                status: 409,
-            }),
-        ));
-    }
+        if total_columns <= 1 {
+            return Err((
+                Status::Conflict,
+                Json(ApiError {
+                    error: "Cannot delete the last column. A board must have at least one column."
+                        .to_string(),
+                    code: "LAST_COLUMN".to_string(),
+                    status: 409,
+                }),
+            ));
+        }
 
-    // Count total columns — prevent deleting the last one
-    let total_columns: i64 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM columns WHERE board_id = ?1",
-            rusqlite::params![board_id],
-            |row| row.get(0),
+        // Delete the column
+        conn.execute(
+            "DELETE FROM columns WHERE id = ?1 AND board_id = ?2",
+            rusqlite::params![column_id, board_id],
         )
-        .unwrap_or(0);
-
-    if total_columns <= 1 {
-        return Err((
-            Status::Conflict,
-            Json(ApiError {
-                error: "Cannot delete the last column. A board must have at least one column."
-                    .to_string(),
-                code: "LAST_COLUMN".to_string(),
-                status: 409,
-            }),
-        ));
-    }
-
-    // Delete the column
-    conn.execute(
-        "DELETE FROM columns WHERE id = ?1 AND board_id = ?2",
-        rusqlite::params![column_id, board_id],
-    )
-    .map_err(|e| db_error(&e.to_string()))?;
+        .map_err(|e| db_error(&e.to_string()))?;
+        let _ = conn.execute("DELETE FROM webhook_routes WHERE column_id = ?1", rusqlite::params![column_id]);
 
-    // Shift positions of columns after the deleted one
-    conn.execute(
-        "UPDATE columns SET position = position - 1 WHERE board_id = ?1 AND position > ?2",
-        rusqlite::params![board_id, col_position],
-    )
-    .map_err(|e| db_error(&e.to_string()))?;
+        // Shift positions of columns after the deleted one
+        conn.execute(
+            "UPDATE columns SET position = position - 1 WHERE board_id = ?1 AND position > ?2",
+            rusqlite::params![board_id, col_position],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        board_events::emit(
+            conn,
+            bus,
+            board_id,
+            event_types::COLUMN_DELETED,
+            actor.name(),
+            serde_json::json!({"column_id": column_id, "name": col_name}),
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
 
-    Ok(Json(serde_json::json!({ "deleted": true, "column_id": column_id })))
+        Ok(Json(serde_json::json!({ "deleted": true, "column_id": column_id })))
+    })
+    .await
 }
 
 /// Reorder columns — requires manage key.
 /// Accepts a list of column IDs in the desired order.
 #[post("/boards/<board_id>/columns/reorder", format = "json", data = "<req>")]
-pub fn reorder_columns(
+pub async fn reorder_columns(
     board_id: &str,
     req: Json<ReorderColumnsRequest>,
     token: BoardToken,
+    actor: Actor,
+    bus: &State<EventBus>,
     db: &State<DbPool>,
 ) -> Result<Json<Vec<ColumnResponse>>, (Status, Json<ApiError>)> {
     let req = req.into_inner();
-    let conn = db.lock().unwrap();
-
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
-    access::require_not_archived(&conn, board_id)?;
-
-    // Get existing column IDs for this board
-    let mut stmt = conn
-        .prepare("SELECT id FROM columns WHERE board_id = ?1")
-        .map_err(|e| db_error(&e.to_string()))?;
-    let existing_ids: Vec<String> = stmt
-        .query_map(rusqlite::params![board_id], |row| row.get(0))
-        .map_err(|e| db_error(&e.to_string()))?
-        .filter_map(|r| r.ok())
-        .collect();
-
-    // Validate: must contain exactly the same set of column IDs
-    if req.column_ids.len() != existing_ids.len() {
-        return Err((
-            Status::BadRequest,
-            Json(ApiError {
-                error: format!(
-                    "Expected {} column IDs, got {}",
-                    existing_ids.len(),
-                    req.column_ids.len()
-                ),
-                code: "INVALID_COLUMN_LIST".to_string(),
-                status: 400,
-            }),
-        ));
-    }
+    let (board_id, bus) = (board_id.to_owned(), bus.inner().clone());
+    with_tx(db, move |conn| {
+        let (board_id, bus) = (board_id.as_str(), &bus);
+        let token_hash = hash_key(&token.0);
+        access::require_role(conn, board_id, &token_hash, BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+
+        // Get existing column IDs for this board
+        let mut stmt = conn
+            .prepare("SELECT id FROM columns WHERE board_id = ?1")
+            .map_err(|e| db_error(&e.to_string()))?;
+        let existing_ids: Vec<String> = stmt
+            .query_map(rusqlite::params![board_id], |row| row.get(0))
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
 
-    for cid in &req.column_ids {
-        if !existing_ids.contains(cid) {
+        // Validate: must contain exactly the same set of column IDs
+        if req.column_ids.len() != existing_ids.len() {
             return Err((
                 Status::BadRequest,
                 Json(ApiError {
-                    error: format!("Column {} not found in this board", cid),
-                    code: "COLUMN_NOT_FOUND".to_string(),
+                    error: format!(
+                        "Expected {} column IDs, got {}",
+                        existing_ids.len(),
+                        req.column_ids.len()
+                    ),
+                    code: "INVALID_COLUMN_LIST".to_string(),
                     status: 400,
                 }),
-            )
-            );
+            ));
         }
-    }
 
-    // Update positions
-    for (i, col_id) in req.column_ids.iter().enumerate() {
-        conn.execute(
-            "UPDATE columns SET position = ?1 WHERE id = ?2 AND board_id = ?3",
-            rusqlite::params![i as i32, col_id, board_id],
-        )
-        .map_err(|e| db_error(&e.to_string()))?;
-    }
+        for cid in &req.column_ids {
+            if !existing_ids.contains(cid) {
+                return Err((
+                    Status::BadRequest,
+                    Json(ApiError {
+                        error: format!("Column {} not found in this board", cid),
+                        code: "COLUMN_NOT_FOUND".to_string(),
+                        status: 400,
+                    }),
+                )
+                );
+            }
+        }
 
-    // Return updated columns
-    let mut col_stmt = conn
-        .prepare(
-            "SELECT c.id, c.name, c.position, c.wip_limit,
-                    (SELECT COUNT(*) FROM tasks WHERE column_id = c.id) as task_count
-             FROM columns c WHERE c.board_id = ?1 ORDER BY c.position",
+        // Update positions
+        for (i, col_id) in req.column_ids.iter().enumerate() {
+            conn.execute(
+                "UPDATE columns SET position = ?1 WHERE id = ?2 AND board_id = ?3",
+                rusqlite::params![i as i32, col_id, board_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        }
+        board_events::emit(
+            conn,
+            bus,
+            board_id,
+            event_types::COLUMN_REORDERED,
+            actor.name(),
+            serde_json::json!({"column_ids": req.column_ids}),
         )
         .map_err(|e| db_error(&e.to_string()))?;
 
-    let columns: Vec<ColumnResponse> = col_stmt
-        .query_map(rusqlite::params![board_id], |row| {
-            Ok(ColumnResponse {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                position: row.get(2)?,
-                wip_limit: row.get(3)?,
-                task_count: row.get(4)?,
+        // Return updated columns
+        let mut col_stmt = conn
+            .prepare(
+                "SELECT c.id, c.name, c.position, c.wip_limit,
+                        (SELECT COUNT(*) FROM tasks WHERE column_id = c.id) as task_count,
+                        c.auto_assign, c.auto_assign_strategy
+                 FROM columns c WHERE c.board_id = ?1 ORDER BY c.position",
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+
+        let columns: Vec<ColumnResponse> = col_stmt
+            .query_map(rusqlite::params![board_id], |row| {
+                Ok(ColumnResponse {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    position: row.get(2)?,
+                    wip_limit: row.get(3)?,
+                    task_count: row.get(4)?,
+                    auto_assign: auto_assign::parse_pool(row.get(5)?),
+                    auto_assign_strategy: row.get::<_, Option<String>>(6)?.unwrap_or_else(|| auto_assign::ROUND_ROBIN.to_string()),
+                })
             })
-        })
-        .map_err(|e| db_error(&e.to_string()))?
-        .filter_map(|r| r.ok())
-        .collect();
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
 
-    Ok(Json(columns))
+        Ok(Json(columns))
+    })
+    .await
 }
 
 // ============ Tasks ============
 
 /// Create a task — requires manage key.
 #[post("/boards/<board_id>/tasks", format = "json", data = "<req>")]
-pub fn create_task(
+pub async fn create_task(
     board_id: &str,
     req: Json<CreateTaskRequest>,
     token: BoardToken,
+    actor: Actor,
     db: &State<DbPool>,
     bus: &State<EventBus>,
 ) -> Result<Json<TaskResponse>, (Status, Json<ApiError>)> {
     let req = req.into_inner();
-    let conn = db.lock().unwrap();
+    let (board_id, bus) = (board_id.to_owned(), bus.inner().clone());
+    let client = bus.outbound().clone();
+    validation_hook::run(db, &client, move |conn, gate| {
+        let (board_id, bus) = (board_id.as_str(), &bus);
+        let scope = access::require_action(conn, board_id, &token.0, ScopeAction::CreateTask)?;
+        access::require_not_archived(conn, board_id)?;
+
+        let actor = actor.or_body(Some(&req.actor_name));
+        access::require_display_name(conn, board_id, &actor)?;
+
+        require_task_content(&req.title, &req.description)?;
+
+        // Resolve column: use provided ID, or first column of the board
+        let column_id = match req.column_id {
+            Some(ref cid) => {
+                let exists: bool = conn
+                    .query_row(
+                        "SELECT COUNT(*) > 0 FROM columns WHERE id = ?1 AND board_id = ?2",
+                        rusqlite::params![cid, board_id],
+                        |row| row.get(0),
+                    )
+                    .unwrap_or(false);
+                if !exists {
+                    return Err((
+                        Status::BadRequest,
+                        Json(ApiError {
+                            error: "Column not found in this board".to_string(),
+                            code: "INVALID_COLUMN".to_string(),
+                            status: 400,
+                        }),
+                    ));
+                }
+                cid.clone()
+            }
+            None => conn
+                .query_row(
+                    "SELECT id FROM columns WHERE board_id = ?1 ORDER BY position ASC LIMIT 1",
+                    rusqlite::params![board_id],
+                    |row| row.get::<_, String>(0),
+                )
+                .map_err(|_| {
+                    (
+                        Status::BadRequest,
+                        Json(ApiError {
+                            error: "Board has no columns".to_string(),
+                            code: "NO_COLUMNS".to_string(),
+                            status: 400,
+                        }),
+                    )
+                })?,
+        };
+
+        if let Some(ref scope) = scope {
+            scope.require_column(&column_id)?;
+        }
+
+        // A double submission inside the board's window gets the original back,
+        // but only once the token has shown it may create here
+        let content_hash = task_content_hash(&req.title, &req.description, actor.name());
+        if let Some(existing) = find_recent_duplicate(conn, board_id, &content_hash) {
+            let mut task = load_task_response(conn, &existing)?;
+            task.duplicate = Some(true);
+            return Ok(task);
+        }
+
+        if let Some(ref parent_id) = req.parent_task_id {
+            hierarchy::check_parent(conn, board_id, parent_id, None)?;
+        }
+
+        // Check WIP limit
+        check_wip_limit(conn, &column_id, None)?;
+
+        let task_id = uuid::Uuid::new_v4().to_string();
+        let creator = actor.name().to_string();
+        let normalized_labels = normalize_labels(&req.labels);
+        let labels_json = serde_json::to_string(&normalized_labels).unwrap_or_else(|_| "[]".to_string());
+        let metadata_json = serde_json::to_string(&req.metadata).unwrap_or_else(|_| "{}".to_string());
+
+        validation_hook::check(
+            conn,
+            gate,
+            board_id,
+            validation_hook::TASK_CREATE,
+            &creator,
+            serde_json::json!({"task": {
+                "title": req.title.trim(),
+                "description": req.description,
+                "column_id": column_id,
+                "priority": req.priority,
+                "assigned_to": req.assigned_to,
+                "labels": normalized_labels,
+                "metadata": req.metadata,
+                "due_at": req.due_at,
+                "parent_task_id": req.parent_task_id,
+            }}),
+        )?;
+
+        // Determine position
+        let position: i32 = if let Some(pos) = req.position {
+            let pos = pos.max(0);
+            conn.execute(
+                "UPDATE tasks SET position = position + 1 WHERE column_id = ?1 AND position >= ?2",
+                rusqlite::params![column_id, pos],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+            pos
+        } else {
+            conn.query_row(
+                "SELECT COALESCE(MAX(position), -1) + 1 FROM tasks WHERE column_id = ?1",
+                rusqlite::params![column_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(0)
+        };
+
+        conn.execute(
+            "INSERT INTO tasks (id, board_id, column_id, title, description, priority, position, created_by, assigned_to, labels, metadata, due_at, content_hash, parent_task_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            rusqlite::params![
+                task_id,
+                board_id,
+                column_id,
+                req.title.trim(),
+                req.description,
+                req.priority,
+                position,
+                creator,
+                req.assigned_to,
+                labels_json,
+                metadata_json,
+                req.due_at,
+                content_hash,
+                req.parent_task_id,
+            ],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+
+        let mut event_data = serde_json::json!({"title": req.title, "task_id": task_id, "column_id": column_id, "creator": creator});
+        if let Some(ref assignee) = req.assigned_to {
+            event_data["assigned_to"] = serde_json::json!(assignee);
+        }
+        log_event(conn, &task_id, "created", &creator, &event_data);
+
+        bus.emit(
+            conn,
+            crate::events::BoardEvent {
+                event: event_types::TASK_CREATED.to_string(),
+                board_id: board_id.to_string(),
+                data: event_data,
+            },
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        if let Some(assignment) =
+            auto_assign::on_create(conn, &task_id, &column_id).map_err(|e| db_error(&e.to_string()))?
+        {
+            assignment.emit(conn, bus, board_id).map_err(|e| db_error(&e.to_string()))?;
+        }
 
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
-    access::require_not_archived(&conn, board_id)?;
+        load_task_response(conn, &task_id)
+    })
+    .await
+}
 
-    // Check display name requirement
-    let creator_name = if req.actor_name.is_empty() { "anonymous" } else { &req.actor_name };
-    access::require_display_name_if_needed(&conn, board_id, creator_name)?;
+/// Longest duplicate suppression window a board can configure.
+const MAX_DUPLICATE_WINDOW_SECONDS: i64 = 3600;
 
-    if req.title.trim().is_empty() && req.description.trim().is_empty() {
+/// A task needs a title or a description; whitespace alone is neither.
+fn require_task_content(title: &str, description: &str) -> Result<(), (Status, Json<ApiError>)> {
+    if title.trim().is_empty() && description.trim().is_empty() {
         return Err((
             Status::BadRequest,
             Json(ApiError {
@@ -809,116 +1941,204 @@ pub fn create_task(
             }),
         ));
     }
+    Ok(())
+}
 
-    // Resolve column: use provided ID, or first column of the board
-    let column_id = match req.column_id {
-        Some(ref cid) => {
-            let exists: bool = conn
-                .query_row(
-                    "SELECT COUNT(*) > 0 FROM columns WHERE id = ?1 AND board_id = ?2",
-                    rusqlite::params![cid, board_id],
-                    |row| row.get(0),
-                )
-                .unwrap_or(false);
-            if !exists {
-                return Err((
-                    Status::BadRequest,
-                    Json(ApiError {
-                        error: "Column not found in this board".to_string(),
-                        code: "INVALID_COLUMN".to_string(),
-                        status: 400,
-                    }),
-                ));
+/// Fingerprint of a create request: title and description with case and
+/// whitespace runs normalized, plus the creator.
+pub(crate) fn task_content_hash(title: &str, description: &str, creator: &str) -> String {
+    let normalize = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    hash_key(&format!("{}\0{}\0{}", normalize(title), normalize(description), creator.trim().to_lowercase()))
+}
+
+/// The newest task with this fingerprint created within the board's duplicate
+/// window, if the board has one.
+pub(crate) fn find_recent_duplicate(conn: &Connection, board_id: &str, content_hash: &str) -> Option<String> {
+    conn.query_row(
+        "SELECT t.id FROM tasks t JOIN boards b ON b.id = t.board_id
+         WHERE t.board_id = ?1 AND t.content_hash = ?2 AND b.duplicate_window_seconds IS NOT NULL
+           AND t.created_at >= datetime('now', '-' || b.duplicate_window_seconds || ' seconds')
+         ORDER BY t.created_at DESC LIMIT 1",
+        rusqlite::params![board_id, content_hash],
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+/// Clone a task — requires manage key.
+/// Copies title, description, priority, assignee, due date, labels and metadata
+/// (minus anything overridden in the body). Comments, claims and completion are not copied.
+#[post("/boards/<board_id>/tasks/<task_id>/clone", format = "json", data = "<req>")]
+pub async fn clone_task(
+    board_id: &str,
+    task_id: &str,
+    req: Json<CloneTaskRequest>,
+    token: BoardToken,
+    actor: Actor,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<TaskResponse>, (Status, Json<ApiError>)> {
+    let req = req.into_inner();
+    let (board_id, bus, task_id) = (board_id.to_owned(), bus.inner().clone(), task_id.to_owned());
+    let client = bus.outbound().clone();
+    validation_hook::run(db, &client, move |conn, gate| {
+        let (board_id, bus, task_id) = (board_id.as_str(), &bus, task_id.as_str());
+        let token_hash = hash_key(&token.0);
+        access::require_role(conn, board_id, &token_hash, BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+
+        let actor = actor.or_body(Some(&req.actor_name));
+        access::require_display_name(conn, board_id, &actor)?;
+        let creator = actor.name().to_string();
+
+        let source = conn
+            .query_row(
+                "SELECT column_id, title, description, priority, assigned_to, labels, metadata, due_at
+                 FROM tasks WHERE id = ?1 AND board_id = ?2",
+                rusqlite::params![task_id, board_id],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, i32>(3)?,
+                        row.get::<_, Option<String>>(4)?,
+                        row.get::<_, String>(5)?,
+                        row.get::<_, String>(6)?,
+                        row.get::<_, Option<String>>(7)?,
+                    ))
+                },
+            )
+            .map_err(|_| not_found("Task"))?;
+        let (src_column, src_title, src_description, src_priority, src_assigned, src_labels, src_metadata, src_due) =
+            source;
+
+        let column_id = match req.column_id {
+            Some(cid) => {
+                let exists: bool = conn
+                    .query_row(
+                        "SELECT COUNT(*) > 0 FROM columns WHERE id = ?1 AND board_id = ?2",
+                        rusqlite::params![cid, board_id],
+                        |row| row.get(0),
+                    )
+                    .unwrap_or(false);
+                if !exists {
+                    return Err((
+                        Status::BadRequest,
+                        Json(ApiError {
+                            error: "Column not found in this board".to_string(),
+                            code: "INVALID_COLUMN".to_string(),
+                            status: 400,
+                        }),
+                    ));
+                }
+                cid
             }
-            cid.clone()
-        }
-        None => conn
+            None => src_column,
+        };
+        check_wip_limit(conn, &column_id, None)?;
+
+        let title = req.title.map(|t| t.trim().to_string()).unwrap_or(src_title);
+        let description = req.description.unwrap_or(src_description);
+        let labels_json = match req.labels {
+            Some(labels) => serde_json::to_string(&normalize_labels(&labels)).unwrap_or_else(|_| "[]".to_string()),
+            None if req.include_labels => src_labels,
+            None => "[]".to_string(),
+        };
+        let metadata_json = match req.metadata {
+            Some(meta) => serde_json::to_string(&meta).unwrap_or_else(|_| "{}".to_string()),
+            None if req.include_metadata => src_metadata,
+            None => "{}".to_string(),
+        };
+        let priority = req.priority.unwrap_or(src_priority);
+        let assigned_to = req.assigned_to.or(src_assigned);
+        let due_at = req.due_at.or(src_due);
+
+        // The merged task must pass the same checks as a fresh create
+        require_task_content(&title, &description)?;
+        validation_hook::check(
+            conn,
+            gate,
+            board_id,
+            validation_hook::TASK_CREATE,
+            &creator,
+            serde_json::json!({"task": {
+                "title": title,
+                "description": description,
+                "column_id": column_id,
+                "priority": priority,
+                "assigned_to": assigned_to,
+                "labels": serde_json::from_str::<serde_json::Value>(&labels_json).unwrap_or_default(),
+                "metadata": serde_json::from_str::<serde_json::Value>(&metadata_json).unwrap_or_default(),
+                "due_at": due_at,
+                "cloned_from": task_id,
+            }}),
+        )?;
+
+        let position: i32 = conn
             .query_row(
-                "SELECT id FROM columns WHERE board_id = ?1 ORDER BY position ASC LIMIT 1",
-                rusqlite::params![board_id],
-                |row| row.get::<_, String>(0),
+                "SELECT COALESCE(MAX(position), -1) + 1 FROM tasks WHERE column_id = ?1",
+                rusqlite::params![column_id],
+                |row| row.get(0),
             )
-            .map_err(|_| {
-                (
-                    Status::BadRequest,
-                    Json(ApiError {
-                        error: "Board has no columns".to_string(),
-                        code: "NO_COLUMNS".to_string(),
-                        status: 400,
-                    }),
-                )
-            })?,
-    };
-
-    // Check WIP limit
-    check_wip_limit(&conn, &column_id, None)?;
-
-    let task_id = uuid::Uuid::new_v4().to_string();
-    let creator = if req.actor_name.is_empty() {
-        "anonymous".to_string()
-    } else {
-        req.actor_name.clone()
-    };
-    let normalized_labels = normalize_labels(&req.labels);
-    let labels_json = serde_json::to_string(&normalized_labels).unwrap_or_else(|_| "[]".to_string());
-    let metadata_json = serde_json::to_string(&req.metadata).unwrap_or_else(|_| "{}".to_string());
+            .unwrap_or(0);
 
-    // Determine position
-    let position: i32 = if let Some(pos) = req.position {
-        let pos = pos.max(0);
+        let new_id = uuid::Uuid::new_v4().to_string();
         conn.execute(
-            "UPDATE tasks SET position = position + 1 WHERE column_id = ?1 AND position >= ?2",
-            rusqlite::params![column_id, pos],
+            "INSERT INTO tasks (id, board_id, column_id, title, description, priority, position, created_by, assigned_to, labels, metadata, due_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            rusqlite::params![
+                new_id,
+                board_id,
+                column_id,
+                title,
+                description,
+                priority,
+                position,
+                creator,
+                assigned_to,
+                labels_json,
+                metadata_json,
+                due_at,
+            ],
         )
         .map_err(|e| db_error(&e.to_string()))?;
-        pos
-    } else {
-        conn.query_row(
-            "SELECT COALESCE(MAX(position), -1) + 1 FROM tasks WHERE column_id = ?1",
-            rusqlite::params![column_id],
-            |row| row.get(0),
-        )
-        .unwrap_or(0)
-    };
-
-    conn.execute(
-        "INSERT INTO tasks (id, board_id, column_id, title, description, priority, position, created_by, assigned_to, labels, metadata, due_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
-        rusqlite::params![
-            task_id,
-            board_id,
-            column_id,
-            req.title.trim(),
-            req.description,
-            req.priority,
-            position,
-            creator,
-            req.assigned_to,
-            labels_json,
-            metadata_json,
-            req.due_at,
-        ],
-    )
-    .map_err(|e| db_error(&e.to_string()))?;
 
-    let event_data = serde_json::json!({"title": req.title, "task_id": task_id, "column_id": column_id, "creator": creator});
-    log_event(&conn, &task_id, "created", &creator, &event_data);
+        let event_data = serde_json::json!({
+            "title": title,
+            "task_id": new_id,
+            "column_id": column_id,
+            "creator": creator,
+            "cloned_from": task_id,
+        });
+        log_event(conn, &new_id, "created", &creator, &event_data);
 
-    bus.emit(crate::events::BoardEvent {
-        event: "task.created".to_string(),
-        board_id: board_id.to_string(),
-        data: event_data,
-    });
+        bus.emit(
+            conn,
+            crate::events::BoardEvent {
+                event: event_types::TASK_CREATED.to_string(),
+                board_id: board_id.to_string(),
+                data: event_data,
+            },
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        if let Some(assignment) =
+            auto_assign::on_create(conn, &new_id, &column_id).map_err(|e| db_error(&e.to_string()))?
+        {
+            assignment.emit(conn, bus, board_id).map_err(|e| db_error(&e.to_string()))?;
+        }
 
-    load_task_response(&conn, &task_id)
+        load_task_response(conn, &new_id)
+    })
+    .await
 }
 
 /// Search tasks — public, no auth required.
 #[allow(clippy::too_many_arguments)]
 #[get(
-    "/boards/<board_id>/tasks/search?<q>&<column>&<assigned>&<priority>&<label>&<archived>&<limit>&<offset>"
+    "/boards/<board_id>/tasks/search?<q>&<column>&<assigned>&<priority>&<label>&<archived>&<comments>&<limit>&<offset>"
 )]
-pub fn search_tasks(
+pub async fn search_tasks(
     board_id: &str,
     q: &str,
     column: Option<&str>,
@@ -926,115 +2146,403 @@ pub fn search_tasks(
     priority: Option<i32>,
     label: Option<&str>,
     archived: Option<bool>,
+    comments: Option<bool>,
     limit: Option<i64>,
     offset: Option<i64>,
+    token: Option<BoardToken>,
     db: &State<DbPool>,
 ) -> Result<Json<SearchResponse>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
-    access::require_board_exists(&conn, board_id)?;
-
-    let query = q.trim();
-    if query.is_empty() {
-        return Err((
-            Status::BadRequest,
-            Json(ApiError {
-                error: "Search query cannot be empty".to_string(),
-                code: "EMPTY_QUERY".to_string(),
-                status: 400,
-            }),
-        ));
-    }
-
-    let limit = limit.unwrap_or(50).clamp(1, 100);
-    let offset = offset.unwrap_or(0).max(0);
-    let like_pattern = format!("%{}%", query);
+    let (assigned, board_id, column, label, q) = (assigned.map(str::to_owned), board_id.to_owned(), column.map(str::to_owned), label.map(str::to_owned), q.to_owned());
+    with_db(db, move |conn| {
+        let (assigned, board_id, column, label, q) = (assigned.as_deref(), board_id.as_str(), column.as_deref(), label.as_deref(), q.as_str());
+        access::require_read_access(conn, board_id, token.as_ref())?;
 
-    let mut sql = String::from(
-        "SELECT t.id, t.board_id, t.column_id, c.name, t.title, t.description,
-                t.priority, t.position, t.created_by, t.assigned_to, t.claimed_by,
-                t.claimed_at, t.labels, t.metadata, t.due_at, t.completed_at, t.archived_at,
-                t.created_at, t.updated_at,
-                (SELECT COUNT(*) FROM task_events te WHERE te.task_id = t.id AND te.event_type = 'comment') as comment_count
-         FROM tasks t
-         JOIN columns c ON t.column_id = c.id
-         WHERE t.board_id = ?1
-           AND (t.title LIKE ?2 OR t.description LIKE ?2 OR t.labels LIKE ?2)",
-    );
-    let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![
-        Box::new(board_id.to_string()),
-        Box::new(like_pattern.clone()),
-    ];
+        let query = q.trim();
+        if query.is_empty() {
+            return Err((
+                Status::BadRequest,
+                Json(ApiError {
+                    error: "Search query cannot be empty".to_string(),
+                    code: "EMPTY_QUERY".to_string(),
+                    status: 400,
+                }),
+            ));
+        }
 
-    if let Some(col) = column {
-        params.push(Box::new(col.to_string()));
-        sql.push_str(&format!(" AND t.column_id = ?{}", params.len()));
+        let scoped = parse_scoped_query(query);
+        let match_expr = scoped.match_expr(comments.unwrap_or(true))?;
+
+        let limit = limit.unwrap_or(50).clamp(1, 100);
+        let offset = offset.unwrap_or(0).max(0);
+
+        let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![Box::new(board_id.to_string())];
+        let mut filters = match &match_expr {
+            Some(expr) => {
+                params.push(Box::new(expr.clone()));
+                String::from(
+                    " FROM tasks_fts f
+                     JOIN tasks t ON t.id = f.task_id
+                     JOIN columns c ON t.column_id = c.id
+                     WHERE tasks_fts MATCH ?2 AND t.board_id = ?1",
+                )
+            }
+            None => String::from(
+                " FROM tasks t
+                 JOIN columns c ON t.column_id = c.id
+                 WHERE t.board_id = ?1",
+            ),
+        };
+
+        // `label:` and `meta.` are not indexed: they are checked per row against the
+        // board's tasks (idx_tasks_board), or against the FTS matches when there is free text
+        for l in &scoped.labels {
+            params.push(Box::new(l.clone()));
+            filters.push_str(&format!(
+                " AND EXISTS (SELECT 1 FROM json_each(t.labels) WHERE json_each.value = ?{} COLLATE NOCASE)",
+                params.len()
+            ));
+        }
+        for (path, value) in &scoped.meta {
+            params.push(Box::new(path.clone()));
+            params.push(Box::new(value.clone()));
+            // json_extract turns booleans into 1/0; compare them by their JSON spelling
+            filters.push_str(&format!(
+                " AND (CASE json_type(t.metadata, ?{p}) WHEN 'true' THEN 'true' WHEN 'false' THEN 'false'
+                       ELSE CAST(json_extract(t.metadata, ?{p}) AS TEXT) END) = ?{v}",
+                p = params.len() - 1,
+                v = params.len(),
+            ));
+        }
+
+        if let Some(col) = column {
+            params.push(Box::new(col.to_string()));
+            filters.push_str(&format!(" AND t.column_id = ?{}", params.len()));
+        }
+        if let Some(a) = assigned {
+            params.push(Box::new(a.to_string()));
+            filters.push_str(&format!(" AND t.assigned_to = ?{}", params.len()));
+        }
+        if let Some(p) = priority {
+            params.push(Box::new(p));
+            filters.push_str(&format!(" AND t.priority >= ?{}", params.len()));
+        }
+        if let Some(l) = label {
+            params.push(Box::new(format!("%\"{}\"%", l)));
+            filters.push_str(&format!(" AND t.labels LIKE ?{}", params.len()));
+        }
+
+        // archived filter: default false (hide archived tasks)
+        match archived {
+            Some(true) => filters.push_str(" AND t.archived_at IS NOT NULL"),
+            _ => filters.push_str(" AND t.archived_at IS NULL"),
+        }
+
+        // Count total matches
+        let count_param_refs: Vec<&dyn rusqlite::types::ToSql> =
+            params.iter().map(|p| p.as_ref()).collect();
+        let total: i64 = conn
+            .query_row(&format!("SELECT COUNT(*){}", filters), count_param_refs.as_slice(), |row| row.get(0))
+            .map_err(|_| invalid_search_query())?;
+
+        // BM25 (lower is better), weighting title > description > labels > comments
+        let rank = if match_expr.is_some() { "bm25(tasks_fts, 0.0, 10.0, 5.0, 3.0, 1.0), " } else { "" };
+        let sql = format!(
+            "SELECT {TASK_COLUMNS}
+             {}
+             ORDER BY {}t.priority DESC, t.updated_at DESC
+             LIMIT ?{} OFFSET ?{}",
+            filters,
+            rank,
+            params.len() + 1,
+            params.len() + 2,
+        );
+        params.push(Box::new(limit));
+        params.push(Box::new(offset));
+
+        let param_refs: Vec<&dyn rusqlite::types::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        let mut stmt = conn.prepare(&sql).map_err(|e| db_error(&e.to_string()))?;
+
+        let tasks: Vec<TaskResponse> = stmt
+            .query_map(param_refs.as_slice(), task_from_row)
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(Json(SearchResponse {
+            query: query.to_string(),
+            tasks,
+            total,
+            limit,
+            offset,
+        }))
+    })
+    .await
+}
+
+/// A search string split into `field:value` scopes and free text.
+#[derive(Debug, Default, PartialEq)]
+struct ScopedQuery {
+    /// Unscoped terms, still in user syntax for `fts_query`.
+    text: String,
+    /// FTS column and raw value from `title:`, `description:` and `comments:`.
+    columns: Vec<(&'static str, String)>,
+    labels: Vec<String>,
+    /// JSON path and value from `meta.<key>:`.
+    meta: Vec<(String, String)>,
+}
+
+impl ScopedQuery {
+    /// FTS5 expression for the text parts, or `None` when the query is purely
+    /// label/metadata scoped. Free text skips comments unless `include_comments`.
+    fn match_expr(&self, include_comments: bool) -> Result<Option<String>, (Status, Json<ApiError>)> {
+        let mut parts = Vec::new();
+        if !self.text.is_empty() {
+            let expr = fts_query(&self.text).ok_or_else(invalid_search_query)?;
+            parts.push(if include_comments {
+                format!("({})", expr)
+            } else {
+                format!("{{title description labels}} : ({})", expr)
+            });
+        }
+        for (column, value) in &self.columns {
+            let expr = fts_query(value).ok_or_else(invalid_search_query)?;
+            parts.push(format!("{} : ({})", column, expr));
+        }
+        if parts.is_empty() {
+            if self.labels.is_empty() && self.meta.is_empty() {
+                return Err(invalid_search_query());
+            }
+            return Ok(None);
+        }
+        Ok(Some(parts.join(" ")))
     }
-    if let Some(a) = assigned {
-        params.push(Box::new(a.to_string()));
-        sql.push_str(&format!(" AND t.assigned_to = ?{}", params.len()));
+
+    /// Record one scope; returns false for unknown fields or empty values.
+    fn push(&mut self, field: &str, raw: &str) -> bool {
+        let value = raw.trim_end_matches('*').trim_matches('"').trim();
+        if value.is_empty() {
+            return false;
+        }
+        let column = match field.to_ascii_lowercase().as_str() {
+            "title" => "title",
+            "description" | "desc" => "description",
+            "comments" | "comment" => "comments",
+            "label" | "labels" => {
+                self.labels.push(value.to_string());
+                return true;
+            }
+            f => {
+                let Some(key) = f.strip_prefix("meta.").filter(|k| !k.is_empty()) else {
+                    return false;
+                };
+                // Keys keep their case; `meta.a.b` walks nested objects
+                let key = &field[field.len() - key.len()..];
+                let path: String = key.split('.').map(|seg| format!(".\"{}\"", seg)).collect();
+                self.meta.push((format!("${}", path), value.to_string()));
+                return true;
+            }
+        };
+        self.columns.push((column, raw.to_string()));
+        true
     }
-    if let Some(p) = priority {
-        params.push(Box::new(p));
-        sql.push_str(&format!(" AND t.priority >= ?{}", params.len()));
+}
+
+/// Pull `title:`, `description:`, `comments:`, `label:` and `meta.<key>:`
+/// scopes out of a search string. Values may be quoted (`title:"fix login"`);
+/// unknown prefixes such as `http:` and empty values stay free text.
+fn parse_scoped_query(input: &str) -> ScopedQuery {
+    let mut scoped = ScopedQuery::default();
+    let mut free: Vec<&str> = Vec::new();
+    let mut rest = input.trim();
+    while !rest.is_empty() {
+        // Free phrases are kept whole (with any trailing `*`) for fts_query
+        let end = if let Some(len) = quoted_len(rest) {
+            len
+        } else {
+            let field_end = rest.find(|c: char| c.is_whitespace() || c == '"' || c == ':').unwrap_or(rest.len());
+            if rest[field_end..].starts_with(':') {
+                let value_start = field_end + 1;
+                let value = &rest[value_start..];
+                let value_len = quoted_len(value)
+                    .unwrap_or_else(|| value.find(char::is_whitespace).unwrap_or(value.len()));
+                let value_len = value_len + usize::from(value[value_len..].starts_with('*'));
+                let raw = &value[..value_len];
+                if scoped.push(&rest[..field_end], raw) {
+                    rest = value[value_len..].trim_start();
+                    continue;
+                }
+            }
+            rest.find(char::is_whitespace).unwrap_or(rest.len())
+        };
+        let end = end + usize::from(rest[end..].starts_with('*'));
+        free.push(&rest[..end]);
+        rest = rest[end..].trim_start();
     }
-    if let Some(l) = label {
-        params.push(Box::new(format!("%\"{}\"%", l)));
-        sql.push_str(&format!(" AND t.labels LIKE ?{}", params.len()));
+    scoped.text = free.join(" ");
+    scoped
+}
+
+/// Byte length of a leading `"quoted phrase"`, or the rest when unterminated.
+fn quoted_len(s: &str) -> Option<usize> {
+    s.strip_prefix('"').map(|after| after.find('"').map_or(s.len(), |i| i + 2))
+}
+
+/// Translate a user search string into an FTS5 query. Words are matched as
+/// terms (`auth*` for prefix), `"quoted text"` as phrases, and a bare `OR`
+/// between terms is kept as an operator; everything else is quoted so user
+/// input can't inject FTS5 syntax. Returns `None` when nothing is searchable.
+fn fts_query(input: &str) -> Option<String> {
+    fn quote(term: &str) -> String {
+        format!("\"{}\"", term.replace('"', "\"\""))
     }
 
-    // archived filter: default false (hide archived tasks)
-    match archived {
-        Some(true) => sql.push_str(" AND t.archived_at IS NOT NULL"),
-        _ => sql.push_str(" AND t.archived_at IS NULL"),
+    let mut parts: Vec<String> = Vec::new();
+    let mut rest = input.trim();
+    while !rest.is_empty() {
+        let (token, is_phrase, remaining) = match rest.strip_prefix('"') {
+            Some(after) => match after.find('"') {
+                Some(end) => (&after[..end], true, &after[end + 1..]),
+                None => (after, true, ""),
+            },
+            None => {
+                let end = rest.find(|c: char| c.is_whitespace() || c == '"').unwrap_or(rest.len());
+                (&rest[..end], false, &rest[end..])
+            }
+        };
+        let (remaining, phrase_prefix) = match remaining.strip_prefix('*') {
+            Some(r) if is_phrase => (r, true),
+            _ => (remaining, false),
+        };
+        rest = remaining.trim_start();
+
+        if !is_phrase && token == "OR" {
+            if parts.last().is_some_and(|p| p != "OR") {
+                parts.push("OR".to_string());
+            }
+            continue;
+        }
+        let prefix = phrase_prefix || (!is_phrase && token.ends_with('*'));
+        let term = token.trim_end_matches('*').trim();
+        if term.chars().any(char::is_alphanumeric) {
+            parts.push(if prefix { format!("{}*", quote(term)) } else { quote(term) });
+        }
+    }
+    if parts.last().is_some_and(|p| p == "OR") {
+        parts.pop();
+    }
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(" "))
     }
+}
 
-    // Count total matches
-    let count_sql = sql.replace(
-        "SELECT t.id, t.board_id, t.column_id, c.name, t.title, t.description,
-                t.priority, t.position, t.created_by, t.assigned_to, t.claimed_by,
-                t.claimed_at, t.labels, t.metadata, t.due_at, t.completed_at, t.archived_at,
-                t.created_at, t.updated_at,
-                (SELECT COUNT(*) FROM task_events te WHERE te.task_id = t.id AND te.event_type = 'comment') as comment_count",
-        "SELECT COUNT(*)",
-    );
-    let count_param_refs: Vec<&dyn rusqlite::types::ToSql> =
-        params.iter().map(|p| p.as_ref()).collect();
-    let total: i64 = conn
-        .query_row(&count_sql, count_param_refs.as_slice(), |row| row.get(0))
-        .unwrap_or(0);
+fn invalid_search_query() -> (Status, Json<ApiError>) {
+    (
+        Status::BadRequest,
+        Json(ApiError {
+            error: "Search query has no searchable terms".to_string(),
+            code: "INVALID_QUERY".to_string(),
+            status: 400,
+        }),
+    )
+}
 
-    sql.push_str(&format!(
-        " ORDER BY CASE WHEN t.title LIKE ?{p} THEN 0 ELSE 1 END, t.priority DESC, t.updated_at DESC LIMIT ?{l} OFFSET ?{o}",
-        p = params.len() + 1,
-        l = params.len() + 2,
-        o = params.len() + 3,
-    ));
-    params.push(Box::new(like_pattern));
-    params.push(Box::new(limit));
-    params.push(Box::new(offset));
-
-    let param_refs: Vec<&dyn rusqlite::types::ToSql> = params.iter().map(|p| p.as_ref()).collect();
-    let mut stmt = conn.prepare(&sql).map_err(|e| db_error(&e.to_string()))?;
-
-    let tasks: Vec<TaskResponse> = stmt
-        .query_map(param_refs.as_slice(), row_to_task)
-        .map_err(|e| db_error(&e.to_string()))?
-        .filter_map(|r| r.ok())
-        .collect();
+/// Random sample of tasks for spot-check review — public, no auth required.
+/// `agent` matches tasks assigned to, claimed by, or created by that name.
+/// `completed=true` restricts to finished tasks; `since` bounds completed_at
+/// (or updated_at when not filtering on completion).
+#[allow(clippy::too_many_arguments)]
+#[get("/boards/<board_id>/tasks/sample?<n>&<column>&<label>&<agent>&<completed>&<since>&<archived>")]
+pub async fn sample_tasks(
+    board_id: &str,
+    n: Option<i64>,
+    column: Option<&str>,
+    label: Option<&str>,
+    agent: Option<&str>,
+    completed: Option<bool>,
+    since: Option<&str>,
+    archived: Option<bool>,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+) -> Result<Json<SampleResponse>, (Status, Json<ApiError>)> {
+    let (agent, board_id, column, label, since) = (agent.map(str::to_owned), board_id.to_owned(), column.map(str::to_owned), label.map(str::to_owned), since.map(str::to_owned));
+    with_db(db, move |conn| {
+        let (agent, board_id, column, label, since) = (agent.as_deref(), board_id.as_str(), column.as_deref(), label.as_deref(), since.as_deref());
+        access::require_read_access(conn, board_id, token.as_ref())?;
 
-    Ok(Json(SearchResponse {
-        query: query.to_string(),
-        tasks,
-        total,
-        limit,
-        offset,
-    }))
+        let n = n.unwrap_or(5).clamp(1, 50);
+
+        let mut filter = String::from(" WHERE t.board_id = ?1");
+        let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![Box::new(board_id.to_string())];
+
+        if let Some(col) = column {
+            params.push(Box::new(col.to_string()));
+            filter.push_str(&format!(" AND t.column_id = ?{}", params.len()));
+        }
+        if let Some(l) = label {
+            params.push(Box::new(format!("%\"{}\"%", normalize_label(l))));
+            filter.push_str(&format!(" AND t.labels LIKE ?{}", params.len()));
+        }
+        if let Some(a) = agent {
+            params.push(Box::new(a.to_string()));
+            filter.push_str(&format!(
+                " AND (t.assigned_to = ?{p} OR t.claimed_by = ?{p} OR t.created_by = ?{p})",
+                p = params.len()
+            ));
+        }
+        let completed_only = completed.unwrap_or(false);
+        if completed_only {
+            filter.push_str(" AND t.completed_at IS NOT NULL");
+        }
+        if let Some(ts) = since {
+            params.push(Box::new(ts.to_string()));
+            let field = if completed_only { "t.completed_at" } else { "t.updated_at" };
+            filter.push_str(&format!(" AND {} >= ?{}", field, params.len()));
+        }
+        match archived {
+            Some(true) => filter.push_str(" AND t.archived_at IS NOT NULL"),
+            _ => filter.push_str(" AND t.archived_at IS NULL"),
+        }
+
+        let param_refs: Vec<&dyn rusqlite::types::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        let matched: i64 = conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM tasks t{}", filter),
+                param_refs.as_slice(),
+                |row| row.get(0),
+            )
+            .unwrap_or(0);
+
+        let sql = format!(
+            "SELECT {TASK_COLUMNS}
+             FROM tasks t
+             JOIN columns c ON t.column_id = c.id{}
+             ORDER BY RANDOM()
+             LIMIT {}",
+            filter, n
+        );
+        let mut stmt = conn.prepare(&sql).map_err(|e| db_error(&e.to_string()))?;
+        let tasks: Vec<TaskResponse> = stmt
+            .query_map(param_refs.as_slice(), task_from_row)
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(Json(SampleResponse {
+            requested: n,
+            matched,
+            tasks,
+        }))
+    })
+    .await
 }
 
 /// List tasks — public, no auth required.
-#[allow(clippy::too_many_arguments)]
+/// Results are streamed as a chunked JSON array (see `stream_tasks`).
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 #[get("/boards/<board_id>/tasks?<column>&<assigned>&<claimed>&<priority>&<label>&<archived>&<updated_before>&<stale>&<limit>&<offset>")]
-pub fn list_tasks(
+pub async fn list_tasks<'r>(
     board_id: &str,
     column: Option<&str>,
     assigned: Option<&str>,
@@ -1046,41 +2554,39 @@ pub fn list_tasks(
     stale: Option<i64>,
     limit: Option<i64>,
     offset: Option<i64>,
-    db: &State<DbPool>,
-) -> Result<Json<Vec<TaskResponse>>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
-    access::require_board_exists(&conn, board_id)?;
-
-    let mut sql = String::from(
-        "SELECT t.id, t.board_id, t.column_id, c.name, t.title, t.description,
-                t.priority, t.position, t.created_by, t.assigned_to, t.claimed_by,
-                t.claimed_at, t.labels, t.metadata, t.due_at, t.completed_at, t.archived_at,
-                t.created_at, t.updated_at,
-                (SELECT COUNT(*) FROM task_events te WHERE te.task_id = t.id AND te.event_type = 'comment') as comment_count
+    token: Option<BoardToken>,
+    db: &'r State<DbPool>,
+) -> Result<(ContentType, ByteStream![Vec<u8> + 'r]), (Status, Json<ApiError>)> {
+    let board = board_id.to_owned();
+    with_db(db, move |conn| access::require_read_access(conn, &board, token.as_ref())).await?;
+
+    let mut sql = format!(
+        "SELECT {TASK_COLUMNS},
+                c.position as column_position
          FROM tasks t
          JOIN columns c ON t.column_id = c.id
          WHERE t.board_id = ?1",
     );
-    let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![Box::new(board_id.to_string())];
+    let mut params: Vec<rusqlite::types::Value> = vec![board_id.to_string().into()];
 
     if let Some(col) = column {
-        params.push(Box::new(col.to_string()));
+        params.push(col.to_string().into());
         sql.push_str(&format!(" AND t.column_id = ?{}", params.len()));
     }
     if let Some(a) = assigned {
-        params.push(Box::new(a.to_string()));
+        params.push(a.to_string().into());
         sql.push_str(&format!(" AND t.assigned_to = ?{}", params.len()));
     }
     if let Some(c) = claimed {
-        params.push(Box::new(c.to_string()));
+        params.push(c.to_string().into());
         sql.push_str(&format!(" AND t.claimed_by = ?{}", params.len()));
     }
     if let Some(p) = priority {
-        params.push(Box::new(p));
+        params.push(p.into());
         sql.push_str(&format!(" AND t.priority >= ?{}", params.len()));
     }
     if let Some(l) = label {
-        params.push(Box::new(format!("%\"{}\"%", l)));
+        params.push(format!("%\"{}\"%", l).into());
         sql.push_str(&format!(" AND t.labels LIKE ?{}", params.len()));
     }
     // stale=<minutes> is a convenience wrapper for updated_before
@@ -1108,7 +2614,7 @@ pub fn list_tasks(
     };
 
     if let Some(ref ub) = computed_updated_before {
-        params.push(Box::new(ub.clone()));
+        params.push(ub.clone().into());
         sql.push_str(&format!(" AND t.updated_at < ?{}", params.len()));
     }
 
@@ -1118,828 +2624,1753 @@ pub fn list_tasks(
         _ => sql.push_str(" AND t.archived_at IS NULL"),
     }
 
-    sql.push_str(" ORDER BY c.position ASC, t.priority DESC, t.position ASC");
-
     // Pagination: limit defaults to 200, max 1000. offset defaults to 0.
-    let effective_limit = limit.unwrap_or(200).min(1000).max(1);
+    let effective_limit = limit.unwrap_or(200).clamp(1, 1000);
     let effective_offset = offset.unwrap_or(0).max(0);
-    params.push(Box::new(effective_limit));
-    sql.push_str(&format!(" LIMIT ?{}", params.len()));
-    params.push(Box::new(effective_offset));
-    sql.push_str(&format!(" OFFSET ?{}", params.len()));
 
-    let mut stmt = conn.prepare(&sql).map_err(|e| db_error(&e.to_string()))?;
-    let param_refs: Vec<&dyn rusqlite::types::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+    // The first page is read up front, so a failing query is still an error status
+    let (page_sql, page_params) = (sql.clone(), params.clone());
+    let first_page = with_db(db, move |conn| {
+        let start = TaskPageStart::Offset(effective_offset);
+        fetch_task_page(conn, &page_sql, &page_params, start, STREAM_PAGE_SIZE.min(effective_limit))
+            .map_err(|e| db_error(&e.to_string()))
+    })
+    .await?;
+    Ok((ContentType::JSON, stream_tasks(db, sql, params, effective_limit, first_page)))
+}
 
-    let tasks = stmt
-        .query_map(param_refs.as_slice(), row_to_task)
-        .map_err(|e| db_error(&e.to_string()))?
-        .filter_map(|r| r.ok())
-        .collect();
+/// Rows fetched per lock acquisition when streaming task listings.
+const STREAM_PAGE_SIZE: i64 = 100;
+
+/// Task listing order; `t.id` breaks ties so every row has a unique place.
+const TASK_STREAM_ORDER: &str = " ORDER BY c.position ASC, t.priority DESC, t.position ASC, t.id ASC";
+
+/// Where a page of a task listing starts: the caller's offset for the first
+/// page, then just past the last row sent.
+enum TaskPageStart {
+    Offset(i64),
+    /// (column position, priority, position, id) of the last row sent
+    After([rusqlite::types::Value; 4]),
+}
+
+/// A page of tasks, plus the position of its last row.
+type TaskPage = (Vec<TaskResponse>, Option<[rusqlite::types::Value; 4]>);
+
+/// One page of a task listing. Pages after the first continue from the last
+/// row's sort key rather than an offset, so tasks created, moved or deleted
+/// while a listing streams don't make it repeat or skip rows.
+fn fetch_task_page(
+    conn: &Connection,
+    sql: &str,
+    params: &[rusqlite::types::Value],
+    start: TaskPageStart,
+    page_size: i64,
+) -> rusqlite::Result<TaskPage> {
+    let mut page_params = params.to_vec();
+    let mut paged_sql = sql.to_string();
+    let offset = match start {
+        TaskPageStart::Offset(offset) => offset,
+        TaskPageStart::After(key) => {
+            let n = page_params.len();
+            paged_sql.push_str(&format!(
+                " AND (c.position > ?{a} OR (c.position = ?{a} AND (t.priority < ?{b} OR (t.priority = ?{b} AND (t.position, t.id) > (?{c}, ?{d})))))",
+                a = n + 1,
+                b = n + 2,
+                c = n + 3,
+                d = n + 4
+            ));
+            page_params.extend(key);
+            0
+        }
+    };
+    paged_sql.push_str(TASK_STREAM_ORDER);
+    paged_sql.push_str(&format!(" LIMIT ?{} OFFSET ?{}", page_params.len() + 1, page_params.len() + 2));
+    page_params.push(page_size.into());
+    page_params.push(offset.into());
+
+    let mut stmt = conn.prepare(&paged_sql)?;
+    let rows = stmt
+        .query_map(rusqlite::params_from_iter(page_params.iter()), |row| {
+            let key = [row.get(28)?, row.get(6)?, row.get(7)?, row.get(0)?];
+            Ok((task_from_row(row)?, key))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    let last = rows.last().map(|(_, key)| key.clone());
+    Ok((rows.into_iter().map(|(task, _)| task).collect(), last))
+}
 
-    Ok(Json(tasks))
+/// Stream a task query as a chunked JSON array, starting from its already
+/// fetched first page. A pooled connection is held only while each page is
+/// fetched, so large boards are never fully materialized in memory. `sql` must
+/// select the `task_from_row` columns then `c.position`, and must not carry
+/// ORDER BY or LIMIT. If a later page fails the stream ends without its
+/// closing `]`, so the client sees a broken listing rather than a short one.
+fn stream_tasks(
+    db: &DbPool,
+    sql: String,
+    params: Vec<rusqlite::types::Value>,
+    limit: i64,
+    first_page: TaskPage,
+) -> ByteStream![Vec<u8> + '_] {
+    let query = std::sync::Arc::new((sql, params));
+    ByteStream! {
+        yield b"[".to_vec();
+        let mut sent = 0i64;
+        let mut page = first_page;
+        let mut page_size = STREAM_PAGE_SIZE.min(limit);
+        loop {
+            let (tasks, last) = page;
+            let fetched = tasks.len() as i64;
+            let mut chunk = Vec::new();
+            for task in &tasks {
+                if sent > 0 || !chunk.is_empty() {
+                    chunk.push(b',');
+                }
+                serde_json::to_writer(&mut chunk, task).unwrap_or_default();
+            }
+            sent += fetched;
+            if !chunk.is_empty() {
+                yield chunk;
+            }
+            let Some(last) = last.filter(|_| fetched == page_size && sent < limit) else {
+                break;
+            };
+
+            page_size = STREAM_PAGE_SIZE.min(limit - sent);
+            let (pool, query) = (db.clone(), query.clone());
+            let next = crate::db::blocking(move || {
+                let conn = pool.get().map_err(|e| e.to_string())?;
+                fetch_task_page(&conn, &query.0, &query.1, TaskPageStart::After(last), page_size).map_err(|e| e.to_string())
+            })
+            .await;
+            page = match next {
+                Ok(page) => page,
+                Err(e) => {
+                    eprintln!("⚠️  Task stream aborted: {}", e);
+                    return;
+                }
+            };
+        }
+        yield b"]".to_vec();
+    }
 }
 
 /// Get a single task — public, no auth required.
 #[get("/boards/<board_id>/tasks/<task_id>")]
-pub fn get_task(
+pub async fn get_task(
     board_id: &str,
     task_id: &str,
+    token: Option<BoardToken>,
     db: &State<DbPool>,
 ) -> Result<Json<TaskResponse>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
-    access::require_board_exists(&conn, board_id)?;
-    load_task_response(&conn, task_id)
+    let (board_id, task_id) = (board_id.to_owned(), task_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, task_id) = (board_id.as_str(), task_id.as_str());
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        load_task_response(conn, task_id)
+    })
+    .await
 }
 
 /// Update a task — requires manage key.
 #[patch("/boards/<board_id>/tasks/<task_id>", format = "json", data = "<req>")]
-pub fn update_task(
+pub async fn update_task(
     board_id: &str,
     task_id: &str,
     req: Json<UpdateTaskRequest>,
     token: BoardToken,
+    actor: Actor,
     db: &State<DbPool>,
     bus: &State<EventBus>,
-) -> Result<Json<TaskResponse>, (Status, Json<ApiError>)> {
+) -> Result<Json<TaskResponse>, MoveError> {
     let req = req.into_inner();
-    let conn = db.lock().unwrap();
-
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
-    access::require_not_archived(&conn, board_id)?;
-    let existing = load_task_response(&conn, task_id)?;
-    let actor = req.actor_name.clone().unwrap_or_else(|| "anonymous".to_string());
-    access::require_display_name_if_needed(&conn, board_id, &actor)?;
-
-    // Prevent clearing both title and description
-    let new_title = req.title.as_deref().unwrap_or(&existing.title);
-    let new_desc = req.description.as_deref().unwrap_or(&existing.description);
-    if new_title.trim().is_empty() && new_desc.trim().is_empty() {
-        return Err((
-            Status::BadRequest,
-            Json(ApiError {
-                error: "Either title or description must be provided".to_string(),
-                code: "EMPTY_TASK".to_string(),
-                status: 400,
-            }),
-        ));
-    }
-
-    let mut changes = serde_json::Map::new();
+    let (board_id, bus, task_id) = (board_id.to_owned(), bus.inner().clone(), task_id.to_owned());
+    let client = bus.outbound().clone();
+    validation_hook::run(db, &client, move |conn, gate| {
+        let (board_id, bus, task_id) = (board_id.as_str(), &bus, task_id.as_str());
+        let scope = access::require_action(conn, board_id, &token.0, ScopeAction::UpdateTask)?;
+        access::require_not_archived(conn, board_id)?;
+        let existing = load_task_response(conn, task_id)?;
+        if let Some(ref scope) = scope {
+            scope.require_column(&existing.column_id)?;
+            if let Some(ref column_id) = req.column_id {
+                scope.require_column(column_id)?;
+            }
+        }
+        let actor = actor.or_body(req.actor_name.as_deref());
+        access::require_display_name(conn, board_id, &actor)?;
+        let actor = actor.name().to_string();
+        check_edit_lock(conn, task_id, &actor)?;
+
+        // Prevent clearing both title and description
+        let new_title = req.title.as_deref().unwrap_or(&existing.title);
+        let new_desc = req.description.as_deref().unwrap_or(&existing.description);
+        require_task_content(new_title, new_desc)?;
+        if let Some(col_id) = req.column_id.as_deref().filter(|c| *c != existing.column_id) {
+            validate_move(conn, board_id, task_id, col_id)?;
+            validation_hook::check_move(conn, gate, board_id, &actor, &existing, col_id)?;
+        }
 
-    if let Some(ref title) = req.title {
-        conn.execute(
-            "UPDATE tasks SET title = ?1, updated_at = datetime('now') WHERE id = ?2",
-            rusqlite::params![title, task_id],
-        )
-        .map_err(|e| db_error(&e.to_string()))?;
-        changes.insert("title".into(), serde_json::json!(title));
-    }
+        let mut changes = serde_json::Map::new();
 
-    if let Some(ref desc) = req.description {
-        conn.execute(
-            "UPDATE tasks SET description = ?1, updated_at = datetime('now') WHERE id = ?2",
-            rusqlite::params![desc, task_id],
-        )
-        .map_err(|e| db_error(&e.to_string()))?;
-        changes.insert("description".into(), serde_json::json!(desc));
-    }
+        if let Some(ref title) = req.title {
+            conn.execute(
+                "UPDATE tasks SET title = ?1, updated_at = datetime('now') WHERE id = ?2",
+                rusqlite::params![title, task_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+            changes.insert("title".into(), serde_json::json!(title));
+        }
 
-    if let Some(ref col_id) = req.column_id {
-        check_wip_limit(&conn, col_id, Some(task_id))?;
-        conn.execute(
-            "UPDATE tasks SET column_id = ?1, updated_at = datetime('now') WHERE id = ?2",
-            rusqlite::params![col_id, task_id],
-        )
-        .map_err(|e| db_error(&e.to_string()))?;
-        changes.insert("column_id".into(), serde_json::json!(col_id));
-    }
+        if let Some(ref desc) = req.description {
+            conn.execute(
+                "UPDATE tasks SET description = ?1, updated_at = datetime('now') WHERE id = ?2",
+                rusqlite::params![desc, task_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+            changes.insert("description".into(), serde_json::json!(desc));
+        }
 
-    if let Some(p) = req.priority {
-        conn.execute(
-            "UPDATE tasks SET priority = ?1, updated_at = datetime('now') WHERE id = ?2",
-            rusqlite::params![p, task_id],
-        )
-        .map_err(|e| db_error(&e.to_string()))?;
-        changes.insert("priority".into(), serde_json::json!(p));
-    }
+        let mut entered_column = None;
+        if let Some(ref col_id) = req.column_id {
+            let from_col: Option<String> = conn
+                .query_row("SELECT column_id FROM tasks WHERE id = ?1", rusqlite::params![task_id], |row| row.get(0))
+                .ok();
+            if from_col.as_deref() != Some(col_id.as_str()) {
+                entered_column = Some(col_id.as_str());
+            }
+            conn.execute(
+                "UPDATE tasks SET column_id = ?1, updated_at = datetime('now') WHERE id = ?2",
+                rusqlite::params![col_id, task_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+            changes.insert("column_id".into(), serde_json::json!(col_id));
+        }
 
-    if let Some(ref assigned) = req.assigned_to {
-        conn.execute(
-            "UPDATE tasks SET assigned_to = ?1, updated_at = datetime('now') WHERE id = ?2",
-            rusqlite::params![assigned, task_id],
-        )
-        .map_err(|e| db_error(&e.to_string()))?;
-        changes.insert("assigned_to".into(), serde_json::json!(assigned));
-    }
+        if let Some(p) = req.priority {
+            conn.execute(
+                "UPDATE tasks SET priority = ?1, updated_at = datetime('now') WHERE id = ?2",
+                rusqlite::params![p, task_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+            changes.insert("priority".into(), serde_json::json!(p));
+        }
 
-    if let Some(ref labels) = req.labels {
-        let normalized = normalize_labels(labels);
-        let labels_json = serde_json::to_string(&normalized).unwrap_or_else(|_| "[]".to_string());
-        conn.execute(
-            "UPDATE tasks SET labels = ?1, updated_at = datetime('now') WHERE id = ?2",
-            rusqlite::params![labels_json, task_id],
-        )
-        .map_err(|e| db_error(&e.to_string()))?;
-        changes.insert("labels".into(), serde_json::json!(normalized));
-    }
+        if let Some(ref assigned) = req.assigned_to {
+            conn.execute(
+                "UPDATE tasks SET assigned_to = ?1, updated_at = datetime('now') WHERE id = ?2",
+                rusqlite::params![assigned, task_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+            changes.insert("assigned_to".into(), serde_json::json!(assigned));
+        }
 
-    if let Some(ref meta) = req.metadata {
-        let meta_json = serde_json::to_string(meta).unwrap_or_else(|_| "{}".to_string());
-        conn.execute(
-            "UPDATE tasks SET metadata = ?1, updated_at = datetime('now') WHERE id = ?2",
-            rusqlite::params![meta_json, task_id],
-        )
-        .map_err(|e| db_error(&e.to_string()))?;
-        changes.insert("metadata".into(), meta.clone());
-    }
+        if let Some(ref labels) = req.labels {
+            let normalized = normalize_labels(labels);
+            let labels_json = serde_json::to_string(&normalized).unwrap_or_else(|_| "[]".to_string());
+            conn.execute(
+                "UPDATE tasks SET labels = ?1, updated_at = datetime('now') WHERE id = ?2",
+                rusqlite::params![labels_json, task_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+            changes.insert("labels".into(), serde_json::json!(normalized));
+        }
 
-    if let Some(ref due) = req.due_at {
-        conn.execute(
-            "UPDATE tasks SET due_at = ?1, updated_at = datetime('now') WHERE id = ?2",
-            rusqlite::params![due, task_id],
-        )
-        .map_err(|e| db_error(&e.to_string()))?;
-        changes.insert("due_at".into(), serde_json::json!(due));
-    }
+        if let Some(ref meta) = req.metadata {
+            let meta_json = serde_json::to_string(meta).unwrap_or_else(|_| "{}".to_string());
+            conn.execute(
+                "UPDATE tasks SET metadata = ?1, updated_at = datetime('now') WHERE id = ?2",
+                rusqlite::params![meta_json, task_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+            changes.insert("metadata".into(), meta.clone());
+        }
 
-    if !changes.is_empty() {
-        let event_data = serde_json::Value::Object(changes.clone());
-        log_event(&conn, task_id, "updated", &actor, &event_data);
+        if let Some(ref due) = req.due_at {
+            conn.execute(
+                "UPDATE tasks SET due_at = ?1, updated_at = datetime('now') WHERE id = ?2",
+                rusqlite::params![due, task_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+            changes.insert("due_at".into(), serde_json::json!(due));
+        }
 
-        let mut emit_data = changes;
-        emit_data.insert("task_id".into(), serde_json::json!(task_id));
-        emit_data.insert("actor".into(), serde_json::json!(actor));
-        bus.emit(crate::events::BoardEvent {
-            event: "task.updated".to_string(),
-            board_id: board_id.to_string(),
-            data: serde_json::Value::Object(emit_data),
-        });
-    }
+        if !changes.is_empty() {
+            let event_data = serde_json::Value::Object(changes.clone());
+            log_event(conn, task_id, "updated", &actor, &event_data);
 
-    load_task_response(&conn, task_id)
-}
+            let mut emit_data = changes;
+            emit_data.insert("task_id".into(), serde_json::json!(task_id));
+            emit_data.insert("actor".into(), serde_json::json!(actor));
+            bus.emit(
+                conn,
+                crate::events::BoardEvent {
+                    event: event_types::TASK_UPDATED.to_string(),
+                    board_id: board_id.to_string(),
+                    data: serde_json::Value::Object(emit_data),
+                },
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        }
+
+        // An explicit assignee in the same request wins over the column rule
+        if let Some(col_id) = entered_column.filter(|_| req.assigned_to.is_none()) {
+            if let Some(assignment) = auto_assign::on_enter(conn, task_id, col_id).map_err(|e| db_error(&e.to_string()))? {
+                assignment.emit(conn, bus, board_id).map_err(|e| db_error(&e.to_string()))?;
+            }
+        }
+
+        Ok(load_task_response(conn, task_id)?)
+    })
+    .await
+}
 
 /// Delete a task — requires manage key. Optional `?actor=` query param for attribution.
-#[delete("/boards/<board_id>/tasks/<task_id>?<actor>")]
-pub fn delete_task(
+#[delete("/boards/<board_id>/tasks/<task_id>")]
+pub async fn delete_task(
     board_id: &str,
     task_id: &str,
-    actor: Option<&str>,
+    actor: Actor,
     token: BoardToken,
     db: &State<DbPool>,
     bus: &State<EventBus>,
 ) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
-    access::require_not_archived(&conn, board_id)?;
+    let (board_id, bus, task_id) = (board_id.to_owned(), bus.inner().clone(), task_id.to_owned());
+    with_tx(db, move |conn| {
+        let (board_id, bus, task_id) = (board_id.as_str(), &bus, task_id.as_str());
+        let token_hash = hash_key(&token.0);
+        access::require_role(conn, board_id, &token_hash, BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+
+        access::require_display_name(conn, board_id, &actor)?;
+        let actor = actor.name();
+
+        let trashed = trash::move_to_trash(conn, board_id, task_id, actor).map_err(|e| db_error(&e.to_string()))?;
+        if let Some(event_data) = trashed {
+            log_event(conn, task_id, "deleted", actor, &event_data);
 
-    let actor = actor.unwrap_or("anonymous");
-    access::require_display_name_if_needed(&conn, board_id, actor)?;
+            bus.emit(
+                conn,
+                crate::events::BoardEvent {
+                    event: event_types::TASK_DELETED.to_string(),
+                    board_id: board_id.to_string(),
+                    data: event_data,
+                },
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+            Ok(Json(serde_json::json!({"deleted": true, "id": task_id, "trashed": true})))
+        } else {
+            Err(not_found("Task"))
+        }
+    })
+    .await
+}
 
-    // Capture task title before deleting for activity feed
-    let task_title: Option<String> = conn
-        .query_row(
-            "SELECT title FROM tasks WHERE id = ?1 AND board_id = ?2",
-            rusqlite::params![task_id, board_id],
-            |row| row.get(0),
-        )
-        .ok();
+// ============ Task Numbers ============
 
-    let affected = conn
-        .execute(
-            "DELETE FROM tasks WHERE id = ?1 AND board_id = ?2",
-            rusqlite::params![task_id, board_id],
+/// Resolve a task key (`OPS-12`, `#12`, `12`, or a key the task had before a
+/// renumber or prefix change) to the task — public, no auth required.
+#[get("/boards/<board_id>/tasks/resolve?<key>")]
+pub async fn resolve_task_key(
+    board_id: &str,
+    key: &str,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+) -> Result<Json<TaskResponse>, (Status, Json<ApiError>)> {
+    let (board_id, key) = (board_id.to_owned(), key.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, key) = (board_id.as_str(), key.as_str());
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        let key = key.trim();
+        let prefix: Option<String> = conn
+            .query_row("SELECT task_prefix FROM boards WHERE id = ?1", rusqlite::params![board_id], |row| row.get(0))
+            .map_err(|e| db_error(&e.to_string()))?;
+        let prefix = prefix.as_deref().unwrap_or(DEFAULT_TASK_PREFIX);
+        let by_number = |number: &str| -> Option<String> {
+            let number: i64 = number.parse().ok()?;
+            conn.query_row(
+                "SELECT id FROM tasks WHERE board_id = ?1 AND number = ?2",
+                rusqlite::params![board_id, number],
+                |row| row.get(0),
+            )
+            .ok()
+        };
+
+        // Current keys win over aliases, so reused numbers point at today's task
+        let current = key
+            .get(..prefix.len())
+            .filter(|head| head.eq_ignore_ascii_case(prefix))
+            .and_then(|_| by_number(&key[prefix.len()..]));
+        let task_id = current
+            .or_else(|| {
+                conn.query_row(
+                    "SELECT task_id FROM task_key_aliases WHERE board_id = ?1 AND key = ?2",
+                    rusqlite::params![board_id, key],
+                    |row| row.get(0),
+                )
+                .ok()
+            })
+            .or_else(|| by_number(key.strip_prefix(DEFAULT_TASK_PREFIX).unwrap_or(key)))
+            .ok_or_else(|| not_found("Task"))?;
+
+        load_task_response(conn, &task_id)
+    })
+    .await
+}
+
+/// Re-sequence task numbers — requires manage key. Every task's previous key
+/// is kept as an alias for `GET /boards/<id>/tasks/resolve`.
+#[post("/boards/<board_id>/tasks/renumber", format = "json", data = "<req>")]
+pub async fn renumber_tasks(
+    board_id: &str,
+    req: Json<RenumberTasksRequest>,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<RenumberTasksResponse>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        let token_hash = hash_key(&token.0);
+        access::require_board_exists(conn, board_id)?;
+        access::require_role(conn, board_id, &token_hash, BoardRole::Editor)?;
+
+        let start = req.start.unwrap_or(1);
+        if start < 1 {
+            return Err((Status::BadRequest, Json(ApiError {
+                error: "start must be 1 or greater".to_string(),
+                code: "INVALID_INPUT".to_string(),
+                status: 400,
+            })));
+        }
+        let order_by = match req.order.as_deref().unwrap_or("created") {
+            "created" => "t.created_at ASC, t.rowid ASC",
+            "position" => "c.position ASC, t.position ASC, t.created_at ASC",
+            other => {
+                return Err((Status::BadRequest, Json(ApiError {
+                    error: format!("Unknown order '{}'. Use 'created' or 'position'", other),
+                    code: "INVALID_INPUT".to_string(),
+                    status: 400,
+                })));
+            }
+        };
+
+        let tx = conn.unchecked_transaction().map_err(|e| db_error(&e.to_string()))?;
+        let aliases_recorded = record_task_key_aliases(&tx, board_id).map_err(|e| db_error(&e.to_string()))?;
+        let task_ids: Vec<String> = tx
+            .prepare(&format!(
+                "SELECT t.id FROM tasks t JOIN columns c ON t.column_id = c.id
+                 WHERE t.board_id = ?1 ORDER BY {}",
+                order_by
+            ))
+            .and_then(|mut stmt| {
+                stmt.query_map(rusqlite::params![board_id], |row| row.get(0))?
+                    .collect::<rusqlite::Result<_>>()
+            })
+            .map_err(|e| db_error(&e.to_string()))?;
+        for (i, task_id) in task_ids.iter().enumerate() {
+            tx.execute(
+                "UPDATE tasks SET number = ?1 WHERE id = ?2",
+                rusqlite::params![start + i as i64, task_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        }
+        let next_number = start + task_ids.len() as i64;
+        tx.execute(
+            "UPDATE boards SET next_task_number = ?1, updated_at = datetime('now') WHERE id = ?2",
+            rusqlite::params![next_number, board_id],
         )
-        .unwrap_or(0);
-    if affected > 0 {
-        let event_data = serde_json::json!({"task_id": task_id, "title": task_title});
-        log_event(&conn, task_id, "deleted", actor, &event_data);
+        .map_err(|e| db_error(&e.to_string()))?;
+        tx.commit().map_err(|e| db_error(&e.to_string()))?;
 
-        bus.emit(crate::events::BoardEvent {
-            event: "task.deleted".to_string(),
+        Ok(Json(RenumberTasksResponse {
             board_id: board_id.to_string(),
-            data: event_data,
-        });
-        Ok(Json(serde_json::json!({"deleted": true, "id": task_id})))
-    } else {
-        Err(not_found("Task"))
-    }
+            renumbered: task_ids.len(),
+            next_number,
+            aliases_recorded,
+        }))
+    })
+    .await
 }
 
 // ============ Task Archive / Unarchive ============
 
 /// Archive a task — requires manage key. Optional `?actor=` query param for attribution.
-#[post("/boards/<board_id>/tasks/<task_id>/archive?<actor>")]
-pub fn archive_task(
+#[post("/boards/<board_id>/tasks/<task_id>/archive")]
+pub async fn archive_task(
     board_id: &str,
     task_id: &str,
-    actor: Option<&str>,
+    actor: Actor,
     token: BoardToken,
     db: &State<DbPool>,
     bus: &State<EventBus>,
 ) -> Result<Json<TaskResponse>, (Status, Json<ApiError>)> {
-    let actor = actor.unwrap_or("anonymous");
-    let conn = db.lock().unwrap();
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
-    access::require_not_archived(&conn, board_id)?;
-    access::require_display_name_if_needed(&conn, board_id, actor)?;
+    let (board_id, bus, task_id) = (board_id.to_owned(), bus.inner().clone(), task_id.to_owned());
+    with_tx(db, move |conn| {
+        let (board_id, bus, task_id) = (board_id.as_str(), &bus, task_id.as_str());
+        let token_hash = hash_key(&token.0);
+        access::require_role(conn, board_id, &token_hash, BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+        access::require_display_name(conn, board_id, &actor)?;
+        let actor = actor.name();
+
+        // Check task exists
+        let _existing = load_task_response(conn, task_id)?;
 
-    // Check task exists
-    let _existing = load_task_response(&conn, task_id)?;
-
-    conn.execute(
-        "UPDATE tasks SET archived_at = datetime('now'), updated_at = datetime('now') WHERE id = ?1 AND board_id = ?2",
-        rusqlite::params![task_id, board_id],
-    )
-    .map_err(|e| db_error(&e.to_string()))?;
+        conn.execute(
+            "UPDATE tasks SET archived_at = datetime('now'), updated_at = datetime('now') WHERE id = ?1 AND board_id = ?2",
+            rusqlite::params![task_id, board_id],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
 
-    let event_data = serde_json::json!({"task_id": task_id});
-    log_event(&conn, task_id, "archived", actor, &event_data);
+        let event_data = serde_json::json!({"task_id": task_id});
+        log_event(conn, task_id, "archived", actor, &event_data);
 
-    bus.emit(crate::events::BoardEvent {
-        event: "task.archived".to_string(),
-        board_id: board_id.to_string(),
-        data: event_data,
-    });
+        bus.emit(
+            conn,
+            crate::events::BoardEvent {
+                event: event_types::TASK_ARCHIVED.to_string(),
+                board_id: board_id.to_string(),
+                data: event_data,
+            },
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
 
-    load_task_response(&conn, task_id)
+        load_task_response(conn, task_id)
+    })
+    .await
 }
 
 /// Unarchive a task — requires manage key. Optional `?actor=` query param for attribution.
-#[post("/boards/<board_id>/tasks/<task_id>/unarchive?<actor>")]
-pub fn unarchive_task(
+#[post("/boards/<board_id>/tasks/<task_id>/unarchive")]
+pub async fn unarchive_task(
     board_id: &str,
     task_id: &str,
-    actor: Option<&str>,
+    actor: Actor,
     token: BoardToken,
     db: &State<DbPool>,
     bus: &State<EventBus>,
 ) -> Result<Json<TaskResponse>, (Status, Json<ApiError>)> {
-    let actor = actor.unwrap_or("anonymous");
-    let conn = db.lock().unwrap();
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
-    access::require_not_archived(&conn, board_id)?;
-    access::require_display_name_if_needed(&conn, board_id, actor)?;
+    let (board_id, bus, task_id) = (board_id.to_owned(), bus.inner().clone(), task_id.to_owned());
+    with_tx(db, move |conn| {
+        let (board_id, bus, task_id) = (board_id.as_str(), &bus, task_id.as_str());
+        let token_hash = hash_key(&token.0);
+        access::require_role(conn, board_id, &token_hash, BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+        access::require_display_name(conn, board_id, &actor)?;
+        let actor = actor.name();
 
-    let _existing = load_task_response(&conn, task_id)?;
+        let _existing = load_task_response(conn, task_id)?;
 
-    conn.execute(
-        "UPDATE tasks SET archived_at = NULL, updated_at = datetime('now') WHERE id = ?1 AND board_id = ?2",
-        rusqlite::params![task_id, board_id],
-    )
-    .map_err(|e| db_error(&e.to_string()))?;
+        conn.execute(
+            "UPDATE tasks SET archived_at = NULL, updated_at = datetime('now') WHERE id = ?1 AND board_id = ?2",
+            rusqlite::params![task_id, board_id],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
 
-    let event_data = serde_json::json!({"task_id": task_id});
-    log_event(&conn, task_id, "unarchived", actor, &event_data);
+        let event_data = serde_json::json!({"task_id": task_id});
+        log_event(conn, task_id, "unarchived", actor, &event_data);
 
-    bus.emit(crate::events::BoardEvent {
-        event: "task.unarchived".to_string(),
-        board_id: board_id.to_string(),
-        data: event_data,
-    });
+        bus.emit(
+            conn,
+            crate::events::BoardEvent {
+                event: event_types::TASK_UNARCHIVED.to_string(),
+                board_id: board_id.to_string(),
+                data: event_data,
+            },
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
 
-    load_task_response(&conn, task_id)
+        load_task_response(conn, task_id)
+    })
+    .await
 }
 
 // ============ Agent-First: Claim / Release ============
 
 /// Claim a task — requires manage key.
-#[post("/boards/<board_id>/tasks/<task_id>/claim?<actor>")]
-pub fn claim_task(
+#[post("/boards/<board_id>/tasks/<task_id>/claim")]
+pub async fn claim_task(
     board_id: &str,
     task_id: &str,
-    actor: Option<&str>,
+    actor: Actor,
     token: BoardToken,
     db: &State<DbPool>,
     bus: &State<EventBus>,
 ) -> Result<Json<TaskResponse>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
-    access::require_not_archived(&conn, board_id)?;
+    let (board_id, bus, task_id) = (board_id.to_owned(), bus.inner().clone(), task_id.to_owned());
+    with_tx(db, move |conn| {
+        let (board_id, bus, task_id) = (board_id.as_str(), &bus, task_id.as_str());
+        if let Some(scope) = access::require_action(conn, board_id, &token.0, ScopeAction::Claim)? {
+            scope.require_task(conn, task_id)?;
+        }
+        access::require_not_archived(conn, board_id)?;
 
-    let actor = actor.unwrap_or("anonymous").to_string();
-    access::require_display_name_if_needed(&conn, board_id, &actor)?;
+        access::require_display_name(conn, board_id, &actor)?;
+        let actor = actor.name().to_string();
 
-    // Check if already claimed by someone else
-    let current_claim: Option<String> = conn
-        .query_row(
-            "SELECT claimed_by FROM tasks WHERE id = ?1 AND board_id = ?2",
-            rusqlite::params![task_id, board_id],
-            |row| row.get(0),
-        )
-        .map_err(|_| not_found("Task"))?;
+        // Check if already claimed by someone else
+        let current_claim: Option<String> = conn
+            .query_row(
+                "SELECT claimed_by FROM tasks WHERE id = ?1 AND board_id = ?2",
+                rusqlite::params![task_id, board_id],
+                |row| row.get(0),
+            )
+            .map_err(|_| not_found("Task"))?;
 
-    if let Some(ref claimer) = current_claim {
-        if claimer != &actor {
-            return Err((
-                Status::Conflict,
-                Json(ApiError {
-                    error: format!("Task already claimed by '{}'", claimer),
-                    code: "ALREADY_CLAIMED".to_string(),
-                    status: 409,
-                }),
-            ));
+        if let Some(ref claimer) = current_claim {
+            if claimer != &actor {
+                return Err((
+                    Status::Conflict,
+                    Json(ApiError {
+                        error: format!("Task already claimed by '{}'", claimer),
+                        code: "ALREADY_CLAIMED".to_string(),
+                        status: 409,
+                    }),
+                ));
+            }
         }
-    }
 
-    conn.execute(
-        "UPDATE tasks SET claimed_by = ?1, claimed_at = datetime('now'), updated_at = datetime('now') WHERE id = ?2 AND board_id = ?3",
-        rusqlite::params![actor, task_id, board_id],
-    )
-    .map_err(|e| db_error(&e.to_string()))?;
+        conn.execute(
+            "UPDATE tasks SET claimed_by = ?1, claimed_at = datetime('now'), updated_at = datetime('now') WHERE id = ?2 AND board_id = ?3",
+            rusqlite::params![actor, task_id, board_id],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
 
-    let event_data = serde_json::json!({"task_id": task_id, "actor": actor});
-    log_event(&conn, task_id, "claimed", &actor, &event_data);
+        let event_data = serde_json::json!({"task_id": task_id, "actor": actor});
+        log_event(conn, task_id, "claimed", &actor, &event_data);
 
-    bus.emit(crate::events::BoardEvent {
-        event: "task.claimed".to_string(),
-        board_id: board_id.to_string(),
-        data: event_data,
-    });
+        bus.emit(
+            conn,
+            crate::events::BoardEvent {
+                event: event_types::TASK_CLAIMED.to_string(),
+                board_id: board_id.to_string(),
+                data: event_data,
+            },
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
 
-    load_task_response(&conn, task_id)
+        load_task_response(conn, task_id)
+    })
+    .await
 }
 
 /// Release a claimed task — requires manage key. Optional `?actor=` query param for attribution.
-#[post("/boards/<board_id>/tasks/<task_id>/release?<actor>")]
-pub fn release_task(
+#[post("/boards/<board_id>/tasks/<task_id>/release")]
+pub async fn release_task(
     board_id: &str,
     task_id: &str,
-    actor: Option<&str>,
+    actor: Actor,
     token: BoardToken,
     db: &State<DbPool>,
     bus: &State<EventBus>,
 ) -> Result<Json<TaskResponse>, (Status, Json<ApiError>)> {
-    let actor = actor.unwrap_or("anonymous");
-    let conn = db.lock().unwrap();
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
-    access::require_not_archived(&conn, board_id)?;
-    access::require_display_name_if_needed(&conn, board_id, actor)?;
+    let (board_id, bus, task_id) = (board_id.to_owned(), bus.inner().clone(), task_id.to_owned());
+    with_tx(db, move |conn| {
+        let (board_id, bus, task_id) = (board_id.as_str(), &bus, task_id.as_str());
+        if let Some(scope) = access::require_action(conn, board_id, &token.0, ScopeAction::Claim)? {
+            scope.require_task(conn, task_id)?;
+        }
+        access::require_not_archived(conn, board_id)?;
+        access::require_display_name(conn, board_id, &actor)?;
+        let actor = actor.name();
 
-    conn.execute(
-        "UPDATE tasks SET claimed_by = NULL, claimed_at = NULL, updated_at = datetime('now') WHERE id = ?1 AND board_id = ?2",
-        rusqlite::params![task_id, board_id],
-    )
-    .map_err(|e| db_error(&e.to_string()))?;
+        conn.execute(
+            "UPDATE tasks SET claimed_by = NULL, claimed_at = NULL, updated_at = datetime('now') WHERE id = ?1 AND board_id = ?2",
+            rusqlite::params![task_id, board_id],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
 
-    let event_data = serde_json::json!({"task_id": task_id});
-    log_event(&conn, task_id, "released", actor, &event_data);
+        let event_data = serde_json::json!({"task_id": task_id});
+        log_event(conn, task_id, "released", actor, &event_data);
 
-    bus.emit(crate::events::BoardEvent {
-        event: "task.released".to_string(),
-        board_id: board_id.to_string(),
-        data: event_data,
-    });
+        bus.emit(
+            conn,
+            crate::events::BoardEvent {
+                event: event_types::TASK_RELEASED.to_string(),
+                board_id: board_id.to_string(),
+                data: event_data,
+            },
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
 
-    load_task_response(&conn, task_id)
+        load_task_response(conn, task_id)
+    })
+    .await
 }
 
-/// Move a task to a different column — requires manage key.
-/// Accepts optional `?actor=` query param for attribution.
-#[post("/boards/<board_id>/tasks/<task_id>/move/<target_column_id>?<actor>")]
-pub fn move_task(
+/// Who is working on what — public. Every claimed task that isn't completed or
+/// archived, grouped by claimer, with claim ages. Only `group_by=agent` exists today.
+#[get("/boards/<board_id>/claims?<group_by>")]
+pub async fn list_claims(
     board_id: &str,
-    task_id: &str,
-    target_column_id: &str,
-    actor: Option<&str>,
-    token: BoardToken,
+    group_by: Option<&str>,
+    token: Option<BoardToken>,
     db: &State<DbPool>,
-    bus: &State<EventBus>,
-) -> Result<Json<TaskResponse>, (Status, Json<ApiError>)> {
-    let actor = actor.unwrap_or("anonymous");
-    let conn = db.lock().unwrap();
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
-    access::require_not_archived(&conn, board_id)?;
-    access::require_display_name_if_needed(&conn, board_id, actor)?;
-
-    // Verify target column belongs to the board
-    let col_exists: bool = conn
-        .query_row(
-            "SELECT COUNT(*) > 0 FROM columns WHERE id = ?1 AND board_id = ?2",
-            rusqlite::params![target_column_id, board_id],
-            |row| row.get(0),
-        )
-        .unwrap_or(false);
-
-    if !col_exists {
+) -> Result<Json<ClaimsResponse>, (Status, Json<ApiError>)> {
+    let group_by = group_by.unwrap_or("agent");
+    if group_by != "agent" {
         return Err((
             Status::BadRequest,
             Json(ApiError {
-                error: "Target column not found in this board".to_string(),
-                code: "INVALID_COLUMN".to_string(),
+                error: format!("Unsupported group_by '{}'. Valid values: agent", group_by),
+                code: "INVALID_INPUT".to_string(),
                 status: 400,
             }),
         ));
     }
+    let (board_id, group_by) = (board_id.to_owned(), group_by.to_owned());
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_read_access(conn, board_id, token.as_ref())?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT t.claimed_by, t.id, t.title, t.column_id, c.name, t.priority, t.claimed_at,
+                        MAX(0, CAST(strftime('%s', 'now') AS INTEGER) - CAST(strftime('%s', t.claimed_at) AS INTEGER))
+                 FROM tasks t JOIN columns c ON c.id = t.column_id
+                 WHERE t.board_id = ?1 AND t.claimed_by IS NOT NULL
+                   AND t.completed_at IS NULL AND t.archived_at IS NULL
+                 ORDER BY t.claimed_at ASC, t.rowid ASC",
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        let rows: Vec<(String, ClaimedTask)> = stmt
+            .query_map(rusqlite::params![board_id], |row| {
+                Ok((
+                    row.get(0)?,
+                    ClaimedTask {
+                        task_id: row.get(1)?,
+                        title: row.get(2)?,
+                        column_id: row.get(3)?,
+                        column_name: row.get(4)?,
+                        priority: row.get(5)?,
+                        claimed_at: row.get::<_, Option<String>>(6)?.unwrap_or_default(),
+                        claim_age_seconds: row.get::<_, Option<i64>>(7)?.unwrap_or(0),
+                    },
+                ))
+            })
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
 
-    check_wip_limit(&conn, target_column_id, Some(task_id))?;
+        let total_claimed = rows.len();
+        let mut groups: Vec<AgentClaims> = Vec::new();
+        for (agent, task) in rows {
+            match groups.iter_mut().find(|g| g.agent == agent) {
+                Some(group) => group.tasks.push(task),
+                None => groups.push(AgentClaims {
+                    agent,
+                    count: 0,
+                    oldest_claim_age_seconds: task.claim_age_seconds,
+                    tasks: vec![task],
+                }),
+            }
+        }
+        for group in groups.iter_mut() {
+            group.count = group.tasks.len();
+        }
+        groups.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.agent.cmp(&b.agent)));
 
-    let from_col: String = conn
-        .query_row(
-            "SELECT column_id FROM tasks WHERE id = ?1 AND board_id = ?2",
-            rusqlite::params![task_id, board_id],
-            |row| row.get(0),
-        )
-        .map_err(|_| not_found("Task"))?;
+        Ok(Json(ClaimsResponse {
+            board_id: board_id.to_string(),
+            group_by: group_by.to_string(),
+            total_claimed,
+            agents: groups.len(),
+            groups,
+        }))
+    })
+    .await
+}
 
-    let is_done_column: bool = conn
-        .query_row(
-            "SELECT position = (SELECT MAX(position) FROM columns WHERE board_id = ?1) FROM columns WHERE id = ?2",
-            rusqlite::params![board_id, target_column_id],
-            |row| row.get(0),
-        )
-        .unwrap_or(false);
+// ============ Edit Locks ============
 
-    if is_done_column {
-        conn.execute(
-            "UPDATE tasks SET column_id = ?1, completed_at = datetime('now'), updated_at = datetime('now') WHERE id = ?2 AND board_id = ?3",
-            rusqlite::params![target_column_id, task_id, board_id],
-        )
-        .map_err(|e| db_error(&e.to_string()))?;
-    } else {
-        conn.execute(
-            "UPDATE tasks SET column_id = ?1, completed_at = NULL, updated_at = datetime('now') WHERE id = ?2 AND board_id = ?3",
-            rusqlite::params![target_column_id, task_id, board_id],
-        )
-        .map_err(|e| db_error(&e.to_string()))?;
-    }
+const DEFAULT_LOCK_TTL_SECS: i64 = 300;
+const MAX_LOCK_TTL_SECS: i64 = 3600;
 
-    // Resolve column names for activity display
-    let from_col_name: String = conn
-        .query_row("SELECT name FROM columns WHERE id = ?1", rusqlite::params![from_col], |row| row.get(0))
-        .unwrap_or_else(|_| from_col.clone());
-    let to_col_name: String = conn
-        .query_row("SELECT name FROM columns WHERE id = ?1", rusqlite::params![target_column_id], |row| row.get(0))
-        .unwrap_or_else(|_| target_column_id.to_string());
+/// Take or renew an edit lock on a task — requires manage key and a named actor.
+/// Locks are separate from claims: they only stop other actors' PATCHes while
+/// someone edits the card, and expire after `ttl` seconds (default 300, max 3600).
+#[post("/boards/<board_id>/tasks/<task_id>/lock?<ttl>")]
+pub async fn lock_task(
+    board_id: &str,
+    task_id: &str,
+    ttl: Option<i64>,
+    actor: Actor,
+    token: BoardToken,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<TaskLockResponse>, (Status, Json<ApiError>)> {
+    let (board_id, bus, task_id) = (board_id.to_owned(), bus.inner().clone(), task_id.to_owned());
+    with_tx(db, move |conn| {
+        let (board_id, bus, task_id) = (board_id.as_str(), &bus, task_id.as_str());
+        let token_hash = hash_key(&token.0);
+        access::require_role(conn, board_id, &token_hash, BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+        require_lock_actor(&actor)?;
+        let actor = actor.name();
+        require_task_on_board(conn, board_id, task_id)?;
+
+        let ttl = ttl.unwrap_or(DEFAULT_LOCK_TTL_SECS).clamp(1, MAX_LOCK_TTL_SECS);
+        // Upsert only when the lock is free, expired, or already ours
+        let acquired = conn
+            .execute(
+                "INSERT INTO task_locks (task_id, locked_by, expires_at)
+                 VALUES (?1, ?2, datetime('now', ?3))
+                 ON CONFLICT(task_id) DO UPDATE SET
+                     locked_by = excluded.locked_by,
+                     expires_at = excluded.expires_at,
+                     created_at = CASE WHEN task_locks.locked_by = excluded.locked_by
+                                       THEN task_locks.created_at ELSE datetime('now') END
+                 WHERE task_locks.locked_by = excluded.locked_by
+                    OR task_locks.expires_at <= datetime('now')",
+                rusqlite::params![task_id, actor, format!("+{} seconds", ttl)],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
 
-    let event_data = serde_json::json!({"task_id": task_id, "from": from_col, "to": target_column_id, "from_column": from_col_name, "to_column": to_col_name});
-    log_event(&conn, task_id, "moved", actor, &event_data);
+        let lock = active_lock(conn, task_id).ok_or_else(|| db_error("Lock vanished"))?;
+        if acquired == 0 {
+            return Err(lock_conflict(&lock));
+        }
 
-    bus.emit(crate::events::BoardEvent {
-        event: "task.moved".to_string(),
-        board_id: board_id.to_string(),
-        data: event_data,
-    });
+        bus.emit(
+            conn,
+            crate::events::BoardEvent {
+                event: event_types::TASK_LOCKED.to_string(),
+                board_id: board_id.to_string(),
+                data: serde_json::json!({"task_id": task_id, "actor": actor, "expires_at": lock.expires_at}),
+            },
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
 
-    load_task_response(&conn, task_id)
+        Ok(Json(lock))
+    })
+    .await
 }
 
-// ============ Task Reorder ============
+/// Get the active edit lock on a task, if any (public).
+#[get("/boards/<board_id>/tasks/<task_id>/lock")]
+pub async fn get_task_lock(
+    board_id: &str,
+    task_id: &str,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+) -> Result<Json<TaskLockResponse>, (Status, Json<ApiError>)> {
+    let (board_id, task_id) = (board_id.to_owned(), task_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, task_id) = (board_id.as_str(), task_id.as_str());
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        require_task_on_board(conn, board_id, task_id)?;
+        active_lock(conn, task_id).map(Json).ok_or_else(not_locked)
+    })
+    .await
+}
 
-/// Reorder a task — requires manage key. Optional `?actor=` query param for attribution.
-#[post(
-    "/boards/<board_id>/tasks/<task_id>/reorder?<actor>",
-    format = "json",
-    data = "<req>"
-)]
-pub fn reorder_task(
+/// Release an edit lock — requires manage key. Only the holder can release an
+/// unexpired lock.
+#[delete("/boards/<board_id>/tasks/<task_id>/lock")]
+pub async fn unlock_task(
     board_id: &str,
     task_id: &str,
-    actor: Option<&str>,
-    req: Json<ReorderTaskRequest>,
+    actor: Actor,
     token: BoardToken,
     db: &State<DbPool>,
     bus: &State<EventBus>,
-) -> Result<Json<TaskResponse>, (Status, Json<ApiError>)> {
-    let req = req.into_inner();
-    let conn = db.lock().unwrap();
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
-    access::require_not_archived(&conn, board_id)?;
-    let actor = actor.unwrap_or("anonymous");
-    access::require_display_name_if_needed(&conn, board_id, actor)?;
-
-    let current_column: String = conn
-        .query_row(
-            "SELECT column_id FROM tasks WHERE id = ?1 AND board_id = ?2",
-            rusqlite::params![task_id, board_id],
-            |row| row.get(0),
+) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
+    let (board_id, bus, task_id) = (board_id.to_owned(), bus.inner().clone(), task_id.to_owned());
+    with_tx(db, move |conn| {
+        let (board_id, bus, task_id) = (board_id.as_str(), &bus, task_id.as_str());
+        let token_hash = hash_key(&token.0);
+        access::require_role(conn, board_id, &token_hash, BoardRole::Editor)?;
+        require_lock_actor(&actor)?;
+        let actor = actor.name();
+        require_task_on_board(conn, board_id, task_id)?;
+
+        let lock = active_lock(conn, task_id).ok_or_else(not_locked)?;
+        if lock.locked_by != actor {
+            return Err(lock_conflict(&lock));
+        }
+        conn.execute("DELETE FROM task_locks WHERE task_id = ?1", rusqlite::params![task_id])
+            .map_err(|e| db_error(&e.to_string()))?;
+
+        bus.emit(
+            conn,
+            crate::events::BoardEvent {
+                event: event_types::TASK_UNLOCKED.to_string(),
+                board_id: board_id.to_string(),
+                data: serde_json::json!({"task_id": task_id, "actor": actor}),
+            },
         )
-        .map_err(|_| not_found("Task"))?;
+        .map_err(|e| db_error(&e.to_string()))?;
 
-    let target_column = req.column_id.as_deref().unwrap_or(&current_column);
-    let moving_columns = target_column != current_column;
+        Ok(Json(serde_json::json!({"message": "Lock released"})))
+    })
+    .await
+}
 
-    if moving_columns {
-        let col_exists: bool = conn
-            .query_row(
-                "SELECT COUNT(*) > 0 FROM columns WHERE id = ?1 AND board_id = ?2",
-                rusqlite::params![target_column, board_id],
-                |row| row.get(0),
-            )
+/// Unexpired lock on a task. Expired rows are treated as absent.
+fn active_lock(conn: &Connection, task_id: &str) -> Option<TaskLockResponse> {
+    conn.query_row(
+        "SELECT locked_by, expires_at,
+                CAST(strftime('%s', expires_at) - strftime('%s', 'now') AS INTEGER)
+         FROM task_locks WHERE task_id = ?1 AND expires_at > datetime('now')",
+        rusqlite::params![task_id],
+        |row| {
+            Ok(TaskLockResponse {
+                task_id: task_id.to_string(),
+                locked_by: row.get(0)?,
+                expires_at: row.get(1)?,
+                ttl_seconds: row.get(2)?,
+            })
+        },
+    )
+    .ok()
+}
+
+/// Reject edits to a task locked by someone else.
+fn check_edit_lock(conn: &Connection, task_id: &str, actor: &str) -> Result<(), (Status, Json<ApiError>)> {
+    match active_lock(conn, task_id) {
+        Some(lock) if lock.locked_by != actor => Err(lock_conflict(&lock)),
+        _ => Ok(()),
+    }
+}
+
+fn require_lock_actor(actor: &Actor) -> Result<(), (Status, Json<ApiError>)> {
+    if actor.is_anonymous() {
+        return Err((
+            Status::BadRequest,
+            Json(ApiError {
+                error: "Edit locks need a named actor (?actor=, ?agent= or X-Actor)".to_string(),
+                code: "ACTOR_REQUIRED".to_string(),
+                status: 400,
+            }),
+        ));
+    }
+    Ok(())
+}
+
+fn require_task_on_board(conn: &Connection, board_id: &str, task_id: &str) -> Result<(), (Status, Json<ApiError>)> {
+    conn.query_row(
+        "SELECT 1 FROM tasks WHERE id = ?1 AND board_id = ?2",
+        rusqlite::params![task_id, board_id],
+        |_| Ok(()),
+    )
+    .map_err(|_| not_found("Task"))
+}
+
+fn lock_conflict(lock: &TaskLockResponse) -> (Status, Json<ApiError>) {
+    (
+        Status::Conflict,
+        Json(ApiError {
+            error: format!(
+                "Task is being edited by '{}' (lock expires at {})",
+                lock.locked_by, lock.expires_at
+            ),
+            code: "TASK_LOCKED".to_string(),
+            status: 409,
+        }),
+    )
+}
+
+fn not_locked() -> (Status, Json<ApiError>) {
+    (
+        Status::NotFound,
+        Json(ApiError {
+            error: "Task is not locked".to_string(),
+            code: "NOT_LOCKED".to_string(),
+            status: 404,
+        }),
+    )
+}
+
+/// Move a task to a different column — requires manage key.
+/// Accepts optional `?actor=` query param for attribution.
+#[post("/boards/<board_id>/tasks/<task_id>/move/<target_column_id>")]
+pub async fn move_task(
+    board_id: &str,
+    task_id: &str,
+    target_column_id: &str,
+    actor: Actor,
+    token: BoardToken,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<TaskResponse>, MoveError> {
+    let (board_id, bus, target_column_id, task_id) = (board_id.to_owned(), bus.inner().clone(), target_column_id.to_owned(), task_id.to_owned());
+    let client = bus.outbound().clone();
+    validation_hook::run(db, &client, move |conn, gate| {
+        let (board_id, bus, target_column_id, task_id) = (board_id.as_str(), &bus, target_column_id.as_str(), task_id.as_str());
+        if let Some(scope) = access::require_action(conn, board_id, &token.0, ScopeAction::MoveTask)? {
+            scope.require_task(conn, task_id)?;
+            scope.require_column(target_column_id)?;
+        }
+        access::require_not_archived(conn, board_id)?;
+        access::require_display_name(conn, board_id, &actor)?;
+        let actor = actor.name();
+
+        // Verify target column belongs to the board
+        let col_exists: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM columns WHERE id = ?1 AND board_id = ?2",
+                rusqlite::params![target_column_id, board_id],
+                |row| row.get(0),
+            )
             .unwrap_or(false);
 
         if !col_exists {
-            return Err((
+            return Err(MoveError::Api((
                 Status::BadRequest,
                 Json(ApiError {
                     error: "Target column not found in this board".to_string(),
                     code: "INVALID_COLUMN".to_string(),
                     status: 400,
                 }),
-            ));
+            )));
         }
 
-        check_wip_limit(&conn, target_column, Some(task_id))?;
-    }
-
-    let new_pos = req.position.max(0);
-
-    if !moving_columns {
-        conn.execute(
-            "UPDATE tasks SET position = position - 1 WHERE column_id = ?1 AND position > (SELECT position FROM tasks WHERE id = ?2) AND id != ?2",
-            rusqlite::params![target_column, task_id],
-        )
-        .map_err(|e| db_error(&e.to_string()))?;
-    }
+        validate_move(conn, board_id, task_id, target_column_id)?;
+        let task = load_task_response(conn, task_id)?;
+        validation_hook::check_move(conn, gate, board_id, actor, &task, target_column_id)?;
 
-    conn.execute(
-        "UPDATE tasks SET position = position + 1 WHERE column_id = ?1 AND position >= ?2 AND id != ?3",
-        rusqlite::params![target_column, new_pos, task_id],
-    )
-    .map_err(|e| db_error(&e.to_string()))?;
+        let from_col: String = conn
+            .query_row(
+                "SELECT column_id FROM tasks WHERE id = ?1 AND board_id = ?2",
+                rusqlite::params![task_id, board_id],
+                |row| row.get(0),
+            )
+            .map_err(|_| not_found("Task"))?;
 
-    if moving_columns {
         let is_done_column: bool = conn
             .query_row(
                 "SELECT position = (SELECT MAX(position) FROM columns WHERE board_id = ?1) FROM columns WHERE id = ?2",
-                rusqlite::params![board_id, target_column],
+                rusqlite::params![board_id, target_column_id],
                 |row| row.get(0),
             )
             .unwrap_or(false);
 
-        let completed = if is_done_column {
-            "datetime('now')"
+        if is_done_column {
+            conn.execute(
+                "UPDATE tasks SET column_id = ?1, completed_at = datetime('now'), updated_at = datetime('now') WHERE id = ?2 AND board_id = ?3",
+                rusqlite::params![target_column_id, task_id, board_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
         } else {
-            "NULL"
-        };
-
-        conn.execute(
-            &format!(
-                "UPDATE tasks SET column_id = ?1, position = ?2, completed_at = {}, updated_at = datetime('now') WHERE id = ?3",
-                completed
-            ),
-            rusqlite::params![target_column, new_pos, task_id],
-        )
-        .map_err(|e| db_error(&e.to_string()))?;
+            conn.execute(
+                "UPDATE tasks SET column_id = ?1, completed_at = NULL, updated_at = datetime('now') WHERE id = ?2 AND board_id = ?3",
+                rusqlite::params![target_column_id, task_id, board_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        }
 
-        conn.execute(
-            "UPDATE tasks SET position = position - 1 WHERE column_id = ?1 AND position > 0 AND id NOT IN (SELECT id FROM tasks WHERE column_id = ?1 AND position = 0) ORDER BY position",
-            rusqlite::params![current_column],
-        )
-        .ok();
-    } else {
-        conn.execute(
-            "UPDATE tasks SET position = ?1, updated_at = datetime('now') WHERE id = ?2",
-            rusqlite::params![new_pos, task_id],
+        // Resolve column names for activity display
+        let from_col_name: String = conn
+            .query_row("SELECT name FROM columns WHERE id = ?1", rusqlite::params![from_col], |row| row.get(0))
+            .unwrap_or_else(|_| from_col.clone());
+        let to_col_name: String = conn
+            .query_row("SELECT name FROM columns WHERE id = ?1", rusqlite::params![target_column_id], |row| row.get(0))
+            .unwrap_or_else(|_| target_column_id.to_string());
+
+        let event_data = serde_json::json!({"task_id": task_id, "from": from_col, "to": target_column_id, "from_column": from_col_name, "to_column": to_col_name});
+        log_event(conn, task_id, "moved", actor, &event_data);
+
+        bus.emit(
+            conn,
+            crate::events::BoardEvent {
+                event: event_types::TASK_MOVED.to_string(),
+                board_id: board_id.to_string(),
+                data: event_data,
+            },
         )
         .map_err(|e| db_error(&e.to_string()))?;
-    }
-
-    let event_data = serde_json::json!({
-        "task_id": task_id,
-        "position": new_pos,
-        "column_id": target_column,
-        "from_column": current_column,
-    });
-    log_event(&conn, task_id, "reordered", actor, &event_data);
-
-    bus.emit(crate::events::BoardEvent {
-        event: "task.reordered".to_string(),
-        board_id: board_id.to_string(),
-        data: event_data,
-    });
+        if from_col != target_column_id {
+            if let Some(assignment) =
+                auto_assign::on_enter(conn, task_id, target_column_id).map_err(|e| db_error(&e.to_string()))?
+            {
+                assignment.emit(conn, bus, board_id).map_err(|e| db_error(&e.to_string()))?;
+            }
+        }
 
-    load_task_response(&conn, task_id)
+        load_task_response(conn, task_id).map_err(MoveError::from)
+    })
+    .await
 }
 
-// ============ Batch Operations ============
+// ============ Task Reorder ============
 
-/// Batch operations — requires manage key.
-#[post("/boards/<board_id>/tasks/batch", format = "json", data = "<req>")]
-pub fn batch_tasks(
+/// Reorder a task — requires manage key. Optional `?actor=` query param for attribution.
+#[post(
+    "/boards/<board_id>/tasks/<task_id>/reorder",
+    format = "json",
+    data = "<req>"
+)]
+pub async fn reorder_task(
     board_id: &str,
-    req: Json<BatchRequest>,
+    task_id: &str,
+    actor: Actor,
+    req: Json<ReorderTaskRequest>,
     token: BoardToken,
     db: &State<DbPool>,
     bus: &State<EventBus>,
-) -> Result<Json<BatchResponse>, (Status, Json<ApiError>)> {
+) -> Result<Json<TaskResponse>, MoveError> {
     let req = req.into_inner();
-    let conn = db.lock().unwrap();
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
-    access::require_not_archived(&conn, board_id)?;
-    let actor = req.actor_name.as_deref().unwrap_or("batch");
-    access::require_display_name_if_needed(&conn, board_id, actor)?;
-
-    if req.operations.is_empty() {
-        return Err((
-            Status::BadRequest,
-            Json(ApiError {
-                error: "No operations provided".to_string(),
-                code: "EMPTY_BATCH".to_string(),
-                status: 400,
-            }),
-        ));
-    }
+    let (board_id, bus, task_id) = (board_id.to_owned(), bus.inner().clone(), task_id.to_owned());
+    let client = bus.outbound().clone();
+    validation_hook::run(db, &client, move |conn, gate| {
+        let (board_id, bus, task_id) = (board_id.as_str(), &bus, task_id.as_str());
+        let token_hash = hash_key(&token.0);
+        access::require_role(conn, board_id, &token_hash, BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+        access::require_display_name(conn, board_id, &actor)?;
+        let actor = actor.name();
+
+        let current_column: String = conn
+            .query_row(
+                "SELECT column_id FROM tasks WHERE id = ?1 AND board_id = ?2",
+                rusqlite::params![task_id, board_id],
+                |row| row.get(0),
+            )
+            .map_err(|_| not_found("Task"))?;
 
-    if req.operations.len() > 50 {
-        return Err((
-            Status::BadRequest,
-            Json(ApiError {
-                error: "Maximum 50 operations per batch request".to_string(),
-                code: "BATCH_TOO_LARGE".to_string(),
-                status: 400,
-            }),
-        ));
-    }
+        let target_column = req.column_id.as_deref().unwrap_or(&current_column);
+        let moving_columns = target_column != current_column;
 
-    let mut results = Vec::new();
-    let mut succeeded = 0;
-    let mut failed = 0;
+        if moving_columns {
+            let col_exists: bool = conn
+                .query_row(
+                    "SELECT COUNT(*) > 0 FROM columns WHERE id = ?1 AND board_id = ?2",
+                    rusqlite::params![target_column, board_id],
+                    |row| row.get(0),
+                )
+                .unwrap_or(false);
 
-    for op in &req.operations {
-        match op {
-            BatchOperation::Move {
-                task_ids,
-                column_id,
-            } => {
-                let result = batch_move(&conn, board_id, task_ids, column_id, actor, bus);
-                match result {
-                    Ok(affected) => {
-                        succeeded += 1;
-                        results.push(BatchOperationResult {
-                            action: "move".to_string(),
-                            task_ids: task_ids.clone(),
-                            success: true,
-                            error: None,
-                            affected,
-                        });
-                    }
-                    Err(msg) => {
-                        failed += 1;
-                        results.push(BatchOperationResult {
-                            action: "move".to_string(),
-                            task_ids: task_ids.clone(),
-                            success: false,
-                            error: Some(msg),
-                            affected: 0,
-                        });
-                    }
-                }
-            }
-            BatchOperation::Update { task_ids, fields } => {
-                let result = batch_update(&conn, board_id, task_ids, fields, actor, bus);
-                match result {
-                    Ok(affected) => {
-                        succeeded += 1;
-                        results.push(BatchOperationResult {
-                            action: "update".to_string(),
-                            task_ids: task_ids.clone(),
-                            success: true,
-                            error: None,
-                            affected,
-                        });
-                    }
-                    Err(msg) => {
-                        failed += 1;
-                        results.push(BatchOperationResult {
-                            action: "update".to_string(),
-                            task_ids: task_ids.clone(),
-                            success: false,
-                            error: Some(msg),
-                            affected: 0,
-                        });
-                    }
-                }
-            }
-            BatchOperation::Delete { task_ids } => {
-                let result = batch_delete(&conn, board_id, task_ids, actor, bus);
-                match result {
-                    Ok(affected) => {
-                        succeeded += 1;
-                        results.push(BatchOperationResult {
-                            action: "delete".to_string(),
-                            task_ids: task_ids.clone(),
-                            success: true,
-                            error: None,
-                            affected,
-                        });
-                    }
-                    Err(msg) => {
-                        failed += 1;
-                        results.push(BatchOperationResult {
-                            action: "delete".to_string(),
-                            task_ids: task_ids.clone(),
-                            success: false,
-                            error: Some(msg),
-                            affected: 0,
-                        });
-                    }
-                }
+            if !col_exists {
+                return Err(MoveError::Api((
+                    Status::BadRequest,
+                    Json(ApiError {
+                        error: "Target column not found in this board".to_string(),
+                        code: "INVALID_COLUMN".to_string(),
+                        status: 400,
+                    }),
+                )));
             }
-        }
-    }
 
-    Ok(Json(BatchResponse {
-        total: req.operations.len(),
-        succeeded,
-        failed,
-        results,
-    }))
-}
+            validate_move(conn, board_id, task_id, target_column)?;
+            let task = load_task_response(conn, task_id)?;
+            validation_hook::check_move(conn, gate, board_id, actor, &task, target_column)?;
+        }
 
-fn batch_move(
-    conn: &Connection,
-    board_id: &str,
-    task_ids: &[String],
-    column_id: &str,
-    actor: &str,
-    bus: &EventBus,
-) -> Result<usize, String> {
-    let col_exists: bool = conn
-        .query_row(
-            "SELECT COUNT(*) > 0 FROM columns WHERE id = ?1 AND board_id = ?2",
-            rusqlite::params![column_id, board_id],
-            |row| row.get(0),
-        )
-        .unwrap_or(false);
+        let new_pos = req.position.max(0);
 
-    if !col_exists {
-        return Err("Target column not found in this board".to_string());
-    }
+        if !moving_columns {
+            conn.execute(
+                "UPDATE tasks SET position = position - 1 WHERE column_id = ?1 AND position > (SELECT position FROM tasks WHERE id = ?2) AND id != ?2",
+                rusqlite::params![target_column, task_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        }
 
-    let is_done_column: bool = conn
-        .query_row(
-            "SELECT position = (SELECT MAX(position) FROM columns WHERE board_id = ?1) FROM columns WHERE id = ?2",
-            rusqlite::params![board_id, column_id],
-            |row| row.get(0),
+        conn.execute(
+            "UPDATE tasks SET position = position + 1 WHERE column_id = ?1 AND position >= ?2 AND id != ?3",
+            rusqlite::params![target_column, new_pos, task_id],
         )
-        .unwrap_or(false);
+        .map_err(|e| db_error(&e.to_string()))?;
 
-    let mut affected = 0;
-    for task_id in task_ids {
-        let belongs: bool = conn
-            .query_row(
-                "SELECT COUNT(*) > 0 FROM tasks WHERE id = ?1 AND board_id = ?2",
-                rusqlite::params![task_id, board_id],
-                |row| row.get(0),
-            )
-            .unwrap_or(false);
+        if moving_columns {
+            let is_done_column: bool = conn
+                .query_row(
+                    "SELECT position = (SELECT MAX(position) FROM columns WHERE board_id = ?1) FROM columns WHERE id = ?2",
+                    rusqlite::params![board_id, target_column],
+                    |row| row.get(0),
+                )
+                .unwrap_or(false);
 
-        if !belongs {
-            continue;
-        }
+            let completed = if is_done_column {
+                "datetime('now')"
+            } else {
+                "NULL"
+            };
 
-        let from_col: String = conn
-            .query_row(
-                "SELECT column_id FROM tasks WHERE id = ?1",
-                rusqlite::params![task_id],
-                |row| row.get(0),
+            conn.execute(
+                &format!(
+                    "UPDATE tasks SET column_id = ?1, position = ?2, completed_at = {}, updated_at = datetime('now') WHERE id = ?3",
+                    completed
+                ),
+                rusqlite::params![target_column, new_pos, task_id],
             )
-            .unwrap_or_default();
+            .map_err(|e| db_error(&e.to_string()))?;
 
-        let rows = if is_done_column {
             conn.execute(
-                "UPDATE tasks SET column_id = ?1, completed_at = datetime('now'), updated_at = datetime('now') WHERE id = ?2 AND board_id = ?3",
-                rusqlite::params![column_id, task_id, board_id],
+                "UPDATE tasks SET position = position - 1 WHERE column_id = ?1 AND position > 0 AND id NOT IN (SELECT id FROM tasks WHERE column_id = ?1 AND position = 0) ORDER BY position",
+                rusqlite::params![current_column],
             )
-            .unwrap_or(0)
+            .ok();
         } else {
             conn.execute(
-                "UPDATE tasks SET column_id = ?1, completed_at = NULL, updated_at = datetime('now') WHERE id = ?2 AND board_id = ?3",
-                rusqlite::params![column_id, task_id, board_id],
+                "UPDATE tasks SET position = ?1, updated_at = datetime('now') WHERE id = ?2",
+                rusqlite::params![new_pos, task_id],
             )
-            .unwrap_or(0)
-        };
+            .map_err(|e| db_error(&e.to_string()))?;
+        }
 
-        if rows > 0 {
-            affected += 1;
-            let from_col_name: String = conn
-                .query_row("SELECT name FROM columns WHERE id = ?1", rusqlite::params![from_col], |row| row.get(0))
-                .unwrap_or_else(|_| from_col.clone());
-            let to_col_name: String = conn
-                .query_row("SELECT name FROM columns WHERE id = ?1", rusqlite::params![column_id], |row| row.get(0))
-                .unwrap_or_else(|_| column_id.to_string());
-            let event_data = serde_json::json!({"task_id": task_id, "from": from_col, "to": column_id, "from_column": from_col_name, "to_column": to_col_name, "batch": true});
-            log_event(conn, task_id, "moved", actor, &event_data);
-            bus.emit(crate::events::BoardEvent {
-                event: "task.moved".to_string(),
+        let event_data = serde_json::json!({
+            "task_id": task_id,
+            "position": new_pos,
+            "column_id": target_column,
+            "from_column": current_column,
+        });
+        log_event(conn, task_id, "reordered", actor, &event_data);
+
+        bus.emit(
+            conn,
+            crate::events::BoardEvent {
+                event: event_types::TASK_REORDERED.to_string(),
                 board_id: board_id.to_string(),
                 data: event_data,
-            });
+            },
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        if moving_columns {
+            if let Some(assignment) =
+                auto_assign::on_enter(conn, task_id, target_column).map_err(|e| db_error(&e.to_string()))?
+            {
+                assignment.emit(conn, bus, board_id).map_err(|e| db_error(&e.to_string()))?;
+            }
         }
-    }
 
-    Ok(affected)
+        load_task_response(conn, task_id).map_err(MoveError::from)
+    })
+    .await
+}
+
+// ============ Board Layout ============
+
+/// Current task ordering per column — public, no auth required. Pass the
+/// returned `version` back when applying a layout.
+#[get("/boards/<board_id>/layout")]
+pub async fn get_board_layout(
+    board_id: &str,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+) -> Result<Json<BoardLayoutResponse>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        let columns = load_layout(conn, board_id)?;
+        Ok(Json(BoardLayoutResponse {
+            board_id: board_id.to_string(),
+            version: layout_version(&columns),
+            columns,
+            changed: None,
+        }))
+    })
+    .await
+}
+
+/// Apply a whole-board ordering snapshot in one transaction — requires manage key.
+/// Fails with 409 `VERSION_CONFLICT` if the board changed since `version` was read,
+/// and with the usual policy violations (WIP limits, dependencies) evaluated
+/// against the final layout. The board's validation hook is asked about every
+/// task that changes column. Nothing is written unless everything succeeds.
+#[put("/boards/<board_id>/layout", format = "json", data = "<req>")]
+pub async fn apply_board_layout(
+    board_id: &str,
+    actor: Actor,
+    req: Json<ApplyLayoutRequest>,
+    token: BoardToken,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<BoardLayoutResponse>, MoveError> {
+    let req = req.into_inner();
+    let (board_id, bus) = (board_id.to_owned(), bus.inner().clone());
+    let client = bus.outbound().clone();
+    validation_hook::run(db, &client, move |conn, gate| {
+        let (board_id, bus) = (board_id.as_str(), &bus);
+        let token_hash = hash_key(&token.0);
+        access::require_role(conn, board_id, &token_hash, BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+        access::require_display_name(conn, board_id, &actor)?;
+        let actor = actor.name();
+
+        let current = load_layout(conn, board_id)?;
+        if layout_version(&current) != req.version {
+            return Err(MoveError::Api((
+                Status::Conflict,
+                Json(ApiError {
+                    error: "Board layout changed since this version was read".to_string(),
+                    code: "VERSION_CONFLICT".to_string(),
+                    status: 409,
+                }),
+            )));
+        }
+
+        let invalid = |error: String, code: &str| {
+            MoveError::Api((
+                Status::BadRequest,
+                Json(ApiError {
+                    error,
+                    code: code.to_string(),
+                    status: 400,
+                }),
+            ))
+        };
+        let mut from_column: HashMap<&str, &str> = HashMap::new();
+        for col in &current {
+            for task_id in &col.task_ids {
+                from_column.insert(task_id, &col.column_id);
+            }
+        }
+        let mut mentioned: HashSet<&str> = HashSet::new();
+        for (column_id, task_ids) in &req.columns {
+            if !current.iter().any(|c| &c.column_id == column_id) {
+                return Err(invalid(format!("Column {} not found in this board", column_id), "INVALID_COLUMN"));
+            }
+            for task_id in task_ids {
+                if !from_column.contains_key(task_id.as_str()) {
+                    return Err(invalid(format!("Task {} is not an active task on this board", task_id), "INVALID_TASK"));
+                }
+                if !mentioned.insert(task_id) {
+                    return Err(invalid(format!("Task {} is listed more than once", task_id), "DUPLICATE_TASK"));
+                }
+            }
+        }
+
+        // Listed tasks first, then the column's unlisted tasks in their old order
+        let target: Vec<(&str, Vec<&str>)> = current
+            .iter()
+            .map(|col| {
+                let mut ids: Vec<&str> = req
+                    .columns
+                    .get(&col.column_id)
+                    .map(|ids| ids.iter().map(String::as_str).collect())
+                    .unwrap_or_default();
+                ids.extend(col.task_ids.iter().map(String::as_str).filter(|id| !mentioned.contains(id)));
+                (col.column_id.as_str(), ids)
+            })
+            .collect();
+
+        let mut checks = Vec::new();
+        for (column_id, ids) in &target {
+            for task_id in ids.iter().filter(|id| from_column[*id] != *column_id) {
+                let task = load_task_response(conn, task_id)?;
+                checks.push((validation_hook::TASK_MOVE, validation_hook::move_subject(&task, column_id)));
+            }
+        }
+        validation_hook::check_all(conn, gate, board_id, actor, checks)?;
+
+        let done_column = current.last().map(|c| c.column_id.as_str());
+        let mut changes: Vec<(&str, &str, &str, usize)> = Vec::new();
+        for (column_id, ids) in &target {
+            let old_ids = current.iter().find(|c| c.column_id == *column_id).map(|c| &c.task_ids);
+            for (pos, task_id) in ids.iter().enumerate() {
+                let from = from_column[task_id];
+                let same_spot = from == *column_id && old_ids.and_then(|o| o.get(pos)).map(String::as_str) == Some(*task_id);
+                if from != *column_id {
+                    let completed = if Some(*column_id) == done_column { "datetime('now')" } else { "NULL" };
+                    conn.execute(
+                        &format!(
+                            "UPDATE tasks SET column_id = ?1, position = ?2, completed_at = {}, updated_at = datetime('now') WHERE id = ?3",
+                            completed
+                        ),
+                        rusqlite::params![column_id, pos as i64, task_id],
+                    )
+                    .map_err(|e| db_error(&e.to_string()))?;
+                } else {
+                    conn.execute(
+                        "UPDATE tasks SET position = ?1 WHERE id = ?2",
+                        rusqlite::params![pos as i64, task_id],
+                    )
+                    .map_err(|e| db_error(&e.to_string()))?;
+                }
+                if !same_spot {
+                    changes.push((task_id, column_id, from, pos));
+                }
+            }
+        }
+
+        // Policies are checked against the final layout
+        let mut violations = Vec::new();
+        let mut checked_columns = HashSet::new();
+        for (task_id, column_id, from, _) in &changes {
+            if from == column_id {
+                continue;
+            }
+            if checked_columns.insert(*column_id) {
+                violations.extend(wip_limit_violation(conn, column_id, Some(task_id))?);
+            }
+            violations.extend(dependency_violation(conn, board_id, task_id, column_id)?);
+        }
+        if !violations.is_empty() {
+            return Err(policy_error(violations));
+        }
+
+        for (task_id, column_id, from, pos) in &changes {
+            let event_data = serde_json::json!({
+                "task_id": task_id,
+                "position": pos,
+                "column_id": column_id,
+                "from_column": from,
+            });
+            log_event(conn, task_id, "reordered", actor, &event_data);
+        }
+        let mut assignments = Vec::new();
+        for (task_id, column_id, _, _) in changes.iter().filter(|(_, to, from, _)| to != from) {
+            assignments.extend(auto_assign::on_enter(conn, task_id, column_id).map_err(|e| db_error(&e.to_string()))?);
+        }
+        for assignment in &assignments {
+            assignment.emit(conn, bus, board_id).map_err(|e| db_error(&e.to_string()))?;
+        }
+        for (task_id, column_id, from, pos) in &changes {
+            bus.emit(
+                conn,
+                crate::events::BoardEvent {
+                    event: event_types::TASK_REORDERED.to_string(),
+                    board_id: board_id.to_string(),
+                    data: serde_json::json!({
+                        "task_id": task_id,
+                        "position": pos,
+                        "column_id": column_id,
+                        "from_column": from,
+                    }),
+                },
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        }
+        let columns = load_layout(conn, board_id)?;
+
+        Ok(Json(BoardLayoutResponse {
+            board_id: board_id.to_string(),
+            version: layout_version(&columns),
+            columns,
+            changed: Some(changes.len()),
+        }))
+    })
+    .await
+}
+
+// ============ Batch Operations ============
+
+/// Batch operations — requires manage key.
+#[post("/boards/<board_id>/tasks/batch", format = "json", data = "<req>")]
+pub async fn batch_tasks(
+    board_id: &str,
+    req: Json<BatchRequest>,
+    token: BoardToken,
+    actor: Actor,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<BatchResponse>, (Status, Json<ApiError>)> {
+    let req = req.into_inner();
+    let (board_id, bus) = (board_id.to_owned(), bus.inner().clone());
+    let client = bus.outbound().clone();
+    validation_hook::run(db, &client, move |conn, gate| {
+        let (board_id, bus) = (board_id.as_str(), &bus);
+        let token_hash = hash_key(&token.0);
+        access::require_role(conn, board_id, &token_hash, BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+        let actor = actor.or_body(req.actor_name.as_deref());
+        access::require_display_name(conn, board_id, &actor)?;
+        let actor = actor.name_or("batch");
+
+        if req.operations.is_empty() {
+            return Err((
+                Status::BadRequest,
+                Json(ApiError {
+                    error: "No operations provided".to_string(),
+                    code: "EMPTY_BATCH".to_string(),
+                    status: 400,
+                }),
+            ));
+        }
+
+        if req.operations.len() > 50 {
+            return Err((
+                Status::BadRequest,
+                Json(ApiError {
+                    error: "Maximum 50 operations per batch request".to_string(),
+                    code: "BATCH_TOO_LARGE".to_string(),
+                    status: 400,
+                }),
+            ));
+        }
+
+        let mut results = Vec::new();
+        let mut succeeded = 0;
+        let mut failed = 0;
+
+        for op in &req.operations {
+            match op {
+                BatchOperation::Move {
+                    task_ids,
+                    column_id,
+                } => {
+                    // A veto fails the whole operation, like any other move error
+                    let result = check_batch_move(conn, gate, board_id, task_ids, column_id, actor)
+                        .map_err(|(_, Json(e))| e.error)
+                        .and_then(|()| batch_move(conn, board_id, task_ids, column_id, actor, bus));
+                    match result {
+                        Ok(affected) => {
+                            succeeded += 1;
+                            results.push(BatchOperationResult {
+                                action: "move".to_string(),
+                                task_ids: task_ids.clone(),
+                                success: true,
+                                error: None,
+                                affected,
+                            });
+                        }
+                        Err(msg) => {
+                            failed += 1;
+                            results.push(BatchOperationResult {
+                                action: "move".to_string(),
+                                task_ids: task_ids.clone(),
+                                success: false,
+                                error: Some(msg),
+                                affected: 0,
+                            });
+                        }
+                    }
+                }
+                BatchOperation::Update { task_ids, fields } => {
+                    let result = batch_update(conn, board_id, task_ids, fields, actor, bus);
+                    match result {
+                        Ok(affected) => {
+                            succeeded += 1;
+                            results.push(BatchOperationResult {
+                                action: "update".to_string(),
+                                task_ids: task_ids.clone(),
+                                success: true,
+                                error: None,
+                                affected,
+                            });
+                        }
+                        Err(msg) => {
+                            failed += 1;
+                            results.push(BatchOperationResult {
+                                action: "update".to_string(),
+                                task_ids: task_ids.clone(),
+                                success: false,
+                                error: Some(msg),
+                                affected: 0,
+                            });
+                        }
+                    }
+                }
+                BatchOperation::Delete { task_ids } => {
+                    let result = batch_delete(conn, board_id, task_ids, actor, bus);
+                    match result {
+                        Ok(affected) => {
+                            succeeded += 1;
+                            results.push(BatchOperationResult {
+                                action: "delete".to_string(),
+                                task_ids: task_ids.clone(),
+                                success: true,
+                                error: None,
+                                affected,
+                            });
+                        }
+                        Err(msg) => {
+                            failed += 1;
+                            results.push(BatchOperationResult {
+                                action: "delete".to_string(),
+                                task_ids: task_ids.clone(),
+                                success: false,
+                                error: Some(msg),
+                                affected: 0,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(Json(BatchResponse {
+            total: req.operations.len(),
+            succeeded,
+            failed,
+            results,
+        }))
+    })
+    .await
+}
+
+/// Ask the validation hook about every task a batch move would take into
+/// `column_id`. Unknown columns are left for `batch_move` to report.
+fn check_batch_move(
+    conn: &Connection,
+    gate: &validation_hook::Gate,
+    board_id: &str,
+    task_ids: &[String],
+    column_id: &str,
+    actor: &str,
+) -> Result<(), (Status, Json<ApiError>)> {
+    let col_exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM columns WHERE id = ?1 AND board_id = ?2",
+            rusqlite::params![column_id, board_id],
+            |row| row.get(0),
+        )
+        .unwrap_or(false);
+    if !col_exists {
+        return Ok(());
+    }
+    let mut checks = Vec::new();
+    for task_id in task_ids {
+        let Ok(task) = load_task_response(conn, task_id) else { continue };
+        if task.board_id == board_id && task.column_id != column_id {
+            checks.push((validation_hook::TASK_MOVE, validation_hook::move_subject(&task, column_id)));
+        }
+    }
+    validation_hook::check_all(conn, gate, board_id, actor, checks)
+}
+
+fn batch_move(
+    conn: &Connection,
+    board_id: &str,
+    task_ids: &[String],
+    column_id: &str,
+    actor: &str,
+    bus: &EventBus,
+) -> Result<usize, String> {
+    let col_exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM columns WHERE id = ?1 AND board_id = ?2",
+            rusqlite::params![column_id, board_id],
+            |row| row.get(0),
+        )
+        .unwrap_or(false);
+
+    if !col_exists {
+        return Err("Target column not found in this board".to_string());
+    }
+
+    let is_done_column: bool = conn
+        .query_row(
+            "SELECT position = (SELECT MAX(position) FROM columns WHERE board_id = ?1) FROM columns WHERE id = ?2",
+            rusqlite::params![board_id, column_id],
+            |row| row.get(0),
+        )
+        .unwrap_or(false);
+
+    let to_col_name: String = conn
+        .query_row("SELECT name FROM columns WHERE id = ?1", rusqlite::params![column_id], |row| row.get(0))
+        .unwrap_or_else(|_| column_id.to_string());
+
+    // Each task is checked against the moves before it, so WIP limits count
+    // the whole operation; any violation undoes the operation's writes
+    conn.execute_batch("SAVEPOINT batch_move").map_err(|e| e.to_string())?;
+    let result = batch_move_tasks(conn, board_id, task_ids, column_id, is_done_column, &to_col_name, actor);
+    let (moves, assignments) = match result {
+        Ok(done) => {
+            conn.execute_batch("RELEASE batch_move").map_err(|e| e.to_string())?;
+            done
+        }
+        Err(msg) => {
+            conn.execute_batch("ROLLBACK TO batch_move; RELEASE batch_move").map_err(|e| e.to_string())?;
+            return Err(msg);
+        }
+    };
+
+    // One aggregated event per operation, so a 50-task move is one delivery
+    let affected = moves.len();
+    if affected > 0 {
+        let task_ids: Vec<&serde_json::Value> = moves.iter().map(|m| &m["task_id"]).collect();
+        bus.emit(
+            conn,
+            crate::events::BoardEvent {
+                event: event_types::TASK_BATCH_MOVED.to_string(),
+                board_id: board_id.to_string(),
+                data: serde_json::json!({
+                    "task_ids": task_ids,
+                    "count": affected,
+                    "to": column_id,
+                    "to_column": to_col_name,
+                    "moves": moves,
+                }),
+            },
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    for assignment in &assignments {
+        assignment.emit(conn, bus, board_id).map_err(|e| e.to_string())?;
+    }
+
+    Ok(affected)
+}
+
+/// Move the tasks of one batch operation, running the move policies for each
+/// task that changes column; the first error or any violation fails the lot.
+fn batch_move_tasks(
+    conn: &Connection,
+    board_id: &str,
+    task_ids: &[String],
+    column_id: &str,
+    is_done_column: bool,
+    to_col_name: &str,
+    actor: &str,
+) -> Result<(Vec<serde_json::Value>, Vec<auto_assign::Assignment>), String> {
+    let mut moves = Vec::new();
+    let mut violations = Vec::new();
+    let mut assignments = Vec::new();
+    for task_id in task_ids {
+        let belongs: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM tasks WHERE id = ?1 AND board_id = ?2",
+                rusqlite::params![task_id, board_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !belongs {
+            continue;
+        }
+
+        let from_col: String = conn
+            .query_row(
+                "SELECT column_id FROM tasks WHERE id = ?1",
+                rusqlite::params![task_id],
+                |row| row.get(0),
+            )
+            .unwrap_or_default();
+        if from_col != column_id {
+            match validate_move(conn, board_id, task_id, column_id) {
+                Ok(()) => {}
+                Err(MoveError::Policy((_, Json(e)))) => {
+                    violations.extend(e.violations);
+                    continue;
+                }
+                Err(MoveError::Api((_, Json(e)))) => return Err(e.error),
+            }
+        }
+
+        let rows = if is_done_column {
+            conn.execute(
+                "UPDATE tasks SET column_id = ?1, completed_at = datetime('now'), updated_at = datetime('now') WHERE id = ?2 AND board_id = ?3",
+                rusqlite::params![column_id, task_id, board_id],
+            )
+            .unwrap_or(0)
+        } else {
+            conn.execute(
+                "UPDATE tasks SET column_id = ?1, completed_at = NULL, updated_at = datetime('now') WHERE id = ?2 AND board_id = ?3",
+                rusqlite::params![column_id, task_id, board_id],
+            )
+            .unwrap_or(0)
+        };
+
+        if rows > 0 {
+            let from_col_name: String = conn
+                .query_row("SELECT name FROM columns WHERE id = ?1", rusqlite::params![from_col], |row| row.get(0))
+                .unwrap_or_else(|_| from_col.clone());
+            let event_data = serde_json::json!({"task_id": task_id, "from": from_col, "to": column_id, "from_column": from_col_name, "to_column": to_col_name, "batch": true});
+            log_event(conn, task_id, "moved", actor, &event_data);
+            moves.push(serde_json::json!({"task_id": task_id, "from": from_col, "from_column": from_col_name}));
+            if from_col != column_id {
+                assignments.extend(auto_assign::on_enter(conn, task_id, column_id).map_err(|e| e.to_string())?);
+            }
+        }
+    }
+
+    if !violations.is_empty() {
+        return Err(policy_violations(violations).error);
+    }
+    Ok((moves, assignments))
 }
 
 fn batch_update(
@@ -1950,7 +4381,8 @@ fn batch_update(
     actor: &str,
     bus: &EventBus,
 ) -> Result<usize, String> {
-    let mut affected = 0;
+    let mut updated: Vec<&str> = Vec::new();
+    let mut applied = serde_json::Map::new();
 
     for task_id in task_ids {
         let belongs: bool = conn
@@ -1961,7 +4393,8 @@ fn batch_update(
             )
             .unwrap_or(false);
 
-        if !belongs {
+        // Tasks someone else holds an edit lock on are skipped, like foreign ones
+        if !belongs || check_edit_lock(conn, task_id, actor).is_err() {
             continue;
         }
 
@@ -2006,19 +4439,29 @@ fn batch_update(
         }
 
         if !changes.is_empty() {
-            affected += 1;
             let event_data = serde_json::Value::Object(changes.clone());
             log_event(conn, task_id, "updated", actor, &event_data);
+            updated.push(task_id);
+            applied = changes;
+        }
+    }
 
-            let mut emit_data = changes;
-            emit_data.insert("task_id".into(), serde_json::json!(task_id));
-            emit_data.insert("batch".into(), serde_json::json!(true));
-            bus.emit(crate::events::BoardEvent {
-                event: "task.updated".to_string(),
+    // Every task gets the same fields, so one event describes the whole operation
+    let affected = updated.len();
+    if affected > 0 {
+        bus.emit(
+            conn,
+            crate::events::BoardEvent {
+                event: event_types::TASK_BATCH_UPDATED.to_string(),
                 board_id: board_id.to_string(),
-                data: serde_json::Value::Object(emit_data),
-            });
-        }
+                data: serde_json::json!({
+                    "task_ids": updated,
+                    "count": affected,
+                    "changes": applied,
+                }),
+            },
+        )
+        .map_err(|e| e.to_string())?;
     }
 
     Ok(affected)
@@ -2034,30 +4477,19 @@ fn batch_delete(
     let mut affected = 0;
 
     for task_id in task_ids {
-        let task_title: Option<String> = conn
-            .query_row(
-                "SELECT title FROM tasks WHERE id = ?1 AND board_id = ?2",
-                rusqlite::params![task_id, board_id],
-                |row| row.get(0),
-            )
-            .ok();
-
-        let rows = conn
-            .execute(
-                "DELETE FROM tasks WHERE id = ?1 AND board_id = ?2",
-                rusqlite::params![task_id, board_id],
-            )
-            .unwrap_or(0);
-
-        if rows > 0 {
+        if let Ok(Some(mut event_data)) = trash::move_to_trash(conn, board_id, task_id, actor) {
             affected += 1;
-            let event_data = serde_json::json!({"task_id": task_id, "title": task_title, "batch": true});
+            event_data["batch"] = serde_json::json!(true);
             log_event(conn, task_id, "deleted", actor, &event_data);
-            bus.emit(crate::events::BoardEvent {
-                event: "task.deleted".to_string(),
-                board_id: board_id.to_string(),
-                data: event_data,
-            });
+            bus.emit(
+                conn,
+                crate::events::BoardEvent {
+                    event: event_types::TASK_DELETED.to_string(),
+                    board_id: board_id.to_string(),
+                    data: event_data,
+                },
+            )
+            .map_err(|e| e.to_string())?;
         }
     }
 
@@ -2068,868 +4500,1781 @@ fn batch_delete(
 
 /// Get board-level activity feed — all events across all tasks, public, no auth required.
 /// Supports cursor pagination via `?after=<seq>` (preferred) or timestamp via `?since=<ISO-8601>` (backward compat).
-/// Use `?mentioned=<name>` to filter for events that @mention the given name.
-#[get("/boards/<board_id>/activity?<since>&<after>&<limit>&<mentioned>")]
-pub fn get_board_activity(
+/// Use `?mentioned=<name>` to filter for events that @mention the given name,
+/// or `?actor=<name>` for events performed by that actor (case-insensitive).
+/// `?watching=<name>` keeps only events on tasks that name watches.
+#[allow(clippy::too_many_arguments)]
+#[get("/boards/<board_id>/activity?<since>&<after>&<limit>&<mentioned>&<actor>&<watching>")]
+pub async fn get_board_activity(
     board_id: &str,
     since: Option<&str>,
     after: Option<i64>,
     limit: Option<u32>,
     mentioned: Option<&str>,
+    actor: Option<&str>,
+    watching: Option<&str>,
+    token: Option<BoardToken>,
     db: &State<DbPool>,
 ) -> Result<Json<Vec<BoardActivityItem>>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
-    access::require_board_exists(&conn, board_id)?;
-
-    let limit = limit.unwrap_or(50).min(200);
-
-    // Prefer `after` (seq cursor) over `since` (timestamp) when both provided
-    let (sql, params): (String, Vec<Box<dyn rusqlite::types::ToSql>>) = if let Some(after_seq) = after {
-        (
-            format!(
-                "SELECT te.id, te.task_id, COALESCE(t.title, '(deleted)'), te.event_type, te.actor, te.data, te.created_at, COALESCE(te.seq, 0)
-                 FROM task_events te
-                 LEFT JOIN tasks t ON t.id = te.task_id
-                 WHERE t.board_id = ?1 AND te.seq > ?2
-                 ORDER BY te.seq ASC
-                 LIMIT ?3"
-            ),
-            vec![
-                Box::new(board_id.to_string()),
-                Box::new(after_seq),
-                Box::new(limit),
-            ],
-        )
-    } else if let Some(since_ts) = since {
-        (
-            format!(
-                "SELECT te.id, te.task_id, COALESCE(t.title, '(deleted)'), te.event_type, te.actor, te.data, te.created_at, COALESCE(te.seq, 0)
-                 FROM task_events te
-                 LEFT JOIN tasks t ON t.id = te.task_id
-                 WHERE t.board_id = ?1 AND te.created_at > ?2
-                 ORDER BY te.created_at DESC
-                 LIMIT ?3"
-            ),
-            vec![
-                Box::new(board_id.to_string()),
-                Box::new(since_ts.to_string()),
-                Box::new(limit),
-            ],
-        )
-    } else {
-        (
-            format!(
-                "SELECT te.id, te.task_id, COALESCE(t.title, '(deleted)'), te.event_type, te.actor, te.data, te.created_at, COALESCE(te.seq, 0)
+    let (actor, board_id, mentioned, since, watching) = (actor.map(str::to_owned), board_id.to_owned(), mentioned.map(str::to_owned), since.map(str::to_owned), watching.map(str::to_owned));
+    with_db(db, move |conn| {
+        let (actor, board_id, mentioned, since, watching) = (actor.as_deref(), board_id.as_str(), mentioned.as_deref(), since.as_deref(), watching.as_deref());
+        access::require_read_access(conn, board_id, token.as_ref())?;
+
+        let limit = limit.unwrap_or(50).min(200);
+
+        // Prefer `after` (seq cursor) over `since` (timestamp) when both provided
+        let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![Box::new(board_id.to_string())];
+        let mut filters = String::new();
+        let order = if let Some(after_seq) = after {
+            params.push(Box::new(after_seq));
+            filters.push_str(&format!(" AND te.seq > ?{}", params.len()));
+            "te.seq ASC"
+        } else if let Some(since_ts) = since {
+            params.push(Box::new(since_ts.to_string()));
+            filters.push_str(&format!(" AND te.created_at > ?{}", params.len()));
+            "te.created_at DESC"
+        } else {
+            "te.created_at DESC"
+        };
+        if let Some(actor) = actor.map(str::trim).filter(|a| !a.is_empty()) {
+            params.push(Box::new(actor.to_string()));
+            filters.push_str(&format!(" AND te.actor = ?{} COLLATE NOCASE", params.len()));
+        }
+        if let Some(watcher) = watching.map(str::trim).filter(|w| !w.is_empty()) {
+            params.push(Box::new(watcher.to_string()));
+            filters.push_str(&format!(
+                " AND te.task_id IN (SELECT task_id FROM task_watchers WHERE board_id = ?1 AND name = ?{})",
+                params.len()
+            ));
+        }
+        params.push(Box::new(limit));
+        let sql = format!(
+            "SELECT te.id, te.task_id, COALESCE(t.title, '(deleted)'), te.event_type, te.actor, te.data, te.created_at, COALESCE(te.seq, 0)
                  FROM task_events te
                  LEFT JOIN tasks t ON t.id = te.task_id
-                 WHERE t.board_id = ?1
-                 ORDER BY te.created_at DESC
-                 LIMIT ?2"
-            ),
-            vec![
-                Box::new(board_id.to_string()),
-                Box::new(limit),
-            ],
-        )
-    };
+                 WHERE t.board_id = ?1{}
+                 ORDER BY {}
+                 LIMIT ?{}",
+            filters,
+            order,
+            params.len()
+        );
 
-    let mut stmt = conn.prepare(&sql).map_err(|e| db_error(&e.to_string()))?;
+        let mut stmt = conn.prepare(&sql).map_err(|e| db_error(&e.to_string()))?;
 
-    let mut items: Vec<BoardActivityItem> = stmt
-        .query_map(rusqlite::params_from_iter(params.iter()), |row| {
-            let data_str: String = row.get(5)?;
-            let data: serde_json::Value = serde_json::from_str(&data_str).unwrap_or(serde_json::json!({}));
-            let mentions = data.get("mentions")
-                .and_then(|v| v.as_array())
-                .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect());
-            Ok(BoardActivityItem {
-                id: row.get(0)?,
-                task_id: row.get(1)?,
-                task_title: row.get(2)?,
-                event_type: row.get(3)?,
-                actor: row.get(4)?,
-                data,
-                created_at: row.get(6)?,
-                seq: row.get(7)?,
-                task: None,
-                recent_comments: None,
-                mentions,
+        let mut items: Vec<BoardActivityItem> = stmt
+            .query_map(rusqlite::params_from_iter(params.iter()), |row| {
+                let data_str: String = row.get(5)?;
+                let data: serde_json::Value = serde_json::from_str(&data_str).unwrap_or(serde_json::json!({}));
+                let mentions = data.get("mentions")
+                    .and_then(|v| v.as_array())
+                    .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect());
+                Ok(BoardActivityItem {
+                    id: row.get(0)?,
+                    task_id: row.get(1)?,
+                    task_title: row.get(2)?,
+                    event_type: row.get(3)?,
+                    actor: row.get(4)?,
+                    data,
+                    created_at: row.get(6)?,
+                    seq: row.get(7)?,
+                    task: None,
+                    recent_comments: None,
+                    mentions,
+                })
             })
-        })
-        .map_err(|e| db_error(&e.to_string()))?
-        .filter_map(|r| r.ok())
-        .collect();
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
 
-    // Filter by @mention if requested
-    if let Some(mention_name) = mentioned {
-        let mention_lower = mention_name.to_lowercase();
-        items.retain(|item| {
-            // Match if mentioned in comment data.mentions array
-            if let Some(ref mentions) = item.mentions {
-                if mentions.iter().any(|m| m.to_lowercase() == mention_lower) {
+        // Filter by @mention if requested
+        if let Some(mention_name) = mentioned {
+            let mention_lower = mention_name.to_lowercase();
+            items.retain(|item| {
+                // Match if mentioned in comment data.mentions array
+                if let Some(ref mentions) = item.mentions {
+                    if mentions.iter().any(|m| m.to_lowercase() == mention_lower) {
+                        return true;
+                    }
+                }
+                // Also match if assigned_to matches (for "my items" filtering)
+                if item.actor.to_lowercase() == mention_lower {
                     return true;
                 }
+                false
+            });
+        }
+
+        // Enrich created/comment events with task snapshot and recent comments.
+        // Collect unique task IDs that need enrichment.
+        let enrich_task_ids: Vec<String> = items
+            .iter()
+            .filter(|i| i.event_type == "created" || i.event_type == "comment")
+            .map(|i| i.task_id.clone())
+            .collect::<std::collections::HashSet<_>>()
+            .into_iter()
+            .collect();
+
+        if !enrich_task_ids.is_empty() {
+            // Batch-fetch task snapshots
+            let placeholders: String = enrich_task_ids
+                .iter()
+                .enumerate()
+                .map(|(i, _)| format!("?{}", i + 1))
+                .collect::<Vec<_>>()
+                .join(",");
+
+            let task_sql = format!(
+                "SELECT {TASK_COLUMNS}
+                 FROM tasks t
+                 JOIN columns c ON t.column_id = c.id
+                 WHERE t.id IN ({})",
+                placeholders
+            );
+
+            let task_params: Vec<Box<dyn rusqlite::types::ToSql>> = enrich_task_ids
+                .iter()
+                .map(|id| Box::new(id.clone()) as Box<dyn rusqlite::types::ToSql>)
+                .collect();
+            let task_param_refs: Vec<&dyn rusqlite::types::ToSql> =
+                task_params.iter().map(|p| p.as_ref()).collect();
+
+            let mut task_stmt = conn.prepare(&task_sql).map_err(|e| db_error(&e.to_string()))?;
+            let task_map: std::collections::HashMap<String, TaskResponse> = task_stmt
+                .query_map(task_param_refs.as_slice(), task_from_row)
+                .map_err(|e| db_error(&e.to_string()))?
+                .filter_map(|r| r.ok())
+                .map(|t| (t.id.clone(), t))
+                .collect();
+
+            // Batch-fetch recent comments for comment-event task IDs
+            let comment_task_ids: Vec<String> = items
+                .iter()
+                .filter(|i| i.event_type == "comment")
+                .map(|i| i.task_id.clone())
+                .collect::<std::collections::HashSet<_>>()
+                .into_iter()
+                .collect();
+
+            let mut comments_map: std::collections::HashMap<String, Vec<CommentSnapshot>> =
+                std::collections::HashMap::new();
+
+            for tid in &comment_task_ids {
+                let mut cmt_stmt = conn
+                    .prepare(
+                        "SELECT id, actor, data, created_at FROM task_events
+                         WHERE task_id = ?1 AND event_type = 'comment'
+                         ORDER BY created_at DESC LIMIT 10",
+                    )
+                    .map_err(|e| db_error(&e.to_string()))?;
+
+                let cmts: Vec<CommentSnapshot> = cmt_stmt
+                    .query_map(rusqlite::params![tid], |row| {
+                        let data_str: String = row.get(2)?;
+                        let data_val: serde_json::Value =
+                            serde_json::from_str(&data_str).unwrap_or(serde_json::json!({}));
+                        let message = data_val
+                            .get("message")
+                            .and_then(|m| m.as_str())
+                            .unwrap_or("")
+                            .to_string();
+                        let attachments = data_val
+                            .get("attachments")
+                            .and_then(|a| serde_json::from_value(a.clone()).ok())
+                            .unwrap_or_default();
+                        Ok(CommentSnapshot {
+                            id: row.get(0)?,
+                            actor: row.get(1)?,
+                            message,
+                            created_at: row.get(3)?,
+                            attachments,
+                            edited_at: data_val.get("edited_at").and_then(|t| t.as_str()).map(String::from),
+                        })
+                    })
+                    .map_err(|e| db_error(&e.to_string()))?
+                    .filter_map(|r| r.ok())
+                    .collect();
+
+                comments_map.insert(tid.clone(), cmts);
             }
-            // Also match if assigned_to matches (for "my items" filtering)
-            if item.actor.to_lowercase() == mention_lower {
-                return true;
+
+            // Apply enrichment to items
+            for item in &mut items {
+                if item.event_type == "created" || item.event_type == "comment" {
+                    item.task = task_map.get(&item.task_id).cloned();
+                }
+                if item.event_type == "comment" {
+                    item.recent_comments = comments_map.remove(&item.task_id).or(Some(vec![]));
+                }
             }
-            false
-        });
-    }
+        }
 
-    // Enrich created/comment events with task snapshot and recent comments.
-    // Collect unique task IDs that need enrichment.
-    let enrich_task_ids: Vec<String> = items
-        .iter()
-        .filter(|i| i.event_type == "created" || i.event_type == "comment")
-        .map(|i| i.task_id.clone())
-        .collect::<std::collections::HashSet<_>>()
-        .into_iter()
-        .collect();
+        Ok(Json(items))
+    })
+    .await
+}
 
-    if !enrich_task_ids.is_empty() {
-        // Batch-fetch task snapshots
-        let placeholders: String = enrich_task_ids
-            .iter()
-            .enumerate()
-            .map(|(i, _)| format!("?{}", i + 1))
-            .collect::<Vec<_>>()
-            .join(",");
-
-        let task_sql = format!(
-            "SELECT t.id, t.board_id, t.column_id, c.name, t.title, t.description,
-                    t.priority, t.position, t.created_by, t.assigned_to, t.claimed_by,
-                    t.claimed_at, t.labels, t.metadata, t.due_at, t.completed_at, t.archived_at,
-                    t.created_at, t.updated_at,
-                    (SELECT COUNT(*) FROM task_events te WHERE te.task_id = t.id AND te.event_type = 'comment') as comment_count
-             FROM tasks t
-             JOIN columns c ON t.column_id = c.id
-             WHERE t.id IN ({})",
-            placeholders
-        );
+// ============ Task Events ============
 
-        let task_params: Vec<Box<dyn rusqlite::types::ToSql>> = enrich_task_ids
-            .iter()
-            .map(|id| Box::new(id.clone()) as Box<dyn rusqlite::types::ToSql>)
-            .collect();
-        let task_param_refs: Vec<&dyn rusqlite::types::ToSql> =
-            task_params.iter().map(|p| p.as_ref()).collect();
+/// Get task events — public, no auth required.
+/// `?actor=<name>` narrows the log to one actor (case-insensitive).
+#[get("/boards/<board_id>/tasks/<task_id>/events?<actor>")]
+pub async fn get_task_events(
+    board_id: &str,
+    task_id: &str,
+    actor: Option<&str>,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+) -> Result<Json<Vec<TaskEventResponse>>, (Status, Json<ApiError>)> {
+    let (actor, board_id, task_id) = (actor.map(str::to_owned), board_id.to_owned(), task_id.to_owned());
+    with_db(db, move |conn| {
+        let (actor, board_id, task_id) = (actor.as_deref(), board_id.as_str(), task_id.as_str());
+        access::require_read_access(conn, board_id, token.as_ref())?;
+
+        let actor = actor.map(str::trim).filter(|a| !a.is_empty());
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, event_type, actor, data, created_at
+                 FROM task_events WHERE task_id = ?1
+                   AND (?2 IS NULL OR actor = ?2 COLLATE NOCASE)
+                 ORDER BY created_at ASC",
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
 
-        let mut task_stmt = conn.prepare(&task_sql).map_err(|e| db_error(&e.to_string()))?;
-        let task_map: std::collections::HashMap<String, TaskResponse> = task_stmt
-            .query_map(task_param_refs.as_slice(), row_to_task)
+        let events = stmt
+            .query_map(rusqlite::params![task_id, actor], |row| {
+                let data_str: String = row.get(3)?;
+                Ok(TaskEventResponse {
+                    id: row.get(0)?,
+                    event_type: row.get(1)?,
+                    actor: row.get(2)?,
+                    data: serde_json::from_str(&data_str).unwrap_or(serde_json::json!({})),
+                    created_at: row.get(4)?,
+                    comment_pressure: None,
+                })
+            })
             .map_err(|e| db_error(&e.to_string()))?
             .filter_map(|r| r.ok())
-            .map(|t| (t.id.clone(), t))
             .collect();
 
-        // Batch-fetch recent comments for comment-event task IDs
-        let comment_task_ids: Vec<String> = items
-            .iter()
-            .filter(|i| i.event_type == "comment")
-            .map(|i| i.task_id.clone())
-            .collect::<std::collections::HashSet<_>>()
-            .into_iter()
+        Ok(Json(events))
+    })
+    .await
+}
+
+/// Post a comment on a task — requires manage key.
+#[post(
+    "/boards/<board_id>/tasks/<task_id>/comment",
+    format = "json",
+    data = "<body>"
+)]
+pub async fn comment_on_task(
+    board_id: &str,
+    task_id: &str,
+    body: Json<serde_json::Value>,
+    token: BoardToken,
+    actor: Actor,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<TaskEventResponse>, (Status, Json<ApiError>)> {
+    let (board_id, bus, task_id) = (board_id.to_owned(), bus.inner().clone(), task_id.to_owned());
+    with_tx(db, move |conn| {
+        let (board_id, bus, task_id) = (board_id.as_str(), &bus, task_id.as_str());
+        if let Some(scope) = access::require_action(conn, board_id, &token.0, ScopeAction::Comment)? {
+            scope.require_task(conn, task_id)?;
+        }
+        let on_board: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM tasks WHERE id = ?1 AND board_id = ?2",
+                rusqlite::params![task_id, board_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+        if !on_board {
+            return Err(not_found("Task"));
+        }
+
+        let actor = actor.or_body(body.get("actor_name").and_then(|v| v.as_str()));
+        access::require_display_name(conn, board_id, &actor)?;
+        let actor = actor.name().to_string();
+
+        let message = body.get("message").and_then(|v| v.as_str()).unwrap_or("");
+        let attachment_ids: Vec<String> = match body.get("attachment_ids") {
+            None | Some(serde_json::Value::Null) => Vec::new(),
+            Some(ids) => serde_json::from_value(ids.clone()).map_err(|_| {
+                (
+                    Status::BadRequest,
+                    Json(ApiError {
+                        error: "attachment_ids must be an array of attachment ids".to_string(),
+                        code: "INVALID_ATTACHMENT".to_string(),
+                        status: 400,
+                    }),
+                )
+            })?,
+        };
+        let attachments = attachments::for_comment(conn, board_id, task_id, &attachment_ids)?;
+
+        // A comment may be just a screenshot, but never empty
+        if message.is_empty() && attachments.is_empty() {
+            return Err((
+                Status::BadRequest,
+                Json(ApiError {
+                    error: "Comment message cannot be empty".to_string(),
+                    code: "EMPTY_MESSAGE".to_string(),
+                    status: 400,
+                }),
+            ));
+        }
+
+        let event_id = uuid::Uuid::new_v4().to_string();
+        let mentions = extract_mentions(message);
+        let mut data = if mentions.is_empty() {
+            serde_json::json!({"message": message, "actor": actor})
+        } else {
+            serde_json::json!({"message": message, "actor": actor, "mentions": mentions})
+        };
+        if !attachments.is_empty() {
+            data["attachments"] = serde_json::json!(attachments);
+        }
+        let data_str = serde_json::to_string(&data).unwrap();
+
+        conn.execute(
+            &format!(
+                "INSERT INTO task_events (id, task_id, event_type, actor, data, seq) VALUES (?1, ?2, 'comment', ?3, ?4, {})",
+                NEXT_EVENT_SEQ
+            ),
+            rusqlite::params![event_id, task_id, actor, data_str],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+
+        let created_at: String = conn
+            .query_row(
+                "SELECT created_at FROM task_events WHERE id = ?1",
+                rusqlite::params![event_id],
+                |row| row.get(0),
+            )
+            .unwrap_or_else(|_| chrono::Utc::now().to_rfc3339());
+
+        bus.emit(
+            conn,
+            crate::events::BoardEvent {
+                event: event_types::TASK_COMMENT.to_string(),
+                board_id: board_id.to_string(),
+                data: serde_json::json!({
+                    "task_id": task_id,
+                    "actor": &actor,
+                    "message": message,
+                    "mentions": &mentions,
+                    "attachments": &attachments,
+                }),
+            },
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        let comment_pressure =
+            comment_pressure::after_comment(conn, bus, board_id, task_id).map_err(|e| db_error(&e.to_string()))?;
+
+        Ok(Json(TaskEventResponse {
+            id: event_id,
+            event_type: "comment".to_string(),
+            actor,
+            data,
+            created_at,
+            comment_pressure,
+        }))
+    })
+    .await
+}
+
+// ============ Webhooks ============
+
+/// Create a webhook — requires manage key.
+#[post("/boards/<board_id>/webhooks", format = "json", data = "<req>")]
+pub async fn create_webhook(
+    board_id: &str,
+    req: Json<CreateWebhookRequest>,
+    token: BoardToken,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<WebhookResponse>, (Status, Json<ApiError>)> {
+    let req = req.into_inner();
+    let (board_id, bus) = (board_id.to_owned(), bus.inner().clone());
+    with_db(db, move |conn| {
+        let (board_id, bus) = (board_id.as_str(), &bus);
+        let token_hash = hash_key(&token.0);
+        access::require_manage_key(conn, board_id, &token_hash)?;
+
+        if req.url.trim().is_empty() {
+            return Err((
+                Status::BadRequest,
+                Json(ApiError {
+                    error: "Webhook URL cannot be empty".to_string(),
+                    code: "EMPTY_URL".to_string(),
+                    status: 400,
+                }),
+            ));
+        }
+
+        bus.outbound().policy.check(&req.url).map_err(invalid_url)?;
+        event_types::validate(&req.events).map_err(invalid_event_type)?;
+        let kind = webhook_kind(req.kind.as_deref().unwrap_or(webhooks::KIND_JSON))?;
+        if let Some(ref template) = req.template {
+            webhook_template::validate(template).map_err(invalid_template)?;
+        }
+        let headers = req.headers.clone().unwrap_or_default();
+        webhooks::validate_headers(&headers).map_err(invalid_header)?;
+        let sealed_headers = webhooks::seal_headers(conn, &headers).map_err(|e| db_error(&e.to_string()))?;
+        let filters = webhook_filters(conn, board_id, req.filters.clone().unwrap_or_default())?;
+
+        let webhook_id = uuid::Uuid::new_v4().to_string();
+        let secret = format!(
+            "whsec_{}",
+            uuid::Uuid::new_v4().to_string().replace('-', "")
+        );
+        let events_json = serde_json::to_string(&req.events).unwrap_or_else(|_| "[]".to_string());
+
+        conn.execute(
+            "INSERT INTO webhooks (id, board_id, url, secret, events, kind, template, headers, filters)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            rusqlite::params![
+                webhook_id,
+                board_id,
+                req.url.trim(),
+                secret,
+                events_json,
+                kind,
+                req.template.as_ref().map(|t| t.to_string()),
+                sealed_headers,
+                stored_filters(&filters)
+            ],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+
+        Ok(Json(WebhookResponse {
+            id: webhook_id,
+            board_id: board_id.to_string(),
+            url: req.url,
+            kind,
+            secret: Some(secret),
+            events: req.events,
+            template: req.template,
+            header_names: headers.into_keys().collect(),
+            filters,
+            active: true,
+            failure_count: 0,
+            last_triggered_at: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }))
+    })
+    .await
+}
+
+/// List webhooks — requires manage key.
+#[get("/boards/<board_id>/webhooks")]
+pub async fn list_webhooks(
+    board_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<Vec<WebhookResponse>>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        let token_hash = hash_key(&token.0);
+        access::require_manage_key(conn, board_id, &token_hash)?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, board_id, url, events, active, failure_count, last_triggered_at, created_at, kind, template, headers, filters
+                 FROM webhooks WHERE board_id = ?1
+                 ORDER BY created_at ASC",
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+
+        let webhooks: Vec<WebhookResponse> = stmt
+            .query_map(rusqlite::params![board_id], |row| {
+                let events_str: String = row.get(3)?;
+                let events: Vec<String> = serde_json::from_str(&events_str).unwrap_or_default();
+                Ok(WebhookResponse {
+                    id: row.get(0)?,
+                    board_id: row.get(1)?,
+                    url: telegram::redact_url(&row.get::<_, String>(2)?),
+                    kind: row.get(8)?,
+                    secret: None,
+                    events,
+                    template: row.get::<_, Option<String>>(9)?.and_then(|t| serde_json::from_str(&t).ok()),
+                    header_names: webhooks::header_names(row.get::<_, Option<String>>(10)?.as_deref()),
+                    filters: row.get::<_, Option<String>>(11)?.and_then(|f| serde_json::from_str(&f).ok()).unwrap_or_default(),
+                    active: row.get::<_, i32>(4)? == 1,
+                    failure_count: row.get(5)?,
+                    last_triggered_at: row.get(6)?,
+                    created_at: row.get(7)?,
+                })
+            })
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
             .collect();
 
-        let mut comments_map: std::collections::HashMap<String, Vec<CommentSnapshot>> =
-            std::collections::HashMap::new();
+        Ok(Json(webhooks))
+    })
+    .await
+}
+
+/// Update a webhook — requires manage key.
+#[patch(
+    "/boards/<board_id>/webhooks/<webhook_id>",
+    format = "json",
+    data = "<req>"
+)]
+pub async fn update_webhook(
+    board_id: &str,
+    webhook_id: &str,
+    req: Json<UpdateWebhookRequest>,
+    token: BoardToken,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<WebhookResponse>, (Status, Json<ApiError>)> {
+    let req = req.into_inner();
+    let (board_id, bus, webhook_id) = (board_id.to_owned(), bus.inner().clone(), webhook_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, bus, webhook_id) = (board_id.as_str(), &bus, webhook_id.as_str());
+        let token_hash = hash_key(&token.0);
+        access::require_manage_key(conn, board_id, &token_hash)?;
+
+        let exists: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM webhooks WHERE id = ?1 AND board_id = ?2",
+                rusqlite::params![webhook_id, board_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !exists {
+            return Err(not_found("Webhook"));
+        }
+
+        if let Some(ref url) = req.url {
+            if url.trim().is_empty() {
+                return Err((
+                    Status::BadRequest,
+                    Json(ApiError {
+                        error: "Webhook URL cannot be empty".to_string(),
+                        code: "EMPTY_URL".to_string(),
+                        status: 400,
+                    }),
+                ));
+            }
+            bus.outbound().policy.check(url).map_err(invalid_url)?;
+            conn.execute(
+                "UPDATE webhooks SET url = ?1 WHERE id = ?2",
+                rusqlite::params![url.trim(), webhook_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        }
+
+        if let Some(ref events) = req.events {
+            event_types::validate(events).map_err(invalid_event_type)?;
+            let events_json = serde_json::to_string(events).unwrap_or_else(|_| "[]".to_string());
+            conn.execute(
+                "UPDATE webhooks SET events = ?1 WHERE id = ?2",
+                rusqlite::params![events_json, webhook_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        }
+
+        if let Some(ref kind) = req.kind {
+            let kind = webhook_kind(kind)?;
+            conn.execute(
+                "UPDATE webhooks SET kind = ?1 WHERE id = ?2",
+                rusqlite::params![kind, webhook_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        }
+
+        if let Some(ref template) = req.template {
+            if !template.is_null() {
+                webhook_template::validate(template).map_err(invalid_template)?;
+            }
+            conn.execute(
+                "UPDATE webhooks SET template = ?1 WHERE id = ?2",
+                rusqlite::params![(!template.is_null()).then(|| template.to_string()), webhook_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        }
+
+        if let Some(ref headers) = req.headers {
+            webhooks::validate_headers(headers).map_err(invalid_header)?;
+            let sealed = webhooks::seal_headers(conn, headers).map_err(|e| db_error(&e.to_string()))?;
+            conn.execute(
+                "UPDATE webhooks SET headers = ?1 WHERE id = ?2",
+                rusqlite::params![sealed, webhook_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        }
+
+        if let Some(ref filters) = req.filters {
+            let filters = webhook_filters(conn, board_id, filters.clone())?;
+            conn.execute(
+                "UPDATE webhooks SET filters = ?1 WHERE id = ?2",
+                rusqlite::params![stored_filters(&filters), webhook_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        }
 
-        for tid in &comment_task_ids {
-            let mut cmt_stmt = conn
-                .prepare(
-                    "SELECT id, actor, data, created_at FROM task_events
-                     WHERE task_id = ?1 AND event_type = 'comment'
-                     ORDER BY created_at DESC LIMIT 10",
+        if let Some(active) = req.active {
+            let active_int: i32 = if active { 1 } else { 0 };
+            if active {
+                conn.execute(
+                    "UPDATE webhooks SET active = ?1, failure_count = 0 WHERE id = ?2",
+                    rusqlite::params![active_int, webhook_id],
+                )
+                .map_err(|e| db_error(&e.to_string()))?;
+            } else {
+                conn.execute(
+                    "UPDATE webhooks SET active = ?1 WHERE id = ?2",
+                    rusqlite::params![active_int, webhook_id],
                 )
                 .map_err(|e| db_error(&e.to_string()))?;
+            }
+        }
 
-            let cmts: Vec<CommentSnapshot> = cmt_stmt
-                .query_map(rusqlite::params![tid], |row| {
-                    let data_str: String = row.get(2)?;
-                    let data_val: serde_json::Value =
-                        serde_json::from_str(&data_str).unwrap_or(serde_json::json!({}));
-                    let message = data_val
-                        .get("message")
-                        .and_then(|m| m.as_str())
-                        .unwrap_or("")
-                        .to_string();
-                    Ok(CommentSnapshot {
+        let wh = conn
+            .query_row(
+                "SELECT id, board_id, url, events, active, failure_count, last_triggered_at, created_at, kind, template, headers, filters
+                 FROM webhooks WHERE id = ?1",
+                rusqlite::params![webhook_id],
+                |row| {
+                    let events_str: String = row.get(3)?;
+                    let events: Vec<String> = serde_json::from_str(&events_str).unwrap_or_default();
+                    Ok(WebhookResponse {
                         id: row.get(0)?,
-                        actor: row.get(1)?,
-                        message,
-                        created_at: row.get(3)?,
+                        board_id: row.get(1)?,
+                        url: telegram::redact_url(&row.get::<_, String>(2)?),
+                        kind: row.get(8)?,
+                        secret: None,
+                        events,
+                        template: row.get::<_, Option<String>>(9)?.and_then(|t| serde_json::from_str(&t).ok()),
+                        header_names: webhooks::header_names(row.get::<_, Option<String>>(10)?.as_deref()),
+                        filters: row.get::<_, Option<String>>(11)?.and_then(|f| serde_json::from_str(&f).ok()).unwrap_or_default(),
+                        active: row.get::<_, i32>(4)? == 1,
+                        failure_count: row.get(5)?,
+                        last_triggered_at: row.get(6)?,
+                        created_at: row.get(7)?,
                     })
-                })
-                .map_err(|e| db_error(&e.to_string()))?
-                .filter_map(|r| r.ok())
-                .collect();
+                },
+            )
+            .map_err(|_| not_found("Webhook"))?;
 
-            comments_map.insert(tid.clone(), cmts);
-        }
+        Ok(Json(wh))
+    })
+    .await
+}
 
-        // Apply enrichment to items
-        for item in &mut items {
-            if item.event_type == "created" || item.event_type == "comment" {
-                item.task = task_map.get(&item.task_id).cloned();
-            }
-            if item.event_type == "comment" {
-                item.recent_comments = comments_map.remove(&item.task_id).or(Some(vec![]));
-            }
-        }
-    }
+/// Delete a webhook — requires manage key.
+#[delete("/boards/<board_id>/webhooks/<webhook_id>")]
+pub async fn delete_webhook(
+    board_id: &str,
+    webhook_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
+    let (board_id, webhook_id) = (board_id.to_owned(), webhook_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, webhook_id) = (board_id.as_str(), webhook_id.as_str());
+        let token_hash = hash_key(&token.0);
+        access::require_manage_key(conn, board_id, &token_hash)?;
+
+        let affected = conn
+            .execute(
+                "DELETE FROM webhooks WHERE id = ?1 AND board_id = ?2",
+                rusqlite::params![webhook_id, board_id],
+            )
+            .unwrap_or(0);
 
-    Ok(Json(items))
+        if affected > 0 {
+            let _ = conn.execute(
+                "DELETE FROM webhook_dead_letters WHERE webhook_id = ?1",
+                rusqlite::params![webhook_id],
+            );
+            let _ = conn.execute("DELETE FROM webhook_deliveries WHERE webhook_id = ?1", rusqlite::params![webhook_id]);
+            let _ = conn.execute("DELETE FROM webhook_outbox WHERE webhook_id = ?1", rusqlite::params![webhook_id]);
+            let _ = conn.execute("DELETE FROM webhook_routes WHERE webhook_id = ?1", rusqlite::params![webhook_id]);
+            let _ = conn.execute(
+                "DELETE FROM webhook_signing_keys WHERE webhook_id = ?1",
+                rusqlite::params![webhook_id],
+            );
+            Ok(Json(serde_json::json!({"deleted": true, "id": webhook_id})))
+        } else {
+            Err(not_found("Webhook"))
+        }
+    })
+    .await
 }
 
-// ============ Task Events ============
+/// List a webhook's dead letters (newest first) — requires manage key.
+#[get("/boards/<board_id>/webhooks/<webhook_id>/dead-letters?<limit>")]
+pub async fn list_dead_letters(
+    board_id: &str,
+    webhook_id: &str,
+    limit: Option<i64>,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<Vec<DeadLetterResponse>>, (Status, Json<ApiError>)> {
+    let (board_id, webhook_id) = (board_id.to_owned(), webhook_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, webhook_id) = (board_id.as_str(), webhook_id.as_str());
+        let token_hash = hash_key(&token.0);
+        access::require_manage_key(conn, board_id, &token_hash)?;
+        require_webhook_on_board(conn, board_id, webhook_id)?;
+        load_dead_letters(conn, board_id, Some(webhook_id), limit).map(Json)
+    })
+    .await
+}
 
-/// Get task events — public, no auth required.
-#[get("/boards/<board_id>/tasks/<task_id>/events")]
-pub fn get_task_events(
+/// List dead letters across all of a board's webhooks (newest first),
+/// optionally for one webhook — requires manage key.
+#[get("/boards/<board_id>/webhooks/dead-letters?<limit>&<webhook_id>")]
+pub async fn list_board_dead_letters(
     board_id: &str,
-    task_id: &str,
+    limit: Option<i64>,
+    webhook_id: Option<&str>,
+    token: BoardToken,
     db: &State<DbPool>,
-) -> Result<Json<Vec<TaskEventResponse>>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
-    access::require_board_exists(&conn, board_id)?;
+) -> Result<Json<Vec<DeadLetterResponse>>, (Status, Json<ApiError>)> {
+    let (board_id, webhook_id) = (board_id.to_owned(), webhook_id.map(str::to_owned));
+    with_db(db, move |conn| {
+        let (board_id, webhook_id) = (board_id.as_str(), webhook_id.as_deref());
+        let token_hash = hash_key(&token.0);
+        access::require_manage_key(conn, board_id, &token_hash)?;
+        if let Some(webhook_id) = webhook_id {
+            require_webhook_on_board(conn, board_id, webhook_id)?;
+        }
+        load_dead_letters(conn, board_id, webhook_id, limit).map(Json)
+    })
+    .await
+}
 
+fn load_dead_letters(
+    conn: &Connection,
+    board_id: &str,
+    webhook_id: Option<&str>,
+    limit: Option<i64>,
+) -> Result<Vec<DeadLetterResponse>, (Status, Json<ApiError>)> {
+    let limit = limit.unwrap_or(100).clamp(1, 1000);
     let mut stmt = conn
         .prepare(
-            "SELECT id, event_type, actor, data, created_at
-             FROM task_events WHERE task_id = ?1
-             ORDER BY created_at ASC",
+            "SELECT id, webhook_id, event, payload, attempts, last_error, created_at
+             FROM webhook_dead_letters WHERE board_id = ?1 AND (?2 IS NULL OR webhook_id = ?2)
+             ORDER BY created_at DESC, rowid DESC LIMIT ?3",
         )
         .map_err(|e| db_error(&e.to_string()))?;
-
-    let events = stmt
-        .query_map(rusqlite::params![task_id], |row| {
-            let data_str: String = row.get(3)?;
-            Ok(TaskEventResponse {
+    let letters = stmt
+        .query_map(rusqlite::params![board_id, webhook_id, limit], |row| {
+            let payload: String = row.get(3)?;
+            Ok(DeadLetterResponse {
                 id: row.get(0)?,
-                event_type: row.get(1)?,
-                actor: row.get(2)?,
-                data: serde_json::from_str(&data_str).unwrap_or(serde_json::json!({})),
-                created_at: row.get(4)?,
+                webhook_id: row.get(1)?,
+                event: row.get(2)?,
+                payload: serde_json::from_str(&payload).unwrap_or(serde_json::Value::Null),
+                attempts: row.get(4)?,
+                last_error: row.get(5)?,
+                created_at: row.get(6)?,
             })
         })
         .map_err(|e| db_error(&e.to_string()))?
         .filter_map(|r| r.ok())
         .collect();
-
-    Ok(Json(events))
+    Ok(letters)
 }
 
-/// Post a comment on a task — requires manage key.
-#[post(
-    "/boards/<board_id>/tasks/<task_id>/comment",
-    format = "json",
-    data = "<body>"
-)]
-pub fn comment_on_task(
+/// Move dead letters back into the outbox for the dispatcher to send, with
+/// fresh retries — requires manage key. Their webhooks' failure counts are
+/// reset so new events flow again. The body is optional: no `ids` requeues
+/// every letter on the board (or on `webhook_id`).
+#[post("/boards/<board_id>/webhooks/dead-letters/requeue", data = "<req>")]
+pub async fn requeue_dead_letters(
     board_id: &str,
-    task_id: &str,
-    body: Json<serde_json::Value>,
+    req: Option<Json<RequeueRequest>>,
     token: BoardToken,
     db: &State<DbPool>,
     bus: &State<EventBus>,
-) -> Result<Json<TaskEventResponse>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
-
-    let actor = body
-        .get("actor_name")
-        .and_then(|v| v.as_str())
-        .unwrap_or("anonymous")
-        .to_string();
-
-    // Check display name requirement
-    access::require_display_name_if_needed(&conn, board_id, &actor)?;
+) -> Result<Json<RequeueReport>, (Status, Json<ApiError>)> {
+    let req = req.map(|r| r.into_inner()).unwrap_or_default();
+    let board_id = board_id.to_owned();
+    let report = with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        let token_hash = hash_key(&token.0);
+        access::require_manage_key(conn, board_id, &token_hash)?;
+        if let Some(ref webhook_id) = req.webhook_id {
+            require_webhook_on_board(conn, board_id, webhook_id)?;
+        }
 
-    let message = body.get("message").and_then(|v| v.as_str()).unwrap_or("");
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, webhook_id FROM webhook_dead_letters
+                 WHERE board_id = ?1 AND (?2 IS NULL OR webhook_id = ?2)
+                 ORDER BY created_at ASC, rowid ASC",
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        let letters: Vec<(String, String)> = stmt
+            .query_map(rusqlite::params![board_id, req.webhook_id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .filter(|(id, _)| req.ids.as_ref().is_none_or(|ids| ids.contains(id)))
+            .collect();
 
-    if message.is_empty() {
-        return Err((
-            Status::BadRequest,
-            Json(ApiError {
-                error: "Comment message cannot be empty".to_string(),
-                code: "EMPTY_MESSAGE".to_string(),
-                status: 400,
-            }),
-        ));
+        let now = chrono::Utc::now().timestamp_millis();
+        let mut webhook_ids: Vec<String> = Vec::new();
+        for (id, webhook_id) in &letters {
+            conn.execute(
+                "INSERT INTO webhook_outbox (id, webhook_id, board_id, event, payload, next_attempt_at)
+                 SELECT ?1, webhook_id, board_id, event, payload, ?2 FROM webhook_dead_letters WHERE id = ?3",
+                rusqlite::params![uuid::Uuid::new_v4().to_string(), now, id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+            conn.execute("DELETE FROM webhook_dead_letters WHERE id = ?1", rusqlite::params![id])
+                .map_err(|e| db_error(&e.to_string()))?;
+            if !webhook_ids.contains(webhook_id) {
+                webhook_ids.push(webhook_id.clone());
+            }
+        }
+        for webhook_id in &webhook_ids {
+            conn.execute("UPDATE webhooks SET failure_count = 0 WHERE id = ?1", rusqlite::params![webhook_id])
+                .map_err(|e| db_error(&e.to_string()))?;
+        }
+        let remaining: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM webhook_dead_letters WHERE board_id = ?1",
+                rusqlite::params![board_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        Ok(RequeueReport {
+            requeued: letters.len(),
+            webhook_ids,
+            remaining,
+        })
+    })
+    .await?;
+    if report.requeued > 0 {
+        bus.wake_webhook_dispatcher();
     }
+    Ok(Json(report))
+}
 
-    let event_id = uuid::Uuid::new_v4().to_string();
-    let mentions = extract_mentions(message);
-    let data = if mentions.is_empty() {
-        serde_json::json!({"message": message, "actor": actor})
-    } else {
-        serde_json::json!({"message": message, "actor": actor, "mentions": mentions})
-    };
-    let data_str = serde_json::to_string(&data).unwrap();
-    let seq = next_event_seq(&conn);
-
-    conn.execute(
-        "INSERT INTO task_events (id, task_id, event_type, actor, data, seq) VALUES (?1, ?2, 'comment', ?3, ?4, ?5)",
-        rusqlite::params![event_id, task_id, actor, data_str, seq],
-    )
-    .map_err(|e| db_error(&e.to_string()))?;
-
-    let created_at: String = conn
-        .query_row(
-            "SELECT created_at FROM task_events WHERE id = ?1",
-            rusqlite::params![event_id],
-            |row| row.get(0),
-        )
-        .unwrap_or_else(|_| chrono::Utc::now().to_rfc3339());
+/// Send the webhook a signed `webhook.ping` now and report the target's
+/// answer — requires manage key. The attempt is added to the delivery log
+/// but doesn't count toward auto-disabling the webhook.
+#[post("/boards/<board_id>/webhooks/<webhook_id>/test")]
+pub async fn test_webhook(
+    board_id: &str,
+    webhook_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<WebhookTestResponse>, (Status, Json<ApiError>)> {
+    let (board, webhook) = (board_id.to_owned(), webhook_id.to_owned());
+    let (url, keys, headers, payload) = with_db(db, move |conn| {
+        let (board_id, webhook_id) = (board.as_str(), webhook.as_str());
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        require_webhook_on_board(conn, board_id, webhook_id)?;
+        let (url, kind, chat_id, template): (String, String, Option<String>, Option<String>) = conn
+            .query_row(
+                "SELECT url, kind, chat_id, template FROM webhooks WHERE id = ?1",
+                rusqlite::params![webhook_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        let keys = webhook_keys::active_keys(conn, webhook_id).map_err(|e| db_error(&e.to_string()))?;
+        let headers = webhooks::custom_headers(conn, webhook_id);
+        let payload = match template.and_then(|t| serde_json::from_str::<serde_json::Value>(&t).ok()) {
+            // A templated webhook gets its template filled in from a JSON ping
+            Some(template) => {
+                let ping = webhooks::ping_payload(webhooks::KIND_JSON, None, board_id, webhook_id);
+                let event = crate::events::BoardEvent {
+                    event: webhooks::PING_EVENT.to_string(),
+                    board_id: board_id.to_string(),
+                    data: ping["data"].clone(),
+                };
+                webhook_template::render(&template, &webhook_template::context(conn, &event, &ping))
+            }
+            None => webhooks::ping_payload(&kind, chat_id.as_deref(), board_id, webhook_id),
+        };
+        Ok((url, keys, headers, payload))
+    })
+    .await?;
 
-    bus.emit(crate::events::BoardEvent {
-        event: "task.comment".to_string(),
-        board_id: board_id.to_string(),
-        data: serde_json::json!({"task_id": task_id, "actor": &actor, "message": message, "mentions": &mentions}),
-    });
+    let body = serde_json::to_vec(&payload).unwrap_or_default();
+    let attempt =
+        webhooks::post_signed(bus.outbound(), &url, &keys, &headers, webhooks::PING_EVENT, board_id, &body).await;
 
-    Ok(Json(TaskEventResponse {
-        id: event_id,
-        event_type: "comment".to_string(),
-        actor,
-        data,
-        created_at,
+    let (board, webhook) = (board_id.to_owned(), webhook_id.to_owned());
+    let attempt = with_db(db, move |conn| {
+        webhooks::log_attempt(conn, &webhook, &board, webhooks::PING_EVENT, &body, 1, &attempt);
+        Ok::<_, (Status, Json<ApiError>)>(attempt)
+    })
+    .await?;
+    Ok(Json(WebhookTestResponse {
+        success: attempt.error.is_none(),
+        status_code: attempt.status_code,
+        duration_ms: attempt.duration_ms,
+        response_body: attempt.response_body,
+        error: attempt.error,
+        payload,
     }))
 }
 
-// ============ Webhooks ============
-
-/// Create a webhook — requires manage key.
-#[post("/boards/<board_id>/webhooks", format = "json", data = "<req>")]
-pub fn create_webhook(
+/// A webhook's delivery attempts (newest first) — requires manage key.
+/// `?failed=true` lists only the failed ones. The last 500 attempts are kept.
+#[get("/boards/<board_id>/webhooks/<webhook_id>/deliveries?<limit>&<failed>")]
+pub async fn list_webhook_deliveries(
     board_id: &str,
-    req: Json<CreateWebhookRequest>,
+    webhook_id: &str,
+    limit: Option<i64>,
+    failed: Option<bool>,
     token: BoardToken,
     db: &State<DbPool>,
-) -> Result<Json<WebhookResponse>, (Status, Json<ApiError>)> {
-    let req = req.into_inner();
-    let conn = db.lock().unwrap();
+) -> Result<Json<Vec<WebhookDeliveryResponse>>, (Status, Json<ApiError>)> {
+    let (board_id, webhook_id) = (board_id.to_owned(), webhook_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, webhook_id) = (board_id.as_str(), webhook_id.as_str());
+        let token_hash = hash_key(&token.0);
+        access::require_manage_key(conn, board_id, &token_hash)?;
+        require_webhook_on_board(conn, board_id, webhook_id)?;
+
+        let limit = limit.unwrap_or(50).clamp(1, 500);
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM webhook_deliveries
+                 WHERE webhook_id = ?1 AND (?2 IS NULL OR (error IS NOT NULL) = ?2)
+                 ORDER BY rowid DESC LIMIT ?3",
+                DELIVERY_COLUMNS
+            ))
+            .map_err(|e| db_error(&e.to_string()))?;
+        let deliveries = stmt
+            .query_map(rusqlite::params![webhook_id, failed, limit], delivery_from_row)
+            .map_err(|e| db_error(&e.to_string()))?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| db_error(&e.to_string()))?;
+        Ok(Json(deliveries))
+    })
+    .await
+}
 
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
+const DELIVERY_COLUMNS: &str =
+    "id, webhook_id, event, attempt, status_code, duration_ms, response_body, error, payload, created_at";
 
-    if req.url.trim().is_empty() {
-        return Err((
-            Status::BadRequest,
-            Json(ApiError {
-                error: "Webhook URL cannot be empty".to_string(),
-                code: "EMPTY_URL".to_string(),
-                status: 400,
-            }),
-        ));
-    }
+fn delivery_from_row(row: &rusqlite::Row) -> rusqlite::Result<WebhookDeliveryResponse> {
+    let error: Option<String> = row.get(7)?;
+    let payload: String = row.get(8)?;
+    Ok(WebhookDeliveryResponse {
+        id: row.get(0)?,
+        webhook_id: row.get(1)?,
+        event: row.get(2)?,
+        attempt: row.get(3)?,
+        success: error.is_none(),
+        status_code: row.get(4)?,
+        duration_ms: row.get(5)?,
+        response_body: row.get(6)?,
+        error,
+        payload: serde_json::from_str(&payload).unwrap_or(serde_json::Value::Null),
+        created_at: row.get(9)?,
+    })
+}
 
-    let valid_events = [
-        "task.created",
-        "task.updated",
-        "task.deleted",
-        "task.claimed",
-        "task.released",
-        "task.moved",
-        "task.reordered",
-        "task.comment",
-        "task.archived",
-        "task.unarchived",
-        "task.dependency.added",
-        "task.dependency.removed",
-    ];
-    for ev in &req.events {
-        if !valid_events.contains(&ev.as_str()) {
-            return Err((
-                Status::BadRequest,
-                Json(ApiError {
-                    error: format!(
-                        "Invalid event type '{}'. Valid types: {}",
-                        ev,
-                        valid_events.join(", ")
-                    ),
-                    code: "INVALID_EVENT_TYPE".to_string(),
-                    status: 400,
-                }),
-            ));
+/// Re-send one logged delivery's payload with a fresh signature — requires
+/// manage key. Returns the new attempt, which joins the delivery log; a
+/// success resets the webhook's failure count.
+#[post("/boards/<board_id>/webhooks/<webhook_id>/deliveries/<delivery_id>/redeliver")]
+pub async fn redeliver_webhook_delivery(
+    board_id: &str,
+    webhook_id: &str,
+    delivery_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<WebhookDeliveryResponse>, (Status, Json<ApiError>)> {
+    let delivery_id = delivery_id.to_owned();
+    let (board, webhook) = (board_id.to_owned(), webhook_id.to_owned());
+    let (url, keys, headers, event, payload, attempt_number) = with_db(db, move |conn| {
+        let (board_id, webhook_id) = (board.as_str(), webhook.as_str());
+        let delivery_id = delivery_id.as_str();
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        require_webhook_on_board(conn, board_id, webhook_id)?;
+        let (event, payload, attempt_number): (String, String, u32) = conn
+            .query_row(
+                "SELECT event, payload, attempt FROM webhook_deliveries WHERE id = ?1 AND webhook_id = ?2",
+                rusqlite::params![delivery_id, webhook_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .map_err(|_| not_found("Delivery"))?;
+        let url: String = conn
+            .query_row("SELECT url FROM webhooks WHERE id = ?1", rusqlite::params![webhook_id], |row| row.get(0))
+            .map_err(|e| db_error(&e.to_string()))?;
+        let keys = webhook_keys::active_keys(conn, webhook_id).map_err(|e| db_error(&e.to_string()))?;
+        let headers = webhooks::custom_headers(conn, webhook_id);
+        Ok((url, keys, headers, event, payload, attempt_number))
+    })
+    .await?;
+
+    let attempt =
+        webhooks::post_signed(bus.outbound(), &url, &keys, &headers, &event, board_id, payload.as_bytes()).await;
+
+    let (board, webhook) = (board_id.to_owned(), webhook_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, webhook_id) = (board.as_str(), webhook.as_str());
+        let id =
+            webhooks::log_attempt(conn, webhook_id, board_id, &event, payload.as_bytes(), attempt_number + 1, &attempt);
+        if attempt.error.is_none() {
+            let _ = conn.execute(
+                "UPDATE webhooks SET failure_count = 0, last_triggered_at = datetime('now') WHERE id = ?1",
+                rusqlite::params![webhook_id],
+            );
         }
+        conn.query_row(
+            &format!("SELECT {} FROM webhook_deliveries WHERE id = ?1", DELIVERY_COLUMNS),
+            rusqlite::params![id],
+            delivery_from_row,
+        )
+        .map(Json)
+        .map_err(|e| db_error(&e.to_string()))
+    })
+    .await
+}
+
+/// Re-send dead letters with a fresh signature — requires manage key.
+/// Delivered letters are removed; failures stay queued with their attempt count bumped.
+#[post("/boards/<board_id>/webhooks/<webhook_id>/dead-letters/redrive", data = "<req>")]
+pub async fn redrive_dead_letters(
+    board_id: &str,
+    webhook_id: &str,
+    req: Option<Json<RedriveRequest>>,
+    token: BoardToken,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<RedriveReport>, (Status, Json<ApiError>)> {
+    let req = req.map(|r| r.into_inner()).unwrap_or_default();
+    let (board, webhook) = (board_id.to_owned(), webhook_id.to_owned());
+    let (url, keys, headers, letters) = with_db(db, move |conn| {
+        let (board_id, webhook_id) = (board.as_str(), webhook.as_str());
+        let token_hash = hash_key(&token.0);
+        access::require_manage_key(conn, board_id, &token_hash)?;
+        require_webhook_on_board(conn, board_id, webhook_id)?;
+
+        let url: String = conn
+            .query_row(
+                "SELECT url FROM webhooks WHERE id = ?1",
+                rusqlite::params![webhook_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        let keys = webhook_keys::active_keys(conn, webhook_id).map_err(|e| db_error(&e.to_string()))?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, event, payload, attempts FROM webhook_dead_letters
+                 WHERE webhook_id = ?1 ORDER BY created_at ASC, rowid ASC",
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        let letters: Vec<(String, String, String, u32)> = stmt
+            .query_map(rusqlite::params![webhook_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .filter(|(id, _, _, _)| req.ids.as_ref().is_none_or(|ids| ids.contains(id)))
+            .collect();
+        Ok((url, keys, webhooks::custom_headers(conn, webhook_id), letters))
+    })
+    .await?;
+
+    let mut outcomes = Vec::new();
+    for (id, event, payload, attempts) in letters {
+        let attempt = crate::webhooks::post_signed(
+            bus.outbound(),
+            &url,
+            &keys,
+            &headers,
+            &event,
+            board_id,
+            payload.as_bytes(),
+        )
+        .await;
+        outcomes.push((id, event, payload, attempts + 1, attempt));
     }
 
-    let webhook_id = uuid::Uuid::new_v4().to_string();
-    let secret = format!(
-        "whsec_{}",
-        uuid::Uuid::new_v4().to_string().replace('-', "")
-    );
-    let events_json = serde_json::to_string(&req.events).unwrap_or_else(|_| "[]".to_string());
+    let (board, webhook) = (board_id.to_owned(), webhook_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, webhook_id) = (board.as_str(), webhook.as_str());
+        let delivered = outcomes.iter().filter(|(.., a)| a.error.is_none()).count();
+        for (id, event, payload, attempt_number, attempt) in &outcomes {
+            crate::webhooks::log_attempt(conn, webhook_id, board_id, event, payload.as_bytes(), *attempt_number, attempt);
+            match attempt.result() {
+                Ok(()) => conn.execute("DELETE FROM webhook_dead_letters WHERE id = ?1", rusqlite::params![id]),
+                Err(error) => conn.execute(
+                    "UPDATE webhook_dead_letters SET attempts = attempts + 1, last_error = ?1 WHERE id = ?2",
+                    rusqlite::params![error, id],
+                ),
+            }
+            .map_err(|e| db_error(&e.to_string()))?;
+        }
+        if delivered > 0 {
+            // A reachable endpoint again: let live deliveries resume
+            let _ = conn.execute(
+                "UPDATE webhooks SET failure_count = 0, last_triggered_at = datetime('now') WHERE id = ?1",
+                rusqlite::params![webhook_id],
+            );
+        }
+        let remaining: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM webhook_dead_letters WHERE webhook_id = ?1",
+                rusqlite::params![webhook_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        Ok(Json(RedriveReport {
+            attempted: outcomes.len(),
+            delivered,
+            failed: outcomes.len() - delivered,
+            remaining,
+        }))
+    })
+    .await
+}
 
-    conn.execute(
-        "INSERT INTO webhooks (id, board_id, url, secret, events) VALUES (?1, ?2, ?3, ?4, ?5)",
-        rusqlite::params![webhook_id, board_id, req.url.trim(), secret, events_json],
+pub(crate) fn require_webhook_on_board(conn: &Connection, board_id: &str, webhook_id: &str) -> Result<(), (Status, Json<ApiError>)> {
+    conn.query_row(
+        "SELECT 1 FROM webhooks WHERE id = ?1 AND board_id = ?2",
+        rusqlite::params![webhook_id, board_id],
+        |_| Ok(()),
     )
-    .map_err(|e| db_error(&e.to_string()))?;
+    .map_err(|_| not_found("Webhook"))
+}
+
+fn load_webhook_routes(conn: &Connection, board_id: &str) -> Result<WebhookRoutesResponse, (Status, Json<ApiError>)> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT c.id, c.name, r.webhook_id FROM webhook_routes r
+             JOIN columns c ON c.id = r.column_id
+             WHERE r.board_id = ?1
+             ORDER BY c.position ASC, r.created_at ASC, r.webhook_id ASC",
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+    let rows = stmt
+        .query_map(rusqlite::params![board_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+        })
+        .map_err(|e| db_error(&e.to_string()))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| db_error(&e.to_string()))?;
 
-    Ok(Json(WebhookResponse {
-        id: webhook_id,
+    let mut routes: Vec<WebhookRoute> = Vec::new();
+    for (column_id, column_name, webhook_id) in rows {
+        match routes.last_mut() {
+            Some(route) if route.column_id == column_id => route.webhook_ids.push(webhook_id),
+            _ => routes.push(WebhookRoute {
+                column_id,
+                column_name,
+                webhook_ids: vec![webhook_id],
+            }),
+        }
+    }
+    Ok(WebhookRoutesResponse {
         board_id: board_id.to_string(),
-        url: req.url,
-        secret: Some(secret),
-        events: req.events,
-        active: true,
-        failure_count: 0,
-        last_triggered_at: None,
-        created_at: chrono::Utc::now().to_rfc3339(),
-    }))
+        routes,
+    })
 }
 
-/// List webhooks — requires manage key.
-#[get("/boards/<board_id>/webhooks")]
-pub fn list_webhooks(
+/// Get the board's column → webhook routing table — requires manage key.
+#[get("/boards/<board_id>/webhook-routes")]
+pub async fn get_webhook_routes(
     board_id: &str,
     token: BoardToken,
     db: &State<DbPool>,
-) -> Result<Json<Vec<WebhookResponse>>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
+) -> Result<Json<WebhookRoutesResponse>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        let token_hash = hash_key(&token.0);
+        access::require_manage_key(conn, board_id, &token_hash)?;
+        load_webhook_routes(conn, board_id).map(Json)
+    })
+    .await
+}
 
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, board_id, url, events, active, failure_count, last_triggered_at, created_at
-             FROM webhooks WHERE board_id = ?1
-             ORDER BY created_at ASC",
-        )
-        .map_err(|e| db_error(&e.to_string()))?;
+/// Replace the board's routing table — requires manage key. A webhook named
+/// in any route only receives events for tasks entering its columns; webhooks
+/// without routes keep receiving everything.
+#[put("/boards/<board_id>/webhook-routes", format = "json", data = "<req>")]
+pub async fn set_webhook_routes(
+    board_id: &str,
+    req: Json<SetWebhookRoutesRequest>,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<WebhookRoutesResponse>, (Status, Json<ApiError>)> {
+    let req = req.into_inner();
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        let token_hash = hash_key(&token.0);
+        access::require_manage_key(conn, board_id, &token_hash)?;
+
+        for route in &req.routes {
+            let column_ok: bool = conn
+                .query_row(
+                    "SELECT COUNT(*) > 0 FROM columns WHERE id = ?1 AND board_id = ?2",
+                    rusqlite::params![route.column_id, board_id],
+                    |row| row.get(0),
+                )
+                .unwrap_or(false);
+            if !column_ok {
+                return Err((
+                    Status::BadRequest,
+                    Json(ApiError {
+                        error: format!("Column '{}' not found on this board", route.column_id),
+                        code: "INVALID_COLUMN".to_string(),
+                        status: 400,
+                    }),
+                ));
+            }
+            for webhook_id in &route.webhook_ids {
+                if require_webhook_on_board(conn, board_id, webhook_id).is_err() {
+                    return Err((
+                        Status::BadRequest,
+                        Json(ApiError {
+                            error: format!("Webhook '{}' not found on this board", webhook_id),
+                            code: "INVALID_WEBHOOK".to_string(),
+                            status: 400,
+                        }),
+                    ));
+                }
+            }
+        }
 
-    let webhooks: Vec<WebhookResponse> = stmt
-        .query_map(rusqlite::params![board_id], |row| {
-            let events_str: String = row.get(3)?;
-            let events: Vec<String> = serde_json::from_str(&events_str).unwrap_or_default();
-            Ok(WebhookResponse {
-                id: row.get(0)?,
-                board_id: row.get(1)?,
-                url: row.get(2)?,
-                secret: None,
-                events,
-                active: row.get::<_, i32>(4)? == 1,
-                failure_count: row.get(5)?,
-                last_triggered_at: row.get(6)?,
-                created_at: row.get(7)?,
-            })
-        })
-        .map_err(|e| db_error(&e.to_string()))?
-        .filter_map(|r| r.ok())
-        .collect();
+        let tx = conn.unchecked_transaction().map_err(|e| db_error(&e.to_string()))?;
+        tx.execute("DELETE FROM webhook_routes WHERE board_id = ?1", rusqlite::params![board_id])
+            .map_err(|e| db_error(&e.to_string()))?;
+        for route in &req.routes {
+            for webhook_id in &route.webhook_ids {
+                tx.execute(
+                    "INSERT OR IGNORE INTO webhook_routes (board_id, column_id, webhook_id) VALUES (?1, ?2, ?3)",
+                    rusqlite::params![board_id, route.column_id, webhook_id],
+                )
+                .map_err(|e| db_error(&e.to_string()))?;
+            }
+        }
+        tx.commit().map_err(|e| db_error(&e.to_string()))?;
 
-    Ok(Json(webhooks))
+        load_webhook_routes(conn, board_id).map(Json)
+    })
+    .await
 }
 
-/// Update a webhook — requires manage key.
-#[patch(
-    "/boards/<board_id>/webhooks/<webhook_id>",
-    format = "json",
-    data = "<req>"
-)]
-pub fn update_webhook(
+// ============ Task Dependencies ============
+
+/// Create a dependency — requires manage key.
+#[post("/boards/<board_id>/dependencies", format = "json", data = "<req>")]
+pub async fn create_dependency(
     board_id: &str,
-    webhook_id: &str,
-    req: Json<UpdateWebhookRequest>,
+    req: Json<CreateDependencyRequest>,
     token: BoardToken,
+    actor: Actor,
     db: &State<DbPool>,
-) -> Result<Json<WebhookResponse>, (Status, Json<ApiError>)> {
+    bus: &State<EventBus>,
+) -> Result<Json<DependencyResponse>, (Status, Json<ApiError>)> {
     let req = req.into_inner();
-    let conn = db.lock().unwrap();
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
+    let (board_id, bus) = (board_id.to_owned(), bus.inner().clone());
+    with_tx(db, move |conn| {
+        let (board_id, bus) = (board_id.as_str(), &bus);
+        let token_hash = hash_key(&token.0);
+        access::require_role(conn, board_id, &token_hash, BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+        access::require_display_name(conn, board_id, &actor)?;
+
+        if req.blocker_task_id == req.blocked_task_id {
+            return Err((
+                Status::BadRequest,
+                Json(ApiError {
+                    error: "A task cannot depend on itself".to_string(),
+                    code: "SELF_DEPENDENCY".to_string(),
+                    status: 400,
+                }),
+            ));
+        }
 
-    let exists: bool = conn
-        .query_row(
-            "SELECT COUNT(*) > 0 FROM webhooks WHERE id = ?1 AND board_id = ?2",
-            rusqlite::params![webhook_id, board_id],
-            |row| row.get(0),
-        )
-        .unwrap_or(false);
+        let blocker_exists: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM tasks WHERE id = ?1 AND board_id = ?2",
+                rusqlite::params![req.blocker_task_id, board_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
 
-    if !exists {
-        return Err(not_found("Webhook"));
-    }
+        let blocked_exists: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM tasks WHERE id = ?1 AND board_id = ?2",
+                rusqlite::params![req.blocked_task_id, board_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !blocker_exists {
+            return Err(not_found("Blocker task"));
+        }
+        if !blocked_exists {
+            return Err(not_found("Blocked task"));
+        }
+
+        let reverse_exists: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM task_dependencies WHERE blocker_task_id = ?1 AND blocked_task_id = ?2",
+                rusqlite::params![req.blocked_task_id, req.blocker_task_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
 
-    if let Some(ref url) = req.url {
-        if url.trim().is_empty() {
+        if reverse_exists {
             return Err((
-                Status::BadRequest,
+                Status::Conflict,
                 Json(ApiError {
-                    error: "Webhook URL cannot be empty".to_string(),
-                    code: "EMPTY_URL".to_string(),
-                    status: 400,
+                    error: "Circular dependency: the reverse relationship already exists".to_string(),
+                    code: "CIRCULAR_DEPENDENCY".to_string(),
+                    status: 409,
                 }),
             ));
         }
-        conn.execute(
-            "UPDATE webhooks SET url = ?1 WHERE id = ?2",
-            rusqlite::params![url.trim(), webhook_id],
-        )
-        .map_err(|e| db_error(&e.to_string()))?;
-    }
 
-    if let Some(ref events) = req.events {
-        let valid_events = [
-            "task.created",
-            "task.updated",
-            "task.deleted",
-            "task.claimed",
-            "task.released",
-            "task.moved",
-            "task.reordered",
-            "task.comment",
-            "task.dependency.added",
-            "task.dependency.removed",
-        ];
-        for ev in events {
-            if !valid_events.contains(&ev.as_str()) {
+        if has_path(conn, &req.blocked_task_id, &req.blocker_task_id) {
+            return Err((
+                Status::Conflict,
+                Json(ApiError {
+                    error: "Circular dependency: this would create a cycle in the dependency graph"
+                        .to_string(),
+                    code: "CIRCULAR_DEPENDENCY".to_string(),
+                    status: 409,
+                }),
+            ));
+        }
+
+        let dep_id = uuid::Uuid::new_v4().to_string();
+        let result = conn.execute(
+            "INSERT INTO task_dependencies (id, board_id, blocker_task_id, blocked_task_id, note) VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![dep_id, board_id, req.blocker_task_id, req.blocked_task_id, req.note],
+        );
+
+        match result {
+            Ok(_) => {}
+            Err(e) if e.to_string().contains("UNIQUE") => {
                 return Err((
-                    Status::BadRequest,
+                    Status::Conflict,
                     Json(ApiError {
-                        error: format!("Invalid event type '{}'", ev),
-                        code: "INVALID_EVENT_TYPE".to_string(),
-                        status: 400,
+                        error: "This dependency already exists".to_string(),
+                        code: "DUPLICATE_DEPENDENCY".to_string(),
+                        status: 409,
                     }),
                 ));
             }
+            Err(e) => return Err(db_error(&e.to_string())),
         }
-        let events_json = serde_json::to_string(events).unwrap_or_else(|_| "[]".to_string());
-        conn.execute(
-            "UPDATE webhooks SET events = ?1 WHERE id = ?2",
-            rusqlite::params![events_json, webhook_id],
+
+        let event_data = serde_json::json!({
+            "dependency_id": dep_id,
+            "blocker_task_id": req.blocker_task_id,
+            "blocked_task_id": req.blocked_task_id,
+            "note": req.note,
+        });
+        log_event(
+            conn,
+            &req.blocked_task_id,
+            "dependency.added",
+            actor.name(),
+            &event_data,
+        );
+
+        bus.emit(
+            conn,
+            crate::events::BoardEvent {
+                event: event_types::TASK_DEPENDENCY_ADDED.to_string(),
+                board_id: board_id.to_string(),
+                data: event_data,
+            },
         )
         .map_err(|e| db_error(&e.to_string()))?;
-    }
 
-    if let Some(active) = req.active {
-        let active_int: i32 = if active { 1 } else { 0 };
-        if active {
-            conn.execute(
-                "UPDATE webhooks SET active = ?1, failure_count = 0 WHERE id = ?2",
-                rusqlite::params![active_int, webhook_id],
+        load_dependency_response(conn, &dep_id)
+    })
+    .await
+}
+
+/// List dependencies — public, no auth required.
+#[get("/boards/<board_id>/dependencies?<task>")]
+pub async fn list_dependencies(
+    board_id: &str,
+    task: Option<&str>,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+) -> Result<Json<Vec<DependencyResponse>>, (Status, Json<ApiError>)> {
+    let (board_id, task) = (board_id.to_owned(), task.map(str::to_owned));
+    with_db(db, move |conn| {
+        let (board_id, task) = (board_id.as_str(), task.as_deref());
+        access::require_read_access(conn, board_id, token.as_ref())?;
+
+        let (sql, params): (String, Vec<Box<dyn rusqlite::types::ToSql>>) = if let Some(task_id) = task
+        {
+            (
+                "SELECT d.id, d.board_id, d.blocker_task_id, bt.title, bc.name, bt.completed_at IS NOT NULL,
+                        d.blocked_task_id, blt.title, blc.name, d.note, d.created_by, d.created_at
+                 FROM task_dependencies d
+                 JOIN tasks bt ON d.blocker_task_id = bt.id
+                 JOIN columns bc ON bt.column_id = bc.id
+                 JOIN tasks blt ON d.blocked_task_id = blt.id
+                 JOIN columns blc ON blt.column_id = blc.id
+                 WHERE d.board_id = ?1 AND (d.blocker_task_id = ?2 OR d.blocked_task_id = ?2)
+                 ORDER BY d.created_at ASC".to_string(),
+                vec![
+                    Box::new(board_id.to_string()) as Box<dyn rusqlite::types::ToSql>,
+                    Box::new(task_id.to_string()),
+                ],
             )
-            .map_err(|e| db_error(&e.to_string()))?;
         } else {
-            conn.execute(
-                "UPDATE webhooks SET active = ?1 WHERE id = ?2",
-                rusqlite::params![active_int, webhook_id],
+            (
+                "SELECT d.id, d.board_id, d.blocker_task_id, bt.title, bc.name, bt.completed_at IS NOT NULL,
+                        d.blocked_task_id, blt.title, blc.name, d.note, d.created_by, d.created_at
+                 FROM task_dependencies d
+                 JOIN tasks bt ON d.blocker_task_id = bt.id
+                 JOIN columns bc ON bt.column_id = bc.id
+                 JOIN tasks blt ON d.blocked_task_id = blt.id
+                 JOIN columns blc ON blt.column_id = blc.id
+                 WHERE d.board_id = ?1
+                 ORDER BY d.created_at ASC".to_string(),
+                vec![Box::new(board_id.to_string()) as Box<dyn rusqlite::types::ToSql>],
             )
-            .map_err(|e| db_error(&e.to_string()))?;
-        }
-    }
+        };
 
-    let wh = conn
-        .query_row(
-            "SELECT id, board_id, url, events, active, failure_count, last_triggered_at, created_at
-             FROM webhooks WHERE id = ?1",
-            rusqlite::params![webhook_id],
-            |row| {
-                let events_str: String = row.get(3)?;
-                let events: Vec<String> = serde_json::from_str(&events_str).unwrap_or_default();
-                Ok(WebhookResponse {
+        let param_refs: Vec<&dyn rusqlite::types::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        let mut stmt = conn.prepare(&sql).map_err(|e| db_error(&e.to_string()))?;
+
+        let deps: Vec<DependencyResponse> = stmt
+            .query_map(param_refs.as_slice(), |row| {
+                Ok(DependencyResponse {
                     id: row.get(0)?,
                     board_id: row.get(1)?,
-                    url: row.get(2)?,
-                    secret: None,
-                    events,
-                    active: row.get::<_, i32>(4)? == 1,
-                    failure_count: row.get(5)?,
-                    last_triggered_at: row.get(6)?,
-                    created_at: row.get(7)?,
+                    blocker_task_id: row.get(2)?,
+                    blocker_title: row.get(3)?,
+                    blocker_column: row.get(4)?,
+                    blocker_completed: row.get(5)?,
+                    blocked_task_id: row.get(6)?,
+                    blocked_title: row.get(7)?,
+                    blocked_column: row.get(8)?,
+                    note: row.get(9)?,
+                    created_by: row.get(10)?,
+                    created_at: row.get(11)?,
                 })
-            },
-        )
-        .map_err(|_| not_found("Webhook"))?;
+            })
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
 
-    Ok(Json(wh))
+        Ok(Json(deps))
+    })
+    .await
 }
 
-/// Delete a webhook — requires manage key.
-#[delete("/boards/<board_id>/webhooks/<webhook_id>")]
-pub fn delete_webhook(
+/// Delete a dependency — requires manage key.
+#[delete("/boards/<board_id>/dependencies/<dep_id>")]
+pub async fn delete_dependency(
     board_id: &str,
-    webhook_id: &str,
+    dep_id: &str,
     token: BoardToken,
+    actor: Actor,
     db: &State<DbPool>,
+    bus: &State<EventBus>,
 ) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
-
-    let affected = conn
-        .execute(
-            "DELETE FROM webhooks WHERE id = ?1 AND board_id = ?2",
-            rusqlite::params![webhook_id, board_id],
-        )
-        .unwrap_or(0);
+    let (board_id, bus, dep_id) = (board_id.to_owned(), bus.inner().clone(), dep_id.to_owned());
+    with_tx(db, move |conn| {
+        let (board_id, bus, dep_id) = (board_id.as_str(), &bus, dep_id.as_str());
+        let token_hash = hash_key(&token.0);
+        access::require_role(conn, board_id, &token_hash, BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+        access::require_display_name(conn, board_id, &actor)?;
+
+        let dep_info = conn.query_row(
+            "SELECT blocker_task_id, blocked_task_id FROM task_dependencies WHERE id = ?1 AND board_id = ?2",
+            rusqlite::params![dep_id, board_id],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+        );
 
-    if affected > 0 {
-        Ok(Json(serde_json::json!({"deleted": true, "id": webhook_id})))
-    } else {
-        Err(not_found("Webhook"))
-    }
+        let (blocker_id, blocked_id) = match dep_info {
+            Ok(info) => info,
+            Err(_) => return Err(not_found("Dependency")),
+        };
+
+        let affected = conn
+            .execute(
+                "DELETE FROM task_dependencies WHERE id = ?1 AND board_id = ?2",
+                rusqlite::params![dep_id, board_id],
+            )
+            .unwrap_or(0);
+
+        if affected > 0 {
+            let event_data = serde_json::json!({
+                "dependency_id": dep_id,
+                "blocker_task_id": blocker_id,
+                "blocked_task_id": blocked_id,
+            });
+            log_event(
+                conn,
+                &blocked_id,
+                "dependency.removed",
+                actor.name(),
+                &event_data,
+            );
+
+            bus.emit(
+                conn,
+                crate::events::BoardEvent {
+                    event: event_types::TASK_DEPENDENCY_REMOVED.to_string(),
+                    board_id: board_id.to_string(),
+                    data: event_data,
+                },
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+
+            Ok(Json(serde_json::json!({"deleted": true, "id": dep_id})))
+        } else {
+            Err(not_found("Dependency"))
+        }
+    })
+    .await
 }
 
-// ============ Task Dependencies ============
+/// Default per-task estimate for dependency scheduling, in hours.
+const DEFAULT_ESTIMATE_HOURS: f64 = 8.0;
 
-/// Create a dependency — requires manage key.
-#[post("/boards/<board_id>/dependencies", format = "json", data = "<req>")]
-pub fn create_dependency(
+/// Back-propagate due dates onto a task's upstream blockers — requires manage key.
+/// Each blocker is due when the earliest task it blocks must start (that task's due
+/// date minus its estimate). Completed blockers are left out. With `dry_run` the
+/// computed schedule is returned without writing anything.
+#[post("/boards/<board_id>/dependencies/schedule", format = "json", data = "<req>")]
+pub async fn schedule_dependencies(
     board_id: &str,
-    req: Json<CreateDependencyRequest>,
+    actor: Actor,
+    req: Json<ScheduleRequest>,
     token: BoardToken,
     db: &State<DbPool>,
     bus: &State<EventBus>,
-) -> Result<Json<DependencyResponse>, (Status, Json<ApiError>)> {
+) -> Result<Json<ScheduleResponse>, (Status, Json<ApiError>)> {
     let req = req.into_inner();
-    let conn = db.lock().unwrap();
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
-    access::require_not_archived(&conn, board_id)?;
-
-    if req.blocker_task_id == req.blocked_task_id {
-        return Err((
+    let deadline = parse_deadline(&req.deadline).ok_or_else(|| {
+        (
             Status::BadRequest,
             Json(ApiError {
-                error: "A task cannot depend on itself".to_string(),
-                code: "SELF_DEPENDENCY".to_string(),
+                error: "deadline must be an RFC 3339 timestamp or YYYY-MM-DD".to_string(),
+                code: "INVALID_DEADLINE".to_string(),
                 status: 400,
             }),
-        ));
-    }
-
-    let blocker_exists: bool = conn
-        .query_row(
-            "SELECT COUNT(*) > 0 FROM tasks WHERE id = ?1 AND board_id = ?2",
-            rusqlite::params![req.blocker_task_id, board_id],
-            |row| row.get(0),
-        )
-        .unwrap_or(false);
-
-    let blocked_exists: bool = conn
-        .query_row(
-            "SELECT COUNT(*) > 0 FROM tasks WHERE id = ?1 AND board_id = ?2",
-            rusqlite::params![req.blocked_task_id, board_id],
-            |row| row.get(0),
-        )
-        .unwrap_or(false);
-
-    if !blocker_exists {
-        return Err(not_found("Blocker task"));
-    }
-    if !blocked_exists {
-        return Err(not_found("Blocked task"));
-    }
-
-    let reverse_exists: bool = conn
-        .query_row(
-            "SELECT COUNT(*) > 0 FROM task_dependencies WHERE blocker_task_id = ?1 AND blocked_task_id = ?2",
-            rusqlite::params![req.blocked_task_id, req.blocker_task_id],
-            |row| row.get(0),
         )
-        .unwrap_or(false);
-
-    if reverse_exists {
+    })?;
+    let default_estimate = req.default_estimate_hours.unwrap_or(DEFAULT_ESTIMATE_HOURS);
+    let valid_estimate = |h: f64| h.is_finite() && h >= 0.0;
+    if !valid_estimate(default_estimate) || !req.estimates.values().all(|h| valid_estimate(*h)) {
         return Err((
-            Status::Conflict,
+            Status::BadRequest,
             Json(ApiError {
-                error: "Circular dependency: the reverse relationship already exists".to_string(),
-                code: "CIRCULAR_DEPENDENCY".to_string(),
-                status: 409,
+                error: "Estimates must be non-negative numbers of hours".to_string(),
+                code: "INVALID_ESTIMATE".to_string(),
+                status: 400,
             }),
         ));
     }
 
-    if has_path(&conn, &req.blocked_task_id, &req.blocker_task_id) {
-        return Err((
-            Status::Conflict,
-            Json(ApiError {
-                error: "Circular dependency: this would create a cycle in the dependency graph"
-                    .to_string(),
-                code: "CIRCULAR_DEPENDENCY".to_string(),
-                status: 409,
-            }),
-        ));
-    }
+    let (board_id, bus) = (board_id.to_owned(), bus.inner().clone());
+    with_db(db, move |conn| {
+        let (board_id, bus) = (board_id.as_str(), &bus);
+        let token_hash = hash_key(&token.0);
+        access::require_role(conn, board_id, &token_hash, BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
 
-    let dep_id = uuid::Uuid::new_v4().to_string();
-    let result = conn.execute(
-        "INSERT INTO task_dependencies (id, board_id, blocker_task_id, blocked_task_id, note) VALUES (?1, ?2, ?3, ?4, ?5)",
-        rusqlite::params![dep_id, board_id, req.blocker_task_id, req.blocked_task_id, req.note],
-    );
+        access::require_display_name(conn, board_id, &actor)?;
+        let actor = actor.name();
 
-    match result {
-        Ok(_) => {}
-        Err(e) if e.to_string().contains("UNIQUE") => {
-            return Err((
-                Status::Conflict,
-                Json(ApiError {
-                    error: "This dependency already exists".to_string(),
-                    code: "DUPLICATE_DEPENDENCY".to_string(),
-                    status: 409,
-                }),
-            ));
+        // task_id → (title, due_at, metadata, completed)
+        let mut tasks: HashMap<String, (String, Option<String>, String, bool)> = HashMap::new();
+        {
+            let mut stmt = conn
+                .prepare("SELECT id, title, due_at, metadata, completed_at IS NOT NULL FROM tasks WHERE board_id = ?1")
+                .map_err(|e| db_error(&e.to_string()))?;
+            let rows = stmt
+                .query_map(rusqlite::params![board_id], |row| {
+                    Ok((row.get::<_, String>(0)?, (row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)))
+                })
+                .map_err(|e| db_error(&e.to_string()))?;
+            tasks.extend(rows.filter_map(|r| r.ok()));
         }
-        Err(e) => return Err(db_error(&e.to_string())),
-    }
-
-    let event_data = serde_json::json!({
-        "dependency_id": dep_id,
-        "blocker_task_id": req.blocker_task_id,
-        "blocked_task_id": req.blocked_task_id,
-        "note": req.note,
-    });
-    log_event(
-        &conn,
-        &req.blocked_task_id,
-        "dependency.added",
-        "anonymous",
-        &event_data,
-    );
-
-    bus.emit(crate::events::BoardEvent {
-        event: "task.dependency.added".to_string(),
-        board_id: board_id.to_string(),
-        data: event_data,
-    });
-
-    load_dependency_response(&conn, &dep_id)
-}
-
-/// List dependencies — public, no auth required.
-#[get("/boards/<board_id>/dependencies?<task>")]
-pub fn list_dependencies(
-    board_id: &str,
-    task: Option<&str>,
-    db: &State<DbPool>,
-) -> Result<Json<Vec<DependencyResponse>>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
-    access::require_board_exists(&conn, board_id)?;
-
-    let (sql, params): (String, Vec<Box<dyn rusqlite::types::ToSql>>) = if let Some(task_id) = task
-    {
-        (
-            "SELECT d.id, d.board_id, d.blocker_task_id, bt.title, bc.name, bt.completed_at IS NOT NULL,
-                    d.blocked_task_id, blt.title, blc.name, d.note, d.created_by, d.created_at
-             FROM task_dependencies d
-             JOIN tasks bt ON d.blocker_task_id = bt.id
-             JOIN columns bc ON bt.column_id = bc.id
-             JOIN tasks blt ON d.blocked_task_id = blt.id
-             JOIN columns blc ON blt.column_id = blc.id
-             WHERE d.board_id = ?1 AND (d.blocker_task_id = ?2 OR d.blocked_task_id = ?2)
-             ORDER BY d.created_at ASC".to_string(),
-            vec![
-                Box::new(board_id.to_string()) as Box<dyn rusqlite::types::ToSql>,
-                Box::new(task_id.to_string()),
-            ],
-        )
-    } else {
-        (
-            "SELECT d.id, d.board_id, d.blocker_task_id, bt.title, bc.name, bt.completed_at IS NOT NULL,
-                    d.blocked_task_id, blt.title, blc.name, d.note, d.created_by, d.created_at
-             FROM task_dependencies d
-             JOIN tasks bt ON d.blocker_task_id = bt.id
-             JOIN columns bc ON bt.column_id = bc.id
-             JOIN tasks blt ON d.blocked_task_id = blt.id
-             JOIN columns blc ON blt.column_id = blc.id
-             WHERE d.board_id = ?1
-             ORDER BY d.created_at ASC".to_string(),
-            vec![Box::new(board_id.to_string()) as Box<dyn rusqlite::types::ToSql>],
-        )
-    };
-
-    let param_refs: Vec<&dyn rusqlite::types::ToSql> = params.iter().map(|p| p.as_ref()).collect();
-    let mut stmt = conn.prepare(&sql).map_err(|e| db_error(&e.to_string()))?;
-
-    let deps: Vec<DependencyResponse> = stmt
-        .query_map(param_refs.as_slice(), |row| {
-            Ok(DependencyResponse {
-                id: row.get(0)?,
-                board_id: row.get(1)?,
-                blocker_task_id: row.get(2)?,
-                blocker_title: row.get(3)?,
-                blocker_column: row.get(4)?,
-                blocker_completed: row.get(5)?,
-                blocked_task_id: row.get(6)?,
-                blocked_title: row.get(7)?,
-                blocked_column: row.get(8)?,
-                note: row.get(9)?,
-                created_by: row.get(10)?,
-                created_at: row.get(11)?,
+        if !tasks.contains_key(&req.task_id) {
+            return Err(not_found("Task"));
+        }
+
+        let edges: Vec<(String, String)> = {
+            let mut stmt = conn
+                .prepare("SELECT blocker_task_id, blocked_task_id FROM task_dependencies WHERE board_id = ?1")
+                .map_err(|e| db_error(&e.to_string()))?;
+            let rows = stmt
+                .query_map(rusqlite::params![board_id], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(|e| db_error(&e.to_string()))?;
+            rows.filter_map(|r| r.ok()).collect()
+        };
+
+        let estimate_of = |id: &str| -> f64 {
+            req.estimates.get(id).copied().unwrap_or_else(|| {
+                tasks
+                    .get(id)
+                    .and_then(|t| serde_json::from_str::<serde_json::Value>(&t.2).ok())
+                    .and_then(|m| m.get("estimate_hours").and_then(|h| h.as_f64()))
+                    .filter(|h| valid_estimate(*h))
+                    .unwrap_or(default_estimate)
             })
-        })
-        .map_err(|e| db_error(&e.to_string()))?
-        .filter_map(|r| r.ok())
-        .collect();
+        };
+        let completed: HashSet<String> = tasks
+            .iter()
+            .filter(|(id, t)| t.3 && **id != req.task_id)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        let plan = back_schedule(&req.task_id, deadline, &edges, &completed, estimate_of);
+
+        let tx = rusqlite::Transaction::new_unchecked(conn, rusqlite::TransactionBehavior::Immediate)
+            .map_err(|e| db_error(&e.to_string()))?;
+        let mut schedule = Vec::with_capacity(plan.len());
+        let mut updated = 0;
+        for (task_id, due) in plan {
+            let (title, current_due_at, _, _) = &tasks[&task_id];
+            let estimate_hours = estimate_of(&task_id);
+            let start_by = due - hours_duration(estimate_hours);
+            let due_at = due.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+            let applied = req.overwrite || current_due_at.is_none();
+
+            if applied && !req.dry_run && current_due_at.as_deref() != Some(due_at.as_str()) {
+                tx.execute(
+                    "UPDATE tasks SET due_at = ?1, updated_at = datetime('now') WHERE id = ?2",
+                    rusqlite::params![due_at, task_id],
+                )
+                .map_err(|e| db_error(&e.to_string()))?;
+                let changes = serde_json::json!({"due_at": due_at, "scheduled_from": req.task_id});
+                log_event(&tx, &task_id, "updated", actor, &changes);
+                bus.emit(
+                    &tx,
+                    crate::events::BoardEvent {
+                        event: event_types::TASK_UPDATED.to_string(),
+                        board_id: board_id.to_string(),
+                        data: serde_json::json!({
+                            "task_id": task_id,
+                            "actor": actor,
+                            "due_at": due_at,
+                            "scheduled_from": req.task_id,
+                        }),
+                    },
+                )
+                .map_err(|e| db_error(&e.to_string()))?;
+                updated += 1;
+            }
 
-    Ok(Json(deps))
+            schedule.push(ScheduledTask {
+                task_id,
+                title: title.clone(),
+                estimate_hours,
+                start_by: start_by.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                due_at,
+                current_due_at: current_due_at.clone(),
+                applied,
+            });
+        }
+        crate::events::commit(tx).map_err(|e| db_error(&e.to_string()))?;
+
+        Ok(Json(ScheduleResponse {
+            task_id: req.task_id.clone(),
+            deadline: deadline.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            dry_run: req.dry_run,
+            schedule,
+            updated,
+        }))
+    })
+    .await
 }
 
-/// Delete a dependency — requires manage key.
-#[delete("/boards/<board_id>/dependencies/<dep_id>")]
-pub fn delete_dependency(
-    board_id: &str,
-    dep_id: &str,
-    token: BoardToken,
-    db: &State<DbPool>,
-    bus: &State<EventBus>,
-) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
-    let conn = db.lock().unwrap();
-    let token_hash = hash_key(&token.0);
-    access::require_manage_key(&conn, board_id, &token_hash)?;
-    access::require_not_archived(&conn, board_id)?;
-
-    let dep_info = conn.query_row(
-        "SELECT blocker_task_id, blocked_task_id FROM task_dependencies WHERE id = ?1 AND board_id = ?2",
-        rusqlite::params![dep_id, board_id],
-        |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
-    );
+// ============ Helpers ============
 
-    let (blocker_id, blocked_id) = match dep_info {
-        Ok(info) => info,
-        Err(_) => return Err(not_found("Dependency")),
-    };
+/// Parse a scheduling deadline: RFC 3339, or a bare date meaning end of that day (UTC).
+fn parse_deadline(s: &str) -> Option<NaiveDateTime> {
+    chrono::DateTime::parse_from_rfc3339(s.trim())
+        .map(|d| d.naive_utc())
+        .ok()
+        .or_else(|| {
+            chrono::NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(23, 59, 59))
+        })
+}
 
-    let affected = conn
-        .execute(
-            "DELETE FROM task_dependencies WHERE id = ?1 AND board_id = ?2",
-            rusqlite::params![dep_id, board_id],
-        )
-        .unwrap_or(0);
+fn hours_duration(hours: f64) -> chrono::Duration {
+    chrono::Duration::seconds((hours * 3600.0).round() as i64)
+}
 
-    if affected > 0 {
-        let event_data = serde_json::json!({
-            "dependency_id": dep_id,
-            "blocker_task_id": blocker_id,
-            "blocked_task_id": blocked_id,
-        });
-        log_event(
-            &conn,
-            &blocked_id,
-            "dependency.removed",
-            "anonymous",
-            &event_data,
-        );
+/// Latest-finish backward pass over the upstream blockers of `target`.
+///
+/// `edges` are `(blocker, blocked)` pairs; tasks in `skip` (e.g. completed ones)
+/// are not traversed. Returns `(task_id, due)` pairs — the target (due at
+/// `deadline`) plus every reachable blocker — sorted earliest due first.
+fn back_schedule(
+    target: &str,
+    deadline: NaiveDateTime,
+    edges: &[(String, String)],
+    skip: &HashSet<String>,
+    estimate: impl Fn(&str) -> f64,
+) -> Vec<(String, NaiveDateTime)> {
+    let mut blockers_of: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (blocker, blocked) in edges {
+        if !skip.contains(blocker) {
+            blockers_of.entry(blocked.as_str()).or_default().push(blocker.as_str());
+        }
+    }
 
-        bus.emit(crate::events::BoardEvent {
-            event: "task.dependency.removed".to_string(),
-            board_id: board_id.to_string(),
-            data: event_data,
-        });
+    // Upstream closure of the target
+    let mut closure: HashSet<&str> = HashSet::from([target]);
+    let mut queue = VecDeque::from([target]);
+    while let Some(task) = queue.pop_front() {
+        for blocker in blockers_of.get(task).into_iter().flatten() {
+            if closure.insert(blocker) {
+                queue.push_back(blocker);
+            }
+        }
+    }
 
-        Ok(Json(serde_json::json!({"deleted": true, "id": dep_id})))
-    } else {
-        Err(not_found("Dependency"))
+    // A blocker's due date is settled once every task it blocks inside the
+    // closure has been scheduled (the dependency graph is acyclic).
+    let mut pending: HashMap<&str, usize> = HashMap::new();
+    for (blocker, blocked) in edges {
+        if closure.contains(blocker.as_str()) && closure.contains(blocked.as_str()) {
+            *pending.entry(blocker.as_str()).or_default() += 1;
+        }
     }
-}
 
-// ============ Helpers ============
+    let mut due: HashMap<&str, NaiveDateTime> = HashMap::from([(target, deadline)]);
+    let mut ready = VecDeque::from([target]);
+    let mut order = Vec::new();
+    while let Some(task) = ready.pop_front() {
+        let task_due = due[task];
+        order.push((task.to_string(), task_due));
+        let start = task_due - hours_duration(estimate(task));
+        for blocker in blockers_of.get(task).into_iter().flatten() {
+            due.entry(blocker).and_modify(|d| *d = (*d).min(start)).or_insert(start);
+            let remaining = pending.entry(blocker).or_default();
+            *remaining = remaining.saturating_sub(1);
+            if *remaining == 0 {
+                ready.push_back(blocker);
+            }
+        }
+    }
+
+    order.sort_by_key(|(_, due)| *due);
+    order
+}
 
 fn has_path(conn: &Connection, from_task: &str, to_task: &str) -> bool {
     let mut visited = std::collections::HashSet::new();
@@ -2995,17 +6340,11 @@ fn load_dependency_response(
     .map_err(|_| not_found("Dependency"))
 }
 
-/// Compute the next monotonic seq value for task_events.
-fn next_event_seq(conn: &Connection) -> i64 {
-    conn.query_row(
-        "SELECT COALESCE(MAX(seq), 0) + 1 FROM task_events",
-        [],
-        |row| row.get(0),
-    )
-    .unwrap_or(1)
-}
+/// Next monotonic seq value for task_events. Evaluated inside the INSERT so the
+/// read and write happen under one write lock — pooled connections can't race.
+pub(crate) const NEXT_EVENT_SEQ: &str = "(SELECT COALESCE(MAX(seq), 0) + 1 FROM task_events)";
 
-fn log_event(
+pub(crate) fn log_event(
     conn: &Connection,
     task_id: &str,
     event_type: &str,
@@ -3014,10 +6353,12 @@ fn log_event(
 ) {
     let id = uuid::Uuid::new_v4().to_string();
     let data_str = serde_json::to_string(data).unwrap_or_else(|_| "{}".to_string());
-    let seq = next_event_seq(conn);
     let _ = conn.execute(
-        "INSERT INTO task_events (id, task_id, event_type, actor, data, seq) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-        rusqlite::params![id, task_id, event_type, actor, data_str, seq],
+        &format!(
+            "INSERT INTO task_events (id, task_id, event_type, actor, data, seq) VALUES (?1, ?2, ?3, ?4, ?5, {})",
+            NEXT_EVENT_SEQ
+        ),
+        rusqlite::params![id, task_id, event_type, actor, data_str],
     );
 }
 
@@ -3030,7 +6371,10 @@ fn load_board_response(
             "SELECT b.id, b.name, b.description, b.archived, b.is_public, b.created_at, b.updated_at,
                     b.quick_done_column_id, b.quick_done_auto_archive,
                     b.quick_reassign_column_id, b.quick_reassign_to,
-                    b.require_display_name
+                    b.require_display_name, b.enforce_dependencies,
+                    b.archived_task_retention_days, b.event_retention_days,
+                    b.task_prefix, b.next_task_number, b.duplicate_window_seconds,
+                    b.comment_pressure_threshold, b.visibility, b.view_password_hash IS NOT NULL
              FROM boards b
              WHERE b.id = ?1",
             rusqlite::params![board_id],
@@ -3048,6 +6392,15 @@ fn load_board_response(
                     row.get::<_, Option<String>>(9)?,
                     row.get::<_, Option<String>>(10)?,
                     row.get::<_, i32>(11).unwrap_or(0) == 1,
+                    row.get::<_, i32>(12).unwrap_or(0) == 1,
+                    row.get::<_, Option<i64>>(13)?,
+                    row.get::<_, Option<i64>>(14)?,
+                    row.get::<_, Option<String>>(15)?,
+                    row.get::<_, i64>(16)?,
+                    row.get::<_, Option<i64>>(17)?,
+                    row.get::<_, Option<i64>>(18)?,
+                    row.get::<_, Option<String>>(19)?,
+                    row.get::<_, bool>(20)?,
                 ))
             },
         )
@@ -3056,7 +6409,8 @@ fn load_board_response(
     let mut col_stmt = conn
         .prepare(
             "SELECT c.id, c.name, c.position, c.wip_limit,
-                    (SELECT COUNT(*) FROM tasks t WHERE t.column_id = c.id)
+                    (SELECT COUNT(*) FROM tasks t WHERE t.column_id = c.id),
+                    c.auto_assign, c.auto_assign_strategy
              FROM columns c WHERE c.board_id = ?1
              ORDER BY c.position ASC",
         )
@@ -3070,6 +6424,8 @@ fn load_board_response(
                 position: row.get(2)?,
                 wip_limit: row.get(3)?,
                 task_count: row.get(4)?,
+                auto_assign: auto_assign::parse_pool(row.get(5)?),
+                auto_assign_strategy: row.get::<_, Option<String>>(6)?.unwrap_or_else(|| auto_assign::ROUND_ROBIN.to_string()),
             })
         })
         .map_err(|e| db_error(&e.to_string()))?
@@ -3086,43 +6442,67 @@ fn load_board_response(
         task_count,
         archived: board.3,
         is_public: board.4,
+        visibility: board.19.as_deref().and_then(Visibility::parse).unwrap_or(if board.4 { Visibility::Public } else { Visibility::Unlisted }),
+        password_protected: board.20,
         require_display_name: board.11,
+        enforce_dependencies: board.12,
         quick_done_column_id: board.7,
         quick_done_auto_archive: board.8,
         quick_reassign_column_id: board.9,
         quick_reassign_to: board.10,
+        archived_task_retention_days: board.13,
+        event_retention_days: board.14,
+        task_prefix: board.15,
+        next_task_number: board.16,
+        duplicate_window_seconds: board.17,
+        comment_pressure_threshold: board.18,
+        tags: tags::board_tags(conn, board_id).map_err(|e| db_error(&e.to_string()))?,
         created_at: board.5,
         updated_at: board.6,
     }))
 }
 
-fn load_task_response(
+/// The columns `task_from_row` reads, in order; select them from `tasks t`
+/// joined to `columns c`.
+pub(crate) const TASK_COLUMNS: &str = "t.id, t.board_id, t.column_id, c.name, t.title, t.description,
+    t.priority, t.position, t.created_by, t.assigned_to, t.claimed_by,
+    t.claimed_at, t.labels, t.metadata, t.due_at, t.completed_at, t.archived_at,
+    t.created_at, t.updated_at,
+    (SELECT COUNT(*) FROM task_events te WHERE te.task_id = t.id AND te.event_type = 'comment') as comment_count,
+    t.number, (SELECT task_prefix FROM boards WHERE id = t.board_id) as task_prefix,
+    t.parent_task_id,
+    (SELECT COUNT(*) FROM tasks ch WHERE ch.parent_task_id = t.id) as child_count,
+    (SELECT COUNT(*) FROM tasks ch WHERE ch.parent_task_id = t.id AND ch.completed_at IS NOT NULL) as children_done,
+    (SELECT comment_pressure_threshold FROM boards WHERE id = t.board_id) as comment_pressure_threshold,
+    (SELECT COUNT(*) FROM attachments a WHERE a.task_id = t.id) as attachment_count,
+    (SELECT json_group_array(name) FROM (SELECT name FROM task_watchers w WHERE w.task_id = t.id ORDER BY w.created_at, w.rowid)) as watchers";
+
+pub(crate) fn load_task_response(
     conn: &Connection,
     task_id: &str,
 ) -> Result<Json<TaskResponse>, (Status, Json<ApiError>)> {
     conn.query_row(
-        "SELECT t.id, t.board_id, t.column_id, c.name, t.title, t.description,
-                t.priority, t.position, t.created_by, t.assigned_to, t.claimed_by,
-                t.claimed_at, t.labels, t.metadata, t.due_at, t.completed_at, t.archived_at,
-                t.created_at, t.updated_at,
-                (SELECT COUNT(*) FROM task_events te WHERE te.task_id = t.id AND te.event_type = 'comment') as comment_count
-         FROM tasks t
-         JOIN columns c ON t.column_id = c.id
-         WHERE t.id = ?1",
+        &format!(
+            "SELECT {TASK_COLUMNS}
+             FROM tasks t
+             JOIN columns c ON t.column_id = c.id
+             WHERE t.id = ?1"
+        ),
         rusqlite::params![task_id],
-        row_to_task,
+        task_from_row,
     )
     .map(Json)
     .map_err(|_| not_found("Task"))
 }
 
-fn row_to_task(row: &rusqlite::Row) -> Result<TaskResponse, rusqlite::Error> {
+pub(crate) fn task_from_row(row: &rusqlite::Row) -> Result<TaskResponse, rusqlite::Error> {
     let labels_str: String = row.get(12)?;
     let meta_str: String = row.get(13)?;
 
+    let task_id: String = row.get(0)?;
+    let board_id: String = row.get(1)?;
+
     Ok(TaskResponse {
-        id: row.get(0)?,
-        board_id: row.get(1)?,
         column_id: row.get(2)?,
         column_name: row.get(3)?,
         title: row.get(4)?,
@@ -3141,12 +6521,124 @@ fn row_to_task(row: &rusqlite::Row) -> Result<TaskResponse, rusqlite::Error> {
         created_at: row.get(17)?,
         updated_at: row.get(18)?,
         comment_count: row.get(19).unwrap_or(0),
+        attachment_count: row.get(26).unwrap_or(0),
+        watchers: serde_json::from_str(&row.get::<_, String>(27).unwrap_or_default()).unwrap_or_default(),
+        number: row.get(20).unwrap_or(None),
+        key: row
+            .get::<_, Option<i64>>(20)
+            .unwrap_or(None)
+            .map(|n| task_key(row.get::<_, Option<String>>(21).unwrap_or(None).as_deref(), n)),
+        duplicate: None,
+        parent_task_id: row.get(22).unwrap_or(None),
+        progress: hierarchy::progress(row.get(23).unwrap_or(0), row.get(24).unwrap_or(0)),
+        comment_pressure: comment_pressure::pressure(
+            &board_id,
+            &task_id,
+            row.get(19).unwrap_or(0),
+            row.get(25).unwrap_or(None),
+        ),
+        id: task_id,
+        board_id,
     })
 }
 
+/// Remember every numbered task's current key so it keeps resolving after
+/// the board's numbering or prefix changes. Returns the number of keys recorded.
+fn record_task_key_aliases(conn: &Connection, board_id: &str) -> rusqlite::Result<usize> {
+    conn.execute(
+        "INSERT OR REPLACE INTO task_key_aliases (board_id, key, task_id)
+         SELECT t.board_id, COALESCE(b.task_prefix, ?2) || t.number, t.id
+         FROM tasks t JOIN boards b ON b.id = t.board_id
+         WHERE t.board_id = ?1 AND t.number IS NOT NULL",
+        rusqlite::params![board_id, DEFAULT_TASK_PREFIX],
+    )
+}
+
+/// Prefix shown before task numbers when a board hasn't configured one.
+const DEFAULT_TASK_PREFIX: &str = "#";
+
+/// Human-facing task reference, e.g. `OPS-12` or `#12`.
+pub(crate) fn task_key(prefix: Option<&str>, number: i64) -> String {
+    format!("{}{}", prefix.unwrap_or(DEFAULT_TASK_PREFIX), number)
+}
+
 use rusqlite::Connection;
 
-fn db_error(msg: &str) -> (Status, Json<ApiError>) {
+/// No pooled connection became available in time.
+pub(crate) fn pool_error(e: r2d2::Error) -> (Status, Json<ApiError>) {
+    (
+        Status::ServiceUnavailable,
+        Json(ApiError {
+            error: format!("Database busy: {}", e),
+            code: "DB_UNAVAILABLE".to_string(),
+            status: 503,
+        }),
+    )
+}
+
+/// Check out a pooled connection and run a handler's database work on it,
+/// off the async workers (see `db::blocking`). `f` owns what it uses, as it
+/// runs on another thread.
+pub(crate) async fn with_db<T, E>(
+    db: &DbPool,
+    f: impl FnOnce(&Connection) -> Result<T, E> + Send + 'static,
+) -> Result<T, E>
+where
+    T: Send + 'static,
+    E: From<(Status, Json<ApiError>)> + Send + 'static,
+{
+    let db = db.clone();
+    crate::db::blocking(move || with_db_blocking(&db, f)).await
+}
+
+/// `with_db` in a transaction, committed if `f` succeeds: for writes that
+/// emit events, so the webhook outbox rows they queue are stored with the
+/// change or not at all. Immediate, since handlers read before writing and a
+/// deferred transaction can't take the write lock while the dispatcher holds it.
+pub(crate) async fn with_tx<T, E>(
+    db: &DbPool,
+    f: impl FnOnce(&Connection) -> Result<T, E> + Send + 'static,
+) -> Result<T, E>
+where
+    T: Send + 'static,
+    E: From<(Status, Json<ApiError>)> + Send + 'static,
+{
+    with_db(db, move |conn| {
+        let tx = rusqlite::Transaction::new_unchecked(conn, rusqlite::TransactionBehavior::Immediate)
+            .map_err(|e| db_error(&e.to_string()))?;
+        let value = f(&tx)?;
+        crate::events::commit(tx).map_err(|e| db_error(&e.to_string()))?;
+        Ok(value)
+    })
+    .await
+}
+
+/// `with_db` for code already off the async workers: background threads and
+/// blocking tasks.
+pub(crate) fn with_db_blocking<T, E>(db: &DbPool, f: impl FnOnce(&Connection) -> Result<T, E>) -> Result<T, E>
+where
+    E: From<(Status, Json<ApiError>)>,
+{
+    let started = std::time::Instant::now();
+    let conn = db.get().map_err(pool_error)?;
+    let waited = started.elapsed();
+    let armed = crate::query_guard::arm(&conn);
+    let mut result = f(&conn);
+    crate::events::discard_uncommitted();
+    if let Some(armed) = armed {
+        crate::query_guard::disarm(&conn);
+        // Handlers that skip failed rows would otherwise return partial results.
+        // Nothing commits once it has tripped, so the error is the truth.
+        if armed.tripped() {
+            result = Err(crate::query_guard::timeout_error().into());
+        }
+    }
+    crate::db::DB_STATS.record_checkout(waited, started.elapsed() - waited);
+    crate::db::DB_STATS.collect_cache_stats(&conn);
+    result
+}
+
+pub(crate) fn db_error(msg: &str) -> (Status, Json<ApiError>) {
     (
         Status::InternalServerError,
         Json(ApiError {
@@ -3157,7 +6649,102 @@ fn db_error(msg: &str) -> (Status, Json<ApiError>) {
     )
 }
 
-fn not_found(entity: &str) -> (Status, Json<ApiError>) {
+fn invalid_event_type(error: String) -> (Status, Json<ApiError>) {
+    (
+        Status::BadRequest,
+        Json(ApiError {
+            error,
+            code: "INVALID_EVENT_TYPE".to_string(),
+            status: 400,
+        }),
+    )
+}
+
+fn invalid_template(error: String) -> (Status, Json<ApiError>) {
+    (
+        Status::BadRequest,
+        Json(ApiError {
+            error,
+            code: "INVALID_TEMPLATE".to_string(),
+            status: 400,
+        }),
+    )
+}
+
+/// Webhook filters with labels normalised, or `INVALID_COLUMN` for a column
+/// not on the board.
+fn webhook_filters(
+    conn: &Connection,
+    board_id: &str,
+    mut filters: WebhookFilters,
+) -> Result<WebhookFilters, (Status, Json<ApiError>)> {
+    filters.labels = normalize_labels(&filters.labels);
+    if let Some(ref column_id) = filters.column_id {
+        let column_ok: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM columns WHERE id = ?1 AND board_id = ?2",
+                rusqlite::params![column_id, board_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+        if !column_ok {
+            return Err((
+                Status::BadRequest,
+                Json(ApiError {
+                    error: format!("Column '{}' not found on this board", column_id),
+                    code: "INVALID_COLUMN".to_string(),
+                    status: 400,
+                }),
+            ));
+        }
+    }
+    Ok(filters)
+}
+
+/// The `webhooks.filters` value: NULL when nothing is filtered.
+fn stored_filters(filters: &WebhookFilters) -> Option<String> {
+    (!filters.is_empty()).then(|| serde_json::to_string(filters).unwrap_or_default())
+}
+
+fn invalid_url(error: String) -> (Status, Json<ApiError>) {
+    (
+        Status::BadRequest,
+        Json(ApiError {
+            error,
+            code: "INVALID_URL".to_string(),
+            status: 400,
+        }),
+    )
+}
+
+fn invalid_header(error: String) -> (Status, Json<ApiError>) {
+    (
+        Status::BadRequest,
+        Json(ApiError {
+            error,
+            code: "INVALID_HEADER".to_string(),
+            status: 400,
+        }),
+    )
+}
+
+/// A known webhook kind, normalised, or `INVALID_INPUT`.
+fn webhook_kind(kind: &str) -> Result<String, (Status, Json<ApiError>)> {
+    let kind = kind.trim().to_lowercase();
+    if webhooks::KINDS.contains(&kind.as_str()) {
+        return Ok(kind);
+    }
+    Err((
+        Status::BadRequest,
+        Json(ApiError {
+            error: format!("kind must be one of: {}", webhooks::KINDS.join(", ")),
+            code: "INVALID_INPUT".to_string(),
+            status: 400,
+        }),
+    ))
+}
+
+pub(crate) fn not_found(entity: &str) -> (Status, Json<ApiError>) {
     (
         Status::NotFound,
         Json(ApiError {
@@ -3169,61 +6756,211 @@ fn not_found(entity: &str) -> (Status, Json<ApiError>) {
 }
 
 /// Check if adding a task to a column would exceed its WIP limit.
-fn check_wip_limit(
+pub(crate) fn check_wip_limit(
     conn: &Connection,
     column_id: &str,
     exclude_task_id: Option<&str>,
 ) -> Result<(), (Status, Json<ApiError>)> {
-    let wip_limit: Option<i32> = conn
+    match wip_limit_violation(conn, column_id, exclude_task_id)? {
+        Some(v) => Err((
+            Status::Conflict,
+            Json(ApiError {
+                error: v.error,
+                code: v.code,
+                status: 409,
+            }),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// WIP limit policy: Some(violation) if the column is already at capacity.
+fn wip_limit_violation(
+    conn: &Connection,
+    column_id: &str,
+    exclude_task_id: Option<&str>,
+) -> Result<Option<PolicyViolation>, (Status, Json<ApiError>)> {
+    let (col_name, wip_limit): (String, Option<i32>) = conn
         .query_row(
-            "SELECT wip_limit FROM columns WHERE id = ?1",
+            "SELECT name, wip_limit FROM columns WHERE id = ?1",
             rusqlite::params![column_id],
-            |row| row.get(0),
+            |row| Ok((row.get(0)?, row.get(1)?)),
         )
         .map_err(|_| not_found("Column"))?;
 
-    if let Some(limit) = wip_limit {
-        let current_count: i32 = match exclude_task_id {
-            Some(tid) => conn
-                .query_row(
-                    "SELECT COUNT(*) FROM tasks WHERE column_id = ?1 AND id != ?2",
-                    rusqlite::params![column_id, tid],
-                    |row| row.get(0),
-                )
-                .unwrap_or(0),
-            None => conn
-                .query_row(
-                    "SELECT COUNT(*) FROM tasks WHERE column_id = ?1",
-                    rusqlite::params![column_id],
-                    |row| row.get(0),
-                )
-                .unwrap_or(0),
-        };
+    let Some(limit) = wip_limit else { return Ok(None) };
+    let current_count: i32 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM tasks WHERE column_id = ?1 AND id != ?2",
+            rusqlite::params![column_id, exclude_task_id.unwrap_or("")],
+            |row| row.get(0),
+        )
+        .unwrap_or(0);
 
-        if current_count >= limit {
-            let col_name: String = conn
-                .query_row(
-                    "SELECT name FROM columns WHERE id = ?1",
-                    rusqlite::params![column_id],
-                    |row| row.get(0),
-                )
-                .unwrap_or_else(|_| "unknown".to_string());
+    if current_count >= limit {
+        Ok(Some(PolicyViolation {
+            code: "WIP_LIMIT_EXCEEDED".to_string(),
+            error: format!("Column '{}' has reached its WIP limit of {} tasks", col_name, limit),
+        }))
+    } else {
+        Ok(None)
+    }
+}
 
-            return Err((
-                Status::Conflict,
-                Json(ApiError {
-                    error: format!(
-                        "Column '{}' has reached its WIP limit of {} tasks",
-                        col_name, limit
-                    ),
-                    code: "WIP_LIMIT_EXCEEDED".to_string(),
-                    status: 409,
-                }),
-            ));
+/// Dependency policy: on boards with `enforce_dependencies`, a task can't enter
+/// the done column while any of its blockers is unfinished.
+fn dependency_violation(
+    conn: &Connection,
+    board_id: &str,
+    task_id: &str,
+    target_column_id: &str,
+) -> Result<Option<PolicyViolation>, (Status, Json<ApiError>)> {
+    let (enforced, is_done_column): (bool, bool) = conn
+        .query_row(
+            "SELECT b.enforce_dependencies = 1,
+                    c.position = (SELECT MAX(position) FROM columns WHERE board_id = b.id)
+             FROM boards b JOIN columns c ON c.board_id = b.id
+             WHERE b.id = ?1 AND c.id = ?2",
+            rusqlite::params![board_id, target_column_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => not_found("Column"),
+            e => db_error(&e.to_string()),
+        })?;
+    if !enforced || !is_done_column {
+        return Ok(None);
+    }
+
+    let blockers: Vec<String> = conn
+        .prepare(
+            "SELECT bt.title FROM task_dependencies d
+             JOIN tasks bt ON bt.id = d.blocker_task_id
+             WHERE d.blocked_task_id = ?1 AND bt.completed_at IS NULL",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map(rusqlite::params![task_id], |row| row.get(0))
+                .and_then(|rows| rows.collect())
+        })
+        .map_err(|e| db_error(&e.to_string()))?;
+    if blockers.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(PolicyViolation {
+        code: "BLOCKED_BY_DEPENDENCY".to_string(),
+        error: format!(
+            "Task is blocked by {} unfinished task(s): {}",
+            blockers.len(),
+            blockers.join(", ")
+        ),
+    }))
+}
+
+/// Active (non-archived) task ids per column, in column and position order.
+fn load_layout(conn: &Connection, board_id: &str) -> Result<Vec<LayoutColumn>, (Status, Json<ApiError>)> {
+    let mut columns: Vec<LayoutColumn> = conn
+        .prepare("SELECT id, name FROM columns WHERE board_id = ?1 ORDER BY position ASC")
+        .and_then(|mut stmt| {
+            stmt.query_map(rusqlite::params![board_id], |row| {
+                Ok(LayoutColumn {
+                    column_id: row.get(0)?,
+                    name: row.get(1)?,
+                    task_ids: Vec::new(),
+                })
+            })
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .map_err(|e| db_error(&e.to_string()))?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, column_id FROM tasks WHERE board_id = ?1 AND archived_at IS NULL
+             ORDER BY position ASC, created_at ASC, id ASC",
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+    let rows = stmt
+        .query_map(rusqlite::params![board_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| db_error(&e.to_string()))?;
+    for (task_id, column_id) in rows.filter_map(|r| r.ok()) {
+        if let Some(col) = columns.iter_mut().find(|c| c.column_id == column_id) {
+            col.task_ids.push(task_id);
         }
     }
+    Ok(columns)
+}
 
-    Ok(())
+/// Content-derived layout version: any change in task column or order changes it.
+fn layout_version(columns: &[LayoutColumn]) -> String {
+    let canonical: Vec<String> = columns
+        .iter()
+        .map(|c| format!("{}:{}", c.column_id, c.task_ids.join(",")))
+        .collect();
+    hash_key(&canonical.join(";"))[..16].to_string()
+}
+
+/// Error type for routes that run the composite move validation.
+#[derive(Debug, Responder)]
+pub enum MoveError {
+    Api((Status, Json<ApiError>)),
+    Policy((Status, Json<PolicyViolationsError>)),
+}
+
+impl From<(Status, Json<ApiError>)> for MoveError {
+    fn from(err: (Status, Json<ApiError>)) -> Self {
+        MoveError::Api(err)
+    }
+}
+
+/// Composite validation for moving a task into another column. Evaluates every
+/// applicable policy — the WIP limit and, on boards that enforce them,
+/// dependencies; there are no column transition rules — and returns all
+/// violations in a single 409, so agents can resolve them together instead of
+/// discovering them one retry at a time.
+fn validate_move(
+    conn: &Connection,
+    board_id: &str,
+    task_id: &str,
+    target_column_id: &str,
+) -> Result<(), MoveError> {
+    let mut violations = Vec::new();
+    violations.extend(wip_limit_violation(conn, target_column_id, Some(task_id))?);
+    violations.extend(dependency_violation(conn, board_id, task_id, target_column_id)?);
+
+    if violations.is_empty() {
+        return Ok(());
+    }
+    Err(policy_error(violations))
+}
+
+/// Build the single 409 response listing every violation; `codes` lists each
+/// violated policy once, `violations` has one entry per column or blocked task.
+fn policy_error(violations: Vec<PolicyViolation>) -> MoveError {
+    MoveError::Policy((Status::Conflict, Json(policy_violations(violations))))
+}
+
+/// The 409 body for a non-empty list of violations.
+fn policy_violations(violations: Vec<PolicyViolation>) -> PolicyViolationsError {
+    let mut codes: Vec<String> = Vec::new();
+    for violation in &violations {
+        if !codes.contains(&violation.code) {
+            codes.push(violation.code.clone());
+        }
+    }
+    let error = if violations.len() == 1 {
+        violations[0].error.clone()
+    } else {
+        let errors: Vec<&str> = violations.iter().map(|v| v.error.as_str()).collect();
+        format!("{} policy violations: {}", violations.len(), errors.join("; "))
+    };
+    PolicyViolationsError {
+        error,
+        code: codes[0].clone(),
+        status: 409,
+        codes,
+        violations,
+    }
 }
 
 // ============ Tests ============
@@ -3250,6 +6987,83 @@ mod tests {
         let result = normalize_labels(&input);
         assert_eq!(result, vec!["bug-fix", "feature"]);
     }
+
+    #[test]
+    fn test_back_schedule_takes_earliest_constraint() {
+        // a → b → target, and a → target directly
+        let edges = vec![
+            ("a".to_string(), "b".to_string()),
+            ("b".to_string(), "target".to_string()),
+            ("a".to_string(), "target".to_string()),
+            ("done".to_string(), "b".to_string()),
+        ];
+        let skip = HashSet::from(["done".to_string()]);
+        let deadline = parse_deadline("2026-03-10").unwrap();
+        let plan = back_schedule("target", deadline, &edges, &skip, |id| if id == "target" { 24.0 } else { 8.0 });
+
+        let ids: Vec<&str> = plan.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "target"]);
+        let hours_before = |id: &str| {
+            let due = plan.iter().find(|(t, _)| t == id).unwrap().1;
+            (deadline - due).num_hours()
+        };
+        assert_eq!(hours_before("target"), 0);
+        assert_eq!(hours_before("b"), 24);
+        // a must finish before b starts (24 + 8), not merely before target starts
+        assert_eq!(hours_before("a"), 32);
+    }
+
+    #[test]
+    fn test_parse_deadline() {
+        assert_eq!(
+            parse_deadline("2026-03-10").unwrap().format("%Y-%m-%dT%H:%M:%S").to_string(),
+            "2026-03-10T23:59:59"
+        );
+        assert_eq!(
+            parse_deadline("2026-03-10T12:00:00+02:00").unwrap().format("%H:%M").to_string(),
+            "10:00"
+        );
+        assert!(parse_deadline("next tuesday").is_none());
+    }
+    #[test]
+    fn test_fts_query() {
+        assert_eq!(fts_query("login bug").unwrap(), r#""login" "bug""#);
+        assert_eq!(fts_query("auth*").unwrap(), r#""auth"*"#);
+        assert_eq!(fts_query(r#""fix login" OR deploy"#).unwrap(), r#""fix login" OR "deploy""#);
+        assert_eq!(fts_query(r#""release notes"*"#).unwrap(), r#""release notes"*"#);
+        // FTS5 syntax in user input is quoted, not interpreted
+        assert_eq!(fts_query("title:x NEAR(a b)").unwrap(), r#""title:x" "NEAR(a" "b)""#);
+        assert_eq!(fts_query(r#"say "hi"#).unwrap(), r#""say" "hi""#);
+        assert_eq!(fts_query("OR foo OR").unwrap(), r#""foo""#);
+        assert!(fts_query("*** --").is_none());
+    }
+
+    #[test]
+    fn test_parse_scoped_query() {
+        let q = parse_scoped_query(r#"title:login label:bug meta.Repo:kanban "fix it"* http://x"#);
+        assert_eq!(q.text, r#""fix it"* http://x"#);
+        assert_eq!(q.columns, vec![("title", "login".to_string())]);
+        assert_eq!(q.labels, vec!["bug".to_string()]);
+        assert_eq!(q.meta, vec![(r#"$."Repo""#.to_string(), "kanban".to_string())]);
+
+        let q = parse_scoped_query(r#"description:"rotate secret" comments:auth* meta.gh.pr:12"#);
+        assert!(q.text.is_empty());
+        assert_eq!(
+            q.match_expr(true).unwrap().unwrap(),
+            r#"description : ("rotate secret") comments : ("auth"*)"#
+        );
+        assert_eq!(q.meta[0].0, r#"$."gh"."pr""#);
+
+        // Free text can be kept out of comments; label-only queries skip FTS
+        let q = parse_scoped_query("deploy label: label:ops");
+        assert_eq!(q.text, "deploy label:");
+        assert_eq!(
+            q.match_expr(false).unwrap().unwrap(),
+            r#"{title description labels} : ("deploy" "label:")"#
+        );
+        assert!(parse_scoped_query("label:ops").match_expr(true).unwrap().is_none());
+        assert!(parse_scoped_query(r#"title:"--""#).match_expr(true).is_err());
+    }
 }
 
 // ============ SPA Fallback ============
@@ -0,0 +1,179 @@
+//! Scoped tokens: least-privilege keys for worker agents. Each is minted by
+//! an admin with a list of the writes it may make (`create_task`,
+//! `update_task`, `move_task`, `comment`, `claim`), optionally limited to
+//! some columns, and can read the board like a share key. A token with no
+//! actions is read-only. Keys are hashed and shown once; the scope itself is
+//! enforced by `access::require_action`.
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::access::{self, ScopeAction};
+use crate::auth::{Actor, BoardToken};
+use crate::db::{hash_key, DbPool};
+use crate::models::*;
+use crate::routes::{db_error, not_found, with_db};
+
+const MAX_NAME_CHARS: usize = 100;
+
+fn invalid(error: String, code: &str) -> (Status, Json<ApiError>) {
+    (
+        Status::BadRequest,
+        Json(ApiError {
+            error,
+            code: code.to_string(),
+            status: 400,
+        }),
+    )
+}
+
+fn token_from_row(board_id: &str, row: &rusqlite::Row) -> rusqlite::Result<ScopedTokenResponse> {
+    let actions: String = row.get(2)?;
+    let columns: String = row.get(3)?;
+    Ok(ScopedTokenResponse {
+        id: row.get(0)?,
+        board_id: board_id.to_string(),
+        name: row.get(1)?,
+        actions: serde_json::from_str(&actions).unwrap_or_default(),
+        columns: serde_json::from_str(&columns).unwrap_or_default(),
+        key: None,
+        created_by: row.get(4)?,
+        created_at: row.get(5)?,
+        expires_at: row.get(6)?,
+    })
+}
+
+fn load(conn: &Connection, board_id: &str, token_id: &str) -> Result<ScopedTokenResponse, (Status, Json<ApiError>)> {
+    conn.query_row(
+        "SELECT id, name, actions, columns, created_by, created_at, expires_at FROM scoped_tokens WHERE id = ?1 AND board_id = ?2",
+        rusqlite::params![token_id, board_id],
+        |row| token_from_row(board_id, row),
+    )
+    .optional()
+    .map_err(|e| db_error(&e.to_string()))?
+    .ok_or_else(|| not_found("Scoped token"))
+}
+
+/// Column references (ids or case-insensitive names) as column ids.
+fn resolve_columns(conn: &Connection, board_id: &str, refs: &[String]) -> Result<Vec<String>, (Status, Json<ApiError>)> {
+    let columns: Vec<(String, String)> = conn
+        .prepare("SELECT id, name FROM columns WHERE board_id = ?1 ORDER BY position ASC")
+        .and_then(|mut stmt| {
+            stmt.query_map(rusqlite::params![board_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect()
+        })
+        .map_err(|e| db_error(&e.to_string()))?;
+    let mut ids: Vec<String> = Vec::new();
+    for reference in refs {
+        let reference = reference.trim();
+        let (id, _) = columns
+            .iter()
+            .find(|(id, name)| id == reference || name.eq_ignore_ascii_case(reference))
+            .ok_or_else(|| invalid(format!("No column '{}' on this board", reference), "INVALID_COLUMN"))?;
+        if !ids.contains(id) {
+            ids.push(id.clone());
+        }
+    }
+    Ok(ids)
+}
+
+/// The board's scoped tokens — requires admin. Keys themselves aren't returned.
+#[get("/boards/<board_id>/tokens")]
+pub async fn list_scoped_tokens(
+    board_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<Vec<ScopedTokenResponse>>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, name, actions, columns, created_by, created_at, expires_at FROM scoped_tokens
+                 WHERE board_id = ?1 ORDER BY created_at ASC, rowid ASC",
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        let tokens = stmt
+            .query_map(rusqlite::params![board_id], |row| token_from_row(board_id, row))
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(Json(tokens))
+    })
+    .await
+}
+
+/// Mint a scoped token — requires admin. The key is in the response only.
+#[post("/boards/<board_id>/tokens", format = "json", data = "<req>")]
+pub async fn create_scoped_token(
+    board_id: &str,
+    req: Json<CreateScopedTokenRequest>,
+    token: BoardToken,
+    actor: Actor,
+    db: &State<DbPool>,
+) -> Result<Json<ScopedTokenResponse>, (Status, Json<ApiError>)> {
+    let req = req.into_inner();
+    let name = req.name.trim().to_owned();
+    if name.chars().count() > MAX_NAME_CHARS {
+        return Err(invalid(format!("name must be at most {} characters", MAX_NAME_CHARS), "INVALID_INPUT"));
+    }
+    let mut actions: Vec<ScopeAction> = Vec::new();
+    for action in req.actions {
+        if !actions.contains(&action) {
+            actions.push(action);
+        }
+    }
+    let expires_at = access::key_expiry(req.expires_at.as_deref(), req.ttl_seconds)?;
+
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        let columns = resolve_columns(conn, board_id, &req.columns)?;
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let key = format!("kb_{}", uuid::Uuid::new_v4().to_string().replace('-', ""));
+        conn.execute(
+            "INSERT INTO scoped_tokens (id, board_id, name, actions, columns, key_hash, created_by, expires_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            rusqlite::params![
+                id,
+                board_id,
+                name,
+                serde_json::to_string(&actions).unwrap_or_else(|_| "[]".to_string()),
+                serde_json::to_string(&columns).unwrap_or_else(|_| "[]".to_string()),
+                hash_key(&key),
+                actor.name(),
+                expires_at
+            ],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        let mut scoped = load(conn, board_id, &id)?;
+        scoped.key = Some(key);
+        Ok(Json(scoped))
+    })
+    .await
+}
+
+/// Revoke a scoped token; it stops working at once — requires admin.
+#[delete("/boards/<board_id>/tokens/<token_id>")]
+pub async fn revoke_scoped_token(
+    board_id: &str,
+    token_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
+    let (board_id, token_id) = (board_id.to_owned(), token_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, token_id) = (board_id.as_str(), token_id.as_str());
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        load(conn, board_id, token_id)?;
+        conn.execute("DELETE FROM scoped_tokens WHERE id = ?1", rusqlite::params![token_id])
+            .map_err(|e| db_error(&e.to_string()))?;
+        Ok(Json(serde_json::json!({"deleted": true, "id": token_id})))
+    })
+    .await
+}
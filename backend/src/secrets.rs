@@ -0,0 +1,103 @@
+//! Encryption for secrets the server has to send on rather than just check,
+//! so a hash won't do — e.g. webhook custom headers. Values are sealed with
+//! ChaCha20-Poly1305 under a key derived from `SECRETS_KEY`. Without it, a
+//! random key is generated and kept in the database (`server_keys`): values
+//! stay out of plain sight in their tables, but anyone holding the whole file
+//! can read them, so production setups should set `SECRETS_KEY`.
+
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rusqlite::{Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+
+/// Prefix of a sealed value, naming the format.
+const PREFIX: &str = "v1:";
+const NONCE_LEN: usize = 12;
+
+/// `SECRETS_KEY`, if configured.
+fn configured_key() -> Option<String> {
+    std::env::var("SECRETS_KEY").ok().filter(|k| !k.is_empty())
+}
+
+/// The random server key stored under `name` in `server_keys`, generated on
+/// first use.
+pub fn server_key(conn: &Connection, name: &str) -> rusqlite::Result<String> {
+    let stored: Option<String> = conn
+        .query_row("SELECT value FROM server_keys WHERE name = ?1", rusqlite::params![name], |row| row.get(0))
+        .optional()?;
+    match stored {
+        Some(key) => Ok(key),
+        None => {
+            let key = hex::encode(rand::random::<[u8; 32]>());
+            // A concurrent first use may have won; keep whichever is stored
+            conn.execute(
+                "INSERT OR IGNORE INTO server_keys (name, value) VALUES (?1, ?2)",
+                rusqlite::params![name, key],
+            )?;
+            conn.query_row("SELECT value FROM server_keys WHERE name = ?1", rusqlite::params![name], |row| row.get(0))
+        }
+    }
+}
+
+fn cipher(conn: &Connection) -> rusqlite::Result<ChaCha20Poly1305> {
+    let secret = match configured_key() {
+        Some(key) => key,
+        None => server_key(conn, "secrets")?,
+    };
+    let key = Sha256::digest(secret.as_bytes());
+    Ok(ChaCha20Poly1305::new(Key::from_slice(&key)))
+}
+
+/// Seal `plaintext` for storage.
+pub fn encrypt(conn: &Connection, plaintext: &str) -> rusqlite::Result<String> {
+    let nonce = rand::random::<[u8; NONCE_LEN]>();
+    let sealed = cipher(conn)?
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_bytes())
+        .map_err(|_| rusqlite::Error::InvalidQuery)?;
+    Ok(format!("{}{}{}", PREFIX, hex::encode(nonce), hex::encode(sealed)))
+}
+
+/// Open a value sealed by `encrypt`. Fails if it was sealed under another key,
+/// e.g. after `SECRETS_KEY` changed.
+pub fn decrypt(conn: &Connection, stored: &str) -> Result<String, String> {
+    let bytes = stored
+        .strip_prefix(PREFIX)
+        .and_then(|hex_str| hex::decode(hex_str).ok())
+        .filter(|bytes| bytes.len() > NONCE_LEN)
+        .ok_or("not an encrypted value")?;
+    let (nonce, sealed) = bytes.split_at(NONCE_LEN);
+    let plain = cipher(conn)
+        .map_err(|e| e.to_string())?
+        .decrypt(Nonce::from_slice(nonce), sealed)
+        .map_err(|_| "cannot be decrypted with the current key (was SECRETS_KEY changed?)")?;
+    String::from_utf8(plain).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE server_keys (name TEXT PRIMARY KEY, value TEXT NOT NULL)").unwrap();
+        let sealed = encrypt(&conn, "Bearer abc").unwrap();
+        assert!(sealed.starts_with(PREFIX));
+        assert!(!sealed.contains("Bearer"));
+        assert_ne!(sealed, encrypt(&conn, "Bearer abc").unwrap(), "fresh nonce each time");
+        assert_eq!(decrypt(&conn, &sealed).unwrap(), "Bearer abc");
+
+        let mut tampered = sealed.clone();
+        let flipped = if sealed.ends_with('0') { "1" } else { "0" };
+        tampered.replace_range(sealed.len() - 1.., flipped);
+        assert!(decrypt(&conn, &tampered).is_err());
+        assert!(decrypt(&conn, "Bearer abc").is_err());
+
+        // Another database has another generated key
+        let other = Connection::open_in_memory().unwrap();
+        other.execute_batch("CREATE TABLE server_keys (name TEXT PRIMARY KEY, value TEXT NOT NULL)").unwrap();
+        if configured_key().is_none() {
+            assert!(decrypt(&other, &sealed).is_err());
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Share keys: extra keys that can read a board but change nothing, for
+//! observers of private boards — dashboards, stakeholders, reporting bots.
+//! They're hashed like the manage key, shown once, and revoked by deleting
+//! them. Anyone who needs to write gets a collaborator key instead.
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::access::{self, BoardRole};
+use crate::auth::{Actor, BoardToken};
+use crate::db::{hash_key, DbPool};
+use crate::models::*;
+use crate::routes::{db_error, not_found, with_db};
+
+const MAX_NAME_CHARS: usize = 100;
+
+fn invalid(error: String) -> (Status, Json<ApiError>) {
+    (
+        Status::BadRequest,
+        Json(ApiError {
+            error,
+            code: "INVALID_INPUT".to_string(),
+            status: 400,
+        }),
+    )
+}
+
+fn key_from_row(board_id: &str, row: &rusqlite::Row) -> rusqlite::Result<ShareKeyResponse> {
+    let role: String = row.get(2)?;
+    Ok(ShareKeyResponse {
+        id: row.get(0)?,
+        board_id: board_id.to_string(),
+        name: row.get(1)?,
+        role: BoardRole::parse(&role).unwrap_or(BoardRole::Read),
+        key: None,
+        created_by: row.get(3)?,
+        created_at: row.get(4)?,
+        expires_at: row.get(5)?,
+    })
+}
+
+fn load(conn: &Connection, board_id: &str, key_id: &str) -> Result<ShareKeyResponse, (Status, Json<ApiError>)> {
+    conn.query_row(
+        "SELECT id, name, role, created_by, created_at, expires_at FROM share_keys WHERE id = ?1 AND board_id = ?2",
+        rusqlite::params![key_id, board_id],
+        |row| key_from_row(board_id, row),
+    )
+    .optional()
+    .map_err(|e| db_error(&e.to_string()))?
+    .ok_or_else(|| not_found("Share key"))
+}
+
+/// The board's share keys — requires admin. Keys themselves aren't returned.
+#[get("/boards/<board_id>/keys")]
+pub async fn list_share_keys(
+    board_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<Vec<ShareKeyResponse>>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, name, role, created_by, created_at, expires_at FROM share_keys
+                 WHERE board_id = ?1 ORDER BY created_at ASC, rowid ASC",
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        let keys = stmt
+            .query_map(rusqlite::params![board_id], |row| key_from_row(board_id, row))
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(Json(keys))
+    })
+    .await
+}
+
+/// Create a read-only key for the board — requires admin. The key is in the
+/// response only.
+#[post("/boards/<board_id>/keys", format = "json", data = "<req>")]
+pub async fn create_share_key(
+    board_id: &str,
+    req: Json<CreateShareKeyRequest>,
+    token: BoardToken,
+    actor: Actor,
+    db: &State<DbPool>,
+) -> Result<Json<ShareKeyResponse>, (Status, Json<ApiError>)> {
+    let req = req.into_inner();
+    let name = req.name.trim().to_owned();
+    if name.chars().count() > MAX_NAME_CHARS {
+        return Err(invalid(format!("name must be at most {} characters", MAX_NAME_CHARS)));
+    }
+    if req.role != BoardRole::Read {
+        return Err(invalid(
+            "Share keys are read-only (role \"read\"); add a collaborator for anything more".to_string(),
+        ));
+    }
+    let expires_at = access::key_expiry(req.expires_at.as_deref(), req.ttl_seconds)?;
+
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        let id = uuid::Uuid::new_v4().to_string();
+        let key = format!("kb_{}", uuid::Uuid::new_v4().to_string().replace('-', ""));
+        conn.execute(
+            "INSERT INTO share_keys (id, board_id, name, role, key_hash, created_by, expires_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![id, board_id, name, req.role.as_str(), hash_key(&key), actor.name(), expires_at],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        let mut share_key = load(conn, board_id, &id)?;
+        share_key.key = Some(key);
+        Ok(Json(share_key))
+    })
+    .await
+}
+
+/// Revoke a share key; it stops working at once — requires admin.
+#[delete("/boards/<board_id>/keys/<key_id>")]
+pub async fn revoke_share_key(
+    board_id: &str,
+    key_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
+    let (board_id, key_id) = (board_id.to_owned(), key_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, key_id) = (board_id.as_str(), key_id.as_str());
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        load(conn, board_id, key_id)?;
+        conn.execute("DELETE FROM share_keys WHERE id = ?1", rusqlite::params![key_id])
+            .map_err(|e| db_error(&e.to_string()))?;
+        Ok(Json(serde_json::json!({"deleted": true, "id": key_id})))
+    })
+    .await
+}
@@ -0,0 +1,181 @@
+//! Share links: URLs for external reviewers, carrying a token signed with a
+//! per-board secret. The token names its scope (`read`, or `comment` to also
+//! comment on tasks) and its expiry, so checking one is an HMAC and a read of
+//! the secret — nothing is stored per link. Individual links can't be
+//! revoked; rotating the secret (`DELETE /boards/<id>/share-links`) revokes
+//! them all.
+
+use rand::RngCore;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use serde::{Deserialize, Serialize};
+
+use crate::access::{self, BoardRole, ScopeAction};
+use crate::auth::BoardToken;
+use crate::db::{hash_key, DbPool};
+use crate::models::*;
+use crate::routes::{db_error, with_db};
+use crate::signing;
+
+/// How long a link works when the request doesn't say.
+const DEFAULT_TTL_SECONDS: i64 = 7 * 24 * 3600;
+
+/// What a share link lets its holder do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShareLinkScope {
+    #[default]
+    Read,
+    /// Read, and comment on tasks
+    Comment,
+}
+
+impl ShareLinkScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ShareLinkScope::Read => "read",
+            ShareLinkScope::Comment => "comment",
+        }
+    }
+
+    pub fn parse(scope: &str) -> Option<Self> {
+        match scope {
+            "read" => Some(ShareLinkScope::Read),
+            "comment" => Some(ShareLinkScope::Comment),
+            _ => None,
+        }
+    }
+
+    /// Allow `action` or refuse it with `403 OUT_OF_SCOPE`.
+    pub fn require(self, action: ScopeAction) -> Result<(), (Status, Json<ApiError>)> {
+        if self == ShareLinkScope::Comment && action == ScopeAction::Comment {
+            return Ok(());
+        }
+        Err((
+            Status::Forbidden,
+            Json(ApiError {
+                error: format!("This share link can't {}", action.as_str()),
+                code: "OUT_OF_SCOPE".to_string(),
+                status: 403,
+            }),
+        ))
+    }
+}
+
+fn sign(secret: &str, board_id: &str, scope: ShareLinkScope, expires: i64) -> String {
+    let message = format!("{}:{}:{}", board_id, scope.as_str(), expires);
+    hex::encode(signing::hmac(secret.as_bytes(), message.as_bytes()))
+}
+
+fn board_secret(conn: &rusqlite::Connection, board_id: &str) -> Option<String> {
+    conn.query_row(
+        "SELECT share_link_secret FROM boards WHERE id = ?1",
+        rusqlite::params![board_id],
+        |row| row.get(0),
+    )
+    .unwrap_or(None)
+}
+
+/// The scope of a share link token for this board. None when `token` isn't
+/// a share link at all; 401 `LINK_EXPIRED` once it has expired and 403
+/// `INVALID_KEY` when the signature doesn't match (another board's link, or
+/// one revoked by rotating the secret).
+pub fn check(
+    conn: &rusqlite::Connection,
+    board_id: &str,
+    token: &str,
+) -> Option<Result<ShareLinkScope, (Status, Json<ApiError>)>> {
+    let parts = token.strip_prefix("sl_")?;
+    let refuse = |status: Status, error: &str, code: &str| {
+        Err((
+            status,
+            Json(ApiError {
+                error: error.to_string(),
+                code: code.to_string(),
+                status: status.code,
+            }),
+        ))
+    };
+    let mut parts = parts.splitn(3, '.');
+    let (Some(scope), Some(expires), Some(signature)) = (parts.next(), parts.next(), parts.next()) else {
+        return Some(refuse(Status::Forbidden, "Malformed share link", "INVALID_KEY"));
+    };
+    let (Some(scope), Ok(expires)) = (ShareLinkScope::parse(scope), expires.parse::<i64>()) else {
+        return Some(refuse(Status::Forbidden, "Malformed share link", "INVALID_KEY"));
+    };
+    let valid = board_secret(conn, board_id).is_some_and(|secret| {
+        signing::same(sign(&secret, board_id, scope, expires).as_bytes(), signature.as_bytes())
+    });
+    if !valid {
+        return Some(refuse(Status::Forbidden, "This share link isn't valid for this board", "INVALID_KEY"));
+    }
+    if expires <= chrono::Utc::now().timestamp() {
+        return Some(refuse(Status::Unauthorized, "This share link has expired", "LINK_EXPIRED"));
+    }
+    Some(Ok(scope))
+}
+
+/// Create a signed share link — requires admin. Links expire after 7 days
+/// unless `expires_at` or `ttl_seconds` says otherwise.
+#[post("/boards/<board_id>/share-links", format = "json", data = "<req>")]
+pub async fn create_share_link(
+    board_id: &str,
+    req: Json<CreateShareLinkRequest>,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<ShareLinkResponse>, (Status, Json<ApiError>)> {
+    let ttl = req.ttl_seconds.or(req.expires_at.is_none().then_some(DEFAULT_TTL_SECONDS));
+    let expires = access::key_expiry(req.expires_at.as_deref(), ttl)?
+        .and_then(|at| chrono::NaiveDateTime::parse_from_str(&at, "%Y-%m-%d %H:%M:%S").ok())
+        .map(|at| at.and_utc().timestamp())
+        .unwrap_or_default();
+
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Admin)?;
+        let secret = match board_secret(conn, board_id) {
+            Some(secret) => secret,
+            None => rotate_secret(conn, board_id)?,
+        };
+        let link = format!("sl_{}.{}.{}", req.scope.as_str(), expires, sign(&secret, board_id, req.scope, expires));
+        Ok(Json(ShareLinkResponse {
+            board_id: board_id.to_string(),
+            scope: req.scope,
+            url: format!("/board/{}?key={}", board_id, link),
+            token: link,
+            expires_at: chrono::DateTime::from_timestamp(expires, 0).unwrap_or_default().to_rfc3339(),
+        }))
+    })
+    .await
+}
+
+/// Revoke every share link the board has handed out — requires admin.
+#[delete("/boards/<board_id>/share-links")]
+pub async fn revoke_share_links(
+    board_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Admin)?;
+        rotate_secret(conn, board_id)?;
+        Ok(Json(serde_json::json!({"revoked": true, "board_id": board_id})))
+    })
+    .await
+}
+
+fn rotate_secret(conn: &rusqlite::Connection, board_id: &str) -> Result<String, (Status, Json<ApiError>)> {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let secret = hex::encode(bytes);
+    conn.execute(
+        "UPDATE boards SET share_link_secret = ?1 WHERE id = ?2",
+        rusqlite::params![secret, board_id],
+    )
+    .map_err(|e| db_error(&e.to_string()))?;
+    Ok(secret)
+}
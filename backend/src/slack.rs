@@ -0,0 +1,213 @@
+//! Slack webhook targets. A webhook created with `"kind": "slack"` points at
+//! a Slack incoming-webhook URL and receives `task.*` events as Block Kit
+//! messages — task title, what happened (column transitions spelled out),
+//! who did it and a link to the board — instead of the raw event JSON.
+//! The lookups and summary line are shared with the other chat targets.
+
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::event_types;
+use crate::events::BoardEvent;
+
+/// Whether a chat target is sent `event`: only task events are.
+pub fn formats(event: &BoardEvent) -> bool {
+    event.event.starts_with("task.")
+}
+
+/// How a chat service marks text up, so the same summary works for each.
+pub(crate) struct Markup {
+    /// Make user text safe to embed
+    pub escape: fn(&str) -> String,
+    /// Embolden (already escaped) text
+    pub bold: fn(&str) -> String,
+    /// Block-quote (already escaped) text
+    pub quote: fn(&str) -> String,
+}
+
+const SLACK: Markup = Markup { escape, bold: slack_bold, quote: quote_lines };
+
+/// Base URL board links point at, from `PUBLIC_URL`. Without it the
+/// message names the board but doesn't link it.
+pub(crate) fn public_url() -> Option<String> {
+    std::env::var("PUBLIC_URL")
+        .ok()
+        .map(|u| u.trim().trim_end_matches('/').to_string())
+        .filter(|u| !u.is_empty())
+}
+
+/// Escape text for Slack `mrkdwn`, which treats `&`, `<` and `>` as markup.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn slack_bold(text: &str) -> String {
+    format!("*{}*", text)
+}
+
+/// `> `-prefixed lines, the quote syntax Slack and Discord share.
+pub(crate) fn quote_lines(text: &str) -> String {
+    text.lines().map(|l| format!("> {}", l)).collect::<Vec<_>>().join("\n")
+}
+
+/// Who caused the event, when its data says.
+pub(crate) fn actor(data: &serde_json::Value) -> Option<&str> {
+    ["actor", "creator"]
+        .iter()
+        .find_map(|key| data[key].as_str())
+        .filter(|a| !a.is_empty())
+}
+
+/// One line saying what happened to the task (comments add the quoted text).
+pub(crate) fn summary(conn: &Connection, event: &BoardEvent, markup: &Markup) -> String {
+    let Markup { escape, bold, quote } = markup;
+    let data = &event.data;
+    let column = |id: &serde_json::Value| -> String {
+        id.as_str()
+            .and_then(|id| {
+                conn.query_row("SELECT name FROM columns WHERE id = ?1", rusqlite::params![id], |row| row.get(0))
+                    .ok()
+            })
+            .unwrap_or_else(|| "?".to_string())
+    };
+    match event.event.as_str() {
+        event_types::TASK_CREATED => format!("Created in {}", bold(&escape(&column(&data["column_id"])))),
+        event_types::TASK_MOVED => {
+            let name = |key: &str, id: &str| data[key].as_str().map(str::to_string).unwrap_or_else(|| column(&data[id]));
+            format!("{} → {}", bold(&escape(&name("from_column", "from"))), bold(&escape(&name("to_column", "to"))))
+        }
+        event_types::TASK_REORDERED if data["column_id"] != data["from_column"] => format!(
+            "{} → {}",
+            bold(&escape(&column(&data["from_column"]))),
+            bold(&escape(&column(&data["column_id"])))
+        ),
+        event_types::TASK_ASSIGNED => match data["assigned_to"].as_str() {
+            Some(name) => format!("Assigned to {}", bold(&escape(name))),
+            None => "Unassigned".to_string(),
+        },
+        event_types::TASK_COMMENT => {
+            let message = data["message"].as_str().unwrap_or_default();
+            format!("Commented:\n{}", quote(&escape(message)))
+        }
+        event_types::TASK_UPDATED => {
+            let mut fields: Vec<&str> = data
+                .as_object()
+                .map(|o| o.keys().map(String::as_str).filter(|k| !matches!(*k, "task_id" | "actor")).collect())
+                .unwrap_or_default();
+            fields.sort_unstable();
+            format!("Updated {}", fields.join(", "))
+        }
+        name => event_types::EVENT_TYPES
+            .iter()
+            .find(|t| t.name == name)
+            .map(|t| t.description.to_string())
+            .unwrap_or_else(|| name.to_string()),
+    }
+}
+
+/// The event's task title; a deleted task falls back to the title in the
+/// event, if any.
+pub(crate) fn task_title(conn: &Connection, event: &BoardEvent) -> String {
+    let data = &event.data;
+    data["task_id"]
+        .as_str()
+        .and_then(|id| {
+            conn.query_row("SELECT title FROM tasks WHERE id = ?1", rusqlite::params![id], |row| row.get(0))
+                .optional()
+                .ok()
+                .flatten()
+        })
+        .or_else(|| data["title"].as_str().map(str::to_string))
+        .unwrap_or_else(|| "A task".to_string())
+}
+
+pub(crate) fn board_name(conn: &Connection, board_id: &str) -> String {
+    conn.query_row("SELECT name FROM boards WHERE id = ?1", rusqlite::params![board_id], |row| row.get(0))
+        .unwrap_or_else(|_| board_id.to_string())
+}
+
+/// The Block Kit message for a task event. Task, board and column names are
+/// read from the database.
+pub fn message(conn: &Connection, event: &BoardEvent) -> serde_json::Value {
+    let data = &event.data;
+    let title = task_title(conn, event);
+    let board = board_name(conn, &event.board_id);
+
+    let summary = summary(conn, event, &SLACK);
+    let heading = match public_url() {
+        Some(base) => format!("*<{}/board/{}|{}>*", base, event.board_id, escape(&title)),
+        None => format!("*{}*", escape(&title)),
+    };
+    let mut context = vec![format!("Board *{}*", escape(&board))];
+    if let Some(actor) = actor(data) {
+        context.push(format!("by {}", escape(actor)));
+    }
+    context.push(format!("`{}`", event.event));
+
+    serde_json::json!({
+        "text": format!("{}: {}", escape(&title), summary.lines().next().unwrap_or_default()),
+        "blocks": [
+            {
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": format!("{}\n{}", heading, summary) },
+            },
+            {
+                "type": "context",
+                "elements": [{ "type": "mrkdwn", "text": context.join(" · ") }],
+            },
+        ],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conn() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE boards (id TEXT, name TEXT);
+             CREATE TABLE columns (id TEXT, name TEXT);
+             CREATE TABLE tasks (id TEXT, title TEXT);
+             INSERT INTO boards VALUES ('b1', 'Launch');
+             INSERT INTO columns VALUES ('c1', 'Todo'), ('c2', 'Done');
+             INSERT INTO tasks VALUES ('t1', 'Fix <login> & signup');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn event(name: &str, data: serde_json::Value) -> BoardEvent {
+        BoardEvent {
+            event: name.to_string(),
+            board_id: "b1".to_string(),
+            data,
+        }
+    }
+
+    #[test]
+    fn test_message_blocks_for_moves_and_comments() {
+        let conn = conn();
+        let moved = message(
+            &conn,
+            &event(
+                event_types::TASK_MOVED,
+                serde_json::json!({"task_id": "t1", "from": "c1", "to": "c2", "from_column": "Todo", "to_column": "Done"}),
+            ),
+        );
+        let section = moved["blocks"][0]["text"]["text"].as_str().unwrap();
+        assert!(section.contains("Fix &lt;login&gt; &amp; signup"));
+        assert!(section.ends_with("*Todo* → *Done*"));
+        assert_eq!(moved["blocks"][1]["elements"][0]["text"], "Board *Launch* · `task.moved`");
+        assert_eq!(moved["text"], "Fix &lt;login&gt; &amp; signup: *Todo* → *Done*");
+
+        let comment = message(
+            &conn,
+            &event(event_types::TASK_COMMENT, serde_json::json!({"task_id": "t1", "actor": "Sam", "message": "ok\nshipping"})),
+        );
+        assert!(comment["blocks"][0]["text"]["text"].as_str().unwrap().ends_with("Commented:\n> ok\n> shipping"));
+        assert_eq!(comment["blocks"][1]["elements"][0]["text"], "Board *Launch* · by Sam · `task.comment`");
+
+        assert!(formats(&event(event_types::TASK_CLAIMED, serde_json::json!({}))));
+        assert!(!formats(&event(event_types::BOARD_MERGED, serde_json::json!({}))));
+    }
+}
@@ -0,0 +1,135 @@
+//! Instance-wide board tags ("project-apollo", "team-infra") for organizing
+//! large installations. Admins curate the directory; board owners apply tags
+//! from it, and `GET /boards?tag=` filters by them. Separate from task labels.
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::Connection;
+
+use crate::db::DbPool;
+use crate::models::*;
+use crate::routes::{db_error, with_db};
+
+/// Longest tag name.
+pub const MAX_TAG_LEN: usize = 50;
+/// Most tags one board can carry.
+pub const MAX_TAGS_PER_BOARD: usize = 20;
+
+fn invalid(error: String, code: &str) -> (Status, Json<ApiError>) {
+    (Status::BadRequest, Json(ApiError {
+        error,
+        code: code.to_string(),
+        status: 400,
+    }))
+}
+
+/// Lowercase and validate a tag name: letters, digits, '-' and '_'.
+pub fn normalize_tag(raw: &str) -> Result<String, (Status, Json<ApiError>)> {
+    let tag = raw.trim().to_lowercase();
+    if tag.is_empty() || tag.len() > MAX_TAG_LEN || !tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(invalid(
+            format!("Invalid tag '{}': use 1-{} letters, digits, '-' or '_'", raw.trim(), MAX_TAG_LEN),
+            "INVALID_TAG",
+        ));
+    }
+    Ok(tag)
+}
+
+/// A board's tags, alphabetically.
+pub fn board_tags(conn: &Connection, board_id: &str) -> rusqlite::Result<Vec<String>> {
+    conn.prepare("SELECT tag FROM board_tags WHERE board_id = ?1 ORDER BY tag")?
+        .query_map(rusqlite::params![board_id], |row| row.get(0))?
+        .collect()
+}
+
+/// Replace a board's tags. Every tag must be in the directory.
+pub fn set_board_tags(conn: &Connection, board_id: &str, tags: &[String]) -> Result<(), (Status, Json<ApiError>)> {
+    let mut wanted: Vec<String> = Vec::new();
+    for raw in tags {
+        let tag = normalize_tag(raw)?;
+        if !wanted.contains(&tag) {
+            wanted.push(tag);
+        }
+    }
+    if wanted.len() > MAX_TAGS_PER_BOARD {
+        return Err(invalid(
+            format!("A board can have at most {} tags", MAX_TAGS_PER_BOARD),
+            "INVALID_INPUT",
+        ));
+    }
+    for tag in &wanted {
+        let known: bool = conn
+            .query_row("SELECT COUNT(*) > 0 FROM tags WHERE name = ?1", rusqlite::params![tag], |row| row.get(0))
+            .map_err(|e| db_error(&e.to_string()))?;
+        if !known {
+            return Err(invalid(
+                format!("Tag '{}' is not in the tag directory. Ask an admin to add it.", tag),
+                "UNKNOWN_TAG",
+            ));
+        }
+    }
+
+    conn.execute("DELETE FROM board_tags WHERE board_id = ?1", rusqlite::params![board_id])
+        .map_err(|e| db_error(&e.to_string()))?;
+    for tag in &wanted {
+        conn.execute(
+            "INSERT INTO board_tags (board_id, tag) VALUES (?1, ?2)",
+            rusqlite::params![board_id, tag],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// One directory entry; `board_count` counts public boards only.
+pub fn load_tag(conn: &Connection, name: &str) -> rusqlite::Result<TagResponse> {
+    conn.query_row(
+        &format!("SELECT {} FROM tags g WHERE g.name = ?1", TAG_COLUMNS),
+        rusqlite::params![name],
+        row_to_tag,
+    )
+}
+
+const TAG_COLUMNS: &str = "g.name, g.description, g.created_at,
+    (SELECT COUNT(*) FROM board_tags bt JOIN boards b ON b.id = bt.board_id
+     WHERE bt.tag = g.name AND b.is_public = 1 AND b.archived = 0)";
+
+fn row_to_tag(row: &rusqlite::Row) -> rusqlite::Result<TagResponse> {
+    Ok(TagResponse {
+        name: row.get(0)?,
+        description: row.get(1)?,
+        created_at: row.get(2)?,
+        board_count: row.get(3)?,
+    })
+}
+
+/// The tag directory — public, so board owners can see what to apply.
+#[get("/tags")]
+pub async fn list_tags(db: &State<DbPool>) -> Result<Json<Vec<TagResponse>>, (Status, Json<ApiError>)> {
+    with_db(db, move |conn| {
+        let tags = conn
+            .prepare(&format!("SELECT {} FROM tags g ORDER BY g.name", TAG_COLUMNS))
+            .map_err(|e| db_error(&e.to_string()))?
+            .query_map([], row_to_tag)
+            .map_err(|e| db_error(&e.to_string()))?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| db_error(&e.to_string()))?;
+        Ok(Json(tags))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_tag() {
+        assert_eq!(normalize_tag(" Project-Apollo ").unwrap(), "project-apollo");
+        assert_eq!(normalize_tag("team_infra2").unwrap(), "team_infra2");
+        assert!(normalize_tag("").is_err());
+        assert!(normalize_tag("has space").is_err());
+        assert!(normalize_tag(&"x".repeat(MAX_TAG_LEN + 1)).is_err());
+    }
+}
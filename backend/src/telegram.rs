@@ -0,0 +1,251 @@
+//! Telegram notifications. A board can have one Telegram chat: a bot token
+//! and chat ID stored as a webhook of kind `telegram` pointing at the bot's
+//! `sendMessage` method, so deliveries get the webhook worker's event
+//! filter, retries and dead letters. Each subscribed task event becomes an
+//! HTML-formatted message in the chat.
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::access;
+use crate::auth::BoardToken;
+use crate::db::{hash_key, DbPool};
+use crate::event_types;
+use crate::events::BoardEvent;
+use crate::models::*;
+use crate::routes::{db_error, not_found, with_db};
+use crate::slack::{self, Markup};
+use crate::webhooks::KIND_TELEGRAM;
+
+const API_BASE: &str = "https://api.telegram.org/bot";
+/// Telegram's limit on a message's text.
+const MAX_TEXT: usize = 4096;
+
+const TELEGRAM: Markup = Markup { escape, bold, quote };
+
+/// Escape text for Telegram's HTML parse mode.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn bold(text: &str) -> String {
+    format!("<b>{}</b>", text)
+}
+
+fn quote(text: &str) -> String {
+    format!("<blockquote>{}</blockquote>", text)
+}
+
+/// The `sendMessage` body for a task event in `chat_id`.
+pub fn message(conn: &Connection, event: &BoardEvent, chat_id: &str) -> serde_json::Value {
+    let title = bold(&escape(&slack::task_title(conn, event)));
+    let title = match slack::public_url() {
+        Some(base) => format!("<a href=\"{}/board/{}\">{}</a>", escape(&base), event.board_id, title),
+        None => title,
+    };
+    let mut context = escape(&slack::board_name(conn, &event.board_id));
+    if let Some(actor) = slack::actor(&event.data) {
+        context = format!("{} · by {}", context, escape(actor));
+    }
+    let text = format!("{}\n{}\n<i>{}</i>", title, slack::summary(conn, event, &TELEGRAM), context);
+    serde_json::json!({
+        "chat_id": chat_id,
+        "text": text.chars().take(MAX_TEXT).collect::<String>(),
+        "parse_mode": "HTML",
+        "disable_web_page_preview": true,
+    })
+}
+
+/// Bot tokens look like `123456789:AA…` — the bot's id, a colon, a secret.
+fn valid_token(token: &str) -> bool {
+    match token.split_once(':') {
+        Some((id, secret)) => {
+            !id.is_empty()
+                && id.chars().all(|c| c.is_ascii_digit())
+                && !secret.is_empty()
+                && secret.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        }
+        None => false,
+    }
+}
+
+/// A stored `sendMessage` URL with the token's secret half hidden, e.g.
+/// `https://api.telegram.org/bot123456:…/sendMessage`. Other URLs are
+/// returned as they are.
+pub fn redact_url(url: &str) -> String {
+    match url.strip_prefix(API_BASE).and_then(|rest| rest.split_once(':')) {
+        Some((bot_id, rest)) => {
+            let method = rest.rsplit_once('/').map(|(_, m)| m).unwrap_or_default();
+            format!("{}{}:…/{}", API_BASE, bot_id, method)
+        }
+        None => url.to_string(),
+    }
+}
+
+fn invalid(error: &str) -> (Status, Json<ApiError>) {
+    (
+        Status::BadRequest,
+        Json(ApiError {
+            error: error.to_string(),
+            code: "INVALID_INPUT".to_string(),
+            status: 400,
+        }),
+    )
+}
+
+fn load(conn: &Connection, board_id: &str) -> Result<Option<TelegramResponse>, (Status, Json<ApiError>)> {
+    conn.query_row(
+        "SELECT id, url, chat_id, events, active, failure_count, last_triggered_at, created_at
+         FROM webhooks WHERE board_id = ?1 AND kind = ?2",
+        rusqlite::params![board_id, KIND_TELEGRAM],
+        |row| {
+            let url: String = row.get(1)?;
+            let events: String = row.get(3)?;
+            Ok(TelegramResponse {
+                board_id: board_id.to_string(),
+                webhook_id: row.get(0)?,
+                url: redact_url(&url),
+                chat_id: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                events: serde_json::from_str(&events).unwrap_or_default(),
+                active: row.get::<_, i32>(4)? == 1,
+                failure_count: row.get(5)?,
+                last_triggered_at: row.get(6)?,
+                created_at: row.get(7)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| db_error(&e.to_string()))
+}
+
+/// The board's Telegram chat — requires manage key. The bot token is never
+/// returned in full.
+#[get("/boards/<board_id>/telegram")]
+pub async fn get_telegram(
+    board_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<TelegramResponse>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        load(conn, board_id)?.map(Json).ok_or_else(|| not_found("Telegram chat"))
+    })
+    .await
+}
+
+/// Set the board's Telegram bot and chat — requires manage key. Replaces any
+/// earlier one and re-activates it; `events` filters like a webhook's and is
+/// kept when omitted.
+#[put("/boards/<board_id>/telegram", format = "json", data = "<req>")]
+pub async fn set_telegram(
+    board_id: &str,
+    req: Json<SetTelegramRequest>,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<TelegramResponse>, (Status, Json<ApiError>)> {
+    let req = req.into_inner();
+    let bot_token = req.bot_token.trim();
+    let chat_id = req.chat_id.trim().to_owned();
+    if !valid_token(bot_token) {
+        return Err(invalid("bot_token must look like 123456789:AAE… (from @BotFather)"));
+    }
+    if chat_id.is_empty() {
+        return Err(invalid("chat_id is required (a numeric chat ID or @channelusername)"));
+    }
+    if let Some(ref events) = req.events {
+        event_types::validate(events).map_err(|e| invalid(&e))?;
+    }
+    let url = format!("{}{}/sendMessage", API_BASE, bot_token);
+
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        match load(conn, board_id)? {
+            Some(existing) => {
+                let events = req.events.as_ref().unwrap_or(&existing.events);
+                conn.execute(
+                    "UPDATE webhooks SET url = ?1, chat_id = ?2, events = ?3, active = 1, failure_count = 0
+                     WHERE id = ?4",
+                    rusqlite::params![
+                        url,
+                        chat_id,
+                        serde_json::to_string(events).unwrap_or_else(|_| "[]".to_string()),
+                        existing.webhook_id
+                    ],
+                )
+                .map_err(|e| db_error(&e.to_string()))?;
+            }
+            None => {
+                let secret = format!("whsec_{}", uuid::Uuid::new_v4().to_string().replace('-', ""));
+                let events = req.events.clone().unwrap_or_default();
+                conn.execute(
+                    "INSERT INTO webhooks (id, board_id, url, secret, events, kind, chat_id)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    rusqlite::params![
+                        uuid::Uuid::new_v4().to_string(),
+                        board_id,
+                        url,
+                        secret,
+                        serde_json::to_string(&events).unwrap_or_else(|_| "[]".to_string()),
+                        KIND_TELEGRAM,
+                        chat_id
+                    ],
+                )
+                .map_err(|e| db_error(&e.to_string()))?;
+            }
+        }
+        load(conn, board_id)?.map(Json).ok_or_else(|| not_found("Telegram chat"))
+    })
+    .await
+}
+
+/// Stop sending the board's events to Telegram — requires manage key.
+#[delete("/boards/<board_id>/telegram")]
+pub async fn delete_telegram(
+    board_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        let existing = load(conn, board_id)?.ok_or_else(|| not_found("Telegram chat"))?;
+        for sql in [
+            "DELETE FROM webhooks WHERE id = ?1",
+            "DELETE FROM webhook_dead_letters WHERE webhook_id = ?1",
+            "DELETE FROM webhook_deliveries WHERE webhook_id = ?1",
+            "DELETE FROM webhook_outbox WHERE webhook_id = ?1",
+            "DELETE FROM webhook_routes WHERE webhook_id = ?1",
+            "DELETE FROM webhook_signing_keys WHERE webhook_id = ?1",
+        ] {
+            conn.execute(sql, rusqlite::params![existing.webhook_id])
+                .map_err(|e| db_error(&e.to_string()))?;
+        }
+        Ok(Json(serde_json::json!({"deleted": true, "id": existing.webhook_id})))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_validation_and_redaction() {
+        assert!(valid_token("123456789:AAE-x_y9"));
+        assert!(!valid_token("123456789"));
+        assert!(!valid_token("bot:AAE"));
+        assert!(!valid_token("123:AA/../x"));
+        assert_eq!(
+            redact_url("https://api.telegram.org/bot123456:AAEsecret/sendMessage"),
+            "https://api.telegram.org/bot123456:…/sendMessage"
+        );
+        assert_eq!(redact_url("https://example.com/hook"), "https://example.com/hook");
+    }
+}
@@ -0,0 +1,390 @@
+//! Task trash: deleted tasks are parked here with a snapshot of their row so
+//! they can be restored until the retention window runs out.
+
+use std::time::Duration;
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::types::Value;
+use rusqlite::Connection;
+
+use crate::access::{self, BoardRole};
+use crate::auth::{Actor, BoardToken};
+use crate::db::{hash_key, DbPool};
+use crate::event_types;
+use crate::events::{BoardEvent, EventBus};
+use crate::models::*;
+use crate::routes::{db_error, load_task_response, log_event, not_found, with_db, with_db_blocking};
+use crate::validation_hook::{self, Gate};
+
+/// Days a deleted task stays restorable by default (`TRASH_RETENTION_DAYS`).
+const DEFAULT_RETENTION_DAYS: u64 = 30;
+
+/// How often the purge job looks for expired trash.
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone)]
+pub struct TrashConfig {
+    /// None keeps deleted tasks until restored
+    pub retention: Option<Duration>,
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        TrashConfig {
+            retention: Some(Duration::from_secs(DEFAULT_RETENTION_DAYS * 24 * 60 * 60)),
+        }
+    }
+}
+
+impl TrashConfig {
+    /// Read `TRASH_RETENTION_DAYS` (default 30; 0 = never purge).
+    pub fn from_env() -> Self {
+        match std::env::var("TRASH_RETENTION_DAYS").ok().and_then(|v| v.parse::<u64>().ok()) {
+            Some(0) => TrashConfig { retention: None },
+            Some(days) => TrashConfig {
+                retention: Some(Duration::from_secs(days * 24 * 60 * 60)),
+            },
+            None => Self::default(),
+        }
+    }
+
+    fn retention_secs(&self) -> Option<i64> {
+        self.retention.map(|r| r.as_secs() as i64)
+    }
+}
+
+// ============ Routes ============
+
+/// List a board's deleted tasks, newest first — requires manage key.
+#[get("/boards/<board_id>/trash")]
+pub async fn list_trash(
+    board_id: &str,
+    token: BoardToken,
+    config: &State<TrashConfig>,
+    db: &State<DbPool>,
+) -> Result<Json<TrashResponse>, (Status, Json<ApiError>)> {
+    let (board_id, config) = (board_id.to_owned(), config.inner().clone());
+    with_db(db, move |conn| {
+        let (board_id, config) = (board_id.as_str(), &config);
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Editor)?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT task_id, title, column_id, snapshot, deleted_by, deleted_at,
+                        CASE WHEN ?2 IS NULL THEN NULL ELSE datetime(deleted_at, '+' || ?2 || ' seconds') END
+                 FROM task_trash WHERE board_id = ?1
+                 ORDER BY deleted_at DESC, rowid DESC",
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        let tasks = stmt
+            .query_map(rusqlite::params![board_id, config.retention_secs()], |row| {
+                let snapshot: serde_json::Value = serde_json::from_str(&row.get::<_, String>(3)?).unwrap_or_default();
+                Ok(TrashedTask {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    column_id: row.get(2)?,
+                    deleted_by: row.get(4)?,
+                    deleted_at: row.get(5)?,
+                    purge_at: row.get(6)?,
+                    task: snapshot["task"].clone(),
+                })
+            })
+            .map_err(|e| db_error(&e.to_string()))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(Json(TrashResponse {
+            board_id: board_id.to_string(),
+            retention_days: config.retention.map(|r| r.as_secs() / (24 * 60 * 60)),
+            tasks,
+        }))
+    })
+    .await
+}
+
+/// Bring a deleted task back — requires manage key. The task returns to its
+/// old column, or the board's first column if that one is gone. The board's
+/// validation hook is asked about it as a create.
+#[post("/boards/<board_id>/tasks/<task_id>/restore")]
+pub async fn restore_task(
+    board_id: &str,
+    task_id: &str,
+    actor: Actor,
+    token: BoardToken,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<TaskResponse>, (Status, Json<ApiError>)> {
+    let (board_id, bus, task_id) = (board_id.to_owned(), bus.inner().clone(), task_id.to_owned());
+    let client = bus.outbound().clone();
+    validation_hook::run(db, &client, move |conn, gate| {
+        let (board_id, bus, task_id) = (board_id.as_str(), &bus, task_id.as_str());
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Editor)?;
+        access::require_not_archived(conn, board_id)?;
+        access::require_display_name(conn, board_id, &actor)?;
+        let actor = actor.name();
+
+        let column_id = restore_from_trash(conn, gate, board_id, task_id, actor)?;
+
+        let event_data = serde_json::json!({"task_id": task_id, "column_id": column_id});
+        log_event(conn, task_id, "restored", actor, &event_data);
+        bus.emit(
+            conn,
+            BoardEvent {
+                event: event_types::TASK_RESTORED.to_string(),
+                board_id: board_id.to_string(),
+                data: event_data,
+            },
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+
+        load_task_response(conn, task_id)
+    })
+    .await
+}
+
+// ============ Trash operations ============
+
+/// Move a task into the trash. Returns what `task.deleted` says about it —
+/// its title, and the column and assignee it had — or None if the task isn't
+/// on this board. Deleting the row cascades to its events, dependencies, edit
+/// lock and comment transcripts and detaches its subtasks, so all of those are
+/// captured in the snapshot too and come back on restore. Attachment rows
+/// don't cascade; they stay put for the sweep.
+pub(crate) fn move_to_trash(
+    conn: &Connection,
+    board_id: &str,
+    task_id: &str,
+    actor: &str,
+) -> rusqlite::Result<Option<serde_json::Value>> {
+    let Some(task) = rows_as_json(conn, "tasks", "id = ?1 AND board_id = ?2", &[task_id, board_id])?.pop() else {
+        return Ok(None);
+    };
+    let events = rows_as_json(conn, "task_events", "task_id = ?1", &[task_id])?;
+    let dependencies = rows_as_json(
+        conn,
+        "task_dependencies",
+        "blocker_task_id = ?1 OR blocked_task_id = ?1",
+        &[task_id],
+    )?;
+    let locks = rows_as_json(conn, "task_locks", "task_id = ?1", &[task_id])?;
+    let transcripts = rows_as_json(conn, "comment_transcripts", "task_id = ?1", &[task_id])?;
+    let subtasks: Vec<String> = conn
+        .prepare("SELECT id FROM tasks WHERE parent_task_id = ?1")?
+        .query_map([task_id], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    let title = task["title"].as_str().unwrap_or_default().to_string();
+    let snapshot = serde_json::json!({
+        "task": task,
+        "events": events,
+        "dependencies": dependencies,
+        "locks": locks,
+        "transcripts": transcripts,
+        "subtasks": subtasks,
+    });
+
+    conn.execute(
+        "INSERT OR REPLACE INTO task_trash (task_id, board_id, title, column_id, snapshot, deleted_by)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        rusqlite::params![task_id, board_id, title, task["column_id"].as_str(), snapshot.to_string(), actor],
+    )?;
+    conn.execute(
+        "DELETE FROM tasks WHERE id = ?1 AND board_id = ?2",
+        rusqlite::params![task_id, board_id],
+    )?;
+    Ok(Some(serde_json::json!({
+        "task_id": task_id,
+        "title": title,
+        "column_id": task["column_id"],
+        "assigned_to": task["assigned_to"],
+    })))
+}
+
+/// Re-insert a trashed task with its events, lock and transcripts, any
+/// dependencies whose other end still exists, and re-attach subtasks that
+/// haven't found another parent since. Returns the column it landed in.
+fn restore_from_trash(
+    conn: &Connection,
+    gate: &Gate,
+    board_id: &str,
+    task_id: &str,
+    actor: &str,
+) -> Result<String, (Status, Json<ApiError>)> {
+    let snapshot: String = conn
+        .query_row(
+            "SELECT snapshot FROM task_trash WHERE task_id = ?1 AND board_id = ?2",
+            rusqlite::params![task_id, board_id],
+            |row| row.get(0),
+        )
+        .map_err(|_| not_found("Deleted task"))?;
+    let snapshot: serde_json::Value = serde_json::from_str(&snapshot).map_err(|e| db_error(&e.to_string()))?;
+    let mut task = snapshot["task"].clone();
+
+    let column_exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM columns WHERE id = ?1 AND board_id = ?2",
+            rusqlite::params![task["column_id"].as_str(), board_id],
+            |row| row.get(0),
+        )
+        .unwrap_or(false);
+    if !column_exists {
+        let first: String = conn
+            .query_row(
+                "SELECT id FROM columns WHERE board_id = ?1 ORDER BY position ASC LIMIT 1",
+                rusqlite::params![board_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        task["column_id"] = serde_json::json!(first);
+    }
+    // Its parent may have been deleted since
+    if let Some(parent) = task["parent_task_id"].as_str() {
+        let parent_exists: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM tasks WHERE id = ?1 AND board_id = ?2",
+                rusqlite::params![parent, board_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+        if !parent_exists {
+            task["parent_task_id"] = serde_json::Value::Null;
+        }
+    }
+    validation_hook::check(
+        conn,
+        gate,
+        board_id,
+        validation_hook::TASK_CREATE,
+        actor,
+        serde_json::json!({"task": task, "restored": true}),
+    )?;
+
+    let restore = || -> rusqlite::Result<()> {
+        insert_json(conn, "tasks", &task)?;
+        for event in snapshot["events"].as_array().into_iter().flatten() {
+            insert_json(conn, "task_events", event)?;
+        }
+        for dep in snapshot["dependencies"].as_array().into_iter().flatten() {
+            let other_end_exists: bool = conn.query_row(
+                "SELECT COUNT(*) = 2 FROM tasks WHERE id IN (?1, ?2)",
+                rusqlite::params![dep["blocker_task_id"].as_str(), dep["blocked_task_id"].as_str()],
+                |row| row.get(0),
+            )?;
+            if other_end_exists {
+                insert_json(conn, "task_dependencies", dep)?;
+            }
+        }
+        for (key, table) in [("locks", "task_locks"), ("transcripts", "comment_transcripts")] {
+            for row in snapshot[key].as_array().into_iter().flatten() {
+                insert_json(conn, table, row)?;
+            }
+        }
+        conn.execute(
+            "UPDATE tasks SET parent_task_id = ?1
+             WHERE board_id = ?2 AND parent_task_id IS NULL AND id IN (SELECT value FROM json_each(?3))",
+            rusqlite::params![task_id, board_id, snapshot["subtasks"].to_string()],
+        )?;
+        conn.execute("DELETE FROM task_trash WHERE task_id = ?1", rusqlite::params![task_id])?;
+        Ok(())
+    };
+    restore().map_err(|e| db_error(&e.to_string()))?;
+    Ok(task["column_id"].as_str().unwrap_or_default().to_string())
+}
+
+/// Permanently remove trash older than the retention window. Returns the number purged.
+pub fn purge_expired(conn: &Connection, config: &TrashConfig) -> rusqlite::Result<usize> {
+    let Some(secs) = config.retention_secs() else {
+        return Ok(0);
+    };
+    conn.execute(
+        "DELETE FROM email_reminders WHERE task_id IN (
+             SELECT task_id FROM task_trash WHERE deleted_at <= datetime('now', '-' || ?1 || ' seconds'))",
+        rusqlite::params![secs],
+    )?;
+    conn.execute(
+        "DELETE FROM task_trash WHERE deleted_at <= datetime('now', '-' || ?1 || ' seconds')",
+        rusqlite::params![secs],
+    )
+}
+
+/// Run `purge_expired` hourly in the background. Does nothing when retention is disabled.
+pub fn spawn_purge(db: DbPool, config: TrashConfig) {
+    if config.retention.is_none() {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let (db, config) = (db.clone(), config.clone());
+            let result = tokio::task::spawn_blocking(move || {
+                with_db_blocking(&db, |conn| purge_expired(conn, &config).map_err(|e| db_error(&e.to_string())))
+                    .map_err(|(_, e)| e.error.clone())
+            })
+            .await;
+            match result {
+                Ok(Ok(0)) => {}
+                Ok(Ok(n)) => println!("🗑️  Purged {} expired task(s) from trash", n),
+                Ok(Err(e)) => eprintln!("⚠️  Trash purge failed: {}", e),
+                Err(e) => eprintln!("⚠️  Trash purge task panicked: {}", e),
+            }
+        }
+    });
+}
+
+// ============ Row snapshots ============
+
+/// Current column names of `table`, in schema order.
+fn table_columns(conn: &Connection, table: &str) -> rusqlite::Result<Vec<String>> {
+    conn.prepare("SELECT name FROM pragma_table_info(?1)")?
+        .query_map([table], |row| row.get(0))?
+        .collect()
+}
+
+/// Matching rows of `table` as JSON objects keyed by column name.
+fn rows_as_json(
+    conn: &Connection,
+    table: &str,
+    filter: &str,
+    params: &[&str],
+) -> rusqlite::Result<Vec<serde_json::Value>> {
+    let fields: Vec<String> = table_columns(conn, table)?
+        .iter()
+        .map(|c| format!("'{0}', \"{0}\"", c))
+        .collect();
+    let sql = format!("SELECT json_object({}) FROM {} WHERE {}", fields.join(", "), table, filter);
+    conn.prepare(&sql)?
+        .query_map(rusqlite::params_from_iter(params), |row| row.get::<_, String>(0))?
+        .map(|r| r.map(|json| serde_json::from_str(&json).unwrap_or_default()))
+        .collect()
+}
+
+/// Insert a `rows_as_json` object back into `table`. Columns added since the
+/// snapshot was taken fall back to their defaults.
+fn insert_json(conn: &Connection, table: &str, row: &serde_json::Value) -> rusqlite::Result<usize> {
+    let columns: Vec<String> = table_columns(conn, table)?
+        .into_iter()
+        .filter(|c| row.get(c).is_some())
+        .collect();
+    let values: Vec<Value> = columns.iter().map(|c| sql_value(&row[c])).collect();
+    let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+    let quoted: Vec<String> = columns.iter().map(|c| format!("\"{}\"", c)).collect();
+    conn.execute(
+        &format!("INSERT OR IGNORE INTO {} ({}) VALUES ({})", table, quoted.join(", "), placeholders.join(", ")),
+        rusqlite::params_from_iter(values),
+    )
+}
+
+fn sql_value(v: &serde_json::Value) -> Value {
+    match v {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Integer(*b as i64),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Real(n.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(s) => Value::Text(s.clone()),
+        other => Value::Text(other.to_string()),
+    }
+}
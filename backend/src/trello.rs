@@ -0,0 +1,401 @@
+//! Trello import: convert a Trello board JSON export into a board export
+//! document and write it through the regular import path.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use serde::Deserialize;
+
+use crate::auth::Actor;
+use crate::db::DbPool;
+use crate::export::{EXPORT_FORMAT, EXPORT_VERSION};
+use crate::import::{db_time, invalid_import, store_document, validate_document};
+use crate::lifecycle::LifecycleHooks;
+use crate::models::*;
+use crate::rate_limit::IpRateLimit;
+use crate::routes::check_board_rate_limit;
+
+/// Actor recorded on events the import itself creates (checklists, card creation).
+const IMPORT_ACTOR: &str = "trello-import";
+
+// Only the parts of Trello's export we map; everything else is ignored.
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrelloBoard {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub desc: String,
+    #[serde(default)]
+    pub lists: Vec<TrelloList>,
+    #[serde(default)]
+    pub cards: Vec<TrelloCard>,
+    #[serde(default)]
+    pub checklists: Vec<TrelloChecklist>,
+    #[serde(default)]
+    pub actions: Vec<TrelloAction>,
+    #[serde(default)]
+    pub members: Vec<TrelloMember>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TrelloList {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub closed: bool,
+    #[serde(default)]
+    pub pos: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrelloCard {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub desc: String,
+    pub id_list: String,
+    #[serde(default)]
+    pub closed: bool,
+    #[serde(default)]
+    pub pos: f64,
+    #[serde(default)]
+    pub due: Option<String>,
+    #[serde(default)]
+    pub due_complete: bool,
+    #[serde(default)]
+    pub labels: Vec<TrelloLabel>,
+    #[serde(default)]
+    pub id_members: Vec<String>,
+    #[serde(default)]
+    pub attachments: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub short_url: Option<String>,
+    #[serde(default)]
+    pub date_last_activity: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TrelloLabel {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrelloChecklist {
+    pub id_card: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub pos: f64,
+    #[serde(default)]
+    pub check_items: Vec<TrelloCheckItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TrelloCheckItem {
+    pub name: String,
+    #[serde(default)]
+    pub state: String,
+    #[serde(default)]
+    pub pos: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrelloAction {
+    #[serde(default)]
+    pub id: String,
+    #[serde(rename = "type")]
+    pub action_type: String,
+    #[serde(default)]
+    pub date: Option<String>,
+    #[serde(default)]
+    pub data: serde_json::Value,
+    #[serde(default)]
+    pub member_creator: Option<TrelloMember>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrelloMember {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub full_name: String,
+    #[serde(default)]
+    pub username: String,
+}
+
+impl TrelloMember {
+    fn display_name(&self) -> &str {
+        if self.full_name.trim().is_empty() {
+            &self.username
+        } else {
+            &self.full_name
+        }
+    }
+}
+
+/// Import a Trello board export (Board menu → Print, export and share → Export as JSON) — no auth required.
+/// Lists become columns, cards become tasks; labels, descriptions and due dates carry over,
+/// card comments become comments, and checklists become one comment per checklist.
+/// Counts against the board-creation rate limit.
+#[post("/boards/import/trello", format = "json", data = "<trello>")]
+pub async fn import_trello_board(
+    trello: Json<TrelloBoard>,
+    actor: Actor,
+    rate_limit: IpRateLimit<'_>,
+    hooks: &State<LifecycleHooks>,
+    db: &State<DbPool>,
+) -> Result<Json<ImportBoardResponse>, (Status, Json<ApiError>)> {
+    let (doc, warnings) = convert(&trello.into_inner())?;
+    validate_document(&doc)?;
+    check_board_rate_limit(&rate_limit).await?;
+
+    let mut response = store_document(db, hooks, doc, &actor).await?;
+    response.report.warnings.splice(0..0, warnings);
+    Ok(Json(response))
+}
+
+/// Map a Trello board onto an export document, collecting what couldn't be converted.
+pub(crate) fn convert(trello: &TrelloBoard) -> Result<(BoardExport, Vec<String>), (Status, Json<ApiError>)> {
+    let mut warnings = Vec::new();
+    let now = Utc::now();
+    let now_str = now.format("%Y-%m-%d %H:%M:%S").to_string();
+
+    let mut lists: Vec<&TrelloList> = trello.lists.iter().collect();
+    lists.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+    let (open_lists, closed_lists): (Vec<&TrelloList>, Vec<&TrelloList>) = lists.into_iter().partition(|l| !l.closed);
+    if open_lists.is_empty() {
+        return Err(invalid_import("Trello board has no open lists".to_string()));
+    }
+    for list in &closed_lists {
+        let cards = trello.cards.iter().filter(|c| c.id_list == list.id).count();
+        warnings.push(format!("Archived list '{}' skipped ({} card(s))", list.name, cards));
+    }
+
+    let columns: Vec<ExportColumn> = open_lists
+        .iter()
+        .enumerate()
+        .map(|(i, list)| ExportColumn {
+            id: list.id.clone(),
+            name: list.name.clone(),
+            position: i as i32,
+            wip_limit: None,
+            created_at: object_id_time(&list.id).unwrap_or_else(|| now_str.clone()),
+        })
+        .collect();
+
+    let members: HashMap<&str, &str> = trello.members.iter().map(|m| (m.id.as_str(), m.display_name())).collect();
+    // Card creators come from createCard actions, when the export still has them
+    let creators: HashMap<&str, &str> = trello
+        .actions
+        .iter()
+        .filter(|a| a.action_type == "createCard")
+        .filter_map(|a| Some((a.data["card"]["id"].as_str()?, a.member_creator.as_ref()?.display_name())))
+        .collect();
+
+    let mut cards: Vec<&TrelloCard> = trello
+        .cards
+        .iter()
+        .filter(|c| open_lists.iter().any(|l| l.id == c.id_list))
+        .collect();
+    cards.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+
+    let mut tasks = Vec::new();
+    let mut events = Vec::new();
+    let mut attachments = 0;
+    let mut positions: HashMap<&str, i32> = HashMap::new();
+    for card in cards {
+        let position = positions.entry(card.id_list.as_str()).or_insert(0);
+        let created_at = object_id_time(&card.id).unwrap_or_else(|| now_str.clone());
+        let updated_at = card.date_last_activity.as_deref().and_then(db_time).unwrap_or_else(|| created_at.clone());
+        let created_by = creators.get(card.id.as_str()).copied().unwrap_or("").to_string();
+
+        let due_at = match card.due.as_deref() {
+            Some(due) => match DateTime::parse_from_rfc3339(due) {
+                Ok(dt) => Some(dt.with_timezone(&Utc).format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+                Err(_) => {
+                    warnings.push(format!("Card '{}': unreadable due date '{}' dropped", card.name, due));
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let assignees: Vec<&str> = card.id_members.iter().filter_map(|id| members.get(id.as_str()).copied()).collect();
+        if assignees.len() > 1 {
+            warnings.push(format!(
+                "Card '{}' had {} members; assigned to {} only",
+                card.name,
+                assignees.len(),
+                assignees[0]
+            ));
+        }
+        attachments += card.attachments.len();
+
+        let labels = card
+            .labels
+            .iter()
+            .filter_map(|l| {
+                let name = l.name.trim();
+                if name.is_empty() {
+                    l.color.clone()
+                } else {
+                    Some(name.to_string())
+                }
+            })
+            .collect();
+        let mut trello_meta = serde_json::json!({"card_id": card.id});
+        if let Some(url) = &card.short_url {
+            trello_meta["url"] = serde_json::json!(url);
+        }
+        if card.due_complete {
+            trello_meta["due_complete"] = serde_json::json!(true);
+        }
+
+        events.push(ExportEvent {
+            id: format!("{}-created", card.id),
+            task_id: card.id.clone(),
+            event_type: "created".to_string(),
+            actor: if created_by.is_empty() { IMPORT_ACTOR.to_string() } else { created_by.clone() },
+            data: serde_json::json!({
+                "title": card.name,
+                "task_id": card.id,
+                "column_id": card.id_list,
+                "creator": created_by,
+            }),
+            seq: None,
+            created_at: created_at.clone(),
+        });
+        tasks.push(ExportTask {
+            id: card.id.clone(),
+            column_id: card.id_list.clone(),
+            title: card.name.clone(),
+            description: card.desc.clone(),
+            priority: 0,
+            position: *position,
+            created_by,
+            assigned_to: assignees.first().map(|a| a.to_string()),
+            claimed_by: None,
+            claimed_at: None,
+            labels,
+            metadata: serde_json::json!({"trello": trello_meta}),
+            due_at,
+            completed_at: None,
+            archived_at: card.closed.then(|| updated_at.clone()),
+            created_at,
+            updated_at,
+        });
+        *position += 1;
+    }
+    if attachments > 0 {
+        warnings.push(format!("{} attachment(s) not imported", attachments));
+    }
+
+    let imported = |card_id: &str| tasks.iter().any(|t| t.id == card_id);
+    let mut comments = Vec::new();
+    for action in trello.actions.iter().filter(|a| a.action_type == "commentCard") {
+        let (Some(card_id), Some(text)) = (action.data["card"]["id"].as_str(), action.data["text"].as_str()) else {
+            continue;
+        };
+        if !imported(card_id) {
+            continue;
+        }
+        let actor = action.member_creator.as_ref().map(|m| m.display_name()).unwrap_or(IMPORT_ACTOR);
+        comments.push(ExportEvent {
+            id: action.id.clone(),
+            task_id: card_id.to_string(),
+            event_type: "comment".to_string(),
+            actor: actor.to_string(),
+            data: serde_json::json!({"message": text, "actor": actor}),
+            seq: None,
+            created_at: action.date.as_deref().and_then(db_time).unwrap_or_else(|| now_str.clone()),
+        });
+    }
+
+    let mut checklists: Vec<&TrelloChecklist> = trello.checklists.iter().filter(|c| imported(&c.id_card)).collect();
+    checklists.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+    for checklist in checklists {
+        let mut items: Vec<&TrelloCheckItem> = checklist.check_items.iter().collect();
+        items.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+        let mut message = format!("Checklist: {}", checklist.name);
+        for item in items {
+            let mark = if item.state == "complete" { "x" } else { " " };
+            message.push_str(&format!("\n- [{}] {}", mark, item.name));
+        }
+        let created_at = tasks
+            .iter()
+            .find(|t| t.id == checklist.id_card)
+            .map(|t| t.created_at.clone())
+            .unwrap_or_else(|| now_str.clone());
+        comments.push(ExportEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            task_id: checklist.id_card.clone(),
+            event_type: "comment".to_string(),
+            actor: IMPORT_ACTOR.to_string(),
+            data: serde_json::json!({"message": message, "actor": IMPORT_ACTOR}),
+            seq: None,
+            created_at,
+        });
+    }
+
+    let board_created = object_id_time(&trello.id).unwrap_or_else(|| now_str.clone());
+    let doc = BoardExport {
+        format: EXPORT_FORMAT.to_string(),
+        version: EXPORT_VERSION,
+        exported_at: now.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        server_version: env!("CARGO_PKG_VERSION").to_string(),
+        board: ExportBoard {
+            id: trello.id.clone(),
+            name: trello.name.clone(),
+            description: trello.desc.clone(),
+            is_public: false,
+            visibility: None,
+            archived: false,
+            require_display_name: false,
+            enforce_dependencies: false,
+            quick_done_column_id: None,
+            quick_done_auto_archive: false,
+            quick_reassign_column_id: None,
+            quick_reassign_to: None,
+            created_at: board_created.clone(),
+            updated_at: board_created,
+        },
+        columns,
+        tasks,
+        events,
+        comments,
+        dependencies: Vec::new(),
+        webhooks: Vec::new(),
+    };
+    Ok((doc, warnings))
+}
+
+/// Trello ids are MongoDB ObjectIds: the first 8 hex digits are the creation time.
+fn object_id_time(id: &str) -> Option<String> {
+    let secs = i64::from_str_radix(id.get(..8)?, 16).ok()?;
+    DateTime::from_timestamp(secs, 0).map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_object_id_time() {
+        assert_eq!(object_id_time("5f0c6a0e8a1b2c3d4e5f6a7b").as_deref(), Some("2020-07-13 14:05:02"));
+        assert_eq!(object_id_time("not-an-id"), None);
+        assert_eq!(object_id_time(""), None);
+    }
+}
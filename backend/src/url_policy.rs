@@ -0,0 +1,233 @@
+//! Which URLs the server may send requests to. A manage key is enough to
+//! register a webhook or validation hook, so without a policy anyone with one
+//! could make the server POST to its own network — cloud metadata services,
+//! admin ports, other internal hosts. Outbound URLs must be http(s), and
+//! unless `ALLOW_PRIVATE_WEBHOOKS` is set they may not point at loopback,
+//! private, link-local or otherwise non-public addresses. That is checked when
+//! a URL is saved, again before each request, on every redirect, and on the
+//! addresses a host name resolves to when connecting — so DNS can't be used to
+//! sneak past it. For the same reason `HTTP_PROXY` / `HTTPS_PROXY` are ignored
+//! while the policy is enforced: a proxy resolves the host itself, out of the
+//! check's reach. They are honoured when private addresses are allowed.
+//!
+//! Every server-initiated request goes out through `OutboundClient`, whose
+//! only way to start one is `request`, so none can skip the check: webhook
+//! deliveries, validation hooks, board lifecycle hooks, S3 attachment
+//! storage, OIDC sign-in and GitHub imports.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+
+/// Redirects a delivery may follow.
+const MAX_REDIRECTS: usize = 10;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UrlPolicy {
+    /// Let webhooks target private and local addresses (self-hosted setups
+    /// whose receivers live on the same network)
+    pub allow_private: bool,
+}
+
+impl UrlPolicy {
+    /// Read `ALLOW_PRIVATE_WEBHOOKS` (`true` / `1` to allow).
+    pub fn from_env() -> Self {
+        let allow_private = std::env::var("ALLOW_PRIVATE_WEBHOOKS")
+            .map(|v| matches!(v.trim().to_lowercase().as_str(), "true" | "1" | "yes"))
+            .unwrap_or(false);
+        UrlPolicy { allow_private }
+    }
+
+    /// Whether the server may send a request to `url`; Err explains why not.
+    pub fn check(&self, url: &str) -> Result<(), String> {
+        let parsed = reqwest::Url::parse(url.trim()).map_err(|e| format!("Invalid URL: {}", e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(format!("Outbound URLs must use http or https, not {}", parsed.scheme()));
+        }
+        let host = parsed.host_str().unwrap_or_default();
+        if host.is_empty() {
+            return Err("URL has no host".to_string());
+        }
+        if self.allow_private {
+            return Ok(());
+        }
+        let name = host.trim_end_matches('.').to_lowercase();
+        let local_name = name == "localhost" || name.ends_with(".localhost");
+        let private_ip = name
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .is_ok_and(is_private);
+        if local_name || private_ip {
+            return Err(format!(
+                "URL points at a private or local address ({}); \
+                 set ALLOW_PRIVATE_WEBHOOKS=true to allow this on a self-hosted server",
+                host
+            ));
+        }
+        Ok(())
+    }
+
+    /// An HTTP client that enforces the policy on redirects and resolved
+    /// addresses. Panics if the client can't be built, which only happens at
+    /// startup: falling back to a default client would drop the protection.
+    fn client(&self) -> reqwest::Client {
+        if self.allow_private {
+            return reqwest::Client::new();
+        }
+        let policy = *self;
+        reqwest::Client::builder()
+            .no_proxy()
+            .dns_resolver(Arc::new(PublicResolver))
+            .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= MAX_REDIRECTS {
+                    return attempt.error("too many redirects");
+                }
+                match policy.check(attempt.url().as_str()) {
+                    Ok(()) => attempt.follow(),
+                    Err(e) => attempt.error(e),
+                }
+            }))
+            .build()
+            .expect("failed to build the outbound HTTP client")
+    }
+}
+
+/// The client every server-initiated request goes out on, with the policy
+/// it enforces.
+#[derive(Debug, Clone)]
+pub struct OutboundClient {
+    http: reqwest::Client,
+    pub policy: UrlPolicy,
+}
+
+impl OutboundClient {
+    pub fn new(policy: UrlPolicy) -> Self {
+        OutboundClient {
+            http: policy.client(),
+            policy,
+        }
+    }
+
+    /// Start a request to `url`; Err if the policy doesn't allow it.
+    pub fn request(&self, method: reqwest::Method, url: &str) -> Result<reqwest::RequestBuilder, String> {
+        self.policy.check(url)?;
+        Ok(self.http.request(method, url))
+    }
+
+    pub fn get(&self, url: &str) -> Result<reqwest::RequestBuilder, String> {
+        self.request(reqwest::Method::GET, url)
+    }
+
+    pub fn post(&self, url: &str) -> Result<reqwest::RequestBuilder, String> {
+        self.request(reqwest::Method::POST, url)
+    }
+}
+
+impl Default for OutboundClient {
+    fn default() -> Self {
+        OutboundClient::new(UrlPolicy::default())
+    }
+}
+
+/// Addresses a webhook must not reach without `ALLOW_PRIVATE_WEBHOOKS`:
+/// anything that isn't a globally routable unicast address.
+pub fn is_private(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => is_private_v4(v4),
+        IpAddr::V6(v6) => {
+            // IPv4-mapped (::ffff:a.b.c.d) and NAT64 (64:ff9b::a.b.c.d) carry an IPv4 address
+            let segments = v6.segments();
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_private_v4(v4);
+            }
+            if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+                let [a, b, c, d] = v6.octets()[12..] else { unreachable!() };
+                return is_private_v4(Ipv4Addr::new(a, b, c, d));
+            }
+            v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                || (segments[0] & 0xfe00) == 0xfc00 // unique local
+                || (segments[0] & 0xffc0) == 0xfe80 // link-local
+                || (segments[0] & 0xffc0) == 0xfec0 // site-local (deprecated)
+                || (segments[0] == 0x2001 && segments[1] == 0x0db8) // documentation
+                || segments[..6] == [0, 0, 0, 0, 0, 0] // IPv4-compatible
+        }
+    }
+}
+
+fn is_private_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || a == 0 // "this network"
+        || (a == 100 && (64..128).contains(&b)) // carrier-grade NAT
+        || (a == 192 && b == 0 && ip.octets()[2] == 0) // IETF protocol assignments
+        || (a == 198 && (18..20).contains(&b)) // benchmarking
+        || a >= 240 // reserved
+}
+
+/// Resolves host names like the system resolver, minus private addresses;
+/// a name with only private addresses fails to resolve.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| !is_private(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} resolves only to private or local addresses", host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let strict = UrlPolicy::default();
+        for ok in ["https://hooks.example.com/x", "http://93.184.216.34:8080/hook", "https://[2606:4700::1111]/"] {
+            assert!(strict.check(ok).is_ok(), "{}", ok);
+        }
+        for blocked in [
+            "http://127.0.0.1:8000/hook",
+            "http://localhost/hook",
+            "http://api.localhost./hook",
+            "http://10.1.2.3/",
+            "http://172.16.0.1/",
+            "http://192.168.1.1/",
+            "http://169.254.169.254/latest/meta-data",
+            "http://100.64.0.1/",
+            "http://0.0.0.0/",
+            "http://2130706433/",
+            "http://0x7f.1/",
+            "http://[::1]/",
+            "http://[::ffff:127.0.0.1]/",
+            "http://[fd00::1]/",
+            "http://[fe80::1]/",
+            "http://[64:ff9b::a9fe:a9fe]/",
+        ] {
+            assert!(strict.check(blocked).unwrap_err().contains("ALLOW_PRIVATE_WEBHOOKS"), "{}", blocked);
+        }
+        for bad in ["ftp://example.com/x", "file:///etc/passwd", "gopher://x", "not a url"] {
+            assert!(strict.check(bad).is_err(), "{}", bad);
+            assert!(UrlPolicy { allow_private: true }.check(bad).is_err(), "{}", bad);
+        }
+        assert!(UrlPolicy { allow_private: true }.check("http://127.0.0.1:8000/hook").is_ok());
+    }
+}
@@ -0,0 +1,234 @@
+//! Instance-wide API usage by client, so maintainers can see which agent
+//! frameworks integrate and how often they read `llms.txt` / `openapi.json`.
+//!
+//! Clients are fingerprinted from `X-Client` (self-identification, e.g.
+//! `my-agent/1.2`) or else their `User-Agent`. Counters live in memory since
+//! startup and never record IP addresses or board IDs.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::{Data, Request};
+
+use crate::models::{ClientStatsResponse, ClientUsageStats, UserAgentCount};
+
+/// Distinct clients tracked before new ones are folded into "other".
+const MAX_CLIENTS: usize = 200;
+/// Distinct user agents remembered per client.
+const MAX_USER_AGENTS: usize = 50;
+/// User agents listed per client in the stats response.
+const TOP_USER_AGENTS: usize = 10;
+/// Longest user agent / client name stored.
+const MAX_LEN: usize = 200;
+
+/// `User-Agent` substrings mapped to a client family. Agent frameworks come
+/// before the HTTP libraries they are built on.
+const FAMILIES: &[(&str, &str)] = &[
+    ("langchain", "langchain"),
+    ("llama-index", "llamaindex"),
+    ("llamaindex", "llamaindex"),
+    ("crewai", "crewai"),
+    ("autogen", "autogen"),
+    ("semantic-kernel", "semantic-kernel"),
+    ("openai", "openai-sdk"),
+    ("kanban-client", "kanban-sdk"),
+    ("python-requests", "python-requests"),
+    ("python-httpx", "python-httpx"),
+    ("aiohttp", "aiohttp"),
+    ("python-urllib", "python-urllib"),
+    ("axios", "axios"),
+    ("node-fetch", "node-fetch"),
+    ("undici", "node"),
+    ("node", "node"),
+    ("deno", "deno"),
+    ("bun/", "bun"),
+    ("go-http-client", "go"),
+    ("reqwest", "reqwest"),
+    ("okhttp", "okhttp"),
+    ("curl/", "curl"),
+    ("wget", "wget"),
+    ("postmanruntime", "postman"),
+    ("insomnia", "insomnia"),
+    ("bot", "crawler"),
+    ("mozilla/", "browser"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RequestKind {
+    Api,
+    LlmsTxt,
+    OpenApi,
+}
+
+impl RequestKind {
+    fn of(path: &str) -> Option<Self> {
+        if path.ends_with("/llms.txt") {
+            Some(RequestKind::LlmsTxt)
+        } else if path.ends_with("/openapi.json") {
+            Some(RequestKind::OpenApi)
+        } else if path.starts_with("/api/") {
+            Some(RequestKind::Api)
+        } else {
+            None
+        }
+    }
+}
+
+/// The client family for a request: `X-Client`'s name (before any `/version`),
+/// else a known `User-Agent` pattern, else "other" ("unknown" with no UA).
+fn fingerprint(x_client: Option<&str>, user_agent: Option<&str>) -> String {
+    if let Some(name) = x_client.and_then(|c| c.split('/').next()).map(str::trim).filter(|n| !n.is_empty()) {
+        return truncate(&name.to_lowercase());
+    }
+    let Some(ua) = user_agent.map(str::trim).filter(|ua| !ua.is_empty()) else {
+        return "unknown".to_string();
+    };
+    let lower = ua.to_lowercase();
+    FAMILIES
+        .iter()
+        .find(|(needle, _)| lower.contains(needle))
+        .map(|(_, family)| family.to_string())
+        .unwrap_or_else(|| "other".to_string())
+}
+
+fn truncate(s: &str) -> String {
+    s.chars().take(MAX_LEN).collect()
+}
+
+#[derive(Debug)]
+struct Counters {
+    requests: u64,
+    llms_txt: u64,
+    openapi: u64,
+    first_seen: DateTime<Utc>,
+    last_seen: DateTime<Utc>,
+    user_agents: HashMap<String, u64>,
+}
+
+#[derive(Debug)]
+struct Table {
+    since: DateTime<Utc>,
+    clients: HashMap<String, Counters>,
+}
+
+/// Request counters per client. Attach as a fairing and manage a clone for
+/// `GET /admin/client-stats`.
+#[derive(Debug, Clone)]
+pub struct ClientUsage {
+    table: Arc<Mutex<Table>>,
+}
+
+impl Default for ClientUsage {
+    fn default() -> Self {
+        ClientUsage {
+            table: Arc::new(Mutex::new(Table {
+                since: Utc::now(),
+                clients: HashMap::new(),
+            })),
+        }
+    }
+}
+
+impl ClientUsage {
+    fn record(&self, kind: RequestKind, x_client: Option<&str>, user_agent: Option<&str>) {
+        let mut client = fingerprint(x_client, user_agent);
+        let now = Utc::now();
+        let mut table = self.table.lock().unwrap();
+        if !table.clients.contains_key(&client) && table.clients.len() >= MAX_CLIENTS {
+            client = "other".to_string();
+        }
+        let counters = table.clients.entry(client).or_insert_with(|| Counters {
+            requests: 0,
+            llms_txt: 0,
+            openapi: 0,
+            first_seen: now,
+            last_seen: now,
+            user_agents: HashMap::new(),
+        });
+        counters.requests += 1;
+        match kind {
+            RequestKind::LlmsTxt => counters.llms_txt += 1,
+            RequestKind::OpenApi => counters.openapi += 1,
+            RequestKind::Api => {}
+        }
+        counters.last_seen = now;
+        let ua = truncate(user_agent.unwrap_or("").trim());
+        if counters.user_agents.contains_key(&ua) || counters.user_agents.len() < MAX_USER_AGENTS {
+            *counters.user_agents.entry(ua).or_insert(0) += 1;
+        }
+    }
+
+    /// Snapshot for the admin endpoint, busiest clients first.
+    pub fn stats(&self) -> ClientStatsResponse {
+        let table = self.table.lock().unwrap();
+        let mut clients: Vec<ClientUsageStats> = table
+            .clients
+            .iter()
+            .map(|(client, c)| {
+                let mut user_agents: Vec<UserAgentCount> = c
+                    .user_agents
+                    .iter()
+                    .map(|(ua, requests)| UserAgentCount {
+                        user_agent: ua.clone(),
+                        requests: *requests,
+                    })
+                    .collect();
+                user_agents.sort_by(|a, b| b.requests.cmp(&a.requests).then_with(|| a.user_agent.cmp(&b.user_agent)));
+                user_agents.truncate(TOP_USER_AGENTS);
+                ClientUsageStats {
+                    client: client.clone(),
+                    requests: c.requests,
+                    llms_txt_fetches: c.llms_txt,
+                    openapi_fetches: c.openapi,
+                    first_seen: c.first_seen.to_rfc3339(),
+                    last_seen: c.last_seen.to_rfc3339(),
+                    user_agents,
+                }
+            })
+            .collect();
+        clients.sort_by(|a, b| b.requests.cmp(&a.requests).then_with(|| a.client.cmp(&b.client)));
+
+        ClientStatsResponse {
+            since: table.since.to_rfc3339(),
+            total_requests: clients.iter().map(|c| c.requests).sum(),
+            llms_txt_fetches: clients.iter().map(|c| c.llms_txt_fetches).sum(),
+            openapi_fetches: clients.iter().map(|c| c.openapi_fetches).sum(),
+            clients,
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for ClientUsage {
+    fn info(&self) -> Info {
+        Info {
+            name: "Client Usage Stats",
+            kind: Kind::Request,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &mut Data<'_>) {
+        if let Some(kind) = RequestKind::of(req.uri().path().as_str()) {
+            let headers = req.headers();
+            self.record(kind, headers.get_one("X-Client"), headers.get_one("User-Agent"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(fingerprint(None, Some("python-requests/2.31.0")), "python-requests");
+        assert_eq!(fingerprint(None, Some("langchain/0.2 python-httpx/0.27")), "langchain");
+        assert_eq!(fingerprint(None, Some("curl/8.5.0")), "curl");
+        assert_eq!(fingerprint(None, Some("Mozilla/5.0 (X11; Linux x86_64)")), "browser");
+        assert_eq!(fingerprint(Some("Fleet-Runner/1.4"), Some("curl/8.5.0")), "fleet-runner");
+        assert_eq!(fingerprint(None, Some("homegrown")), "other");
+        assert_eq!(fingerprint(None, None), "unknown");
+    }
+}
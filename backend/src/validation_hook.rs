@@ -0,0 +1,444 @@
+//! Write-ahead validation. A board can name a policy service that is asked,
+//! synchronously and before anything is written, whether a task create or
+//! move may go ahead — so teams can put governance around autonomous agents
+//! without trusting every client. Every path that puts a task on a board or
+//! into another column asks: single creates and moves, PATCHes, reorders,
+//! batches, layout snapshots, CSV and GitHub imports, inbound webhooks, board
+//! merges and trash restores.
+//!
+//! The service gets a signed POST (same `X-Kanban-Signature` scheme as
+//! webhooks) and answers `{"allow": true}` or `{"allow": false, "reason": …}`.
+//! A timeout, error status or unreadable answer is a failure: the board's
+//! `fail_open` policy decides whether the write then proceeds. The hook URL
+//! is held to the `UrlPolicy`, and the call is never made while a
+//! pooled connection is held (see `run`).
+
+use std::cell::{Cell, RefCell};
+use std::time::Duration;
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::{Connection, OptionalExtension};
+use serde::Deserialize;
+
+use crate::access;
+use crate::auth::BoardToken;
+use crate::db::{hash_key, DbPool};
+use crate::models::*;
+use crate::events::EventBus;
+use crate::routes::{db_error, not_found, with_db, with_tx};
+use crate::url_policy::OutboundClient;
+use crate::webhooks::sign_payload;
+
+/// Action names sent as `action` and `X-Kanban-Event`.
+pub const TASK_CREATE: &str = "task.create";
+pub const TASK_MOVE: &str = "task.move";
+
+const DEFAULT_TIMEOUT_MS: i64 = 2000;
+const MAX_TIMEOUT_MS: i64 = 10_000;
+
+/// Times `run` asks again before giving up on a write whose request keeps
+/// changing.
+const MAX_ASKS: usize = 3;
+
+#[derive(Clone)]
+struct Hook {
+    url: String,
+    secret: String,
+    timeout_ms: i64,
+    fail_open: bool,
+}
+
+#[derive(Deserialize)]
+struct Verdict {
+    allow: bool,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+fn load_hook(conn: &Connection, board_id: &str) -> rusqlite::Result<Option<Hook>> {
+    conn.query_row(
+        "SELECT url, secret, timeout_ms, fail_open FROM validation_hooks WHERE board_id = ?1",
+        rusqlite::params![board_id],
+        |row| {
+            Ok(Hook {
+                url: row.get(0)?,
+                secret: row.get(1)?,
+                timeout_ms: row.get(2)?,
+                fail_open: row.get::<_, i32>(3)? == 1,
+            })
+        },
+    )
+    .optional()
+}
+
+/// POST the request and wait for the verdict.
+async fn ask(client: &OutboundClient, hook: &Hook, action: &str, board_id: &str, body: &[u8]) -> Result<Verdict, String> {
+    let resp = client
+        .post(&hook.url)?
+        .header("Content-Type", "application/json")
+        .header("X-Kanban-Signature", format!("sha256={}", sign_payload(&hook.secret, body)))
+        .header("X-Kanban-Event", action)
+        .header("X-Kanban-Board", board_id)
+        .body(body.to_vec())
+        .timeout(Duration::from_millis(hook.timeout_ms as u64))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        return Err(format!("HTTP {}", resp.status().as_u16()));
+    }
+    resp.json::<Verdict>().await.map_err(|e| format!("unreadable verdict: {}", e))
+}
+
+/// A hook request found by `check`, sent by `run` once the connection is back
+/// in the pool.
+struct Call {
+    hook: Hook,
+    action: String,
+    board_id: String,
+    /// The request body, minus its timestamp
+    payload: serde_json::Value,
+}
+
+impl Call {
+    /// Ask the hook; Ok if the write may go ahead.
+    async fn decide(&self, client: &OutboundClient) -> Answer {
+        let mut payload = self.payload.clone();
+        payload["timestamp"] = serde_json::json!(chrono::Utc::now().to_rfc3339());
+        let body = serde_json::to_vec(&payload).unwrap_or_default();
+
+        match ask(client, &self.hook, &self.action, &self.board_id, &body).await {
+            Ok(Verdict { allow: true, .. }) => Ok(()),
+            Ok(Verdict { allow: false, reason }) => Err((
+                Status::Conflict,
+                Json(ApiError {
+                    error: match reason.filter(|r| !r.trim().is_empty()) {
+                        Some(reason) => format!("Rejected by the board's validation hook: {}", reason),
+                        None => "Rejected by the board's validation hook".to_string(),
+                    },
+                    code: "VALIDATION_REJECTED".to_string(),
+                    status: 409,
+                }),
+            )),
+            Err(e) if self.hook.fail_open => {
+                eprintln!(
+                    "⚠️  Validation hook for board {} failed ({}); allowing {} (fail-open)",
+                    self.board_id, e, self.action
+                );
+                Ok(())
+            }
+            Err(e) => Err(unavailable(&format!(
+                "The board's validation hook could not be reached ({}); try again later",
+                e
+            ))),
+        }
+    }
+}
+
+fn unavailable(error: &str) -> (Status, Json<ApiError>) {
+    (
+        Status::ServiceUnavailable,
+        Json(ApiError {
+            error: error.to_string(),
+            code: "VALIDATION_UNAVAILABLE".to_string(),
+            status: 503,
+        }),
+    )
+}
+
+/// The hook's answer to one request: Ok, or the error the write fails with.
+type Answer = Result<(), (Status, Json<ApiError>)>;
+
+/// A write's standing with the board's validation hook, handed to the
+/// route's database work by `run`.
+pub struct Gate {
+    /// Requests the hook has answered, with its answer
+    answered: Vec<(serde_json::Value, Answer)>,
+    /// Checks this pass settled from `answered` before the first it had to ask
+    settled: Cell<usize>,
+    /// The requests `check` stopped the write to send
+    pending: RefCell<Vec<Call>>,
+}
+
+/// Run a route's database work, asking the board's validation hook wherever
+/// it calls `check`. Each pass runs in its own transaction, committed only
+/// when the work succeeds. A pass that reaches unanswered requests stops
+/// there and is rolled back; the hook is asked with the connection back in
+/// the pool, and the work runs again with the answers, going through once
+/// every request it makes has one.
+pub async fn run<T, E>(
+    db: &DbPool,
+    client: &OutboundClient,
+    work: impl FnOnce(&Connection, &Gate) -> Result<T, E> + Clone + Send + 'static,
+) -> Result<T, E>
+where
+    T: Send + 'static,
+    E: From<(Status, Json<ApiError>)> + Send + 'static,
+{
+    let mut answered = Vec::new();
+    let (mut furthest, mut repeats) = (None, 0);
+    loop {
+        let (pass, known) = (work.clone(), answered.clone());
+        let outcome = with_tx(db, move |conn| {
+            let gate = Gate {
+                answered: known,
+                settled: Cell::new(0),
+                pending: RefCell::new(Vec::new()),
+            };
+            let result = pass(conn, &gate);
+            let calls = gate.pending.take();
+            match result {
+                _ if !calls.is_empty() => Err(Pass::Ask(calls, gate.settled.get())),
+                Ok(value) => Ok(value),
+                Err(e) => Err(Pass::Failed(e)),
+            }
+        })
+        .await;
+        let (calls, settled) = match outcome {
+            Ok(value) => return Ok(value),
+            Err(Pass::Failed(e)) => return Err(e),
+            Err(Pass::Ask(calls, settled)) => (calls, settled),
+        };
+        // Stopping no further along than last time means the request changed
+        // while the hook was deciding
+        if furthest.is_some_and(|f| settled <= f) {
+            repeats += 1;
+            if repeats > MAX_ASKS {
+                return Err(unavailable(
+                    "The task kept changing while the board's validation hook was deciding; try again",
+                )
+                .into());
+            }
+        }
+        furthest = furthest.max(Some(settled));
+        for call in calls {
+            let answer = call.decide(client).await;
+            answered.push((call.payload, answer));
+        }
+    }
+}
+
+/// Why a pass of `run` stopped without committing.
+enum Pass<E> {
+    Failed(E),
+    /// It reached requests the hook has to be asked first, after settling
+    /// this many from earlier answers
+    Ask(Vec<Call>, usize),
+}
+
+impl<E: From<(Status, Json<ApiError>)>> From<(Status, Json<ApiError>)> for Pass<E> {
+    fn from(e: (Status, Json<ApiError>)) -> Self {
+        Pass::Failed(e.into())
+    }
+}
+
+/// Ask the board's validation hook whether a write may proceed; a no-op for
+/// boards without one. `subject` carries the action's details (`task`, and
+/// for moves `from_column_id` / `to_column_id`). A veto is a 409
+/// `VALIDATION_REJECTED`; a failed call on a fail-closed hook is a 503
+/// `VALIDATION_UNAVAILABLE`. Must come before the work writes anything.
+pub fn check(
+    conn: &Connection,
+    gate: &Gate,
+    board_id: &str,
+    action: &str,
+    actor: &str,
+    subject: serde_json::Value,
+) -> Result<(), (Status, Json<ApiError>)> {
+    check_all(conn, gate, board_id, actor, [(action, subject)])
+}
+
+/// `check` for several writes at once, so a batch is asked about in one
+/// round instead of one rollback per item. Err is the first refusal.
+pub fn check_all<'a>(
+    conn: &Connection,
+    gate: &Gate,
+    board_id: &str,
+    actor: &str,
+    checks: impl IntoIterator<Item = (&'a str, serde_json::Value)>,
+) -> Result<(), (Status, Json<ApiError>)> {
+    let Some(hook) = load_hook(conn, board_id).map_err(|e| db_error(&e.to_string()))? else {
+        return Ok(());
+    };
+    let mut outcome = Ok(());
+    for (action, subject) in checks {
+        let mut payload = serde_json::json!({
+            "action": action,
+            "board_id": board_id,
+            "actor": actor,
+        });
+        if let (Some(payload), serde_json::Value::Object(subject)) = (payload.as_object_mut(), subject) {
+            payload.extend(subject);
+        }
+        match gate.answered.iter().find(|(asked, _)| *asked == payload) {
+            Some((_, answer)) => {
+                if gate.pending.borrow().is_empty() {
+                    gate.settled.set(gate.settled.get() + 1);
+                }
+                if outcome.is_ok() {
+                    outcome = answer.clone();
+                }
+            }
+            None => gate.pending.borrow_mut().push(Call {
+                hook: hook.clone(),
+                action: action.to_string(),
+                board_id: board_id.to_string(),
+                payload,
+            }),
+        }
+    }
+    if !gate.pending.borrow().is_empty() {
+        // `run` sends the calls and discards this
+        return Err(unavailable("The board's validation hook has not been asked yet"));
+    }
+    outcome
+}
+
+/// `check_move`'s subject: `task` leaving its column for `to_column_id`.
+pub fn move_subject(task: &TaskResponse, to_column_id: &str) -> serde_json::Value {
+    serde_json::json!({
+        "task": task,
+        "from_column_id": task.column_id,
+        "to_column_id": to_column_id,
+    })
+}
+
+/// `check` for moving `task` into another column.
+pub fn check_move(
+    conn: &Connection,
+    gate: &Gate,
+    board_id: &str,
+    actor: &str,
+    task: &TaskResponse,
+    to_column_id: &str,
+) -> Result<(), (Status, Json<ApiError>)> {
+    check(conn, gate, board_id, TASK_MOVE, actor, move_subject(task, to_column_id))
+}
+
+fn invalid(error: &str, code: &str) -> (Status, Json<ApiError>) {
+    (
+        Status::BadRequest,
+        Json(ApiError {
+            error: error.to_string(),
+            code: code.to_string(),
+            status: 400,
+        }),
+    )
+}
+
+fn load_response(conn: &Connection, board_id: &str) -> Result<ValidationHookResponse, (Status, Json<ApiError>)> {
+    conn.query_row(
+        "SELECT url, timeout_ms, fail_open, created_at, updated_at FROM validation_hooks WHERE board_id = ?1",
+        rusqlite::params![board_id],
+        |row| {
+            Ok(ValidationHookResponse {
+                board_id: board_id.to_string(),
+                url: row.get(0)?,
+                secret: None,
+                timeout_ms: row.get(1)?,
+                fail_open: row.get::<_, i32>(2)? == 1,
+                created_at: row.get(3)?,
+                updated_at: row.get(4)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| db_error(&e.to_string()))?
+    .ok_or_else(|| not_found("Validation hook"))
+}
+
+/// Get the board's validation hook — requires manage key.
+#[get("/boards/<board_id>/validation-hook")]
+pub async fn get_validation_hook(
+    board_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<ValidationHookResponse>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        load_response(conn, board_id).map(Json)
+    })
+    .await
+}
+
+/// Set or replace the board's validation hook — requires manage key. The
+/// signing secret is generated when the hook is first set and kept after.
+#[put("/boards/<board_id>/validation-hook", format = "json", data = "<req>")]
+pub async fn set_validation_hook(
+    board_id: &str,
+    req: Json<SetValidationHookRequest>,
+    token: BoardToken,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+) -> Result<Json<ValidationHookResponse>, (Status, Json<ApiError>)> {
+    let url = req.url.trim().to_owned();
+    if url.is_empty() {
+        return Err(invalid("Validation hook URL cannot be empty", "EMPTY_URL"));
+    }
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err(invalid("Validation hook URL must be http:// or https://", "INVALID_INPUT"));
+    }
+    bus.outbound().policy.check(&url).map_err(|e| invalid(&e, "INVALID_URL"))?;
+    let timeout_ms = req.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS);
+    if !(1..=MAX_TIMEOUT_MS).contains(&timeout_ms) {
+        return Err(invalid(
+            &format!("timeout_ms must be between 1 and {}", MAX_TIMEOUT_MS),
+            "INVALID_INPUT",
+        ));
+    }
+
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        let secret = format!("vhsec_{}", uuid::Uuid::new_v4().to_string().replace('-', ""));
+        let created = conn
+            .execute(
+                "INSERT INTO validation_hooks (board_id, url, secret, timeout_ms, fail_open) VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT(board_id) DO NOTHING",
+                rusqlite::params![board_id, url, secret, timeout_ms, req.fail_open as i32],
+            )
+            .map_err(|e| db_error(&e.to_string()))?
+            > 0;
+        if !created {
+            conn.execute(
+                "UPDATE validation_hooks SET url = ?2, timeout_ms = ?3, fail_open = ?4, updated_at = datetime('now')
+                 WHERE board_id = ?1",
+                rusqlite::params![board_id, url, timeout_ms, req.fail_open as i32],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        }
+        let mut response = load_response(conn, board_id)?;
+        if created {
+            response.secret = Some(secret);
+        }
+        Ok(Json(response))
+    })
+    .await
+}
+
+/// Remove the board's validation hook — requires manage key.
+#[delete("/boards/<board_id>/validation-hook")]
+pub async fn delete_validation_hook(
+    board_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
+    let board_id = board_id.to_owned();
+    with_db(db, move |conn| {
+        let board_id = board_id.as_str();
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        let affected = conn
+            .execute("DELETE FROM validation_hooks WHERE board_id = ?1", rusqlite::params![board_id])
+            .map_err(|e| db_error(&e.to_string()))?;
+        if affected == 0 {
+            return Err(not_found("Validation hook"));
+        }
+        Ok(Json(serde_json::json!({"deleted": true, "board_id": board_id})))
+    })
+    .await
+}
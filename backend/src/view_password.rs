@@ -0,0 +1,194 @@
+//! View passwords: a lighter alternative to private boards for human
+//! viewers. A board with a view password answers reads only to a board key
+//! or a view token, which anyone with the password can get from
+//! `POST /boards/<id>/view-session`. Tokens are signed with the stored
+//! password hash and carry their expiry, so nothing is kept per session and
+//! changing or clearing the password ends every session at once. The token
+//! also comes back as the `kb_view` cookie, scoped to the board's API paths,
+//! so browsers (and `EventSource`) send it without help.
+
+use rand::RngCore;
+use rocket::http::{Cookie, CookieJar, SameSite, Status};
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::OptionalExtension;
+use sha2::Sha256;
+
+use crate::db::DbPool;
+use crate::models::*;
+use crate::rate_limit::{IpRateLimit, RouteGroup};
+use crate::routes::{db_error, with_db};
+use crate::signing::{hmac, same};
+
+/// Cookie the view token is set in.
+pub const VIEW_COOKIE: &str = "kb_view";
+/// How long a view token works.
+const VIEW_TOKEN_TTL_SECONDS: i64 = 12 * 3600;
+/// PBKDF2-HMAC-SHA256 rounds when hashing a password, to slow down guessing.
+const PBKDF2_ROUNDS: u32 = 100_000;
+/// Prefix of stored hashes, which carry their round count.
+const PBKDF2_PREFIX: &str = "pbkdf2-sha256$";
+const MIN_PASSWORD_CHARS: usize = 4;
+const MAX_PASSWORD_CHARS: usize = 200;
+
+fn derive(salt: &[u8], password: &str, rounds: u32) -> Vec<u8> {
+    let mut digest = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, rounds, &mut digest);
+    digest.to_vec()
+}
+
+/// Validate a new password and hash it as
+/// `pbkdf2-sha256$<rounds>$<salt>$<digest>` (hex). Slow on purpose, so call
+/// it off the async workers.
+pub fn hash_password(password: &str) -> Result<String, (Status, Json<ApiError>)> {
+    let chars = password.chars().count();
+    if !(MIN_PASSWORD_CHARS..=MAX_PASSWORD_CHARS).contains(&chars) {
+        return Err((
+            Status::BadRequest,
+            Json(ApiError {
+                error: format!(
+                    "view_password must be {}-{} characters (empty string removes it)",
+                    MIN_PASSWORD_CHARS, MAX_PASSWORD_CHARS
+                ),
+                code: "INVALID_INPUT".to_string(),
+                status: 400,
+            }),
+        ));
+    }
+    let mut salt = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut salt);
+    Ok(format!(
+        "{}{}${}${}",
+        PBKDF2_PREFIX,
+        PBKDF2_ROUNDS,
+        hex::encode(salt),
+        hex::encode(derive(&salt, password, PBKDF2_ROUNDS))
+    ))
+}
+
+fn verify_password(stored: &str, password: &str) -> bool {
+    let Some((rounds, rest)) = stored.strip_prefix(PBKDF2_PREFIX).and_then(|rest| rest.split_once('$')) else {
+        return false;
+    };
+    let Some((salt, digest)) = rest.split_once('$') else {
+        return false;
+    };
+    match (rounds.parse::<u32>(), hex::decode(salt), hex::decode(digest)) {
+        (Ok(rounds), Ok(salt), Ok(digest)) if rounds > 0 => same(&derive(&salt, password, rounds), &digest),
+        _ => false,
+    }
+}
+
+fn sign(board_id: &str, password_hash: &str, expires: i64) -> String {
+    hex::encode(hmac(password_hash.as_bytes(), format!("{}:{}", board_id, expires).as_bytes()))
+}
+
+/// Whether `token` is an unexpired view token for the board's current password.
+pub fn token_valid(board_id: &str, password_hash: &str, token: &str) -> bool {
+    let Some((expires, signature)) = token.strip_prefix("vt_").and_then(|t| t.split_once('.')) else {
+        return false;
+    };
+    let Ok(expires) = expires.parse::<i64>() else {
+        return false;
+    };
+    expires > chrono::Utc::now().timestamp() && same(sign(board_id, password_hash, expires).as_bytes(), signature.as_bytes())
+}
+
+/// Trade the board's view password for a view token — public, but each
+/// attempt counts against the client IP's `view_session` limit. The token is
+/// returned and set as the `kb_view` cookie.
+#[post("/boards/<board_id>/view-session", format = "json", data = "<req>")]
+pub async fn create_view_session(
+    board_id: &str,
+    req: Json<ViewSessionRequest>,
+    cookies: &CookieJar<'_>,
+    rate_limit: IpRateLimit<'_>,
+    db: &State<DbPool>,
+) -> Result<Json<ViewSessionResponse>, (Status, Json<ApiError>)> {
+    let board = board_id.to_owned();
+    let password_hash = with_db(db, move |conn| {
+        let board_id = board.as_str();
+        crate::access::require_board_exists(conn, board_id)?;
+        conn.query_row(
+            "SELECT view_password_hash FROM boards WHERE id = ?1",
+            rusqlite::params![board_id],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .optional()
+        .map(Option::flatten)
+        .map_err(|e| db_error(&e.to_string()))
+    })
+    .await?;
+    let Some(password_hash) = password_hash else {
+        return Err((
+            Status::BadRequest,
+            Json(ApiError {
+                error: "This board has no view password".to_string(),
+                code: "NO_VIEW_PASSWORD".to_string(),
+                status: 400,
+            }),
+        ));
+    };
+    if let Some(result) = rate_limit.check(RouteGroup::ViewSession).await.filter(|r| !r.allowed) {
+        return Err((
+            Status::TooManyRequests,
+            Json(ApiError {
+                error: format!("Too many view password attempts. Try again in {} seconds.", result.reset_secs),
+                code: "RATE_LIMIT_EXCEEDED".to_string(),
+                status: 429,
+            }),
+        ));
+    }
+    let (stored, password) = (password_hash.clone(), req.into_inner().password);
+    if !crate::db::blocking(move || verify_password(&stored, &password)).await {
+        return Err((
+            Status::Forbidden,
+            Json(ApiError {
+                error: "Wrong view password".to_string(),
+                code: "INVALID_PASSWORD".to_string(),
+                status: 403,
+            }),
+        ));
+    }
+
+    let expires = chrono::Utc::now().timestamp() + VIEW_TOKEN_TTL_SECONDS;
+    let token = format!("vt_{}.{}", expires, sign(board_id, &password_hash, expires));
+    cookies.add(
+        Cookie::build((VIEW_COOKIE, token.clone()))
+            .path(format!("/api/v1/boards/{}", board_id))
+            .http_only(true)
+            .same_site(SameSite::Lax)
+            .max_age(rocket::time::Duration::seconds(VIEW_TOKEN_TTL_SECONDS)),
+    );
+    Ok(Json(ViewSessionResponse {
+        board_id: board_id.to_string(),
+        token,
+        expires_at: chrono::DateTime::from_timestamp(expires, 0).unwrap_or_default().to_rfc3339(),
+        expires_in_seconds: VIEW_TOKEN_TTL_SECONDS,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_password_and_tokens() {
+        let hash = hash_password("hunter22").unwrap();
+        assert!(verify_password(&hash, "hunter22"));
+        assert!(!verify_password(&hash, "hunter2"));
+        assert_ne!(hash, hash_password("hunter22").unwrap(), "salted");
+        assert!(hash_password("abc").is_err());
+        assert!(hash.starts_with("pbkdf2-sha256$100000$"));
+        assert!(!verify_password("pbkdf2-sha256$0$00$00", "hunter22"));
+
+        let expires = chrono::Utc::now().timestamp() + 60;
+        let token = format!("vt_{}.{}", expires, sign("b1", &hash, expires));
+        assert!(token_valid("b1", &hash, &token));
+        assert!(!token_valid("b2", &hash, &token));
+        assert!(!token_valid("b1", &hash_password("hunter22").unwrap(), &token), "new password, new tokens");
+        let stale = chrono::Utc::now().timestamp() - 1;
+        assert!(!token_valid("b1", &hash, &format!("vt_{}.{}", stale, sign("b1", &hash, stale))));
+        assert!(!token_valid("b1", &hash, "vt_garbage"));
+    }
+}
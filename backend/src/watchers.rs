@@ -0,0 +1,129 @@
+//! Task watchers: names that follow a task without being its assignee.
+//! Watchers are listed on every task response, narrow the activity feed
+//! (`?watching=`), and are who notifications about a task fan out to.
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::Connection;
+
+use crate::access::{self, BoardRole};
+use crate::auth::{Actor, BoardToken};
+use crate::db::{hash_key, DbPool};
+use crate::models::*;
+use crate::routes::{db_error, not_found, with_db};
+
+/// Longest watcher name accepted.
+const MAX_NAME_LEN: usize = 100;
+/// Watchers a single task can have.
+const MAX_WATCHERS: usize = 50;
+
+/// A task's watchers, in the order they started watching.
+pub fn watchers(conn: &Connection, task_id: &str) -> rusqlite::Result<Vec<String>> {
+    conn.prepare_cached("SELECT name FROM task_watchers WHERE task_id = ?1 ORDER BY created_at, rowid")?
+        .query_map(rusqlite::params![task_id], |row| row.get(0))?
+        .collect()
+}
+
+fn invalid(error: String) -> (Status, Json<ApiError>) {
+    (
+        Status::BadRequest,
+        Json(ApiError {
+            error,
+            code: "INVALID_INPUT".to_string(),
+            status: 400,
+        }),
+    )
+}
+
+/// The watcher named by `?name=`, or else the request's actor.
+fn watcher_name(name: Option<&str>, actor: &Actor) -> Result<String, (Status, Json<ApiError>)> {
+    let name = match name.map(str::trim).filter(|n| !n.is_empty()) {
+        Some(name) => name,
+        None if !actor.is_anonymous() => actor.name(),
+        None => return Err(invalid("Say who is watching with ?name= (or ?actor= / X-Actor)".to_string())),
+    };
+    if name.chars().count() > MAX_NAME_LEN {
+        return Err(invalid(format!("Watcher names are at most {} characters", MAX_NAME_LEN)));
+    }
+    Ok(name.to_string())
+}
+
+fn require_task(conn: &Connection, board_id: &str, task_id: &str) -> Result<(), (Status, Json<ApiError>)> {
+    conn.query_row(
+        "SELECT 1 FROM tasks WHERE id = ?1 AND board_id = ?2",
+        rusqlite::params![task_id, board_id],
+        |_| Ok(()),
+    )
+    .map_err(|_| not_found("Task"))
+}
+
+fn response(conn: &Connection, task_id: &str) -> Result<Json<WatchersResponse>, (Status, Json<ApiError>)> {
+    Ok(Json(WatchersResponse {
+        task_id: task_id.to_string(),
+        watchers: watchers(conn, task_id).map_err(|e| db_error(&e.to_string()))?,
+    }))
+}
+
+/// Start watching a task — requires manage key. Watching twice is a no-op.
+#[post("/boards/<board_id>/tasks/<task_id>/watch?<name>")]
+pub async fn watch_task(
+    board_id: &str,
+    task_id: &str,
+    name: Option<&str>,
+    token: BoardToken,
+    actor: Actor,
+    db: &State<DbPool>,
+) -> Result<Json<WatchersResponse>, (Status, Json<ApiError>)> {
+    let name = watcher_name(name, &actor)?;
+    let (board_id, task_id) = (board_id.to_owned(), task_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, task_id) = (board_id.as_str(), task_id.as_str());
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Viewer)?;
+        require_task(conn, board_id, task_id)?;
+        let current = watchers(conn, task_id).map_err(|e| db_error(&e.to_string()))?;
+        if current.iter().any(|w| w.eq_ignore_ascii_case(&name)) {
+            return response(conn, task_id);
+        }
+        if current.len() >= MAX_WATCHERS {
+            return Err(invalid(format!("A task can have at most {} watchers", MAX_WATCHERS)));
+        }
+        conn.execute(
+            "INSERT INTO task_watchers (task_id, board_id, name) VALUES (?1, ?2, ?3)",
+            rusqlite::params![task_id, board_id, name],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        response(conn, task_id)
+    })
+    .await
+}
+
+/// Stop watching a task — requires manage key.
+#[delete("/boards/<board_id>/tasks/<task_id>/watch?<name>")]
+pub async fn unwatch_task(
+    board_id: &str,
+    task_id: &str,
+    name: Option<&str>,
+    token: BoardToken,
+    actor: Actor,
+    db: &State<DbPool>,
+) -> Result<Json<WatchersResponse>, (Status, Json<ApiError>)> {
+    let name = watcher_name(name, &actor)?;
+    let (board_id, task_id) = (board_id.to_owned(), task_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, task_id) = (board_id.as_str(), task_id.as_str());
+        access::require_role(conn, board_id, &hash_key(&token.0), BoardRole::Viewer)?;
+        require_task(conn, board_id, task_id)?;
+        let affected = conn
+            .execute(
+                "DELETE FROM task_watchers WHERE task_id = ?1 AND name = ?2",
+                rusqlite::params![task_id, name],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        if affected == 0 {
+            return Err(not_found("Watcher"));
+        }
+        response(conn, task_id)
+    })
+    .await
+}
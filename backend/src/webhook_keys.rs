@@ -0,0 +1,254 @@
+//! Webhook signing key rotation. Besides its own secret (key id `default`),
+//! a webhook can hold extra keys, each signing only inside its activation
+//! window. While more than one key is active every delivery carries a
+//! signature per key, so a consumer can switch verification keys at its own
+//! pace: add a key, deploy it on the consumer, then retire the old one.
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use rusqlite::Connection;
+
+use crate::access;
+use crate::auth::BoardToken;
+use crate::db::{hash_key, DbPool};
+use crate::import::db_time;
+use crate::models::*;
+use crate::routes::{db_error, not_found, require_webhook_on_board, with_db};
+use crate::webhooks::{SigningKey, DEFAULT_KEY_ID};
+
+/// Extra keys a webhook can hold at once, expired ones included.
+const MAX_KEYS: i64 = 10;
+
+/// Keys that sign a delivery right now, newest first with the default key
+/// last. Falls back to the webhook's own secret if every window has closed,
+/// so deliveries are never unsigned.
+pub fn active_keys(conn: &Connection, webhook_id: &str) -> rusqlite::Result<Vec<SigningKey>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, secret FROM (
+             SELECT id, secret, active_from, 0 AS is_default FROM webhook_signing_keys
+             WHERE webhook_id = ?1 AND active_from <= datetime('now')
+               AND (active_until IS NULL OR active_until > datetime('now'))
+             UNION ALL
+             SELECT ?2, secret, created_at, 1 FROM webhooks
+             WHERE id = ?1 AND (secret_expires_at IS NULL OR secret_expires_at > datetime('now'))
+         )
+         ORDER BY is_default ASC, active_from DESC, id ASC",
+    )?;
+    let keys = stmt
+        .query_map(rusqlite::params![webhook_id, DEFAULT_KEY_ID], |row| {
+            Ok(SigningKey {
+                id: row.get(0)?,
+                secret: row.get(1)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    if !keys.is_empty() {
+        return Ok(keys);
+    }
+    let secret: String =
+        conn.query_row("SELECT secret FROM webhooks WHERE id = ?1", rusqlite::params![webhook_id], |row| row.get(0))?;
+    Ok(vec![SigningKey::default_key(&secret)])
+}
+
+fn invalid(error: String) -> (Status, Json<ApiError>) {
+    (
+        Status::BadRequest,
+        Json(ApiError {
+            error,
+            code: "INVALID_INPUT".to_string(),
+            status: 400,
+        }),
+    )
+}
+
+/// An RFC 3339 request time in the form the database compares.
+fn parse_time(field: &str, value: &str) -> Result<String, (Status, Json<ApiError>)> {
+    db_time(value).ok_or_else(|| invalid(format!("{} must be an RFC 3339 timestamp", field)))
+}
+
+const STATUS_SQL: &str = "CASE WHEN active_from > datetime('now') THEN 'pending'
+                               WHEN active_until IS NOT NULL AND active_until <= datetime('now') THEN 'expired'
+                               ELSE 'active' END";
+
+fn row_to_key(webhook_id: &str, row: &rusqlite::Row) -> rusqlite::Result<SigningKeyResponse> {
+    Ok(SigningKeyResponse {
+        id: row.get(0)?,
+        webhook_id: webhook_id.to_string(),
+        secret: None,
+        active_from: row.get(1)?,
+        active_until: row.get(2)?,
+        status: row.get(3)?,
+        created_at: row.get(4)?,
+    })
+}
+
+fn load_keys(conn: &Connection, webhook_id: &str) -> Result<Vec<SigningKeyResponse>, (Status, Json<ApiError>)> {
+    conn.prepare(&format!(
+        "SELECT id, active_from, active_until, {status}, created_at FROM (
+             SELECT ?2 AS id, created_at AS active_from, secret_expires_at AS active_until, created_at, 0 AS sort
+             FROM webhooks WHERE id = ?1
+             UNION ALL
+             SELECT id, active_from, active_until, created_at, 1 FROM webhook_signing_keys WHERE webhook_id = ?1
+         )
+         ORDER BY sort ASC, active_from ASC, id ASC",
+        status = STATUS_SQL
+    ))
+    .map_err(|e| db_error(&e.to_string()))?
+    .query_map(rusqlite::params![webhook_id, DEFAULT_KEY_ID], |row| row_to_key(webhook_id, row))
+    .map_err(|e| db_error(&e.to_string()))?
+    .collect::<rusqlite::Result<Vec<_>>>()
+    .map_err(|e| db_error(&e.to_string()))
+}
+
+fn load_key(conn: &Connection, webhook_id: &str, key_id: &str) -> Result<SigningKeyResponse, (Status, Json<ApiError>)> {
+    load_keys(conn, webhook_id)?
+        .into_iter()
+        .find(|k| k.id == key_id)
+        .ok_or_else(|| not_found("Signing key"))
+}
+
+/// List a webhook's signing keys, the default key first — requires manage
+/// key. Secrets are not included.
+#[get("/boards/<board_id>/webhooks/<webhook_id>/signing-keys")]
+pub async fn list_signing_keys(
+    board_id: &str,
+    webhook_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<Vec<SigningKeyResponse>>, (Status, Json<ApiError>)> {
+    let (board_id, webhook_id) = (board_id.to_owned(), webhook_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, webhook_id) = (board_id.as_str(), webhook_id.as_str());
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        require_webhook_on_board(conn, board_id, webhook_id)?;
+        load_keys(conn, webhook_id).map(Json)
+    })
+    .await
+}
+
+/// Add a signing key — requires manage key. The secret is only returned here.
+#[post("/boards/<board_id>/webhooks/<webhook_id>/signing-keys", format = "json", data = "<req>")]
+pub async fn create_signing_key(
+    board_id: &str,
+    webhook_id: &str,
+    req: Option<Json<CreateSigningKeyRequest>>,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<SigningKeyResponse>, (Status, Json<ApiError>)> {
+    let req = req.map(|r| r.into_inner()).unwrap_or_default();
+    let active_from = match req.active_from.as_deref() {
+        Some(t) => parse_time("active_from", t)?,
+        None => chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+    };
+    let active_until = req.active_until.as_deref().map(|t| parse_time("active_until", t)).transpose()?;
+    if active_until.as_ref().is_some_and(|until| *until <= active_from) {
+        return Err(invalid("active_until must be after active_from".to_string()));
+    }
+
+    let (board_id, webhook_id) = (board_id.to_owned(), webhook_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, webhook_id) = (board_id.as_str(), webhook_id.as_str());
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        require_webhook_on_board(conn, board_id, webhook_id)?;
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM webhook_signing_keys WHERE webhook_id = ?1",
+                rusqlite::params![webhook_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        if count >= MAX_KEYS {
+            return Err(invalid(format!(
+                "A webhook can hold at most {} signing keys; delete expired ones first",
+                MAX_KEYS
+            )));
+        }
+
+        let key_id = format!("whk_{}", &uuid::Uuid::new_v4().simple().to_string()[..12]);
+        let secret = format!("whsec_{}", uuid::Uuid::new_v4().to_string().replace('-', ""));
+        conn.execute(
+            "INSERT INTO webhook_signing_keys (id, webhook_id, board_id, secret, active_from, active_until)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![key_id, webhook_id, board_id, secret, active_from, active_until],
+        )
+        .map_err(|e| db_error(&e.to_string()))?;
+        let mut key = load_key(conn, webhook_id, &key_id)?;
+        key.secret = Some(secret);
+        Ok(Json(key))
+    })
+    .await
+}
+
+/// Set when a key stops signing (`null` = never) — requires manage key.
+/// Works on `default` too, to retire the webhook's own secret.
+#[patch("/boards/<board_id>/webhooks/<webhook_id>/signing-keys/<key_id>", format = "json", data = "<req>")]
+pub async fn update_signing_key(
+    board_id: &str,
+    webhook_id: &str,
+    key_id: &str,
+    req: Json<UpdateSigningKeyRequest>,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<SigningKeyResponse>, (Status, Json<ApiError>)> {
+    let active_until = req.active_until.as_deref().map(|t| parse_time("active_until", t)).transpose()?;
+
+    let (board_id, key_id, webhook_id) = (board_id.to_owned(), key_id.to_owned(), webhook_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, key_id, webhook_id) = (board_id.as_str(), key_id.as_str(), webhook_id.as_str());
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        require_webhook_on_board(conn, board_id, webhook_id)?;
+        let key = load_key(conn, webhook_id, key_id)?;
+        if active_until.as_ref().is_some_and(|until| *until <= key.active_from) {
+            return Err(invalid("active_until must be after active_from".to_string()));
+        }
+        if key_id == DEFAULT_KEY_ID {
+            conn.execute(
+                "UPDATE webhooks SET secret_expires_at = ?1 WHERE id = ?2",
+                rusqlite::params![active_until, webhook_id],
+            )
+        } else {
+            conn.execute(
+                "UPDATE webhook_signing_keys SET active_until = ?1 WHERE id = ?2",
+                rusqlite::params![active_until, key_id],
+            )
+        }
+        .map_err(|e| db_error(&e.to_string()))?;
+        load_key(conn, webhook_id, key_id).map(Json)
+    })
+    .await
+}
+
+/// Delete a signing key — requires manage key. The default key can only be
+/// retired, not deleted.
+#[delete("/boards/<board_id>/webhooks/<webhook_id>/signing-keys/<key_id>")]
+pub async fn delete_signing_key(
+    board_id: &str,
+    webhook_id: &str,
+    key_id: &str,
+    token: BoardToken,
+    db: &State<DbPool>,
+) -> Result<Json<serde_json::Value>, (Status, Json<ApiError>)> {
+    let (board_id, key_id, webhook_id) = (board_id.to_owned(), key_id.to_owned(), webhook_id.to_owned());
+    with_db(db, move |conn| {
+        let (board_id, key_id, webhook_id) = (board_id.as_str(), key_id.as_str(), webhook_id.as_str());
+        access::require_manage_key(conn, board_id, &hash_key(&token.0))?;
+        require_webhook_on_board(conn, board_id, webhook_id)?;
+        if key_id == DEFAULT_KEY_ID {
+            return Err(invalid(
+                "The default key can't be deleted; set its active_until to retire it".to_string(),
+            ));
+        }
+        let affected = conn
+            .execute(
+                "DELETE FROM webhook_signing_keys WHERE id = ?1 AND webhook_id = ?2",
+                rusqlite::params![key_id, webhook_id],
+            )
+            .map_err(|e| db_error(&e.to_string()))?;
+        if affected == 0 {
+            return Err(not_found("Signing key"));
+        }
+        Ok(Json(serde_json::json!({"deleted": true, "id": key_id})))
+    })
+    .await
+}
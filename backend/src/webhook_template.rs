@@ -0,0 +1,188 @@
+//! Webhook payload templates. A webhook with a `template` is sent that JSON
+//! instead of the event, with `{{path}}` placeholders filled in from the
+//! event — so Slack, PagerDuty, Zapier and the like can be pointed at
+//! directly. A string that is a single placeholder takes the value as is
+//! (numbers and objects included); placeholders inside longer strings are
+//! replaced with the value's text. Unknown paths render as null / empty.
+
+use rusqlite::Connection;
+
+use crate::events::BoardEvent;
+use crate::slack::{self, Markup};
+
+/// Largest template accepted, serialized.
+pub const MAX_TEMPLATE_BYTES: usize = 16 * 1024;
+
+/// Top-level names a placeholder may start with.
+const ROOTS: &[&str] = &[
+    "version",
+    "event",
+    "board_id",
+    "seq",
+    "occurred_at",
+    "timestamp",
+    "data",
+    "board_name",
+    "task_title",
+    "summary",
+    "board_url",
+];
+
+const PLAIN: Markup = Markup { escape: str::to_string, bold: str::to_string, quote: str::to_string };
+
+/// What placeholders can refer to: the payload a JSON webhook would get
+/// (`payload`, the envelope plus `timestamp`), and names looked up for
+/// readable messages.
+pub fn context(conn: &Connection, event: &BoardEvent, payload: &serde_json::Value) -> serde_json::Value {
+    let mut context = payload.clone();
+    context["board_name"] = serde_json::json!(slack::board_name(conn, &event.board_id));
+    context["task_title"] = serde_json::json!(event.data.get("task_id").map(|_| slack::task_title(conn, event)));
+    context["summary"] = serde_json::json!(slack::summary(conn, event, &PLAIN));
+    context["board_url"] = serde_json::json!(slack::public_url().map(|base| format!("{}/board/{}", base, event.board_id)));
+    context
+}
+
+/// The placeholder paths in a string, in order; Err for an unclosed `{{`.
+fn placeholders(text: &str) -> Result<Vec<(usize, usize, &str)>, String> {
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(start) = text[from..].find("{{").map(|i| from + i) {
+        let end = text[start..]
+            .find("}}")
+            .map(|i| start + i + 2)
+            .ok_or_else(|| format!("Unclosed placeholder in \"{}\"", text))?;
+        found.push((start, end, text[start + 2..end - 2].trim()));
+        from = end;
+    }
+    Ok(found)
+}
+
+fn lookup<'a>(context: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    path.split('.').try_fold(context, |value, key| match value {
+        serde_json::Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+        _ => value.get(key),
+    })
+}
+
+/// Check a template before it's saved: size, well-formed placeholders, and
+/// paths starting at a known name.
+pub fn validate(template: &serde_json::Value) -> Result<(), String> {
+    validate_paths(template, ROOTS)
+}
+
+/// `validate`, for a template rendered against a context whose top-level
+/// names are `roots`.
+pub fn validate_paths(template: &serde_json::Value, roots: &[&str]) -> Result<(), String> {
+    if template.is_null() {
+        return Err("template must be a JSON value; use null only to remove it".to_string());
+    }
+    if serde_json::to_vec(template).map_or(0, |t| t.len()) > MAX_TEMPLATE_BYTES {
+        return Err(format!("template is larger than {} bytes", MAX_TEMPLATE_BYTES));
+    }
+    let mut strings = Vec::new();
+    collect_strings(template, &mut strings);
+    for text in strings {
+        for (_, _, path) in placeholders(text)? {
+            let root = path.split('.').next().unwrap_or_default();
+            if !roots.contains(&root) {
+                return Err(format!("Unknown placeholder {{{{{}}}}}; paths start with one of: {}", path, roots.join(", ")));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn collect_strings<'a>(value: &'a serde_json::Value, out: &mut Vec<&'a str>) {
+    match value {
+        serde_json::Value::String(s) => out.push(s),
+        serde_json::Value::Array(items) => items.iter().for_each(|v| collect_strings(v, out)),
+        serde_json::Value::Object(map) => map.iter().for_each(|(k, v)| {
+            out.push(k);
+            collect_strings(v, out)
+        }),
+        _ => {}
+    }
+}
+
+fn render_str(text: &str, context: &serde_json::Value) -> serde_json::Value {
+    let Ok(found) = placeholders(text) else {
+        return serde_json::json!(text);
+    };
+    if let [(0, end, path)] = found.as_slice() {
+        if *end == text.len() {
+            return lookup(context, path).cloned().unwrap_or(serde_json::Value::Null);
+        }
+    }
+    let mut out = String::new();
+    let mut from = 0;
+    for (start, end, path) in found {
+        out.push_str(&text[from..start]);
+        match lookup(context, path) {
+            Some(serde_json::Value::String(s)) => out.push_str(s),
+            Some(serde_json::Value::Null) | None => {}
+            Some(other) => out.push_str(&other.to_string()),
+        }
+        from = end;
+    }
+    out.push_str(&text[from..]);
+    serde_json::json!(out)
+}
+
+/// Fill in a template's placeholders (in keys too) from `context`.
+pub fn render(template: &serde_json::Value, context: &serde_json::Value) -> serde_json::Value {
+    match template {
+        serde_json::Value::String(s) => render_str(s, context),
+        serde_json::Value::Array(items) => items.iter().map(|v| render(v, context)).collect(),
+        serde_json::Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| {
+                let key = match render_str(k, context) {
+                    serde_json::Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                (key, render(v, context))
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_and_validate() {
+        let context = serde_json::json!({
+            "event": "task.moved",
+            "seq": 7,
+            "data": {"task_id": "t1", "labels": ["bug", "p1"]},
+            "board_name": "Launch",
+            "task_title": null,
+        });
+        let template = serde_json::json!({
+            "text": "{{board_name}}: {{ event }} #{{seq}} ({{data.labels.0}}){{task_title}}",
+            "seq": "{{seq}}",
+            "data": "{{data}}",
+            "missing": "{{data.nope}}",
+            "{{event}}": [true, 3, "plain"],
+        });
+        assert_eq!(
+            render(&template, &context),
+            serde_json::json!({
+                "text": "Launch: task.moved #7 (bug)",
+                "seq": 7,
+                "data": {"task_id": "t1", "labels": ["bug", "p1"]},
+                "missing": null,
+                "task.moved": [true, 3, "plain"],
+            })
+        );
+
+        assert!(validate(&template).is_ok());
+        assert!(validate(&serde_json::json!({"text": "{{secret}}"})).unwrap_err().contains("Unknown placeholder"));
+        assert!(validate(&serde_json::json!({"text": "{{event"})).unwrap_err().contains("Unclosed"));
+        assert!(validate(&serde_json::Value::Null).is_err());
+        assert!(validate(&serde_json::json!("x".repeat(MAX_TEMPLATE_BYTES))).is_err());
+    }
+}
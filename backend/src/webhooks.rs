@@ -1,22 +1,261 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
 use hmac::{Hmac, Mac};
 use sha2::Sha256;
+use tokio::sync::Notify;
 
 use crate::db::WebhookDb;
-use crate::events::BoardEvent;
+use crate::event_types;
+use crate::events::{BoardEvent, Envelope};
+use crate::models::WebhookFilters;
+use crate::url_policy::OutboundClient;
+use crate::{discord, secrets, slack, telegram, webhook_keys, webhook_template};
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// Webhook kinds: `json` receives the raw signed event, `slack` a Block Kit
+/// message and `discord` an embed for each task event.
+pub const KIND_JSON: &str = "json";
+pub const KIND_SLACK: &str = "slack";
+pub const KIND_DISCORD: &str = "discord";
+pub const KINDS: &[&str] = &[KIND_JSON, KIND_SLACK, KIND_DISCORD];
+/// The board's Telegram chat, managed through `/boards/<id>/telegram`
+/// rather than the webhooks API.
+pub const KIND_TELEGRAM: &str = "telegram";
+
+/// Event name of the synthetic payload sent by the webhook test endpoint.
+/// It isn't a board event, so it can't be subscribed to.
+pub const PING_EVENT: &str = "webhook.ping";
+
+/// Key id of a webhook's own `secret` in signature headers.
+pub const DEFAULT_KEY_ID: &str = "default";
+
+/// A secret deliveries are signed with.
+#[derive(Debug, Clone)]
+pub struct SigningKey {
+    pub id: String,
+    pub secret: String,
+}
+
+impl SigningKey {
+    pub fn default_key(secret: &str) -> Self {
+        SigningKey {
+            id: DEFAULT_KEY_ID.to_string(),
+            secret: secret.to_string(),
+        }
+    }
+}
+
+/// Most custom headers a webhook can have.
+pub const MAX_HEADERS: usize = 20;
+/// Headers the server sets on every delivery, besides `X-Kanban-*`.
+const RESERVED_HEADERS: &[&str] = &["content-type", "content-length", "host", "connection", "transfer-encoding"];
+
+/// A webhook's custom headers, decrypted for sending; Err when they can't be
+/// (e.g. `SECRETS_KEY` changed), which fails the delivery rather than
+/// sending it without them.
+pub type CustomHeaders = Result<Vec<(String, String)>, String>;
+
+/// Check custom headers before they're saved: valid names and values, and
+/// none that the server sets itself.
+pub fn validate_headers(headers: &BTreeMap<String, String>) -> Result<(), String> {
+    if headers.len() > MAX_HEADERS {
+        return Err(format!("A webhook can have at most {} custom headers", MAX_HEADERS));
+    }
+    for (name, value) in headers {
+        let lower = name.to_ascii_lowercase();
+        if reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_err() {
+            return Err(format!("\"{}\" is not a valid header name", name));
+        }
+        if RESERVED_HEADERS.contains(&lower.as_str()) || lower.starts_with("x-kanban-") {
+            return Err(format!("{} is set by the server and can't be overridden", name));
+        }
+        if reqwest::header::HeaderValue::from_str(value).is_err() {
+            return Err(format!("The value of {} contains characters not allowed in a header", name));
+        }
+    }
+    Ok(())
+}
+
+/// Custom headers as stored in `webhooks.headers`: a JSON object of names to
+/// encrypted values, or None when there are none.
+pub fn seal_headers(conn: &rusqlite::Connection, headers: &BTreeMap<String, String>) -> rusqlite::Result<Option<String>> {
+    if headers.is_empty() {
+        return Ok(None);
+    }
+    let sealed = headers
+        .iter()
+        .map(|(name, value)| Ok((name.clone(), serde_json::json!(secrets::encrypt(conn, value)?))))
+        .collect::<rusqlite::Result<serde_json::Map<_, _>>>()?;
+    Ok(Some(serde_json::Value::Object(sealed).to_string()))
+}
+
+/// The names of stored custom headers; their values are never shown.
+pub fn header_names(stored: Option<&str>) -> Vec<String> {
+    stored
+        .and_then(|s| serde_json::from_str::<BTreeMap<String, String>>(s).ok())
+        .map(|headers| headers.into_keys().collect())
+        .unwrap_or_default()
+}
+
+/// A webhook's custom headers, decrypted.
+pub fn custom_headers(conn: &rusqlite::Connection, webhook_id: &str) -> CustomHeaders {
+    let stored: Option<String> = conn
+        .query_row("SELECT headers FROM webhooks WHERE id = ?1", rusqlite::params![webhook_id], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    let Some(stored) = stored else {
+        return Ok(Vec::new());
+    };
+    serde_json::from_str::<BTreeMap<String, String>>(&stored)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(name, sealed)| {
+            secrets::decrypt(conn, &sealed)
+                .map(|value| (name.clone(), value))
+                .map_err(|e| format!("Custom header {} {}", name, e))
+        })
+        .collect()
+}
+
 /// Webhook metadata loaded from the database.
 #[derive(Debug, Clone)]
 struct WebhookTarget {
     id: String,
-    url: String,
-    secret: String,
+    kind: String,
+    /// Telegram chat the messages go to
+    chat_id: Option<String>,
+    /// Payload template (JSON), if set
+    template: Option<String>,
+    /// What a chat or templated target is sent instead of the event JSON
+    body: Option<Vec<u8>>,
     events: Vec<String>,
+    /// Columns this webhook is routed to; empty = not routed
+    columns: Vec<String>,
+    filters: WebhookFilters,
+}
+
+impl WebhookTarget {
+    /// Whether this webhook should receive `event`: it must pass the event
+    /// filter, and a routed webhook only hears about tasks entering its columns.
+    fn wants(&self, event: &BoardEvent) -> bool {
+        if !self.events.is_empty() && !self.events.contains(&event.event) {
+            return false;
+        }
+        if self.kind != KIND_JSON && !slack::formats(event) {
+            return false;
+        }
+        self.columns.is_empty() || entered_column(event).is_some_and(|c| self.columns.iter().any(|r| r == c))
+    }
+}
+
+/// What webhook filters look at in a task an event is about.
+#[derive(Debug, Default)]
+struct TaskFacts {
+    column_id: Option<String>,
+    priority: i32,
+    labels: Vec<String>,
+}
+
+/// The tasks an event is about (`task_id` or `task_ids`) as they are now, or
+/// as the event describes them for tasks that no longer exist.
+fn task_facts(conn: &rusqlite::Connection, event: &BoardEvent) -> Vec<TaskFacts> {
+    let single = event.data["task_id"].as_str();
+    let batch = event.data["task_ids"].as_array().into_iter().flatten().filter_map(|id| id.as_str());
+    single
+        .into_iter()
+        .chain(batch)
+        .map(|task_id| {
+            conn.query_row(
+                "SELECT column_id, priority, labels FROM tasks WHERE id = ?1",
+                rusqlite::params![task_id],
+                |row| {
+                    Ok(TaskFacts {
+                        column_id: row.get(0)?,
+                        priority: row.get(1)?,
+                        labels: serde_json::from_str(&row.get::<_, String>(2)?).unwrap_or_default(),
+                    })
+                },
+            )
+            .unwrap_or_else(|_| TaskFacts {
+                column_id: event.data["column_id"].as_str().map(str::to_string),
+                priority: event.data["priority"].as_i64().unwrap_or_default() as i32,
+                labels: serde_json::from_value(event.data["labels"].clone()).unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// Whether any of an event's tasks passes every filter that is set.
+fn filters_match(filters: &WebhookFilters, tasks: &[TaskFacts]) -> bool {
+    tasks.iter().any(|task| {
+        (filters.labels.is_empty() || task.labels.iter().any(|l| filters.labels.contains(l)))
+            && filters.column_id.as_ref().is_none_or(|c| task.column_id.as_ref() == Some(c))
+            && filters.min_priority.is_none_or(|min| task.priority >= min)
+    })
+}
+
+/// The body a chat target is sent instead of the event JSON, rendered for
+/// its service; None for `json` targets.
+fn chat_message(conn: &rusqlite::Connection, target: &WebhookTarget, event: &BoardEvent) -> Option<Vec<u8>> {
+    let message = match target.kind.as_str() {
+        KIND_SLACK => slack::message(conn, event),
+        KIND_DISCORD => discord::message(conn, event),
+        KIND_TELEGRAM => telegram::message(conn, event, target.chat_id.as_deref().unwrap_or_default()),
+        _ => return None,
+    };
+    serde_json::to_vec(&message).ok()
+}
+
+/// The body of a test delivery to a webhook: for `json` targets an envelope
+/// shaped like a real event's (with `seq` 0), for chat targets a plain
+/// message in the service's format.
+pub fn ping_payload(kind: &str, chat_id: Option<&str>, board_id: &str, webhook_id: &str) -> serde_json::Value {
+    let text = "Kanban webhook test: this endpoint will receive the board's events.";
+    match kind {
+        KIND_SLACK => serde_json::json!({ "text": text }),
+        KIND_DISCORD => serde_json::json!({ "content": text }),
+        KIND_TELEGRAM => serde_json::json!({ "chat_id": chat_id.unwrap_or_default(), "text": text }),
+        _ => {
+            let now = chrono::Utc::now().to_rfc3339();
+            serde_json::json!({
+                "version": crate::events::ENVELOPE_VERSION,
+                "event": PING_EVENT,
+                "board_id": board_id,
+                "data": { "webhook_id": webhook_id },
+                "seq": 0,
+                "occurred_at": now,
+                "timestamp": now,
+            })
+        }
+    }
+}
+
+/// The column an event brings a task into, if any.
+fn entered_column(event: &BoardEvent) -> Option<&str> {
+    let data = &event.data;
+    match event.event.as_str() {
+        event_types::TASK_CREATED | event_types::TASK_ASSIGNED => data["column_id"].as_str(),
+        event_types::TASK_MOVED | event_types::TASK_BATCH_MOVED => data["to"].as_str(),
+        event_types::TASK_REORDERED if data["column_id"] != data["from_column"] => data["column_id"].as_str(),
+        _ => None,
+    }
+}
+
+/// A webhook URL cut back to its origin, e.g. `https://hooks.slack.com/…`.
+/// Slack, Discord and Telegram put the credential in the path, and plain
+/// receivers often carry a token in the path or query, so copies of a board
+/// that leave the server keep only where the hook pointed.
+pub fn redact_url(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if parsed.path() == "/" && parsed.query().is_none() => parsed.origin().ascii_serialization() + "/",
+        Ok(parsed) => parsed.origin().ascii_serialization() + "/…",
+        Err(_) => "…".to_string(),
+    }
 }
 
 /// Compute HMAC-SHA256 signature for a payload.
-fn sign_payload(secret: &str, payload: &[u8]) -> String {
+pub(crate) fn sign_payload(secret: &str, payload: &[u8]) -> String {
     let mut mac =
         HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
     mac.update(payload);
@@ -24,91 +263,570 @@ fn sign_payload(secret: &str, payload: &[u8]) -> String {
     hex::encode(result.into_bytes())
 }
 
-/// Deliver a board event to all registered webhooks for that board.
-/// Runs asynchronously — failures are logged and counted, not propagated.
-pub fn deliver_webhooks(db: WebhookDb, event: BoardEvent, client: reqwest::Client) {
-    tokio::spawn(async move {
-        let targets = {
-            let conn = db.lock().unwrap();
-            let mut stmt = conn
-                .prepare(
-                    "SELECT id, url, secret, events FROM webhooks
-                     WHERE board_id = ?1 AND active = 1 AND failure_count < 10",
-                )
-                .ok();
-
-            match stmt {
-                Some(ref mut s) => s
-                    .query_map(rusqlite::params![event.board_id], |row| {
-                        let events_str: String = row.get(3)?;
-                        let events: Vec<String> =
-                            serde_json::from_str(&events_str).unwrap_or_default();
-                        Ok(WebhookTarget {
-                            id: row.get(0)?,
-                            url: row.get(1)?,
-                            secret: row.get(2)?,
-                            events,
-                        })
-                    })
-                    .ok()
-                    .map(|rows| rows.filter_map(|r| r.ok()).collect::<Vec<_>>())
+/// `X-Kanban-Signature` for `payload`. With only the default key this is the
+/// plain `sha256=<hex>`; during a rotation it lists every active key as
+/// `kid=<id>;sha256=<hex>`, comma-separated, so a consumer holding any one
+/// of them can verify.
+pub(crate) fn signature_header(keys: &[SigningKey], payload: &[u8]) -> String {
+    match keys {
+        [key] if key.id == DEFAULT_KEY_ID => format!("sha256={}", sign_payload(&key.secret, payload)),
+        _ => keys
+            .iter()
+            .map(|key| format!("kid={};sha256={}", key.id, sign_payload(&key.secret, payload)))
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+/// How hard to try before an event is dead-lettered.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Total attempts per event, including the first
+    pub max_attempts: u32,
+    /// Delay before the second attempt; doubles after each further failure
+    pub base_delay: std::time::Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: std::time::Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Read `WEBHOOK_MAX_ATTEMPTS` and `WEBHOOK_RETRY_BASE_MS`.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let max_attempts = std::env::var("WEBHOOK_MAX_ATTEMPTS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|n| *n >= 1)
+            .unwrap_or(default.max_attempts);
+        let base_delay = std::env::var("WEBHOOK_RETRY_BASE_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(std::time::Duration::from_millis)
+            .unwrap_or(default.base_delay);
+        RetryPolicy { max_attempts, base_delay }
+    }
+}
+
+/// How much of a target's response body the delivery log keeps.
+const MAX_LOGGED_BODY: usize = 1024;
+/// Delivery log entries kept per webhook; older ones are pruned as new ones arrive.
+const MAX_LOGGED_DELIVERIES: i64 = 500;
+
+/// What came of POSTing one payload, as kept in `webhook_deliveries`.
+#[derive(Debug, Clone)]
+pub struct Attempt {
+    /// None when no response arrived (connection error, timeout)
+    pub status_code: Option<u16>,
+    pub duration_ms: i64,
+    /// The start of the response body
+    pub response_body: String,
+    /// Why the attempt failed: the connection error, or `HTTP <status>`
+    pub error: Option<String>,
+}
+
+impl Attempt {
+    pub fn result(&self) -> Result<(), String> {
+        match &self.error {
+            Some(error) => Err(error.clone()),
+            None => Ok(()),
+        }
+    }
+
+    /// An attempt that never left the server.
+    fn refused(error: String) -> Self {
+        Attempt {
+            status_code: None,
+            duration_ms: 0,
+            response_body: String::new(),
+            error: Some(error),
+        }
+    }
+}
+
+/// POST one signed payload and report how it went. Any non-2xx status
+/// counts as a failure.
+pub async fn post_signed(
+    client: &OutboundClient,
+    url: &str,
+    keys: &[SigningKey],
+    headers: &CustomHeaders,
+    event: &str,
+    board_id: &str,
+    payload: &[u8],
+) -> Attempt {
+    let headers = match headers {
+        Ok(headers) => headers,
+        Err(e) => return Attempt::refused(e.clone()),
+    };
+    post(client, url, keys, headers, event, board_id, payload).await
+}
+
+async fn post(
+    client: &OutboundClient,
+    url: &str,
+    keys: &[SigningKey],
+    headers: &[(String, String)],
+    event: &str,
+    board_id: &str,
+    payload: &[u8],
+) -> Attempt {
+    // Webhooks saved before the URL policy, or before it was tightened, are held to it too
+    let request = match client.post(url) {
+        Ok(request) => request,
+        Err(e) => return Attempt::refused(e),
+    };
+    let started = std::time::Instant::now();
+    let sent = headers
+        .iter()
+        .fold(request, |request, (name, value)| request.header(name, value))
+        .header("Content-Type", "application/json")
+        .header("X-Kanban-Signature", signature_header(keys, payload))
+        .header("X-Kanban-Event", event)
+        .header("X-Kanban-Board", board_id)
+        .body(payload.to_vec())
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await;
+    let (status_code, response_body, error) = match sent {
+        Ok(resp) => {
+            let status = resp.status();
+            let body = resp.bytes().await.map(|b| truncate_body(&b)).unwrap_or_default();
+            let error = (!status.is_success()).then(|| format!("HTTP {}", status.as_u16()));
+            (Some(status.as_u16()), body, error)
+        }
+        Err(e) => (None, String::new(), Some(e.to_string())),
+    };
+    Attempt {
+        status_code,
+        duration_ms: started.elapsed().as_millis() as i64,
+        response_body,
+        error,
+    }
+}
+
+/// POST one signed payload. Any non-2xx status counts as a failure.
+pub async fn send_signed(
+    client: &OutboundClient,
+    url: &str,
+    keys: &[SigningKey],
+    event: &str,
+    board_id: &str,
+    payload: &[u8],
+) -> Result<(), String> {
+    post(client, url, keys, &[], event, board_id, payload).await.result()
+}
+
+/// The first `MAX_LOGGED_BODY` bytes of a response, cut at a character boundary.
+fn truncate_body(body: &[u8]) -> String {
+    let text = String::from_utf8_lossy(body);
+    let mut end = text.len().min(MAX_LOGGED_BODY);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text[..end].to_string()
+}
+
+/// Add an attempt to the webhook's delivery log, dropping its oldest entries
+/// past `MAX_LOGGED_DELIVERIES`. Returns the entry's id.
+pub fn log_attempt(
+    conn: &rusqlite::Connection,
+    webhook_id: &str,
+    board_id: &str,
+    event: &str,
+    payload: &[u8],
+    attempt_number: u32,
+    attempt: &Attempt,
+) -> String {
+    let id = uuid::Uuid::new_v4().to_string();
+    let _ = conn.execute(
+        "INSERT INTO webhook_deliveries
+            (id, webhook_id, board_id, event, payload, attempt, status_code, duration_ms, response_body, error)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        rusqlite::params![
+            id,
+            webhook_id,
+            board_id,
+            event,
+            String::from_utf8_lossy(payload),
+            attempt_number,
+            attempt.status_code,
+            attempt.duration_ms,
+            attempt.response_body,
+            attempt.error,
+        ],
+    );
+    let _ = conn.execute(
+        "DELETE FROM webhook_deliveries WHERE webhook_id = ?1 AND rowid NOT IN
+            (SELECT rowid FROM webhook_deliveries WHERE webhook_id = ?1 ORDER BY rowid DESC LIMIT ?2)",
+        rusqlite::params![webhook_id, MAX_LOGGED_DELIVERIES],
+    );
+    id
+}
+
+/// Consecutive undeliverable events after which a webhook stops being sent
+/// new ones; they go straight to its dead letters until it recovers.
+pub const MAX_CONSECUTIVE_FAILURES: i64 = 10;
+
+/// Queue a board event for every webhook on its board that wants it, one
+/// `webhook_outbox` row per target, to be sent by the dispatcher. JSON
+/// targets get the envelope, plus `timestamp` (when it was queued) as
+/// payloads had before the envelope. Webhooks at the failure cap get a dead
+/// letter instead, so the event can be requeued later rather than lost.
+/// Returns how many outbox rows were queued.
+pub fn enqueue(conn: &rusqlite::Connection, event: &Envelope) -> rusqlite::Result<usize> {
+    let mut targets = conn
+        .prepare(
+            "SELECT id, events,
+                    (SELECT json_group_array(column_id) FROM webhook_routes r WHERE r.webhook_id = webhooks.id),
+                    kind, chat_id, template, filters, failure_count
+             FROM webhooks
+             WHERE board_id = ?1 AND active = 1",
+        )?
+        .query_map(rusqlite::params![event.board_id], |row| {
+            let events_str: String = row.get(1)?;
+            let columns_str: String = row.get(2)?;
+            let target = WebhookTarget {
+                id: row.get(0)?,
+                kind: row.get(3)?,
+                chat_id: row.get(4)?,
+                template: row.get(5)?,
+                body: None,
+                events: serde_json::from_str(&events_str).unwrap_or_default(),
+                columns: serde_json::from_str(&columns_str).unwrap_or_default(),
+                filters: row
+                    .get::<_, Option<String>>(6)?
+                    .and_then(|f| serde_json::from_str(&f).ok())
                     .unwrap_or_default(),
-                None => Vec::new(),
+            };
+            Ok((target, row.get::<_, i64>(7)? >= MAX_CONSECUTIVE_FAILURES))
+        })?
+        .filter_map(|r| r.ok())
+        .filter(|(target, _)| target.wants(event))
+        .collect::<Vec<_>>();
+    let mut tasks = None;
+    targets.retain(|(target, _)| {
+        target.filters.is_empty() || filters_match(&target.filters, tasks.get_or_insert_with(|| task_facts(conn, event)))
+    });
+    if targets.is_empty() {
+        return Ok(0);
+    }
+
+    let mut payload = serde_json::json!(event);
+    payload["timestamp"] = serde_json::json!(chrono::Utc::now().to_rfc3339());
+    let mut context = None;
+    let body = serde_json::to_vec(&payload).unwrap_or_default();
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut queued = 0;
+    for (mut target, capped) in targets {
+        target.body = match target.template.as_deref().and_then(|t| serde_json::from_str(t).ok()) {
+            Some(template) => {
+                let context = context.get_or_insert_with(|| webhook_template::context(conn, event, &payload));
+                serde_json::to_vec(&webhook_template::render(&template, context)).ok()
             }
+            None => chat_message(conn, &target, event),
         };
-
-        if targets.is_empty() {
-            return;
+        let payload = String::from_utf8_lossy(target.body.as_deref().unwrap_or(&body));
+        if capped {
+            conn.execute(
+                "INSERT INTO webhook_dead_letters (id, webhook_id, board_id, event, payload, attempts, last_error)
+                 VALUES (?1, ?2, ?3, ?4, ?5, 0, ?6)",
+                rusqlite::params![
+                    uuid::Uuid::new_v4().to_string(),
+                    target.id,
+                    event.board_id,
+                    event.event,
+                    payload,
+                    format!("Not sent: webhook paused after {} consecutive failures", MAX_CONSECUTIVE_FAILURES),
+                ],
+            )?;
+            continue;
         }
+        conn.execute(
+            "INSERT INTO webhook_outbox (id, webhook_id, board_id, event, payload, next_attempt_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![uuid::Uuid::new_v4().to_string(), target.id, event.board_id, event.event, payload, now],
+        )?;
+        queued += 1;
+    }
+    Ok(queued)
+}
 
-        let payload = serde_json::json!({
-            "event": event.event,
-            "board_id": event.board_id,
-            "data": event.data,
-            "timestamp": chrono::Utc::now().to_rfc3339(),
-        });
-        let payload_bytes = serde_json::to_vec(&payload).unwrap_or_default();
+/// Outbox rows sent per dispatcher pass.
+const OUTBOX_BATCH: i64 = 50;
+/// Longest the dispatcher sleeps with nothing due; new rows wake it sooner.
+const OUTBOX_IDLE: std::time::Duration = std::time::Duration::from_secs(30);
 
-        for target in targets {
-            // Filter: if webhook has specific events configured, check if this event matches
-            if !target.events.is_empty() && !target.events.contains(&event.event) {
-                continue;
-            }
+/// An outbox row ready to send, with its webhook's current URL.
+struct Queued {
+    id: String,
+    webhook_id: String,
+    board_id: String,
+    event: String,
+    payload: String,
+    /// Attempts made before this one
+    attempts: u32,
+    url: String,
+}
 
-            let signature = sign_payload(&target.secret, &payload_bytes);
-
-            let result = client
-                .post(&target.url)
-                .header("Content-Type", "application/json")
-                .header("X-Kanban-Signature", format!("sha256={}", signature))
-                .header("X-Kanban-Event", &event.event)
-                .header("X-Kanban-Board", &event.board_id)
-                .body(payload_bytes.clone())
-                .timeout(std::time::Duration::from_secs(10))
-                .send()
-                .await;
-
-            let success = match result {
-                Ok(resp) => resp.status().is_success(),
-                Err(_) => false,
-            };
+fn due(conn: &rusqlite::Connection, now: i64) -> rusqlite::Result<Vec<Queued>> {
+    conn.prepare(
+        "SELECT o.id, o.webhook_id, o.board_id, o.event, o.payload, o.attempts, w.url
+         FROM webhook_outbox o JOIN webhooks w ON w.id = o.webhook_id
+         WHERE o.next_attempt_at <= ?1
+         ORDER BY o.next_attempt_at ASC, o.rowid ASC LIMIT ?2",
+    )?
+    .query_map(rusqlite::params![now, OUTBOX_BATCH], |row| {
+        Ok(Queued {
+            id: row.get(0)?,
+            webhook_id: row.get(1)?,
+            board_id: row.get(2)?,
+            event: row.get(3)?,
+            payload: row.get(4)?,
+            attempts: row.get(5)?,
+            url: row.get(6)?,
+        })
+    })?
+    .collect()
+}
+
+/// Record an attempt on a queued row: delivered rows leave the outbox, failed
+/// ones wait out their backoff, and rows out of attempts become dead letters.
+fn settle(conn: &rusqlite::Connection, queued: &Queued, attempt: &Attempt, policy: &RetryPolicy) -> rusqlite::Result<()> {
+    let attempts = queued.attempts + 1;
+    log_attempt(conn, &queued.webhook_id, &queued.board_id, &queued.event, queued.payload.as_bytes(), attempts, attempt);
+    match &attempt.error {
+        None => {
+            conn.execute(
+                "UPDATE webhooks SET failure_count = 0, last_triggered_at = datetime('now') WHERE id = ?1",
+                rusqlite::params![queued.webhook_id],
+            )?;
+        }
+        Some(error) if attempts < policy.max_attempts => {
+            let delay = policy.base_delay * 2u32.saturating_pow(attempts - 1);
+            conn.execute(
+                "UPDATE webhook_outbox SET attempts = ?2, last_error = ?3, next_attempt_at = ?4 WHERE id = ?1",
+                rusqlite::params![
+                    queued.id,
+                    attempts,
+                    error,
+                    chrono::Utc::now().timestamp_millis() + delay.as_millis() as i64
+                ],
+            )?;
+            return Ok(());
+        }
+        Some(error) => {
+            conn.execute(
+                "UPDATE webhooks SET failure_count = failure_count + 1, last_triggered_at = datetime('now') WHERE id = ?1",
+                rusqlite::params![queued.webhook_id],
+            )?;
+            conn.execute(
+                "INSERT INTO webhook_dead_letters (id, webhook_id, board_id, event, payload, attempts, last_error)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                rusqlite::params![
+                    uuid::Uuid::new_v4().to_string(),
+                    queued.webhook_id,
+                    queued.board_id,
+                    queued.event,
+                    queued.payload,
+                    attempts,
+                    error,
+                ],
+            )?;
+        }
+    }
+    conn.execute("DELETE FROM webhook_outbox WHERE id = ?1", rusqlite::params![queued.id])?;
+    Ok(())
+}
 
-            // Update webhook stats in the database
-            let db_ref = db.clone();
-            let webhook_id = target.id.clone();
-            if success {
-                let conn = db_ref.lock().unwrap();
-                let _ = conn.execute(
-                    "UPDATE webhooks SET failure_count = 0, last_triggered_at = datetime('now') WHERE id = ?1",
-                    rusqlite::params![webhook_id],
-                );
-            } else {
-                let conn = db_ref.lock().unwrap();
-                let _ = conn.execute(
-                    "UPDATE webhooks SET failure_count = failure_count + 1, last_triggered_at = datetime('now') WHERE id = ?1",
-                    rusqlite::params![webhook_id],
-                );
+/// Drain `webhook_outbox` forever: send what's due (each batch concurrently,
+/// signed with the webhook's keys at send time), then sleep until the next
+/// retry is due or `wake` fires. Rows are only removed once settled, so a
+/// restart resumes where the last process stopped — delivery is at least once.
+pub async fn run_dispatcher(db: WebhookDb, client: OutboundClient, policy: RetryPolicy, wake: Arc<Notify>) {
+    loop {
+        let now = chrono::Utc::now().timestamp_millis();
+        let outbox = db.clone();
+        let read = crate::db::blocking(move || {
+            let conn = outbox.lock().unwrap();
+            let rows = due(&conn, now)?;
+            let mut batch = Vec::with_capacity(rows.len());
+            let mut stuck = false;
+            for row in rows {
+                match webhook_keys::active_keys(&conn, &row.webhook_id) {
+                    Ok(keys) => batch.push((keys, custom_headers(&conn, &row.webhook_id), row)),
+                    // Without its keys the row can't be signed; count it as a
+                    // failed attempt so it backs off instead of staying due
+                    Err(e) => {
+                        let attempt = Attempt::refused(format!("Signing keys unavailable: {}", e));
+                        if let Err(e) = settle(&conn, &row, &attempt, &policy) {
+                            eprintln!("⚠️  Webhook outbox update failed: {}", e);
+                            stuck = true;
+                        }
+                    }
+                }
             }
+            Ok::<_, rusqlite::Error>((batch, stuck))
+        })
+        .await;
+        let (batch, stuck) = match read {
+            Ok(read) => read,
+            Err(e) => {
+                eprintln!("⚠️  Webhook outbox read failed: {}", e);
+                (Vec::new(), true)
+            }
+        };
+
+        if !batch.is_empty() {
+            let sends = batch.iter().map(|(keys, headers, row)| {
+                post_signed(&client, &row.url, keys, headers, &row.event, &row.board_id, row.payload.as_bytes())
+            });
+            let attempts = rocket::futures::future::join_all(sends).await;
+            let outbox = db.clone();
+            crate::db::blocking(move || {
+                let conn = outbox.lock().unwrap();
+                for ((_, _, row), attempt) in batch.iter().zip(&attempts) {
+                    if let Err(e) = settle(&conn, row, attempt, &policy) {
+                        eprintln!("⚠️  Webhook outbox update failed: {}", e);
+                    }
+                }
+            })
+            .await;
+            continue;
         }
-    });
+
+        let outbox = db.clone();
+        let next: Option<i64> = crate::db::blocking(move || {
+            let conn = outbox.lock().unwrap();
+            conn.query_row("SELECT MIN(next_attempt_at) FROM webhook_outbox", [], |row| row.get(0))
+                .ok()
+                .flatten()
+        })
+        .await;
+        // A row that can't be read or settled stays due; wait rather than spin on it
+        let idle = next
+            .filter(|_| !stuck)
+            .map(|at| std::time::Duration::from_millis((at - chrono::Utc::now().timestamp_millis()).max(0) as u64))
+            .map_or(OUTBOX_IDLE, |wait| wait.min(OUTBOX_IDLE));
+        let _ = tokio::time::timeout(idle, wake.notified()).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(events: &[&str], columns: &[&str]) -> WebhookTarget {
+        WebhookTarget {
+            id: "wh".to_string(),
+            kind: KIND_JSON.to_string(),
+            chat_id: None,
+            template: None,
+            body: None,
+            events: events.iter().map(|e| e.to_string()).collect(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            filters: WebhookFilters::default(),
+        }
+    }
+
+    fn event(name: &str, data: serde_json::Value) -> BoardEvent {
+        BoardEvent {
+            event: name.to_string(),
+            board_id: "b".to_string(),
+            data,
+        }
+    }
+
+    #[test]
+    fn test_column_routing() {
+        let into_incident = event(event_types::TASK_MOVED, serde_json::json!({"from": "todo", "to": "incident"}));
+        let into_review = event(event_types::TASK_MOVED, serde_json::json!({"from": "todo", "to": "review"}));
+        let comment = event(event_types::TASK_COMMENT, serde_json::json!({"task_id": "t"}));
+        let same_column = event(
+            event_types::TASK_REORDERED,
+            serde_json::json!({"column_id": "incident", "from_column": "incident"}),
+        );
+
+        let unrouted = target(&[], &[]);
+        assert!(unrouted.wants(&into_review) && unrouted.wants(&comment));
+
+        let pager = target(&[], &["incident"]);
+        assert!(pager.wants(&into_incident));
+        assert!(!pager.wants(&into_review));
+        assert!(!pager.wants(&comment));
+        assert!(!pager.wants(&same_column));
+
+        // The event filter still applies to routed webhooks
+        let created_only = target(&[event_types::TASK_CREATED], &["incident"]);
+        assert!(!created_only.wants(&into_incident));
+        assert!(created_only.wants(&event(event_types::TASK_CREATED, serde_json::json!({"column_id": "incident"}))));
+    }
+
+    #[test]
+    fn test_signature_header_lists_keys_during_rotation() {
+        let payload = b"{}";
+        let default = SigningKey::default_key("old");
+        assert_eq!(signature_header(std::slice::from_ref(&default), payload), format!("sha256={}", sign_payload("old", payload)));
+
+        let new = SigningKey {
+            id: "whk_1".to_string(),
+            secret: "new".to_string(),
+        };
+        assert_eq!(
+            signature_header(&[new.clone(), default], payload),
+            format!("kid=whk_1;sha256={}, kid=default;sha256={}", sign_payload("new", payload), sign_payload("old", payload))
+        );
+        assert_eq!(signature_header(&[new], payload), format!("kid=whk_1;sha256={}", sign_payload("new", payload)));
+    }
+
+    #[test]
+    fn test_redact_url_keeps_only_the_origin() {
+        assert_eq!(redact_url("https://hooks.slack.com/services/T0/B0/secret"), "https://hooks.slack.com/…");
+        assert_eq!(redact_url("https://discord.com/api/webhooks/1/token?wait=true"), "https://discord.com/…");
+        assert_eq!(redact_url("https://user:pw@example.com:8443/?token=x"), "https://example.com:8443/…");
+        assert_eq!(redact_url("https://example.com"), "https://example.com/");
+        assert_eq!(redact_url("not a url"), "…");
+    }
+
+    #[test]
+    fn test_truncate_body_at_char_boundary() {
+        assert_eq!(truncate_body(b"ok"), "ok");
+        let body = "é".repeat(MAX_LOGGED_BODY);
+        let truncated = truncate_body(body.as_bytes());
+        assert_eq!(truncated.len(), MAX_LOGGED_BODY);
+        assert!(truncated.chars().all(|c| c == 'é'));
+    }
+
+    #[test]
+    fn test_task_filters() {
+        let task = |column: &str, priority: i32, labels: &[&str]| TaskFacts {
+            column_id: Some(column.to_string()),
+            priority,
+            labels: labels.iter().map(|l| l.to_string()).collect(),
+        };
+        let deploys = WebhookFilters {
+            labels: vec!["deploy".to_string()],
+            min_priority: Some(2),
+            ..Default::default()
+        };
+        assert!(filters_match(&deploys, &[task("todo", 3, &["deploy", "infra"])]));
+        assert!(!filters_match(&deploys, &[task("todo", 1, &["deploy"])]));
+        assert!(!filters_match(&deploys, &[task("todo", 3, &["docs"])]));
+        // A batch passes if any of its tasks does; an event about no task never does
+        assert!(filters_match(&deploys, &[task("todo", 0, &[]), task("done", 2, &["deploy"])]));
+        assert!(!filters_match(&deploys, &[]));
+
+        let done = WebhookFilters {
+            column_id: Some("done".to_string()),
+            ..Default::default()
+        };
+        assert!(filters_match(&done, &[task("done", 0, &[])]));
+        assert!(!filters_match(&done, &[task("todo", 0, &[])]));
+    }
 }
@@ -0,0 +1,233 @@
+//! WebSocket alternative to the SSE stream, for clients and proxies that
+//! handle WebSockets better than EventSource. The server sends the same
+//! events and heartbeats as JSON text frames; the client can send
+//! `{"type": "subscribe", "events": [...], "assigned": "..."}` to change its
+//! filter and
+//! `{"type": "ping"}` to check the connection.
+
+use std::io;
+use std::pin::Pin;
+
+use rocket::data::{IoHandler, IoStream};
+use rocket::futures::{SinkExt, StreamExt};
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::response::{self, Responder, Response};
+use rocket::serde::json::Json;
+use rocket::{Shutdown, State};
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+use crate::access;
+use crate::auth::BoardToken;
+use crate::db::DbPool;
+use crate::event_types;
+use crate::events::{EventBus, EventFilter, Subscription};
+use crate::models::*;
+use crate::routes::{board_heartbeat, db_error, event_filter, token_columns, with_db, HEARTBEAT_INTERVAL};
+
+/// The `Sec-WebSocket-Accept` value for a valid upgrade request, or None
+/// when the request isn't one.
+pub struct WebSocketUpgrade(Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for WebSocketUpgrade {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let headers = request.headers();
+        let has_token = |name: &str, token: &str| {
+            headers
+                .get(name)
+                .flat_map(|v| v.split(','))
+                .any(|v| v.trim().eq_ignore_ascii_case(token))
+        };
+        let accept = (has_token("Upgrade", "websocket")
+            && has_token("Connection", "upgrade")
+            && headers.get_one("Sec-WebSocket-Version") == Some("13"))
+        .then(|| headers.get_one("Sec-WebSocket-Key"))
+        .flatten()
+        .map(|key| derive_accept_key(key.trim().as_bytes()));
+        Outcome::Success(WebSocketUpgrade(accept))
+    }
+}
+
+/// A message from the client.
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum ClientMessage {
+    /// Replace the filter; [] = all events, no `assigned` = any assignee
+    Subscribe {
+        #[serde(default)]
+        events: Vec<String>,
+        #[serde(default)]
+        assigned: Option<String>,
+    },
+    Ping,
+}
+
+/// An open connection to one board's events.
+struct Session {
+    board_id: String,
+    subscription: Subscription,
+    pool: DbPool,
+    shutdown: Shutdown,
+}
+
+impl Session {
+    /// The reply to a client text frame.
+    fn handle(&mut self, text: &str) -> serde_json::Value {
+        match serde_json::from_str::<ClientMessage>(text) {
+            Ok(ClientMessage::Subscribe { events, assigned }) => match event_types::validate(&events) {
+                Ok(()) => {
+                    let assigned = assigned.map(|a| a.trim().to_string()).filter(|a| !a.is_empty());
+                    // A column-scoped token's scope stays in place
+                    let columns = std::mem::take(&mut self.subscription.filter.columns);
+                    self.subscription.filter = EventFilter { events, assigned, columns };
+                    let filter = &self.subscription.filter;
+                    serde_json::json!({"type": "subscribed", "events": filter.events, "assigned": filter.assigned})
+                }
+                Err(error) => serde_json::json!({"type": "error", "code": "INVALID_EVENT_TYPE", "error": error}),
+            },
+            Ok(ClientMessage::Ping) => serde_json::json!({"type": "pong"}),
+            Err(e) => serde_json::json!({"type": "error", "code": "INVALID_MESSAGE", "error": e.to_string()}),
+        }
+    }
+
+    async fn heartbeat(&self) -> serde_json::Value {
+        let board = self.board_id.clone();
+        match with_db(&self.pool, move |conn| board_heartbeat(conn, &board).map_err(|e| db_error(&e.to_string()))).await {
+            Ok(beat) => serde_json::json!({"type": "heartbeat", "data": beat}),
+            Err(_) => serde_json::json!({"type": "heartbeat"}),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl IoHandler for Session {
+    async fn io(self: Pin<Box<Self>>, io: IoStream) -> io::Result<()> {
+        let mut session = *Pin::into_inner(self);
+        let mut ws = WebSocketStream::from_raw_socket(io, Role::Server, None).await;
+        let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+        let to_io = io::Error::other;
+
+        loop {
+            let frame = tokio::select! {
+                _ = heartbeat.tick() => session.heartbeat().await,
+                msg = session.subscription.recv() => match msg {
+                    Ok(event) => {
+                        let mut frame = serde_json::json!(event);
+                        frame["type"] = serde_json::json!("event");
+                        frame
+                    }
+                    Err(RecvError::Lagged(_)) => serde_json::json!({"type": "warning", "data": "events_lost"}),
+                    Err(RecvError::Closed) => break,
+                },
+                incoming = ws.next() => match incoming {
+                    Some(Ok(Message::Text(text))) => session.handle(&text),
+                    Some(Ok(Message::Close(_))) | None => break,
+                    // Pings are answered by the protocol layer; binary frames are ignored
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(to_io(e)),
+                },
+                _ = &mut session.shutdown => break,
+            };
+            ws.send(Message::Text(frame.to_string())).await.map_err(to_io)?;
+        }
+        let _ = ws.close(None).await;
+        Ok(())
+    }
+}
+
+/// Switches the connection over to a `Session`.
+pub struct WebSocket {
+    accept: String,
+    session: Session,
+}
+
+impl<'r> Responder<'r, 'static> for WebSocket {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+        Response::build()
+            .raw_header("Sec-WebSocket-Accept", self.accept)
+            .upgrade("websocket", self.session)
+            .ok()
+    }
+}
+
+/// Board events over a WebSocket — public, like the SSE stream. Frames are
+/// JSON: `event` (the event's envelope), `heartbeat` (on connect and every
+/// 15 seconds), `warning`, and replies to client messages.
+/// `?events=` and `?assigned=` set the initial filter, as for SSE. Plain
+/// HTTP requests get 426.
+#[allow(clippy::too_many_arguments)]
+#[get("/boards/<board_id>/events/ws?<events>&<assigned>")]
+pub async fn board_event_socket(
+    board_id: &str,
+    events: Option<&str>,
+    assigned: Option<&str>,
+    upgrade: WebSocketUpgrade,
+    token: Option<BoardToken>,
+    db: &State<DbPool>,
+    bus: &State<EventBus>,
+    shutdown: Shutdown,
+) -> Result<WebSocket, (Status, Json<ApiError>)> {
+    let board = board_id.to_owned();
+    let columns = with_db(db, move |conn| {
+        let board_id = board.as_str();
+        access::require_read_access(conn, board_id, token.as_ref())?;
+        Ok::<_, (Status, Json<ApiError>)>(token_columns(conn, board_id, token.as_ref()))
+    })
+    .await?;
+    let filter = EventFilter { columns, ..event_filter(events, assigned)? };
+    let Some(accept) = upgrade.0 else {
+        return Err((
+            Status::UpgradeRequired,
+            Json(ApiError {
+                error: "Connect with a WebSocket client (Upgrade: websocket), or use /events/stream for SSE".to_string(),
+                code: "UPGRADE_REQUIRED".to_string(),
+                status: 426,
+            }),
+        ));
+    };
+    let board = board_id.to_owned();
+    with_db(db, move |conn| {
+        crate::analytics::record_view(conn, &board, crate::analytics::VIEW_SSE_CONNECT);
+        Ok::<(), (Status, Json<ApiError>)>(())
+    })
+    .await?;
+
+    Ok(WebSocket {
+        accept,
+        session: Session {
+            board_id: board_id.to_string(),
+            subscription: bus.subscribe_filtered(board_id, "websocket", filter),
+            pool: db.inner().clone(),
+            shutdown,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_messages() {
+        assert_eq!(
+            serde_json::from_str::<ClientMessage>(r#"{"type": "subscribe", "events": ["task.moved"]}"#).unwrap(),
+            ClientMessage::Subscribe { events: vec!["task.moved".to_string()], assigned: None }
+        );
+        assert_eq!(
+            serde_json::from_str::<ClientMessage>(r#"{"type": "subscribe", "assigned": "Sam"}"#).unwrap(),
+            ClientMessage::Subscribe { events: vec![], assigned: Some("Sam".to_string()) }
+        );
+        assert_eq!(serde_json::from_str::<ClientMessage>(r#"{"type": "ping"}"#).unwrap(), ClientMessage::Ping);
+        assert!(serde_json::from_str::<ClientMessage>(r#"{"type": "shout"}"#).is_err());
+        // RFC 6455's example handshake
+        assert_eq!(derive_accept_key(b"dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+}
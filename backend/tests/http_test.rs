@@ -1,6 +1,9 @@
 // HTTP integration tests using Rocket's test client.
 // These test the full request→response cycle including auth guards, rate limiting, and error handling.
 
+// Some older tests keep bindings for setup they no longer assert on
+#![allow(unused_variables)]
+
 #[macro_use]
 extern crate rocket;
 
@@ -8,67 +11,353 @@ use rocket::http::{ContentType, Header, Status};
 use rocket::local::blocking::Client;
 
 use std::time::Duration;
+use kanban::rate_limit::RouteGroup;
 
 /// Build a Rocket test client with a fresh database.
 /// Uses `init_db_with_path` to avoid process-global env var races in parallel tests.
+const TEST_ADMIN_KEY: &str = "test-admin-key";
+
+/// Webhook retries that give up in milliseconds rather than seconds.
+fn fast_retries() -> kanban::webhooks::RetryPolicy {
+    kanban::webhooks::RetryPolicy {
+        max_attempts: 2,
+        base_delay: Duration::from_millis(10),
+    }
+}
+
 fn test_client() -> Client {
+    test_client_with_hooks(kanban::lifecycle::LifecycleHooks::default())
+}
+
+/// Like `test_client`, but with instance-level board lifecycle hooks configured.
+fn test_client_with_hooks(hooks: kanban::lifecycle::LifecycleHooks) -> Client {
+    build_test_client(
+        hooks,
+        kanban::query_guard::QueryGuard::default(),
+        kanban::features::FeatureFlags::default(),
+        LOCAL_WEBHOOKS,
+        kanban::oidc::OidcConfig::disabled(),
+        UNLIMITED_WRITES,
+        NO_TRUSTED_PROXIES,
+    )
+}
+
+/// Like `test_client`, but with custom request timeouts / slow-query threshold.
+fn test_client_with_query_guard(guard: kanban::query_guard::QueryGuard) -> Client {
+    build_test_client(
+        kanban::lifecycle::LifecycleHooks::default(),
+        guard,
+        kanban::features::FeatureFlags::default(),
+        LOCAL_WEBHOOKS,
+        kanban::oidc::OidcConfig::disabled(),
+        UNLIMITED_WRITES,
+        NO_TRUSTED_PROXIES,
+    )
+}
+
+/// Like `test_client`, but with experimental features switched on.
+fn test_client_with_features(features: kanban::features::FeatureFlags) -> Client {
+    build_test_client(
+        kanban::lifecycle::LifecycleHooks::default(),
+        kanban::query_guard::QueryGuard::default(),
+        features,
+        LOCAL_WEBHOOKS,
+        kanban::oidc::OidcConfig::disabled(),
+        UNLIMITED_WRITES,
+        NO_TRUSTED_PROXIES,
+    )
+}
+
+/// Like `test_client`, but with outbound URLs held to `policy`.
+fn test_client_with_url_policy(policy: kanban::url_policy::UrlPolicy) -> Client {
+    build_test_client(
+        kanban::lifecycle::LifecycleHooks::default(),
+        kanban::query_guard::QueryGuard::default(),
+        kanban::features::FeatureFlags::default(),
+        policy,
+        kanban::oidc::OidcConfig::disabled(),
+        UNLIMITED_WRITES,
+        NO_TRUSTED_PROXIES,
+    )
+}
+
+/// Like `test_client`, but with OIDC sign-in against `oidc`'s provider.
+fn test_client_with_oidc(oidc: kanban::oidc::OidcConfig) -> Client {
+    build_test_client(
+        kanban::lifecycle::LifecycleHooks::default(),
+        kanban::query_guard::QueryGuard::default(),
+        kanban::features::FeatureFlags::default(),
+        LOCAL_WEBHOOKS,
+        oidc,
+        UNLIMITED_WRITES,
+        NO_TRUSTED_PROXIES,
+    )
+}
+
+/// Like `test_client`, but with per-key write limits.
+fn test_client_with_write_limits(tasks: u64, comments: u64, batch: u64) -> Client {
+    build_test_client(
+        kanban::lifecycle::LifecycleHooks::default(),
+        kanban::query_guard::QueryGuard::default(),
+        kanban::features::FeatureFlags::default(),
+        LOCAL_WEBHOOKS,
+        kanban::oidc::OidcConfig::disabled(),
+        (tasks, comments, batch),
+        NO_TRUSTED_PROXIES,
+    )
+}
+
+/// Like `test_client`, but behind proxies whose forwarding headers are believed.
+fn test_client_with_trusted_proxies(proxies: &str) -> Client {
+    build_test_client(
+        kanban::lifecycle::LifecycleHooks::default(),
+        kanban::query_guard::QueryGuard::default(),
+        kanban::features::FeatureFlags::default(),
+        LOCAL_WEBHOOKS,
+        kanban::oidc::OidcConfig::disabled(),
+        UNLIMITED_WRITES,
+        proxies,
+    )
+}
+
+/// Rate limits counting in memory.
+fn in_memory_limits(config: &kanban::rate_limit::RateLimitConfig) -> kanban::rate_limit::RateLimits {
+    kanban::rate_limit::RateLimits::with_stores(config, |_| Box::new(kanban::rate_limit::MemoryStore::default()))
+}
+
+/// Per-key write limits (tasks, comments, batch) of 0: off unless a test sets them.
+const UNLIMITED_WRITES: (u64, u64, u64) = (0, 0, 0);
+
+/// No `TRUSTED_PROXIES`: the client address is the socket peer.
+const NO_TRUSTED_PROXIES: &str = "";
+
+/// Test receivers listen on 127.0.0.1, so tests allow private outbound URLs.
+const LOCAL_WEBHOOKS: kanban::url_policy::UrlPolicy = kanban::url_policy::UrlPolicy { allow_private: true };
+
+fn build_test_client(
+    hooks: kanban::lifecycle::LifecycleHooks,
+    query_guard: kanban::query_guard::QueryGuard,
+    features: kanban::features::FeatureFlags,
+    url_policy: kanban::url_policy::UrlPolicy,
+    oidc: kanban::oidc::OidcConfig,
+    (task_writes, comments, batch): (u64, u64, u64),
+    trusted_proxies: &str,
+) -> Client {
     let db_path = format!("/tmp/kanban_http_test_{}.db", uuid::Uuid::new_v4());
 
     let db = kanban::db::init_db_with_path(&db_path).expect("DB should initialize");
     let webhook_db = kanban::db::init_webhook_db_with_path(&db_path).expect("Webhook DB should initialize");
 
-    // High rate limit so tests don't trip over it (unless testing rate limiting specifically)
-    let rate_limiter = kanban::rate_limit::RateLimiter::new(Duration::from_secs(3600), 1000);
+    // High board rate limit so tests don't trip over it (unless testing rate limiting specifically)
+    let rate_limits = in_memory_limits(
+        &kanban::rate_limit::RateLimitConfig::default()
+            .with_rule(RouteGroup::BoardCreation, 1000, 3600)
+            .with_rule(RouteGroup::TaskWrites, task_writes, 60)
+            .with_rule(RouteGroup::Comments, comments, 60)
+            .with_rule(RouteGroup::Batch, batch, 60),
+    );
 
-    let rocket = rocket::build()
+    let client_usage = kanban::usage::ClientUsage::default();
+    let trusted_proxies = kanban::rate_limit::TrustedProxies::parse(trusted_proxies).expect("valid trusted proxies");
+    let rocket = rocket::custom(trusted_proxies.apply(rocket::Config::figment()))
+        .attach(kanban::compression::Compression::default())
+        .attach(client_usage.clone())
+        .attach(query_guard.clone())
+        .attach(kanban::rate_limit::RateLimitHeaders)
+        .manage(client_usage)
+        .manage(query_guard)
         .manage(db)
-        .manage(rate_limiter)
-        .manage(kanban::events::EventBus::with_webhooks(webhook_db))
+        .manage(rate_limits)
+        .manage(
+            kanban::events::EventBus::with_webhooks(webhook_db)
+                .with_retry_policy(fast_retries())
+                .with_url_policy(url_policy),
+        )
+        .manage(kanban::auth::AdminConfig::new(Some(TEST_ADMIN_KEY)))
+        .manage(oidc.with_url_policy(url_policy))
+        .manage(hooks.with_url_policy(url_policy))
+        .manage(kanban::backup::BackupConfig::new(format!("{}-backups", db_path)))
+        .manage(kanban::trash::TrashConfig::default())
+        .manage(features)
+        .manage(kanban::attachments::AttachmentConfig {
+            max_bytes: 1024,
+            ..kanban::attachments::AttachmentConfig::disk(format!("{}-attachments", db_path))
+        })
         .mount(
             "/api/v1",
             routes![
                 kanban::routes::health,
                 kanban::routes::create_board,
                 kanban::routes::list_boards,
+                kanban::tags::list_tags,
                 kanban::routes::get_board,
                 kanban::routes::update_board,
                 kanban::routes::archive_board,
                 kanban::routes::unarchive_board,
+                kanban::routes::rotate_manage_key,
+                kanban::routes::delete_board,
+                kanban::collaborators::list_collaborators,
+                kanban::collaborators::my_board_role,
+                kanban::collaborators::add_collaborator,
+                kanban::collaborators::update_collaborator,
+                kanban::collaborators::remove_collaborator,
+                kanban::share_keys::list_share_keys,
+                kanban::share_keys::create_share_key,
+                kanban::share_keys::revoke_share_key,
+                kanban::share_links::create_share_link,
+                kanban::share_links::revoke_share_links,
+                kanban::oidc::auth_config,
+                kanban::oidc::login,
+                kanban::oidc::callback,
+                kanban::oidc::me,
+                kanban::oidc::logout,
+                kanban::ip_allowlist::get_ip_allowlist,
+                kanban::ip_allowlist::set_ip_allowlist,
+                kanban::scoped_tokens::list_scoped_tokens,
+                kanban::scoped_tokens::create_scoped_token,
+                kanban::scoped_tokens::revoke_scoped_token,
+                kanban::view_password::create_view_session,
+                kanban::agent_keys::list_agent_keys,
+                kanban::agent_keys::create_agent_key,
+                kanban::agent_keys::revoke_agent_key,
+                kanban::credentials::list_active_keys,
+                kanban::merge::merge_boards,
                 kanban::routes::create_column,
                 kanban::routes::update_column,
                 kanban::routes::delete_column,
                 kanban::routes::reorder_columns,
                 kanban::routes::create_task,
+                kanban::routes::clone_task,
                 kanban::routes::search_tasks,
+                kanban::routes::sample_tasks,
+                kanban::routes::resolve_task_key,
+                kanban::routes::renumber_tasks,
                 kanban::routes::list_tasks,
                 kanban::routes::get_task,
                 kanban::routes::update_task,
                 kanban::routes::delete_task,
+                kanban::github::import_github_issues,
                 kanban::routes::archive_task,
+                kanban::trash::list_trash,
+                kanban::trash::restore_task,
                 kanban::routes::unarchive_task,
                 kanban::routes::batch_tasks,
+                kanban::import::import_tasks_csv,
                 kanban::routes::claim_task,
                 kanban::routes::release_task,
+                kanban::routes::list_claims,
                 kanban::routes::move_task,
                 kanban::routes::reorder_task,
+                kanban::routes::lock_task,
+                kanban::routes::get_task_lock,
+                kanban::routes::unlock_task,
+                kanban::routes::get_board_layout,
+                kanban::routes::apply_board_layout,
                 kanban::routes::get_board_activity,
+                kanban::board_events::list_board_events,
+                kanban::feed::board_feed,
+                kanban::feed::board_activity_atom,
+                kanban::calendar::board_calendar,
                 kanban::routes::get_task_events,
                 kanban::routes::comment_on_task,
+                kanban::comment_pressure::collapse_comments,
+                kanban::comments::edit_comment,
+                kanban::comments::delete_comment,
+                kanban::comments::comment_history,
+                kanban::watchers::watch_task,
+                kanban::watchers::unwatch_task,
+                kanban::inbox::get_inbox,
+                kanban::inbox::ack_inbox,
+                kanban::notifications::list_preferences,
+                kanban::notifications::get_preference,
+                kanban::notifications::set_preference,
+                kanban::email::list_email_subscriptions,
+                kanban::email::set_email_subscription,
+                kanban::email::delete_email_subscription,
+                kanban::telegram::get_telegram,
+                kanban::telegram::set_telegram,
+                kanban::telegram::delete_telegram,
+                kanban::inbound::list_inbound_hooks,
+                kanban::inbound::create_inbound_hook,
+                kanban::inbound::update_inbound_hook,
+                kanban::inbound::delete_inbound_hook,
+                kanban::inbound::receive_inbound,
+                kanban::comment_pressure::list_transcripts,
+                kanban::comment_pressure::get_transcript,
+                kanban::attachments::upload_attachment,
+                kanban::attachments::list_attachments,
+                kanban::attachments::download_attachment,
+                kanban::attachments::delete_attachment,
                 kanban::routes::board_event_stream,
+                kanban::routes::board_event_poll,
+                kanban::websocket::board_event_socket,
+                kanban::routes::multiplexed_event_stream,
                 kanban::routes::create_dependency,
                 kanban::routes::list_dependencies,
                 kanban::routes::delete_dependency,
+                kanban::hierarchy::list_children,
+                kanban::hierarchy::attach_child,
+                kanban::hierarchy::detach_child,
+                kanban::routes::schedule_dependencies,
                 kanban::routes::create_webhook,
                 kanban::routes::list_webhooks,
                 kanban::routes::update_webhook,
                 kanban::routes::delete_webhook,
+                kanban::routes::get_webhook_routes,
+                kanban::routes::set_webhook_routes,
+                kanban::webhook_keys::list_signing_keys,
+                kanban::webhook_keys::create_signing_key,
+                kanban::webhook_keys::update_signing_key,
+                kanban::webhook_keys::delete_signing_key,
+                kanban::routes::test_webhook,
+                kanban::routes::list_webhook_deliveries,
+                kanban::routes::redeliver_webhook_delivery,
+                kanban::routes::list_dead_letters,
+                kanban::routes::list_board_dead_letters,
+                kanban::routes::requeue_dead_letters,
+                kanban::routes::redrive_dead_letters,
+                kanban::validation_hook::get_validation_hook,
+                kanban::validation_hook::set_validation_hook,
+                kanban::validation_hook::delete_validation_hook,
                 kanban::routes::openapi,
+                kanban::routes::list_event_types,
                 kanban::routes::llms_txt,
+                kanban::routes::client_sdk,
+                kanban::admin::scrub_pii,
+                kanban::admin::apply_workspace_settings,
+                kanban::admin::db_stats,
+                kanban::admin::client_stats,
+                kanban::admin::slow_queries,
+                kanban::admin::event_stats,
+                kanban::admin::upsert_tag,
+                kanban::admin::delete_tag,
+                kanban::admin::list_all_boards,
+                kanban::admin::force_unarchive_board,
+                kanban::admin::force_delete_board,
+                kanban::admin::instance_stats,
+                kanban::admin::rate_limits,
+                kanban::backup::create_backup,
+                kanban::backup::restore_backup,
+                kanban::actors::get_actor,
+                kanban::actors::actor_avatar,
+                kanban::actors::upload_actor_avatar,
+                kanban::actors::delete_actor_avatar,
+                kanban::export::export_board,
+                kanban::import::import_board,
+                kanban::import::validate_import,
+                kanban::import::commit_import,
+                kanban::trello::import_trello_board,
+                kanban::analytics::activity_heatmap,
+                kanban::analytics::aging_report,
+                kanban::analytics::suggest_assignee,
+                kanban::analytics::board_views,
             ],
         )
         .register("/", catchers![
             kanban::catchers::unauthorized,
+            kanban::catchers::forbidden,
             kanban::catchers::not_found,
+            kanban::catchers::payload_too_large,
             kanban::catchers::unprocessable,
             kanban::catchers::too_many_requests,
             kanban::catchers::internal_error,
@@ -103,6 +392,8 @@ fn test_http_health() {
     assert_eq!(resp.status(), Status::Ok);
     let body: serde_json::Value = resp.into_json().unwrap();
     assert_eq!(body["status"], "ok");
+    assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(body["features"], serde_json::json!([]));
 }
 
 // ============ Board CRUD ============
@@ -205,6 +496,100 @@ fn test_http_list_boards_only_public() {
     assert_eq!(boards[0]["name"], "Public Board");
 }
 
+#[test]
+fn test_http_board_tags() {
+    let client = test_client();
+    let admin = Header::new("Authorization", format!("Bearer {}", TEST_ADMIN_KEY));
+    let mut boards = Vec::new();
+    for name in ["Apollo API", "Apollo Web", "Infra"] {
+        let body: serde_json::Value = client
+            .post("/api/v1/boards")
+            .header(ContentType::JSON)
+            .body(format!(r#"{{"name": "{}", "is_public": true}}"#, name))
+            .dispatch()
+            .into_json()
+            .unwrap();
+        let auth = Header::new("Authorization", format!("Bearer {}", body["manage_key"].as_str().unwrap()));
+        boards.push((body["id"].as_str().unwrap().to_string(), auth));
+    }
+    let set_tags = |i: usize, tags: &str| {
+        client
+            .patch(format!("/api/v1/boards/{}", boards[i].0))
+            .header(ContentType::JSON)
+            .header(boards[i].1.clone())
+            .body(format!(r#"{{"tags": {}}}"#, tags))
+            .dispatch()
+    };
+
+    // Only admins manage the directory, and only directory tags can be applied
+    let resp = client
+        .post("/api/v1/admin/tags")
+        .header(ContentType::JSON)
+        .header(boards[0].1.clone())
+        .body(r#"{"name": "project-apollo"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = set_tags(0, r#"["project-apollo"]"#);
+    assert_eq!(resp.status(), Status::BadRequest);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["code"], "UNKNOWN_TAG");
+    for (name, description) in [("Project-Apollo", "Apollo launch"), ("team-infra", "")] {
+        let resp = client
+            .post("/api/v1/admin/tags")
+            .header(ContentType::JSON)
+            .header(admin.clone())
+            .body(format!(r#"{{"name": "{}", "description": "{}"}}"#, name, description))
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+    }
+    let resp = client
+        .post("/api/v1/admin/tags")
+        .header(ContentType::JSON)
+        .header(admin.clone())
+        .body(r#"{"name": "not a tag"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+
+    let resp = set_tags(0, r#"["project-apollo", "Team-Infra"]"#);
+    assert_eq!(resp.status(), Status::Ok);
+    let board: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(board["tags"], serde_json::json!(["project-apollo", "team-infra"]));
+    assert_eq!(set_tags(1, r#"["project-apollo"]"#).status(), Status::Ok);
+    assert_eq!(set_tags(2, r#"["team-infra"]"#).status(), Status::Ok);
+
+    // Filter the public listing by tag
+    let names = |url: &str| -> Vec<String> {
+        let body: serde_json::Value = client.get(url).dispatch().into_json().unwrap();
+        let mut names: Vec<String> =
+            body.as_array().unwrap().iter().map(|b| b["name"].as_str().unwrap().to_string()).collect();
+        names.sort();
+        names
+    };
+    assert_eq!(names("/api/v1/boards?tag=project-apollo"), ["Apollo API", "Apollo Web"]);
+    assert_eq!(names("/api/v1/boards?tag=TEAM-INFRA"), ["Apollo API", "Infra"]);
+    assert!(names("/api/v1/boards?tag=nothing").is_empty());
+    assert_eq!(names("/api/v1/boards").len(), 3);
+
+    let tags: serde_json::Value = client.get("/api/v1/tags").dispatch().into_json().unwrap();
+    assert_eq!(tags[0]["name"], "project-apollo");
+    assert_eq!(tags[0]["description"], "Apollo launch");
+    assert_eq!(tags[0]["board_count"], 2);
+    assert_eq!(tags[1]["board_count"], 2);
+
+    // [] clears a board's tags; deleting a tag removes it everywhere
+    let board: serde_json::Value = set_tags(1, "[]").into_json().unwrap();
+    assert_eq!(board["tags"], serde_json::json!([]));
+    let resp = client.delete("/api/v1/admin/tags/team-infra").header(admin.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["boards_untagged"], 2);
+    let board: serde_json::Value =
+        client.get(format!("/api/v1/boards/{}", boards[0].0)).dispatch().into_json().unwrap();
+    assert_eq!(board["tags"], serde_json::json!(["project-apollo"]));
+    let resp = client.delete("/api/v1/admin/tags/team-infra").header(admin).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
 // ============ Auth Guard ============
 
 #[test]
@@ -265,1848 +650,9962 @@ fn test_http_write_with_x_api_key_header() {
 }
 
 #[test]
-fn test_http_write_with_query_param_key() {
+fn test_http_collaborator_roles() {
     let client = test_client();
-    let (board_id, manage_key) = create_test_board(&client, "Query Param Test");
+    let (board_id, manage_key) = create_test_board(&client, "Roles Board");
+    let owner = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let collaborators_url = format!("/api/v1/boards/{}/collaborators", board_id);
+    let bearer = |key: &serde_json::Value| Header::new("Authorization", format!("Bearer {}", key.as_str().unwrap()));
+    let add = |auth: &Header<'static>, name: &str, role: &str| {
+        client
+            .post(&collaborators_url)
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(serde_json::json!({"name": name, "role": role}).to_string())
+            .dispatch()
+    };
 
+    let resp = add(&owner, "Ada", "admin");
+    assert_eq!(resp.status(), Status::Ok);
+    let admin: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(admin["role"], "admin");
+    assert!(admin["key"].as_str().unwrap().starts_with("kb_"));
+    let admin_auth = bearer(&admin["key"]);
+
+    // Admins add editors and viewers, but not other admins
+    let editor: serde_json::Value = add(&admin_auth, "Eve", "editor").into_json().unwrap();
+    let viewer: serde_json::Value = add(&admin_auth, "Vic", "viewer").into_json().unwrap();
+    let resp = add(&admin_auth, "Al", "admin");
+    assert_eq!(resp.status(), Status::Forbidden);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "INSUFFICIENT_ROLE");
+    let editor_auth = bearer(&editor["key"]);
+    let viewer_auth = bearer(&viewer["key"]);
+
+    let me: serde_json::Value = client
+        .get(format!("{}/me", collaborators_url))
+        .header(editor_auth.clone())
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(me["role"], "editor");
+    assert_eq!(me["name"], "Eve");
+    let me: serde_json::Value = client.get(format!("{}/me", collaborators_url)).header(owner.clone()).dispatch().into_json().unwrap();
+    assert_eq!(me["role"], "owner");
+    assert!(me["collaborator_id"].is_null());
+
+    // Editors work on tasks but can't change board settings or integrations
     let resp = client
-        .post(format!(
-            "/api/v1/boards/{}/tasks?key={}",
-            board_id, manage_key
-        ))
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
         .header(ContentType::JSON)
-        .body(r#"{"title": "Query param task"}"#)
+        .header(editor_auth.clone())
+        .body(r#"{"title": "By an editor"}"#)
         .dispatch();
-
     assert_eq!(resp.status(), Status::Ok);
-}
-
-#[test]
-fn test_http_write_wrong_key_rejected() {
-    let client = test_client();
-    let (board_id, _) = create_test_board(&client, "Wrong Key Test");
-
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .patch(format!("/api/v1/boards/{}", board_id))
         .header(ContentType::JSON)
-        .header(Header::new("Authorization", "Bearer kb_wrong_key_12345"))
-        .body(r#"{"title": "Should fail"}"#)
+        .header(editor_auth.clone())
+        .body(r#"{"name": "Renamed"}"#)
         .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client.get(format!("/api/v1/boards/{}/webhooks", board_id)).header(editor_auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client.get(&collaborators_url).header(editor_auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
 
-    assert!(
-        resp.status() == Status::Forbidden || resp.status() == Status::Unauthorized,
-        "Wrong key should be rejected, got {}",
-        resp.status()
-    );
-}
-
-// ============ Tasks ============
-
-#[test]
-fn test_http_task_crud() {
-    let client = test_client();
-    let (board_id, manage_key) = create_test_board(&client, "Task CRUD");
-    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
-
-    // Create task
+    // Viewers can't write tasks; admins can change settings but not delete the board
     let resp = client
         .post(format!("/api/v1/boards/{}/tasks", board_id))
         .header(ContentType::JSON)
-        .header(auth.clone())
-        .body(r#"{"title": "My Task", "description": "Do stuff", "priority": 2, "labels": ["bug", "urgent"]}"#)
+        .header(viewer_auth.clone())
+        .body(r#"{"title": "By a viewer"}"#)
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let task: serde_json::Value = resp.into_json().unwrap();
-    let task_id = task["id"].as_str().unwrap();
-    assert_eq!(task["title"], "My Task");
-
-    // Read task (no auth needed)
+    assert_eq!(resp.status(), Status::Forbidden);
     let resp = client
-        .get(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(admin_auth.clone())
+        .body(r#"{"name": "Renamed"}"#)
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let body: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(body["title"], "My Task");
+    let resp = client.delete(format!("/api/v1/boards/{}", board_id)).header(admin_auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
 
-    // Update task
+    // Keys only work on their own board
+    let (other_board, _) = create_test_board(&client, "Other Board");
     let resp = client
-        .patch(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
+        .post(format!("/api/v1/boards/{}/tasks", other_board))
         .header(ContentType::JSON)
-        .header(auth.clone())
-        .body(r#"{"title": "Updated Task", "priority": 3}"#)
+        .header(admin_auth.clone())
+        .body(r#"{"title": "Elsewhere"}"#)
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let body: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(body["title"], "Updated Task");
+    assert_eq!(resp.status(), Status::Forbidden);
 
-    // List tasks (no auth)
+    // Promote, demote and remove; the list never shows keys
     let resp = client
-        .get(format!("/api/v1/boards/{}/tasks", board_id))
+        .patch(format!("{}/{}", collaborators_url, viewer["id"].as_str().unwrap()))
+        .header(ContentType::JSON)
+        .header(admin_auth.clone())
+        .body(r#"{"role": "editor"}"#)
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let tasks: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(tasks.as_array().unwrap().len(), 1);
+    let resp = client
+        .patch(format!("{}/{}", collaborators_url, admin["id"].as_str().unwrap()))
+        .header(ContentType::JSON)
+        .header(admin_auth.clone())
+        .body(r#"{"role": "viewer"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let list: serde_json::Value = client.get(&collaborators_url).header(admin_auth.clone()).dispatch().into_json().unwrap();
+    let roles: Vec<&str> = list.as_array().unwrap().iter().map(|c| c["role"].as_str().unwrap()).collect();
+    assert_eq!(roles, vec!["admin", "editor", "editor"]);
+    assert!(list[0].get("key").is_none());
 
-    // Delete task
+    // Collaborators may leave; a removed key stops working
     let resp = client
-        .delete(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
-        .header(auth.clone())
+        .delete(format!("{}/{}", collaborators_url, editor["id"].as_str().unwrap()))
+        .header(editor_auth.clone())
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-
-    // Verify deleted
     let resp = client
-        .get(format!("/api/v1/boards/{}/tasks", board_id))
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(editor_auth)
+        .body(r#"{"title": "After leaving"}"#)
         .dispatch();
-    let tasks: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(tasks.as_array().unwrap().len(), 0);
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client
+        .delete(format!("{}/{}", collaborators_url, admin["id"].as_str().unwrap()))
+        .header(owner.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.get(&collaborators_url).header(admin_auth).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
 }
 
 #[test]
-fn test_http_task_empty_title_and_desc_rejected() {
+fn test_http_share_keys() {
     let client = test_client();
-    let (board_id, manage_key) = create_test_board(&client, "Empty Task");
-    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let (board_id, manage_key) = create_test_board(&client, "Shared Board");
+    let owner = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let keys_url = format!("/api/v1/boards/{}/keys", board_id);
 
-    // Both empty → rejected
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .post(&keys_url)
         .header(ContentType::JSON)
-        .header(auth.clone())
-        .body(r#"{"title": "   "}"#)
+        .header(owner.clone())
+        .body(r#"{"name": "Stakeholders", "role": "editor"}"#)
         .dispatch();
-
     assert_eq!(resp.status(), Status::BadRequest);
-    let body: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(body["code"], "EMPTY_TASK");
-
-    // Title only → accepted
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .post(format!("/api/v1/boards/{}/collaborators", board_id))
         .header(ContentType::JSON)
-        .header(auth.clone())
-        .body(r#"{"title": "Has title"}"#)
+        .header(owner.clone())
+        .body(r#"{"name": "Obs", "role": "read"}"#)
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(resp.status(), Status::BadRequest);
 
-    // Description only → accepted
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .post(&keys_url)
         .header(ContentType::JSON)
-        .header(auth.clone())
-        .body(r#"{"description": "Has description but no title"}"#)
+        .header(owner.clone())
+        .body(r#"{"name": "Stakeholders"}"#)
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let body: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(body["title"], "");
-    assert_eq!(body["description"], "Has description but no title");
+    let share: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(share["role"], "read");
+    let reader = Header::new("Authorization", format!("Bearer {}", share["key"].as_str().unwrap()));
 
-    // No title field at all, just description → accepted
+    // Reads that need a key work; writes and exports don't
+    let resp = client.get(format!("/api/v1/boards/{}/views", board_id)).header(reader.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.get(format!("/api/v1/boards/{}/export", board_id)).header(reader.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let me: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/collaborators/me", board_id))
+        .header(reader.clone())
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(me["role"], "read");
     let resp = client
         .post(format!("/api/v1/boards/{}/tasks", board_id))
         .header(ContentType::JSON)
-        .header(auth)
-        .body(r#"{"description": "Description-only task for AI"}"#)
+        .header(reader.clone())
+        .body(r#"{"title": "Sneaky"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "INSUFFICIENT_ROLE");
+    let resp = client.get(&keys_url).header(reader.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    let keys: serde_json::Value = client.get(&keys_url).header(owner.clone()).dispatch().into_json().unwrap();
+    assert_eq!(keys.as_array().unwrap().len(), 1);
+    assert_eq!(keys[0]["name"], "Stakeholders");
+    assert!(keys[0].get("key").is_none());
+
+    // Revoked keys stop working
+    let resp = client
+        .delete(format!("{}/{}", keys_url, share["id"].as_str().unwrap()))
+        .header(owner.clone())
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
+    let resp = client.get(format!("/api/v1/boards/{}/views", board_id)).header(reader).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client
+        .delete(format!("{}/{}", keys_url, share["id"].as_str().unwrap()))
+        .header(owner)
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
 }
 
-// ============ Move / Claim / Release ============
-
 #[test]
-fn test_http_move_task() {
+fn test_http_share_links() {
     let client = test_client();
-    let (board_id, manage_key) = create_test_board(&client, "Move Test");
-    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
-
-    // Get board to find column IDs
-    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
-    let board: serde_json::Value = resp.into_json().unwrap();
-    let columns = board["columns"].as_array().unwrap();
-    let todo_col = columns[0]["id"].as_str().unwrap();
-    let done_col = columns[2]["id"].as_str().unwrap();
-
-    // Create a task (goes to first column by default)
+    let (board_id, manage_key) = create_test_board(&client, "Review Board");
+    let owner = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let board_url = format!("/api/v1/boards/{}", board_id);
+    let links_url = format!("{}/share-links", board_url);
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .patch(&board_url)
         .header(ContentType::JSON)
-        .header(auth.clone())
-        .body(r#"{"title": "Moveable Task"}"#)
+        .header(owner.clone())
+        .body(r#"{"visibility": "private"}"#)
         .dispatch();
-    let task: serde_json::Value = resp.into_json().unwrap();
-    let task_id = task["id"].as_str().unwrap();
-    assert_eq!(task["column_id"].as_str().unwrap(), todo_col);
+    assert_eq!(resp.status(), Status::Ok);
+    let task: serde_json::Value = client
+        .post(format!("{}/tasks", board_url))
+        .header(ContentType::JSON)
+        .header(owner.clone())
+        .body(r#"{"title": "Review me"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let comment_url = format!("{}/tasks/{}/comment", board_url, task["id"].as_str().unwrap());
+
+    let resp = client.post(&links_url).header(ContentType::JSON).body("{}").dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let read: serde_json::Value = client
+        .post(&links_url)
+        .header(ContentType::JSON)
+        .header(owner.clone())
+        .body("{}")
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(read["scope"], "read");
+    let read_token = read["token"].as_str().unwrap().to_string();
+    assert!(read["url"].as_str().unwrap().ends_with(&format!("?key={}", read_token)));
+    let comment: serde_json::Value = client
+        .post(&links_url)
+        .header(ContentType::JSON)
+        .header(owner.clone())
+        .body(r#"{"scope": "comment", "ttl_seconds": 3600}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let comment_token = comment["token"].as_str().unwrap().to_string();
+
+    // Both read the private board; only the comment link comments
+    assert_eq!(client.get(format!("{}?key={}", board_url, read_token)).dispatch().status(), Status::Ok);
+    assert_eq!(client.get(format!("{}/tasks?key={}", board_url, comment_token)).dispatch().status(), Status::Ok);
+    let post_comment = |token: &str| {
+        client
+            .post(format!("{}?key={}", comment_url, token))
+            .header(ContentType::JSON)
+            .body(r#"{"message": "LGTM", "actor_name": "Reviewer"}"#)
+            .dispatch()
+            .status()
+    };
+    assert_eq!(post_comment(&read_token), Status::Forbidden);
+    assert_eq!(post_comment(&comment_token), Status::Ok);
+    let resp = client
+        .post(format!("{}/tasks?key={}", board_url, comment_token))
+        .header(ContentType::JSON)
+        .body(r#"{"title": "Not allowed"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
 
-    // Move to Done
+    // Tampered, other-board and expired links are refused
+    let tampered = comment_token.replacen("sl_comment.", "sl_read.", 1);
+    assert_eq!(client.get(format!("{}?key={}", board_url, tampered)).dispatch().status(), Status::Forbidden);
+    let (other_id, _) = create_test_board(&client, "Other Board");
+    let resp = client.get(format!("/api/v1/boards/{}/tasks?key={}", other_id, read_token)).dispatch();
+    assert_eq!(resp.status(), Status::Ok, "public boards ignore keys on reads");
     let resp = client
-        .post(format!(
-            "/api/v1/boards/{}/tasks/{}/move/{}",
-            board_id, task_id, done_col
-        ))
-        .header(auth.clone())
+        .post(format!("/api/v1/boards/{}/share-links", other_id))
+        .header(ContentType::JSON)
+        .header(owner.clone())
+        .body("{}")
         .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let parts: Vec<&str> = read_token.splitn(3, '.').collect();
+    let stale = format!("{}.{}.{}", parts[0], chrono::Utc::now().timestamp() - 10, parts[2]);
+    assert_eq!(client.get(format!("{}?key={}", board_url, stale)).dispatch().status(), Status::Forbidden);
+
+    // Rotating the secret revokes every link
+    let resp = client.delete(&links_url).header(owner).dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let moved: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(moved["column_id"].as_str().unwrap(), done_col);
-    // Moving to last column should set completed_at
-    assert!(moved["completed_at"].as_str().is_some());
+    assert_eq!(client.get(format!("{}?key={}", board_url, read_token)).dispatch().status(), Status::Forbidden);
+    assert_eq!(post_comment(&comment_token), Status::Forbidden);
 }
 
 #[test]
-fn test_http_claim_and_release() {
+fn test_http_comment_on_another_boards_task() {
     let client = test_client();
-    let (board_id, manage_key) = create_test_board(&client, "Claim Test");
-    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
-
-    let resp = client
-        .post(format!("/api/v1/boards/{}/tasks", board_id))
+    let (board_id, manage_key) = create_test_board(&client, "Reviewed Board");
+    let (other_id, other_key) = create_test_board(&client, "Someone Else's Board");
+    let owner = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let task: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks", other_id))
         .header(ContentType::JSON)
-        .header(auth.clone())
-        .body(r#"{"title": "Claimable Task"}"#)
-        .dispatch();
-    let task: serde_json::Value = resp.into_json().unwrap();
+        .header(Header::new("Authorization", format!("Bearer {}", other_key)))
+        .body(r#"{"title": "Not yours"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
     let task_id = task["id"].as_str().unwrap();
+    let link: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/share-links", board_id))
+        .header(ContentType::JSON)
+        .header(owner.clone())
+        .body(r#"{"scope": "comment"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
 
-    // Claim
+    // A key for one board can't comment on a task of another through its URL
+    let comment_url = format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id);
     let resp = client
-        .post(format!(
-            "/api/v1/boards/{}/tasks/{}/claim?actor=Nanook",
-            board_id, task_id
-        ))
-        .header(auth.clone())
+        .post(&comment_url)
+        .header(ContentType::JSON)
+        .header(owner)
+        .body(r#"{"message": "Sneaky", "actor_name": "Mallory"}"#)
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let body: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(body["claimed_by"], "Nanook");
-
-    // Double-claim by same agent is OK
+    assert_eq!(resp.status(), Status::NotFound);
     let resp = client
-        .post(format!(
-            "/api/v1/boards/{}/tasks/{}/claim?actor=Nanook",
-            board_id, task_id
-        ))
-        .header(auth.clone())
+        .post(format!("{}?key={}", comment_url, link["token"].as_str().unwrap()))
+        .header(ContentType::JSON)
+        .body(r#"{"message": "Sneaky", "actor_name": "Mallory"}"#)
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(resp.status(), Status::NotFound);
 
-    // Claim by different agent should fail (conflict)
-    let resp = client
-        .post(format!(
-            "/api/v1/boards/{}/tasks/{}/claim?actor=OtherAgent",
-            board_id, task_id
-        ))
+    let activity: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/tasks/{}/events", other_id, task_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert!(activity.as_array().unwrap().iter().all(|e| e["event_type"] != "comment"));
+}
+
+/// A stand-in OIDC provider: discovery, a token endpoint that accepts the
+/// code `good-code`, and a userinfo endpoint for that token. Returns its URL.
+fn spawn_oidc_provider() -> String {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let issuer = base.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            let _ = reader.read_line(&mut request_line);
+            let (mut content_length, mut authorization) = (0, String::new());
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                    break;
+                }
+                let lower = line.to_lowercase();
+                if let Some(len) = lower.strip_prefix("content-length:") {
+                    content_length = len.trim().parse().unwrap_or(0);
+                }
+                if lower.starts_with("authorization:") {
+                    authorization = line.trim().to_string();
+                }
+            }
+            let mut body = vec![0; content_length];
+            let _ = reader.read_exact(&mut body);
+            let body = String::from_utf8_lossy(&body);
+            let path = request_line.split_whitespace().nth(1).unwrap_or("");
+            let (status, reply) = match path {
+                "/.well-known/openid-configuration" => (
+                    "200 OK",
+                    serde_json::json!({
+                        "issuer": issuer,
+                        "authorization_endpoint": format!("{}/authorize", issuer),
+                        "token_endpoint": format!("{}/token", issuer),
+                        "userinfo_endpoint": format!("{}/userinfo", issuer),
+                    })
+                    .to_string(),
+                ),
+                "/token" if body.contains("code=good-code") && body.contains("client_secret=shh") => {
+                    ("200 OK", r#"{"access_token": "at-1", "token_type": "Bearer"}"#.to_string())
+                }
+                "/userinfo" if authorization.ends_with("Bearer at-1") => (
+                    "200 OK",
+                    r#"{"sub": "user-1", "email": "ada@example.com", "name": "Ada"}"#.to_string(),
+                ),
+                _ => ("401 Unauthorized", "{}".to_string()),
+            };
+            let _ = stream.write_all(
+                format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    reply.len(),
+                    reply
+                )
+                .as_bytes(),
+            );
+        }
+    });
+    base
+}
+
+#[test]
+fn test_http_oidc_sign_in() {
+    let disabled = test_client();
+    assert_eq!(disabled.get("/api/v1/auth/login").dispatch().status(), Status::NotFound);
+
+    let provider = spawn_oidc_provider();
+    let client = test_client_with_oidc(kanban::oidc::OidcConfig::new(
+        &provider,
+        "kanban",
+        "shh",
+        "http://localhost:8000/api/v1/auth/callback",
+    ));
+    let (their_id, _) = create_test_board(&client, "Theirs");
+    let config: serde_json::Value = client.get("/api/v1/auth/config").dispatch().into_json().unwrap();
+    assert_eq!(config["enabled"], true);
+    let state_of = |location: &str| location.split("state=").nth(1).unwrap().split('&').next().unwrap().to_string();
+
+    // Login redirects to the provider; the callback must echo its state
+    let resp = client.get("/api/v1/auth/login?return_to=/board/mine").dispatch();
+    assert_eq!(resp.status(), Status::SeeOther);
+    let location = resp.headers().get_one("Location").unwrap().to_string();
+    assert!(location.starts_with(&format!("{}/authorize?", provider)), "{}", location);
+    let resp = client.get("/api/v1/auth/callback?code=good-code&state=forged").dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    assert_eq!(client.get("/api/v1/auth/me").dispatch().status(), Status::Unauthorized);
+
+    let resp = client.get("/api/v1/auth/login?return_to=/board/mine").dispatch();
+    let state = state_of(resp.headers().get_one("Location").unwrap());
+    let resp = client.get(format!("/api/v1/auth/callback?code=bad-code&state={}", state)).dispatch();
+    assert_eq!(resp.status(), Status::BadGateway);
+
+    // return_to is kept on this site
+    let resp = client.get("/api/v1/auth/login?return_to=https://evil.example").dispatch();
+    let state = state_of(resp.headers().get_one("Location").unwrap());
+    let resp = client.get(format!("/api/v1/auth/callback?code=good-code&state={}", state)).dispatch();
+    assert_eq!(resp.status(), Status::SeeOther);
+    assert_eq!(resp.headers().get_one("Location"), Some("/"));
+    let me: serde_json::Value = client.get("/api/v1/auth/me").dispatch().into_json().unwrap();
+    assert_eq!(me["name"], "Ada");
+    assert_eq!(me["email"], "ada@example.com");
+
+    // Boards made while signed in belong to the user; the session manages them
+    let created: serde_json::Value = client
+        .post("/api/v1/boards")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "Ada's Board"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let board_id = created["id"].as_str().unwrap().to_string();
+    let update = |id: &str| {
+        client
+            .patch(format!("/api/v1/boards/{}", id))
+            .header(ContentType::JSON)
+            .body(r#"{"description": "signed in"}"#)
+            .dispatch()
+            .status()
+    };
+    assert_eq!(update(&board_id), Status::Ok);
+    let me: serde_json::Value = client.get("/api/v1/auth/me").dispatch().into_json().unwrap();
+    assert_eq!(me["boards"][0]["id"], board_id.as_str());
+    assert_eq!(me["boards"][0]["name"], "Ada's Board");
+
+    // ...but not boards made before signing in
+    assert_eq!(update(&their_id), Status::Forbidden);
+
+    // The session acts with an owner key listed among the collaborators, so
+    // that record's role and expiry apply to it
+    let owner = Header::new("Authorization", format!("Bearer {}", created["manage_key"].as_str().unwrap()));
+    let collaborators: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/collaborators", board_id))
+        .header(owner)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let collaborators = collaborators.as_array().unwrap();
+    assert_eq!(collaborators.len(), 1);
+    assert_eq!(collaborators[0]["name"], "Ada");
+    assert_eq!(collaborators[0]["role"], "owner");
+    let member = |sql: &str| {
+        let pool = client.rocket().state::<kanban::db::DbPool>().unwrap();
+        pool.get().unwrap().execute(sql, rusqlite::params![board_id]).unwrap();
+    };
+    member("UPDATE board_members SET role = 'read' WHERE board_id = ?1");
+    assert_eq!(update(&board_id), Status::Forbidden);
+    member("UPDATE board_members SET role = 'owner', expires_at = datetime('now', '-1 minute') WHERE board_id = ?1");
+    assert_eq!(update(&board_id), Status::Unauthorized);
+    member("UPDATE board_members SET expires_at = NULL WHERE board_id = ?1");
+    assert_eq!(update(&board_id), Status::Ok);
+
+    let resp = client.post("/api/v1/auth/logout").dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(client.get("/api/v1/auth/me").dispatch().status(), Status::Unauthorized);
+    assert_eq!(update(&board_id), Status::Unauthorized);
+}
+
+#[test]
+fn test_http_oidc_session_with_view_cookie() {
+    let provider = spawn_oidc_provider();
+    let client = test_client_with_oidc(kanban::oidc::OidcConfig::new(
+        &provider,
+        "kanban",
+        "shh",
+        "http://localhost:8000/api/v1/auth/callback",
+    ));
+    let protected = |client: &Client| -> String {
+        let created: serde_json::Value = client
+            .post("/api/v1/boards")
+            .header(ContentType::JSON)
+            .body(r#"{"name": "Family Board", "view_password": "open sesame"}"#)
+            .dispatch()
+            .into_json()
+            .unwrap();
+        created["id"].as_str().unwrap().to_string()
+    };
+    let theirs = protected(&client);
+
+    let resp = client.get("/api/v1/auth/login").dispatch();
+    let location = resp.headers().get_one("Location").unwrap().to_string();
+    let state = location.split("state=").nth(1).unwrap().split('&').next().unwrap().to_string();
+    let resp = client.get(format!("/api/v1/auth/callback?code=good-code&state={}", state)).dispatch();
+    assert_eq!(resp.status(), Status::SeeOther);
+    let mine = protected(&client);
+
+    // Both cookies are sent; the session keeps the owner's role...
+    let view_token = |board_id: &str| -> String {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/view-session", board_id))
+            .header(ContentType::JSON)
+            .body(r#"{"password": "open sesame"}"#)
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        resp.cookies().get("kb_view").unwrap().value().to_string()
+    };
+    let token = view_token(&mine);
+    let resp = client
+        .patch(format!("/api/v1/boards/{}", mine))
+        .header(ContentType::JSON)
+        .cookie(rocket::http::Cookie::new("kb_view", token))
+        .body(r#"{"description": "still mine"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // ...and the view token still opens a board the session has no role on
+    let token = view_token(&theirs);
+    let view = rocket::http::Cookie::new("kb_view", token);
+    let resp = client.get(format!("/api/v1/boards/{}", theirs)).cookie(view.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", theirs))
+        .header(ContentType::JSON)
+        .cookie(view)
+        .body(r#"{"title": "Sneaky"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+}
+
+#[test]
+fn test_http_scoped_tokens() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Scoped Board");
+    let owner = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let tokens_url = format!("/api/v1/boards/{}/tokens", board_id);
+    let board: serde_json::Value = client.get(format!("/api/v1/boards/{}", board_id)).dispatch().into_json().unwrap();
+    let todo = board["columns"][0]["id"].as_str().unwrap().to_string();
+    let doing = board["columns"][1]["id"].as_str().unwrap().to_string();
+
+    let resp = client
+        .post(&tokens_url)
+        .header(ContentType::JSON)
+        .header(owner.clone())
+        .body(r#"{"name": "Worker", "actions": ["create_task"], "columns": ["Nowhere"]}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+
+    let resp = client
+        .post(&tokens_url)
+        .header(ContentType::JSON)
+        .header(owner.clone())
+        .body(r#"{"name": "Worker", "actions": ["create_task", "comment"], "columns": ["to do"]}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let scoped: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(scoped["columns"], serde_json::json!([todo]));
+    let worker = Header::new("Authorization", format!("Bearer {}", scoped["key"].as_str().unwrap()));
+
+    // In scope: create in To Do, comment there
+    let task: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(worker.clone())
+        .body(r#"{"title": "Scoped work"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let task_id = task["id"].as_str().unwrap();
+    assert_eq!(task["column_id"], todo.as_str());
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+        .header(ContentType::JSON)
+        .header(worker.clone())
+        .body(r#"{"message": "on it"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Out of scope: another column, another action, anything needing a role
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(worker.clone())
+        .body(format!(r#"{{"title": "Elsewhere", "column_id": "{}"}}"#, doing))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "OUT_OF_SCOPE");
+    // Even when it repeats a task already there
+    client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(owner.clone())
+        .body(r#"{"duplicate_window_seconds": 60}"#)
+        .dispatch();
+    let repeat = format!(r#"{{"title": "Elsewhere", "column_id": "{}", "actor_name": "w"}}"#, doing);
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(owner.clone())
+        .body(repeat.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(worker.clone())
+        .body(repeat)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}", board_id, task_id, doing))
+        .header(worker.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "OUT_OF_SCOPE");
+    let resp = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(worker.clone())
+        .body(r#"{"name": "Mine now"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client.get(&tokens_url).header(worker.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // Once the task leaves its columns, the token can't comment on it
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}", board_id, task_id, doing))
+        .header(owner.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+        .header(ContentType::JSON)
+        .header(worker.clone())
+        .body(r#"{"message": "still here"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // A token without actions is read-only
+    let reader: serde_json::Value = client
+        .post(&tokens_url)
+        .header(ContentType::JSON)
+        .header(owner.clone())
+        .body(r#"{"name": "Reader"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(reader["actions"], serde_json::json!([]));
+    let reader_key = Header::new("Authorization", format!("Bearer {}", reader["key"].as_str().unwrap()));
+    let resp = client.get(format!("/api/v1/boards/{}/views", board_id)).header(reader_key.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/claim", board_id, task_id))
+        .header(reader_key)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    let listed: serde_json::Value = client.get(&tokens_url).header(owner.clone()).dispatch().into_json().unwrap();
+    assert_eq!(listed.as_array().unwrap().len(), 2);
+    assert!(listed[0].get("key").is_none());
+
+    // Revoked tokens stop working
+    let resp = client
+        .delete(format!("{}/{}", tokens_url, scoped["id"].as_str().unwrap()))
+        .header(owner)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(worker)
+        .body(r#"{"title": "After revoke"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "INVALID_KEY");
+}
+
+#[test]
+fn test_http_agent_keys() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Agent Board");
+    let owner = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let keys_url = format!("/api/v1/boards/{}/agent-keys", board_id);
+
+    for bad in [r#"{"agent_id": "  "}"#, r#"{"agent_id": "anonymous"}"#, r#"{"agent_id": "bot", "role": "read"}"#] {
+        let resp = client.post(&keys_url).header(ContentType::JSON).header(owner.clone()).body(bad).dispatch();
+        assert_eq!(resp.status(), Status::BadRequest, "{}", bad);
+    }
+    let resp = client
+        .post(&keys_url)
+        .header(ContentType::JSON)
+        .header(owner.clone())
+        .body(r#"{"agent_id": "builder-bot"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let agent_key: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(agent_key["role"], "editor");
+    assert_eq!(agent_key["agent_id"], "builder-bot");
+    let agent = Header::new("Authorization", format!("Bearer {}", agent_key["key"].as_str().unwrap()));
+
+    // Whatever the request claims, the write is the agent's
+    let task: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(agent.clone())
+        .header(Header::new("X-Actor", "Someone Else"))
+        .body(r#"{"title": "Attributed", "actor_name": "Spoof"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let task_id = task["id"].as_str().unwrap();
+    assert_eq!(task["created_by"], "builder-bot");
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/claim?agent=Spoof", board_id, task_id))
+        .header(agent.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let claimed: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(claimed["claimed_by"], "builder-bot");
+    let comment: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+        .header(ContentType::JSON)
+        .header(agent.clone())
+        .body(r#"{"message": "done", "actor_name": "Spoof"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(comment["actor"], "builder-bot");
+    let events: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/boards/{}/tasks/{}/events", board_id, task_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert!(!events.is_empty());
+    assert!(events.iter().all(|e| e["actor"] == "builder-bot"), "{:?}", events);
+
+    // Other keys still name themselves
+    let task: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(owner.clone())
+        .body(r#"{"title": "Mine", "actor_name": "Ada"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(task["created_by"], "Ada");
+
+    // Listed (without the key) for admins only; revoking stops it at once
+    let resp = client.get(&keys_url).header(agent.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let listed: Vec<serde_json::Value> = client.get(&keys_url).header(owner.clone()).dispatch().into_json().unwrap();
+    assert_eq!(listed.len(), 1);
+    assert!(listed[0].get("key").is_none());
+    let resp = client
+        .delete(format!("{}/{}", keys_url, agent_key["id"].as_str().unwrap()))
+        .header(owner.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(agent)
+        .body(r#"{"title": "Too late"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+}
+
+#[test]
+fn test_http_ip_allowlist() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Allowlisted Board");
+    let owner = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let allowlist_url = format!("/api/v1/boards/{}/ip-allowlist", board_id);
+    let from = |ip: &str| format!("{}:40000", ip).parse::<std::net::SocketAddr>().unwrap();
+    let set = |ranges: &str, ip: &str| {
+        client
+            .put(&allowlist_url)
+            .header(ContentType::JSON)
+            .header(owner.clone())
+            .remote(from(ip))
+            .body(format!(r#"{{"ranges": {}}}"#, ranges))
+            .dispatch()
+    };
+    let write = |ip: Option<&str>| {
+        let mut req = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(owner.clone())
+            .body(r#"{"title": "From somewhere"}"#);
+        if let Some(ip) = ip {
+            req = req.remote(from(ip));
+        }
+        req.dispatch()
+    };
+
+    let body: serde_json::Value =
+        client.get(&allowlist_url).header(owner.clone()).remote(from("203.0.113.5")).dispatch().into_json().unwrap();
+    assert_eq!(body["ranges"], serde_json::json!([]));
+    assert_eq!(body["client_ip"], "203.0.113.5");
+
+    let resp = set(r#"["not-an-ip"]"#, "203.0.113.5");
+    assert_eq!(resp.status(), Status::BadRequest);
+    assert_eq!(resp.into_json::<serde_json::Value>().unwrap()["code"], "INVALID_INPUT");
+    // Leaving yourself out would lock you out of the board
+    let resp = set(r#"["10.0.0.0/8"]"#, "203.0.113.5");
+    assert_eq!(resp.status(), Status::BadRequest);
+    assert_eq!(resp.into_json::<serde_json::Value>().unwrap()["code"], "WOULD_LOCK_OUT");
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/inbound", board_id))
+        .header(ContentType::JSON)
+        .header(owner.clone())
+        .body(r#"{"name": "Alerts", "mapping": {"title": "{{payload.alert}}"}}"#)
+        .dispatch();
+    let hook_url = format!("/api/v1/boards/{}/inbound/{}", board_id, resp.into_json::<serde_json::Value>().unwrap()["token"].as_str().unwrap());
+
+    let resp = set(r#"["203.0.113.0/24", "10.1.2.3/8", "10.0.0.0/8"]"#, "203.0.113.5");
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["ranges"], serde_json::json!(["203.0.113.0/24", "10.0.0.0/8"]));
+
+    // Writes need an address inside the list, even with the manage key
+    assert_eq!(write(Some("10.9.8.7")).status(), Status::Ok);
+    let resp = write(Some("198.51.100.1"));
+    assert_eq!(resp.status(), Status::Forbidden);
+    assert_eq!(resp.into_json::<serde_json::Value>().unwrap()["error"], "IP_NOT_ALLOWED");
+    assert_eq!(write(None).status(), Status::Forbidden);
+    assert_eq!(set("[]", "198.51.100.1").status(), Status::Forbidden);
+
+    // Forwarding headers from an untrusted peer don't move the address inside
+    for (name, value) in [("X-Forwarded-For", "10.9.8.7"), ("X-Real-Ip", "10.9.8.7")] {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(owner.clone())
+            .header(Header::new(name, value))
+            .remote(from("198.51.100.1"))
+            .body(r#"{"title": "Spoofed"}"#)
+            .dispatch();
+        assert_eq!(resp.status(), Status::Forbidden, "{}", name);
+    }
+
+    // Inbound hook payloads are held to the list too
+    let deliver = |ip: &str| {
+        client.post(&hook_url).header(ContentType::JSON).remote(from(ip)).body(r#"{"alert": "disk full"}"#).dispatch()
+    };
+    let resp = deliver("198.51.100.1");
+    assert_eq!(resp.status(), Status::Forbidden);
+    assert_eq!(resp.into_json::<serde_json::Value>().unwrap()["error"], "IP_NOT_ALLOWED");
+    assert_eq!(deliver("10.0.0.9").status(), Status::Ok);
+
+    // Reads don't
+    let resp = client.get(format!("/api/v1/boards/{}/tasks", board_id)).remote(from("198.51.100.1")).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    assert_eq!(set("[]", "10.0.0.1").status(), Status::Ok);
+    assert_eq!(write(Some("198.51.100.1")).status(), Status::Ok);
+}
+
+#[test]
+fn test_http_client_ip_behind_trusted_proxy() {
+    let client = test_client_with_trusted_proxies("10.0.0.0/8");
+    let (board_id, manage_key) = create_test_board(&client, "Proxied Board");
+    let client_ip = |peer: &str, forwarded_for: &str| {
+        let body: serde_json::Value = client
+            .get(format!("/api/v1/boards/{}/ip-allowlist", board_id))
+            .header(Header::new("Authorization", format!("Bearer {}", manage_key)))
+            .header(Header::new("X-Forwarded-For", forwarded_for.to_string()))
+            .remote(format!("{}:40000", peer).parse().unwrap())
+            .dispatch()
+            .into_json()
+            .unwrap();
+        body["client_ip"].as_str().unwrap().to_string()
+    };
+
+    // The rightmost hop the proxies didn't add is the client; what it claims
+    // further left is ignored
+    assert_eq!(client_ip("10.0.0.2", "192.0.2.1, 203.0.113.5, 10.0.0.3"), "203.0.113.5");
+    assert_eq!(client_ip("10.0.0.2", "203.0.113.5"), "203.0.113.5");
+    // Headers from anyone else are ignored
+    assert_eq!(client_ip("198.51.100.1", "203.0.113.5"), "198.51.100.1");
+}
+
+#[test]
+fn test_http_expiring_keys() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Expiring Board");
+    let owner = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let mint = |path: &str, body: &str| {
+        client
+            .post(format!("/api/v1/boards/{}/{}", board_id, path))
+            .header(ContentType::JSON)
+            .header(owner.clone())
+            .body(body)
+            .dispatch()
+    };
+
+    assert_eq!(mint("keys", r#"{"ttl_seconds": 0}"#).status(), Status::BadRequest);
+    assert_eq!(mint("keys", r#"{"expires_at": "2001-01-01T00:00:00Z"}"#).status(), Status::BadRequest);
+    assert_eq!(
+        mint("keys", r#"{"ttl_seconds": 60, "expires_at": "2999-01-01T00:00:00Z"}"#).status(),
+        Status::BadRequest
+    );
+
+    let share: serde_json::Value = mint("keys", r#"{"name": "Run 42", "ttl_seconds": 600}"#).into_json().unwrap();
+    assert!(share["expires_at"].is_string());
+    let collaborator: serde_json::Value =
+        mint("collaborators", r#"{"name": "Nightly", "role": "editor", "expires_at": "2999-01-01T00:00:00Z"}"#)
+            .into_json()
+            .unwrap();
+    assert_eq!(collaborator["expires_at"], "2999-01-01 00:00:00");
+    let scoped: serde_json::Value = mint("tokens", r#"{"name": "Forever"}"#).into_json().unwrap();
+    assert!(scoped["expires_at"].is_null());
+
+    let listing = || -> Vec<serde_json::Value> {
+        client
+            .get(format!("/api/v1/boards/{}/credentials", board_id))
+            .header(owner.clone())
+            .dispatch()
+            .into_json()
+            .unwrap()
+    };
+    let keys = listing();
+    let kinds: Vec<&str> = keys.iter().map(|k| k["kind"].as_str().unwrap()).collect();
+    assert_eq!(kinds, vec!["share", "collaborator", "scoped"]);
+    let remaining = keys[0]["expires_in_seconds"].as_i64().unwrap();
+    assert!((590..=600).contains(&remaining), "{}", remaining);
+    assert!(keys[2]["expires_in_seconds"].is_null());
+
+    // Once the expiry passes, the key is refused before any route runs
+    let reader = Header::new("Authorization", format!("Bearer {}", share["key"].as_str().unwrap()));
+    let resp = client.get(format!("/api/v1/boards/{}/views", board_id)).header(reader.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let pool = client.rocket().state::<kanban::db::DbPool>().unwrap();
+    pool.get()
+        .unwrap()
+        .execute(
+            "UPDATE share_keys SET expires_at = datetime('now', '-1 second') WHERE id = ?1",
+            [share["id"].as_str().unwrap()],
+        )
+        .unwrap();
+    let resp = client.get(format!("/api/v1/boards/{}/views", board_id)).header(reader).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["error"], "TOKEN_EXPIRED");
+    let resp = client
+        .get(format!("/api/v1/events/stream?token={}", share["key"].as_str().unwrap()))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    assert_eq!(listing().len(), 2);
+}
+
+#[test]
+fn test_http_private_boards() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Private Board");
+    let owner = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let board_url = format!("/api/v1/boards/{}", board_id);
+
+    // Unlisted by default: readable without a key
+    let board: serde_json::Value = client.get(&board_url).dispatch().into_json().unwrap();
+    assert_eq!(board["visibility"], "unlisted");
+    assert_eq!(board["is_public"], false);
+
+    let resp = client
+        .patch(&board_url)
+        .header(ContentType::JSON)
+        .header(owner.clone())
+        .body(r#"{"visibility": "private"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let board: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(board["visibility"], "private");
+
+    // Reads need a key now
+    for path in ["", "/tasks", "/events/poll?timeout=0", "/activity"] {
+        let resp = client.get(format!("{}{}", board_url, path)).dispatch();
+        assert_eq!(resp.status(), Status::Unauthorized, "{}", path);
+        let err: serde_json::Value = resp.into_json().unwrap();
+        assert_eq!(err["code"], "KEY_REQUIRED");
+    }
+    let resp = client.get(&board_url).header(Header::new("Authorization", "Bearer kb_wrong")).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client.get(format!("{}/tasks", board_url)).header(owner.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Share keys and ?key= work too
+    let share: serde_json::Value = client
+        .post(format!("{}/keys", board_url))
+        .header(ContentType::JSON)
+        .header(owner.clone())
+        .body(r#"{"name": "Dashboard"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let share_key = share["key"].as_str().unwrap();
+    let resp = client.get(format!("{}?key={}", board_url, share_key)).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // The multi-board stream only takes private boards via their keys
+    let resp = client.get(format!("/api/v1/events/stream?boards={}", board_id)).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // Unlisting a private board with is_public keeps it private; public lists it
+    let board: serde_json::Value = client
+        .patch(&board_url)
+        .header(ContentType::JSON)
+        .header(owner.clone())
+        .body(r#"{"is_public": false}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(board["visibility"], "private");
+    let listed: serde_json::Value = client.get("/api/v1/boards").dispatch().into_json().unwrap();
+    assert!(!listed.as_array().unwrap().iter().any(|b| b["id"] == board_id.as_str()));
+    let board: serde_json::Value = client
+        .patch(&board_url)
+        .header(ContentType::JSON)
+        .header(owner)
+        .body(r#"{"is_public": true}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(board["visibility"], "public");
+    assert_eq!(client.get(&board_url).dispatch().status(), Status::Ok);
+    let listed: serde_json::Value = client.get("/api/v1/boards").dispatch().into_json().unwrap();
+    assert!(listed.as_array().unwrap().iter().any(|b| b["id"] == board_id.as_str()));
+
+    // Created private
+    let resp = client
+        .post("/api/v1/boards")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "Secret", "visibility": "private", "is_public": true}"#)
+        .dispatch();
+    let created: serde_json::Value = resp.into_json().unwrap();
+    let resp = client.get(format!("/api/v1/boards/{}", created["id"].as_str().unwrap())).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+}
+
+#[test]
+fn test_http_view_password() {
+    let client = test_client();
+    let resp = client
+        .post("/api/v1/boards")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "Family Board", "view_password": "open sesame"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let created: serde_json::Value = resp.into_json().unwrap();
+    let board_id = created["id"].as_str().unwrap().to_string();
+    let owner = Header::new("Authorization", format!("Bearer {}", created["manage_key"].as_str().unwrap()));
+    let board_url = format!("/api/v1/boards/{}", board_id);
+    let session_url = format!("/api/v1/boards/{}/view-session", board_id);
+
+    // Keys still read; anyone else needs the password
+    let board: serde_json::Value = client.get(&board_url).header(owner.clone()).dispatch().into_json().unwrap();
+    assert_eq!(board["password_protected"], true);
+    let resp = client.get(&board_url).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "PASSWORD_REQUIRED");
+    let resp = client.get(format!("{}/tasks", board_url)).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    let resp = client.post(&session_url).header(ContentType::JSON).body(r#"{"password": "guess"}"#).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client.post(&session_url).header(ContentType::JSON).body(r#"{"password": "open sesame"}"#).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert!(resp.cookies().get("kb_view").is_some());
+    let session: serde_json::Value = resp.into_json().unwrap();
+    let token = session["token"].as_str().unwrap().to_string();
+    assert_eq!(session["expires_in_seconds"], 12 * 3600);
+
+    // The token reads (as a header or the cookie) but can't write
+    let viewer = Header::new("Authorization", format!("Bearer {}", token));
+    assert_eq!(client.get(&board_url).header(viewer.clone()).dispatch().status(), Status::Ok);
+    assert_eq!(client.get(format!("{}/tasks", board_url)).header(viewer.clone()).dispatch().status(), Status::Ok);
+    let resp = client.get(&board_url).cookie(rocket::http::Cookie::new("kb_view", token.clone())).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client
+        .post(format!("{}/tasks", board_url))
+        .header(ContentType::JSON)
+        .header(viewer.clone())
+        .body(r#"{"title": "Sneaky"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // Changing the password ends the session; clearing it opens the board
+    let resp = client
+        .patch(&board_url)
+        .header(ContentType::JSON)
+        .header(owner.clone())
+        .body(r#"{"view_password": "new secret"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(client.get(&board_url).header(viewer).dispatch().status(), Status::Forbidden);
+    let resp = client.patch(&board_url).header(ContentType::JSON).header(owner).body(r#"{"view_password": ""}"#).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(board["password_protected"], false);
+    assert_eq!(client.get(&board_url).dispatch().status(), Status::Ok);
+    let resp = client.post(&session_url).header(ContentType::JSON).body(r#"{"password": "new secret"}"#).dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+}
+
+#[test]
+fn test_http_write_with_query_param_key() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Query Param Test");
+
+    let resp = client
+        .post(format!(
+            "/api/v1/boards/{}/tasks?key={}",
+            board_id, manage_key
+        ))
+        .header(ContentType::JSON)
+        .body(r#"{"title": "Query param task"}"#)
+        .dispatch();
+
+    assert_eq!(resp.status(), Status::Ok);
+}
+
+#[test]
+fn test_http_write_wrong_key_rejected() {
+    let client = test_client();
+    let (board_id, _) = create_test_board(&client, "Wrong Key Test");
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", "Bearer kb_wrong_key_12345"))
+        .body(r#"{"title": "Should fail"}"#)
+        .dispatch();
+
+    assert!(
+        resp.status() == Status::Forbidden || resp.status() == Status::Unauthorized,
+        "Wrong key should be rejected, got {}",
+        resp.status()
+    );
+}
+
+// ============ Tasks ============
+
+#[test]
+fn test_http_task_crud() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Task CRUD");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    // Create task
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "My Task", "description": "Do stuff", "priority": 2, "labels": ["bug", "urgent"]}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let task: serde_json::Value = resp.into_json().unwrap();
+    let task_id = task["id"].as_str().unwrap();
+    assert_eq!(task["title"], "My Task");
+
+    // Read task (no auth needed)
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["title"], "My Task");
+
+    // Update task
+    let resp = client
+        .patch(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Updated Task", "priority": 3}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["title"], "Updated Task");
+
+    // List tasks (no auth)
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let tasks: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(tasks.as_array().unwrap().len(), 1);
+
+    // Delete task
+    let resp = client
+        .delete(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Verify deleted
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks", board_id))
+        .dispatch();
+    let tasks: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(tasks.as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn test_http_task_empty_title_and_desc_rejected() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Empty Task");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    // Both empty → rejected
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "   "}"#)
+        .dispatch();
+
+    assert_eq!(resp.status(), Status::BadRequest);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["code"], "EMPTY_TASK");
+
+    // Title only → accepted
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Has title"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Description only → accepted
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"description": "Has description but no title"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["title"], "");
+    assert_eq!(body["description"], "Has description but no title");
+
+    // No title field at all, just description → accepted
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth)
+        .body(r#"{"description": "Description-only task for AI"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+}
+
+#[test]
+fn test_http_duplicate_task_suppression() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Dedup");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let create = |body: &'static str| -> serde_json::Value {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(body)
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        resp.into_json().unwrap()
+    };
+
+    // Off by default: identical creates make two tasks
+    let first = create(r#"{"title": "Deploy", "description": "ship it", "actor_name": "bot"}"#);
+    let second = create(r#"{"title": "Deploy", "description": "ship it", "actor_name": "bot"}"#);
+    assert_ne!(first["id"], second["id"]);
+    assert!(first.get("duplicate").is_none());
+
+    let resp = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"duplicate_window_seconds": 99999}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let board: serde_json::Value = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"duplicate_window_seconds": 60}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(board["duplicate_window_seconds"], 60);
+
+    // Same content modulo case and whitespace, same creator → the original back
+    let original = create(r#"{"title": "Rotate keys", "description": "all of them", "actor_name": "bot"}"#);
+    let again = create(r#"{"title": "  rotate   KEYS ", "description": "All of them", "actor_name": "Bot"}"#);
+    assert_eq!(again["id"], original["id"]);
+    assert_eq!(again["duplicate"], true);
+    assert_eq!(again["title"], "Rotate keys");
+
+    // Different creator or different content is a new task
+    let other = create(r#"{"title": "Rotate keys", "description": "all of them", "actor_name": "human"}"#);
+    assert_ne!(other["id"], original["id"]);
+    let changed = create(r#"{"title": "Rotate keys", "description": "only the old ones", "actor_name": "bot"}"#);
+    assert_ne!(changed["id"], original["id"]);
+
+    let tasks: serde_json::Value =
+        client.get(format!("/api/v1/boards/{}/tasks", board_id)).dispatch().into_json().unwrap();
+    assert_eq!(tasks.as_array().unwrap().len(), 5);
+
+    // 0 turns suppression off again
+    let board: serde_json::Value = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"duplicate_window_seconds": 0}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert!(board["duplicate_window_seconds"].is_null());
+    let fresh = create(r#"{"title": "Rotate keys", "description": "all of them", "actor_name": "bot"}"#);
+    assert_ne!(fresh["id"], original["id"]);
+}
+
+#[test]
+fn test_http_clone_task() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Clone HTTP Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Weekly report", "description": "Compile stats", "priority": 2, "labels": ["Ops"], "metadata": {"checklist": ["a", "b"]}, "actor_name": "alice"}"#)
+        .dispatch();
+    let source: serde_json::Value = resp.into_json().unwrap();
+    let source_id = source["id"].as_str().unwrap();
+
+    client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, source_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "not copied"}"#)
+        .dispatch();
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/clone", board_id, source_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Weekly report (next)", "actor_name": "bob"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let clone: serde_json::Value = resp.into_json().unwrap();
+    assert_ne!(clone["id"], source["id"]);
+    assert_eq!(clone["title"], "Weekly report (next)");
+    assert_eq!(clone["description"], "Compile stats");
+    assert_eq!(clone["priority"], 2);
+    assert_eq!(clone["labels"], serde_json::json!(["ops"]));
+    assert_eq!(clone["metadata"]["checklist"], serde_json::json!(["a", "b"]));
+    assert_eq!(clone["created_by"], "bob");
+    assert_eq!(clone["comment_count"], 0);
+
+    // Created event attributed to the cloner
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks/{}/events", board_id, clone["id"].as_str().unwrap()))
+        .dispatch();
+    let events: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(events[0]["event_type"], "created");
+    assert_eq!(events[0]["actor"], "bob");
+    assert_eq!(events[0]["data"]["cloned_from"], source_id);
+
+    // Opt out of labels and metadata
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/clone", board_id, source_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"include_labels": false, "include_metadata": false}"#)
+        .dispatch();
+    let bare: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(bare["title"], "Weekly report");
+    assert_eq!(bare["labels"], serde_json::json!([]));
+    assert_eq!(bare["metadata"], serde_json::json!({}));
+
+    // Overrides can't leave the clone without a title or description
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Title only"}"#)
+        .dispatch();
+    let title_only: serde_json::Value = resp.into_json().unwrap();
+    let title_only_id = title_only["id"].as_str().unwrap();
+    for body in [r#"{"title": ""}"#, r#"{"title": "   ", "description": " "}"#] {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks/{}/clone", board_id, title_only_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(body)
+            .dispatch();
+        assert_eq!(resp.status(), Status::BadRequest);
+        let err: serde_json::Value = resp.into_json().unwrap();
+        assert_eq!(err["code"], "EMPTY_TASK");
+    }
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/clone", board_id, title_only_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "", "description": "Described instead"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/nonexistent/clone", board_id))
+        .header(ContentType::JSON)
+        .header(auth)
+        .body("{}")
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+// ============ Move / Claim / Release ============
+
+#[test]
+fn test_http_move_task() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Move Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    // Get board to find column IDs
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let columns = board["columns"].as_array().unwrap();
+    let todo_col = columns[0]["id"].as_str().unwrap();
+    let done_col = columns[2]["id"].as_str().unwrap();
+
+    // Create a task (goes to first column by default)
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Moveable Task"}"#)
+        .dispatch();
+    let task: serde_json::Value = resp.into_json().unwrap();
+    let task_id = task["id"].as_str().unwrap();
+    assert_eq!(task["column_id"].as_str().unwrap(), todo_col);
+
+    // Move to Done
+    let resp = client
+        .post(format!(
+            "/api/v1/boards/{}/tasks/{}/move/{}",
+            board_id, task_id, done_col
+        ))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let moved: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(moved["column_id"].as_str().unwrap(), done_col);
+    // Moving to last column should set completed_at
+    assert!(moved["completed_at"].as_str().is_some());
+}
+
+#[test]
+fn test_http_claim_and_release() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Claim Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Claimable Task"}"#)
+        .dispatch();
+    let task: serde_json::Value = resp.into_json().unwrap();
+    let task_id = task["id"].as_str().unwrap();
+
+    // Claim
+    let resp = client
+        .post(format!(
+            "/api/v1/boards/{}/tasks/{}/claim?actor=Nanook",
+            board_id, task_id
+        ))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["claimed_by"], "Nanook");
+
+    // Double-claim by same agent is OK
+    let resp = client
+        .post(format!(
+            "/api/v1/boards/{}/tasks/{}/claim?actor=Nanook",
+            board_id, task_id
+        ))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Claim by different agent should fail (conflict)
+    let resp = client
+        .post(format!(
+            "/api/v1/boards/{}/tasks/{}/claim?actor=OtherAgent",
+            board_id, task_id
+        ))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+
+    // Release
+    let resp = client
+        .post(format!(
+            "/api/v1/boards/{}/tasks/{}/release",
+            board_id, task_id
+        ))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert!(body["claimed_by"].is_null());
+}
+
+#[test]
+fn test_http_claims_by_agent() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Claims Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    let mut task_ids = Vec::new();
+    for title in ["One", "Two", "Three", "Unclaimed"] {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "{}"}}"#, title))
+            .dispatch();
+        let task: serde_json::Value = resp.into_json().unwrap();
+        task_ids.push(task["id"].as_str().unwrap().to_string());
+    }
+    for (task_id, agent) in task_ids.iter().zip(["Ada", "Ada", "Grace"]) {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks/{}/claim?actor={}", board_id, task_id, agent))
+            .header(auth.clone())
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+    }
+
+    // Public, like the task list
+    let resp = client.get(format!("/api/v1/boards/{}/claims?group_by=agent", board_id)).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let claims: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(claims["total_claimed"], 3);
+    assert_eq!(claims["agents"], 2);
+    let groups = claims["groups"].as_array().unwrap();
+    assert_eq!(groups[0]["agent"], "Ada");
+    assert_eq!(groups[0]["count"], 2);
+    let ada_titles: Vec<&str> = groups[0]["tasks"].as_array().unwrap().iter().map(|t| t["title"].as_str().unwrap()).collect();
+    assert_eq!(ada_titles, vec!["One", "Two"]);
+    assert_eq!(groups[0]["tasks"][0]["column_name"], "To Do");
+    assert!(groups[0]["tasks"][0]["claim_age_seconds"].as_i64().unwrap() >= 0);
+    assert_eq!(groups[1]["agent"], "Grace");
+
+    // Released and archived tasks drop out
+    client
+        .post(format!("/api/v1/boards/{}/tasks/{}/release", board_id, task_ids[2]))
+        .header(auth.clone())
+        .dispatch();
+    client
+        .post(format!("/api/v1/boards/{}/tasks/{}/archive", board_id, task_ids[1]))
+        .header(auth.clone())
+        .dispatch();
+    let claims: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/claims", board_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(claims["total_claimed"], 1);
+    assert_eq!(claims["groups"][0]["agent"], "Ada");
+
+    let resp = client.get(format!("/api/v1/boards/{}/claims?group_by=column", board_id)).dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let resp = client.get("/api/v1/boards/nonexistent/claims").dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[test]
+fn test_http_move_reports_all_policy_violations() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Policy HTTP Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    let resp = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"enforce_dependencies": true}"#)
+        .dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(board["enforce_dependencies"], true);
+    let done_col = board["columns"][2]["id"].as_str().unwrap().to_string();
+
+    let mut ids = Vec::new();
+    for title in ["Blocker", "Blocked", "Filler"] {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "{}"}}"#, title))
+            .dispatch();
+        let task: serde_json::Value = resp.into_json().unwrap();
+        ids.push(task["id"].as_str().unwrap().to_string());
+    }
+    client
+        .post(format!("/api/v1/boards/{}/dependencies", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(format!(r#"{{"blocker_task_id": "{}", "blocked_task_id": "{}"}}"#, ids[0], ids[1]))
+        .dispatch();
+    // Fill Done to its WIP limit
+    client
+        .patch(format!("/api/v1/boards/{}/columns/{}", board_id, done_col))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"wip_limit": 1}"#)
+        .dispatch();
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}", board_id, ids[2], done_col))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Both policies reported in one response
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}", board_id, ids[1], done_col))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["code"], "WIP_LIMIT_EXCEEDED");
+    assert_eq!(body["codes"], serde_json::json!(["WIP_LIMIT_EXCEEDED", "BLOCKED_BY_DEPENDENCY"]));
+    assert_eq!(body["violations"].as_array().unwrap().len(), 2);
+
+    // Reorder into the column runs the same validation
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/reorder", board_id, ids[1]))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(format!(r#"{{"position": 0, "column_id": "{}"}}"#, done_col))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["codes"].as_array().unwrap().len(), 2);
+
+    // A layout snapshot overfilling two columns lists both, but the code once
+    let doing_col = board["columns"][1]["id"].as_str().unwrap().to_string();
+    client
+        .patch(format!("/api/v1/boards/{}/columns/{}", board_id, doing_col))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"wip_limit": 1}"#)
+        .dispatch();
+    let mut extra = Vec::new();
+    for title in ["First", "Second"] {
+        let task: serde_json::Value = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "{}"}}"#, title))
+            .dispatch()
+            .into_json()
+            .unwrap();
+        extra.push(task["id"].as_str().unwrap().to_string());
+    }
+    let layout: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/layout", board_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let resp = client
+        .put(format!("/api/v1/boards/{}/layout", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(
+            serde_json::json!({
+                "version": layout["version"],
+                "columns": {&doing_col: &extra, &done_col: [&ids[1], &ids[0]]},
+            })
+            .to_string(),
+        )
+        .dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["codes"], serde_json::json!(["WIP_LIMIT_EXCEEDED"]));
+    assert_eq!(body["violations"].as_array().unwrap().len(), 2);
+    assert!(body["error"].as_str().unwrap().starts_with("2 policy violations: Column"));
+
+    // Raising the WIP limit leaves only the dependency
+    client
+        .patch(format!("/api/v1/boards/{}/columns/{}", board_id, done_col))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"wip_limit": 5}"#)
+        .dispatch();
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}", board_id, ids[1], done_col))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["codes"], serde_json::json!(["BLOCKED_BY_DEPENDENCY"]));
+
+    // PATCH with a new column_id runs the same validation
+    let resp = client
+        .patch(format!("/api/v1/boards/{}/tasks/{}", board_id, ids[1]))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(format!(r#"{{"column_id": "{}"}}"#, done_col))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["codes"], serde_json::json!(["BLOCKED_BY_DEPENDENCY"]));
+
+    // So does a batch move, which fails as a whole: the unblocked task stays put
+    let first_col = board["columns"][0]["id"].as_str().unwrap().to_string();
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/batch", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(
+            serde_json::json!({
+                "operations": [{"action": "move", "task_ids": [&extra[0], &ids[1]], "column_id": &done_col}],
+            })
+            .to_string(),
+        )
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["failed"], 1);
+    assert!(body["results"][0]["error"].as_str().unwrap().starts_with("Task is blocked by 1 unfinished task(s)"));
+    let task: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/tasks/{}", board_id, extra[0]))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(task["column_id"], first_col.as_str());
+
+    // A batch move counts its own tasks against the WIP limit
+    client
+        .patch(format!("/api/v1/boards/{}/columns/{}", board_id, doing_col))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"wip_limit": 1}"#)
+        .dispatch();
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/batch", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(
+            serde_json::json!({
+                "operations": [{"action": "move", "task_ids": &extra, "column_id": &doing_col}],
+            })
+            .to_string(),
+        )
+        .dispatch();
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["failed"], 1);
+    assert!(body["results"][0]["error"].as_str().unwrap().contains("WIP limit of 1"));
+
+    // Finishing the blocker clears it
+    client
+        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}", board_id, ids[0], done_col))
+        .header(auth.clone())
+        .dispatch();
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}", board_id, ids[1], done_col))
+        .header(auth)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+}
+
+#[test]
+fn test_http_apply_board_layout() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Layout Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    let mut ids = Vec::new();
+    for title in ["A", "B", "C"] {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "{}"}}"#, title))
+            .dispatch();
+        let task: serde_json::Value = resp.into_json().unwrap();
+        ids.push(task["id"].as_str().unwrap().to_string());
+    }
+
+    let resp = client.get(format!("/api/v1/boards/{}/layout", board_id)).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let layout: serde_json::Value = resp.into_json().unwrap();
+    let version = layout["version"].as_str().unwrap().to_string();
+    let todo = layout["columns"][0]["column_id"].as_str().unwrap().to_string();
+    let doing = layout["columns"][1]["column_id"].as_str().unwrap().to_string();
+    let done = layout["columns"][2]["column_id"].as_str().unwrap().to_string();
+    assert_eq!(layout["columns"][0]["task_ids"].as_array().unwrap().len(), 3);
+
+    // C to the top of To Do, A into Done, B stays (unlisted) in To Do after C
+    let snapshot = format!(
+        r#"{{"version": "{}", "columns": {{"{}": ["{}"], "{}": ["{}"]}}}}"#,
+        version, todo, ids[2], done, ids[0]
+    );
+    let resp = client
+        .put(format!("/api/v1/boards/{}/layout", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(snapshot.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let applied: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(applied["columns"][0]["task_ids"], serde_json::json!([ids[2], ids[1]]));
+    assert_eq!(applied["columns"][2]["task_ids"], serde_json::json!([ids[0]]));
+    assert_eq!(applied["changed"], 2);
+    assert_ne!(applied["version"], version.as_str());
+
+    let resp = client.get(format!("/api/v1/boards/{}/tasks/{}", board_id, ids[0])).dispatch();
+    let task: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(task["column_id"], done.as_str());
+    assert!(!task["completed_at"].is_null());
+
+    // Replaying the stale snapshot is rejected
+    let resp = client
+        .put(format!("/api/v1/boards/{}/layout", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(snapshot)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["code"], "VERSION_CONFLICT");
+
+    // Policy violations roll back the whole snapshot
+    client
+        .patch(format!("/api/v1/boards/{}/columns/{}", board_id, doing))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"wip_limit": 1}"#)
+        .dispatch();
+    let version = applied["version"].as_str().unwrap();
+    let resp = client
+        .put(format!("/api/v1/boards/{}/layout", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(format!(
+            r#"{{"version": "{}", "columns": {{"{}": ["{}", "{}"]}}}}"#,
+            version, doing, ids[1], ids[2]
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["codes"], serde_json::json!(["WIP_LIMIT_EXCEEDED"]));
+    let resp = client.get(format!("/api/v1/boards/{}/layout", board_id)).dispatch();
+    let layout: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(layout["version"], version);
+
+    let resp = client
+        .put(format!("/api/v1/boards/{}/layout", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(format!(
+            r#"{{"version": "{}", "columns": {{"{}": ["{}"], "{}": ["{}"]}}}}"#,
+            version, todo, ids[1], doing, ids[1]
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["code"], "DUPLICATE_TASK");
+}
+
+// ============ Edit Locks ============
+
+#[test]
+fn test_http_task_edit_locks() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Lock Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Shared card"}"#)
+        .dispatch();
+    let task: serde_json::Value = resp.into_json().unwrap();
+    let task_id = task["id"].as_str().unwrap().to_string();
+    let lock_url = format!("/api/v1/boards/{}/tasks/{}/lock", board_id, task_id);
+    let patch = |who: &str| {
+        client
+            .patch(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"description": "edited by {}", "actor_name": "{}"}}"#, who, who))
+            .dispatch()
+            .status()
+    };
+
+    // Anonymous locks would be meaningless
+    let resp = client.post(&lock_url).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "ACTOR_REQUIRED");
+
+    let resp = client
+        .post(format!("{}?actor=Alice&ttl=60", lock_url))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let lock: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(lock["locked_by"], "Alice");
+    let ttl = lock["ttl_seconds"].as_i64().unwrap();
+    assert!(ttl > 0 && ttl <= 60);
+
+    // Someone else can neither take the lock nor edit the card
+    let resp = client.post(format!("{}?actor=Bob", lock_url)).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "TASK_LOCKED");
+    assert_eq!(patch("Bob"), Status::Conflict);
+    assert_eq!(patch("Alice"), Status::Ok);
+
+    // Locks are independent of claims
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/claim?actor=Bob", board_id, task_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Holder renews; lock is visible publicly
+    let resp = client.post(format!("{}?actor=Alice", lock_url)).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.get(&lock_url).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let lock: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(lock["locked_by"], "Alice");
+
+    // Only the holder can release
+    let resp = client.delete(format!("{}?actor=Bob", lock_url)).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+    let resp = client.delete(format!("{}?actor=Alice", lock_url)).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.get(&lock_url).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "NOT_LOCKED");
+
+    // Expired locks are ignored and can be taken over
+    let resp = client
+        .post(format!("{}?actor=Alice&ttl=1", lock_url))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    std::thread::sleep(std::time::Duration::from_millis(2100));
+    assert_eq!(patch("Bob"), Status::Ok);
+    let resp = client.post(format!("{}?actor=Bob", lock_url)).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let lock: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(lock["locked_by"], "Bob");
+}
+
+// ============ Comments ============
+
+#[test]
+fn test_http_comments() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Comment Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    // Create task
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Commentable Task"}"#)
+        .dispatch();
+    let task: serde_json::Value = resp.into_json().unwrap();
+    let task_id = task["id"].as_str().unwrap();
+
+    // Post a comment
+    let resp = client
+        .post(format!(
+            "/api/v1/boards/{}/tasks/{}/comment",
+            board_id, task_id
+        ))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "Hello from tests!", "actor_name": "TestBot"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["event_type"], "comment");
+    assert_eq!(body["actor"], "TestBot");
+
+    // Empty comment rejected
+    let resp = client
+        .post(format!(
+            "/api/v1/boards/{}/tasks/{}/comment",
+            board_id, task_id
+        ))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": ""}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+
+    // Read events (no auth needed)
+    let resp = client
+        .get(format!(
+            "/api/v1/boards/{}/tasks/{}/events",
+            board_id, task_id
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let events: serde_json::Value = resp.into_json().unwrap();
+    let events_arr = events.as_array().unwrap();
+    // Should have at least: created + comment
+    let comment_events: Vec<_> = events_arr
+        .iter()
+        .filter(|e| e["event_type"] == "comment")
+        .collect();
+    assert_eq!(comment_events.len(), 1);
+}
+
+#[test]
+fn test_http_comment_pressure() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Chatty Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Busy Task"}"#)
+        .dispatch();
+    let task: serde_json::Value = resp.into_json().unwrap();
+    let task_id = task["id"].as_str().unwrap();
+    let task_url = format!("/api/v1/boards/{}/tasks/{}", board_id, task_id);
+    let comment = |message: &str| -> serde_json::Value {
+        client
+            .post(format!("{}/comment", task_url))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(serde_json::json!({"message": message, "actor_name": "agent-1"}).to_string())
+            .dispatch()
+            .into_json()
+            .unwrap()
+    };
+
+    let resp = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"comment_pressure_threshold": -1}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let resp = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"comment_pressure_threshold": 3}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let board: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(board["comment_pressure_threshold"], 3);
+
+    // At the threshold there is no hint; past it, the comment and the task carry one
+    for i in 1..=3 {
+        assert!(comment(&format!("step {}", i)).get("comment_pressure").is_none());
+    }
+    let posted = comment("step 4");
+    assert_eq!(posted["comment_pressure"]["comment_count"], 4);
+    assert_eq!(posted["comment_pressure"]["threshold"], 3);
+    assert!(posted["comment_pressure"]["hint"].as_str().unwrap().contains("/comments/collapse"));
+    comment("step 5");
+    let task: serde_json::Value = client.get(&task_url).dispatch().into_json().unwrap();
+    assert_eq!(task["comment_pressure"]["comment_count"], 5);
+
+    // Collapse all but the newest two into a transcript
+    let resp = client
+        .post(format!("{}/comments/collapse", task_url))
+        .header(ContentType::JSON)
+        .body(r#"{"keep": 2}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let resp = client
+        .post(format!("{}/comments/collapse", task_url))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"keep": 2, "actor_name": "summarizer"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let collapsed: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(collapsed["collapsed"], 3);
+    assert_eq!(collapsed["remaining"], 2);
+    assert_eq!(collapsed["transcript"]["comment_count"], 3);
+    assert_eq!(collapsed["transcript"]["created_by"], "summarizer");
+
+    let task: serde_json::Value = client.get(&task_url).dispatch().into_json().unwrap();
+    assert_eq!(task["comment_count"], 2);
+    assert!(task.get("comment_pressure").is_none());
+    let events: serde_json::Value = client.get(format!("{}/events", task_url)).dispatch().into_json().unwrap();
+    let messages: Vec<_> = events
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|e| e["event_type"] == "comment")
+        .map(|e| e["data"]["message"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(messages, vec!["step 4", "step 5"]);
+
+    let transcripts: serde_json::Value =
+        client.get(format!("{}/transcripts", task_url)).dispatch().into_json().unwrap();
+    assert_eq!(transcripts.as_array().unwrap().len(), 1);
+    let resp = client.get(transcripts[0]["url"].as_str().unwrap()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(resp.content_type(), Some(ContentType::Plain));
+    let text = resp.into_string().unwrap();
+    assert!(text.contains("agent-1:\nstep 1"));
+    assert!(text.contains("step 3"));
+    assert!(!text.contains("step 4"));
+
+    // Nothing older than the kept comments is left
+    let resp = client
+        .post(format!("{}/comments/collapse", task_url))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"keep": 2}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "NOTHING_TO_COLLAPSE");
+}
+
+#[test]
+fn test_http_edit_and_delete_comments() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Editable Comments");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let task: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Typo magnet"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let task_id = task["id"].as_str().unwrap();
+    let comment: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "Ping @Sam about teh fix", "actor_name": "Nanook"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let comment_url = format!("/api/v1/boards/{}/tasks/{}/comments/{}", board_id, task_id, comment["id"].as_str().unwrap());
+
+    let resp = client
+        .patch(&comment_url)
+        .header(ContentType::JSON)
+        .body(r#"{"message": "nope"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let resp = client
+        .patch(format!("/api/v1/boards/{}/tasks/{}/comments/missing", board_id, task_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "nope"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    let resp = client
+        .patch(&comment_url)
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "Ping @Alex about the fix", "actor_name": "Editor"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let edited: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(edited["actor"], "Nanook");
+    assert_eq!(edited["data"]["message"], "Ping @Alex about the fix");
+    assert_eq!(edited["data"]["mentions"], serde_json::json!(["Alex"]));
+    assert_eq!(edited["data"]["edited_by"], "Editor");
+    assert!(edited["data"]["edited_at"].is_string());
+    assert_eq!(edited["created_at"], comment["created_at"]);
+
+    let events: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/tasks/{}/events", board_id, task_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let comments: Vec<&serde_json::Value> =
+        events.as_array().unwrap().iter().filter(|e| e["event_type"] == "comment").collect();
+    assert_eq!(comments.len(), 1);
+    assert_eq!(comments[0]["data"]["message"], "Ping @Alex about the fix");
+    assert!(events.as_array().unwrap().iter().any(|e| e["event_type"] == "comment_edited"));
+
+    let history_url = format!("{}/history", comment_url);
+    let history: serde_json::Value = client.get(&history_url).dispatch().into_json().unwrap();
+    assert_eq!(history["deleted"], false);
+    assert_eq!(history["message"], "Ping @Alex about the fix");
+    assert_eq!(history["revisions"][0]["message"], "Ping @Sam about teh fix");
+    assert_eq!(history["revisions"][0]["change"], "edited");
+    assert_eq!(history["revisions"][0]["changed_by"], "Editor");
+
+    // Deleted comments leave the thread but keep their history
+    let resp = client.delete(format!("{}?actor=Cleaner", comment_url)).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let task: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(task["comment_count"], 0);
+    let history: serde_json::Value = client.get(&history_url).dispatch().into_json().unwrap();
+    assert_eq!(history["deleted"], true);
+    assert!(history["message"].is_null());
+    let revisions = history["revisions"].as_array().unwrap();
+    assert_eq!(revisions.len(), 2);
+    assert_eq!(revisions[1]["change"], "deleted");
+    assert_eq!(revisions[1]["message"], "Ping @Alex about the fix");
+    assert_eq!(revisions[1]["changed_by"], "Cleaner");
+    let resp = client.delete(&comment_url).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[test]
+fn test_http_task_watchers() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Watched Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let mut task_ids = Vec::new();
+    for title in ["Watched", "Ignored"] {
+        let task: serde_json::Value = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "{}"}}"#, title))
+            .dispatch()
+            .into_json()
+            .unwrap();
+        assert_eq!(task["watchers"], serde_json::json!([]));
+        task_ids.push(task["id"].as_str().unwrap().to_string());
+    }
+    let watch_url = format!("/api/v1/boards/{}/tasks/{}/watch", board_id, task_ids[0]);
+
+    let resp = client.post(format!("{}?name=Nanook", watch_url)).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let resp = client.post(&watch_url).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+
+    client.post(format!("{}?name=Nanook", watch_url)).header(auth.clone()).dispatch();
+    client.post(&watch_url).header(auth.clone()).header(Header::new("X-Actor", "Sam")).dispatch();
+    // Watching again (any case) changes nothing
+    let resp = client.post(format!("{}?name=nanook", watch_url)).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["watchers"], serde_json::json!(["Nanook", "Sam"]));
+
+    let task: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/tasks/{}", board_id, task_ids[0]))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(task["watchers"], serde_json::json!(["Nanook", "Sam"]));
+
+    for task_id in &task_ids {
+        client
+            .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(r#"{"message": "update", "actor_name": "Other"}"#)
+            .dispatch();
+    }
+    let activity: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/activity?watching=NANOOK", board_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let items = activity.as_array().unwrap();
+    assert!(!items.is_empty());
+    assert!(items.iter().all(|i| i["task_id"] == task_ids[0].as_str()));
+
+    let resp = client.delete(format!("{}?name=Sam", watch_url)).header(auth.clone()).dispatch();
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["watchers"], serde_json::json!(["Nanook"]));
+    let resp = client.delete(format!("{}?name=Sam", watch_url)).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/missing/watch?name=Sam", board_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+/// A `multipart/form-data` body with one `file` field, plus its content type.
+fn multipart_file(filename: &str, content_type: &str, contents: &[u8]) -> (ContentType, Vec<u8>) {
+    let boundary = "kanban-test-boundary";
+    let mut body = format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+        boundary, filename, content_type
+    )
+    .into_bytes();
+    body.extend_from_slice(contents);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    (ContentType::new("multipart", "form-data").with_params(("boundary", boundary)), body)
+}
+
+#[test]
+fn test_http_attachments() {
+    // Off unless the feature flag is on
+    let client = test_client();
+    let (board_id, _) = create_test_board(&client, "No Attachments");
+    let resp = client.get(format!("/api/v1/boards/{}/tasks/x/attachments", board_id)).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "FEATURE_DISABLED");
+
+    let client = test_client_with_features(
+        kanban::features::FeatureFlags::default().with(kanban::features::ATTACHMENTS),
+    );
+    let (board_id, manage_key) = create_test_board(&client, "Attachment Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let task: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Has files"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let task_id = task["id"].as_str().unwrap();
+    assert_eq!(task["attachment_count"], 0);
+    let attachments_url = format!("/api/v1/boards/{}/tasks/{}/attachments", board_id, task_id);
+    let upload = |filename: &str, content_type: &str, contents: &[u8]| {
+        let (multipart, body) = multipart_file(filename, content_type, contents);
+        client.post(&attachments_url).header(multipart).header(auth.clone()).body(body).dispatch()
+    };
+
+    let (multipart, body) = multipart_file("notes.txt", "text/plain", b"hello");
+    let resp = client.post(&attachments_url).header(multipart).body(body).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    let resp = upload("../../notes.txt", "text/plain", b"release notes");
+    assert_eq!(resp.status(), Status::Ok);
+    let attachment: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(attachment["filename"], "notes.txt");
+    assert_eq!(attachment["content_type"], "text/plain");
+    assert_eq!(attachment["size_bytes"], 13);
+    assert_eq!(attachment["sha256"].as_str().unwrap().len(), 64);
+
+    // Size and type limits
+    let resp = upload("big.txt", "text/plain", &[b'x'; 2048]);
+    assert_eq!(resp.status(), Status::PayloadTooLarge);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "ATTACHMENT_TOO_LARGE");
+    let resp = upload("page.html", "text/html", b"<script>alert(1)</script>");
+    assert_eq!(resp.status(), Status::UnsupportedMediaType);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "ATTACHMENT_TYPE_NOT_ALLOWED");
+
+    // Counted on the task, listed and downloadable without auth
+    let task: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(task["attachment_count"], 1);
+    let list: serde_json::Value = client.get(&attachments_url).dispatch().into_json().unwrap();
+    assert_eq!(list.as_array().unwrap().len(), 1);
+    let resp = client.get(attachment["url"].as_str().unwrap()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(resp.content_type(), Some(ContentType::Plain));
+    assert_eq!(
+        resp.headers().get_one("Content-Disposition"),
+        Some("attachment; filename=\"notes.txt\"")
+    );
+    assert_eq!(resp.into_string().unwrap(), "release notes");
+
+    let events: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/tasks/{}/events", board_id, task_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert!(events.as_array().unwrap().iter().any(|e| e["event_type"] == "attachment.added"));
+
+    let resp = client.delete(attachment["url"].as_str().unwrap()).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.get(attachment["url"].as_str().unwrap()).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+    let list: serde_json::Value = client.get(&attachments_url).dispatch().into_json().unwrap();
+    assert!(list.as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_http_attachment_files_kept_until_trash_purged() {
+    let client = test_client_with_features(
+        kanban::features::FeatureFlags::default().with(kanban::features::ATTACHMENTS),
+    );
+    let (board_id, manage_key) = create_test_board(&client, "Trashed Files");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let task: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Has a file"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let task_id = task["id"].as_str().unwrap();
+    let (multipart, body) = multipart_file("notes.txt", "text/plain", b"keep me");
+    let attachment: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/attachments", board_id, task_id))
+        .header(multipart)
+        .header(auth.clone())
+        .body(body)
+        .dispatch()
+        .into_json()
+        .unwrap();
+
+    let config = client.rocket().state::<kanban::attachments::AttachmentConfig>().unwrap();
+    let kanban::attachments::AttachmentStore::Disk { dir } = &config.store else {
+        panic!("tests store attachments on disk");
+    };
+    let file = dir.join(format!("{}/{}/{}", board_id, task_id, attachment["id"].as_str().unwrap()));
+    assert!(file.exists());
+
+    let resp = client
+        .delete(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // In the trash the row and file stay put
+    let pool = client.rocket().state::<kanban::db::DbPool>().unwrap();
+    let runtime = rocket::tokio::runtime::Runtime::new().unwrap();
+    let outbound = kanban::url_policy::OutboundClient::default();
+    let removed = runtime.block_on(kanban::attachments::sweep(pool, config, &outbound)).unwrap();
+    assert_eq!(removed, 0);
+    assert!(file.exists());
+    let rows: i64 = pool
+        .get()
+        .unwrap()
+        .query_row("SELECT COUNT(*) FROM attachments WHERE task_id = ?1", [task_id], |row| row.get(0))
+        .unwrap();
+    assert_eq!(rows, 1);
+
+    // Once the trash is purged the sweep removes both
+    let conn = pool.get().unwrap();
+    conn.execute(
+        "UPDATE task_trash SET deleted_at = datetime('now', '-40 days') WHERE task_id = ?1",
+        [task_id],
+    )
+    .unwrap();
+    assert_eq!(kanban::trash::purge_expired(&conn, &kanban::trash::TrashConfig::default()).unwrap(), 1);
+    drop(conn);
+    let removed = runtime.block_on(kanban::attachments::sweep(pool, config, &outbound)).unwrap();
+    assert_eq!(removed, 1);
+    assert!(!file.exists());
+    let rows: i64 = pool
+        .get()
+        .unwrap()
+        .query_row("SELECT COUNT(*) FROM attachments WHERE task_id = ?1", [task_id], |row| row.get(0))
+        .unwrap();
+    assert_eq!(rows, 0);
+}
+
+#[test]
+fn test_http_comment_attachments() {
+    let client = test_client_with_features(
+        kanban::features::FeatureFlags::default().with(kanban::features::ATTACHMENTS),
+    );
+    let (board_id, manage_key) = create_test_board(&client, "Bug Reports");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let mut task_ids = Vec::new();
+    for title in ["Broken layout", "Other"] {
+        let task: serde_json::Value = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "{}"}}"#, title))
+            .dispatch()
+            .into_json()
+            .unwrap();
+        task_ids.push(task["id"].as_str().unwrap().to_string());
+    }
+    let (multipart, body) = multipart_file("screenshot.png", "image/png", b"\x89PNG fake");
+    let screenshot: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/attachments", board_id, task_ids[0]))
+        .header(multipart)
+        .header(auth.clone())
+        .body(body)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let screenshot_id = screenshot["id"].as_str().unwrap();
+
+    // Only the task's own attachments can be referenced
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_ids[1]))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(format!(r#"{{"message": "See this", "attachment_ids": ["{}"]}}"#, screenshot_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "INVALID_ATTACHMENT");
+
+    // A screenshot alone is a valid comment
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_ids[0]))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(format!(r#"{{"attachment_ids": ["{}", "{}"]}}"#, screenshot_id, screenshot_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let comment: serde_json::Value = resp.into_json().unwrap();
+    let attached = comment["data"]["attachments"].as_array().unwrap();
+    assert_eq!(attached.len(), 1);
+    assert_eq!(attached[0]["filename"], "screenshot.png");
+    assert_eq!(attached[0]["content_type"], "image/png");
+    assert_eq!(attached[0]["url"], screenshot["url"]);
+
+    let events: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/tasks/{}/events", board_id, task_ids[0]))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let logged = events.as_array().unwrap().iter().find(|e| e["event_type"] == "comment").unwrap();
+    assert_eq!(logged["data"]["attachments"][0]["id"], screenshot_id);
+
+    let activity: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/activity", board_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let item = activity.as_array().unwrap().iter().find(|e| e["event_type"] == "comment").unwrap();
+    assert_eq!(item["recent_comments"][0]["attachments"][0]["id"], screenshot_id);
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_ids[0]))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "", "attachment_ids": []}"#)
+        .dispatch();
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "EMPTY_MESSAGE");
+}
+
+// ============ Archive / Unarchive ============
+
+#[test]
+fn test_http_archive_board() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Archive HTTP Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    // Archive
+    let resp = client
+        .post(format!("/api/v1/boards/{}/archive", board_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Double-archive should conflict
+    let resp = client
+        .post(format!("/api/v1/boards/{}/archive", board_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+
+    // Write to archived board should fail (409 Conflict — board is archived)
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Should Fail"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+
+    // Unarchive
+    let resp = client
+        .post(format!("/api/v1/boards/{}/unarchive", board_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Now writing should work again
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Post-unarchive task"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+}
+
+#[test]
+fn test_http_rotate_manage_key() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Rotating Board");
+    let rotate_url = format!("/api/v1/boards/{}/rotate-key", board_id);
+
+    let resp = client
+        .post(&rotate_url)
+        .header(Header::new("Authorization", "Bearer kb_wrong"))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    let resp = client
+        .post(&rotate_url)
+        .header(Header::new("Authorization", format!("Bearer {}", manage_key)))
+        .header(Header::new("X-Actor", "Ops"))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let rotated: serde_json::Value = resp.into_json().unwrap();
+    let new_key = rotated["manage_key"].as_str().unwrap();
+    assert!(new_key.starts_with("kb_"));
+    assert_ne!(new_key, manage_key);
+
+    // The old key is dead, the new one works
+    let resp = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {}", manage_key)))
+        .body(r#"{"name": "Hijacked"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {}", new_key)))
+        .body(r#"{"name": "Rotated Board"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let events: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/boards/{}/board-events", board_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(events[1]["event"], "board.key_rotated");
+    assert_eq!(events[1]["actor"], "Ops");
+    assert!(!events[1].to_string().contains(new_key));
+}
+
+/// Accept HTTP posts on a local port; each request's headers and body arrive on the channel.
+fn spawn_hook_receiver() -> (String, std::sync::mpsc::Receiver<(String, serde_json::Value)>) {
+    spawn_hook_responder(|_| String::new())
+}
+
+/// Like `spawn_hook_receiver`, answering each post with `respond(body)` as the response body.
+fn spawn_hook_responder(
+    respond: impl Fn(&serde_json::Value) -> String + Send + 'static,
+) -> (String, std::sync::mpsc::Receiver<(String, serde_json::Value)>) {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut headers = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                    break;
+                }
+                if let Some(len) = line.to_lowercase().strip_prefix("content-length:") {
+                    content_length = len.trim().parse().unwrap_or(0);
+                }
+                headers.push_str(&line);
+            }
+            let mut body = vec![0; content_length];
+            let _ = reader.read_exact(&mut body);
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+            let reply = respond(&body);
+            let _ = stream.write_all(
+                format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", reply.len(), reply)
+                    .as_bytes(),
+            );
+            let _ = tx.send((headers, body));
+        }
+    });
+    (url, rx)
+}
+
+#[test]
+fn test_http_board_lifecycle_hooks() {
+    let (url, hooks_rx) = spawn_hook_receiver();
+    let hooks = kanban::lifecycle::LifecycleHooks::new(vec![url], "hook-secret").with_retry_policy(fast_retries());
+    let client = test_client_with_hooks(hooks);
+    let wait = || hooks_rx.recv_timeout(Duration::from_secs(5)).expect("hook should be delivered");
+
+    let resp = client
+        .post("/api/v1/boards")
+        .header(ContentType::JSON)
+        .header(Header::new("X-Actor", "Agent-7"))
+        .body(r#"{"name": "Provisioned", "description": "needs a channel", "columns": ["Todo", "Done"]}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let board_id = board["id"].as_str().unwrap();
+    let auth = Header::new("Authorization", format!("Bearer {}", board["manage_key"].as_str().unwrap()));
+
+    let (headers, payload) = wait();
+    assert!(headers.to_lowercase().contains("x-kanban-event: board.created"));
+    assert!(headers.to_lowercase().contains("x-kanban-signature: sha256="));
+    assert_eq!(payload["event"], "board.created");
+    assert_eq!(payload["board_id"], board_id);
+    assert_eq!(payload["data"]["board"]["name"], "Provisioned");
+    assert_eq!(payload["data"]["board"]["description"], "needs a channel");
+    assert_eq!(payload["data"]["board"]["columns"], serde_json::json!(["Todo", "Done"]));
+    assert_eq!(payload["data"]["owner"]["name"], "Agent-7");
+    assert!(payload["data"].get("manage_key").is_none());
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/archive?actor=Janitor", board_id))
+        .header(auth)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let (_, payload) = wait();
+    assert_eq!(payload["event"], "board.archived");
+    assert_eq!(payload["data"]["board"]["archived"], true);
+    assert_eq!(payload["data"]["owner"]["name"], "Agent-7");
+    assert_eq!(payload["data"]["actor"], "Janitor");
+
+    // Imports create boards too; an anonymous board has no owner
+    let export: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/export", board_id))
+        .header(Header::new("Authorization", format!("Bearer {}", board["manage_key"].as_str().unwrap())))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let resp = client
+        .post("/api/v1/boards/import")
+        .header(ContentType::JSON)
+        .body(export.to_string())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let imported: serde_json::Value = resp.into_json().unwrap();
+
+    let (_, payload) = wait();
+    assert_eq!(payload["event"], "board.created");
+    assert_eq!(payload["board_id"], imported["id"]);
+    assert!(payload["data"]["owner"]["name"].is_null());
+}
+
+#[test]
+fn test_http_board_and_column_events() {
+    let (url, hooks_rx) = spawn_hook_receiver();
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Structure Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let resp = client
+        .post(format!("/api/v1/boards/{}/webhooks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(format!(
+            r#"{{"url": "{}", "events": ["board.updated", "board.archived", "column.created", "column.updated", "column.deleted", "column.reordered"]}}"#,
+            url
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/columns?actor=Architect", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"name": "Review"}"#)
+        .dispatch();
+    let column: serde_json::Value = resp.into_json().unwrap();
+    let column_id = column["id"].as_str().unwrap();
+    client
+        .patch(format!("/api/v1/boards/{}/columns/{}", board_id, column_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"name": "Code Review", "wip_limit": 2}"#)
+        .dispatch();
+    let board: serde_json::Value = client.get(format!("/api/v1/boards/{}", board_id)).dispatch().into_json().unwrap();
+    let mut order: Vec<String> = board["columns"].as_array().unwrap().iter().map(|c| c["id"].as_str().unwrap().to_string()).collect();
+    order.reverse();
+    client
+        .post(format!("/api/v1/boards/{}/columns/reorder", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(serde_json::json!({"column_ids": order}).to_string())
+        .dispatch();
+    client
+        .delete(format!("/api/v1/boards/{}/columns/{}", board_id, column_id))
+        .header(auth.clone())
+        .dispatch();
+    client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"name": "Renamed", "description": "new"}"#)
+        .dispatch();
+    // Not subscribed
+    client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Ignored"}"#)
+        .dispatch();
+    let resp = client.post(format!("/api/v1/boards/{}/archive", board_id)).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let mut delivered: Vec<serde_json::Value> = (0..6)
+        .map(|_| hooks_rx.recv_timeout(Duration::from_secs(5)).expect("webhook should be delivered").1)
+        .collect();
+    assert!(hooks_rx.recv_timeout(Duration::from_millis(300)).is_err(), "task events are filtered out");
+    delivered.sort_by_key(|p| p["event"].as_str().unwrap().to_string());
+    let names: Vec<&str> = delivered.iter().map(|p| p["event"].as_str().unwrap()).collect();
+    assert_eq!(
+        names,
+        vec!["board.archived", "board.updated", "column.created", "column.deleted", "column.reordered", "column.updated"]
+    );
+    assert_eq!(delivered[1]["data"]["fields"], serde_json::json!(["name", "description"]));
+    assert_eq!(delivered[1]["data"]["name"], "Renamed");
+    assert_eq!(delivered[2]["data"]["column_id"], column_id);
+    assert_eq!(delivered[2]["data"]["actor"], "Architect");
+    assert_eq!(delivered[3]["data"]["name"], "Code Review");
+    assert_eq!(delivered[4]["data"]["column_ids"], serde_json::json!(order));
+    assert_eq!(delivered[5]["data"]["fields"], serde_json::json!(["name", "wip_limit"]));
+
+    // Persisted, newest first; `after` pages forward
+    let events: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/boards/{}/board-events", board_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let names: Vec<&str> = events.iter().map(|e| e["event"].as_str().unwrap()).collect();
+    assert_eq!(
+        names,
+        vec!["board.archived", "board.updated", "column.deleted", "column.reordered", "column.updated", "column.created"]
+    );
+    assert_eq!(events[5]["actor"], "Architect");
+    let first_seq = events[5]["seq"].as_i64().unwrap();
+    let later: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/boards/{}/board-events?after={}&limit=2", board_id, first_seq))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let names: Vec<&str> = later.iter().map(|e| e["event"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["column.updated", "column.reordered"]);
+}
+
+#[test]
+fn test_http_validation_hook() {
+    // The policy service vetoes destructive titles and moves of frozen tasks
+    let (url, rx) = spawn_hook_responder(|payload| {
+        let task = &payload["task"];
+        if task["title"].as_str().unwrap_or("").contains("rm -rf") {
+            r#"{"allow": false, "reason": "destructive task"}"#.to_string()
+        } else if payload["action"] == "task.move" && task["labels"].as_array().is_some_and(|l| l.contains(&"frozen".into())) {
+            r#"{"allow": false}"#.to_string()
+        } else {
+            r#"{"allow": true}"#.to_string()
+        }
+    });
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Governed Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let board: serde_json::Value = client.get(format!("/api/v1/boards/{}", board_id)).dispatch().into_json().unwrap();
+    let done_col = board["columns"].as_array().unwrap().last().unwrap()["id"].as_str().unwrap().to_string();
+    let hook_url = format!("/api/v1/boards/{}/validation-hook", board_id);
+    let create = |title: &str, labels: serde_json::Value| {
+        client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(serde_json::json!({"title": title, "labels": labels, "actor_name": "agent-9"}).to_string())
+            .dispatch()
+    };
+
+    let resp = client.get(&hook_url).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let resp = client.get(&hook_url).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    // The secret is returned only when the hook is first set
+    let resp = client
+        .put(&hook_url)
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(serde_json::json!({"url": url}).to_string())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let hook: serde_json::Value = resp.into_json().unwrap();
+    assert!(hook["secret"].as_str().unwrap().starts_with("vhsec_"));
+    assert_eq!(hook["timeout_ms"], 2000);
+    assert_eq!(hook["fail_open"], false);
+    let hook: serde_json::Value = client
+        .put(&hook_url)
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(serde_json::json!({"url": url, "timeout_ms": 3000}).to_string())
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert!(hook.get("secret").is_none());
+    assert_eq!(hook["timeout_ms"], 3000);
+    let resp = client
+        .put(&hook_url)
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(serde_json::json!({"url": url, "timeout_ms": 60000}).to_string())
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+
+    // Allowed create: the service sees the proposed task, signed
+    let resp = create("Write docs", serde_json::json!(["frozen"]));
+    assert_eq!(resp.status(), Status::Ok);
+    let frozen: serde_json::Value = resp.into_json().unwrap();
+    let (headers, payload) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(headers.to_lowercase().contains("x-kanban-event: task.create"));
+    assert!(headers.to_lowercase().contains("x-kanban-signature: sha256="));
+    assert_eq!(payload["action"], "task.create");
+    assert_eq!(payload["board_id"], board_id.as_str());
+    assert_eq!(payload["actor"], "agent-9");
+    assert_eq!(payload["task"]["title"], "Write docs");
+
+    // Vetoed create: nothing is written
+    let resp = create("rm -rf /var/data", serde_json::json!([]));
+    assert_eq!(resp.status(), Status::Conflict);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "VALIDATION_REJECTED");
+    assert!(err["error"].as_str().unwrap().contains("destructive task"));
+    rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    let tasks: Vec<serde_json::Value> =
+        client.get(format!("/api/v1/boards/{}/tasks", board_id)).dispatch().into_json().unwrap();
+    assert_eq!(tasks.len(), 1);
+
+    // Vetoed moves, through both the move endpoint and PATCH
+    let frozen_id = frozen["id"].as_str().unwrap();
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}", board_id, frozen_id, done_col))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+    let (_, payload) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(payload["action"], "task.move");
+    assert_eq!(payload["to_column_id"], done_col.as_str());
+    assert_eq!(payload["task"]["id"], frozen_id);
+    let resp = client
+        .patch(format!("/api/v1/boards/{}/tasks/{}", board_id, frozen_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(serde_json::json!({"column_id": done_col}).to_string())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+    let task: serde_json::Value =
+        client.get(format!("/api/v1/boards/{}/tasks/{}", board_id, frozen_id)).dispatch().into_json().unwrap();
+    assert_eq!(task["column_id"], frozen["column_id"]);
+
+    // An unreachable service rejects writes unless the hook fails open
+    let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let dead_url = format!("http://{}/hook", closed.local_addr().unwrap());
+    drop(closed);
+    client
+        .put(&hook_url)
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(serde_json::json!({"url": dead_url}).to_string())
+        .dispatch();
+    let resp = create("Anything", serde_json::json!([]));
+    assert_eq!(resp.status(), Status::ServiceUnavailable);
+    assert_eq!(resp.into_json::<serde_json::Value>().unwrap()["code"], "VALIDATION_UNAVAILABLE");
+    client
+        .put(&hook_url)
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(serde_json::json!({"url": dead_url, "fail_open": true}).to_string())
+        .dispatch();
+    assert_eq!(create("Anything", serde_json::json!([])).status(), Status::Ok);
+
+    let resp = client.delete(&hook_url).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.get(&hook_url).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[test]
+fn test_http_validation_hook_bulk_paths() {
+    // Moves of the frozen task, destructive titles and restores are vetoed
+    let (url, _rx) = spawn_hook_responder(|payload| {
+        let task = &payload["task"];
+        let title = task["title"].as_str().unwrap_or("");
+        let vetoed = (payload["action"] == "task.move" && title == "Frozen")
+            || title.contains("rm -rf")
+            || payload["restored"] == true;
+        if vetoed {
+            r#"{"allow": false, "reason": "policy"}"#.to_string()
+        } else {
+            r#"{"allow": true}"#.to_string()
+        }
+    });
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Governed Bulk Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let board: serde_json::Value = client.get(format!("/api/v1/boards/{}", board_id)).dispatch().into_json().unwrap();
+    let done_col = board["columns"].as_array().unwrap().last().unwrap()["id"].as_str().unwrap().to_string();
+    let mut ids = Vec::new();
+    for title in ["Frozen", "Free"] {
+        let task: serde_json::Value = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(serde_json::json!({"title": title}).to_string())
+            .dispatch()
+            .into_json()
+            .unwrap();
+        ids.push(task["id"].as_str().unwrap().to_string());
+    }
+    client
+        .put(format!("/api/v1/boards/{}/validation-hook", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(serde_json::json!({"url": url}).to_string())
+        .dispatch();
+    let column_of = |task_id: &str| {
+        let task: serde_json::Value = client
+            .get(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
+            .dispatch()
+            .into_json()
+            .unwrap();
+        task["column_id"].as_str().unwrap().to_string()
+    };
+
+    // A vetoed task fails the whole batch move
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/batch", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(serde_json::json!({"operations": [
+            {"action": "move", "task_ids": &ids, "column_id": done_col},
+            {"action": "move", "task_ids": [&ids[1]], "column_id": done_col},
+        ]}).to_string())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["results"][0]["success"], false);
+    assert!(body["results"][0]["error"].as_str().unwrap().contains("validation hook"));
+    assert_eq!(body["results"][1]["success"], true);
+    assert_ne!(column_of(&ids[0]), done_col);
+
+    // And the whole layout update
+    let layout: serde_json::Value =
+        client.get(format!("/api/v1/boards/{}/layout", board_id)).dispatch().into_json().unwrap();
+    let resp = client
+        .put(format!("/api/v1/boards/{}/layout", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(serde_json::json!({"version": layout["version"], "columns": {&done_col: [&ids[0]]}}).to_string())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+    assert_eq!(resp.into_json::<serde_json::Value>().unwrap()["code"], "VALIDATION_REJECTED");
+    assert_ne!(column_of(&ids[0]), done_col);
+
+    // CSV imports report vetoed rows and create the rest
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/import", board_id))
+        .header(ContentType::CSV)
+        .header(auth.clone())
+        .body("title\nWrite docs\nrm -rf /\n")
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let report: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(report["succeeded"], 1);
+    assert_eq!(report["results"][1]["code"], "VALIDATION_REJECTED");
+
+    // Restoring from the trash is asked about as a create
+    let resp = client
+        .delete(format!("/api/v1/boards/{}/tasks/{}", board_id, ids[1]))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/restore", board_id, ids[1]))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+    let resp = client.get(format!("/api/v1/boards/{}/tasks/{}", board_id, ids[1])).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[test]
+fn test_http_delete_board() {
+    let (url, hooks_rx) = spawn_hook_receiver();
+    let hooks = kanban::lifecycle::LifecycleHooks::new(vec![url], "hook-secret").with_retry_policy(fast_retries());
+    let client = test_client_with_hooks(hooks);
+    let wait = || hooks_rx.recv_timeout(Duration::from_secs(5)).expect("hook should be delivered");
+    let (board_id, manage_key) = create_test_board(&client, "Doomed Board");
+    let (other_id, _) = create_test_board(&client, "Survivor");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    wait();
+    wait();
+
+    let mut ids = Vec::new();
+    for title in ["Blocker", "Blocked"] {
+        let task: serde_json::Value = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "{}"}}"#, title))
+            .dispatch()
+            .into_json()
+            .unwrap();
+        ids.push(task["id"].as_str().unwrap().to_string());
+    }
+    client
+        .post(format!("/api/v1/boards/{}/dependencies", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(format!(r#"{{"blocker_task_id": "{}", "blocked_task_id": "{}"}}"#, ids[0], ids[1]))
+        .dispatch();
+    client
+        .post(format!("/api/v1/boards/{}/webhooks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"url": "https://example.com/hook"}"#)
+        .dispatch();
+
+    // Manage key and the exact board name are both required
+    let resp = client.delete(format!("/api/v1/boards/{}?confirm=Doomed%20Board", board_id)).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let resp = client.delete(format!("/api/v1/boards/{}", board_id)).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["code"], "CONFIRMATION_REQUIRED");
+    let resp = client
+        .delete(format!("/api/v1/boards/{}?confirm=doomed%20board", board_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+
+    let resp = client
+        .delete(format!("/api/v1/boards/{}?confirm=Doomed%20Board&actor=Janitor", board_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["deleted"], true);
+    assert_eq!(body["tasks_deleted"], 2);
+    assert_eq!(body["columns_deleted"], 3);
+    assert_eq!(body["webhooks_deleted"], 1);
+
+    let (_, payload) = wait();
+    assert_eq!(payload["event"], "board.deleted");
+    assert_eq!(payload["board_id"], board_id.as_str());
+    assert_eq!(payload["data"]["board"]["name"], "Doomed Board");
+    assert_eq!(payload["data"]["board"]["task_count"], 2);
+    assert_eq!(payload["data"]["actor"], "Janitor");
+
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+    let resp = client.get(format!("/api/v1/boards/{}", other_id)).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let pool = client.rocket().state::<kanban::db::DbPool>().unwrap();
+    let conn = pool.get().unwrap();
+    for table in ["tasks", "columns", "webhooks", "task_dependencies"] {
+        let left: i64 = conn
+            .query_row(&format!("SELECT COUNT(*) FROM {} WHERE board_id = ?1", table), [&board_id], |row| row.get(0))
+            .unwrap();
+        assert_eq!(left, 0, "{} rows left behind", table);
+    }
+    let events: i64 = conn
+        .query_row("SELECT COUNT(*) FROM task_events WHERE task_id IN (?1, ?2)", [&ids[0], &ids[1]], |row| row.get(0))
+        .unwrap();
+    assert_eq!(events, 0);
+}
+
+#[test]
+fn test_http_merge_boards() {
+    let client = test_client();
+    let (target_id, target_key) = create_test_board(&client, "Main Board");
+    let (source_id, source_key) = create_test_board(&client, "Side Board");
+    let target_auth = Header::new("Authorization", format!("Bearer {}", target_key));
+    let source_auth = Header::new("Authorization", format!("Bearer {}", source_key));
+
+    let source: serde_json::Value = client.get(format!("/api/v1/boards/{}", source_id)).dispatch().into_json().unwrap();
+    let source_todo = source["columns"][0]["id"].as_str().unwrap().to_string();
+    client
+        .post(format!("/api/v1/boards/{}/columns", source_id))
+        .header(ContentType::JSON)
+        .header(source_auth.clone())
+        .body(r#"{"name": "Review"}"#)
+        .dispatch();
+    client
+        .post(format!("/api/v1/boards/{}/tasks", target_id))
+        .header(ContentType::JSON)
+        .header(target_auth.clone())
+        .body(r#"{"title": "Already here"}"#)
+        .dispatch();
+    let mut ids = Vec::new();
+    for (title, column) in [("Blocker", "To Do"), ("Blocked", "To Do"), ("Reviewing", "Review")] {
+        let source: serde_json::Value =
+            client.get(format!("/api/v1/boards/{}", source_id)).dispatch().into_json().unwrap();
+        let column_id = source["columns"].as_array().unwrap().iter().find(|c| c["name"] == column).unwrap()["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let task: serde_json::Value = client
+            .post(format!("/api/v1/boards/{}/tasks", source_id))
+            .header(ContentType::JSON)
+            .header(source_auth.clone())
+            .body(format!(r#"{{"title": "{}", "column_id": "{}"}}"#, title, column_id))
+            .dispatch()
+            .into_json()
+            .unwrap();
+        ids.push(task["id"].as_str().unwrap().to_string());
+    }
+    client
+        .post(format!("/api/v1/boards/{}/dependencies", source_id))
+        .header(ContentType::JSON)
+        .header(source_auth.clone())
+        .body(format!(r#"{{"blocker_task_id": "{}", "blocked_task_id": "{}"}}"#, ids[0], ids[1]))
+        .dispatch();
+
+    // Both manage keys are required, and a board cannot absorb itself
+    let merge = |auth: &Header<'static>, body: String| {
+        client
+            .post(format!("/api/v1/boards/{}/merge", target_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(body)
+            .dispatch()
+    };
+    let resp = merge(&target_auth, format!(r#"{{"source_board_id": "{}", "source_key": "wrong"}}"#, source_id));
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = merge(&source_auth, format!(r#"{{"source_board_id": "{}", "source_key": "{}"}}"#, source_id, source_key));
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = merge(&target_auth, format!(r#"{{"source_board_id": "{}", "source_key": "{}"}}"#, target_id, target_key));
+    assert_eq!(resp.status(), Status::BadRequest);
+    let resp = merge(
+        &target_auth,
+        format!(r#"{{"source_board_id": "{}", "source_key": "{}", "source_action": "shred"}}"#, source_id, source_key),
+    );
+    assert_eq!(resp.status(), Status::BadRequest);
+    let resp = merge(
+        &target_auth,
+        format!(
+            r#"{{"source_board_id": "{}", "source_key": "{}", "column_mapping": {{"To Do": "Nowhere"}}}}"#,
+            source_id, source_key
+        ),
+    );
+    assert_eq!(resp.status(), Status::BadRequest);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["code"], "INVALID_COLUMN");
+
+    // To Do maps explicitly onto In Progress; Review has no match and is created
+    let resp = merge(
+        &target_auth,
+        format!(
+            r#"{{"source_board_id": "{}", "source_key": "{}", "column_mapping": {{"{}": "in progress"}}, "actor_name": "Merger"}}"#,
+            source_id, source_key, source_todo
+        ),
+    );
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["tasks_moved"], 3);
+    assert_eq!(body["dependencies_moved"], 1);
+    assert_eq!(body["columns_created"], serde_json::json!(["Review"]));
+    assert_eq!(body["source_action"], "archived");
+    let mapping = body["column_mapping"].as_array().unwrap();
+    assert_eq!(mapping.len(), 4);
+    assert_eq!(mapping[0]["from_column"], "To Do");
+    assert_eq!(mapping[0]["to_column"], "In Progress");
+    assert_eq!(mapping[1]["to_column"], "In Progress");
+    assert_eq!(mapping[3]["to_column"], "Review");
+
+    let tasks: serde_json::Value =
+        client.get(format!("/api/v1/boards/{}/tasks", target_id)).dispatch().into_json().unwrap();
+    let tasks = tasks.as_array().unwrap();
+    assert_eq!(tasks.len(), 4);
+    let blocker = tasks.iter().find(|t| t["id"] == ids[0].as_str()).unwrap();
+    assert_eq!(blocker["column_name"], "In Progress");
+    assert_eq!(blocker["number"], 2);
+    let reviewing = tasks.iter().find(|t| t["id"] == ids[2].as_str()).unwrap();
+    assert_eq!(reviewing["column_name"], "Review");
+    assert_eq!(reviewing["number"], 4);
+
+    // History and dependencies come along
+    let events: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/tasks/{}/events", target_id, ids[0]))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let events = events.as_array().unwrap();
+    assert!(events.iter().any(|e| e["event_type"] == "created"));
+    let merged = events.iter().find(|e| e["event_type"] == "merged").unwrap();
+    assert_eq!(merged["actor"], "Merger");
+    assert_eq!(merged["data"]["from_board"], "Side Board");
+    let deps: serde_json::Value =
+        client.get(format!("/api/v1/boards/{}/dependencies", target_id)).dispatch().into_json().unwrap();
+    assert_eq!(deps.as_array().unwrap().len(), 1);
+
+    // The emptied source is archived
+    let source: serde_json::Value = client.get(format!("/api/v1/boards/{}", source_id)).dispatch().into_json().unwrap();
+    assert_eq!(source["archived"], true);
+    assert_eq!(source["task_count"], 0);
+
+    // source_action=delete removes the source board outright
+    let (other_id, other_key) = create_test_board(&client, "Scratch");
+    let resp = merge(
+        &target_auth,
+        format!(r#"{{"source_board_id": "{}", "source_key": "{}", "source_action": "delete"}}"#, other_id, other_key),
+    );
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["source_action"], "deleted");
+    assert_eq!(body["tasks_moved"], 0);
+    let resp = client.get(format!("/api/v1/boards/{}", other_id)).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+// ============ Search ============
+
+#[test]
+fn test_http_search_tasks() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Search HTTP Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    // Create a few tasks
+    for title in ["Fix login bug", "Add search feature", "Update docs"] {
+        client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "{}"}}"#, title))
+            .dispatch();
+    }
+
+    // Search for "login"
+    let resp = client
+        .get(format!(
+            "/api/v1/boards/{}/tasks/search?q=login",
+            board_id
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["total"], 1);
+    assert_eq!(body["tasks"].as_array().unwrap().len(), 1);
+
+    // Empty query rejected
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks/search?q=", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+}
+
+#[test]
+fn test_http_search_ranking_and_syntax() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "FTS Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    let mut ids = Vec::new();
+    for body in [
+        r#"{"title": "Refresh tokens", "description": "Rotate the authentication secret"}"#,
+        r#"{"title": "Authentication overhaul", "labels": ["security"]}"#,
+        r#"{"title": "Billing export", "description": "CSV for finance"}"#,
+    ] {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(body)
+            .dispatch();
+        let task: serde_json::Value = resp.into_json().unwrap();
+        ids.push(task["id"].as_str().unwrap().to_string());
+    }
+    client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, ids[2]))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "Finance wants quarterly totals", "actor_name": "Tester"}"#)
+        .dispatch();
+
+    let search = |q: &str| -> serde_json::Value {
+        let resp = client
+            .get(format!("/api/v1/boards/{}/tasks/search?q={}", board_id, q))
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok, "query {}", q);
+        resp.into_json().unwrap()
+    };
+
+    // Title hits outrank description hits
+    let body = search("authentication");
+    assert_eq!(body["total"], 2);
+    assert_eq!(body["tasks"][0]["id"], ids[1].as_str());
+
+    // Prefix, phrase, labels and comments
+    assert_eq!(search("auth*")["total"], 2);
+    assert_eq!(search("%22rotate%20the%22")["tasks"][0]["id"], ids[0].as_str());
+    assert_eq!(search("%22the%20rotate%22")["total"], 0);
+    assert_eq!(search("security")["tasks"][0]["id"], ids[1].as_str());
+    assert_eq!(search("quarterly")["tasks"][0]["id"], ids[2].as_str());
+
+    // Edits are re-indexed
+    client
+        .patch(format!("/api/v1/boards/{}/tasks/{}", board_id, ids[2]))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Invoice export"}"#)
+        .dispatch();
+    assert_eq!(search("billing")["total"], 0);
+    assert_eq!(search("invoice")["total"], 1);
+
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks/search?q=***", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+}
+
+#[test]
+fn test_http_search_field_scopes() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Scoped Search Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    let mut ids = Vec::new();
+    for body in [
+        r#"{"title": "Login page crash", "labels": ["bug"], "metadata": {"repo": "kanban", "urgent": true}}"#,
+        r#"{"title": "Docs", "description": "Explain login flow", "labels": ["docs"], "metadata": {"repo": "kanban"}}"#,
+        r#"{"title": "Login rate limits", "labels": ["bug"], "metadata": {"repo": "other"}}"#,
+    ] {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(body)
+            .dispatch();
+        let task: serde_json::Value = resp.into_json().unwrap();
+        ids.push(task["id"].as_str().unwrap().to_string());
+    }
+    client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, ids[2]))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "Seen in staging", "actor_name": "Tester"}"#)
+        .dispatch();
+
+    let search = |q: &str| -> serde_json::Value {
+        let resp = client
+            .get(format!("/api/v1/boards/{}/tasks/search?q={}", board_id, q))
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok, "query {}", q);
+        resp.into_json().unwrap()
+    };
+
+    assert_eq!(search("login")["total"], 3);
+    assert_eq!(search("title:login")["total"], 2);
+    let body = search("title:login%20label:bug%20meta.repo:kanban");
+    assert_eq!(body["total"], 1);
+    assert_eq!(body["tasks"][0]["id"], ids[0].as_str());
+
+    // Label and metadata scopes work without any text terms
+    assert_eq!(search("label:BUG")["total"], 2);
+    assert_eq!(search("meta.repo:kanban")["total"], 2);
+    assert_eq!(search("meta.urgent:true")["tasks"][0]["id"], ids[0].as_str());
+    assert_eq!(search("label:bu")["total"], 0);
+
+    // Comments can be searched explicitly or left out of free text
+    assert_eq!(search("comments:staging")["tasks"][0]["id"], ids[2].as_str());
+    assert_eq!(search("staging&comments=false")["total"], 0);
+    assert_eq!(search("description:login")["tasks"][0]["id"], ids[1].as_str());
+}
+
+// ============ Sampling ============
+
+#[test]
+fn test_http_sample_tasks() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Sample HTTP Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let done_col = board["columns"][2]["id"].as_str().unwrap().to_string();
+
+    let mut task_ids = Vec::new();
+    for i in 0..6 {
+        let actor = if i % 2 == 0 { "agent-a" } else { "agent-b" };
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "Task {}", "actor_name": "{}"}}"#, i, actor))
+            .dispatch();
+        let task: serde_json::Value = resp.into_json().unwrap();
+        task_ids.push(task["id"].as_str().unwrap().to_string());
+    }
+    // Complete the first three (moving into the last column marks them done)
+    for id in &task_ids[..3] {
+        client
+            .post(format!("/api/v1/boards/{}/tasks/{}/move/{}", board_id, id, done_col))
+            .header(auth.clone())
+            .dispatch();
+    }
+
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks/sample?n=2", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["matched"], 6);
+    assert_eq!(body["requested"], 2);
+    assert_eq!(body["tasks"].as_array().unwrap().len(), 2);
+
+    // Completed work by agent-a: tasks 0 and 2
+    let resp = client
+        .get(format!(
+            "/api/v1/boards/{}/tasks/sample?n=10&completed=true&agent=agent-a",
+            board_id
+        ))
+        .dispatch();
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["matched"], 2);
+    let tasks = body["tasks"].as_array().unwrap();
+    assert_eq!(tasks.len(), 2);
+    assert!(tasks.iter().all(|t| t["created_by"] == "agent-a" && !t["completed_at"].is_null()));
+}
+
+// ============ Export ============
+
+#[test]
+fn test_http_export_board() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Export Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    let mut ids = Vec::new();
+    for title in ["Blocker", "Blocked"] {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "{}", "labels": ["ops"], "actor_name": "Exporter"}}"#, title))
+            .dispatch();
+        let task: serde_json::Value = resp.into_json().unwrap();
+        ids.push(task["id"].as_str().unwrap().to_string());
+    }
+    client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, ids[0]))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "Backed up", "actor_name": "Exporter"}"#)
+        .dispatch();
+    client
+        .post(format!("/api/v1/boards/{}/dependencies", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(format!(r#"{{"blocker_task_id": "{}", "blocked_task_id": "{}", "note": "first"}}"#, ids[0], ids[1]))
+        .dispatch();
+    client
+        .post(format!("/api/v1/boards/{}/webhooks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"url": "https://example.com/hook?token=abc"}"#)
+        .dispatch();
+    client
+        .post(format!("/api/v1/boards/{}/webhooks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"url": "https://hooks.slack.com/services/T000/B000/XXXXSECRET", "kind": "slack"}"#)
+        .dispatch();
+
+    // Requires the manage key
+    let resp = client.get(format!("/api/v1/boards/{}/export", board_id)).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    let resp = client
+        .get(format!("/api/v1/boards/{}/export", board_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let disposition = resp.headers().get_one("Content-Disposition").unwrap().to_string();
+    assert!(disposition.contains(&format!("board-{}.json", board_id)));
+    let export: serde_json::Value = resp.into_json().unwrap();
+
+    assert_eq!(export["format"], "kanban-board-export");
+    assert_eq!(export["version"], 1);
+    assert_eq!(export["board"]["id"], board_id.as_str());
+    assert_eq!(export["board"]["name"], "Export Board");
+    assert_eq!(export["columns"].as_array().unwrap().len(), 3);
+    assert_eq!(export["tasks"].as_array().unwrap().len(), 2);
+    assert_eq!(export["tasks"][0]["labels"][0], "ops");
+    assert!(export["events"].as_array().unwrap().iter().all(|e| e["event_type"] != "comment"));
+    assert_eq!(export["events"].as_array().unwrap().len(), 3);
+    assert_eq!(export["comments"].as_array().unwrap().len(), 1);
+    assert_eq!(export["comments"][0]["data"]["message"], "Backed up");
+    assert_eq!(export["dependencies"][0]["note"], "first");
+    let webhooks = export["webhooks"].as_array().unwrap();
+    assert_eq!(webhooks.len(), 2);
+    assert!(webhooks[0].get("secret").is_none());
+    let mut urls: Vec<&str> = webhooks.iter().map(|w| w["url"].as_str().unwrap()).collect();
+    urls.sort();
+    assert_eq!(urls, vec!["https://example.com/…", "https://hooks.slack.com/…"]);
+    let raw = serde_json::to_string(&export).unwrap();
+    assert!(!raw.contains("whsec_"));
+    assert!(!raw.contains("token=abc") && !raw.contains("XXXXSECRET"));
+}
+
+#[test]
+fn test_http_import_round_trip() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Migrating Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let doing_col = board["columns"][1]["id"].as_str().unwrap().to_string();
+
+    let mut ids = Vec::new();
+    for title in ["First", "Second", "Third"] {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "{}", "actor_name": "Mover"}}"#, title))
+            .dispatch();
+        let task: serde_json::Value = resp.into_json().unwrap();
+        ids.push(task["id"].as_str().unwrap().to_string());
+    }
+    client
+        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}?actor=Mover", board_id, ids[2], doing_col))
+        .header(auth.clone())
+        .dispatch();
+    client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, ids[0]))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "Carry me over", "actor_name": "Mover"}"#)
+        .dispatch();
+    client
+        .post(format!("/api/v1/boards/{}/dependencies", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(format!(r#"{{"blocker_task_id": "{}", "blocked_task_id": "{}"}}"#, ids[0], ids[1]))
+        .dispatch();
+    client
+        .post(format!("/api/v1/boards/{}/webhooks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"url": "https://example.com/hook"}"#)
+        .dispatch();
+
+    let resp = client
+        .get(format!("/api/v1/boards/{}/export", board_id))
+        .header(auth.clone())
+        .dispatch();
+    let export = resp.into_string().unwrap();
+
+    let resp = client
+        .post("/api/v1/boards/import")
+        .header(ContentType::JSON)
+        .body(export.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let imported: serde_json::Value = resp.into_json().unwrap();
+    let new_id = imported["id"].as_str().unwrap().to_string();
+    assert_ne!(new_id, board_id);
+    let new_key = imported["manage_key"].as_str().unwrap().to_string();
+    assert_ne!(new_key, manage_key);
+    assert_eq!(imported["report"]["tasks"], 3);
+    assert_eq!(imported["report"]["comments"], 1);
+    assert_eq!(imported["report"]["dependencies"], 1);
+    assert_eq!(imported["report"]["webhooks_skipped"], 1);
+
+    // Same shape, fresh ids, and the new key manages it
+    let resp = client.get(format!("/api/v1/boards/{}", new_id)).dispatch();
+    let copy: serde_json::Value = resp.into_json().unwrap();
+    let names: Vec<&str> = copy["columns"].as_array().unwrap().iter().map(|c| c["name"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["To Do", "In Progress", "Done"]);
+    assert_eq!(copy["columns"][1]["task_count"], 1);
+    assert!(copy["columns"].as_array().unwrap().iter().all(|c| c["id"] != doing_col.as_str()));
+
+    let resp = client
+        .get(format!("/api/v1/boards/{}/export", new_id))
+        .header(Header::new("Authorization", format!("Bearer {}", new_key)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let copy_export: serde_json::Value = resp.into_json().unwrap();
+    let original: serde_json::Value = serde_json::from_str(&export).unwrap();
+    let titles = |doc: &serde_json::Value| -> Vec<(String, i64)> {
+        doc["tasks"].as_array().unwrap().iter()
+            .map(|t| (t["title"].as_str().unwrap().to_string(), t["position"].as_i64().unwrap()))
+            .collect()
+    };
+    assert_eq!(titles(&copy_export), titles(&original));
+    assert!(copy_export["tasks"].as_array().unwrap().iter().all(|t| !ids.contains(&t["id"].as_str().unwrap().to_string())));
+    assert_eq!(copy_export["events"].as_array().unwrap().len(), original["events"].as_array().unwrap().len());
+    assert_eq!(copy_export["comments"][0]["data"]["message"], "Carry me over");
+    // Ids inside event data point at the new rows
+    let moved = copy_export["events"].as_array().unwrap().iter().find(|e| e["event_type"] == "moved").unwrap();
+    assert!(!moved.to_string().contains(&doing_col));
+    assert_eq!(copy_export["webhooks"].as_array().unwrap().len(), 0);
+
+    // Wrong format / future version are rejected
+    let mut bad: serde_json::Value = serde_json::from_str(&export).unwrap();
+    bad["format"] = serde_json::json!("something-else");
+    let resp = client.post("/api/v1/boards/import").header(ContentType::JSON).body(bad.to_string()).dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "INVALID_IMPORT");
+    let mut future: serde_json::Value = serde_json::from_str(&export).unwrap();
+    future["version"] = serde_json::json!(99);
+    let resp = client.post("/api/v1/boards/import").header(ContentType::JSON).body(future.to_string()).dispatch();
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "UNSUPPORTED_EXPORT_VERSION");
+    let mut dangling: serde_json::Value = serde_json::from_str(&export).unwrap();
+    dangling["tasks"][0]["column_id"] = serde_json::json!("no-such-column");
+    let resp = client.post("/api/v1/boards/import").header(ContentType::JSON).body(dangling.to_string()).dispatch();
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "INVALID_IMPORT");
+}
+
+#[test]
+fn test_http_import_validate_then_commit() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Staged Import");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    for title in ["One", "Two"] {
+        client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "{}"}}"#, title))
+            .dispatch();
+    }
+    let export = client
+        .get(format!("/api/v1/boards/{}/export", board_id))
+        .header(auth.clone())
+        .dispatch()
+        .into_string()
+        .unwrap();
+
+    // Every problem is reported, with where it is; nothing is held for commit
+    let mut broken: serde_json::Value = serde_json::from_str(&export).unwrap();
+    broken["board"]["name"] = serde_json::json!(" ");
+    broken["tasks"][1]["column_id"] = serde_json::json!("no-such-column");
+    let resp = client
+        .post("/api/v1/boards/import/validate")
+        .header(ContentType::JSON)
+        .body(broken.to_string())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let report: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(report["valid"], false);
+    assert!(report["expires_at"].is_null());
+    let issues = report["issues"].as_array().unwrap();
+    assert_eq!(issues.len(), 2);
+    assert_eq!(issues[0]["code"], "EMPTY_NAME");
+    assert_eq!(issues[1]["path"], "tasks[1].column_id");
+    let resp = client
+        .post("/api/v1/boards/import/commit")
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"payload_hash": report["payload_hash"]}).to_string())
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    let resp = client
+        .post("/api/v1/boards/import/validate")
+        .header(ContentType::JSON)
+        .body("{\"format\": ")
+        .dispatch();
+    let report: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(report["issues"][0]["code"], "INVALID_IMPORT");
+
+    // A clean document gets the dry-run counts and can be committed once
+    let resp = client
+        .post("/api/v1/boards/import/validate")
+        .header(ContentType::JSON)
+        .body(export.clone())
+        .dispatch();
+    let report: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(report["valid"], true);
+    assert_eq!(report["source"], "kanban");
+    assert_eq!(report["report"]["tasks"], 2);
+    assert!(report["expires_at"].is_string());
+    let hash = report["payload_hash"].as_str().unwrap().to_string();
+    assert_eq!(hash.len(), 64);
+
+    let resp = client
+        .post("/api/v1/boards/import/commit")
+        .header(ContentType::JSON)
+        .body(format!(r#"{{"payload_hash": "{}"}}"#, hash))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let imported: serde_json::Value = resp.into_json().unwrap();
+    assert_ne!(imported["id"], board_id);
+    assert_eq!(imported["report"]["tasks"], 2);
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks", imported["id"].as_str().unwrap()))
+        .dispatch();
+    let tasks: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(tasks.as_array().unwrap().len(), 2);
+
+    let resp = client
+        .post("/api/v1/boards/import/commit")
+        .header(ContentType::JSON)
+        .body(format!(r#"{{"payload_hash": "{}"}}"#, hash))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    let resp = client
+        .post("/api/v1/boards/import/validate?source=asana")
+        .header(ContentType::JSON)
+        .body(export)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+}
+
+#[test]
+fn test_http_import_trello() {
+    let client = test_client();
+    let trello = serde_json::json!({
+        "id": "5f0c6a0e8a1b2c3d4e5f6a70",
+        "name": "Trello Board",
+        "desc": "From Trello",
+        "lists": [
+            {"id": "5f0c6a0e8a1b2c3d4e5f6a72", "name": "Doing", "closed": false, "pos": 2048},
+            {"id": "5f0c6a0e8a1b2c3d4e5f6a71", "name": "Backlog", "closed": false, "pos": 1024},
+            {"id": "5f0c6a0e8a1b2c3d4e5f6a73", "name": "Old", "closed": true, "pos": 4096}
+        ],
+        "members": [
+            {"id": "m1", "fullName": "Ada Lovelace", "username": "ada"},
+            {"id": "m2", "fullName": "", "username": "grace"}
+        ],
+        "cards": [
+            {"id": "5f0c6a0e8a1b2c3d4e5f6a81", "name": "Second", "desc": "", "idList": "5f0c6a0e8a1b2c3d4e5f6a71",
+             "pos": 200, "labels": [], "idMembers": []},
+            {"id": "5f0c6a0e8a1b2c3d4e5f6a80", "name": "First", "desc": "Card body", "idList": "5f0c6a0e8a1b2c3d4e5f6a71",
+             "pos": 100, "due": "2026-03-01T17:00:00.000Z", "labels": [{"name": "bug", "color": "red"}, {"name": "", "color": "green"}],
+             "idMembers": ["m1", "m2"], "attachments": [{"url": "https://example.com/a.png"}], "shortUrl": "https://trello.com/c/abc"},
+            {"id": "5f0c6a0e8a1b2c3d4e5f6a82", "name": "Archived card", "idList": "5f0c6a0e8a1b2c3d4e5f6a72",
+             "closed": true, "pos": 1},
+            {"id": "5f0c6a0e8a1b2c3d4e5f6a83", "name": "In old list", "idList": "5f0c6a0e8a1b2c3d4e5f6a73", "pos": 1}
+        ],
+        "checklists": [
+            {"id": "c1", "idCard": "5f0c6a0e8a1b2c3d4e5f6a80", "name": "Steps", "pos": 1,
+             "checkItems": [{"name": "Two", "state": "incomplete", "pos": 2}, {"name": "One", "state": "complete", "pos": 1}]}
+        ],
+        "actions": [
+            {"id": "a1", "type": "commentCard", "date": "2026-02-01T10:00:00.000Z",
+             "data": {"text": "Looks good", "card": {"id": "5f0c6a0e8a1b2c3d4e5f6a80"}},
+             "memberCreator": {"id": "m1", "fullName": "Ada Lovelace", "username": "ada"}},
+            {"id": "a2", "type": "createCard", "date": "2020-07-13T14:05:02.000Z",
+             "data": {"card": {"id": "5f0c6a0e8a1b2c3d4e5f6a80"}},
+             "memberCreator": {"id": "m2", "fullName": "", "username": "grace"}}
+        ]
+    });
+
+    let resp = client
+        .post("/api/v1/boards/import/trello")
+        .header(ContentType::JSON)
+        .body(trello.to_string())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let imported: serde_json::Value = resp.into_json().unwrap();
+    let board_id = imported["id"].as_str().unwrap().to_string();
+    let auth = Header::new("Authorization", format!("Bearer {}", imported["manage_key"].as_str().unwrap()));
+
+    // Open lists in Trello order; the archived list is reported, not imported
+    let columns: Vec<&str> = imported["columns"].as_array().unwrap().iter().map(|c| c["name"].as_str().unwrap()).collect();
+    assert_eq!(columns, vec!["Backlog", "Doing"]);
+    let report = &imported["report"];
+    assert_eq!(report["tasks"], 3);
+    assert_eq!(report["comments"], 2);
+    let warnings = report["warnings"].to_string();
+    assert!(warnings.contains("Archived list 'Old' skipped (1 card(s))"));
+    assert!(warnings.contains("1 attachment(s) not imported"));
+    assert!(warnings.contains("assigned to Ada Lovelace only"));
+
+    let export: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/export", board_id))
+        .header(auth.clone())
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let tasks = export["tasks"].as_array().unwrap();
+    let titles: Vec<&str> = tasks.iter().map(|t| t["title"].as_str().unwrap()).collect();
+    assert_eq!(titles, vec!["First", "Second", "Archived card"]);
+    let first = &tasks[0];
+    assert_eq!(first["description"], "Card body");
+    assert_eq!(first["labels"], serde_json::json!(["bug", "green"]));
+    assert_eq!(first["due_at"], "2026-03-01T17:00:00Z");
+    assert_eq!(first["assigned_to"], "Ada Lovelace");
+    assert_eq!(first["created_by"], "grace");
+    assert_eq!(first["created_at"], "2020-07-13 14:05:02");
+    assert_eq!(first["metadata"]["trello"]["url"], "https://trello.com/c/abc");
+    assert!(tasks[2]["archived_at"].is_string());
+
+    // Card comment keeps its author; the checklist becomes a markdown comment
+    let comments = export["comments"].as_array().unwrap();
+    let messages: Vec<&str> = comments.iter().map(|c| c["data"]["message"].as_str().unwrap()).collect();
+    assert!(messages.contains(&"Looks good"));
+    assert!(messages.contains(&"Checklist: Steps\n- [x] One\n- [ ] Two"));
+    assert!(comments.iter().any(|c| c["actor"] == "Ada Lovelace"));
+
+    // A board with no open lists can't be imported
+    let resp = client
+        .post("/api/v1/boards/import/trello")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "Empty", "lists": [{"id": "l1", "name": "Gone", "closed": true}]}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["code"], "INVALID_IMPORT");
+}
+
+#[test]
+fn test_http_import_github_issues() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "GitHub Import Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let issues = serde_json::json!([
+        {"number": 2, "title": "Crash on save", "body": "Steps...", "state": "open",
+         "html_url": "https://github.com/acme/app/issues/2",
+         "labels": [{"name": "Bug"}], "assignees": [{"login": "ada"}, {"login": "grace"}],
+         "user": {"login": "reporter"}, "milestone": {"title": "v1.0"},
+         "created_at": "2026-01-05T09:30:00Z"},
+        {"number": 1, "title": "Add docs", "body": null, "state": "open",
+         "html_url": "https://github.com/acme/app/issues/1", "labels": [], "assignees": []},
+        {"number": 3, "title": "Old thing", "state": "closed", "html_url": "https://github.com/acme/app/issues/3",
+         "closed_at": "2026-01-10T12:00:00Z"},
+        {"number": 4, "title": "A pull request", "state": "open", "html_url": "https://github.com/acme/app/pull/4",
+         "pull_request": {"url": "..."}}
+    ]);
+    let body = serde_json::json!({"issues": issues, "milestone_columns": {"v1.0": "in progress"}});
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/import/github", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(body.to_string())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let report: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(report["imported"], 3);
+    assert_eq!(report["pull_requests_skipped"], 1);
+    assert!(report["warnings"][0].as_str().unwrap().contains("assigned to ada only"));
+
+    let tasks: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/tasks", board_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let by_title = |title: &str| tasks.as_array().unwrap().iter().find(|t| t["title"] == title).unwrap().clone();
+    let crash = by_title("Crash on save");
+    assert_eq!(crash["column_name"], "In Progress");
+    assert_eq!(crash["assigned_to"], "ada");
+    assert_eq!(crash["labels"], serde_json::json!(["bug"]));
+    assert_eq!(crash["description"], "Steps...");
+    assert_eq!(crash["created_by"], "reporter");
+    assert_eq!(crash["metadata"]["github"]["number"], 2);
+    assert_eq!(by_title("Add docs")["column_name"], "To Do");
+    let closed = by_title("Old thing");
+    assert_eq!(closed["column_name"], "Done");
+    assert!(closed["completed_at"].is_string());
+
+    // Importing the same issues again adds nothing
+    let resp = client
+        .post(format!("/api/v1/boards/{}/import/github", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(body.to_string())
+        .dispatch();
+    let report: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(report["imported"], 0);
+    assert_eq!(report["already_imported"], 3);
+
+    // Column mapping is checked before anything is imported
+    let resp = client
+        .post(format!("/api/v1/boards/{}/import/github", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"issues": [], "milestone_columns": {"v2": "Nowhere"}}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "INVALID_COLUMN");
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/import/github", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"issues": [], "repo": "acme/app"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/import/github", board_id))
+        .header(ContentType::JSON)
+        .body(r#"{"issues": []}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+}
+
+#[test]
+fn test_http_import_tasks_csv() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "CSV Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let csv = "title,description,column,priority,labels,assigned_to,due_at\n\
+               \"Write docs, part 1\",Intro,in progress,high,\"docs;Writing\",ada,2026-03-01T00:00:00Z\n\
+               Plain task,,,,,,\n\
+               Bad column,,Nowhere,,,,\n\
+               Bad priority,,,sometimes,,,\n\
+               ,,,,,,\n";
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/import?actor=Importer", board_id))
+        .header(ContentType::CSV)
+        .header(auth.clone())
+        .body(csv)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let report: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(report["total"], 4);
+    assert_eq!(report["succeeded"], 2);
+    assert_eq!(report["failed"], 2);
+    let results = report["results"].as_array().unwrap();
+    assert_eq!(results[0]["line"], 2);
+    assert_eq!(results[0]["success"], true);
+    assert_eq!(results[2]["line"], 4);
+    assert_eq!(results[2]["code"], "INVALID_COLUMN");
+    assert_eq!(results[3]["code"], "INVALID_PRIORITY");
+
+    let task_id = results[0]["task_id"].as_str().unwrap();
+    let task: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(task["title"], "Write docs, part 1");
+    assert_eq!(task["column_name"], "In Progress");
+    assert_eq!(task["priority"], 2);
+    assert_eq!(task["labels"], serde_json::json!(["docs", "writing"]));
+    assert_eq!(task["assigned_to"], "ada");
+    assert_eq!(task["due_at"], "2026-03-01T00:00:00Z");
+    assert_eq!(task["created_by"], "Importer");
+
+    // Header problems reject the whole file
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/import", board_id))
+        .header(ContentType::CSV)
+        .header(auth.clone())
+        .body("title,colour\nx,red\n")
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "INVALID_CSV");
+
+    // WIP limits apply row by row
+    let board: serde_json::Value = client.get(format!("/api/v1/boards/{}", board_id)).dispatch().into_json().unwrap();
+    let done_id = board["columns"].as_array().unwrap().iter().find(|c| c["name"] == "Done").unwrap()["id"].as_str().unwrap().to_string();
+    client
+        .patch(format!("/api/v1/boards/{}/columns/{}", board_id, done_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"wip_limit": 1}"#)
+        .dispatch();
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/import", board_id))
+        .header(ContentType::CSV)
+        .header(auth.clone())
+        .body("title,column\nFirst done,Done\nSecond done,Done\n")
+        .dispatch();
+    let report: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(report["succeeded"], 1);
+    assert_eq!(report["results"][1]["code"], "WIP_LIMIT_EXCEEDED");
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/import", board_id))
+        .header(ContentType::CSV)
+        .body("title\nx\n")
+        .dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+}
+
+// ============ Analytics ============
+
+#[test]
+fn test_http_activity_heatmap() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Heatmap HTTP Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    for actor in ["agent-a", "agent-a", "agent-b"] {
+        client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "Heat", "actor_name": "{}"}}"#, actor))
+            .dispatch();
+    }
+
+    let resp = client
+        .get(format!("/api/v1/boards/{}/analytics/heatmap?days=7&tz_offset=120", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["days"], 7);
+    assert_eq!(body["tz_offset_minutes"], 120);
+    assert_eq!(body["total_events"], 3);
+    let cells = body["cells"].as_array().unwrap();
+    assert_eq!(cells.len(), 7);
+    assert!(cells.iter().all(|row| row.as_array().unwrap().len() == 24));
+    let cell_sum: i64 = cells
+        .iter()
+        .flat_map(|row| row.as_array().unwrap().iter().map(|c| c.as_i64().unwrap()))
+        .sum();
+    assert_eq!(cell_sum, 3);
+
+    let by_actor = body["by_actor"].as_array().unwrap();
+    assert_eq!(by_actor[0]["actor"], "agent-a");
+    assert_eq!(by_actor[0]["total"], 2);
+    assert_eq!(by_actor[1]["actor"], "agent-b");
+
+    let resp = client.get("/api/v1/boards/nope/analytics/heatmap").dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[test]
+fn test_http_aging_report() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Aging HTTP Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let doing_col = board["columns"][1]["id"].as_str().unwrap().to_string();
+
+    let mut ids = Vec::new();
+    for title in ["Stuck", "Moving"] {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "{}"}}"#, title))
+            .dispatch();
+        let task: serde_json::Value = resp.into_json().unwrap();
+        ids.push(task["id"].as_str().unwrap().to_string());
+    }
+    // Leaving "To Do" records a completed stint there
+    client
+        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}", board_id, ids[1], doing_col))
+        .header(auth.clone())
+        .dispatch();
+
+    let resp = client
+        .get(format!("/api/v1/boards/{}/analytics/aging", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    let columns = body["columns"].as_array().unwrap();
+    assert_eq!(columns.len(), 3);
+
+    let todo = &columns[0];
+    assert_eq!(todo["name"], "To Do");
+    assert_eq!(todo["sample_size"], 1);
+    assert!(todo["percentiles"]["p85"].is_number());
+    let todo_tasks = todo["tasks"].as_array().unwrap();
+    assert_eq!(todo_tasks.len(), 1);
+    assert_eq!(todo_tasks[0]["title"], "Stuck");
+    assert!(todo_tasks[0]["band"].is_string());
+
+    let doing = &columns[1];
+    assert!(doing["percentiles"].is_null());
+    assert_eq!(doing["tasks"][0]["title"], "Moving");
+    assert!(doing["tasks"][0]["band"].is_null());
+}
+
+#[test]
+fn test_http_suggest_assignee() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Suggest Assignee Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let board: serde_json::Value = client.get(format!("/api/v1/boards/{}", board_id)).dispatch().into_json().unwrap();
+    let done_col = board["columns"].as_array().unwrap().last().unwrap()["id"].as_str().unwrap().to_string();
+
+    let create = |body: serde_json::Value| -> String {
+        let task: serde_json::Value = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(body.to_string())
+            .dispatch()
+            .into_json()
+            .unwrap();
+        task["id"].as_str().unwrap().to_string()
+    };
+    let complete = |task_id: &str, actor: &str| {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks/{}/move/{}?actor={}", board_id, task_id, done_col, actor))
+            .header(auth.clone())
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+    };
+
+    // alice finishes backend/db work, bob finishes UI work
+    for title in ["Migrate schema", "Add index"] {
+        let id = create(serde_json::json!({"title": title, "labels": ["backend", "db"]}));
+        complete(&id, "alice");
+    }
+    let id = create(serde_json::json!({"title": "Polish header", "labels": ["ui"]}));
+    complete(&id, "bob");
+    create(serde_json::json!({"title": "Open work", "assigned_to": "alice"}));
+    let target = create(serde_json::json!({"title": "Tune queries", "labels": ["Backend", "db"]}));
+
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks/{}/suggest-assignee", board_id, target))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["sample_size"], 3);
+    assert_eq!(body["days"], 90);
+    let suggestions = body["suggestions"].as_array().unwrap();
+    assert_eq!(suggestions.len(), 2);
+    // 2 full label matches + 2 moves out of To Do × 0.5 + 2 completions × 0.1
+    assert_eq!(suggestions[0]["actor"], "alice");
+    assert_eq!(suggestions[0]["score"], 3.2);
+    assert_eq!(suggestions[0]["label_matches"], 2);
+    assert_eq!(suggestions[0]["matched_labels"], serde_json::json!(["backend", "db"]));
+    assert_eq!(suggestions[0]["column_moves"], 2);
+    assert_eq!(suggestions[0]["completed"], 2);
+    assert_eq!(suggestions[0]["open_tasks"], 1);
+    assert_eq!(suggestions[1]["actor"], "bob");
+    assert_eq!(suggestions[1]["score"], 0.6);
+    assert_eq!(suggestions[1]["label_matches"], 0);
+
+    let body: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/tasks/{}/suggest-assignee?limit=1", board_id, target))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(body["suggestions"].as_array().unwrap().len(), 1);
+
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks/nope/suggest-assignee", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[test]
+fn test_http_board_view_counters() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Views HTTP Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    for _ in 0..3 {
+        client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    }
+
+    // Owner only
+    let resp = client.get(format!("/api/v1/boards/{}/views", board_id)).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let (_, other_key) = create_test_board(&client, "Someone Else");
+    let resp = client
+        .get(format!("/api/v1/boards/{}/views", board_id))
+        .header(Header::new("Authorization", format!("Bearer {}", other_key)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    let resp = client
+        .get(format!("/api/v1/boards/{}/views?days=7", board_id))
+        .header(auth)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["days"], 7);
+    assert_eq!(body["totals"]["board_loads"], 3);
+    assert_eq!(body["totals"]["sse_connects"], 0);
+    let daily = body["daily"].as_array().unwrap();
+    assert_eq!(daily.len(), 1);
+    assert_eq!(daily[0]["board_loads"], 3);
+}
+
+// ============ Compression & Streaming ============
+
+#[test]
+fn test_http_list_tasks_streams_across_pages() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Streaming HTTP Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    // More than one stream page (100 rows)
+    for i in 0..130 {
+        client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "Bulk task {}"}}"#, i))
+            .dispatch();
+    }
+
+    let resp = client.get(format!("/api/v1/boards/{}/tasks", board_id)).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(resp.content_type(), Some(ContentType::JSON));
+    let tasks: Vec<serde_json::Value> = resp.into_json().unwrap();
+    assert_eq!(tasks.len(), 130);
+
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks?limit=101&offset=50", board_id))
+        .dispatch();
+    let tasks: Vec<serde_json::Value> = resp.into_json().unwrap();
+    assert_eq!(tasks.len(), 80);
+
+    // A task that sorts first, created while a listing streams, doesn't shift
+    // the later pages onto rows already sent
+    let resp = client.get(format!("/api/v1/boards/{}/tasks", board_id)).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let urgent = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Jumps the queue", "priority": 3}"#)
+        .dispatch();
+    assert_eq!(urgent.status(), Status::Ok);
+    let tasks: Vec<serde_json::Value> = resp.into_json().unwrap();
+    let ids: std::collections::HashSet<&str> = tasks.iter().map(|t| t["id"].as_str().unwrap()).collect();
+    assert_eq!((tasks.len(), ids.len()), (130, 130));
+
+    // Empty result is still a valid array
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks?archived=true", board_id))
+        .dispatch();
+    let tasks: Vec<serde_json::Value> = resp.into_json().unwrap();
+    assert!(tasks.is_empty());
+}
+
+#[test]
+fn test_http_gzip_compression() {
+    use std::io::Read;
+
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Compression HTTP Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    for i in 0..20 {
+        client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "Compressible task {}"}}"#, i))
+            .dispatch();
+    }
+
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(Header::new("Accept-Encoding", "gzip"))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(resp.headers().get_one("Content-Encoding"), Some("gzip"));
+    let compressed = resp.into_bytes().unwrap();
+    let mut json = String::new();
+    flate2::read::GzDecoder::new(&compressed[..]).read_to_string(&mut json).unwrap();
+    let tasks: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+    assert_eq!(tasks.len(), 20);
+
+    // Small sized bodies are left alone
+    let resp = client
+        .get("/api/v1/health")
+        .header(Header::new("Accept-Encoding", "gzip"))
+        .dispatch();
+    assert!(resp.headers().get_one("Content-Encoding").is_none());
+
+    // No Accept-Encoding, no compression
+    let resp = client.get(format!("/api/v1/boards/{}/tasks", board_id)).dispatch();
+    assert!(resp.headers().get_one("Content-Encoding").is_none());
+}
+
+// ============ Rate Limiting ============
+
+#[test]
+fn test_http_rate_limiting() {
+    let db_path = format!("/tmp/kanban_http_rl_{}.db", uuid::Uuid::new_v4());
+    std::env::set_var("DATABASE_PATH", &db_path);
+    std::env::set_var("BOARD_RATE_LIMIT", "3"); // Only 3 boards/hour for this test
+
+    let db = kanban::db::init_db().expect("DB should initialize");
+    let webhook_db = kanban::db::init_webhook_db().expect("Webhook DB should initialize");
+    let rate_limits = in_memory_limits(
+        &kanban::rate_limit::RateLimitConfig::default().with_rule(RouteGroup::BoardCreation, 3, 3600),
+    );
+
+    let rocket = rocket::build()
+        .attach(kanban::rate_limit::RateLimitHeaders)
+        .manage(db)
+        .manage(rate_limits)
+        .manage(kanban::events::EventBus::with_webhooks(webhook_db))
+        .manage(kanban::lifecycle::LifecycleHooks::default())
+        .mount(
+            "/api/v1",
+            routes![
+                kanban::routes::create_board,
+            ],
+        );
+
+    let client = Client::tracked(rocket).expect("valid rocket instance");
+
+    // First 3 should succeed
+    for i in 0..3 {
+        let resp = client
+            .post("/api/v1/boards")
+            .header(ContentType::JSON)
+            .body(format!(r#"{{"name": "RL Board {}", "columns": []}}"#, i))
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok, "Board {} should succeed", i);
+        assert_eq!(resp.headers().get_one("X-RateLimit-Limit"), Some("3"));
+        assert_eq!(resp.headers().get_one("X-RateLimit-Remaining"), Some((2 - i).to_string().as_str()));
+        assert!(resp.headers().get_one("Retry-After").is_none());
+    }
+
+    // 4th should be rate limited
+    let resp = client
+        .post("/api/v1/boards")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "RL Board 3", "columns": []}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::TooManyRequests);
+    assert_eq!(resp.headers().get_one("X-RateLimit-Remaining"), Some("0"));
+    let retry_after: u64 = resp.headers().get_one("Retry-After").unwrap().parse().unwrap();
+    assert!((1..=3600).contains(&retry_after));
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["code"], "RATE_LIMIT_EXCEEDED");
+}
+
+#[test]
+fn test_http_view_password_rate_limit() {
+    let db_path = format!("/tmp/kanban_http_view_rl_{}.db", uuid::Uuid::new_v4());
+    let db = kanban::db::init_db_with_path(&db_path).expect("DB should initialize");
+    let webhook_db = kanban::db::init_webhook_db_with_path(&db_path).expect("Webhook DB should initialize");
+    let rate_limits = in_memory_limits(
+        &kanban::rate_limit::RateLimitConfig::default().with_rule(RouteGroup::ViewSession, 2, 900),
+    );
+    let rocket = rocket::build()
+        .attach(kanban::rate_limit::RateLimitHeaders)
+        .manage(db)
+        .manage(rate_limits)
+        .manage(kanban::events::EventBus::with_webhooks(webhook_db))
+        .manage(kanban::lifecycle::LifecycleHooks::default())
+        .mount(
+            "/api/v1",
+            routes![kanban::routes::create_board, kanban::view_password::create_view_session],
+        );
+    let client = Client::tracked(rocket).expect("valid rocket instance");
+
+    let created: serde_json::Value = client
+        .post("/api/v1/boards")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "Guarded", "view_password": "open sesame"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let session_url = format!("/api/v1/boards/{}/view-session", created["id"].as_str().unwrap());
+
+    // Every attempt counts, right or wrong; past the limit even the right
+    // password is refused until the window resets
+    let resp = client.post(&session_url).header(ContentType::JSON).body(r#"{"password": "guess"}"#).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    assert_eq!(resp.headers().get_one("X-RateLimit-Remaining"), Some("1"));
+    let resp = client.post(&session_url).header(ContentType::JSON).body(r#"{"password": "open sesame"}"#).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.post(&session_url).header(ContentType::JSON).body(r#"{"password": "open sesame"}"#).dispatch();
+    assert_eq!(resp.status(), Status::TooManyRequests);
+    assert!(resp.headers().get_one("Retry-After").is_some());
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["code"], "RATE_LIMIT_EXCEEDED");
+}
+
+#[test]
+fn test_http_cors_policy() {
+    let client_with = |config: kanban::cors::CorsConfig| {
+        let rocket = rocket::build()
+            .attach(config.to_cors().unwrap())
+            .manage(kanban::features::FeatureFlags::default())
+            .mount("/api/v1", rocket::routes![kanban::routes::health]);
+        Client::tracked(rocket).expect("valid rocket instance")
+    };
+    let preflight = |client: &Client, origin: &str, method: &str| {
+        let resp = client
+            .options("/api/v1/health")
+            .header(Header::new("Origin", origin.to_string()))
+            .header(Header::new("Access-Control-Request-Method", method.to_string()))
+            .dispatch();
+        (
+            resp.status(),
+            resp.headers().get_one("Access-Control-Allow-Origin").map(str::to_string),
+            resp.headers().get_one("Access-Control-Allow-Credentials").map(str::to_string),
+        )
+    };
+
+    // Default: any origin, no credentials
+    let open = client_with(kanban::cors::CorsConfig::parse(None, None, None, None).unwrap());
+    let (status, origin, credentials) = preflight(&open, "https://anywhere.example", "DELETE");
+    assert_eq!(status, Status::NoContent);
+    assert_eq!(origin.as_deref(), Some("https://anywhere.example"));
+    assert_eq!(credentials, None);
+
+    let strict = client_with(
+        kanban::cors::CorsConfig::parse(Some("https://app.example.com"), Some("GET,POST"), Some("true"), None).unwrap(),
+    );
+    let (status, origin, credentials) = preflight(&strict, "https://app.example.com", "POST");
+    assert_eq!(status, Status::NoContent);
+    assert_eq!(origin.as_deref(), Some("https://app.example.com"));
+    assert_eq!(credentials.as_deref(), Some("true"));
+    let (status, origin, _) = preflight(&strict, "https://evil.example", "POST");
+    assert_eq!(status, Status::Forbidden);
+    assert_eq!(origin, None);
+    let (status, _, _) = preflight(&strict, "https://app.example.com", "DELETE");
+    assert_eq!(status, Status::Forbidden);
+
+    let resp = strict
+        .get("/api/v1/health")
+        .header(Header::new("Origin", "https://evil.example"))
+        .dispatch();
+    assert_eq!(resp.headers().get_one("Access-Control-Allow-Origin"), None);
+}
+
+#[test]
+fn test_http_write_rate_limits_per_key() {
+    let client = test_client_with_write_limits(3, 1, 1);
+    let (board_id, manage_key) = create_test_board(&client, "Busy Board");
+    let (other_board, other_key) = create_test_board(&client, "Quiet Board");
+    let auth = |key: &str| Header::new("Authorization", format!("Bearer {}", key));
+    let create = |board: &str, key: &str| {
+        client
+            .post(format!("/api/v1/boards/{}/tasks", board))
+            .header(ContentType::JSON)
+            .header(auth(key))
+            .body(r#"{"title": "Another one"}"#)
+            .dispatch()
+    };
+
+    let task: serde_json::Value = create(&board_id, &manage_key).into_json().unwrap();
+    let task_id = task["id"].as_str().unwrap().to_string();
+    let resp = client
+        .patch(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
+        .header(ContentType::JSON)
+        .header(auth(&manage_key))
+        .body(r#"{"title": "Renamed"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(resp.headers().get_one("X-RateLimit-Limit"), Some("3"));
+    assert_eq!(resp.headers().get_one("X-RateLimit-Remaining"), Some("1"));
+    assert_eq!(create(&board_id, &manage_key).status(), Status::Ok);
+
+    // The fourth task write in the window is refused, with a Retry-After
+    let resp = create(&board_id, &manage_key);
+    assert_eq!(resp.status(), Status::TooManyRequests);
+    let retry_after: u64 = resp.headers().get_one("Retry-After").unwrap().parse().unwrap();
+    assert!((1..=60).contains(&retry_after));
+    assert_eq!(resp.into_json::<serde_json::Value>().unwrap()["error"], "RATE_LIMIT_EXCEEDED");
+
+    // Other keys, reads and other kinds of write have their own allowance
+    assert_eq!(create(&other_board, &other_key).status(), Status::Ok);
+    let resp = client.get(format!("/api/v1/boards/{}/tasks", board_id)).header(auth(&manage_key)).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let comment = || {
+        client
+            .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+            .header(ContentType::JSON)
+            .header(auth(&manage_key))
+            .body(r#"{"message": "still here"}"#)
+            .dispatch()
+            .status()
+    };
+    assert_eq!(comment(), Status::Ok);
+    assert_eq!(comment(), Status::TooManyRequests);
+    let batch = || {
+        client
+            .post(format!("/api/v1/boards/{}/tasks/batch", board_id))
+            .header(ContentType::JSON)
+            .header(auth(&manage_key))
+            .body(serde_json::json!({"operations": [{"action": "update", "task_ids": [task_id], "priority": 2}]}).to_string())
+            .dispatch()
+            .status()
+    };
+    assert_eq!(batch(), Status::Ok);
+    assert_eq!(batch(), Status::TooManyRequests);
+
+    // Board settings count as task writes
+    let resp = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(auth(&manage_key))
+        .body(r#"{"description": "settings are limited too"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::TooManyRequests);
+
+    // Imports count every row; one that doesn't fit writes nothing but still counts as a write
+    let import = |csv: &'static str| {
+        client
+            .post(format!("/api/v1/boards/{}/tasks/import", other_board))
+            .header(ContentType::CSV)
+            .header(auth(&other_key))
+            .body(csv)
+            .dispatch()
+            .status()
+    };
+    assert_eq!(import("title\nOne\nTwo\nThree\n"), Status::TooManyRequests);
+    assert_eq!(import("title\nOne\n"), Status::Ok);
+    let tasks: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/tasks", other_board))
+        .header(auth(&other_key))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(tasks.as_array().unwrap().len(), 2);
+
+    // A share link is counted by the link itself
+    let (shared_board, shared_key) = create_test_board(&client, "Shared Board");
+    let link: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/share-links", shared_board))
+        .header(ContentType::JSON)
+        .header(auth(&shared_key))
+        .body(r#"{"scope": "comment"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let shared_task: serde_json::Value = create(&shared_board, &shared_key).into_json().unwrap();
+    let link_comment = || {
+        client
+            .post(format!(
+                "/api/v1/boards/{}/tasks/{}/comment?key={}",
+                shared_board,
+                shared_task["id"].as_str().unwrap(),
+                link["token"].as_str().unwrap()
+            ))
+            .header(ContentType::JSON)
+            .body(r#"{"message": "LGTM", "actor_name": "Reviewer"}"#)
+            .dispatch()
+            .status()
+    };
+    assert_eq!(link_comment(), Status::Ok);
+    assert_eq!(link_comment(), Status::TooManyRequests);
+
+    // Keys without a role on the board are refused without being counted
+    for key in ["kb_made_up_1", "kb_made_up_2", other_key.as_str()] {
+        let resp = create(&board_id, key);
+        assert_eq!(resp.status(), Status::Forbidden);
+        assert!(resp.headers().get_one("X-RateLimit-Limit").is_none());
+    }
+    let resp = client
+        .get("/api/v1/admin/rate-limits")
+        .header(auth(TEST_ADMIN_KEY))
+        .dispatch();
+    let limits: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(limits["task_writes"]["clients"].as_array().unwrap().len(), 3);
+    assert_eq!(limits["comments"]["clients"].as_array().unwrap().len(), 2);
+}
+
+// ============ Column Management ============
+
+#[test]
+fn test_http_update_column_rename() {
+    let client = test_client();
+    let (board_id, key) = create_test_board(&client, "Col Rename Test");
+
+    // Get the board to find column IDs
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let col_id = board["columns"][0]["id"].as_str().unwrap();
+    assert_eq!(board["columns"][0]["name"], "To Do");
+
+    // Rename the column
+    let resp = client
+        .patch(format!("/api/v1/boards/{}/columns/{}", board_id, col_id))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {}", key)))
+        .body(r#"{"name": "Backlog"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let col: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(col["name"], "Backlog");
+    assert_eq!(col["id"], col_id);
+}
+
+#[test]
+fn test_http_column_auto_assign() {
+    let client = test_client();
+    let (board_id, key) = create_test_board(&client, "Auto Assign Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", key));
+    let board: serde_json::Value =
+        client.get(format!("/api/v1/boards/{}", board_id)).dispatch().into_json().unwrap();
+    let doing = board["columns"][1]["id"].as_str().unwrap().to_string();
+    assert_eq!(board["columns"][1]["auto_assign"], serde_json::json!([]));
+
+    let resp = client
+        .patch(format!("/api/v1/boards/{}/columns/{}", board_id, doing))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"auto_assign": ["alice", "bob"], "auto_assign_strategy": "fastest"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let col: serde_json::Value = client
+        .patch(format!("/api/v1/boards/{}/columns/{}", board_id, doing))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"auto_assign": ["alice", " bob ", "Alice", ""]}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(col["auto_assign"], serde_json::json!(["alice", "bob"]));
+    assert_eq!(col["auto_assign_strategy"], "round_robin");
+
+    let create = |body: &str| -> String {
+        let task: serde_json::Value = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(body)
+            .dispatch()
+            .into_json()
+            .unwrap();
+        task["id"].as_str().unwrap().to_string()
+    };
+    let move_to = |task_id: &str, column: &str| -> serde_json::Value {
+        client
+            .post(format!("/api/v1/boards/{}/tasks/{}/move/{}", board_id, task_id, column))
+            .header(auth.clone())
+            .dispatch()
+            .into_json()
+            .unwrap()
+    };
+
+    // Round robin across moves into the column
+    let assignees: Vec<serde_json::Value> = (0..3)
+        .map(|i| move_to(&create(&format!(r#"{{"title": "Job {}"}}"#, i)), &doing)["assigned_to"].clone())
+        .collect();
+    assert_eq!(assignees, vec!["alice", "bob", "alice"]);
+
+    // Someone already in the pool keeps the task; outsiders are replaced
+    let kept = create(r#"{"title": "Bob's", "assigned_to": "BOB"}"#);
+    assert_eq!(move_to(&kept, &doing)["assigned_to"], "BOB");
+    let replaced = create(r#"{"title": "Carol's", "assigned_to": "carol"}"#);
+    assert_eq!(move_to(&replaced, &doing)["assigned_to"], "bob");
+    let events: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/boards/{}/tasks/{}/events", board_id, replaced))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let assigned = events.iter().find(|e| e["event_type"] == "assigned").expect("assigned event");
+    assert_eq!(assigned["data"]["previous"], "carol");
+    assert_eq!(assigned["data"]["assigned_to"], "bob");
+
+    // Least-loaded picks whoever holds fewer open claims, including on create
+    let review: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/columns", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"name": "Review", "auto_assign": ["alice", "bob"], "auto_assign_strategy": "least_loaded"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(review["auto_assign_strategy"], "least_loaded");
+    let claimed = create(r#"{"title": "Busy work"}"#);
+    client
+        .post(format!("/api/v1/boards/{}/tasks/{}/claim?actor=alice", board_id, claimed))
+        .header(auth.clone())
+        .dispatch();
+    let id = create(&format!(r#"{{"title": "Needs review", "column_id": "{}"}}"#, review["id"].as_str().unwrap()));
+    let task: serde_json::Value =
+        client.get(format!("/api/v1/boards/{}/tasks/{}", board_id, id)).dispatch().into_json().unwrap();
+    assert_eq!(task["assigned_to"], "bob");
+
+    // An empty list turns the rule off
+    let col: serde_json::Value = client
+        .patch(format!("/api/v1/boards/{}/columns/{}", board_id, doing))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"auto_assign": []}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(col["auto_assign"], serde_json::json!([]));
+    assert!(move_to(&create(r#"{"title": "Manual"}"#), &doing)["assigned_to"].is_null());
+}
+
+#[test]
+fn test_http_update_column_no_auth() {
+    let client = test_client();
+    let (board_id, _key) = create_test_board(&client, "Col No Auth");
+
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let col_id = board["columns"][0]["id"].as_str().unwrap();
+
+    // Try without auth — should fail
+    let resp = client
+        .patch(format!("/api/v1/boards/{}/columns/{}", board_id, col_id))
+        .header(ContentType::JSON)
+        .body(r#"{"name": "Nope"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+}
+
+#[test]
+fn test_http_delete_empty_column() {
+    let client = test_client();
+    let (board_id, key) = create_test_board(&client, "Col Delete Test");
+
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    // Board has 3 columns: To Do, In Progress, Done. Delete the middle one (no tasks).
+    let col_id = board["columns"][1]["id"].as_str().unwrap();
+
+    let resp = client
+        .delete(format!("/api/v1/boards/{}/columns/{}", board_id, col_id))
+        .header(Header::new("Authorization", format!("Bearer {}", key)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["deleted"], true);
+
+    // Verify board now has 2 columns
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(board["columns"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_http_delete_column_with_tasks_rejected() {
+    let client = test_client();
+    let (board_id, key) = create_test_board(&client, "Col Delete Tasks");
+
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let col_id = board["columns"][0]["id"].as_str().unwrap();
+
+    // Add a task to the first column
+    client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {}", key)))
+        .body(format!(
+            r#"{{"title": "Block Delete", "column_id": "{}"}}"#,
+            col_id
+        ))
+        .dispatch();
+
+    // Try to delete — should fail with 409
+    let resp = client
+        .delete(format!("/api/v1/boards/{}/columns/{}", board_id, col_id))
+        .header(Header::new("Authorization", format!("Bearer {}", key)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["code"], "COLUMN_NOT_EMPTY");
+}
+
+#[test]
+fn test_http_delete_last_column_rejected() {
+    let client = test_client();
+
+    // Create a board with just 1 column
+    let resp = client
+        .post("/api/v1/boards")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "Single Col", "columns": ["Only"]}"#)
+        .dispatch();
+    let body: serde_json::Value = resp.into_json().unwrap();
+    let board_id = body["id"].as_str().unwrap();
+    let key = body["manage_key"].as_str().unwrap();
+    let col_id = body["columns"][0]["id"].as_str().unwrap();
+
+    // Try to delete the only column — should fail with 409
+    let resp = client
+        .delete(format!("/api/v1/boards/{}/columns/{}", board_id, col_id))
+        .header(Header::new("Authorization", format!("Bearer {}", key)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["code"], "LAST_COLUMN");
+}
+
+#[test]
+fn test_http_reorder_columns() {
+    let client = test_client();
+    let (board_id, key) = create_test_board(&client, "Col Reorder Test");
+
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let cols = board["columns"].as_array().unwrap();
+    // Original order: To Do (0), In Progress (1), Done (2)
+    let id0 = cols[0]["id"].as_str().unwrap().to_string();
+    let id1 = cols[1]["id"].as_str().unwrap().to_string();
+    let id2 = cols[2]["id"].as_str().unwrap().to_string();
+
+    // Reorder: Done, To Do, In Progress
+    let resp = client
+        .post(format!("/api/v1/boards/{}/columns/reorder", board_id))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {}", key)))
+        .body(serde_json::json!({ "column_ids": [id2, id0, id1] }).to_string())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let reordered: Vec<serde_json::Value> = resp.into_json().unwrap();
+    assert_eq!(reordered[0]["name"], "Done");
+    assert_eq!(reordered[0]["position"], 0);
+    assert_eq!(reordered[1]["name"], "To Do");
+    assert_eq!(reordered[1]["position"], 1);
+    assert_eq!(reordered[2]["name"], "In Progress");
+    assert_eq!(reordered[2]["position"], 2);
+}
+
+#[test]
+fn test_http_reorder_columns_wrong_count() {
+    let client = test_client();
+    let (board_id, key) = create_test_board(&client, "Col Reorder Bad");
+
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let cols = board["columns"].as_array().unwrap();
+    let id0 = cols[0]["id"].as_str().unwrap().to_string();
+
+    // Send only 1 of 3 column IDs
+    let resp = client
+        .post(format!("/api/v1/boards/{}/columns/reorder", board_id))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {}", key)))
+        .body(serde_json::json!({ "column_ids": [id0] }).to_string())
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["code"], "INVALID_COLUMN_LIST");
+}
+
+// ============ Update Board Settings ============
+
+#[test]
+fn test_http_update_board() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Settings Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    // Update name and description
+    let resp = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"name": "Updated Name", "description": "New desc", "is_public": true}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["name"], "Updated Name");
+    assert_eq!(body["description"], "New desc");
+    assert_eq!(body["is_public"], true);
+}
+
+#[test]
+fn test_http_quick_done_settings() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Quick Done Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    // Board should start with no quick_done settings
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["quick_done_column_id"], serde_json::Value::Null);
+    assert_eq!(body["quick_done_auto_archive"], false);
+
+    // Get the first column's ID
+    let first_col_id = body["columns"][0]["id"].as_str().unwrap().to_string();
+
+    // Set quick_done_column_id and auto_archive
+    let resp = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(format!(r#"{{"quick_done_column_id": "{}", "quick_done_auto_archive": true}}"#, first_col_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["quick_done_column_id"], first_col_id);
+    assert_eq!(body["quick_done_auto_archive"], true);
+
+    // Clear quick_done_column_id by sending empty string
+    let resp = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"quick_done_column_id": ""}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["quick_done_column_id"], serde_json::Value::Null);
+    // auto_archive should still be true
+    assert_eq!(body["quick_done_auto_archive"], true);
+
+    // Invalid column ID should be rejected
+    let resp = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"quick_done_column_id": "nonexistent-col"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+}
+
+#[test]
+fn test_http_update_board_empty_name_rejected() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Empty Name Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    let resp = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"name": "  "}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+}
+
+#[test]
+fn test_http_update_board_no_auth() {
+    let client = test_client();
+    let (board_id, _) = create_test_board(&client, "No Auth Update");
+
+    let resp = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .body(r#"{"name": "Hacked"}"#)
+        .dispatch();
+    assert!(resp.status() == Status::Unauthorized || resp.status() == Status::Forbidden);
+}
+
+#[test]
+fn test_http_board_retention_policy() {
+    let (url, hooks_rx) = spawn_hook_receiver();
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Retention Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    let patch = |body: &str| {
+        client
+            .patch(format!("/api/v1/boards/{}", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(body)
+            .dispatch()
+    };
+    let resp = patch(r#"{"archived_task_retention_days": -1}"#);
+    assert_eq!(resp.status(), Status::BadRequest);
+    let resp = patch(r#"{"archived_task_retention_days": 90, "event_retention_days": 30}"#);
+    assert_eq!(resp.status(), Status::Ok);
+    let board: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(board["archived_task_retention_days"], 90);
+    assert_eq!(board["event_retention_days"], 30);
+
+    client
+        .post(format!("/api/v1/boards/{}/webhooks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(format!(r#"{{"url": "{}", "events": ["board.retention_purged"]}}"#, url))
+        .dispatch();
+
+    let mut ids = Vec::new();
+    for title in ["Old archived", "Fresh archived", "Active"] {
+        let task: serde_json::Value = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "{}"}}"#, title))
+            .dispatch()
+            .into_json()
+            .unwrap();
+        ids.push(task["id"].as_str().unwrap().to_string());
+    }
+    for id in &ids[..2] {
+        client
+            .post(format!("/api/v1/boards/{}/tasks/{}/archive", board_id, id))
+            .header(auth.clone())
+            .dispatch();
+    }
+    client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, ids[2]))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "Old but kept"}"#)
+        .dispatch();
+
+    // Age the first archive and all of the active task's history past the windows
+    let pool = client.rocket().state::<kanban::db::DbPool>().unwrap();
+    let conn = pool.get().unwrap();
+    conn.execute(
+        "UPDATE tasks SET archived_at = datetime('now', '-100 days') WHERE id = ?1",
+        rusqlite::params![ids[0]],
+    )
+    .unwrap();
+    conn.execute(
+        "UPDATE task_events SET created_at = datetime('now', '-40 days') WHERE task_id = ?1",
+        rusqlite::params![ids[2]],
+    )
+    .unwrap();
+    drop(conn);
+
+    // Webhook delivery spawns onto the current runtime, as it does inside the job
+    let runtime = rocket::tokio::runtime::Runtime::new().unwrap();
+    let _guard = runtime.enter();
+    let bus = client.rocket().state::<kanban::events::EventBus>().unwrap();
+    let purges = kanban::retention::run(pool, bus).unwrap();
+    assert_eq!(purges.len(), 1);
+    assert_eq!(purges[0].archived_task_ids, vec![ids[0].clone()]);
+    assert_eq!(purges[0].events_pruned, 1);
+
+    let (_, payload) = hooks_rx.recv_timeout(Duration::from_secs(5)).expect("purge event delivered");
+    assert_eq!(payload["event"], "board.retention_purged");
+    assert_eq!(payload["data"]["archived_tasks_purged"], 1);
+    assert_eq!(payload["data"]["events_pruned"], 1);
+
+    let resp = client.get(format!("/api/v1/boards/{}/tasks/{}", board_id, ids[0])).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+    let resp = client.get(format!("/api/v1/boards/{}/tasks/{}", board_id, ids[1])).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let events: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/boards/{}/tasks/{}/events", board_id, ids[2]))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["event_type"], "comment");
+
+    // Nothing left to purge; clearing the policy stops future runs
+    assert!(kanban::retention::run(pool, bus).unwrap().is_empty());
+    let board: serde_json::Value =
+        patch(r#"{"archived_task_retention_days": 0, "event_retention_days": 0}"#).into_json().unwrap();
+    assert!(board["archived_task_retention_days"].is_null());
+    assert!(board["event_retention_days"].is_null());
+}
+
+// ============ Task Archive / Unarchive ============
+
+#[test]
+fn test_http_task_archive_unarchive() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Archive Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    // Get first column
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let col_id = board["columns"][0]["id"].as_str().unwrap();
+
+    // Create a task
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(serde_json::json!({"title": "Archivable", "column_id": col_id}).to_string())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let task: serde_json::Value = resp.into_json().unwrap();
+    let task_id = task["id"].as_str().unwrap();
+    assert!(task["archived_at"].is_null());
+
+    // Archive it
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/archive", board_id, task_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let archived: serde_json::Value = resp.into_json().unwrap();
+    assert!(archived["archived_at"].is_string());
+
+    // Archived tasks should be hidden from default list
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks", board_id))
+        .dispatch();
+    let tasks: Vec<serde_json::Value> = resp.into_json().unwrap();
+    assert!(tasks.iter().all(|t| t["id"] != task_id));
+
+    // But visible with archived=true
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks?archived=true", board_id))
+        .dispatch();
+    let tasks: Vec<serde_json::Value> = resp.into_json().unwrap();
+    assert!(tasks.iter().any(|t| t["id"] == task_id));
+
+    // Unarchive it
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/unarchive", board_id, task_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let unarchived: serde_json::Value = resp.into_json().unwrap();
+    assert!(unarchived["archived_at"].is_null());
+
+    // Now visible in default list again
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks", board_id))
+        .dispatch();
+    let tasks: Vec<serde_json::Value> = resp.into_json().unwrap();
+    assert!(tasks.iter().any(|t| t["id"] == task_id));
+}
+
+#[test]
+fn test_http_task_archive_no_auth() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Archive NoAuth");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let col_id = board["columns"][0]["id"].as_str().unwrap();
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(serde_json::json!({"title": "NoAuth Archive", "column_id": col_id}).to_string())
+        .dispatch();
+    let task: serde_json::Value = resp.into_json().unwrap();
+    let task_id = task["id"].as_str().unwrap();
+
+    // Try archive without auth
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/archive", board_id, task_id))
+        .dispatch();
+    assert!(resp.status() == Status::Unauthorized || resp.status() == Status::Forbidden);
+}
+
+#[test]
+fn test_http_trash_restores_attachments_and_subtasks() {
+    let client = test_client_with_features(
+        kanban::features::FeatureFlags::default().with(kanban::features::ATTACHMENTS),
+    );
+    let (board_id, manage_key) = create_test_board(&client, "Restore Everything");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let create = |body: String| -> serde_json::Value {
+        client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(body)
+            .dispatch()
+            .into_json()
+            .unwrap()
+    };
+    let parent = create(r#"{"title": "Epic"}"#.to_string());
+    let parent_id = parent["id"].as_str().unwrap();
+    let child = create(format!(r#"{{"title": "Step", "parent_task_id": "{}"}}"#, parent_id));
+    let child_id = child["id"].as_str().unwrap();
+    let task_url = format!("/api/v1/boards/{}/tasks/{}", board_id, parent_id);
+
+    let (multipart, body) = multipart_file("spec.txt", "text/plain", b"the plan");
+    let resp = client
+        .post(format!("{}/attachments", task_url))
+        .header(multipart)
+        .header(auth.clone())
+        .body(body)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    for message in ["first", "second"] {
+        client
+            .post(format!("{}/comment", task_url))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(serde_json::json!({"message": message, "actor_name": "agent-1"}).to_string())
+            .dispatch();
+    }
+    let resp = client
+        .post(format!("{}/comments/collapse", task_url))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"keep": 1, "actor_name": "summarizer"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.post(format!("{}/lock?actor=Alice&ttl=600", task_url)).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client.delete(&task_url).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let child: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/tasks/{}", board_id, child_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert!(child["parent_task_id"].is_null());
+
+    let resp = client
+        .post(format!("{}/restore", task_url))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let task: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(task["attachment_count"], 1);
+
+    let attachments: serde_json::Value =
+        client.get(format!("{}/attachments", task_url)).dispatch().into_json().unwrap();
+    assert_eq!(attachments[0]["filename"], "spec.txt");
+    let resp = client.get(attachments[0]["url"].as_str().unwrap()).dispatch();
+    assert_eq!(resp.into_string().unwrap(), "the plan");
+    let transcripts: serde_json::Value =
+        client.get(format!("{}/transcripts", task_url)).dispatch().into_json().unwrap();
+    assert_eq!(transcripts.as_array().unwrap().len(), 1);
+    assert_eq!(transcripts[0]["comment_count"], 1);
+    let child: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/tasks/{}", board_id, child_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(child["parent_task_id"], parent_id);
+    let locked_by: String = client
+        .rocket()
+        .state::<kanban::db::DbPool>()
+        .unwrap()
+        .get()
+        .unwrap()
+        .query_row("SELECT locked_by FROM task_locks WHERE task_id = ?1", [parent_id], |row| row.get(0))
+        .unwrap();
+    assert_eq!(locked_by, "Alice");
+}
+
+#[test]
+fn test_http_task_trash_and_restore() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Trash Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    let mut ids = Vec::new();
+    for title in ["Keep me", "Oops"] {
+        let task: serde_json::Value = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "{}", "labels": ["x"], "metadata": {{"n": 1}}}}"#, title))
+            .dispatch()
+            .into_json()
+            .unwrap();
+        ids.push(task["id"].as_str().unwrap().to_string());
+    }
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, ids[1]))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "zeppelin notes", "actor_name": "Tester"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    client
+        .post(format!("/api/v1/boards/{}/dependencies", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(format!(r#"{{"blocker_task_id": "{}", "blocked_task_id": "{}"}}"#, ids[0], ids[1]))
+        .dispatch();
+
+    let resp = client
+        .delete(format!("/api/v1/boards/{}/tasks/{}?actor=Cleaner", board_id, ids[1]))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["trashed"], true);
+
+    let tasks: Vec<serde_json::Value> =
+        client.get(format!("/api/v1/boards/{}/tasks", board_id)).dispatch().into_json().unwrap();
+    assert_eq!(tasks.len(), 1);
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks/{}", board_id, ids[1]))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    // Trash needs the manage key
+    let resp = client.get(format!("/api/v1/boards/{}/trash", board_id)).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let trash: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/trash", board_id))
+        .header(auth.clone())
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(trash["retention_days"], 30);
+    assert_eq!(trash["tasks"].as_array().unwrap().len(), 1);
+    assert_eq!(trash["tasks"][0]["id"], ids[1].as_str());
+    assert_eq!(trash["tasks"][0]["deleted_by"], "Cleaner");
+    assert_eq!(trash["tasks"][0]["task"]["title"], "Oops");
+    assert!(trash["tasks"][0]["purge_at"].is_string());
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/restore", board_id, ids[1]))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let task: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(task["title"], "Oops");
+    assert_eq!(task["labels"], serde_json::json!(["x"]));
+    assert_eq!(task["metadata"]["n"], 1);
+    assert_eq!(task["comment_count"], 1);
+
+    // Comments and dependencies come back with it, and the trash is empty
+    let deps: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/boards/{}/dependencies", board_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(deps.len(), 1);
+    let found: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/tasks/search?q=zeppelin", board_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(found["total"], 1);
+    let trash: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/trash", board_id))
+        .header(auth.clone())
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert!(trash["tasks"].as_array().unwrap().is_empty());
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/restore", board_id, ids[1]))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    // Batch deletes go to the trash too
+    client
+        .post(format!("/api/v1/boards/{}/tasks/batch", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(serde_json::json!({"operations": [{"action": "delete", "task_ids": ids}]}).to_string())
+        .dispatch();
+    let trash: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/trash", board_id))
+        .header(auth.clone())
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(trash["tasks"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_http_task_numbers_prefix_and_renumber() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Numbered Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    let mut ids = Vec::new();
+    for title in ["First", "Second", "Third"] {
+        let task: serde_json::Value = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "{}"}}"#, title))
+            .dispatch()
+            .into_json()
+            .unwrap();
+        ids.push(task["id"].as_str().unwrap().to_string());
+    }
+    let task: serde_json::Value =
+        client.get(format!("/api/v1/boards/{}/tasks/{}", board_id, ids[2])).dispatch().into_json().unwrap();
+    assert_eq!(task["number"], 3);
+    assert_eq!(task["key"], "#3");
+
+    // Invalid prefixes are rejected
+    let resp = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"task_prefix": "no spaces"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+
+    let board: serde_json::Value = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"task_prefix": "OPS-"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(board["task_prefix"], "OPS-");
+    assert_eq!(board["next_task_number"], 4);
+
+    let resolve = |key: &str| {
+        client
+            .get(format!("/api/v1/boards/{}/tasks/resolve?key={}", board_id, key.replace('#', "%23")))
+            .dispatch()
+    };
+    let task: serde_json::Value = resolve("ops-2").into_json().unwrap();
+    assert_eq!(task["id"], ids[1].as_str());
+    assert_eq!(task["key"], "OPS-2");
+    // Old default-prefix key still resolves
+    let task: serde_json::Value = resolve("#3").into_json().unwrap();
+    assert_eq!(task["id"], ids[2].as_str());
+
+    // Drop the first task, then close the gap
+    client
+        .delete(format!("/api/v1/boards/{}/tasks/{}", board_id, ids[0]))
+        .header(auth.clone())
+        .dispatch();
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/renumber", board_id))
+        .header(ContentType::JSON)
+        .body("{}")
+        .dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/renumber", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"order": "alphabetical"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let result: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks/renumber", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body("{}")
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(result["renumbered"], 2);
+    assert_eq!(result["next_number"], 3);
+    assert_eq!(result["aliases_recorded"], 2);
+
+    let task: serde_json::Value = resolve("OPS-1").into_json().unwrap();
+    assert_eq!(task["id"], ids[1].as_str());
+    // OPS-3 is no longer anyone's current key, so the alias answers
+    let task: serde_json::Value = resolve("OPS-3").into_json().unwrap();
+    assert_eq!(task["id"], ids[2].as_str());
+    assert_eq!(task["key"], "OPS-2");
+    assert_eq!(resolve("OPS-99").status(), Status::NotFound);
+
+    // New tasks continue from the re-sequenced counter
+    let task: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Fourth"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(task["key"], "OPS-3");
+}
+
+#[test]
+fn test_http_board_activity_feed() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Activity Feed Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let col_id = board["columns"][0]["id"].as_str().unwrap();
+
+    // Create a task (generates a task.created event)
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(serde_json::json!({"title": "Activity Task", "column_id": col_id, "actor_name": "TestBot"}).to_string())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let task: serde_json::Value = resp.into_json().unwrap();
+    let task_id = task["id"].as_str().unwrap();
+
+    // Add a comment (generates a task.comment event)
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(serde_json::json!({"message": "Test comment", "actor_name": "TestBot"}).to_string())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Fetch activity feed — should have at least 2 events
+    let resp = client
+        .get(format!("/api/v1/boards/{}/activity", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let activity: Vec<serde_json::Value> = resp.into_json().unwrap();
+    assert!(activity.len() >= 2, "Expected at least 2 events, got {}", activity.len());
+
+    // Should contain both event types
+    let types: Vec<&str> = activity.iter().map(|e| e["event_type"].as_str().unwrap()).collect();
+    assert!(types.contains(&"comment"), "Should have comment event");
+    assert!(types.contains(&"created"), "Should have created event");
+
+    // All events should reference our task
+    for event in &activity {
+        assert_eq!(event["task_title"], "Activity Task");
+        assert!(!event["task_id"].as_str().unwrap().is_empty());
+    }
+
+    // --- Enrichment checks ---
+    // Created events should have a task snapshot
+    let created_event = activity.iter().find(|e| e["event_type"] == "created").unwrap();
+    assert!(created_event.get("task").is_some(), "Created event should have task snapshot");
+    let task_snapshot = &created_event["task"];
+    assert_eq!(task_snapshot["title"], "Activity Task");
+    assert_eq!(task_snapshot["id"], task_id);
+    assert!(!task_snapshot["column_id"].as_str().unwrap().is_empty());
+    // Created events should NOT have recent_comments
+    assert!(created_event.get("recent_comments").is_none(), "Created event should not have recent_comments");
+
+    // Comment events should have both task snapshot and recent_comments
+    let comment_event = activity.iter().find(|e| e["event_type"] == "comment").unwrap();
+    assert!(comment_event.get("task").is_some(), "Comment event should have task snapshot");
+    assert_eq!(comment_event["task"]["title"], "Activity Task");
+    let recent = comment_event["recent_comments"].as_array().unwrap();
+    assert!(!recent.is_empty(), "Comment event should have recent_comments");
+    assert_eq!(recent[0]["message"], "Test comment");
+    assert_eq!(recent[0]["actor"], "TestBot");
+
+    // Move the task (generates a moved event) — should NOT be enriched
+    let second_col_id = board["columns"][1]["id"].as_str().unwrap();
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}", board_id, task_id, second_col_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Re-fetch activity — moved events should stay lean
+    let resp = client
+        .get(format!("/api/v1/boards/{}/activity", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let activity: Vec<serde_json::Value> = resp.into_json().unwrap();
+    let moved_event = activity.iter().find(|e| e["event_type"] == "moved").unwrap();
+    assert!(moved_event.get("task").is_none(), "Moved event should NOT have task snapshot");
+    assert!(moved_event.get("recent_comments").is_none(), "Moved event should NOT have recent_comments");
+
+    // Test since filter — use a future timestamp to get 0 results
+    let resp = client
+        .get(format!("/api/v1/boards/{}/activity?since=2099-01-01T00:00:00", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let activity: Vec<serde_json::Value> = resp.into_json().unwrap();
+    assert_eq!(activity.len(), 0);
+
+    // Test limit parameter
+    let resp = client
+        .get(format!("/api/v1/boards/{}/activity?limit=1", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let activity: Vec<serde_json::Value> = resp.into_json().unwrap();
+    assert_eq!(activity.len(), 1);
+
+    // --- Seq cursor pagination tests ---
+    // All events should have a seq field (monotonic integer)
+    let resp = client
+        .get(format!("/api/v1/boards/{}/activity", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let activity: Vec<serde_json::Value> = resp.into_json().unwrap();
+    for event in &activity {
+        assert!(event.get("seq").is_some(), "Event should have seq field");
+        assert!(event["seq"].as_i64().unwrap() > 0, "seq should be positive");
+    }
+
+    // Test after= cursor — use seq 0 to get all events
+    let resp = client
+        .get(format!("/api/v1/boards/{}/activity?after=0", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let all_after_0: Vec<serde_json::Value> = resp.into_json().unwrap();
+    assert_eq!(all_after_0.len(), activity.len(), "after=0 should return all events");
+
+    // after= results should be ordered by seq ASC (oldest first)
+    let seqs: Vec<i64> = all_after_0.iter().map(|e| e["seq"].as_i64().unwrap()).collect();
+    for i in 1..seqs.len() {
+        assert!(seqs[i] > seqs[i-1], "after= results should be ordered by seq ASC, got {:?}", seqs);
+    }
+
+    // Test after= with a specific seq — should return only events after that seq
+    let mid_seq = seqs[seqs.len() / 2];
+    let resp = client
+        .get(format!("/api/v1/boards/{}/activity?after={}", board_id, mid_seq))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let partial: Vec<serde_json::Value> = resp.into_json().unwrap();
+    assert!(partial.len() < all_after_0.len(), "after=mid should return fewer events");
+    for event in &partial {
+        assert!(event["seq"].as_i64().unwrap() > mid_seq, "All events should have seq > {}", mid_seq);
+    }
+
+    // Test after= with a very high seq — should return 0 events
+    let resp = client
+        .get(format!("/api/v1/boards/{}/activity?after=999999", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let empty: Vec<serde_json::Value> = resp.into_json().unwrap();
+    assert_eq!(empty.len(), 0, "after=999999 should return no events");
+}
+
+#[test]
+fn test_http_board_json_feed() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Feed Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let board: serde_json::Value = client.get(format!("/api/v1/boards/{}", board_id)).dispatch().into_json().unwrap();
+    let done_col = board["columns"].as_array().unwrap().last().unwrap()["id"].as_str().unwrap().to_string();
+
+    let task: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Ship it", "description": "Release notes", "labels": ["release"], "actor_name": "agent-a"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let task_id = task["id"].as_str().unwrap();
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}", board_id, task_id, done_col))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Public, served as application/feed+json with absolute URLs
+    let resp = client
+        .get(format!("/api/v1/boards/{}/feed.json", board_id))
+        .header(Header::new("Host", "kanban.example"))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(resp.content_type().unwrap().to_string(), "application/feed+json");
+    let feed: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(feed["version"], "https://jsonfeed.org/version/1.1");
+    assert_eq!(feed["title"], "Feed Board");
+    assert_eq!(feed["home_page_url"], format!("http://kanban.example/board/{}", board_id));
+    assert_eq!(feed["feed_url"], format!("http://kanban.example/api/v1/boards/{}/feed.json", board_id));
+
+    // Completion is listed before the creation it follows
+    let items = feed["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["id"], format!("{}:completed", task_id));
+    assert_eq!(items[0]["title"], format!("Completed: {} Ship it", task["key"].as_str().unwrap()));
+    assert_eq!(items[0]["_kanban"]["event"], "completed");
+    assert_eq!(items[0]["_kanban"]["schema_version"], 1);
+    assert!(items[0]["_kanban"]["completed_at"].as_str().unwrap().contains('T'));
+    assert_eq!(items[1]["id"], format!("{}:created", task_id));
+    assert_eq!(items[1]["url"], format!("http://kanban.example/board/{}?task={}", board_id, task_id));
+    assert_eq!(items[1]["content_text"], "Release notes");
+    assert_eq!(items[1]["tags"], serde_json::json!(["release"]));
+    assert_eq!(items[1]["authors"][0]["name"], "agent-a");
+    assert!(items[1]["date_published"].as_str().unwrap().ends_with("+00:00"));
+
+    let feed: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/feed.json?limit=1", board_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(feed["items"].as_array().unwrap().len(), 1);
+
+    let resp = client.get("/api/v1/boards/nope/feed.json").dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[test]
+fn test_http_board_calendar() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Release, Q3");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    for body in [
+        r#"{"title": "Cut release", "due_at": "2026-03-01", "labels": ["release"], "assigned_to": "Sam"}"#,
+        r#"{"title": "Announce; loudly", "due_at": "2026-03-02T15:30:00Z", "assigned_to": "Jordan"}"#,
+        r#"{"title": "No deadline", "labels": ["release"]}"#,
+    ] {
+        client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(body)
+            .dispatch();
+    }
+    let url = format!("/api/v1/boards/{}/calendar.ics", board_id);
+
+    // Public, served as text/calendar
+    let resp = client.get(&url).header(Header::new("Host", "kanban.example")).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(resp.content_type(), Some(ContentType::Calendar));
+    let ics = resp.into_string().unwrap();
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+    assert!(ics.contains("X-WR-CALNAME:Release\\, Q3 — due dates\r\n"));
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+    assert!(ics.contains("DTSTART;VALUE=DATE:20260301\r\nDTEND;VALUE=DATE:20260302\r\n"));
+    assert!(ics.contains("DTSTART:20260302T153000Z\r\n"));
+    assert!(ics.contains(r"Announce\; loudly"));
+    assert!(ics.contains("CATEGORIES:release\r\n"));
+    assert!(ics.contains("DESCRIPTION:Column: "));
+    assert!(ics.contains(&format!("URL:http://kanban.example/board/{}?task=", board_id)));
+
+    let ics = client.get(format!("{}?label=Release", url)).dispatch().into_string().unwrap();
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+    assert!(ics.contains("Cut release"));
+    let ics = client.get(format!("{}?assignee=jordan", url)).dispatch().into_string().unwrap();
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+    assert!(ics.contains("Announce"));
+
+    let resp = client.get("/api/v1/boards/nope/calendar.ics").dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[test]
+fn test_http_board_activity_atom() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Atom & Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let board: serde_json::Value = client.get(format!("/api/v1/boards/{}", board_id)).dispatch().into_json().unwrap();
+    let columns = board["columns"].as_array().unwrap();
+    let task: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Ship <it>", "actor_name": "agent-a"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let task_id = task["id"].as_str().unwrap();
+    client
+        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}", board_id, task_id, columns[1]["id"].as_str().unwrap()))
+        .header(auth.clone())
+        .dispatch();
+    client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "Looks good & ready", "actor_name": "agent-b"}"#)
+        .dispatch();
+    // Claims aren't part of the feed
+    client
+        .post(format!("/api/v1/boards/{}/tasks/{}/claim", board_id, task_id))
+        .header(auth.clone())
+        .header(Header::new("X-Actor", "agent-b"))
+        .dispatch();
+
+    let resp = client
+        .get(format!("/api/v1/boards/{}/activity.atom", board_id))
+        .header(Header::new("Host", "kanban.example"))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(resp.content_type().unwrap().to_string(), "application/atom+xml");
+    let xml = resp.into_string().unwrap();
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">"));
+    assert!(xml.contains("<title>Atom &amp; Board — activity</title>"));
+    assert!(xml.contains(&format!("<id>http://kanban.example/api/v1/boards/{}/activity.atom</id>", board_id)));
+    assert_eq!(xml.matches("<entry>").count(), 3);
+    let key = task["key"].as_str().unwrap();
+    let comment = xml.find(&format!("<title>Comment on {} Ship &lt;it&gt;</title>", key)).unwrap();
+    let moved = xml
+        .find(&format!(
+            "<title>Moved: {} Ship &lt;it&gt; ({} → {})</title>",
+            key,
+            columns[0]["name"].as_str().unwrap(),
+            columns[1]["name"].as_str().unwrap()
+        ))
+        .unwrap();
+    let created = xml.find(&format!("<title>Created: {} Ship &lt;it&gt;</title>", key)).unwrap();
+    assert!(comment < moved && moved < created);
+    assert!(xml.contains("<content type=\"text\">Looks good &amp; ready</content>"));
+    assert!(xml.contains("<author><name>agent-b</name></author>"));
+    assert!(xml.contains(&format!("href=\"http://kanban.example/board/{}?task={}\"", board_id, task_id)));
+
+    let xml = client
+        .get(format!("/api/v1/boards/{}/activity.atom?limit=1", board_id))
+        .dispatch()
+        .into_string()
+        .unwrap();
+    assert_eq!(xml.matches("<entry>").count(), 1);
+}
+
+// ============ Quick Reassign Settings ============
+
+#[test]
+fn test_http_quick_reassign_settings() {
+    let client = test_client();
+    let (board_id, key) = create_test_board(&client, "Quick Reassign Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", key));
+
+    // Get board to find column IDs
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let first_col_id = board["columns"][0]["id"].as_str().unwrap();
+
+    // Initially null
+    assert!(board["quick_reassign_column_id"].is_null());
+    assert!(board["quick_reassign_to"].is_null());
+
+    // Set quick reassign settings
+    let resp = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(format!(r#"{{"quick_reassign_column_id": "{}", "quick_reassign_to": "Jordan"}}"#, first_col_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let board: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(board["quick_reassign_column_id"], first_col_id);
+    assert_eq!(board["quick_reassign_to"], "Jordan");
+
+    // Clear with empty strings
+    let resp = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"quick_reassign_column_id": "", "quick_reassign_to": ""}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let board: serde_json::Value = resp.into_json().unwrap();
+    assert!(board["quick_reassign_column_id"].is_null());
+    assert!(board["quick_reassign_to"].is_null());
+
+    // Invalid column ID should be rejected
+    let resp = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"quick_reassign_column_id": "nonexistent-col"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "INVALID_COLUMN");
+}
+
+// ============ Require Display Name ============
+
+#[test]
+fn test_http_require_display_name() {
+    let client = test_client();
+
+    // Create board with require_display_name enabled
+    let resp = client
+        .post("/api/v1/boards")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "Named Board", "require_display_name": true}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    let board_id = body["id"].as_str().unwrap().to_string();
+    let manage_key = body["manage_key"].as_str().unwrap().to_string();
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    // Verify board setting is returned
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(board["require_display_name"], true);
+
+    // Creating a task without actor_name should fail
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Anonymous Task"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "DISPLAY_NAME_REQUIRED");
+
+    // Creating a task WITH actor_name should succeed
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Named Task", "actor_name": "TestBot"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let task: serde_json::Value = resp.into_json().unwrap();
+    let task_id = task["id"].as_str().unwrap();
+
+    // Commenting without actor_name should fail
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "Anonymous comment"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "DISPLAY_NAME_REQUIRED");
+
+    // Commenting WITH actor_name should succeed
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "Named comment", "actor_name": "TestBot"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Toggling setting off should allow anonymous again
+    let resp = client
+        .patch(format!("/api/v1/boards/{}", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"require_display_name": false}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let board: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(board["require_display_name"], false);
+
+    // Now anonymous task creation should work
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Anonymous OK Now"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+}
+
+#[test]
+fn test_http_comment_mentions() {
+    let client = test_client();
+    let (board_id, key) = create_test_board(&client, "Mentions Test");
+    let auth = Header::new("Authorization", format!("Bearer {}", key));
+
+    // Create a task
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Test mentions"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let task: serde_json::Value = resp.into_json().unwrap();
+    let task_id = task["id"].as_str().unwrap();
+
+    // Post a comment with @mentions
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "Hey @Jordan and @Nanook, please review this", "actor_name": "TestBot"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Post a comment without mentions
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "No mentions here", "actor_name": "TestBot"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Check activity — should show mentions on first comment
+    let resp = client
+        .get(format!("/api/v1/boards/{}/activity?limit=50", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let items: Vec<serde_json::Value> = resp.into_json().unwrap();
+    let comment_events: Vec<&serde_json::Value> = items.iter()
+        .filter(|i| i["event_type"] == "comment")
+        .collect();
+    assert_eq!(comment_events.len(), 2);
+
+    // Find the comment with mentions (check data.mentions)
+    let with_mentions = comment_events.iter()
+        .find(|e| e["data"]["mentions"].is_array())
+        .expect("Should have a comment with mentions");
+    let mentions = with_mentions["mentions"].as_array()
+        .expect("Top-level mentions field should exist");
+    assert_eq!(mentions.len(), 2);
+    assert!(mentions.iter().any(|m| m == "Jordan"));
+    assert!(mentions.iter().any(|m| m == "Nanook"));
+
+    // The other comment should not have mentions
+    let without_mentions = comment_events.iter()
+        .find(|e| !e["data"]["mentions"].is_array())
+        .expect("Should have a comment without mentions");
+    assert!(without_mentions["mentions"].is_null());
+
+    // Filter activity by ?mentioned=Jordan — should return only relevant events
+    let resp = client
+        .get(format!("/api/v1/boards/{}/activity?mentioned=Jordan", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let items: Vec<serde_json::Value> = resp.into_json().unwrap();
+    // Should have at least the comment that mentions Jordan
+    assert!(items.iter().any(|i| i["event_type"] == "comment" && i["data"]["mentions"].is_array()));
+
+    // Filter by ?mentioned=nobody — should return no comment mentions but may return actor-matched events
+    let resp = client
+        .get(format!("/api/v1/boards/{}/activity?mentioned=nobody", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let items: Vec<serde_json::Value> = resp.into_json().unwrap();
+    let mention_comments: Vec<&serde_json::Value> = items.iter()
+        .filter(|i| i["event_type"] == "comment" && i["data"]["mentions"].is_array())
+        .collect();
+    assert_eq!(mention_comments.len(), 0);
+}
+
+#[test]
+fn test_http_mention_inbox() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Inbox Board");
+    let (other_board, other_key) = create_test_board(&client, "Other Inbox Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let comment = |board: &str, key: &str, message: &str| -> (String, serde_json::Value) {
+        let task: serde_json::Value = client
+            .post(format!("/api/v1/boards/{}/tasks", board))
+            .header(ContentType::JSON)
+            .header(Header::new("Authorization", format!("Bearer {}", key)))
+            .body(r#"{"title": "Pinged"}"#)
+            .dispatch()
+            .into_json()
+            .unwrap();
+        let task_id = task["id"].as_str().unwrap().to_string();
+        let comment = client
+            .post(format!("/api/v1/boards/{}/tasks/{}/comment", board, task_id))
+            .header(ContentType::JSON)
+            .header(Header::new("Authorization", format!("Bearer {}", key)))
+            .body(serde_json::json!({"message": message, "actor_name": "Nanook"}).to_string())
+            .dispatch()
+            .into_json()
+            .unwrap();
+        (task_id, comment)
+    };
+    let (_, first) = comment(&board_id, &manage_key, "@Sam can you look?");
+    comment(&board_id, &manage_key, "No ping here");
+    let (third_task, third) = comment(&board_id, &manage_key, "cc @sam and @\"Big Sam\"");
+    comment(&other_board, &other_key, "@Sam elsewhere");
+
+    let inbox_url = format!("/api/v1/boards/{}/inbox/Sam", board_id);
+    let inbox: serde_json::Value = client.get(&inbox_url).dispatch().into_json().unwrap();
+    assert_eq!(inbox["cursor"], 0);
+    assert_eq!(inbox["unread"], 2);
+    let items = inbox["items"].as_array().unwrap();
+    assert_eq!(items[0]["event_id"], first["id"]);
+    assert_eq!(items[0]["message"], "@Sam can you look?");
+    assert_eq!(items[0]["actor"], "Nanook");
+    assert_eq!(items[0]["task_title"], "Pinged");
+    let first_seq = items[0]["seq"].as_i64().unwrap();
+    assert!(items[1]["seq"].as_i64().unwrap() > first_seq);
+    let big: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/inbox/Big%20Sam", board_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(big["unread"], 1);
+    let limited: serde_json::Value = client.get(format!("{}?limit=1", inbox_url)).dispatch().into_json().unwrap();
+    assert_eq!(limited["items"].as_array().unwrap().len(), 1);
+    assert_eq!(limited["unread"], 2);
+
+    // Editing a mention away drops it from the inbox; editing it back restores it
+    client
+        .patch(format!("/api/v1/boards/{}/tasks/{}/comments/{}", board_id, third_task, third["id"].as_str().unwrap()))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "cc @\"Big Sam\""}"#)
+        .dispatch();
+    let inbox: serde_json::Value = client.get(&inbox_url).dispatch().into_json().unwrap();
+    assert_eq!(inbox["unread"], 1);
+    let edited: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/inbox/Big%20Sam", board_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(edited["unread"], 1);
+    client
+        .patch(format!("/api/v1/boards/{}/tasks/{}/comments/{}", board_id, third_task, third["id"].as_str().unwrap()))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "cc @sam again"}"#)
+        .dispatch();
+    let inbox: serde_json::Value = client.get(&inbox_url).dispatch().into_json().unwrap();
+    assert_eq!(inbox["unread"], 2);
+
+    let ack_url = format!("{}/ack", inbox_url);
+    let resp = client.post(&ack_url).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let acked: serde_json::Value = client
+        .post(format!("{}?seq={}", ack_url, first_seq))
+        .header(auth.clone())
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(acked["cursor"], first_seq);
+    assert_eq!(acked["unread"], 1);
+    let acked: serde_json::Value = client.post(&ack_url).header(auth.clone()).dispatch().into_json().unwrap();
+    assert_eq!(acked["unread"], 0);
+    // The cursor only moves forward
+    let acked: serde_json::Value = client.post(format!("{}?seq=0", ack_url)).header(auth.clone()).dispatch().into_json().unwrap();
+    assert_eq!(acked["unread"], 0);
+    assert!(acked["cursor"].as_i64().unwrap() > first_seq);
+
+    let inbox: serde_json::Value =
+        client.get(format!("/api/v1/boards/{}/inbox/sam", other_board)).dispatch().into_json().unwrap();
+    assert_eq!(inbox["unread"], 1);
+}
+
+#[test]
+fn test_http_notification_preferences() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Prefs Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    for assignee in ["Sam", "Jordan"] {
+        let task: serde_json::Value = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(serde_json::json!({"title": "Mentioned", "assigned_to": assignee}).to_string())
+            .dispatch()
+            .into_json()
+            .unwrap();
+        client
+            .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task["id"].as_str().unwrap()))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(r#"{"message": "@Sam heads up", "actor_name": "Nanook"}"#)
+            .dispatch();
+    }
+    let pref_url = format!("/api/v1/boards/{}/notification-preferences/Sam", board_id);
+    let inbox_url = format!("/api/v1/boards/{}/inbox/Sam", board_id);
+
+    let pref: serde_json::Value = client.get(&pref_url).dispatch().into_json().unwrap();
+    assert_eq!(pref["level"], "mentions");
+    assert!(pref["updated_at"].is_null());
+    let inbox: serde_json::Value = client.get(&inbox_url).dispatch().into_json().unwrap();
+    assert_eq!((inbox["level"].as_str(), inbox["unread"].as_i64()), (Some("mentions"), Some(2)));
+
+    let resp = client.put(&pref_url).header(ContentType::JSON).body(r#"{"level": "mute"}"#).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let resp = client
+        .put(&pref_url)
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"level": "sometimes"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+
+    let resp = client
+        .put(&pref_url)
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"level": "assigned"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let pref: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(pref["level"], "assigned");
+    assert!(pref["updated_at"].is_string());
+    let inbox: serde_json::Value = client.get(&inbox_url).dispatch().into_json().unwrap();
+    assert_eq!(inbox["unread"], 1);
+
+    client
+        .put(format!("/api/v1/boards/{}/notification-preferences/sam", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"level": "mute"}"#)
+        .dispatch();
+    let inbox: serde_json::Value = client.get(&inbox_url).dispatch().into_json().unwrap();
+    assert_eq!(inbox["level"], "mute");
+    assert_eq!(inbox["unread"], 0);
+    assert!(inbox["items"].as_array().unwrap().is_empty());
+
+    let resp = client.get(format!("/api/v1/boards/{}/notification-preferences", board_id)).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let prefs: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/notification-preferences", board_id))
+        .header(auth.clone())
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let prefs = prefs.as_array().unwrap();
+    assert_eq!(prefs.len(), 1);
+    assert_eq!((prefs[0]["name"].as_str(), prefs[0]["level"].as_str()), (Some("Sam"), Some("mute")));
+}
+
+#[test]
+fn test_http_email_notifications() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Email Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let subs_url = format!("/api/v1/boards/{}/email-subscriptions", board_id);
+
+    let resp = client
+        .put(format!("{}/Sam", subs_url))
+        .header(ContentType::JSON)
+        .body(r#"{"email": "sam@example.com"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let resp = client
+        .put(format!("{}/Sam", subs_url))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"email": "not an address"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    for (name, email) in [("Sam", "sam@example.com"), ("Jordan", "jordan@example.com")] {
+        let resp = client
+            .put(format!("{}/{}", subs_url, name))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(serde_json::json!({ "email": email }).to_string())
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+    }
+    // The default level only mails mentions; Sam wants assignments too
+    for (name, level) in [("Sam", "all"), ("Jordan", "mute")] {
+        client
+            .put(format!("/api/v1/boards/{}/notification-preferences/{}", board_id, name))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(serde_json::json!({ "level": level }).to_string())
+            .dispatch();
+    }
+
+    let task: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Ship it", "actor_name": "Nanook"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let task_url = format!("/api/v1/boards/{}/tasks/{}", board_id, task["id"].as_str().unwrap());
+    client
+        .post(format!("{}/comment", task_url))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "@Sam @Jordan take a look", "actor_name": "Nanook"}"#)
+        .dispatch();
+    client
+        .patch(&task_url)
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"assigned_to": "Sam", "actor_name": "Nanook"}"#)
+        .dispatch();
+    let due_at = (chrono::Utc::now() + chrono::Duration::hours(2)).format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(serde_json::json!({"title": "Due soon", "assigned_to": "Sam", "due_at": due_at}).to_string())
+        .dispatch();
+
+    let conn = client.rocket().state::<kanban::db::DbPool>().unwrap().get().unwrap();
+    assert_eq!(kanban::email::collect(&conn, None).unwrap(), 4);
+    let kinds: Vec<(String, String)> = conn
+        .prepare("SELECT name, kind FROM email_outbox ORDER BY kind")
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let kinds: Vec<(&str, &str)> = kinds.iter().map(|(n, k)| (n.as_str(), k.as_str())).collect();
+    assert_eq!(
+        kinds,
+        [("Sam", "assignment"), ("Sam", "assignment"), ("Sam", "due_reminder"), ("Sam", "mention")]
+    );
+    assert_eq!(kanban::email::collect(&conn, None).unwrap(), 0);
+    drop(conn);
+
+    let subs: serde_json::Value = client.get(&subs_url).header(auth.clone()).dispatch().into_json().unwrap();
+    assert_eq!(subs.as_array().unwrap().len(), 2);
+    assert_eq!(subs[1]["email"], "sam@example.com");
+    let resp = client.delete(format!("{}/jordan", subs_url)).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.delete(format!("{}/jordan", subs_url)).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    // Reminder bookkeeping goes once the due date passes, or with the board
+    let conn = client.rocket().state::<kanban::db::DbPool>().unwrap().get().unwrap();
+    let reminders = || -> i64 { conn.query_row("SELECT COUNT(*) FROM email_reminders", [], |row| row.get(0)).unwrap() };
+    conn.execute("INSERT INTO email_reminders (task_id, due_at) VALUES ('gone', '2020-01-01T00:00:00Z')", []).unwrap();
+    assert_eq!(reminders(), 2);
+    kanban::email::collect(&conn, None).unwrap();
+    assert_eq!(reminders(), 1);
+    let resp = client
+        .delete(format!("/api/v1/boards/{}?confirm=Email%20Board", board_id))
+        .header(auth)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(reminders(), 0);
+}
+
+#[test]
+fn test_mention_extraction_quoted() {
+    let client = test_client();
+    let (board_id, key) = create_test_board(&client, "Quoted Mentions");
+    let auth = Header::new("Authorization", format!("Bearer {}", key));
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Quoted mention test"}"#)
+        .dispatch();
+    let task: serde_json::Value = resp.into_json().unwrap();
+    let task_id = task["id"].as_str().unwrap();
+
+    // Post comment with quoted mention
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "cc @\"Team Lead\" and @dev-bot", "actor_name": "Tester"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client
+        .get(format!("/api/v1/boards/{}/activity?limit=10", board_id))
+        .dispatch();
+    let items: Vec<serde_json::Value> = resp.into_json().unwrap();
+    let comment = items.iter()
+        .find(|i| i["event_type"] == "comment" && i["data"]["mentions"].is_array())
+        .expect("Should have comment with mentions");
+    let mentions = comment["mentions"].as_array().unwrap();
+    assert_eq!(mentions.len(), 2);
+    assert!(mentions.iter().any(|m| m == "Team Lead"));
+    assert!(mentions.iter().any(|m| m == "dev-bot"));
+}
+
+#[test]
+fn test_http_require_display_name_all_endpoints() {
+    let client = test_client();
+
+    // Create board with require_display_name enabled
+    let resp = client
+        .post("/api/v1/boards")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "Display Name Audit", "require_display_name": true}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    let board_id = body["id"].as_str().unwrap().to_string();
+    let manage_key = body["manage_key"].as_str().unwrap().to_string();
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    // Get column ID for moves
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let columns = board["columns"].as_array().unwrap();
+    let col_id = columns[0]["id"].as_str().unwrap().to_string();
+    let col2_id = columns[1]["id"].as_str().unwrap().to_string();
+
+    // Create a task WITH actor_name (should succeed)
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
         .header(auth.clone())
+        .body(r#"{"title": "Test Task", "actor_name": "TestBot"}"#)
         .dispatch();
-    assert_eq!(resp.status(), Status::Conflict);
+    assert_eq!(resp.status(), Status::Ok);
+    let task: serde_json::Value = resp.into_json().unwrap();
+    let task_id = task["id"].as_str().unwrap().to_string();
 
-    // Release
+    // UPDATE task without actor_name → should fail
     let resp = client
-        .post(format!(
-            "/api/v1/boards/{}/tasks/{}/release",
-            board_id, task_id
+        .patch(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Updated Title"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "DISPLAY_NAME_REQUIRED");
+
+    // UPDATE task with actor_name → should succeed
+    let resp = client
+        .patch(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Updated Title", "actor_name": "TestBot"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // MOVE task without actor → should fail
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}", board_id, task_id, col2_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "DISPLAY_NAME_REQUIRED");
+
+    // MOVE task with actor → should succeed
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}?actor=TestBot", board_id, task_id, col2_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // CLAIM task without agent → should fail
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/claim", board_id, task_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "DISPLAY_NAME_REQUIRED");
+
+    // CLAIM task with agent → should succeed
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/claim?actor=TestBot", board_id, task_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // RELEASE task without actor → should fail
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/release", board_id, task_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "DISPLAY_NAME_REQUIRED");
+
+    // RELEASE task with actor → should succeed
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/release?actor=TestBot", board_id, task_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // ARCHIVE task without actor → should fail
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/archive", board_id, task_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "DISPLAY_NAME_REQUIRED");
+
+    // ARCHIVE task with actor → should succeed
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/archive?actor=TestBot", board_id, task_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // UNARCHIVE task without actor → should fail
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/unarchive", board_id, task_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "DISPLAY_NAME_REQUIRED");
+
+    // UNARCHIVE task with actor → should succeed
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/unarchive?actor=TestBot", board_id, task_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // DELETE task without actor → should fail
+    let resp = client
+        .delete(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "DISPLAY_NAME_REQUIRED");
+
+    // DELETE task with actor → should succeed
+    let resp = client
+        .delete(format!("/api/v1/boards/{}/tasks/{}?actor=TestBot", board_id, task_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+}
+
+#[test]
+fn test_http_list_tasks_updated_before_filter() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Stale Filter");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    // Create two tasks
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Task A", "priority": 1}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Task B", "priority": 2}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Without filter → both tasks returned
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let tasks: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(tasks.as_array().unwrap().len(), 2);
+
+    // With updated_before far in the future → both tasks returned
+    let resp = client
+        .get(format!(
+            "/api/v1/boards/{}/tasks?updated_before=2099-12-31T23:59:59",
+            board_id
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let tasks: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(tasks.as_array().unwrap().len(), 2);
+
+    // With updated_before far in the past → no tasks returned
+    let resp = client
+        .get(format!(
+            "/api/v1/boards/{}/tasks?updated_before=2000-01-01T00:00:00",
+            board_id
         ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let tasks: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(tasks.as_array().unwrap().len(), 0);
+}
+
+// ============ Stale Query Parameter ============
+
+#[test]
+fn test_http_list_tasks_stale_filter() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Stale Filter Minutes");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    // Create a task
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
         .header(auth.clone())
+        .body(r#"{"title": "Fresh Task", "priority": 1}"#)
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let body: serde_json::Value = resp.into_json().unwrap();
-    assert!(body["claimed_by"].is_null());
+
+    // stale=1 (1 minute) — task was just created, so it's NOT stale yet
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks?stale=1", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let tasks: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(tasks.as_array().unwrap().len(), 0, "freshly created task should not be stale");
+
+    // stale=0 should return error (must be positive)
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks?stale=0", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "INVALID_STALE");
+
+    // stale=-5 should return error
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks?stale=-5", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+
+    // stale=999999 (tasks older than 999999 min) — fresh task is NOT that old
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks?stale=999999", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let tasks: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(tasks.as_array().unwrap().len(), 0, "fresh task should not be stale even with large window");
+
+    // Verify without stale filter — task is there
+    let resp = client
+        .get(format!("/api/v1/boards/{}/tasks", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let tasks: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(tasks.as_array().unwrap().len(), 1, "task exists without stale filter");
 }
 
-// ============ Comments ============
+// ============ Reorder & Batch Actor Attribution ============
 
 #[test]
-fn test_http_comments() {
+fn test_http_reorder_and_batch_actor_attribution() {
     let client = test_client();
-    let (board_id, manage_key) = create_test_board(&client, "Comment Test");
+    let (board_id, manage_key) = create_test_board(&client, "Actor Attribution");
     let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
 
-    // Create task
+    // Create a task
     let resp = client
         .post(format!("/api/v1/boards/{}/tasks", board_id))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"title": "Commentable Task"}"#)
+        .body(r#"{"title": "Reorder Me", "actor_name": "TestUser"}"#)
         .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
     let task: serde_json::Value = resp.into_json().unwrap();
     let task_id = task["id"].as_str().unwrap();
 
-    // Post a comment
+    // Get the column IDs
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let col_id = board["columns"][0]["id"].as_str().unwrap();
+
+    // Reorder with actor param
     let resp = client
         .post(format!(
-            "/api/v1/boards/{}/tasks/{}/comment",
+            "/api/v1/boards/{}/tasks/{}/reorder?actor=ReorderBot",
             board_id, task_id
         ))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"message": "Hello from tests!", "actor_name": "TestBot"}"#)
+        .body(format!(r#"{{"position": 0, "column_id": "{}"}}"#, col_id))
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let body: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(body["event_type"], "comment");
-    assert_eq!(body["actor"], "TestBot");
 
-    // Empty comment rejected
+    // Check activity for reorder event with correct actor
+    let resp = client
+        .get(format!("/api/v1/boards/{}/activity?limit=10", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let activity: serde_json::Value = resp.into_json().unwrap();
+    let events = activity.as_array().unwrap();
+    let reorder_event = events.iter().find(|e| e["event_type"] == "reordered");
+    assert!(reorder_event.is_some(), "Should have a reordered event");
+    assert_eq!(reorder_event.unwrap()["actor"], "ReorderBot");
+
+    // Create another task for batch test
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Batch Me", "actor_name": "TestUser"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let task2: serde_json::Value = resp.into_json().unwrap();
+    let task2_id = task2["id"].as_str().unwrap();
+
+    // Batch update with actor
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/batch", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(format!(
+            r#"{{"actor_name": "BatchBot", "operations": [{{"action": "update", "task_ids": ["{}"], "priority": 3}}]}}"#,
+            task2_id
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Check activity for batch update event with correct actor
+    let resp = client
+        .get(format!("/api/v1/boards/{}/activity?limit=20", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let activity: serde_json::Value = resp.into_json().unwrap();
+    let events = activity.as_array().unwrap();
+    let batch_update_event = events.iter().find(|e| {
+        e["event_type"] == "updated" && e["actor"] == "BatchBot"
+    });
+    assert!(batch_update_event.is_some(), "Should have a batch updated event with BatchBot actor");
+
+    // Reorder without actor param → defaults to "anonymous"
     let resp = client
         .post(format!(
-            "/api/v1/boards/{}/tasks/{}/comment",
+            "/api/v1/boards/{}/tasks/{}/reorder",
             board_id, task_id
         ))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"message": ""}"#)
+        .body(format!(r#"{{"position": 1, "column_id": "{}"}}"#, col_id))
         .dispatch();
-    assert_eq!(resp.status(), Status::BadRequest);
+    assert_eq!(resp.status(), Status::Ok);
 
-    // Read events (no auth needed)
+    // Batch without actor → defaults to "batch"
     let resp = client
-        .get(format!(
-            "/api/v1/boards/{}/tasks/{}/events",
-            board_id, task_id
+        .post(format!("/api/v1/boards/{}/tasks/batch", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(format!(
+            r#"{{"operations": [{{"action": "update", "task_ids": ["{}"], "priority": 1}}]}}"#,
+            task2_id
         ))
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let events: serde_json::Value = resp.into_json().unwrap();
-    let events_arr = events.as_array().unwrap();
-    // Should have at least: created + comment
-    let comment_events: Vec<_> = events_arr
-        .iter()
-        .filter(|e| e["event_type"] == "comment")
-        .collect();
-    assert_eq!(comment_events.len(), 1);
-}
 
-// ============ Archive / Unarchive ============
+    // Verify activity has both defaults
+    let resp = client
+        .get(format!("/api/v1/boards/{}/activity?limit=30", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let activity: serde_json::Value = resp.into_json().unwrap();
+    let events = activity.as_array().unwrap();
+    let anon_reorder = events.iter().find(|e| e["event_type"] == "reordered" && e["actor"] == "anonymous");
+    assert!(anon_reorder.is_some(), "Reorder without actor should default to anonymous");
+    let batch_default = events.iter().find(|e| e["event_type"] == "updated" && e["actor"] == "batch");
+    assert!(batch_default.is_some(), "Batch without actor should default to batch");
+}
 
 #[test]
-fn test_http_archive_board() {
+fn test_http_batch_emits_aggregated_events() {
+    let (url, hooks_rx) = spawn_hook_receiver();
     let client = test_client();
-    let (board_id, manage_key) = create_test_board(&client, "Archive HTTP Test");
+    let (board_id, manage_key) = create_test_board(&client, "Batch Events");
     let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
 
-    // Archive
     let resp = client
-        .post(format!("/api/v1/boards/{}/archive", board_id))
+        .post(format!("/api/v1/boards/{}/webhooks", board_id))
+        .header(ContentType::JSON)
         .header(auth.clone())
+        .body(format!(
+            r#"{{"url": "{}", "events": ["task.moved", "task.updated", "task.batch_moved", "task.batch_updated"]}}"#,
+            url
+        ))
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
 
-    // Double-archive should conflict
+    let board: serde_json::Value = client.get(format!("/api/v1/boards/{}", board_id)).dispatch().into_json().unwrap();
+    let done_col = board["columns"][2]["id"].as_str().unwrap().to_string();
+    let ids: Vec<String> = (0..3)
+        .map(|i| {
+            let task: serde_json::Value = client
+                .post(format!("/api/v1/boards/{}/tasks", board_id))
+                .header(ContentType::JSON)
+                .header(auth.clone())
+                .body(format!(r#"{{"title": "Batch {}"}}"#, i))
+                .dispatch()
+                .into_json()
+                .unwrap();
+            task["id"].as_str().unwrap().to_string()
+        })
+        .collect();
+
     let resp = client
-        .post(format!("/api/v1/boards/{}/archive", board_id))
+        .post(format!("/api/v1/boards/{}/tasks/batch", board_id))
+        .header(ContentType::JSON)
         .header(auth.clone())
+        .body(
+            serde_json::json!({"operations": [
+                {"action": "move", "task_ids": ids, "column_id": done_col},
+                {"action": "update", "task_ids": [ids[0], ids[1]], "priority": 3},
+            ]})
+            .to_string(),
+        )
         .dispatch();
-    assert_eq!(resp.status(), Status::Conflict);
+    assert_eq!(resp.status(), Status::Ok);
 
-    // Write to archived board should fail (409 Conflict — board is archived)
+    // One delivery per operation, not one per task
+    let mut deliveries: Vec<serde_json::Value> = (0..2)
+        .map(|_| hooks_rx.recv_timeout(Duration::from_secs(5)).expect("batch event delivered").1)
+        .collect();
+    assert!(hooks_rx.recv_timeout(Duration::from_millis(500)).is_err());
+    deliveries.sort_by_key(|d| d["event"].as_str().unwrap().to_string());
+
+    assert_eq!(deliveries[0]["event"], "task.batch_moved");
+    assert_eq!(deliveries[0]["data"]["count"], 3);
+    assert_eq!(deliveries[0]["data"]["task_ids"], serde_json::json!(ids));
+    assert_eq!(deliveries[0]["data"]["to_column"], "Done");
+    assert_eq!(deliveries[0]["data"]["moves"][0]["from_column"], "To Do");
+    assert_eq!(deliveries[1]["event"], "task.batch_updated");
+    assert_eq!(deliveries[1]["data"]["task_ids"], serde_json::json!([ids[0], ids[1]]));
+    assert_eq!(deliveries[1]["data"]["changes"]["priority"], 3);
+
+    // The per-task history is unchanged
+    let activity: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/boards/{}/activity?limit=50", board_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(activity.iter().filter(|e| e["event_type"] == "moved").count(), 3);
+    assert_eq!(activity.iter().filter(|e| e["event_type"] == "updated").count(), 2);
+}
+
+#[test]
+fn test_http_actor_guard_sources() {
+    let client = test_client();
+    let resp = client
+        .post("/api/v1/boards")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "Actor Sources", "require_display_name": true}"#)
+        .dispatch();
+    let body: serde_json::Value = resp.into_json().unwrap();
+    let board_id = body["id"].as_str().unwrap().to_string();
+    let auth = Header::new("Authorization", format!("Bearer {}", body["manage_key"].as_str().unwrap()));
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let col2_id = board["columns"][1]["id"].as_str().unwrap().to_string();
+
+    // X-Actor header satisfies the display-name requirement for body routes too
     let resp = client
         .post(format!("/api/v1/boards/{}/tasks", board_id))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"title": "Should Fail"}"#)
+        .header(Header::new("X-Actor", "HeaderBot"))
+        .body(r#"{"title": "From header"}"#)
         .dispatch();
-    assert_eq!(resp.status(), Status::Conflict);
+    assert_eq!(resp.status(), Status::Ok);
+    let task: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(task["created_by"], "HeaderBot");
+    let task_id = task["id"].as_str().unwrap().to_string();
 
-    // Unarchive
+    // ?agent= is accepted as an alias for ?actor=
     let resp = client
-        .post(format!("/api/v1/boards/{}/unarchive", board_id))
+        .post(format!("/api/v1/boards/{}/tasks/{}/claim?agent=AgentBot", board_id, task_id))
         .header(auth.clone())
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
+    let claimed: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(claimed["claimed_by"], "AgentBot");
 
-    // Now writing should work again
+    // Body actor_name wins over query and header
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment?actor=QueryBot", board_id, task_id))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"title": "Post-unarchive task"}"#)
+        .header(Header::new("X-Actor", "HeaderBot"))
+        .body(r#"{"message": "hi", "actor_name": "BodyBot"}"#)
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-}
-
-// ============ Search ============
-
-#[test]
-fn test_http_search_tasks() {
-    let client = test_client();
-    let (board_id, manage_key) = create_test_board(&client, "Search HTTP Test");
-    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
-
-    // Create a few tasks
-    for title in ["Fix login bug", "Add search feature", "Update docs"] {
-        client
-            .post(format!("/api/v1/boards/{}/tasks", board_id))
-            .header(ContentType::JSON)
-            .header(auth.clone())
-            .body(format!(r#"{{"title": "{}"}}"#, title))
-            .dispatch();
-    }
+    let comment: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(comment["actor"], "BodyBot");
 
-    // Search for "login"
+    // The literal name "anonymous" does not count as a display name
     let resp = client
-        .get(format!(
-            "/api/v1/boards/{}/tasks/search?q=login",
-            board_id
-        ))
+        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}?actor=anonymous", board_id, task_id, col2_id))
+        .header(auth.clone())
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let body: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(body["total"], 1);
-    assert_eq!(body["tasks"].as_array().unwrap().len(), 1);
+    assert_eq!(resp.status(), Status::BadRequest);
 
-    // Empty query rejected
+    // Batch no longer slips past the requirement via its "batch" fallback
     let resp = client
-        .get(format!("/api/v1/boards/{}/tasks/search?q=", board_id))
+        .post(format!("/api/v1/boards/{}/tasks/batch", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(format!(
+            r#"{{"operations": [{{"action": "update", "task_ids": ["{}"], "priority": 1}}]}}"#,
+            task_id
+        ))
         .dispatch();
     assert_eq!(resp.status(), Status::BadRequest);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "DISPLAY_NAME_REQUIRED");
 }
 
-// ============ Rate Limiting ============
+// ============ API Discovery Endpoints ============
 
 #[test]
-fn test_http_rate_limiting() {
-    let db_path = format!("/tmp/kanban_http_rl_{}.db", uuid::Uuid::new_v4());
-    std::env::set_var("DATABASE_PATH", &db_path);
-    std::env::set_var("BOARD_RATE_LIMIT", "3"); // Only 3 boards/hour for this test
+fn test_http_openapi_json() {
+    let client = test_client();
+    let resp = client.get("/api/v1/openapi.json").dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    // Verify it's a valid OpenAPI spec
+    assert_eq!(body["openapi"].as_str().unwrap_or(""), "3.0.3");
+    assert!(body["info"].is_object());
+    assert!(body["paths"].is_object());
+}
 
-    let db = kanban::db::init_db().expect("DB should initialize");
-    let webhook_db = kanban::db::init_webhook_db().expect("Webhook DB should initialize");
-    let rate_limiter = kanban::rate_limit::RateLimiter::new(Duration::from_secs(3600), 3);
+#[test]
+fn test_http_openapi_lists_every_route() {
+    let client = test_client();
+    let spec: serde_json::Value = client.get("/api/v1/openapi.json").dispatch().into_json().unwrap();
+    // `/boards/<board_id>` and `/boards/{boardId}` both become `/boards/{}`
+    let shape = |path: &str| -> String {
+        path.split('?')
+            .next()
+            .unwrap_or_default()
+            .split('/')
+            .map(|seg| if seg.starts_with('<') || seg.starts_with('{') { "{}" } else { seg })
+            .collect::<Vec<_>>()
+            .join("/")
+    };
+    let documented: std::collections::BTreeSet<String> = spec["paths"]
+        .as_object()
+        .unwrap()
+        .iter()
+        .flat_map(|(path, item)| item.as_object().unwrap().keys().map(move |m| format!("{} {}", m.to_uppercase(), shape(path))))
+        .collect();
+    let mounted: std::collections::BTreeSet<String> = client
+        .rocket()
+        .routes()
+        .filter(|r| r.uri.base() == "/api/v1")
+        .map(|r| format!("{} {}", r.method, shape(r.uri.unmounted_origin.path().as_str())))
+        .collect();
 
-    let rocket = rocket::build()
-        .manage(db)
-        .manage(rate_limiter)
-        .manage(kanban::events::EventBus::with_webhooks(webhook_db))
-        .mount(
-            "/api/v1",
-            routes![
-                kanban::routes::create_board,
-            ],
-        );
+    let undocumented: Vec<_> = mounted.difference(&documented).collect();
+    assert!(undocumented.is_empty(), "routes missing from openapi.json: {:?}", undocumented);
+    let stale: Vec<_> = documented.difference(&mounted).collect();
+    assert!(stale.is_empty(), "openapi.json documents routes that don't exist: {:?}", stale);
+}
 
-    let client = Client::tracked(rocket).expect("valid rocket instance");
+#[test]
+fn test_http_llms_txt() {
+    let client = test_client();
+    let resp = client.get("/api/v1/llms.txt").dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body = resp.into_string().unwrap();
+    assert!(body.contains("Kanban"), "llms.txt should mention Kanban");
+    assert!(body.contains("/api/v1"), "llms.txt should reference API paths");
+}
 
-    // First 3 should succeed
-    for i in 0..3 {
-        let resp = client
-            .post("/api/v1/boards")
-            .header(ContentType::JSON)
-            .body(format!(r#"{{"name": "RL Board {}", "columns": []}}"#, i))
-            .dispatch();
-        assert_eq!(resp.status(), Status::Ok, "Board {} should succeed", i);
+#[test]
+fn test_http_client_sdk_archives() {
+    let client = test_client();
+
+    for path in ["/api/v1/clients/typescript.zip", "/api/v1/clients/py.zip"] {
+        let resp = client.get(path).dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        assert_eq!(resp.content_type(), Some(ContentType::ZIP));
+        assert_eq!(
+            resp.headers().get_one("X-Kanban-Version"),
+            Some(env!("CARGO_PKG_VERSION"))
+        );
+        let disposition = resp.headers().get_one("Content-Disposition").unwrap().to_string();
+        assert!(disposition.contains(env!("CARGO_PKG_VERSION")));
+        let bytes = resp.into_bytes().unwrap();
+        assert!(bytes.starts_with(b"PK"), "archive should be a zip file");
     }
 
-    // 4th should be rate limited
-    let resp = client
-        .post("/api/v1/boards")
-        .header(ContentType::JSON)
-        .body(r#"{"name": "RL Board 3", "columns": []}"#)
-        .dispatch();
-    assert_eq!(resp.status(), Status::TooManyRequests);
+    let resp = client.get("/api/v1/clients/cobol.zip").dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
     let body: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(body["code"], "RATE_LIMIT_EXCEEDED");
+    assert_eq!(body["code"], "UNKNOWN_CLIENT");
 }
 
-// ============ Column Management ============
-
 #[test]
-fn test_http_update_column_rename() {
+fn test_http_event_type_registry() {
     let client = test_client();
-    let (board_id, key) = create_test_board(&client, "Col Rename Test");
+    let resp = client.get("/api/v1/event-types").dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let types: serde_json::Value = resp.into_json().unwrap();
+    let names: Vec<&str> = types.as_array().unwrap().iter().map(|t| t["name"].as_str().unwrap()).collect();
+    assert!(names.contains(&"task.archived") && names.contains(&"task.unarchived"));
 
-    // Get the board to find column IDs
-    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
-    let board: serde_json::Value = resp.into_json().unwrap();
-    let col_id = board["columns"][0]["id"].as_str().unwrap();
-    assert_eq!(board["columns"][0]["name"], "To Do");
+    // The served OpenAPI enum is generated from the same registry
+    let resp = client.get("/api/v1/openapi.json").dispatch();
+    let spec: serde_json::Value = resp.into_json().unwrap();
+    let spec_enum: Vec<&str> = spec["components"]["schemas"]["EventType"]["enum"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_str().unwrap())
+        .collect();
+    assert_eq!(spec_enum, names);
 
-    // Rename the column
+    // Create and update accept exactly the same set
+    let (board_id, manage_key) = create_test_board(&client, "Event Types Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
     let resp = client
-        .patch(format!("/api/v1/boards/{}/columns/{}", board_id, col_id))
+        .post(format!("/api/v1/boards/{}/webhooks", board_id))
         .header(ContentType::JSON)
-        .header(Header::new("Authorization", format!("Bearer {}", key)))
-        .body(r#"{"name": "Backlog"}"#)
+        .header(auth.clone())
+        .body(r#"{"url": "https://example.com/hook", "events": ["task.unarchived"]}"#)
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let col: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(col["name"], "Backlog");
-    assert_eq!(col["id"], col_id);
-}
+    let webhook: serde_json::Value = resp.into_json().unwrap();
+    let webhook_id = webhook["id"].as_str().unwrap();
 
-#[test]
-fn test_http_update_column_no_auth() {
-    let client = test_client();
-    let (board_id, _key) = create_test_board(&client, "Col No Auth");
-
-    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
-    let board: serde_json::Value = resp.into_json().unwrap();
-    let col_id = board["columns"][0]["id"].as_str().unwrap();
+    let resp = client
+        .patch(format!("/api/v1/boards/{}/webhooks/{}", board_id, webhook_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"events": ["task.archived", "task.unarchived"]}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
 
-    // Try without auth — should fail
     let resp = client
-        .patch(format!("/api/v1/boards/{}/columns/{}", board_id, col_id))
+        .patch(format!("/api/v1/boards/{}/webhooks/{}", board_id, webhook_id))
         .header(ContentType::JSON)
-        .body(r#"{"name": "Nope"}"#)
+        .header(auth.clone())
+        .body(r#"{"events": ["task.exploded"]}"#)
         .dispatch();
-    assert_eq!(resp.status(), Status::Unauthorized);
+    assert_eq!(resp.status(), Status::BadRequest);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["code"], "INVALID_EVENT_TYPE");
+
+    // SSE filters are validated against the registry too
+    let resp = client
+        .get(format!("/api/v1/boards/{}/events/stream?events=task.moved,task.exploded", board_id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
 }
 
 #[test]
-fn test_http_delete_empty_column() {
-    let client = test_client();
-    let (board_id, key) = create_test_board(&client, "Col Delete Test");
+fn test_http_sse_heartbeat_counts() {
+    use std::io::Read;
 
-    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
-    let board: serde_json::Value = resp.into_json().unwrap();
-    // Board has 3 columns: To Do, In Progress, Done. Delete the middle one (no tasks).
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Heartbeat Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let board: serde_json::Value = client.get(format!("/api/v1/boards/{}", board_id)).dispatch().into_json().unwrap();
     let col_id = board["columns"][1]["id"].as_str().unwrap();
 
-    let resp = client
-        .delete(format!("/api/v1/boards/{}/columns/{}", board_id, col_id))
-        .header(Header::new("Authorization", format!("Bearer {}", key)))
+    for title in ["One", "Two"] {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "{}", "column_id": "{}"}}"#, title, col_id))
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+    }
+    let activity: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/boards/{}/activity", board_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let latest_seq = activity.iter().map(|e| e["seq"].as_i64().unwrap()).max().unwrap();
+
+    // A heartbeat is sent as soon as the stream opens, even with an event filter
+    let mut resp = client
+        .get(format!("/api/v1/boards/{}/events/stream?events=task.moved", board_id))
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let body: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(body["deleted"], true);
-
-    // Verify board now has 2 columns
-    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
-    let board: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(board["columns"].as_array().unwrap().len(), 2);
+    let mut frame = String::new();
+    let mut buf = [0u8; 1024];
+    while !frame.contains("\n\n") {
+        let n = resp.read(&mut buf).unwrap();
+        assert!(n > 0, "stream ended before a heartbeat");
+        frame.push_str(&String::from_utf8_lossy(&buf[..n]));
+    }
+    assert!(frame.contains("event:heartbeat"), "unexpected frame: {}", frame);
+    let data = frame.lines().find_map(|l| l.strip_prefix("data:")).unwrap();
+    let heartbeat: serde_json::Value = serde_json::from_str(data).unwrap();
+    assert_eq!(heartbeat["seq"], latest_seq);
+    assert_eq!(heartbeat["task_count"], 2);
+    assert_eq!(heartbeat["columns"].as_array().unwrap().len(), 3);
+    assert_eq!(heartbeat["columns"][0]["task_count"], 0);
+    assert_eq!(heartbeat["columns"][1]["id"], col_id);
+    assert_eq!(heartbeat["columns"][1]["task_count"], 2);
 }
 
 #[test]
-fn test_http_delete_column_with_tasks_rejected() {
+fn test_http_event_socket_handshake() {
     let client = test_client();
-    let (board_id, key) = create_test_board(&client, "Col Delete Tasks");
+    let (board_id, _) = create_test_board(&client, "Socket Board");
+    let url = format!("/api/v1/boards/{}/events/ws", board_id);
 
-    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
-    let board: serde_json::Value = resp.into_json().unwrap();
-    let col_id = board["columns"][0]["id"].as_str().unwrap();
+    // Plain HTTP gets pointed at a WebSocket client
+    let resp = client.get(url.clone()).dispatch();
+    assert_eq!(resp.status(), Status::UpgradeRequired);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["code"], "UPGRADE_REQUIRED");
 
-    // Add a task to the first column
-    client
-        .post(format!("/api/v1/boards/{}/tasks", board_id))
-        .header(ContentType::JSON)
-        .header(Header::new("Authorization", format!("Bearer {}", key)))
-        .body(format!(
-            r#"{{"title": "Block Delete", "column_id": "{}"}}"#,
-            col_id
-        ))
-        .dispatch();
+    fn handshake(req: rocket::local::blocking::LocalRequest<'_>) -> rocket::local::blocking::LocalRequest<'_> {
+        req.header(Header::new("Upgrade", "websocket"))
+            .header(Header::new("Connection", "keep-alive, Upgrade"))
+            .header(Header::new("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ=="))
+            .header(Header::new("Sec-WebSocket-Version", "13"))
+    }
+    // The local client doesn't switch protocols (hyper sends the 101), but
+    // the handshake is accepted
+    let resp = handshake(client.get(url.clone())).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(resp.headers().get_one("Sec-WebSocket-Accept"), Some("s3pPLMBiTxaQ9kYGzzhZRbK+xOo="));
 
-    // Try to delete — should fail with 409
-    let resp = client
-        .delete(format!("/api/v1/boards/{}/columns/{}", board_id, col_id))
-        .header(Header::new("Authorization", format!("Bearer {}", key)))
-        .dispatch();
-    assert_eq!(resp.status(), Status::Conflict);
+    let resp = handshake(client.get(format!("{}?events=task.nope", url))).dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
     let body: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(body["code"], "COLUMN_NOT_EMPTY");
+    assert_eq!(body["code"], "INVALID_EVENT_TYPE");
+
+    let resp = handshake(client.get("/api/v1/boards/nope/events/ws")).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
 }
 
 #[test]
-fn test_http_delete_last_column_rejected() {
-    let client = test_client();
+fn test_http_multiplexed_event_stream() {
+    use std::io::Read;
 
-    // Create a board with just 1 column
+    let client = test_client();
+    let (public_id, _) = create_test_board(&client, "Team Board");
     let resp = client
         .post("/api/v1/boards")
         .header(ContentType::JSON)
-        .body(r#"{"name": "Single Col", "columns": ["Only"]}"#)
+        .body(r#"{"name": "My Private Board", "is_public": false}"#)
         .dispatch();
-    let body: serde_json::Value = resp.into_json().unwrap();
-    let board_id = body["id"].as_str().unwrap();
-    let key = body["manage_key"].as_str().unwrap();
-    let col_id = body["columns"][0]["id"].as_str().unwrap();
+    let private: serde_json::Value = resp.into_json().unwrap();
+    let private_id = private["id"].as_str().unwrap().to_string();
+    let private_key = private["manage_key"].as_str().unwrap().to_string();
 
-    // Try to delete the only column — should fail with 409
-    let resp = client
-        .delete(format!("/api/v1/boards/{}/columns/{}", board_id, col_id))
-        .header(Header::new("Authorization", format!("Bearer {}", key)))
+    let resp = client.get("/api/v1/events/stream").dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let resp = client.get("/api/v1/events/stream?token=kb_wrong").dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    let mut resp = client
+        .get(format!("/api/v1/events/stream?token={}&boards={}&events=task.created", private_key, public_id))
         .dispatch();
-    assert_eq!(resp.status(), Status::Conflict);
-    let body: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(body["code"], "LAST_COLUMN");
+    assert_eq!(resp.status(), Status::Ok);
+    let mut buf = [0u8; 4096];
+    let mut pending = String::new();
+    let mut next_frame = || -> (String, serde_json::Value) {
+        while !pending.contains("\n\n") {
+            let n = resp.read(&mut buf).unwrap();
+            assert!(n > 0, "stream ended early");
+            pending.push_str(&String::from_utf8_lossy(&buf[..n]));
+        }
+        let end = pending.find("\n\n").unwrap();
+        let frame: String = pending.drain(..end + 2).collect();
+        let name = frame.lines().find_map(|l| l.strip_prefix("event:")).unwrap_or_default().to_string();
+        let data = frame.lines().find_map(|l| l.strip_prefix("data:")).unwrap();
+        (name, serde_json::from_str(data).unwrap())
+    };
+
+    // Token boards first, then listed boards
+    let (name, first) = next_frame();
+    assert_eq!(name, "heartbeat");
+    assert_eq!(first["board_id"], private_id.as_str());
+    assert!(first["data"]["columns"].is_array());
+    let (_, second) = next_frame();
+    assert_eq!(second["board_id"], public_id.as_str());
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", private_id))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {}", private_key)))
+        .body(r#"{"title": "Only on my board"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let (name, created) = next_frame();
+    assert_eq!(name, "task.created");
+    assert_eq!(created["board_id"], private_id.as_str());
+    assert!(created["seq"].as_u64().unwrap() > 0);
+    assert_eq!(created["data"]["title"], "Only on my board");
 }
 
-#[test]
-fn test_http_reorder_columns() {
-    let client = test_client();
-    let (board_id, key) = create_test_board(&client, "Col Reorder Test");
+/// The next SSE frame on a stream: its event name, id and JSON data.
+fn next_sse_frame(resp: &mut rocket::local::blocking::LocalResponse<'_>, pending: &mut String) -> (String, Option<String>, serde_json::Value) {
+    use std::io::Read;
 
-    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
-    let board: serde_json::Value = resp.into_json().unwrap();
-    let cols = board["columns"].as_array().unwrap();
-    // Original order: To Do (0), In Progress (1), Done (2)
-    let id0 = cols[0]["id"].as_str().unwrap().to_string();
-    let id1 = cols[1]["id"].as_str().unwrap().to_string();
-    let id2 = cols[2]["id"].as_str().unwrap().to_string();
+    let mut buf = [0u8; 4096];
+    while !pending.contains("\n\n") {
+        let n = resp.read(&mut buf).unwrap();
+        assert!(n > 0, "stream ended early");
+        pending.push_str(&String::from_utf8_lossy(&buf[..n]));
+    }
+    let end = pending.find("\n\n").unwrap();
+    let frame: String = pending.drain(..end + 2).collect();
+    let name = frame.lines().find_map(|l| l.strip_prefix("event:")).unwrap_or_default().to_string();
+    let id = frame.lines().find_map(|l| l.strip_prefix("id:")).map(|id| id.trim().to_string());
+    let data = frame.lines().find_map(|l| l.strip_prefix("data:")).unwrap();
+    (name, id, serde_json::from_str(data).unwrap())
+}
 
-    // Reorder: Done, To Do, In Progress
+#[test]
+fn test_http_multiplexed_event_stream_session_resume() {
+    let client = test_client();
     let resp = client
-        .post(format!("/api/v1/boards/{}/columns/reorder", board_id))
+        .post("/api/v1/boards")
         .header(ContentType::JSON)
-        .header(Header::new("Authorization", format!("Bearer {}", key)))
-        .body(serde_json::json!({ "column_ids": [id2, id0, id1] }).to_string())
+        .body(r#"{"name": "Signed In Board", "is_public": false}"#)
         .dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let board_id = board["id"].as_str().unwrap().to_string();
+    let auth = Header::new("Authorization", format!("Bearer {}", board["manage_key"].as_str().unwrap()));
+    let pool = client.rocket().state::<kanban::db::DbPool>().unwrap();
+    let conn = pool.get().unwrap();
+    conn.execute_batch(&format!(
+        "INSERT INTO users (id, issuer, subject, name) VALUES ('u1', 'https://idp.example', 'ada', 'Ada');
+         INSERT INTO user_sessions (token_hash, user_id, expires_at) VALUES ('{}', 'u1', datetime('now', '+1 hour'));
+         UPDATE boards SET owner_user_id = 'u1' WHERE id = '{}';
+         INSERT INTO board_members (id, board_id, name, role, key_hash) VALUES ('m1', '{}', 'Ada', 'owner', '{}');",
+        kanban::db::hash_key("ks_session"),
+        board_id,
+        board_id,
+        kanban::db::hash_key(&kanban::oidc::board_key(&conn, "u1", &board_id).unwrap())
+    ))
+    .unwrap();
+    drop(conn);
+    let create = |title: &str| {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "{}"}}"#, title))
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+    };
+
+    let resp = client.get(format!("/api/v1/events/stream?token=ks_session&after={}", board_id)).dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+
+    // A sign-in session opens the boards its user owns; ids are board_id:seq
+    let mut resp = client.get("/api/v1/events/stream?token=ks_session&events=task.created").dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let reordered: Vec<serde_json::Value> = resp.into_json().unwrap();
-    assert_eq!(reordered[0]["name"], "Done");
-    assert_eq!(reordered[0]["position"], 0);
-    assert_eq!(reordered[1]["name"], "To Do");
-    assert_eq!(reordered[1]["position"], 1);
-    assert_eq!(reordered[2]["name"], "In Progress");
-    assert_eq!(reordered[2]["position"], 2);
+    let mut pending = String::new();
+    let (name, id, beat) = next_sse_frame(&mut resp, &mut pending);
+    assert_eq!(name, "heartbeat");
+    assert_eq!(id, None);
+    assert_eq!(beat["board_id"], board_id.as_str());
+    create("Before the drop");
+    let (_, id, created) = next_sse_frame(&mut resp, &mut pending);
+    let seq = created["seq"].as_u64().unwrap();
+    let last_id = id.unwrap();
+    assert_eq!(last_id, format!("{}:{}", board_id, seq));
+    drop(resp);
+
+    // Reconnecting with Last-Event-ID replays what was missed, once
+    create("While away");
+    let mut resp = client
+        .get("/api/v1/events/stream?token=ks_session&events=task.created")
+        .header(Header::new("Last-Event-ID", last_id))
+        .dispatch();
+    let mut pending = String::new();
+    let (name, id, missed) = next_sse_frame(&mut resp, &mut pending);
+    assert_eq!(name, "task.created");
+    assert_eq!(missed["data"]["title"], "While away");
+    assert!(missed["seq"].as_u64().unwrap() > seq);
+    assert_eq!(id.unwrap(), format!("{}:{}", board_id, missed["seq"]));
+    let (name, _, _) = next_sse_frame(&mut resp, &mut pending);
+    assert_eq!(name, "heartbeat");
+    create("Live again");
+    let (_, _, live) = next_sse_frame(&mut resp, &mut pending);
+    assert_eq!(live["data"]["title"], "Live again");
 }
 
 #[test]
-fn test_http_reorder_columns_wrong_count() {
-    let client = test_client();
-    let (board_id, key) = create_test_board(&client, "Col Reorder Bad");
+fn test_http_multiplexed_event_stream_column_scope() {
+    use std::io::Read;
 
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Scoped Stream Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
     let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
     let board: serde_json::Value = resp.into_json().unwrap();
-    let cols = board["columns"].as_array().unwrap();
-    let id0 = cols[0]["id"].as_str().unwrap().to_string();
+    let inbox = board["columns"][0]["id"].as_str().unwrap().to_string();
+    let other = board["columns"][1]["id"].as_str().unwrap().to_string();
 
-    // Send only 1 of 3 column IDs
     let resp = client
-        .post(format!("/api/v1/boards/{}/columns/reorder", board_id))
+        .post(format!("/api/v1/boards/{}/tokens", board_id))
         .header(ContentType::JSON)
-        .header(Header::new("Authorization", format!("Bearer {}", key)))
-        .body(serde_json::json!({ "column_ids": [id0] }).to_string())
+        .header(auth.clone())
+        .body(format!(r#"{{"name": "triage-bot", "columns": ["{}"]}}"#, inbox))
         .dispatch();
-    assert_eq!(resp.status(), Status::BadRequest);
-    let body: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(body["code"], "INVALID_COLUMN_LIST");
-}
+    assert_eq!(resp.status(), Status::Ok);
+    let scoped: serde_json::Value = resp.into_json().unwrap();
+    let scoped_key = scoped["key"].as_str().unwrap();
 
-// ============ Update Board Settings ============
+    let mut resp = client
+        .get(format!("/api/v1/events/stream?token={}&events=task.created", scoped_key))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let mut buf = [0u8; 4096];
+    let mut pending = String::new();
+    let mut next_frame = || -> (String, serde_json::Value) {
+        while !pending.contains("\n\n") {
+            let n = resp.read(&mut buf).unwrap();
+            assert!(n > 0, "stream ended early");
+            pending.push_str(&String::from_utf8_lossy(&buf[..n]));
+        }
+        let end = pending.find("\n\n").unwrap();
+        let frame: String = pending.drain(..end + 2).collect();
+        let name = frame.lines().find_map(|l| l.strip_prefix("event:")).unwrap_or_default().to_string();
+        let data = frame.lines().find_map(|l| l.strip_prefix("data:")).unwrap();
+        (name, serde_json::from_str(data).unwrap())
+    };
+    let (name, _) = next_frame();
+    assert_eq!(name, "heartbeat");
+
+    // Only events about the token's columns come through
+    for (title, column) in [("Outside the scope", &other), ("Inside the scope", &inbox)] {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "{}", "column_id": "{}"}}"#, title, column))
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+    }
+    let (name, created) = next_frame();
+    assert_eq!(name, "task.created");
+    assert_eq!(created["data"]["title"], "Inside the scope");
+}
 
 #[test]
-fn test_http_update_board() {
+fn test_http_event_stream_assigned_filter() {
+    use std::io::Read;
+
     let client = test_client();
-    let (board_id, manage_key) = create_test_board(&client, "Settings Test");
+    let (board_id, manage_key) = create_test_board(&client, "Busy Board");
     let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
 
-    // Update name and description
-    let resp = client
-        .patch(format!("/api/v1/boards/{}", board_id))
-        .header(ContentType::JSON)
-        .header(auth.clone())
-        .body(r#"{"name": "Updated Name", "description": "New desc", "is_public": true}"#)
+    let mut resp = client
+        .get(format!(
+            "/api/v1/boards/{}/events/stream?events=task.created,task.updated&assigned=nanookbot",
+            board_id
+        ))
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let body: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(body["name"], "Updated Name");
-    assert_eq!(body["description"], "New desc");
-    assert_eq!(body["is_public"], true);
+    let mut buf = [0u8; 4096];
+    let mut pending = String::new();
+    let mut next_frame = || -> (String, serde_json::Value) {
+        while !pending.contains("\n\n") {
+            let n = resp.read(&mut buf).unwrap();
+            assert!(n > 0, "stream ended early");
+            pending.push_str(&String::from_utf8_lossy(&buf[..n]));
+        }
+        let end = pending.find("\n\n").unwrap();
+        let frame: String = pending.drain(..end + 2).collect();
+        let name = frame.lines().find_map(|l| l.strip_prefix("event:")).unwrap_or_default().to_string();
+        let data = frame.lines().find_map(|l| l.strip_prefix("data:")).unwrap();
+        (name, serde_json::from_str(data).unwrap())
+    };
+    assert_eq!(next_frame().0, "heartbeat");
+
+    let mut task_ids = vec![];
+    for (title, assignee) in [("Sam's", "Sam"), ("Nanook's", "NanookBot")] {
+        let task: serde_json::Value = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "{}", "assigned_to": "{}"}}"#, title, assignee))
+            .dispatch()
+            .into_json()
+            .unwrap();
+        task_ids.push(task["id"].as_str().unwrap().to_string());
+    }
+    for task_id in &task_ids {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(r#"{"message": "not a subscribed event"}"#)
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        let resp = client
+            .patch(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(r#"{"priority": 2}"#)
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+    }
+
+    // Only NanookBot's task comes through: created, then updated
+    let (name, created) = next_frame();
+    assert_eq!(name, "task.created");
+    assert_eq!(created["title"], "Nanook's");
+    let (name, updated) = next_frame();
+    assert_eq!(name, "task.updated");
+    assert_eq!(updated["task_id"], task_ids[1].as_str());
 }
 
 #[test]
-fn test_http_quick_done_settings() {
+fn test_http_event_long_poll() {
     let client = test_client();
-    let (board_id, manage_key) = create_test_board(&client, "Quick Done Test");
+    let (board_id, manage_key) = create_test_board(&client, "Poll Board");
     let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
-
-    // Board should start with no quick_done settings
-    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
-    let body: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(body["quick_done_column_id"], serde_json::Value::Null);
-    assert_eq!(body["quick_done_auto_archive"], false);
-
-    // Get the first column's ID
-    let first_col_id = body["columns"][0]["id"].as_str().unwrap().to_string();
-
-    // Set quick_done_column_id and auto_archive
-    let resp = client
-        .patch(format!("/api/v1/boards/{}", board_id))
+    let poll = |query: &str| -> serde_json::Value {
+        let resp = client.get(format!("/api/v1/boards/{}/events/poll?{}", board_id, query)).dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        resp.into_json().unwrap()
+    };
+
+    // Without a cursor, only events from now on
+    let first = poll("timeout=0");
+    assert_eq!(first["events"], serde_json::json!([]));
+    let cursor = first["cursor"].as_u64().unwrap();
+
+    let task: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(format!(r#"{{"quick_done_column_id": "{}", "quick_done_auto_archive": true}}"#, first_col_id))
-        .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let body: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(body["quick_done_column_id"], first_col_id);
-    assert_eq!(body["quick_done_auto_archive"], true);
-
-    // Clear quick_done_column_id by sending empty string
+        .body(r#"{"title": "Polled"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
     let resp = client
-        .patch(format!("/api/v1/boards/{}", board_id))
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task["id"].as_str().unwrap()))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"quick_done_column_id": ""}"#)
+        .body(r#"{"message": "hello"}"#)
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let body: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(body["quick_done_column_id"], serde_json::Value::Null);
-    // auto_archive should still be true
-    assert_eq!(body["quick_done_auto_archive"], true);
 
-    // Invalid column ID should be rejected
-    let resp = client
-        .patch(format!("/api/v1/boards/{}", board_id))
-        .header(ContentType::JSON)
-        .header(auth.clone())
-        .body(r#"{"quick_done_column_id": "nonexistent-col"}"#)
+    let all = poll(&format!("after={}", cursor));
+    let names: Vec<&str> = all["events"].as_array().unwrap().iter().map(|e| e["event"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["task.created", "task.comment"]);
+    assert_eq!(all["events"][0]["seq"], cursor + 1);
+    assert_eq!(all["events"][0]["data"]["title"], "Polled");
+    assert_eq!(all["cursor"], cursor + 2);
+    assert_eq!(all["events_lost"], false);
+
+    let comments = poll(&format!("after={}&events=task.comment", cursor));
+    assert_eq!(comments["events"].as_array().unwrap().len(), 1);
+    assert_eq!(comments["events"][0]["data"]["message"], "hello");
+
+    // Nothing new: waits out the timeout, then returns the same cursor
+    let started = std::time::Instant::now();
+    let idle = poll(&format!("after={}&timeout=1", all["cursor"]));
+    assert!(started.elapsed() >= std::time::Duration::from_millis(900));
+    assert_eq!(idle["events"], serde_json::json!([]));
+    assert_eq!(idle["cursor"], all["cursor"]);
+
+    // A cursor from before a restart is flagged
+    assert_eq!(poll("after=99999&timeout=0")["events_lost"], true);
+
+    let resp = client.get("/api/v1/boards/nope/events/poll?timeout=0").dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+    let resp = client
+        .get(format!("/api/v1/boards/{}/events/poll?events=task.nope", board_id))
         .dispatch();
     assert_eq!(resp.status(), Status::BadRequest);
 }
 
 #[test]
-fn test_http_update_board_empty_name_rejected() {
+fn test_http_event_envelope() {
+    use std::io::Read;
+
+    let (url, hooks_rx) = spawn_hook_receiver();
     let client = test_client();
-    let (board_id, manage_key) = create_test_board(&client, "Empty Name Test");
+    let (board_id, manage_key) = create_test_board(&client, "Envelope Board");
     let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
-
-    let resp = client
-        .patch(format!("/api/v1/boards/{}", board_id))
+    client
+        .post(format!("/api/v1/boards/{}/webhooks", board_id))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"name": "  "}"#)
+        .body(format!(r#"{{"url": "{}", "events": ["task.created"]}}"#, url))
         .dispatch();
-    assert_eq!(resp.status(), Status::BadRequest);
-}
 
-#[test]
-fn test_http_update_board_no_auth() {
-    let client = test_client();
-    let (board_id, _) = create_test_board(&client, "No Auth Update");
+    let resp = client.get(format!("/api/v1/boards/{}/events/stream?version=2", board_id)).dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["code"], "UNSUPPORTED_VERSION");
 
-    let resp = client
-        .patch(format!("/api/v1/boards/{}", board_id))
+    let mut resp = client.get(format!("/api/v1/boards/{}/events/stream?version=1", board_id)).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let mut buf = [0u8; 4096];
+    let mut pending = String::new();
+    let mut next_frame = || -> String {
+        while !pending.contains("\n\n") {
+            let n = resp.read(&mut buf).unwrap();
+            assert!(n > 0, "stream ended early");
+            pending.push_str(&String::from_utf8_lossy(&buf[..n]));
+        }
+        let end = pending.find("\n\n").unwrap();
+        pending.drain(..end + 2).collect()
+    };
+    assert!(next_frame().contains("event:heartbeat"));
+
+    let task: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
         .header(ContentType::JSON)
-        .body(r#"{"name": "Hacked"}"#)
-        .dispatch();
-    assert!(resp.status() == Status::Unauthorized || resp.status() == Status::Forbidden);
+        .header(auth.clone())
+        .body(r#"{"title": "Wrapped"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+
+    let frame = next_frame();
+    assert!(frame.contains("event:task.created"), "unexpected frame: {}", frame);
+    let id = frame.lines().find_map(|l| l.strip_prefix("id:")).unwrap();
+    let envelope: serde_json::Value =
+        serde_json::from_str(frame.lines().find_map(|l| l.strip_prefix("data:")).unwrap()).unwrap();
+    assert_eq!(envelope["version"], 1);
+    assert_eq!(envelope["event"], "task.created");
+    assert_eq!(envelope["board_id"], board_id.as_str());
+    assert_eq!(envelope["seq"].to_string(), id);
+    assert!(envelope["occurred_at"].is_string());
+    assert_eq!(envelope["data"]["task_id"], task["id"]);
+
+    // Webhooks get the same envelope, plus the older `timestamp`
+    let (_, payload) = hooks_rx.recv_timeout(Duration::from_secs(5)).expect("webhook should be delivered");
+    assert_eq!(payload["version"], 1);
+    assert_eq!(payload["seq"], envelope["seq"]);
+    assert_eq!(payload["occurred_at"], envelope["occurred_at"]);
+    assert_eq!(payload["data"], envelope["data"]);
+    assert!(payload["timestamp"].is_string());
 }
 
-// ============ Task Archive / Unarchive ============
-
 #[test]
-fn test_http_task_archive_unarchive() {
+fn test_http_webhook_dead_letters() {
     let client = test_client();
-    let (board_id, manage_key) = create_test_board(&client, "Archive Test");
+    let (board_id, manage_key) = create_test_board(&client, "Dead Letter Board");
     let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
 
-    // Get first column
-    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
-    let board: serde_json::Value = resp.into_json().unwrap();
-    let col_id = board["columns"][0]["id"].as_str().unwrap();
-
-    // Create a task
+    // Nothing listens on port 1, so every attempt fails
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .post(format!("/api/v1/boards/{}/webhooks", board_id))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(serde_json::json!({"title": "Archivable", "column_id": col_id}).to_string())
+        .body(r#"{"url": "http://127.0.0.1:1/hook", "events": ["task.created"]}"#)
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let task: serde_json::Value = resp.into_json().unwrap();
-    let task_id = task["id"].as_str().unwrap();
-    assert!(task["archived_at"].is_null());
+    let webhook: serde_json::Value = resp.into_json().unwrap();
+    let webhook_id = webhook["id"].as_str().unwrap().to_string();
+    let dead_letters_url = format!("/api/v1/boards/{}/webhooks/{}/dead-letters", board_id, webhook_id);
 
-    // Archive it
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/archive", board_id, task_id))
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
         .header(auth.clone())
+        .body(r#"{"title": "Undeliverable"}"#)
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let archived: serde_json::Value = resp.into_json().unwrap();
-    assert!(archived["archived_at"].is_string());
-
-    // Archived tasks should be hidden from default list
-    let resp = client
-        .get(format!("/api/v1/boards/{}/tasks", board_id))
-        .dispatch();
-    let tasks: Vec<serde_json::Value> = resp.into_json().unwrap();
-    assert!(tasks.iter().all(|t| t["id"] != task_id));
 
-    // But visible with archived=true
-    let resp = client
-        .get(format!("/api/v1/boards/{}/tasks?archived=true", board_id))
-        .dispatch();
-    let tasks: Vec<serde_json::Value> = resp.into_json().unwrap();
-    assert!(tasks.iter().any(|t| t["id"] == task_id));
+    // Delivery happens in the background; wait for retries to run out
+    let mut letters = serde_json::Value::Null;
+    for _ in 0..100 {
+        let resp = client.get(&dead_letters_url).header(auth.clone()).dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        letters = resp.into_json().unwrap();
+        if !letters.as_array().unwrap().is_empty() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    let letters = letters.as_array().unwrap();
+    assert_eq!(letters.len(), 1);
+    assert_eq!(letters[0]["event"], "task.created");
+    assert_eq!(letters[0]["attempts"], 2);
+    assert_eq!(letters[0]["payload"]["data"]["title"], "Undeliverable");
+    assert!(!letters[0]["last_error"].as_str().unwrap().is_empty());
+
+    // Manage key required
+    let resp = client.get(&dead_letters_url).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
 
-    // Unarchive it
+    // Re-drive still fails, so the letter stays with one more attempt recorded
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/unarchive", board_id, task_id))
+        .post(format!("{}/redrive", dead_letters_url))
         .header(auth.clone())
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let unarchived: serde_json::Value = resp.into_json().unwrap();
-    assert!(unarchived["archived_at"].is_null());
-
-    // Now visible in default list again
-    let resp = client
-        .get(format!("/api/v1/boards/{}/tasks", board_id))
-        .dispatch();
-    let tasks: Vec<serde_json::Value> = resp.into_json().unwrap();
-    assert!(tasks.iter().any(|t| t["id"] == task_id));
-}
-
-#[test]
-fn test_http_task_archive_no_auth() {
-    let client = test_client();
-    let (board_id, manage_key) = create_test_board(&client, "Archive NoAuth");
-    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let report: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(report["attempted"], 1);
+    assert_eq!(report["delivered"], 0);
+    assert_eq!(report["failed"], 1);
+    assert_eq!(report["remaining"], 1);
 
-    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
-    let board: serde_json::Value = resp.into_json().unwrap();
-    let col_id = board["columns"][0]["id"].as_str().unwrap();
+    let letters: serde_json::Value = client.get(&dead_letters_url).header(auth.clone()).dispatch().into_json().unwrap();
+    assert_eq!(letters[0]["attempts"], 3);
 
+    // Selecting ids that don't exist re-drives nothing
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .post(format!("{}/redrive", dead_letters_url))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(serde_json::json!({"title": "NoAuth Archive", "column_id": col_id}).to_string())
+        .body(r#"{"ids": ["no-such-letter"]}"#)
         .dispatch();
-    let task: serde_json::Value = resp.into_json().unwrap();
-    let task_id = task["id"].as_str().unwrap();
+    let report: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(report["attempted"], 0);
+    assert_eq!(report["remaining"], 1);
 
-    // Try archive without auth
+    // Deleting the webhook discards its dead letters
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/archive", board_id, task_id))
+        .delete(format!("/api/v1/boards/{}/webhooks/{}", board_id, webhook_id))
+        .header(auth.clone())
         .dispatch();
-    assert!(resp.status() == Status::Unauthorized || resp.status() == Status::Forbidden);
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.get(&dead_letters_url).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
 }
 
 #[test]
-fn test_http_board_activity_feed() {
+fn test_http_webhook_dead_letters_at_failure_cap() {
     let client = test_client();
-    let (board_id, manage_key) = create_test_board(&client, "Activity Feed Test");
+    let (board_id, manage_key) = create_test_board(&client, "Failure Cap Board");
     let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let (hook_url, hooks_rx) = spawn_hook_receiver();
 
-    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
-    let board: serde_json::Value = resp.into_json().unwrap();
-    let col_id = board["columns"][0]["id"].as_str().unwrap();
-
-    // Create a task (generates a task.created event)
+    let webhook: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/webhooks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(serde_json::json!({"url": hook_url, "events": ["task.created"]}).to_string())
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let webhook_id = webhook["id"].as_str().unwrap().to_string();
+
+    // A webhook at the failure cap isn't sent new events, but keeps them
+    let pool = client.rocket().state::<kanban::db::DbPool>().unwrap();
+    pool.get()
+        .unwrap()
+        .execute("UPDATE webhooks SET failure_count = 10 WHERE id = ?1", rusqlite::params![webhook_id])
+        .unwrap();
     let resp = client
         .post(format!("/api/v1/boards/{}/tasks", board_id))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(serde_json::json!({"title": "Activity Task", "column_id": col_id, "actor_name": "TestBot"}).to_string())
+        .body(r#"{"title": "Held back"}"#)
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let task: serde_json::Value = resp.into_json().unwrap();
-    let task_id = task["id"].as_str().unwrap();
+    assert!(hooks_rx.recv_timeout(Duration::from_millis(300)).is_err(), "capped webhooks get nothing");
 
-    // Add a comment (generates a task.comment event)
+    let board_letters_url = format!("/api/v1/boards/{}/webhooks/dead-letters", board_id);
+    let resp = client.get(&board_letters_url).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let letters: serde_json::Value = resp.into_json().unwrap();
+    let letters = letters.as_array().unwrap();
+    assert_eq!(letters.len(), 1);
+    assert_eq!(letters[0]["webhook_id"], webhook_id.as_str());
+    assert_eq!(letters[0]["attempts"], 0);
+    assert_eq!(letters[0]["payload"]["data"]["title"], "Held back");
+    assert!(letters[0]["last_error"].as_str().unwrap().contains("10 consecutive failures"));
+
+    let filtered: serde_json::Value = client
+        .get(format!("{}?webhook_id={}", board_letters_url, webhook_id))
+        .header(auth.clone())
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(filtered.as_array().unwrap().len(), 1);
+    let resp = client.get(format!("{}?webhook_id=nope", board_letters_url)).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+    let resp = client.get(&board_letters_url).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // Requeuing sends them through the outbox and lifts the cap
+    let requeue_url = format!("{}/requeue", board_letters_url);
+    let resp = client.post(&requeue_url).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+        .post(&requeue_url)
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(serde_json::json!({"message": "Test comment", "actor_name": "TestBot"}).to_string())
+        .body(r#"{"ids": ["no-such-letter"]}"#)
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
+    let report: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(report["requeued"], 0);
+    assert_eq!(report["remaining"], 1);
 
-    // Fetch activity feed — should have at least 2 events
-    let resp = client
-        .get(format!("/api/v1/boards/{}/activity", board_id))
-        .dispatch();
+    let resp = client.post(&requeue_url).header(auth.clone()).dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let activity: Vec<serde_json::Value> = resp.into_json().unwrap();
-    assert!(activity.len() >= 2, "Expected at least 2 events, got {}", activity.len());
-
-    // Should contain both event types
-    let types: Vec<&str> = activity.iter().map(|e| e["event_type"].as_str().unwrap()).collect();
-    assert!(types.contains(&"comment"), "Should have comment event");
-    assert!(types.contains(&"created"), "Should have created event");
-
-    // All events should reference our task
-    for event in &activity {
-        assert_eq!(event["task_title"], "Activity Task");
-        assert!(!event["task_id"].as_str().unwrap().is_empty());
-    }
+    let report: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(report["requeued"], 1);
+    assert_eq!(report["webhook_ids"], serde_json::json!([webhook_id]));
+    assert_eq!(report["remaining"], 0);
 
-    // --- Enrichment checks ---
-    // Created events should have a task snapshot
-    let created_event = activity.iter().find(|e| e["event_type"] == "created").unwrap();
-    assert!(created_event.get("task").is_some(), "Created event should have task snapshot");
-    let task_snapshot = &created_event["task"];
-    assert_eq!(task_snapshot["title"], "Activity Task");
-    assert_eq!(task_snapshot["id"], task_id);
-    assert!(!task_snapshot["column_id"].as_str().unwrap().is_empty());
-    // Created events should NOT have recent_comments
-    assert!(created_event.get("recent_comments").is_none(), "Created event should not have recent_comments");
+    let (_, body) = hooks_rx.recv_timeout(Duration::from_secs(5)).expect("requeued letter should be delivered");
+    assert_eq!(body["event"], "task.created");
+    assert_eq!(body["data"]["title"], "Held back");
 
-    // Comment events should have both task snapshot and recent_comments
-    let comment_event = activity.iter().find(|e| e["event_type"] == "comment").unwrap();
-    assert!(comment_event.get("task").is_some(), "Comment event should have task snapshot");
-    assert_eq!(comment_event["task"]["title"], "Activity Task");
-    let recent = comment_event["recent_comments"].as_array().unwrap();
-    assert!(!recent.is_empty(), "Comment event should have recent_comments");
-    assert_eq!(recent[0]["message"], "Test comment");
-    assert_eq!(recent[0]["actor"], "TestBot");
+    let hooks: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/webhooks", board_id))
+        .header(auth.clone())
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(hooks[0]["failure_count"], 0);
 
-    // Move the task (generates a moved event) — should NOT be enriched
-    let second_col_id = board["columns"][1]["id"].as_str().unwrap();
-    let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}", board_id, task_id, second_col_id))
+    // New events flow again
+    client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
         .header(auth.clone())
+        .body(r#"{"title": "Live again"}"#)
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
+    let (_, body) = hooks_rx.recv_timeout(Duration::from_secs(5)).expect("webhook should be delivered");
+    assert_eq!(body["data"]["title"], "Live again");
+}
 
-    // Re-fetch activity — moved events should stay lean
-    let resp = client
-        .get(format!("/api/v1/boards/{}/activity", board_id))
-        .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let activity: Vec<serde_json::Value> = resp.into_json().unwrap();
-    let moved_event = activity.iter().find(|e| e["event_type"] == "moved").unwrap();
-    assert!(moved_event.get("task").is_none(), "Moved event should NOT have task snapshot");
-    assert!(moved_event.get("recent_comments").is_none(), "Moved event should NOT have recent_comments");
+#[test]
+fn test_http_webhook_delivery_log() {
+    let (url, rx) = spawn_hook_responder(|_| "thanks".to_string());
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Delivery Log Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let add_hook = |url: &str| -> String {
+        let hook: serde_json::Value = client
+            .post(format!("/api/v1/boards/{}/webhooks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(serde_json::json!({"url": url, "events": ["task.created"]}).to_string())
+            .dispatch()
+            .into_json()
+            .unwrap();
+        format!("/api/v1/boards/{}/webhooks/{}/deliveries", board_id, hook["id"].as_str().unwrap())
+    };
+    let working = add_hook(&url);
+    // Nothing listens on port 1
+    let broken = add_hook("http://127.0.0.1:1/hook");
 
-    // Test since filter — use a future timestamp to get 0 results
     let resp = client
-        .get(format!("/api/v1/boards/{}/activity?since=2099-01-01T00:00:00", board_id))
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Logged"}"#)
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let activity: Vec<serde_json::Value> = resp.into_json().unwrap();
-    assert_eq!(activity.len(), 0);
+    rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+    let deliveries = |url: &str, want: usize| -> Vec<serde_json::Value> {
+        for _ in 0..100 {
+            let resp = client.get(url).header(auth.clone()).dispatch();
+            assert_eq!(resp.status(), Status::Ok);
+            let list: Vec<serde_json::Value> = resp.into_json().unwrap();
+            if list.len() >= want {
+                return list;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        panic!("fewer than {} deliveries logged at {}", want, url);
+    };
+
+    let ok = deliveries(&working, 1);
+    assert_eq!(ok.len(), 1);
+    assert_eq!(ok[0]["event"], "task.created");
+    assert_eq!(ok[0]["attempt"], 1);
+    assert_eq!(ok[0]["success"], true);
+    assert_eq!(ok[0]["status_code"], 200);
+    assert_eq!(ok[0]["response_body"], "thanks");
+    assert_eq!(ok[0]["error"], serde_json::Value::Null);
+    assert_eq!(ok[0]["payload"]["data"]["title"], "Logged");
+    assert!(ok[0]["duration_ms"].is_i64());
+
+    // Each retry is its own entry, newest first
+    let failed = deliveries(&broken, 2);
+    assert_eq!(failed.iter().map(|d| d["attempt"].as_i64().unwrap()).collect::<Vec<_>>(), vec![2, 1]);
+    assert!(failed.iter().all(|d| d["success"] == false && d["status_code"].is_null()));
+    assert!(!failed[0]["error"].as_str().unwrap().is_empty());
+
+    let resp = client.get(format!("{}?failed=true", working)).header(auth.clone()).dispatch();
+    assert_eq!(resp.into_json::<Vec<serde_json::Value>>().unwrap().len(), 0);
+    let resp = client.get(format!("{}?failed=false&limit=1", broken)).header(auth.clone()).dispatch();
+    assert_eq!(resp.into_json::<Vec<serde_json::Value>>().unwrap().len(), 0);
+
+    // Manage key required
+    let resp = client.get(&working).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+}
 
-    // Test limit parameter
-    let resp = client
-        .get(format!("/api/v1/boards/{}/activity?limit=1", board_id))
-        .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let activity: Vec<serde_json::Value> = resp.into_json().unwrap();
-    assert_eq!(activity.len(), 1);
+#[test]
+fn test_http_webhook_test_ping() {
+    use hmac::{Hmac, Mac};
 
-    // --- Seq cursor pagination tests ---
-    // All events should have a seq field (monotonic integer)
-    let resp = client
-        .get(format!("/api/v1/boards/{}/activity", board_id))
-        .dispatch();
+    let (url, rx) = spawn_hook_responder(|_| "pong".to_string());
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Ping Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let add_hook = |url: &str| -> serde_json::Value {
+        client
+            .post(format!("/api/v1/boards/{}/webhooks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(serde_json::json!({"url": url}).to_string())
+            .dispatch()
+            .into_json()
+            .unwrap()
+    };
+    let hook = add_hook(&url);
+    let test_url = format!("/api/v1/boards/{}/webhooks/{}/test", board_id, hook["id"].as_str().unwrap());
+
+    let resp = client.post(&test_url).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    let resp = client.post(&test_url).header(auth.clone()).dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let activity: Vec<serde_json::Value> = resp.into_json().unwrap();
-    for event in &activity {
-        assert!(event.get("seq").is_some(), "Event should have seq field");
-        assert!(event["seq"].as_i64().unwrap() > 0, "seq should be positive");
-    }
+    let result: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(result["success"], true);
+    assert_eq!(result["status_code"], 200);
+    assert_eq!(result["response_body"], "pong");
+    assert!(result["duration_ms"].is_i64());
+    assert_eq!(result["payload"]["event"], "webhook.ping");
+    assert_eq!(result["payload"]["data"]["webhook_id"], hook["id"]);
+
+    // Signed like a real delivery
+    let (headers, body) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(body, result["payload"]);
+    assert!(headers.to_lowercase().contains("x-kanban-event: webhook.ping"));
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(hook["secret"].as_str().unwrap().as_bytes()).unwrap();
+    mac.update(&serde_json::to_vec(&body).unwrap());
+    let expected = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+    assert!(headers.lines().any(|l| l.to_lowercase() == format!("x-kanban-signature: {}", expected)));
+
+    // A dead endpoint is reported, not raised, and logged
+    let dead = add_hook("http://127.0.0.1:1/hook");
+    let dead_url = format!("/api/v1/boards/{}/webhooks/{}", board_id, dead["id"].as_str().unwrap());
+    let result: serde_json::Value =
+        client.post(format!("{}/test", dead_url)).header(auth.clone()).dispatch().into_json().unwrap();
+    assert_eq!(result["success"], false);
+    assert!(result["status_code"].is_null());
+    assert!(!result["error"].as_str().unwrap().is_empty());
+    let log: serde_json::Value =
+        client.get(format!("{}/deliveries", dead_url)).header(auth.clone()).dispatch().into_json().unwrap();
+    assert_eq!(log[0]["event"], "webhook.ping");
+    assert_eq!(log[0]["success"], false);
+    let webhooks: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/webhooks", board_id))
+        .header(auth.clone())
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert!(webhooks.as_array().unwrap().iter().all(|w| w["failure_count"] == 0));
 
-    // Test after= cursor — use seq 0 to get all events
     let resp = client
-        .get(format!("/api/v1/boards/{}/activity?after=0", board_id))
+        .post(format!("/api/v1/boards/{}/webhooks/no-such-hook/test", board_id))
+        .header(auth.clone())
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let all_after_0: Vec<serde_json::Value> = resp.into_json().unwrap();
-    assert_eq!(all_after_0.len(), activity.len(), "after=0 should return all events");
+    assert_eq!(resp.status(), Status::NotFound);
+}
 
-    // after= results should be ordered by seq ASC (oldest first)
-    let seqs: Vec<i64> = all_after_0.iter().map(|e| e["seq"].as_i64().unwrap()).collect();
-    for i in 1..seqs.len() {
-        assert!(seqs[i] > seqs[i-1], "after= results should be ordered by seq ASC, got {:?}", seqs);
-    }
+#[test]
+fn test_http_webhook_payload_template() {
+    let (url, rx) = spawn_hook_receiver();
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Template Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let hooks_url = format!("/api/v1/boards/{}/webhooks", board_id);
 
-    // Test after= with a specific seq — should return only events after that seq
-    let mid_seq = seqs[seqs.len() / 2];
     let resp = client
-        .get(format!("/api/v1/boards/{}/activity?after={}", board_id, mid_seq))
+        .post(&hooks_url)
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(serde_json::json!({"url": url, "template": {"text": "{{secret_key}}"}}).to_string())
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let partial: Vec<serde_json::Value> = resp.into_json().unwrap();
-    assert!(partial.len() < all_after_0.len(), "after=mid should return fewer events");
-    for event in &partial {
-        assert!(event["seq"].as_i64().unwrap() > mid_seq, "All events should have seq > {}", mid_seq);
-    }
+    assert_eq!(resp.status(), Status::BadRequest);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "INVALID_TEMPLATE");
 
-    // Test after= with a very high seq — should return 0 events
-    let resp = client
-        .get(format!("/api/v1/boards/{}/activity?after=999999", board_id))
-        .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let empty: Vec<serde_json::Value> = resp.into_json().unwrap();
-    assert_eq!(empty.len(), 0, "after=999999 should return no events");
+    let template = serde_json::json!({
+        "text": "{{board_name}}: {{task_title}} ({{event}})",
+        "routing_key": "kanban",
+        "details": {"seq": "{{seq}}", "column": "{{data.column_id}}"},
+    });
+    let hook: serde_json::Value = client
+        .post(&hooks_url)
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(serde_json::json!({"url": url, "events": ["task.created"], "template": template}).to_string())
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(hook["template"], template);
+
+    let task: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Page the on-call"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let (_, body) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(body["text"], "Template Board: Page the on-call (task.created)");
+    assert_eq!(body["routing_key"], "kanban");
+    assert!(body["details"]["seq"].is_u64());
+    assert_eq!(body["details"]["column"], task["column_id"]);
+
+    // The test ping is rendered through the template too
+    let hook_url = format!("{}/{}", hooks_url, hook["id"].as_str().unwrap());
+    let result: serde_json::Value =
+        client.post(format!("{}/test", hook_url)).header(auth.clone()).dispatch().into_json().unwrap();
+    assert_eq!(result["payload"]["details"]["seq"], 0);
+    assert!(result["payload"]["text"].as_str().unwrap().ends_with("(webhook.ping)"));
+    rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+    // null removes it: back to the plain event
+    let updated: serde_json::Value = client
+        .patch(&hook_url)
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"template": null}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert!(updated["template"].is_null());
+    client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Plain again"}"#)
+        .dispatch();
+    let (_, body) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(body["event"], "task.created");
+    assert_eq!(body["data"]["title"], "Plain again");
 }
 
-// ============ Quick Reassign Settings ============
-
 #[test]
-fn test_http_quick_reassign_settings() {
+fn test_http_webhook_custom_headers() {
+    let (url, rx) = spawn_hook_receiver();
     let client = test_client();
-    let (board_id, key) = create_test_board(&client, "Quick Reassign Test");
-    let auth = Header::new("Authorization", format!("Bearer {}", key));
-
-    // Get board to find column IDs
-    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
-    let board: serde_json::Value = resp.into_json().unwrap();
-    let first_col_id = board["columns"][0]["id"].as_str().unwrap();
+    let (board_id, manage_key) = create_test_board(&client, "Header Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let hooks_url = format!("/api/v1/boards/{}/webhooks", board_id);
+
+    for headers in [
+        serde_json::json!({"X-Kanban-Signature": "forged"}),
+        serde_json::json!({"Content-Type": "text/plain"}),
+        serde_json::json!({"Bad Name": "x"}),
+        serde_json::json!({"X-Tenant": "line\nbreak"}),
+    ] {
+        let resp = client
+            .post(&hooks_url)
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(serde_json::json!({"url": url, "headers": headers}).to_string())
+            .dispatch();
+        assert_eq!(resp.status(), Status::BadRequest, "{}", headers);
+        let err: serde_json::Value = resp.into_json().unwrap();
+        assert_eq!(err["code"], "INVALID_HEADER");
+    }
 
-    // Initially null
-    assert!(board["quick_reassign_column_id"].is_null());
-    assert!(board["quick_reassign_to"].is_null());
+    let hook: serde_json::Value = client
+        .post(&hooks_url)
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(
+            serde_json::json!({
+                "url": url,
+                "events": ["task.created"],
+                "headers": {"Authorization": "Bearer receiver-token", "X-Tenant": "acme"},
+            })
+            .to_string(),
+        )
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(hook["header_names"], serde_json::json!(["Authorization", "X-Tenant"]));
+    let listed: serde_json::Value = client.get(&hooks_url).header(auth.clone()).dispatch().into_json().unwrap();
+    assert_eq!(listed[0]["header_names"], hook["header_names"]);
+    assert!(!listed.to_string().contains("receiver-token"));
 
-    // Set quick reassign settings
-    let resp = client
-        .patch(format!("/api/v1/boards/{}", board_id))
+    client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(format!(r#"{{"quick_reassign_column_id": "{}", "quick_reassign_to": "Jordan"}}"#, first_col_id))
-        .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let board: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(board["quick_reassign_column_id"], first_col_id);
-    assert_eq!(board["quick_reassign_to"], "Jordan");
+        .body(r#"{"title": "Needs a token"}"#)
+        .dispatch();
+    let (headers, body) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(body["event"], "task.created");
+    let headers = headers.to_lowercase();
+    assert!(headers.lines().any(|l| l == "authorization: bearer receiver-token"));
+    assert!(headers.lines().any(|l| l == "x-tenant: acme"));
+    assert!(headers.contains("x-kanban-signature: sha256="));
+
+    // The test ping carries them too; `{}` removes them
+    let hook_url = format!("{}/{}", hooks_url, hook["id"].as_str().unwrap());
+    client.post(format!("{}/test", hook_url)).header(auth.clone()).dispatch();
+    let (headers, _) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(headers.to_lowercase().contains("x-tenant: acme"));
+    let updated: serde_json::Value = client
+        .patch(&hook_url)
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"headers": {}}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(updated["header_names"], serde_json::json!([]));
+    client.post(format!("{}/test", hook_url)).header(auth.clone()).dispatch();
+    let (headers, _) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(!headers.to_lowercase().contains("x-tenant"));
+}
 
-    // Clear with empty strings
+#[test]
+fn test_http_webhook_url_policy() {
+    let client = test_client_with_url_policy(kanban::url_policy::UrlPolicy::default());
+    let (board_id, manage_key) = create_test_board(&client, "SSRF Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let hooks_url = format!("/api/v1/boards/{}/webhooks", board_id);
+    let create = |url: &str| {
+        client
+            .post(&hooks_url)
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(serde_json::json!({"url": url}).to_string())
+            .dispatch()
+    };
+
+    for url in [
+        "http://127.0.0.1:8000/api/v1/admin/boards",
+        "http://localhost:6379/",
+        "http://169.254.169.254/latest/meta-data/",
+        "http://10.0.0.5/hook",
+        "http://[::1]/hook",
+        "file:///etc/passwd",
+        "gopher://example.com/",
+    ] {
+        let resp = create(url);
+        assert_eq!(resp.status(), Status::BadRequest, "{}", url);
+        let err: serde_json::Value = resp.into_json().unwrap();
+        assert_eq!(err["code"], "INVALID_URL", "{}", url);
+    }
+
+    let resp = create("https://hooks.example.com/kanban");
+    assert_eq!(resp.status(), Status::Ok);
+    let hook: serde_json::Value = resp.into_json().unwrap();
+    let hook_url = format!("{}/{}", hooks_url, hook["id"].as_str().unwrap());
     let resp = client
-        .patch(format!("/api/v1/boards/{}", board_id))
+        .patch(&hook_url)
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"quick_reassign_column_id": "", "quick_reassign_to": ""}"#)
+        .body(r#"{"url": "http://192.168.1.10/hook"}"#)
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let board: serde_json::Value = resp.into_json().unwrap();
-    assert!(board["quick_reassign_column_id"].is_null());
-    assert!(board["quick_reassign_to"].is_null());
+    assert_eq!(resp.status(), Status::BadRequest);
+    let listed: serde_json::Value = client.get(&hooks_url).header(auth.clone()).dispatch().into_json().unwrap();
+    assert_eq!(listed[0]["url"], "https://hooks.example.com/kanban");
 
-    // Invalid column ID should be rejected
+    // The validation hook is held to the same policy
+    let validation_url = format!("/api/v1/boards/{}/validation-hook", board_id);
+    for url in ["http://127.0.0.1:9000/policy", "http://169.254.169.254/latest/meta-data/", "http://[fe80::1]/policy"] {
+        let resp = client
+            .put(&validation_url)
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(serde_json::json!({"url": url}).to_string())
+            .dispatch();
+        assert_eq!(resp.status(), Status::BadRequest, "{}", url);
+        let err: serde_json::Value = resp.into_json().unwrap();
+        assert_eq!(err["code"], "INVALID_URL", "{}", url);
+    }
+    assert_eq!(client.get(&validation_url).header(auth.clone()).dispatch().status(), Status::NotFound);
+
+    // A hook saved before the policy was tightened is refused when called, never reached
+    let pool = client.rocket().state::<kanban::db::DbPool>().unwrap();
+    pool.get()
+        .unwrap()
+        .execute(
+            "INSERT INTO validation_hooks (board_id, url, secret, timeout_ms, fail_open)
+             VALUES (?1, 'http://127.0.0.1:9/policy', 'vhsec_old', 2000, 0)",
+            rusqlite::params![board_id],
+        )
+        .unwrap();
     let resp = client
-        .patch(format!("/api/v1/boards/{}", board_id))
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"quick_reassign_column_id": "nonexistent-col"}"#)
+        .body(r#"{"title": "Blocked"}"#)
         .dispatch();
-    assert_eq!(resp.status(), Status::BadRequest);
+    assert_eq!(resp.status(), Status::ServiceUnavailable);
     let err: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(err["code"], "INVALID_COLUMN");
+    assert_eq!(err["code"], "VALIDATION_UNAVAILABLE");
+    assert!(err["error"].as_str().unwrap().contains("ALLOW_PRIVATE_WEBHOOKS"));
 }
 
-// ============ Require Display Name ============
-
 #[test]
-fn test_http_require_display_name() {
+fn test_http_webhook_task_filters() {
+    let (url, rx) = spawn_hook_receiver();
     let client = test_client();
-
-    // Create board with require_display_name enabled
-    let resp = client
-        .post("/api/v1/boards")
-        .header(ContentType::JSON)
-        .body(r#"{"name": "Named Board", "require_display_name": true}"#)
-        .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let body: serde_json::Value = resp.into_json().unwrap();
-    let board_id = body["id"].as_str().unwrap().to_string();
-    let manage_key = body["manage_key"].as_str().unwrap().to_string();
+    let (board_id, manage_key) = create_test_board(&client, "Filter Board");
     let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let hooks_url = format!("/api/v1/boards/{}/webhooks", board_id);
+    let create_hook = |body: serde_json::Value| {
+        client.post(&hooks_url).header(ContentType::JSON).header(auth.clone()).body(body.to_string()).dispatch()
+    };
 
-    // Verify board setting is returned
-    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
-    let board: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(board["require_display_name"], true);
+    let resp = create_hook(serde_json::json!({"url": url, "filters": {"column_id": "no-such-column"}}));
+    assert_eq!(resp.status(), Status::BadRequest);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "INVALID_COLUMN");
 
-    // Creating a task without actor_name should fail
-    let resp = client
+    let hook: serde_json::Value = create_hook(serde_json::json!({
+        "url": url,
+        "events": ["task.created"],
+        "filters": {"labels": ["Deploy"], "min_priority": "high"},
+    }))
+    .into_json()
+    .unwrap();
+    assert_eq!(hook["filters"], serde_json::json!({"labels": ["deploy"], "min_priority": 2}));
+
+    let create_task = |body: serde_json::Value| {
+        client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(body.to_string())
+            .dispatch()
+    };
+    create_task(serde_json::json!({"title": "Low deploy", "labels": ["deploy"], "priority": 0}));
+    create_task(serde_json::json!({"title": "Urgent docs", "labels": ["docs"], "priority": 3}));
+    create_task(serde_json::json!({"title": "Roll back", "labels": ["deploy"], "priority": 3}));
+    let (_, body) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(body["data"]["title"], "Roll back");
+    assert!(rx.recv_timeout(Duration::from_millis(300)).is_err(), "Only the matching task is delivered");
+
+    // `{}` removes the filters
+    let updated: serde_json::Value = client
+        .patch(format!("{}/{}", hooks_url, hook["id"].as_str().unwrap()))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"filters": {}}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(updated["filters"], serde_json::json!({}));
+    create_task(serde_json::json!({"title": "Anything"}));
+    let (_, body) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(body["data"]["title"], "Anything");
+}
+
+#[test]
+fn test_http_webhook_redeliver() {
+    let (url, rx) = spawn_hook_receiver();
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Redeliver Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    // The consumer is down while the task is created
+    let hook: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/webhooks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"url": "http://127.0.0.1:1/hook", "events": ["task.created"]}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let hook_url = format!("/api/v1/boards/{}/webhooks/{}", board_id, hook["id"].as_str().unwrap());
+    client
         .post(format!("/api/v1/boards/{}/tasks", board_id))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"title": "Anonymous Task"}"#)
-        .dispatch();
-    assert_eq!(resp.status(), Status::BadRequest);
-    let err: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(err["code"], "DISPLAY_NAME_REQUIRED");
+        .body(r#"{"title": "Missed"}"#)
+        .dispatch();
+    let mut log = serde_json::Value::Null;
+    for _ in 0..100 {
+        log = client.get(format!("{}/deliveries", hook_url)).header(auth.clone()).dispatch().into_json().unwrap();
+        if log.as_array().unwrap().len() == 2 {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    let missed = &log[0];
+    assert_eq!(missed["success"], false);
 
-    // Creating a task WITH actor_name should succeed
+    // Back up (at a new address): resend that payload
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .patch(&hook_url)
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"title": "Named Task", "actor_name": "TestBot"}"#)
+        .body(serde_json::json!({"url": url}).to_string())
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let task: serde_json::Value = resp.into_json().unwrap();
-    let task_id = task["id"].as_str().unwrap();
+    let redeliver_url = format!("{}/deliveries/{}/redeliver", hook_url, missed["id"].as_str().unwrap());
+    let resp = client.post(&redeliver_url).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
 
-    // Commenting without actor_name should fail
+    let resp = client.post(&redeliver_url).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let delivery: serde_json::Value = resp.into_json().unwrap();
+    assert_ne!(delivery["id"], missed["id"]);
+    assert_eq!(delivery["success"], true);
+    assert_eq!(delivery["status_code"], 200);
+    assert_eq!(delivery["attempt"], 3);
+    assert_eq!(delivery["payload"], missed["payload"]);
+
+    let (headers, body) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(body, missed["payload"]);
+    assert_eq!(body["data"]["title"], "Missed");
+    assert!(headers.to_lowercase().contains("x-kanban-event: task.created"));
+    assert!(headers.to_lowercase().contains("x-kanban-signature: sha256="));
+
+    let log: serde_json::Value =
+        client.get(format!("{}/deliveries", hook_url)).header(auth.clone()).dispatch().into_json().unwrap();
+    assert_eq!(log.as_array().unwrap().len(), 3);
+    assert_eq!(log[0]["id"], delivery["id"]);
+
+    let resp = client
+        .post(format!("{}/deliveries/no-such-delivery/redeliver", hook_url))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[test]
+fn test_http_webhook_column_routing() {
+    let (pager_url, pager_rx) = spawn_hook_receiver();
+    let (chat_url, chat_rx) = spawn_hook_receiver();
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Routed Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let board: serde_json::Value = client.get(format!("/api/v1/boards/{}", board_id)).dispatch().into_json().unwrap();
+    let columns = board["columns"].as_array().unwrap();
+    let first_col = columns[0]["id"].as_str().unwrap().to_string();
+    let last_col = columns.last().unwrap()["id"].as_str().unwrap().to_string();
+    let add_hook = |url: &str| -> String {
+        let hook: serde_json::Value = client
+            .post(format!("/api/v1/boards/{}/webhooks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(serde_json::json!({"url": url, "events": ["task.created", "task.moved"]}).to_string())
+            .dispatch()
+            .into_json()
+            .unwrap();
+        hook["id"].as_str().unwrap().to_string()
+    };
+    let pager_id = add_hook(&pager_url);
+    add_hook(&chat_url);
+    let routes_url = format!("/api/v1/boards/{}/webhook-routes", board_id);
+
+    let resp = client.get(&routes_url).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+        .put(&routes_url)
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"message": "Anonymous comment"}"#)
+        .body(serde_json::json!({"routes": [{"column_id": last_col, "webhook_ids": ["nope"]}]}).to_string())
         .dispatch();
     assert_eq!(resp.status(), Status::BadRequest);
     let err: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(err["code"], "DISPLAY_NAME_REQUIRED");
+    assert_eq!(err["code"], "INVALID_WEBHOOK");
 
-    // Commenting WITH actor_name should succeed
+    // Only the last column notifies the pager hook
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+        .put(&routes_url)
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"message": "Named comment", "actor_name": "TestBot"}"#)
+        .body(serde_json::json!({"routes": [{"column_id": last_col, "webhook_ids": [pager_id]}]}).to_string())
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
+    let routes: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(routes["routes"].as_array().unwrap().len(), 1);
+    assert_eq!(routes["routes"][0]["column_id"], last_col.as_str());
+    assert_eq!(routes["routes"][0]["webhook_ids"][0], pager_id.as_str());
 
-    // Toggling setting off should allow anonymous again
-    let resp = client
-        .patch(format!("/api/v1/boards/{}", board_id))
+    let task: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"require_display_name": false}"#)
+        .body(serde_json::json!({"title": "Outage", "column_id": first_col}).to_string())
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let task_id = task["id"].as_str().unwrap();
+    let (_, created) = chat_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(created["event"], "task.created");
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}", board_id, task_id, last_col))
+        .header(auth.clone())
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let board: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(board["require_display_name"], false);
+    // The pager hook never saw the create in the first column
+    let (_, moved) = pager_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(moved["event"], "task.moved");
+    assert_eq!(moved["data"]["to"], last_col.as_str());
+    let (_, moved) = chat_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(moved["event"], "task.moved");
 
-    // Now anonymous task creation should work
+    // Clearing the table restores delivery of everything
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .put(&routes_url)
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"title": "Anonymous OK Now"}"#)
+        .body(r#"{"routes": []}"#)
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
+    let routes: serde_json::Value = resp.into_json().unwrap();
+    assert!(routes["routes"].as_array().unwrap().is_empty());
 }
 
 #[test]
-fn test_http_comment_mentions() {
+fn test_http_slack_webhook() {
+    let (slack_url, slack_rx) = spawn_hook_receiver();
     let client = test_client();
-    let (board_id, key) = create_test_board(&client, "Mentions Test");
-    let auth = Header::new("Authorization", format!("Bearer {}", key));
+    let (board_id, manage_key) = create_test_board(&client, "Slack Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let hooks_url = format!("/api/v1/boards/{}/webhooks", board_id);
 
-    // Create a task
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .post(&hooks_url)
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"title": "Test mentions"}"#)
+        .body(serde_json::json!({"url": slack_url, "kind": "teams"}).to_string())
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let task: serde_json::Value = resp.into_json().unwrap();
-    let task_id = task["id"].as_str().unwrap();
-
-    // Post a comment with @mentions
-    let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+    assert_eq!(resp.status(), Status::BadRequest);
+    let hook: serde_json::Value = client
+        .post(&hooks_url)
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"message": "Hey @Jordan and @Nanook, please review this", "actor_name": "TestBot"}"#)
-        .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
+        .body(serde_json::json!({"url": slack_url, "kind": "Slack"}).to_string())
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(hook["kind"], "slack");
+    let hooks: serde_json::Value = client.get(&hooks_url).header(auth.clone()).dispatch().into_json().unwrap();
+    assert_eq!(hooks[0]["kind"], "slack");
 
-    // Post a comment without mentions
+    // Board events aren't sent to Slack; the first delivery is the task's
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+        .patch(format!("/api/v1/boards/{}", board_id))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"message": "No mentions here", "actor_name": "TestBot"}"#)
-        .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-
-    // Check activity — should show mentions on first comment
-    let resp = client
-        .get(format!("/api/v1/boards/{}/activity?limit=50", board_id))
+        .body(r#"{"description": "Launch tracking"}"#)
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let items: Vec<serde_json::Value> = resp.into_json().unwrap();
-    let comment_events: Vec<&serde_json::Value> = items.iter()
-        .filter(|i| i["event_type"] == "comment")
-        .collect();
-    assert_eq!(comment_events.len(), 2);
-
-    // Find the comment with mentions (check data.mentions)
-    let with_mentions = comment_events.iter()
-        .find(|e| e["data"]["mentions"].is_array())
-        .expect("Should have a comment with mentions");
-    let mentions = with_mentions["mentions"].as_array()
-        .expect("Top-level mentions field should exist");
-    assert_eq!(mentions.len(), 2);
-    assert!(mentions.iter().any(|m| m == "Jordan"));
-    assert!(mentions.iter().any(|m| m == "Nanook"));
-
-    // The other comment should not have mentions
-    let without_mentions = comment_events.iter()
-        .find(|e| !e["data"]["mentions"].is_array())
-        .expect("Should have a comment without mentions");
-    assert!(without_mentions["mentions"].is_null());
+    let board: serde_json::Value = client.get(format!("/api/v1/boards/{}", board_id)).dispatch().into_json().unwrap();
+    let columns = board["columns"].as_array().unwrap();
+    let task: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"title": "Write changelog", "actor_name": "Nanook"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let (_, created) = slack_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(created.get("event").is_none());
+    let section = created["blocks"][0]["text"]["text"].as_str().unwrap();
+    assert_eq!(section, format!("*Write changelog*\nCreated in *{}*", columns[0]["name"].as_str().unwrap()));
+    assert_eq!(created["blocks"][1]["elements"][0]["text"], "Board *Slack Board* · by Nanook · `task.created`");
 
-    // Filter activity by ?mentioned=Jordan — should return only relevant events
-    let resp = client
-        .get(format!("/api/v1/boards/{}/activity?mentioned=Jordan", board_id))
+    client
+        .post(format!(
+            "/api/v1/boards/{}/tasks/{}/move/{}",
+            board_id,
+            task["id"].as_str().unwrap(),
+            columns[1]["id"].as_str().unwrap()
+        ))
+        .header(auth.clone())
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let items: Vec<serde_json::Value> = resp.into_json().unwrap();
-    // Should have at least the comment that mentions Jordan
-    assert!(items.iter().any(|i| i["event_type"] == "comment" && i["data"]["mentions"].is_array()));
+    let (_, moved) = slack_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    let section = moved["blocks"][0]["text"]["text"].as_str().unwrap();
+    assert!(section.ends_with(&format!(
+        "*{}* → *{}*",
+        columns[0]["name"].as_str().unwrap(),
+        columns[1]["name"].as_str().unwrap()
+    )));
 
-    // Filter by ?mentioned=nobody — should return no comment mentions but may return actor-matched events
     let resp = client
-        .get(format!("/api/v1/boards/{}/activity?mentioned=nobody", board_id))
+        .patch(format!("{}/{}", hooks_url, hook["id"].as_str().unwrap()))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"kind": "json"}"#)
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let items: Vec<serde_json::Value> = resp.into_json().unwrap();
-    let mention_comments: Vec<&serde_json::Value> = items.iter()
-        .filter(|i| i["event_type"] == "comment" && i["data"]["mentions"].is_array())
-        .collect();
-    assert_eq!(mention_comments.len(), 0);
+    let hook: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(hook["kind"], "json");
 }
 
 #[test]
-fn test_mention_extraction_quoted() {
+fn test_http_discord_webhook() {
+    let (discord_url, discord_rx) = spawn_hook_receiver();
     let client = test_client();
-    let (board_id, key) = create_test_board(&client, "Quoted Mentions");
-    let auth = Header::new("Authorization", format!("Bearer {}", key));
-
-    let resp = client
-        .post(format!("/api/v1/boards/{}/tasks", board_id))
+    let (board_id, manage_key) = create_test_board(&client, "Discord Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let hook: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/webhooks", board_id))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"title": "Quoted mention test"}"#)
-        .dispatch();
-    let task: serde_json::Value = resp.into_json().unwrap();
-    let task_id = task["id"].as_str().unwrap();
+        .body(serde_json::json!({"url": discord_url, "kind": "discord", "events": ["task.created", "task.comment"]}).to_string())
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(hook["kind"], "discord");
 
-    // Post comment with quoted mention
-    let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+    let task: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"message": "cc @\"Team Lead\" and @dev-bot", "actor_name": "Tester"}"#)
-        .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-
-    let resp = client
-        .get(format!("/api/v1/boards/{}/activity?limit=10", board_id))
-        .dispatch();
-    let items: Vec<serde_json::Value> = resp.into_json().unwrap();
-    let comment = items.iter()
-        .find(|i| i["event_type"] == "comment" && i["data"]["mentions"].is_array())
-        .expect("Should have comment with mentions");
-    let mentions = comment["mentions"].as_array().unwrap();
-    assert_eq!(mentions.len(), 2);
-    assert!(mentions.iter().any(|m| m == "Team Lead"));
-    assert!(mentions.iter().any(|m| m == "dev-bot"));
+        .body(r#"{"title": "Page on-call", "priority": "critical", "actor_name": "Nanook"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let (_, created) = discord_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    let embed = &created["embeds"][0];
+    assert_eq!(embed["title"], "Page on-call");
+    assert!(embed["description"].as_str().unwrap().starts_with("Created in **"));
+    assert_eq!(embed["color"], 0xe74c3c);
+    assert_eq!(embed["fields"][0]["value"], "critical");
+    assert_eq!(embed["footer"]["text"], "Discord Board · task.created · by Nanook");
+
+    client
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task["id"].as_str().unwrap()))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "paged *twice*", "actor_name": "Sam"}"#)
+        .dispatch();
+    let (_, comment) = discord_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(comment["embeds"][0]["description"], "Commented:\n> paged \\*twice\\*");
 }
 
 #[test]
-fn test_http_require_display_name_all_endpoints() {
+fn test_http_telegram_notifications() {
+    let (relay_url, relay_rx) = spawn_hook_receiver();
     let client = test_client();
-
-    // Create board with require_display_name enabled
-    let resp = client
-        .post("/api/v1/boards")
-        .header(ContentType::JSON)
-        .body(r#"{"name": "Display Name Audit", "require_display_name": true}"#)
-        .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let body: serde_json::Value = resp.into_json().unwrap();
-    let board_id = body["id"].as_str().unwrap().to_string();
-    let manage_key = body["manage_key"].as_str().unwrap().to_string();
+    let (board_id, manage_key) = create_test_board(&client, "Telegram Board");
     let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let telegram_url = format!("/api/v1/boards/{}/telegram", board_id);
 
-    // Get column ID for moves
-    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
-    let board: serde_json::Value = resp.into_json().unwrap();
-    let columns = board["columns"].as_array().unwrap();
-    let col_id = columns[0]["id"].as_str().unwrap().to_string();
-    let col2_id = columns[1]["id"].as_str().unwrap().to_string();
-
-    // Create a task WITH actor_name (should succeed)
+    let resp = client.get(&telegram_url).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .put(&telegram_url)
         .header(ContentType::JSON)
-        .header(auth.clone())
-        .body(r#"{"title": "Test Task", "actor_name": "TestBot"}"#)
+        .body(r#"{"bot_token": "123456:AAEsecret", "chat_id": "-100200"}"#)
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let task: serde_json::Value = resp.into_json().unwrap();
-    let task_id = task["id"].as_str().unwrap().to_string();
-
-    // UPDATE task without actor_name → should fail
+    assert_eq!(resp.status(), Status::Unauthorized);
     let resp = client
-        .patch(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
+        .put(&telegram_url)
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"title": "Updated Title"}"#)
+        .body(r#"{"bot_token": "not-a-token", "chat_id": "-100200"}"#)
         .dispatch();
     assert_eq!(resp.status(), Status::BadRequest);
-    let err: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(err["code"], "DISPLAY_NAME_REQUIRED");
 
-    // UPDATE task with actor_name → should succeed
     let resp = client
-        .patch(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
+        .put(&telegram_url)
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"title": "Updated Title", "actor_name": "TestBot"}"#)
+        .body(r#"{"bot_token": "123456:AAEsecret", "chat_id": "-100200", "events": ["task.created"]}"#)
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
+    let chat: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(chat["url"], "https://api.telegram.org/bot123456:…/sendMessage");
+    assert_eq!(chat["chat_id"], "-100200");
+    assert_eq!(chat["events"], serde_json::json!(["task.created"]));
+    let webhook_id = chat["webhook_id"].as_str().unwrap().to_string();
+
+    // Setting it again replaces the chat and keeps the filter
+    let chat: serde_json::Value = client
+        .put(&telegram_url)
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"bot_token": "123456:AAEsecret", "chat_id": "@ops"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!((chat["webhook_id"].as_str(), chat["chat_id"].as_str()), (Some(webhook_id.as_str()), Some("@ops")));
+    assert_eq!(chat["events"], serde_json::json!(["task.created"]));
+    let hooks: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/webhooks", board_id))
+        .header(auth.clone())
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(hooks[0]["kind"], "telegram");
+    assert!(!hooks[0]["url"].as_str().unwrap().contains("AAEsecret"));
 
-    // MOVE task without actor → should fail
-    let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}", board_id, task_id, col2_id))
+    // Point the delivery at a local receiver to see what Telegram would get
+    client
+        .patch(format!("/api/v1/boards/{}/webhooks/{}", board_id, webhook_id))
+        .header(ContentType::JSON)
         .header(auth.clone())
+        .body(serde_json::json!({ "url": relay_url }).to_string())
         .dispatch();
-    assert_eq!(resp.status(), Status::BadRequest);
-    let err: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(err["code"], "DISPLAY_NAME_REQUIRED");
-
-    // MOVE task with actor → should succeed
-    let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}?actor=TestBot", board_id, task_id, col2_id))
+    client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
         .header(auth.clone())
+        .body(r#"{"title": "Restart <worker>", "actor_name": "Nanook"}"#)
         .dispatch();
+    let (_, sent) = relay_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(sent["chat_id"], "@ops");
+    assert_eq!(sent["parse_mode"], "HTML");
+    let text = sent["text"].as_str().unwrap();
+    assert!(text.starts_with("<b>Restart &lt;worker&gt;</b>\nCreated in <b>"));
+    assert!(text.ends_with("<i>Telegram Board · by Nanook</i>"));
+
+    let resp = client.delete(&telegram_url).header(auth.clone()).dispatch();
     assert_eq!(resp.status(), Status::Ok);
+    let resp = client.get(&telegram_url).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[test]
+fn test_http_inbound_hooks() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Inbound Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let hooks_url = format!("/api/v1/boards/{}/inbound", board_id);
 
-    // CLAIM task without agent → should fail
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/claim", board_id, task_id))
+        .post(&hooks_url)
+        .header(ContentType::JSON)
+        .body(r#"{"name": "CI"}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let resp = client
+        .post(&hooks_url)
+        .header(ContentType::JSON)
         .header(auth.clone())
+        .body(r#"{"name": "CI", "mapping": {"title": "{{secret}}"}}"#)
         .dispatch();
     assert_eq!(resp.status(), Status::BadRequest);
     let err: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(err["code"], "DISPLAY_NAME_REQUIRED");
+    assert_eq!(err["code"], "INVALID_TEMPLATE");
 
-    // CLAIM task with agent → should succeed
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/claim?actor=TestBot", board_id, task_id))
+        .post(&hooks_url)
+        .header(ContentType::JSON)
         .header(auth.clone())
+        .body(
+            r#"{"name": "CI failures", "mapping": {
+                "title": "{{payload.pipeline}} failed on {{payload.branch}}",
+                "description": "{{payload.url}}",
+                "priority": "high",
+                "labels": ["ci", "{{payload.branch}}"],
+                "column": "{{payload.stage}}"
+            }}"#,
+        )
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
+    let hook: serde_json::Value = resp.into_json().unwrap();
+    let hook_id = hook["id"].as_str().unwrap().to_string();
+    let hook_token = hook["token"].as_str().unwrap().to_string();
+    assert!(hook_token.starts_with("inb_"));
+    assert_eq!(hook["name"], "CI failures");
 
-    // RELEASE task without actor → should fail
+    // The token is the only credential the sender needs
+    let receive_url = format!("{}/{}", hooks_url, hook_token);
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/release", board_id, task_id))
-        .header(auth.clone())
+        .post(&receive_url)
+        .header(ContentType::JSON)
+        .body(r#"{"pipeline": "deploy", "branch": "main", "url": "https://ci.example.com/1", "stage": "in progress"}"#)
         .dispatch();
-    assert_eq!(resp.status(), Status::BadRequest);
-    let err: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(err["code"], "DISPLAY_NAME_REQUIRED");
-
-    // RELEASE task with actor → should succeed
-    let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/release?actor=TestBot", board_id, task_id))
+    assert_eq!(resp.status(), Status::Ok);
+    let task: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(task["title"], "deploy failed on main");
+    assert_eq!(task["description"], "https://ci.example.com/1");
+    assert_eq!(task["priority"], 2);
+    assert_eq!(task["labels"], serde_json::json!(["ci", "main"]));
+    assert_eq!(task["column_name"], "In Progress");
+    assert_eq!(task["created_by"], "CI failures");
+    assert_eq!(task["metadata"]["inbound"]["hook_id"], hook_id.as_str());
+    assert_eq!(task["metadata"]["inbound"]["payload"]["pipeline"], "deploy");
+
+    // Fields the payload lacks fall back: first column, hook name as title
+    let resp = client
+        .patch(format!("{}/{}", hooks_url, hook_id))
+        .header(ContentType::JSON)
         .header(auth.clone())
+        .body(r#"{"mapping": {"description": "{{payload.message}}"}}"#)
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
+    let task: serde_json::Value = client
+        .post(&receive_url)
+        .header(ContentType::JSON)
+        .body(r#"{"message": "no title here"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(task["title"], "CI failures");
+    assert_eq!(task["description"], "no title here");
+    assert_eq!(task["column_name"], "To Do");
+
+    let resp = client.post(format!("{}/inb_wrong", hooks_url)).header(ContentType::JSON).body("{}").dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+    let big = serde_json::json!({"blob": "x".repeat(70 * 1024)}).to_string();
+    let resp = client.post(&receive_url).header(ContentType::JSON).body(big).dispatch();
+    assert_eq!(resp.status(), Status::PayloadTooLarge);
 
-    // ARCHIVE task without actor → should fail
-    let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/archive", board_id, task_id))
+    // Listing never shows the token
+    let hooks: serde_json::Value = client.get(&hooks_url).header(auth.clone()).dispatch().into_json().unwrap();
+    assert_eq!(hooks.as_array().unwrap().len(), 1);
+    assert!(hooks[0].get("token").is_none());
+    assert!(hooks[0]["last_used_at"].is_string());
+
+    let resp = client.delete(format!("{}/{}", hooks_url, hook_id)).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.post(&receive_url).header(ContentType::JSON).body("{}").dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[test]
+fn test_http_webhook_signing_key_rotation() {
+    use hmac::{Hmac, Mac};
+
+    let (url, hooks_rx) = spawn_hook_receiver();
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Rotating Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let hook: serde_json::Value = client
+        .post(format!("/api/v1/boards/{}/webhooks", board_id))
+        .header(ContentType::JSON)
         .header(auth.clone())
-        .dispatch();
+        .body(serde_json::json!({"url": url, "events": ["task.created"]}).to_string())
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let keys_url = format!("/api/v1/boards/{}/webhooks/{}/signing-keys", board_id, hook["id"].as_str().unwrap());
+    let add_key = |body: serde_json::Value| {
+        client.post(&keys_url).header(ContentType::JSON).header(auth.clone()).body(body.to_string()).dispatch()
+    };
+    let create_task = |title: &str| {
+        client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(serde_json::json!({"title": title}).to_string())
+            .dispatch();
+        let (headers, _) = hooks_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        headers
+            .lines()
+            .find_map(|l| l.strip_prefix("x-kanban-signature: ").or_else(|| l.strip_prefix("X-Kanban-Signature: ")))
+            .unwrap()
+            .trim()
+            .to_string()
+    };
+
+    let resp = client.get(&keys_url).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let keys: serde_json::Value = client.get(&keys_url).header(auth.clone()).dispatch().into_json().unwrap();
+    assert_eq!(keys.as_array().unwrap().len(), 1);
+    assert_eq!(keys[0]["id"], "default");
+    assert_eq!(keys[0]["status"], "active");
+    assert!(keys[0].get("secret").is_none());
+    assert!(create_task("Before rotation").starts_with("sha256="));
+
+    let resp = add_key(serde_json::json!({"active_from": "2030-01-01T00:00:00Z", "active_until": "2029-01-01T00:00:00Z"}));
     assert_eq!(resp.status(), Status::BadRequest);
-    let err: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(err["code"], "DISPLAY_NAME_REQUIRED");
-
-    // ARCHIVE task with actor → should succeed
-    let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/archive?actor=TestBot", board_id, task_id))
+    let pending: serde_json::Value =
+        add_key(serde_json::json!({"active_from": "2099-01-01T00:00:00Z"})).into_json().unwrap();
+    assert_eq!(pending["status"], "pending");
+    let expired: serde_json::Value = add_key(serde_json::json!({
+        "active_from": "2020-01-01T00:00:00Z",
+        "active_until": "2021-01-01T00:00:00Z"
+    }))
+    .into_json()
+    .unwrap();
+    assert_eq!(expired["status"], "expired");
+    let resp = add_key(serde_json::json!({}));
+    assert_eq!(resp.status(), Status::Ok);
+    let new_key: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(new_key["status"], "active");
+    let new_id = new_key["id"].as_str().unwrap();
+    let new_secret = new_key["secret"].as_str().unwrap();
+
+    // Both active keys sign, newest first; pending and expired keys don't
+    let header = create_task("During rotation");
+    let entries: Vec<&str> = header.split(", ").collect();
+    assert_eq!(entries.len(), 2);
+    assert!(entries[0].starts_with(&format!("kid={};sha256=", new_id)));
+    assert!(entries[1].starts_with("kid=default;sha256="));
+    assert!(!header.contains(pending["id"].as_str().unwrap()));
+    assert!(!header.contains(expired["id"].as_str().unwrap()));
+
+    // The new key's signature checks out against the delivered body
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(new_secret.as_bytes()).unwrap();
+    client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
         .header(auth.clone())
+        .body(r#"{"title": "Verify me"}"#)
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
+    let (headers, body) = hooks_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    mac.update(serde_json::to_vec(&body).unwrap().as_slice());
+    let expected = hex::encode(mac.finalize().into_bytes());
+    assert!(headers.contains(&format!("kid={};sha256={}", new_id, expected)));
 
-    // UNARCHIVE task without actor → should fail
+    // Retiring the default key is scheduled, not immediate; it can't be deleted
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/unarchive", board_id, task_id))
+        .patch(format!("{}/default", keys_url))
+        .header(ContentType::JSON)
         .header(auth.clone())
+        .body(r#"{"active_until": "2098-01-01T00:00:00Z"}"#)
         .dispatch();
+    let default: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(default["active_until"], "2098-01-01 00:00:00");
+    assert_eq!(default["status"], "active");
+    let resp = client.delete(format!("{}/default", keys_url)).header(auth.clone()).dispatch();
     assert_eq!(resp.status(), Status::BadRequest);
-    let err: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(err["code"], "DISPLAY_NAME_REQUIRED");
 
-    // UNARCHIVE task with actor → should succeed
+    let resp = client.delete(format!("{}/{}", keys_url, new_id)).header(auth.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert!(create_task("After rotation").starts_with("sha256="));
+}
+
+// ============ Single Task GET ============
+
+#[test]
+fn test_http_get_single_task() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Single Task Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    // Get columns to find first column ID
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let col_id = board["columns"][0]["id"].as_str().unwrap();
+
+    // Create a task
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/unarchive?actor=TestBot", board_id, task_id))
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
         .header(auth.clone())
+        .body(format!(
+            r#"{{"title": "Test Task", "description": "A description", "column_id": "{}", "priority": 2, "labels": ["bug", "urgent"], "actor_name": "Tester"}}"#,
+            col_id
+        ))
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
+    let task: serde_json::Value = resp.into_json().unwrap();
+    let task_id = task["id"].as_str().unwrap();
 
-    // DELETE task without actor → should fail
+    // GET single task
     let resp = client
-        .delete(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
-        .header(auth.clone())
+        .get(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
         .dispatch();
-    assert_eq!(resp.status(), Status::BadRequest);
-    let err: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(err["code"], "DISPLAY_NAME_REQUIRED");
+    assert_eq!(resp.status(), Status::Ok);
+    let fetched: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(fetched["title"], "Test Task");
+    assert_eq!(fetched["description"], "A description");
+    assert_eq!(fetched["priority"], 2);
+    assert_eq!(fetched["created_by"], "Tester");
+}
+
+#[test]
+fn test_http_get_single_task_not_found() {
+    let client = test_client();
+    let (board_id, _) = create_test_board(&client, "Task Not Found Board");
 
-    // DELETE task with actor → should succeed
     let resp = client
-        .delete(format!("/api/v1/boards/{}/tasks/{}?actor=TestBot", board_id, task_id))
-        .header(auth.clone())
+        .get(format!("/api/v1/boards/{}/tasks/nonexistent-id", board_id))
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(resp.status(), Status::NotFound);
 }
 
+// ============ Task Events (Activity History) ============
+
 #[test]
-fn test_http_list_tasks_updated_before_filter() {
+fn test_http_task_events() {
     let client = test_client();
-    let (board_id, manage_key) = create_test_board(&client, "Stale Filter");
+    let (board_id, manage_key) = create_test_board(&client, "Task Events Board");
     let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
 
-    // Create two tasks
+    // Get columns
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let col_id = board["columns"][0]["id"].as_str().unwrap();
+    let col2_id = board["columns"][1]["id"].as_str().unwrap();
+
+    // Create a task
     let resp = client
         .post(format!("/api/v1/boards/{}/tasks", board_id))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"title": "Task A", "priority": 1}"#)
+        .body(format!(
+            r#"{{"title": "Events Task", "column_id": "{}", "actor_name": "Creator"}}"#,
+            col_id
+        ))
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
+    let task: serde_json::Value = resp.into_json().unwrap();
+    let task_id = task["id"].as_str().unwrap();
 
+    // Move the task to generate an event
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks", board_id))
-        .header(ContentType::JSON)
+        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}?actor=Mover", board_id, task_id, col2_id))
         .header(auth.clone())
-        .body(r#"{"title": "Task B", "priority": 2}"#)
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
 
-    // Without filter → both tasks returned
+    // Add a comment to generate another event
     let resp = client
-        .get(format!("/api/v1/boards/{}/tasks", board_id))
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(r#"{"message": "A test comment", "actor_name": "Commenter"}"#)
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let tasks: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(tasks.as_array().unwrap().len(), 2);
 
-    // With updated_before far in the future → both tasks returned
+    // GET task events
     let resp = client
-        .get(format!(
-            "/api/v1/boards/{}/tasks?updated_before=2099-12-31T23:59:59",
-            board_id
-        ))
+        .get(format!("/api/v1/boards/{}/tasks/{}/events", board_id, task_id))
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let tasks: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(tasks.as_array().unwrap().len(), 2);
+    let events: serde_json::Value = resp.into_json().unwrap();
+    let events_arr = events.as_array().unwrap();
 
-    // With updated_before far in the past → no tasks returned
-    let resp = client
-        .get(format!(
-            "/api/v1/boards/{}/tasks?updated_before=2000-01-01T00:00:00",
-            board_id
-        ))
-        .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let tasks: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(tasks.as_array().unwrap().len(), 0);
-}
+    // Should have at least 3 events: created, moved, comment
+    assert!(events_arr.len() >= 3, "Expected at least 3 events, got {}", events_arr.len());
 
-// ============ Stale Query Parameter ============
+    // Verify event types
+    let event_types: Vec<&str> = events_arr.iter()
+        .map(|e| e["event_type"].as_str().unwrap_or(""))
+        .collect();
+    assert!(event_types.contains(&"created"), "Should have 'created' event");
+    assert!(event_types.contains(&"moved"), "Should have 'moved' event");
+    assert!(event_types.contains(&"comment"), "Should have 'comment' event");
+}
 
 #[test]
-fn test_http_list_tasks_stale_filter() {
+fn test_http_events_filter_by_actor() {
     let client = test_client();
-    let (board_id, manage_key) = create_test_board(&client, "Stale Filter Minutes");
+    let (board_id, manage_key) = create_test_board(&client, "Actor Filter Board");
     let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
 
-    // Create a task
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let col_id = board["columns"][0]["id"].as_str().unwrap();
+    let col2_id = board["columns"][1]["id"].as_str().unwrap();
+
+    let mut task_ids = Vec::new();
+    for (title, actor) in [("First", "Agent-7"), ("Second", "Agent-9")] {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(
+                r#"{{"title": "{}", "column_id": "{}", "actor_name": "{}"}}"#,
+                title, col_id, actor
+            ))
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        let task: serde_json::Value = resp.into_json().unwrap();
+        task_ids.push(task["id"].as_str().unwrap().to_string());
+    }
+
+    // Agent-7 also moves the second task
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks", board_id))
-        .header(ContentType::JSON)
+        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}?actor=Agent-7", board_id, task_ids[1], col2_id))
         .header(auth.clone())
-        .body(r#"{"title": "Fresh Task", "priority": 1}"#)
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
 
-    // stale=1 (1 minute) — task was just created, so it's NOT stale yet
+    // Board feed: only Agent-7's events, matched case-insensitively
     let resp = client
-        .get(format!("/api/v1/boards/{}/tasks?stale=1", board_id))
+        .get(format!("/api/v1/boards/{}/activity?actor=agent-7", board_id))
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let tasks: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(tasks.as_array().unwrap().len(), 0, "freshly created task should not be stale");
-
-    // stale=0 should return error (must be positive)
-    let resp = client
-        .get(format!("/api/v1/boards/{}/tasks?stale=0", board_id))
-        .dispatch();
-    assert_eq!(resp.status(), Status::BadRequest);
-    let err: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(err["code"], "INVALID_STALE");
+    let items: Vec<serde_json::Value> = resp.into_json().unwrap();
+    assert_eq!(items.len(), 2);
+    assert!(items.iter().all(|i| i["actor"] == "Agent-7"));
 
-    // stale=-5 should return error
+    // Combines with the seq cursor
+    let first_seq = items.iter().map(|i| i["seq"].as_i64().unwrap()).min().unwrap();
     let resp = client
-        .get(format!("/api/v1/boards/{}/tasks?stale=-5", board_id))
+        .get(format!("/api/v1/boards/{}/activity?actor=Agent-7&after={}", board_id, first_seq))
         .dispatch();
-    assert_eq!(resp.status(), Status::BadRequest);
+    let items: Vec<serde_json::Value> = resp.into_json().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["event_type"], "moved");
 
-    // stale=999999 (tasks older than 999999 min) — fresh task is NOT that old
+    // Task log: the second task has a created event by Agent-9 and a move by Agent-7
     let resp = client
-        .get(format!("/api/v1/boards/{}/tasks?stale=999999", board_id))
+        .get(format!("/api/v1/boards/{}/tasks/{}/events?actor=Agent-9", board_id, task_ids[1]))
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let tasks: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(tasks.as_array().unwrap().len(), 0, "fresh task should not be stale even with large window");
+    let events: Vec<serde_json::Value> = resp.into_json().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["event_type"], "created");
 
-    // Verify without stale filter — task is there
     let resp = client
-        .get(format!("/api/v1/boards/{}/tasks", board_id))
+        .get(format!("/api/v1/boards/{}/tasks/{}/events?actor=Nobody", board_id, task_ids[1]))
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let tasks: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(tasks.as_array().unwrap().len(), 1, "task exists without stale filter");
+    let events: Vec<serde_json::Value> = resp.into_json().unwrap();
+    assert!(events.is_empty());
 }
 
-// ============ Reorder & Batch Actor Attribution ============
+// ============ Column Creation ============
 
 #[test]
-fn test_http_reorder_and_batch_actor_attribution() {
+fn test_http_create_column() {
     let client = test_client();
-    let (board_id, manage_key) = create_test_board(&client, "Actor Attribution");
+    let (board_id, manage_key) = create_test_board(&client, "Column Create Board");
     let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
 
-    // Create a task
+    // Get initial column count
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let initial_count = board["columns"].as_array().unwrap().len();
+
+    // Create a new column
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .post(format!("/api/v1/boards/{}/columns", board_id))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"title": "Reorder Me", "actor_name": "TestUser"}"#)
+        .body(r#"{"name": "Testing", "wip_limit": 5}"#)
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let task: serde_json::Value = resp.into_json().unwrap();
-    let task_id = task["id"].as_str().unwrap();
+    let col: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(col["name"], "Testing");
+    assert_eq!(col["wip_limit"], 5);
 
-    // Get the column IDs
+    // Verify column count increased
     let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
     let board: serde_json::Value = resp.into_json().unwrap();
-    let col_id = board["columns"][0]["id"].as_str().unwrap();
+    assert_eq!(board["columns"].as_array().unwrap().len(), initial_count + 1);
+}
+
+#[test]
+fn test_http_create_column_no_auth() {
+    let client = test_client();
+    let (board_id, _) = create_test_board(&client, "Column No Auth Board");
 
-    // Reorder with actor param
     let resp = client
-        .post(format!(
-            "/api/v1/boards/{}/tasks/{}/reorder?actor=ReorderBot",
-            board_id, task_id
-        ))
+        .post(format!("/api/v1/boards/{}/columns", board_id))
         .header(ContentType::JSON)
-        .header(auth.clone())
-        .body(format!(r#"{{"position": 0, "column_id": "{}"}}"#, col_id))
+        .body(r#"{"name": "Unauthorized Column"}"#)
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(resp.status(), Status::Unauthorized);
+}
 
-    // Check activity for reorder event with correct actor
+// ============ Dependency Deletion ============
+
+#[test]
+fn test_http_delete_dependency() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Dep Delete Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    // Get first column
+    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
+    let board: serde_json::Value = resp.into_json().unwrap();
+    let col_id = board["columns"][0]["id"].as_str().unwrap();
+
+    // Create two tasks
     let resp = client
-        .get(format!("/api/v1/boards/{}/activity?limit=10", board_id))
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(format!(r#"{{"title": "Blocker", "column_id": "{}", "actor_name": "Tester"}}"#, col_id))
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let activity: serde_json::Value = resp.into_json().unwrap();
-    let events = activity.as_array().unwrap();
-    let reorder_event = events.iter().find(|e| e["event_type"] == "reordered");
-    assert!(reorder_event.is_some(), "Should have a reordered event");
-    assert_eq!(reorder_event.unwrap()["actor"], "ReorderBot");
+    let task1: serde_json::Value = resp.into_json().unwrap();
+    let task1_id = task1["id"].as_str().unwrap();
 
-    // Create another task for batch test
     let resp = client
         .post(format!("/api/v1/boards/{}/tasks", board_id))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(r#"{"title": "Batch Me", "actor_name": "TestUser"}"#)
+        .body(format!(r#"{{"title": "Blocked", "column_id": "{}", "actor_name": "Tester"}}"#, col_id))
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
     let task2: serde_json::Value = resp.into_json().unwrap();
     let task2_id = task2["id"].as_str().unwrap();
 
-    // Batch update with actor
+    // Create a dependency
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/batch", board_id))
+        .post(format!("/api/v1/boards/{}/dependencies", board_id))
         .header(ContentType::JSON)
         .header(auth.clone())
         .body(format!(
-            r#"{{"actor_name": "BatchBot", "operations": [{{"action": "update", "task_ids": ["{}"], "priority": 3}}]}}"#,
-            task2_id
+            r#"{{"blocker_task_id": "{}", "blocked_task_id": "{}"}}"#,
+            task1_id, task2_id
         ))
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
+    let dep: serde_json::Value = resp.into_json().unwrap();
+    let dep_id = dep["id"].as_str().unwrap();
 
-    // Check activity for batch update event with correct actor
+    // Verify dependency exists
     let resp = client
-        .get(format!("/api/v1/boards/{}/activity?limit=20", board_id))
+        .get(format!("/api/v1/boards/{}/dependencies", board_id))
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let activity: serde_json::Value = resp.into_json().unwrap();
-    let events = activity.as_array().unwrap();
-    let batch_update_event = events.iter().find(|e| {
-        e["event_type"] == "updated" && e["actor"] == "BatchBot"
-    });
-    assert!(batch_update_event.is_some(), "Should have a batch updated event with BatchBot actor");
+    let deps: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(deps.as_array().unwrap().len(), 1);
 
-    // Reorder without actor param → defaults to "anonymous"
+    // Delete the dependency
     let resp = client
-        .post(format!(
-            "/api/v1/boards/{}/tasks/{}/reorder",
-            board_id, task_id
-        ))
-        .header(ContentType::JSON)
+        .delete(format!("/api/v1/boards/{}/dependencies/{}", board_id, dep_id))
         .header(auth.clone())
-        .body(format!(r#"{{"position": 1, "column_id": "{}"}}"#, col_id))
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
 
-    // Batch without actor → defaults to "batch"
+    // Verify it's gone
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/batch", board_id))
+        .get(format!("/api/v1/boards/{}/dependencies", board_id))
+        .dispatch();
+    let deps: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(deps.as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn test_http_task_hierarchy() {
+    let client = test_client();
+    let (board_id, manage_key) = create_test_board(&client, "Epics");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let board: serde_json::Value = client.get(format!("/api/v1/boards/{}", board_id)).dispatch().into_json().unwrap();
+    let done_col = board["columns"][2]["id"].as_str().unwrap().to_string();
+    let create = |body: String| -> serde_json::Value {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(body)
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        resp.into_json().unwrap()
+    };
+    let attach = |parent: &str, child: &str| {
+        client
+            .post(format!("/api/v1/boards/{}/tasks/{}/children", board_id, parent))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"child_task_id": "{}"}}"#, child))
+            .dispatch()
+    };
+
+    let epic = create(r#"{"title": "Launch"}"#.to_string());
+    let epic_id = epic["id"].as_str().unwrap().to_string();
+    assert!(epic["parent_task_id"].is_null());
+    assert!(epic.get("progress").is_none());
+
+    // Children via parent_task_id on create, or attached afterwards
+    let docs = create(format!(r#"{{"title": "Docs", "parent_task_id": "{}"}}"#, epic_id));
+    assert_eq!(docs["parent_task_id"], epic_id.as_str());
+    let docs_id = docs["id"].as_str().unwrap().to_string();
+    let api = create(r#"{"title": "API"}"#.to_string());
+    let api_id = api["id"].as_str().unwrap().to_string();
+    let resp = attach(&epic_id, &api_id);
+    assert_eq!(resp.status(), Status::Ok);
+    let parent: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(parent["progress"], serde_json::json!({"done": 0, "total": 2, "percent": 0}));
+
+    // Completing a child rolls up to the parent
+    client
+        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}", board_id, docs_id, done_col))
+        .header(auth.clone())
+        .dispatch();
+    let children: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/tasks/{}/children", board_id, epic_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(children["progress"], serde_json::json!({"done": 1, "total": 2, "percent": 50}));
+    assert_eq!(children["children"].as_array().unwrap().len(), 2);
+    assert_eq!(children["children"][0]["title"], "API");
+
+    // No cycles: not itself, not an ancestor; unknown tasks are 404
+    let grandchild = create(format!(r#"{{"title": "Endpoint", "parent_task_id": "{}"}}"#, api_id));
+    let grandchild_id = grandchild["id"].as_str().unwrap();
+    assert_eq!(attach(&epic_id, &epic_id).status(), Status::Conflict);
+    let resp = attach(grandchild_id, &epic_id);
+    assert_eq!(resp.status(), Status::Conflict);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["code"], "CIRCULAR_HIERARCHY");
+    assert_eq!(attach(&epic_id, "no-such-task").status(), Status::NotFound);
+    let resp = client
+        .post(format!("/api/v1/boards/{}/tasks", board_id))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(format!(
-            r#"{{"operations": [{{"action": "update", "task_ids": ["{}"], "priority": 1}}]}}"#,
-            task2_id
-        ))
+        .body(r#"{"title": "Orphan", "parent_task_id": "no-such-task"}"#)
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(resp.status(), Status::NotFound);
 
-    // Verify activity has both defaults
+    // Detach, then deleting the parent detaches the rest
     let resp = client
-        .get(format!("/api/v1/boards/{}/activity?limit=30", board_id))
+        .delete(format!("/api/v1/boards/{}/tasks/{}/children/{}", board_id, epic_id, api_id))
+        .header(auth.clone())
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let activity: serde_json::Value = resp.into_json().unwrap();
-    let events = activity.as_array().unwrap();
-    let anon_reorder = events.iter().find(|e| e["event_type"] == "reordered" && e["actor"] == "anonymous");
-    assert!(anon_reorder.is_some(), "Reorder without actor should default to anonymous");
-    let batch_default = events.iter().find(|e| e["event_type"] == "updated" && e["actor"] == "batch");
-    assert!(batch_default.is_some(), "Batch without actor should default to batch");
-}
-
-// ============ API Discovery Endpoints ============
+    let parent: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(parent["progress"]["total"], 1);
+    let resp = client
+        .delete(format!("/api/v1/boards/{}/tasks/{}/children/{}", board_id, epic_id, api_id))
+        .header(auth.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+    let events: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/tasks/{}/events", board_id, epic_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let kinds: Vec<&str> = events.as_array().unwrap().iter().map(|e| e["event_type"].as_str().unwrap()).collect();
+    assert!(kinds.contains(&"child.added") && kinds.contains(&"child.removed"));
 
-#[test]
-fn test_http_openapi_json() {
-    let client = test_client();
-    let resp = client.get("/api/v1/openapi.json").dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let body: serde_json::Value = resp.into_json().unwrap();
-    // Verify it's a valid OpenAPI spec
-    assert_eq!(body["openapi"].as_str().unwrap_or(""), "3.0.3");
-    assert!(body["info"].is_object());
-    assert!(body["paths"].is_object());
+    client
+        .delete(format!("/api/v1/boards/{}/tasks/{}", board_id, epic_id))
+        .header(auth.clone())
+        .dispatch();
+    let docs: serde_json::Value = client
+        .get(format!("/api/v1/boards/{}/tasks/{}", board_id, docs_id))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert!(docs["parent_task_id"].is_null());
 }
 
 #[test]
-fn test_http_llms_txt() {
+fn test_http_schedule_dependencies() {
     let client = test_client();
-    let resp = client.get("/api/v1/llms.txt").dispatch();
+    let (board_id, manage_key) = create_test_board(&client, "Schedule Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+
+    let mut ids = Vec::new();
+    for (title, due) in [("Design", "null"), ("Build", r#""2020-01-01T00:00:00Z""#), ("Launch", "null")] {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "{}", "due_at": {}}}"#, title, due))
+            .dispatch();
+        let task: serde_json::Value = resp.into_json().unwrap();
+        ids.push(task["id"].as_str().unwrap().to_string());
+    }
+    for (blocker, blocked) in [(0, 1), (1, 2)] {
+        client
+            .post(format!("/api/v1/boards/{}/dependencies", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"blocker_task_id": "{}", "blocked_task_id": "{}"}}"#, ids[blocker], ids[blocked]))
+            .dispatch();
+    }
+
+    let body = format!(
+        r#"{{"task_id": "{}", "deadline": "2026-06-30T17:00:00Z", "estimates": {{"{}": 16}}, "default_estimate_hours": 4, "dry_run": true}}"#,
+        ids[2], ids[1]
+    );
+    let resp = client
+        .post(format!("/api/v1/boards/{}/dependencies/schedule", board_id))
+        .header(ContentType::JSON)
+        .body(body.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/dependencies/schedule", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(body.clone())
+        .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let body = resp.into_string().unwrap();
-    assert!(body.contains("Kanban"), "llms.txt should mention Kanban");
-    assert!(body.contains("/api/v1"), "llms.txt should reference API paths");
+    let plan: serde_json::Value = resp.into_json().unwrap();
+    let schedule = plan["schedule"].as_array().unwrap();
+    assert_eq!(schedule.len(), 3);
+    // Launch (4h) ← Build (16h) ← Design
+    assert_eq!(schedule[0]["task_id"], ids[0].as_str());
+    assert_eq!(schedule[0]["due_at"], "2026-06-29T21:00:00Z");
+    assert_eq!(schedule[1]["due_at"], "2026-06-30T13:00:00Z");
+    assert_eq!(schedule[1]["start_by"], "2026-06-29T21:00:00Z");
+    assert_eq!(schedule[2]["due_at"], "2026-06-30T17:00:00Z");
+    // Build already has a due date, so it's left alone without `overwrite`
+    assert_eq!(schedule[1]["applied"], false);
+    assert_eq!(plan["updated"], 0);
+
+    // Dry run wrote nothing
+    let resp = client.get(format!("/api/v1/boards/{}/tasks/{}", board_id, ids[0])).dispatch();
+    let task: serde_json::Value = resp.into_json().unwrap();
+    assert!(task["due_at"].is_null());
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/dependencies/schedule", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(body.replace(r#""dry_run": true"#, r#""overwrite": true"#))
+        .dispatch();
+    let plan: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(plan["updated"], 3);
+    let resp = client.get(format!("/api/v1/boards/{}/tasks/{}", board_id, ids[1])).dispatch();
+    let task: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(task["due_at"], "2026-06-30T13:00:00Z");
+
+    let resp = client
+        .post(format!("/api/v1/boards/{}/dependencies/schedule", board_id))
+        .header(ContentType::JSON)
+        .header(auth.clone())
+        .body(format!(r#"{{"task_id": "{}", "deadline": "soon"}}"#, ids[2]))
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
 }
 
-// ============ Single Task GET ============
+// ============ Actor Avatars ============
 
 #[test]
-fn test_http_get_single_task() {
+fn test_http_actor_avatars() {
     let client = test_client();
-    let (board_id, manage_key) = create_test_board(&client, "Single Task Board");
-    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
 
-    // Get columns to find first column ID
-    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
-    let board: serde_json::Value = resp.into_json().unwrap();
-    let col_id = board["columns"][0]["id"].as_str().unwrap();
+    let resp = client.get("/api/v1/actors/Jane%20Doe").dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let profile: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(profile["name"], "Jane Doe");
+    assert_eq!(profile["initials"], "JD");
+    assert_eq!(profile["has_uploaded_avatar"], false);
+    let color = profile["color"].as_str().unwrap().to_string();
+
+    // Same name, same color — regardless of case
+    let resp = client.get("/api/v1/actors/jane%20doe").dispatch();
+    let again: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(again["color"], color.as_str());
+
+    // Generated SVG fallback
+    let resp = client.get("/api/v1/actors/Jane%20Doe/avatar").dispatch();
+    assert_eq!(resp.content_type(), Some(ContentType::SVG));
+    assert_eq!(resp.headers().get_one("X-Content-Type-Options"), Some("nosniff"));
+    let svg = resp.into_string().unwrap();
+    assert!(svg.contains(&color) && svg.contains(">JD<"));
+
+    // Mismatched bytes are rejected
+    let resp = client
+        .put("/api/v1/actors/Jane%20Doe/avatar")
+        .header(ContentType::PNG)
+        .body("not a png")
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
 
-    // Create a task
+    // Claiming a name takes a board key (or a session); made-up keys don't count
+    let png = b"\x89PNG\r\n\x1a\nfake-image-data".to_vec();
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks", board_id))
-        .header(ContentType::JSON)
-        .header(auth.clone())
-        .body(format!(
-            r#"{{"title": "Test Task", "description": "A description", "column_id": "{}", "priority": 2, "labels": ["bug", "urgent"], "actor_name": "Tester"}}"#,
-            col_id
-        ))
+        .put("/api/v1/actors/Jane%20Doe/avatar")
+        .header(ContentType::PNG)
+        .body(png.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let resp = client
+        .put("/api/v1/actors/Jane%20Doe/avatar")
+        .header(ContentType::PNG)
+        .header(Header::new("Authorization", "Bearer kb_made_up"))
+        .body(png.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // First upload returns the avatar key
+    let (_, manage_key) = create_test_board(&client, "Avatar Board");
+    let resp = client
+        .put("/api/v1/actors/Jane%20Doe/avatar")
+        .header(ContentType::PNG)
+        .header(Header::new("Authorization", format!("Bearer {}", manage_key)))
+        .body(png.clone())
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let task: serde_json::Value = resp.into_json().unwrap();
-    let task_id = task["id"].as_str().unwrap();
+    let upload: serde_json::Value = resp.into_json().unwrap();
+    let avatar_key = upload["avatar_key"].as_str().unwrap().to_string();
 
-    // GET single task
+    let resp = client.get("/api/v1/actors/jane%20doe/avatar").dispatch();
+    assert_eq!(resp.content_type(), Some(ContentType::PNG));
+    assert_eq!(resp.headers().get_one("X-Content-Type-Options"), Some("nosniff"));
+    assert_eq!(resp.into_bytes().unwrap(), png);
+
+    // Replacing requires the key
     let resp = client
-        .get(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id))
+        .put("/api/v1/actors/Jane%20Doe/avatar")
+        .header(ContentType::GIF)
+        .body(b"GIF89a...")
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client
+        .put("/api/v1/actors/Jane%20Doe/avatar")
+        .header(ContentType::GIF)
+        .header(Header::new("Authorization", format!("Bearer {}", avatar_key)))
+        .body(b"GIF89a...")
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let fetched: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(fetched["title"], "Test Task");
-    assert_eq!(fetched["description"], "A description");
-    assert_eq!(fetched["priority"], 2);
-    assert_eq!(fetched["created_by"], "Tester");
-}
+    let replaced: serde_json::Value = resp.into_json().unwrap();
+    assert!(replaced.get("avatar_key").is_none());
 
-#[test]
-fn test_http_get_single_task_not_found() {
-    let client = test_client();
-    let (board_id, _) = create_test_board(&client, "Task Not Found Board");
+    // Oversized uploads are refused
+    let mut big = b"GIF89a".to_vec();
+    big.resize(70 * 1024, 0);
+    let resp = client
+        .put("/api/v1/actors/someone-else/avatar")
+        .header(ContentType::GIF)
+        .body(big)
+        .dispatch();
+    assert_eq!(resp.status(), Status::PayloadTooLarge);
 
+    // Delete falls back to the generated avatar
     let resp = client
-        .get(format!("/api/v1/boards/{}/tasks/nonexistent-id", board_id))
+        .delete("/api/v1/actors/Jane%20Doe/avatar")
+        .header(Header::new("Authorization", format!("Bearer {}", avatar_key)))
         .dispatch();
-    assert_eq!(resp.status(), Status::NotFound);
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.get("/api/v1/actors/Jane%20Doe/avatar").dispatch();
+    assert_eq!(resp.content_type(), Some(ContentType::SVG));
 }
 
-// ============ Task Events (Activity History) ============
+// ============ Admin ============
 
 #[test]
-fn test_http_task_events() {
+fn test_http_admin_scrub() {
     let client = test_client();
-    let (board_id, manage_key) = create_test_board(&client, "Task Events Board");
+    let (board_id, manage_key) = create_test_board(&client, "Scrub HTTP Test");
     let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let admin = Header::new("Authorization", format!("Bearer {}", TEST_ADMIN_KEY));
 
-    // Get columns
-    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
-    let board: serde_json::Value = resp.into_json().unwrap();
-    let col_id = board["columns"][0]["id"].as_str().unwrap();
-    let col2_id = board["columns"][1]["id"].as_str().unwrap();
-
-    // Create a task
     let resp = client
         .post(format!("/api/v1/boards/{}/tasks", board_id))
         .header(ContentType::JSON)
         .header(auth.clone())
-        .body(format!(
-            r#"{{"title": "Events Task", "column_id": "{}", "actor_name": "Creator"}}"#,
-            col_id
-        ))
+        .body(r#"{"title": "Follow up", "description": "Mail alice@example.com from 192.168.1.20", "actor_name": "alice", "assigned_to": "bob"}"#)
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
     let task: serde_json::Value = resp.into_json().unwrap();
-    let task_id = task["id"].as_str().unwrap();
+    let task_id = task["id"].as_str().unwrap().to_string();
 
-    // Move the task to generate an event
+    // Board keys are not admin keys
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/move/{}?actor=Mover", board_id, task_id, col2_id))
+        .post("/api/v1/admin/scrub")
+        .header(ContentType::JSON)
         .header(auth.clone())
+        .body("{}")
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(resp.status(), Status::Forbidden);
 
-    // Add a comment to generate another event
+    // Dry run (the default) reports without writing
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task_id))
+        .post("/api/v1/admin/scrub")
         .header(ContentType::JSON)
-        .header(auth.clone())
-        .body(r#"{"message": "A test comment", "actor_name": "Commenter"}"#)
+        .header(admin.clone())
+        .body(format!(r#"{{"actors": ["Alice"], "board_ids": ["{}"]}}"#, board_id))
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
+    let report: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(report["dry_run"], true);
+    assert_eq!(report["tasks_affected"], 1);
+    assert!(report["events_affected"].as_i64().unwrap() >= 1);
+    assert_eq!(report["emails"], 1);
+    assert_eq!(report["ip_addresses"], 1);
+
+    let resp = client.get(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id)).dispatch();
+    let task: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(task["created_by"], "alice");
 
-    // GET task events
+    // Apply: only alice is pseudonymized, bob is untouched
     let resp = client
-        .get(format!("/api/v1/boards/{}/tasks/{}/events", board_id, task_id))
+        .post("/api/v1/admin/scrub")
+        .header(ContentType::JSON)
+        .header(admin.clone())
+        .body(r#"{"actors": ["alice"], "dry_run": false}"#)
         .dispatch();
-    assert_eq!(resp.status(), Status::Ok);
-    let events: serde_json::Value = resp.into_json().unwrap();
-    let events_arr = events.as_array().unwrap();
+    let report: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(report["dry_run"], false);
 
-    // Should have at least 3 events: created, moved, comment
-    assert!(events_arr.len() >= 3, "Expected at least 3 events, got {}", events_arr.len());
+    let resp = client.get(format!("/api/v1/boards/{}/tasks/{}", board_id, task_id)).dispatch();
+    let task: serde_json::Value = resp.into_json().unwrap();
+    assert!(task["created_by"].as_str().unwrap().starts_with("anon-"));
+    assert_eq!(task["assigned_to"], "bob");
+    assert_eq!(task["description"], "Mail [email] from [ip]");
+    // Without a salt in the request a random one is used, never none
+    let unsalted = format!("anon-{}", &kanban::db::hash_key(":alice")[..10]);
+    assert_ne!(task["created_by"], unsalted.as_str());
+    let resp = client
+        .post("/api/v1/admin/scrub")
+        .header(ContentType::JSON)
+        .header(admin.clone())
+        .body(r#"{"salt": " "}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
 
-    // Verify event types
-    let event_types: Vec<&str> = events_arr.iter()
-        .map(|e| e["event_type"].as_str().unwrap_or(""))
-        .collect();
-    assert!(event_types.contains(&"created"), "Should have 'created' event");
-    assert!(event_types.contains(&"moved"), "Should have 'moved' event");
-    assert!(event_types.contains(&"comment"), "Should have 'comment' event");
+    // Nothing left to scrub for alice
+    let resp = client
+        .post("/api/v1/admin/scrub")
+        .header(ContentType::JSON)
+        .header(admin)
+        .body(r#"{"actors": ["alice"], "mode": "remove"}"#)
+        .dispatch();
+    let report: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(report["tasks_affected"], 0);
+    assert_eq!(report["actor_names"], 0);
 }
 
-// ============ Column Creation ============
-
 #[test]
-fn test_http_create_column() {
+fn test_http_admin_scrub_reaches_every_table() {
     let client = test_client();
-    let (board_id, manage_key) = create_test_board(&client, "Column Create Board");
+    let (board_id, manage_key) = create_test_board(&client, "Scrub Everything");
     let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let admin = Header::new("Authorization", format!("Bearer {}", TEST_ADMIN_KEY));
 
-    // Get initial column count
-    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
-    let board: serde_json::Value = resp.into_json().unwrap();
-    let initial_count = board["columns"].as_array().unwrap().len();
-
-    // Create a new column
+    let mut task_ids = Vec::new();
+    for title in ["First", "Second"] {
+        let resp = client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "{}", "description": "ping alice@example.com", "actor_name": "alice"}}"#, title))
+            .dispatch();
+        let task: serde_json::Value = resp.into_json().unwrap();
+        task_ids.push(task["id"].as_str().unwrap().to_string());
+    }
+    let (task, other) = (&task_ids[0], &task_ids[1]);
     let resp = client
-        .post(format!("/api/v1/boards/{}/columns", board_id))
+        .post(format!("/api/v1/boards/{}/tasks/{}/comment", board_id, task))
         .header(ContentType::JSON)
-        .header(auth.clone())
-        .body(r#"{"name": "Testing", "wip_limit": 5}"#)
+        .header(auth)
+        .body(r#"{"message": "@alice seen from 203.0.113.7", "actor_name": "alice"}"#)
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let col: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(col["name"], "Testing");
-    assert_eq!(col["wip_limit"], 5);
 
-    // Verify column count increased
-    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
-    let board: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(board["columns"].as_array().unwrap().len(), initial_count + 1);
+    // Everything else the API would record about alice, written directly
+    let pool = client.rocket().state::<kanban::db::DbPool>().unwrap();
+    let conn = pool.get().unwrap();
+    let column: String = conn
+        .query_row("SELECT column_id FROM tasks WHERE id = ?1", [task], |row| row.get(0))
+        .unwrap();
+    conn.execute_batch(&format!(
+        r#"
+        UPDATE boards SET created_by = 'alice', quick_reassign_to = 'alice', description = 'owner alice@example.com' WHERE id = '{b}';
+        UPDATE columns SET auto_assign = '["alice", "bob"]' WHERE board_id = '{b}';
+        INSERT INTO task_dependencies (id, board_id, blocker_task_id, blocked_task_id, created_by, note)
+            VALUES ('dep', '{b}', '{t}', '{o}', 'alice', 'ask alice@example.com');
+        INSERT INTO task_trash (task_id, board_id, title, column_id, snapshot, deleted_by)
+            VALUES ('gone', '{b}', 'from 203.0.113.7', '{c}', '{{"created_by": "alice"}}', 'alice');
+        INSERT INTO task_locks (task_id, locked_by, expires_at) VALUES ('{o}', 'alice', datetime('now', '+1 hour'));
+        INSERT INTO task_watchers (task_id, board_id, name) VALUES ('{t}', '{b}', 'alice');
+        INSERT INTO comment_revisions (id, comment_id, task_id, board_id, data, change, changed_by)
+            VALUES ('rev', 'c', '{t}', '{b}', '{{"message": "mail alice@example.com", "actor": "alice"}}', 'edited', 'alice');
+        INSERT INTO comment_transcripts (id, task_id, board_id, comment_count, first_comment_at, last_comment_at, transcript, created_by)
+            VALUES ('tr', '{t}', '{b}', 1, '', '', '[2026-01-01 00:00:00] alice:
+hi from 203.0.113.7
+', 'alice');
+        INSERT INTO attachments (id, task_id, board_id, filename, content_type, size_bytes, sha256, storage_key, uploaded_by)
+            VALUES ('att', '{t}', '{b}', 'log for alice@example.com', 'text/plain', 1, '', 'k', 'alice');
+        INSERT INTO board_events (board_id, event_type, actor, data) VALUES ('{b}', 'board.updated', 'alice', '{{"actor": "alice"}}');
+        INSERT INTO board_members (id, board_id, name, role, key_hash, created_by) VALUES ('mem', '{b}', 'alice', 'editor', 'h1', 'alice');
+        INSERT INTO share_keys (id, board_id, name, key_hash, created_by) VALUES ('sk', '{b}', 'for alice@example.com', 'h2', 'alice');
+        INSERT INTO scoped_tokens (id, board_id, name, key_hash, created_by) VALUES ('st', '{b}', '@alice bot', 'h3', 'alice');
+        INSERT INTO agent_keys (id, board_id, agent_id, key_hash, created_by) VALUES ('ak', '{b}', 'alice', 'h4', 'alice');
+        INSERT OR REPLACE INTO inbox_cursors (board_id, name, seq) VALUES ('{b}', 'alice', 1);
+        INSERT OR REPLACE INTO notification_preferences (board_id, name) VALUES ('{b}', 'alice');
+        INSERT OR REPLACE INTO email_subscriptions (board_id, name, email) VALUES ('{b}', 'alice', 'alice@example.com');
+        INSERT INTO email_outbox (id, board_id, name, email, kind, subject, body)
+            VALUES ('mail', '{b}', 'alice', 'alice@example.com', 'mention', 'Mentioned', 'You were mentioned');
+        INSERT INTO webhooks (id, board_id, url, secret, created_by) VALUES ('hook', '{b}', 'https://hooks.test/x', 's', 'alice');
+        INSERT INTO webhook_outbox (id, webhook_id, board_id, event, payload, next_attempt_at)
+            VALUES ('out', 'hook', '{b}', 'task.created', '{{"actor": "alice"}}', 0);
+        INSERT INTO webhook_deliveries (id, webhook_id, board_id, event, payload, response_body)
+            VALUES ('del', 'hook', '{b}', 'task.created', '{{"actor": "alice"}}', 'seen from 203.0.113.7');
+        INSERT INTO webhook_dead_letters (id, webhook_id, board_id, event, payload, last_error)
+            VALUES ('dead', 'hook', '{b}', 'task.created', '{{"data": {{"assigned_to": "alice"}}}}', 'refused by 203.0.113.7');
+        INSERT INTO actor_avatars (name_key, name, content_type, data, key_hash) VALUES ('alice', 'alice', 'image/png', x'00', 'h');
+        INSERT INTO import_validations (payload_hash, source, document, expires_at)
+            VALUES ('imp', 'trello', '{{"tasks": [{{"created_by": "alice"}}]}}', datetime('now', '+1 hour'));
+        INSERT OR REPLACE INTO rate_limit_windows (scope, key, window_start_ms, count) VALUES ('task_writes', '203.0.113.7', 0, 1);
+        INSERT INTO users (id, issuer, subject, email, name) VALUES ('user', 'https://idp.test', 'sub', 'alice@example.com', 'alice');
+        INSERT INTO user_sessions (token_hash, user_id, expires_at) VALUES ('session', 'user', datetime('now', '+1 day'));
+        "#,
+        b = board_id,
+        t = task,
+        o = other,
+        c = column,
+    ))
+    .unwrap();
+
+    let resp = client
+        .post("/api/v1/admin/scrub")
+        .header(ContentType::JSON)
+        .header(admin)
+        .body(r#"{"actors": ["alice"], "dry_run": false}"#)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let report: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(report["tables"]["email_subscriptions"], 1);
+    assert_eq!(report["tables"]["rate_limit_windows"], 1);
+    assert_eq!(report["tables"]["users"], 1);
+    assert_eq!(report["tables"]["user_sessions"], 1);
+    let sessions: i64 = conn.query_row("SELECT COUNT(*) FROM user_sessions", [], |row| row.get(0)).unwrap();
+    assert_eq!(sessions, 0);
+
+    // No table still holds the name, the address or the IP (FTS shadow
+    // tables are the index's internals; tasks_fts itself is checked)
+    let tables: Vec<String> = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name NOT LIKE 'tasks_fts_%'")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    for table in tables {
+        let mut stmt = conn.prepare(&format!("SELECT * FROM {}", table)).unwrap();
+        let width = stmt.column_count();
+        let mut rows = stmt.query([]).unwrap();
+        while let Some(row) = rows.next().unwrap() {
+            for i in 0..width {
+                if let rusqlite::types::ValueRef::Text(text) = row.get_ref(i).unwrap() {
+                    let text = String::from_utf8_lossy(text).to_lowercase();
+                    for needle in ["alice", "example.com", "203.0.113.7"] {
+                        assert!(!text.contains(needle), "{} still holds {}: {}", table, needle, text);
+                    }
+                }
+            }
+        }
+    }
 }
 
 #[test]
-fn test_http_create_column_no_auth() {
+fn test_http_workspace_apply_settings() {
     let client = test_client();
-    let (board_id, _) = create_test_board(&client, "Column No Auth Board");
+    let admin = Header::new("Authorization", format!("Bearer {}", TEST_ADMIN_KEY));
+    let (board_a, key_a) = create_test_board(&client, "Fleet A");
+    let (board_b, key_b) = create_test_board(&client, "Fleet B");
+    let (outside, _) = create_test_board(&client, "Elsewhere");
+    let apply = |workspace: &str, body: &str| {
+        client
+            .post(format!("/api/v1/workspaces/{}/apply-settings", workspace))
+            .header(ContentType::JSON)
+            .header(admin.clone())
+            .body(body)
+            .dispatch()
+    };
+    let updates = |board_id: &str| -> Vec<serde_json::Value> {
+        let events: Vec<serde_json::Value> = client
+            .get(format!("/api/v1/boards/{}/board-events", board_id))
+            .dispatch()
+            .into_json()
+            .unwrap();
+        events.into_iter().filter(|e| e["event"] == "board.updated").collect()
+    };
+    let body = r#"{"dry_run": true, "settings": {"require_display_name": true, "wip_limits": {"in progress": 2, "Review": 1}}}"#;
+
+    // A workspace is a directory tag; its members are the boards carrying it
+    assert_eq!(apply("fleet", body).status(), Status::NotFound);
+    let resp = client.post("/api/v1/admin/tags").header(ContentType::JSON).header(admin.clone()).body(r#"{"name": "fleet"}"#).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    for (board_id, key) in [(&board_a, &key_a), (&board_b, &key_b)] {
+        let resp = client
+            .patch(format!("/api/v1/boards/{}", board_id))
+            .header(ContentType::JSON)
+            .header(Header::new("Authorization", format!("Bearer {}", key)))
+            .body(r#"{"tags": ["fleet"]}"#)
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+    }
+    let before = updates(&board_a).len();
 
+    // Admin key required
     let resp = client
-        .post(format!("/api/v1/boards/{}/columns", board_id))
+        .post("/api/v1/workspaces/fleet/apply-settings")
         .header(ContentType::JSON)
-        .body(r#"{"name": "Unauthorized Column"}"#)
+        .body(body)
         .dispatch();
     assert_eq!(resp.status(), Status::Unauthorized);
+
+    // Dry run reports without writing
+    let resp = apply("Fleet", body);
+    assert_eq!(resp.status(), Status::Ok);
+    let report: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(report["dry_run"], true);
+    assert_eq!(report["boards_updated"], 2);
+    let results = report["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["board_id"], board_a.as_str());
+    assert_eq!(results[0]["changes"].as_array().unwrap().len(), 2);
+    assert_eq!(results[0]["skipped"][0], "wip_limits.Review: no such column");
+    let board: serde_json::Value = client.get(format!("/api/v1/boards/{}", board_a)).dispatch().into_json().unwrap();
+    assert_eq!(board["require_display_name"], false);
+    assert_eq!(updates(&board_a).len(), before);
+
+    // Real run applies to every member with one board.updated each; a second run is a no-op
+    let body = body.replace(r#""dry_run": true"#, r#""dry_run": false"#);
+    for expected in ["updated", "unchanged"] {
+        let report: serde_json::Value = apply("fleet", &body).into_json().unwrap();
+        assert_eq!(report["results"][1]["status"], expected);
+    }
+    for board_id in [&board_a, &board_b] {
+        let board: serde_json::Value = client.get(format!("/api/v1/boards/{}", board_id)).dispatch().into_json().unwrap();
+        assert_eq!(board["require_display_name"], true);
+        assert_eq!(board["columns"][1]["wip_limit"], 2);
+    }
+    let events = updates(&board_a);
+    assert_eq!(events.len(), before + 1);
+    assert_eq!(events[0]["data"]["fields"], serde_json::json!(["require_display_name", "wip_limit"]));
+    let board: serde_json::Value = client.get(format!("/api/v1/boards/{}", outside)).dispatch().into_json().unwrap();
+    assert_eq!(board["require_display_name"], false);
+
+    // Retention policies; 0 keeps forever, negative is refused
+    let resp = apply("fleet", r#"{"settings": {"event_retention_days": 30, "archived_task_retention_days": 0}}"#);
+    assert_eq!(resp.status(), Status::Ok);
+    let report: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(report["results"][0]["changes"], serde_json::json!(["event_retention_days: forever → 30"]));
+    let board: serde_json::Value = client.get(format!("/api/v1/boards/{}", board_a)).dispatch().into_json().unwrap();
+    assert_eq!(board["event_retention_days"], 30);
+    assert!(board["archived_task_retention_days"].is_null());
+    assert_eq!(apply("fleet", r#"{"settings": {"event_retention_days": -1}}"#).status(), Status::BadRequest);
+
+    // Unsupported settings — rate limits are instance-wide — and board lists are rejected rather than silently ignored
+    for body in [
+        r#"{"settings": {"retention_days": 30}}"#,
+        r#"{"settings": {"write_rate_limit": 10}}"#,
+        r#"{"board_ids": ["x"], "settings": {"require_display_name": true}}"#,
+    ] {
+        assert_eq!(apply("fleet", body).status(), Status::UnprocessableEntity);
+    }
+    assert_eq!(apply("fleet", r#"{"settings": {}}"#).status(), Status::BadRequest);
 }
 
-// ============ Dependency Deletion ============
+#[test]
+fn test_http_admin_db_stats() {
+    let client = test_client();
+    create_test_board(&client, "Stats Board");
+
+    let resp = client.get("/api/v1/admin/db-stats").dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    let resp = client
+        .get("/api/v1/admin/db-stats")
+        .header(Header::new("Authorization", format!("Bearer {}", TEST_ADMIN_KEY)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let stats: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(stats["storage"]["journal_mode"], "wal");
+    assert!(stats["storage"]["page_count"].as_i64().unwrap() > 0);
+    assert!(stats["storage"]["database_bytes"].as_u64().unwrap() > 0);
+    assert!(stats["pool"]["checkouts"].as_u64().unwrap() > 0);
+    assert!(stats["pool"]["max_size"].as_u64().unwrap() > 0);
+    assert!(stats["cache"]["hits"].as_u64().unwrap() > 0);
+    assert!(stats["locks"]["busy_timeouts"].is_u64());
+    assert!(stats["busy_timeout_ms"].as_u64().unwrap() > 0);
+}
 
 #[test]
-fn test_http_delete_dependency() {
+fn test_http_admin_client_stats() {
     let client = test_client();
-    let (board_id, manage_key) = create_test_board(&client, "Dep Delete Board");
-    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    let admin = Header::new("Authorization", format!("Bearer {}", TEST_ADMIN_KEY));
 
-    // Get first column
-    let resp = client.get(format!("/api/v1/boards/{}", board_id)).dispatch();
-    let board: serde_json::Value = resp.into_json().unwrap();
-    let col_id = board["columns"][0]["id"].as_str().unwrap();
+    for _ in 0..2 {
+        client.get("/api/v1/llms.txt").header(Header::new("User-Agent", "python-requests/2.31.0")).dispatch();
+    }
+    client.get("/api/v1/openapi.json").header(Header::new("User-Agent", "langchain/0.2")).dispatch();
+    client
+        .get("/api/v1/health")
+        .header(Header::new("User-Agent", "curl/8.5.0"))
+        .header(Header::new("X-Client", "fleet-runner/1.4"))
+        .dispatch();
 
-    // Create two tasks
+    let resp = client.get("/api/v1/admin/client-stats").dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let stats: serde_json::Value =
+        client.get("/api/v1/admin/client-stats").header(admin).dispatch().into_json().unwrap();
+    assert_eq!(stats["llms_txt_fetches"], 2);
+    assert_eq!(stats["openapi_fetches"], 1);
+
+    let clients = stats["clients"].as_array().unwrap();
+    let find = |name: &str| clients.iter().find(|c| c["client"] == name).unwrap_or_else(|| panic!("{} missing", name));
+    let requests = find("python-requests");
+    assert_eq!(requests["requests"], 2);
+    assert_eq!(requests["llms_txt_fetches"], 2);
+    assert_eq!(requests["user_agents"][0]["user_agent"], "python-requests/2.31.0");
+    assert_eq!(find("langchain")["openapi_fetches"], 1);
+    assert_eq!(find("fleet-runner")["user_agents"][0]["user_agent"], "curl/8.5.0");
+}
+
+#[test]
+fn test_http_admin_event_stats() {
+    let client = test_client();
+    let (board_id, key) = create_test_board(&client, "Busy Board");
     let resp = client
         .post(format!("/api/v1/boards/{}/tasks", board_id))
         .header(ContentType::JSON)
-        .header(auth.clone())
-        .body(format!(r#"{{"title": "Blocker", "column_id": "{}", "actor_name": "Tester"}}"#, col_id))
+        .header(Header::new("Authorization", format!("Bearer {}", key)))
+        .body(r#"{"title": "One"}"#)
         .dispatch();
-    let task1: serde_json::Value = resp.into_json().unwrap();
-    let task1_id = task1["id"].as_str().unwrap();
+    assert_eq!(resp.status(), Status::Ok);
 
+    let resp = client.get("/api/v1/admin/event-stats").dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let stats: serde_json::Value = client
+        .get("/api/v1/admin/event-stats")
+        .header(Header::new("Authorization", format!("Bearer {}", TEST_ADMIN_KEY)))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(stats["capacity"], 256);
+    let board = stats["boards"].as_array().unwrap().iter().find(|b| b["board_id"] == board_id.as_str()).unwrap();
+    assert!(board["events_emitted"].as_u64().unwrap() >= 1);
+    assert_eq!(board["buffered"], board["events_emitted"]);
+    assert_eq!(board["events_lost"], 0);
+    assert_eq!(board["subscriber_count"], 0);
+}
+
+#[test]
+fn test_http_admin_boards() {
+    let client = test_client();
+    let admin = Header::new("Authorization", format!("Bearer {}", TEST_ADMIN_KEY));
+    let (public_id, _) = create_test_board(&client, "Open Board");
+    let (private_id, private_key) = create_test_board(&client, "Spam Board");
+    let owner = Header::new("Authorization", format!("Bearer {}", private_key));
     let resp = client
-        .post(format!("/api/v1/boards/{}/tasks", board_id))
+        .patch(format!("/api/v1/boards/{}", private_id))
         .header(ContentType::JSON)
-        .header(auth.clone())
-        .body(format!(r#"{{"title": "Blocked", "column_id": "{}", "actor_name": "Tester"}}"#, col_id))
+        .header(owner.clone())
+        .body(r#"{"visibility": "private"}"#)
         .dispatch();
-    let task2: serde_json::Value = resp.into_json().unwrap();
-    let task2_id = task2["id"].as_str().unwrap();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.post(format!("/api/v1/boards/{}/archive", private_id)).header(owner.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
 
-    // Create a dependency
+    // A board key isn't an admin key
+    let resp = client.get("/api/v1/admin/boards").header(owner.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    let boards: Vec<serde_json::Value> = client.get("/api/v1/admin/boards").header(admin.clone()).dispatch().into_json().unwrap();
+    assert!(boards.iter().any(|b| b["id"] == public_id.as_str()));
+    let private = boards.iter().find(|b| b["id"] == private_id.as_str()).unwrap();
+    assert_eq!(private["visibility"], "private");
+    assert_eq!(private["archived"], true);
+    let boards: Vec<serde_json::Value> = client
+        .get("/api/v1/admin/boards?visibility=private")
+        .header(admin.clone())
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert!(boards.iter().all(|b| b["visibility"] == "private"));
+    assert_eq!(
+        client.get("/api/v1/admin/boards?visibility=secret").header(admin.clone()).dispatch().status(),
+        Status::BadRequest
+    );
+
+    let stats: serde_json::Value = client.get("/api/v1/admin/stats").header(admin.clone()).dispatch().into_json().unwrap();
+    assert!(stats["boards"]["total"].as_i64().unwrap() >= 2);
+    assert!(stats["boards"]["private"].as_i64().unwrap() >= 1);
+    assert!(stats["boards"]["archived"].as_i64().unwrap() >= 1);
+    assert!(stats["boards_created_24h"].as_i64().unwrap() >= 2);
+
+    let limits: serde_json::Value = client.get("/api/v1/admin/rate-limits").header(admin.clone()).dispatch().into_json().unwrap();
+    assert_eq!(limits["board_creation"]["window_seconds"], 3600);
+    assert!(limits["board_creation"]["clients"][0]["count"].as_u64().unwrap() >= 2);
+    assert_eq!(limits["task_writes"]["window_seconds"], 60);
+    assert!(limits["batch"]["clients"].as_array().unwrap().is_empty());
+
+    // Force-unarchive, then delete outright
+    let resp = client.post(format!("/api/v1/admin/boards/{}/unarchive", private_id)).header(admin.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let board: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(board["archived"], false);
+    let resp = client.post(format!("/api/v1/admin/boards/{}/unarchive", private_id)).header(admin.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+    let resp = client.delete(format!("/api/v1/admin/boards/{}", private_id)).header(admin.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let deleted: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(deleted["name"], "Spam Board");
+    let resp = client.get(format!("/api/v1/boards/{}", private_id)).header(owner).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+    let resp = client.delete(format!("/api/v1/admin/boards/{}", private_id)).header(admin).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[test]
+fn test_http_request_timeout_and_slow_queries() {
+    // Log every statement; searches get a deadline that has passed by the time they run
+    let guard = kanban::query_guard::QueryGuard::new(
+        Some(Duration::from_secs(30)),
+        vec![("/tasks/search".to_string(), Duration::from_nanos(1))],
+        Some(Duration::ZERO),
+    );
+    let client = test_client_with_query_guard(guard);
+    let (board_id, manage_key) = create_test_board(&client, "Slow Board");
+    let auth = Header::new("Authorization", format!("Bearer {}", manage_key));
+    for i in 0..30 {
+        client
+            .post(format!("/api/v1/boards/{}/tasks", board_id))
+            .header(ContentType::JSON)
+            .header(auth.clone())
+            .body(format!(r#"{{"title": "Task {}", "description": "searchable text"}}"#, i))
+            .dispatch();
+    }
+
+    let resp = client.get(format!("/api/v1/boards/{}/tasks/search?q=searchable", board_id)).dispatch();
+    assert_eq!(resp.status(), Status::ServiceUnavailable);
+    let body: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(body["code"], "REQUEST_TIMEOUT");
+    // Other routes keep the default timeout
+    let resp = client.get(format!("/api/v1/boards/{}/tasks", board_id)).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client.get("/api/v1/admin/slow-queries").header(auth).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let body: serde_json::Value = client
+        .get("/api/v1/admin/slow-queries")
+        .header(Header::new("Authorization", format!("Bearer {}", TEST_ADMIN_KEY)))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(body["threshold_ms"], 0);
+    assert_eq!(body["request_timeout_ms"], 30000);
+    let queries = body["queries"].as_array().unwrap();
+    // Newest first: the task listing, logged under its route pattern
+    assert_eq!(queries[0]["method"], "GET");
+    assert!(queries[0]["route"].as_str().unwrap().starts_with("/api/v1/boards/<board_id>/tasks"));
+    assert!(queries[0]["sql"].as_str().unwrap().contains("SELECT"));
+    assert!(queries.iter().any(|q| q["method"] == "POST" && q["sql"].as_str().unwrap().contains("INSERT INTO tasks")));
+}
+
+#[test]
+fn test_http_admin_backup_and_restore() {
+    let client = test_client();
+    let admin = Header::new("Authorization", format!("Bearer {}", TEST_ADMIN_KEY));
+    let (kept_id, _) = create_test_board(&client, "Before Backup");
+
+    let resp = client.post("/api/v1/admin/backup").dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    let resp = client.post("/api/v1/admin/backup").header(admin.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let backup: serde_json::Value = resp.into_json().unwrap();
+    let file = backup["file"].as_str().unwrap().to_string();
+    assert!(file.starts_with("kanban-") && file.ends_with(".db"));
+    assert!(backup["bytes"].as_u64().unwrap() > 0);
+
+    // Streamed download is a standalone SQLite file
+    let resp = client.post("/api/v1/admin/backup?download=true").header(admin.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(resp.content_type().unwrap().to_string(), "application/vnd.sqlite3");
+    let snapshot = resp.into_bytes().unwrap();
+    assert!(snapshot.starts_with(b"SQLite format 3\0"));
+
+    let (lost_id, _) = create_test_board(&client, "After Backup");
+
+    // Restore from the file in BACKUP_DIR: the later board is gone
     let resp = client
-        .post(format!("/api/v1/boards/{}/dependencies", board_id))
-        .header(ContentType::JSON)
-        .header(auth.clone())
-        .body(format!(
-            r#"{{"blocker_task_id": "{}", "blocked_task_id": "{}"}}"#,
-            task1_id, task2_id
-        ))
+        .post(format!("/api/v1/admin/restore?file={}", file))
+        .header(admin.clone())
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let dep: serde_json::Value = resp.into_json().unwrap();
-    let dep_id = dep["id"].as_str().unwrap();
+    let report: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(report["restored_from"], file.as_str());
+    assert_eq!(report["boards"], 1);
+    assert!(report["pre_restore_backup"].as_str().unwrap().starts_with("kanban-pre-restore-"));
+    assert_eq!(client.get(format!("/api/v1/boards/{}", kept_id)).dispatch().status(), Status::Ok);
+    assert_eq!(client.get(format!("/api/v1/boards/{}", lost_id)).dispatch().status(), Status::NotFound);
 
-    // Verify dependency exists
+    // The pre-restore snapshot brings it back
     let resp = client
-        .get(format!("/api/v1/boards/{}/dependencies", board_id))
+        .post(format!("/api/v1/admin/restore?file={}", report["pre_restore_backup"].as_str().unwrap()))
+        .header(admin.clone())
         .dispatch();
-    let deps: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(deps.as_array().unwrap().len(), 1);
+    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(client.get(format!("/api/v1/boards/{}", lost_id)).dispatch().status(), Status::Ok);
 
-    // Delete the dependency
+    // Uploading the downloaded snapshot works too
     let resp = client
-        .delete(format!("/api/v1/boards/{}/dependencies/{}", board_id, dep_id))
-        .header(auth.clone())
+        .post("/api/v1/admin/restore")
+        .header(admin.clone())
+        .header(ContentType::Binary)
+        .body(snapshot)
         .dispatch();
     assert_eq!(resp.status(), Status::Ok);
+    let report: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(report["restored_from"], "upload");
+    assert_eq!(client.get(format!("/api/v1/boards/{}", lost_id)).dispatch().status(), Status::NotFound);
 
-    // Verify it's gone
+    // Junk and path tricks are refused before the live database is touched
     let resp = client
-        .get(format!("/api/v1/boards/{}/dependencies", board_id))
+        .post("/api/v1/admin/restore")
+        .header(admin.clone())
+        .body("definitely not sqlite")
         .dispatch();
-    let deps: serde_json::Value = resp.into_json().unwrap();
-    assert_eq!(deps.as_array().unwrap().len(), 0);
+    assert_eq!(resp.status(), Status::BadRequest);
+    let err: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(err["code"], "INVALID_BACKUP");
+
+    let resp = client.post("/api/v1/admin/restore?file=../kanban.db").header(admin.clone()).dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    let resp = client.post("/api/v1/admin/restore?file=missing.db").header(admin).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+    assert_eq!(client.get(format!("/api/v1/boards/{}", kept_id)).dispatch().status(), Status::Ok);
 }
@@ -4,7 +4,7 @@
 fn test_db_initialization() {
     let db_path = format!("/tmp/kanban_test_{}.db", uuid::Uuid::new_v4());
     let pool = kanban::db::init_db_with_path(&db_path).expect("DB should initialize");
-    let conn = pool.lock().unwrap();
+    let conn = pool.get().unwrap();
 
     // Verify tables exist
     let tables: Vec<String> = conn
@@ -58,7 +58,7 @@ fn test_key_hashing() {
 fn test_db_wal_mode() {
     let db_path = format!("/tmp/kanban_test_wal_{}.db", uuid::Uuid::new_v4());
     let pool = kanban::db::init_db_with_path(&db_path).expect("DB should initialize");
-    let conn = pool.lock().unwrap();
+    let conn = pool.get().unwrap();
 
     let journal_mode: String = conn
         .query_row("PRAGMA journal_mode", [], |row| row.get(0))
@@ -76,7 +76,7 @@ fn test_db_wal_mode() {
 fn test_board_creation_and_manage_key() {
     let db_path = format!("/tmp/kanban_test_board_{}.db", uuid::Uuid::new_v4());
     let pool = kanban::db::init_db_with_path(&db_path).expect("DB should initialize");
-    let conn = pool.lock().unwrap();
+    let conn = pool.get().unwrap();
 
     // Create a board with a manage key
     let board_id = uuid::Uuid::new_v4().to_string();
@@ -124,7 +124,7 @@ fn test_access_control_manage_key() {
 
     let db_path = format!("/tmp/kanban_test_access_{}.db", uuid::Uuid::new_v4());
     let pool = kanban::db::init_db_with_path(&db_path).expect("DB should initialize");
-    let conn = pool.lock().unwrap();
+    let conn = pool.get().unwrap();
 
     // Create a board
     let board_id = uuid::Uuid::new_v4().to_string();
@@ -168,7 +168,7 @@ fn test_board_archiving() {
 
     let db_path = format!("/tmp/kanban_test_archive_{}.db", uuid::Uuid::new_v4());
     let pool = kanban::db::init_db_with_path(&db_path).expect("DB should initialize");
-    let conn = pool.lock().unwrap();
+    let conn = pool.get().unwrap();
 
     let board_id = uuid::Uuid::new_v4().to_string();
     let manage_key_hash = kanban::db::hash_key("test_key");
@@ -238,7 +238,7 @@ fn test_board_archiving() {
 fn test_wip_limit_enforcement() {
     let db_path = format!("/tmp/kanban_test_wip_{}.db", uuid::Uuid::new_v4());
     let pool = kanban::db::init_db_with_path(&db_path).expect("DB should initialize");
-    let conn = pool.lock().unwrap();
+    let conn = pool.get().unwrap();
 
     let board_id = uuid::Uuid::new_v4().to_string();
     let manage_key_hash = kanban::db::hash_key("test_key");
@@ -313,7 +313,7 @@ fn test_wip_limit_enforcement() {
 fn test_task_search() {
     let db_path = format!("/tmp/kanban_test_search_{}.db", uuid::Uuid::new_v4());
     let pool = kanban::db::init_db_with_path(&db_path).expect("DB should initialize");
-    let conn = pool.lock().unwrap();
+    let conn = pool.get().unwrap();
 
     let board_id = uuid::Uuid::new_v4().to_string();
     let manage_key_hash = kanban::db::hash_key("test_key");
@@ -384,7 +384,7 @@ fn test_task_search() {
 fn test_task_ordering_positions() {
     let db_path = format!("/tmp/kanban_test_order_{}.db", uuid::Uuid::new_v4());
     let pool = kanban::db::init_db_with_path(&db_path).expect("DB should initialize");
-    let conn = pool.lock().unwrap();
+    let conn = pool.get().unwrap();
 
     let board_id = uuid::Uuid::new_v4().to_string();
     let manage_key_hash = kanban::db::hash_key("test_key");
@@ -457,7 +457,7 @@ fn test_task_ordering_positions() {
 fn test_batch_operations() {
     let db_path = format!("/tmp/kanban_test_batch_{}.db", uuid::Uuid::new_v4());
     let pool = kanban::db::init_db_with_path(&db_path).expect("DB should initialize");
-    let conn = pool.lock().unwrap();
+    let conn = pool.get().unwrap();
 
     let board_id = uuid::Uuid::new_v4().to_string();
     let manage_key_hash = kanban::db::hash_key("test_key");
@@ -555,7 +555,7 @@ fn test_batch_operations() {
 fn test_webhooks_crud() {
     let db_path = format!("/tmp/kanban_test_webhooks_{}.db", uuid::Uuid::new_v4());
     let pool = kanban::db::init_db_with_path(&db_path).expect("DB should initialize");
-    let conn = pool.lock().unwrap();
+    let conn = pool.get().unwrap();
 
     let board_id = uuid::Uuid::new_v4().to_string();
     let manage_key_hash = kanban::db::hash_key("test_key");
@@ -636,7 +636,7 @@ fn test_webhooks_crud() {
 fn test_task_dependencies() {
     let db_path = format!("/tmp/kanban_test_deps_{}.db", uuid::Uuid::new_v4());
     let pool = kanban::db::init_db_with_path(&db_path).expect("DB should initialize");
-    let conn = pool.lock().unwrap();
+    let conn = pool.get().unwrap();
 
     let board_id = uuid::Uuid::new_v4().to_string();
     let manage_key_hash = kanban::db::hash_key("test_key");
@@ -719,7 +719,7 @@ fn test_task_dependencies() {
 fn test_board_public_listing() {
     let db_path = format!("/tmp/kanban_test_public_{}.db", uuid::Uuid::new_v4());
     let pool = kanban::db::init_db_with_path(&db_path).expect("DB should initialize");
-    let conn = pool.lock().unwrap();
+    let conn = pool.get().unwrap();
 
     let manage_key_hash = kanban::db::hash_key("test_key");
 
@@ -762,23 +762,367 @@ fn test_board_public_listing() {
 
 #[test]
 fn test_rate_limiter() {
-    use kanban::rate_limit::RateLimiter;
+    use kanban::rate_limit::{MemoryStore, RateLimiter};
     use std::time::Duration;
 
-    let rl = RateLimiter::new(Duration::from_secs(60), 3);
+    let rl = RateLimiter::with_store(Duration::from_secs(60), 3, Box::new(MemoryStore::default()));
     let key_id = "test-rate-limit-key";
     let limit = 3u64;
 
     for i in 0..3 {
-        let result = rl.check(key_id, limit);
+        let result = rl.check_cost(key_id, 1, limit);
         assert!(result.allowed, "Request {} should be allowed", i + 1);
         assert_eq!(result.remaining, 2 - i);
     }
 
-    let result = rl.check(key_id, limit);
+    let result = rl.check_cost(key_id, 1, limit);
     assert!(!result.allowed, "4th request should be blocked");
     assert_eq!(result.remaining, 0);
 
-    let result = rl.check("other-key", limit);
+    let result = rl.check_cost("other-key", 1, limit);
     assert!(result.allowed, "Different key unaffected");
 }
+
+#[test]
+fn test_pool_concurrent_reads() {
+    let db_path = format!("/tmp/kanban_test_{}.db", uuid::Uuid::new_v4());
+    let pool = kanban::db::init_db_with_path(&db_path).expect("DB should initialize");
+
+    let writer = pool.get().unwrap();
+    writer
+        .execute(
+            "INSERT INTO boards (id, name, manage_key_hash) VALUES ('b1', 'Pooled', 'hash')",
+            [],
+        )
+        .unwrap();
+
+    // An open write transaction must not block readers on other connections (WAL)
+    writer.execute_batch("BEGIN IMMEDIATE; UPDATE boards SET name = 'Pending' WHERE id = 'b1';").unwrap();
+
+    let readers: Vec<_> = (0..4)
+        .map(|_| {
+            let pool = pool.clone();
+            std::thread::spawn(move || {
+                let conn = pool.get().unwrap();
+                conn.query_row("SELECT name FROM boards WHERE id = 'b1'", [], |row| {
+                    row.get::<_, String>(0)
+                })
+                .unwrap()
+            })
+        })
+        .collect();
+    for reader in readers {
+        assert_eq!(reader.join().unwrap(), "Pooled", "Readers see the last committed state");
+    }
+
+    writer.execute_batch("COMMIT;").unwrap();
+    let name: String = pool
+        .get()
+        .unwrap()
+        .query_row("SELECT name FROM boards WHERE id = 'b1'", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(name, "Pending");
+
+    drop(writer);
+    drop(pool);
+    let _ = std::fs::remove_file(&db_path);
+}
+
+#[test]
+fn test_blocking_db_work_does_not_stall_runtime() {
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    // A single-threaded runtime: if blocking work held its thread, the ticker task could never run
+    let rt = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+
+    let (tx, rx) = mpsc::channel();
+    let got_tick = rt.block_on(async move {
+        let worker =
+            tokio::spawn(async move { kanban::db::blocking(move || rx.recv_timeout(Duration::from_secs(2)).is_ok()).await });
+        tokio::task::yield_now().await;
+        let ticker = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            let _ = tx.send(());
+        });
+        ticker.await.unwrap();
+        worker.await.unwrap()
+    });
+    assert!(got_tick, "Other tasks should keep running during blocking DB work");
+}
+
+#[test]
+fn test_search_index_rebuilt_when_stale() {
+    let db_path = format!("/tmp/kanban_test_{}.db", uuid::Uuid::new_v4());
+    let pool = kanban::db::init_db_with_path(&db_path).expect("DB should initialize");
+    let conn = pool.get().unwrap();
+    conn.execute_batch(
+        "INSERT INTO boards (id, name, manage_key_hash) VALUES ('b1', 'FTS', 'hash');
+         INSERT INTO columns (id, board_id, name, position) VALUES ('c1', 'b1', 'Todo', 0);
+         INSERT INTO tasks (id, board_id, column_id, title) VALUES ('t1', 'b1', 'c1', 'Kerberos rollout');
+         DELETE FROM tasks_fts;",
+    )
+    .unwrap();
+    drop(conn);
+    drop(pool);
+
+    // Reopening detects the missing rows and repopulates the index
+    let pool = kanban::db::init_db_with_path(&db_path).expect("DB should reopen");
+    let conn = pool.get().unwrap();
+    let hit: String = conn
+        .query_row("SELECT task_id FROM tasks_fts WHERE tasks_fts MATCH 'kerberos'", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(hit, "t1");
+
+    drop(conn);
+    drop(pool);
+    let _ = std::fs::remove_file(&db_path);
+}
+
+#[test]
+fn test_pool_writers_wait_for_lock() {
+    use std::sync::atomic::Ordering;
+
+    let db_path = format!("/tmp/kanban_test_{}.db", uuid::Uuid::new_v4());
+    let pool = kanban::db::init_db_with_path(&db_path).expect("DB should initialize");
+    let stats = &kanban::db::DB_STATS;
+    let waits_before = stats.busy_waits.load(Ordering::Relaxed);
+
+    let writer = pool.get().unwrap();
+    writer.execute_batch("BEGIN IMMEDIATE;").unwrap();
+
+    // A second writer queues behind the busy handler instead of failing
+    let pool2 = pool.clone();
+    let waiter = std::thread::spawn(move || {
+        pool2
+            .get()
+            .unwrap()
+            .execute("INSERT INTO boards (id, name, manage_key_hash) VALUES ('b2', 'Queued', 'hash')", [])
+    });
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    writer.execute_batch("COMMIT;").unwrap();
+
+    assert_eq!(waiter.join().unwrap().unwrap(), 1);
+    assert!(stats.busy_waits.load(Ordering::Relaxed) > waits_before);
+    assert!(stats.busy_wait_max_us.load(Ordering::Relaxed) >= 50_000);
+
+    drop(writer);
+    drop(pool);
+    let _ = std::fs::remove_file(&db_path);
+}
+
+#[test]
+fn test_database_key_encryption() {
+    let db_path = format!("/tmp/kanban_test_{}.db", uuid::Uuid::new_v4());
+    let probe = rusqlite::Connection::open_in_memory().unwrap();
+
+    if !kanban::db::cipher_available(&probe) {
+        // Plain SQLite would ignore the key and write plaintext — refuse instead
+        let err = kanban::db::init_db_with_key(&db_path, Some("secret")).unwrap_err();
+        assert!(err.contains("SQLCipher"), "{}", err);
+        let err = kanban::db::rekey(&db_path, None, Some("secret")).unwrap_err();
+        assert!(err.contains("SQLCipher"), "{}", err);
+        return;
+    }
+
+    let pool = kanban::db::init_db_with_key(&db_path, Some("secret")).expect("encrypted DB should initialize");
+    pool.get()
+        .unwrap()
+        .execute("INSERT INTO boards (id, name, manage_key_hash) VALUES ('b1', 'Secret Board', 'h')", [])
+        .unwrap();
+    drop(pool);
+
+    let raw = std::fs::read(&db_path).unwrap();
+    assert!(!raw.starts_with(b"SQLite format 3"), "file should not be plaintext");
+    assert!(kanban::db::init_db_with_path(&db_path).is_err());
+    assert!(kanban::db::init_db_with_key(&db_path, Some("wrong")).is_err());
+
+    kanban::db::rekey(&db_path, Some("secret"), Some("rotated")).expect("rekey should succeed");
+    assert!(!std::path::Path::new(&format!("{}.pre-rekey", db_path)).exists(), "old file is deleted");
+    assert!(kanban::db::init_db_with_key(&db_path, Some("secret")).is_err());
+    let pool = kanban::db::init_db_with_key(&db_path, Some("rotated")).expect("new key should open");
+    let name: String = pool
+        .get()
+        .unwrap()
+        .query_row("SELECT name FROM boards WHERE id = 'b1'", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(name, "Secret Board");
+}
+
+#[test]
+fn test_trash_purge_respects_retention() {
+    let db_path = format!("/tmp/kanban_test_trash_{}.db", uuid::Uuid::new_v4());
+    let pool = kanban::db::init_db_with_path(&db_path).expect("DB should initialize");
+    let conn = pool.get().unwrap();
+
+    conn.execute_batch(
+        "INSERT INTO boards (id, name, manage_key_hash) VALUES ('b1', 'Trash', 'h');
+         INSERT INTO task_trash (task_id, board_id, title, column_id, snapshot, deleted_by, deleted_at)
+         VALUES ('old', 'b1', 'Old', 'c1', '{}', 'x', datetime('now', '-40 days')),
+                ('new', 'b1', 'New', 'c1', '{}', 'x', datetime('now', '-2 days'));",
+    )
+    .unwrap();
+
+    let forever = kanban::trash::TrashConfig { retention: None };
+    assert_eq!(kanban::trash::purge_expired(&conn, &forever).unwrap(), 0);
+
+    let month = kanban::trash::TrashConfig::default();
+    assert_eq!(kanban::trash::purge_expired(&conn, &month).unwrap(), 1);
+    let left: String = conn.query_row("SELECT task_id FROM task_trash", [], |row| row.get(0)).unwrap();
+    assert_eq!(left, "new");
+
+    drop(conn);
+    drop(pool);
+    let _ = std::fs::remove_file(&db_path);
+}
+
+#[test]
+fn test_webhook_dispatcher_backs_off_rows_it_cannot_sign() {
+    use std::time::Duration;
+
+    let db_path = format!("/tmp/kanban_test_outbox_keys_{}.db", uuid::Uuid::new_v4());
+    let pool = kanban::db::init_db_with_path(&db_path).expect("DB should initialize");
+    let conn = pool.get().unwrap();
+    let board_id = uuid::Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO boards (id, name, description, manage_key_hash) VALUES (?1, 'Keyless Board', '', ?2)",
+        rusqlite::params![board_id, kanban::db::hash_key("test_key")],
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO webhooks (id, board_id, url, secret, events) VALUES ('wh1', ?1, 'http://127.0.0.1:9/hook', 'whsec_test', '[]')",
+        rusqlite::params![board_id],
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO webhook_outbox (id, webhook_id, board_id, event, payload, next_attempt_at)
+         VALUES ('ob1', 'wh1', ?1, 'task.created', '{}', 0)",
+        rusqlite::params![board_id],
+    )
+    .unwrap();
+    // The webhook's signing keys can't be read
+    conn.execute_batch("ALTER TABLE webhook_signing_keys RENAME TO webhook_signing_keys_gone").unwrap();
+
+    let rt = tokio::runtime::Builder::new_multi_thread().worker_threads(2).enable_all().build().unwrap();
+    let bus = kanban::events::EventBus::with_webhooks(kanban::db::init_webhook_db_with_path(&db_path).unwrap())
+        .with_retry_policy(kanban::webhooks::RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_secs(60),
+        });
+    rt.block_on(async { bus.start_webhook_dispatcher() });
+
+    // The row is counted as a failed attempt and waits out its backoff
+    // rather than staying due
+    let settled = || -> Option<(i64, i64, String)> {
+        conn.query_row(
+            "SELECT attempts, next_attempt_at, last_error FROM webhook_outbox WHERE id = 'ob1' AND attempts > 0",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .ok()
+    };
+    let mut row = None;
+    for _ in 0..100 {
+        row = settled();
+        if row.is_some() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    let (attempts, next_attempt_at, last_error) = row.expect("unsignable row should be settled as a failure");
+    assert_eq!(attempts, 1);
+    assert!(next_attempt_at > chrono::Utc::now().timestamp_millis() + 30_000);
+    assert!(last_error.contains("Signing keys unavailable"), "{}", last_error);
+
+    drop(rt);
+    drop(conn);
+    drop(pool);
+    let _ = std::fs::remove_file(&db_path);
+}
+
+#[test]
+fn test_webhook_outbox_survives_restart() {
+    use std::io::{Read, Write};
+    use std::time::Duration;
+
+    let db_path = format!("/tmp/kanban_test_outbox_{}.db", uuid::Uuid::new_v4());
+    let pool = kanban::db::init_db_with_path(&db_path).expect("DB should initialize");
+    let conn = pool.get().unwrap();
+
+    // A receiver that records each request and answers 200
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let mut buf = [0u8; 8192];
+            let n = stream.read(&mut buf).unwrap_or(0);
+            let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+            let _ = tx.send(String::from_utf8_lossy(&buf[..n]).to_string());
+        }
+    });
+
+    let board_id = uuid::Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO boards (id, name, description, manage_key_hash) VALUES (?1, 'Outbox Board', '', ?2)",
+        rusqlite::params![board_id, kanban::db::hash_key("test_key")],
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO webhooks (id, board_id, url, secret, events) VALUES ('wh1', ?1, ?2, 'whsec_test', '[]')",
+        rusqlite::params![board_id, url],
+    )
+    .unwrap();
+    let event = kanban::events::BoardEvent {
+        event: "task.created".to_string(),
+        board_id: board_id.clone(),
+        data: serde_json::json!({"task_id": "t1", "title": "Queued before the crash"}),
+    };
+    let outbox = || -> i64 { conn.query_row("SELECT COUNT(*) FROM webhook_outbox", [], |row| row.get(0)).unwrap() };
+
+    // First process: the event is queued, but the process stops before sending it
+    let first = kanban::events::EventBus::with_webhooks(kanban::db::init_webhook_db_with_path(&db_path).unwrap());
+    // An event emitted in a transaction that rolls back goes nowhere
+    let rolled_back = conn.unchecked_transaction().unwrap();
+    first.emit(&rolled_back, event.clone()).unwrap();
+    drop(rolled_back);
+    assert_eq!(outbox(), 0);
+    let tx = conn.unchecked_transaction().unwrap();
+    first.emit(&tx, event).unwrap();
+    assert!(first.poll(&board_id, 0).0.events.is_empty());
+    kanban::events::commit(tx).unwrap();
+    let (backlog, _) = first.poll(&board_id, 0);
+    assert_eq!(backlog.events.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![1]);
+    drop(first);
+    assert_eq!(outbox(), 1);
+    assert!(rx.recv_timeout(Duration::from_millis(100)).is_err());
+
+    // Next process: the dispatcher sends what was left
+    let rt = tokio::runtime::Builder::new_multi_thread().worker_threads(2).enable_all().build().unwrap();
+    let second = kanban::events::EventBus::with_webhooks(kanban::db::init_webhook_db_with_path(&db_path).unwrap())
+        .with_url_policy(kanban::url_policy::UrlPolicy { allow_private: true });
+    rt.block_on(async { second.start_webhook_dispatcher() });
+    let request = rx.recv_timeout(Duration::from_secs(5)).expect("queued webhook should be sent after restart");
+    assert!(request.contains("Queued before the crash"));
+    assert!(request.to_lowercase().contains("x-kanban-signature: sha256="));
+
+    let mut delivered = false;
+    for _ in 0..100 {
+        delivered = outbox() == 0;
+        if delivered {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    assert!(delivered, "Delivered rows leave the outbox");
+    let status: i64 = conn
+        .query_row("SELECT status_code FROM webhook_deliveries WHERE webhook_id = 'wh1'", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(status, 200);
+
+    drop(rt);
+    drop(conn);
+    drop(pool);
+    let _ = std::fs::remove_file(&db_path);
+}
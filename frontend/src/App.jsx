@@ -1316,6 +1316,7 @@ function TaskDetailModal({ boardId, task, canEdit, onClose, onRefresh, isMobile,
       case 'archived': return '📦 Archived';
       case 'unarchived': return '📤 Unarchived';
       case 'deleted': return '🗑️ Deleted';
+      case 'restored': return '♻️ Restored';
       default: return evt.event_type;
     }
   };
@@ -1345,6 +1346,7 @@ function TaskDetailModal({ boardId, task, canEdit, onClose, onRefresh, isMobile,
                   </span>
                   {task.assigned_to && <span>→ {task.assigned_to}</span>}
                   {task.claimed_by && <span>🔒 {task.claimed_by}</span>}
+                  {task.watchers?.length > 0 && <span title="Watching">👀 {task.watchers.join(', ')}</span>}
                   {task.column_name && <span>in {task.column_name}</span>}
                 </div>
               )}
@@ -1578,11 +1580,24 @@ function TaskDetailModal({ boardId, task, canEdit, onClose, onRefresh, isMobile,
                 <div key={evt.id} style={{ marginBottom: '10px', padding: '8px 10px', background: '#0f172a', borderRadius: '6px', border: '1px solid #334155' }}>
                   <div style={{ display: 'flex', justifyContent: 'space-between', marginBottom: '4px' }}>
                     <span style={{ fontSize: '0.78rem', fontWeight: 600, color: '#a5b4fc' }}>{evt.actor || 'anonymous'}</span>
-                    <span style={{ fontSize: '0.7rem', color: '#475569' }}>{formatTime(evt.created_at)}</span>
+                    <span style={{ fontSize: '0.7rem', color: '#475569' }}>
+                      {formatTime(evt.created_at)}{evt.data?.edited_at && ' (edited)'}
+                    </span>
                   </div>
                   <div style={{ fontSize: '0.83rem', color: '#cbd5e1', whiteSpace: 'pre-wrap' }}>
                     {renderWithMentions(evt.data?.message || '')}
                   </div>
+                  {evt.data?.attachments?.length > 0 && (
+                    <div style={{ display: 'flex', flexWrap: 'wrap', gap: '6px', marginTop: '6px' }}>
+                      {evt.data.attachments.map(a => (
+                        <a key={a.id} href={a.url} target="_blank" rel="noreferrer" style={{ fontSize: '0.75rem', color: '#93c5fd' }}>
+                          {a.content_type?.startsWith('image/')
+                            ? <img src={a.url} alt={a.filename} style={{ maxWidth: '160px', maxHeight: '120px', borderRadius: '4px', border: '1px solid #334155' }} />
+                            : `📎 ${a.filename}`}
+                        </a>
+                      ))}
+                    </div>
+                  )}
                 </div>
               ))}
               <div ref={commentsEndRef} />
@@ -1774,6 +1789,8 @@ function CreateBoardModal({ onClose, onCreated, isMobile }) {
 const WEBHOOK_EVENTS = [
   'task.created', 'task.updated', 'task.deleted',
   'task.moved', 'task.claimed', 'task.released', 'task.comment',
+  'task.comment.edited', 'task.comment.deleted', 'task.batch_moved', 'task.batch_updated', 'task.assigned',
+  'task.comment_pressure', 'task.comments_collapsed', 'board.merged',
 ];
 
 function BoardSettingsModal({ board, canEdit, onClose, onRefresh, onBoardListRefresh, isMobile }) {
@@ -3537,6 +3554,27 @@ function App() {
 
   const refreshMyBoards = useCallback(() => setMyBoards(api.getMyBoards()), []);
 
+  // Optional web sign-in: boards owned by the signed-in user are editable without a key
+  const [authConfig, setAuthConfig] = useState(null);
+  const [me, setMe] = useState(null);
+
+  useEffect(() => {
+    api.getAuthConfig().then(({ data }) => {
+      setAuthConfig(data);
+      if (!data?.enabled) return;
+      api.getMe().then(({ data: user }) => {
+        setMe(user);
+        user.boards.forEach(b => api.addMyBoard(b.id, b.name));
+        refreshMyBoards();
+      }).catch(() => setMe(null));
+    }).catch(() => setAuthConfig(null));
+  }, [refreshMyBoards]);
+
+  const handleSignOut = async () => {
+    await api.logout().catch(() => {});
+    setMe(null);
+  };
+
   const [pendingTaskId, setPendingTaskId] = useState(null);
 
   useEffect(() => {
@@ -3575,7 +3613,8 @@ function App() {
 
   // keyVersion bumps when a manage key is added/removed, forcing canEdit re-derive
   const [keyVersion, setKeyVersion] = useState(0);
-  const canEdit = selectedBoardId ? api.hasBoardKey(selectedBoardId) : false;
+  const ownsBoard = !!me?.boards.some(b => b.id === selectedBoardId);
+  const canEdit = selectedBoardId ? api.hasBoardKey(selectedBoardId) || ownsBoard : false;
   // eslint-disable-next-line no-unused-vars
   void keyVersion; // Referenced to ensure React includes it in dependency tracking
 
@@ -3586,6 +3625,7 @@ function App() {
 
   const handleBoardCreated = (newBoardId) => {
     if (newBoardId) setSelectedBoardId(newBoardId);
+    if (me) api.getMe().then(({ data }) => setMe(data)).catch(() => {});
   };
 
   const handleOpenDirect = (boardId) => {
@@ -3667,6 +3707,13 @@ function App() {
           {selectedBoardId && (
             <AccessIndicator boardId={selectedBoardId} canEdit={canEdit} isMobile={isMobile} onKeyUpgraded={handleKeyUpgraded} />
           )}
+          {authConfig?.enabled && (me ? (
+            <button style={styles.btnSmall} onClick={handleSignOut} title={me.email || me.name}>
+              {isMobile ? 'Sign out' : `Sign out ${me.name}`}
+            </button>
+          ) : (
+            <a style={{ ...styles.btnSmall, textDecoration: 'none' }} href={api.loginUrl(authConfig.login_url)}>Sign in</a>
+          ))}
         </div>
       </div>
 
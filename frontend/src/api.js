@@ -256,7 +256,10 @@ function subscribeToBoardEvents(boardId, onEvent, onStatus) {
     const eventTypes = [
       'task.created', 'task.updated', 'task.deleted',
       'task.moved', 'task.claimed', 'task.released',
-      'task.reordered', 'task.comment', 'warning',
+      'task.reordered', 'task.comment', 'task.comment.edited',
+      'task.comment.deleted', 'task.batch_moved',
+      'task.batch_updated', 'task.assigned', 'task.comments_collapsed',
+      'board.merged', 'warning',
     ];
 
     eventTypes.forEach(type => {
@@ -358,6 +361,18 @@ const validateKey = async (boardId, key) => {
   }
 };
 
+// ---- Sign-in (optional, when the server has OIDC configured) ----
+
+const getAuthConfig = () => request('/auth/config');
+
+// The signed-in user and the boards they own; 401 when not signed in
+const getMe = () => request('/auth/me');
+
+const logout = () => request('/auth/logout', { method: 'POST' });
+
+const loginUrl = (loginPath) =>
+  `${loginPath}?return_to=${encodeURIComponent(window.location.pathname + window.location.search)}`;
+
 // ---- Health ----
 
 const health = () => request('/health');
@@ -376,5 +391,6 @@ export {
   subscribeToBoardEvents,
   listWebhooks, createWebhook, updateWebhook, deleteWebhook,
   validateKey,
+  getAuthConfig, getMe, logout, loginUrl,
   health,
 };